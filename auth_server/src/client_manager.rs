@@ -12,16 +12,17 @@
 //! 2. Client sends `CMD_AUTH_LOGON_PROOF` -> the server verifies the SRP proof, stores the
 //!    session key, marks the client authenticated and allows realm list requests.
 //! 3. Optional: client reconnects using `CMD_AUTH_RECONNECT_CHALLENGE/PROOF`, which is
-//!    validated against the stored `SrpServer` state.
+//!    validated against the account's `Session`. Sessions are kept by account, so a reconnect
+//!    works even after the connection that logged in is gone.
 //! 4. Authenticated client requests `CMD_REALM_LIST` and receives the available realms.
 //!
-//! Cleanup: connections and authenticated addresses are pruned periodically based on the
+//! Cleanup: connections and sessions are pruned periodically based on the
 //! `AUTH_RECONNECT_LIFETIME` environment variable (seconds).
 
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Instant;
-use std::{collections::HashMap, env, time::Duration};
+use std::{collections::HashMap, time::Duration};
 
 use anyhow::{anyhow, Result};
 use flume::Receiver;
@@ -35,10 +36,11 @@ use wow_login_messages::version_8::*;
 use wow_srp::normalized_string::NormalizedString;
 use wow_srp::server::{SrpServer, SrpVerifier};
 use wow_srp::{PublicKey, GENERATOR, LARGE_SAFE_PRIME_LITTLE_ENDIAN, PASSWORD_VERIFIER_LENGTH, SALT_LENGTH};
-use wrath_auth_db::AuthDatabase;
+use wrath_auth_db::{AuthDatabase, DBAccount};
+use wrath_common::{config, session_key, FeatureFlags};
 
 use crate::realms::get_realm_list;
-use crate::state::ClientState;
+use crate::state::{check_step, check_transition, AuthStep, ClientState};
 
 /// Internal events consumed by the `ClientManager` loop.
 #[allow(clippy::large_enum_variant)]
@@ -90,11 +92,8 @@ impl Connection {
 pub struct Client {
     connection: Connection,
 
-    /// Reflects the progress in the login/reconnect flows.
-    state: Option<ClientState>,
-
-    /// Populated once SRP6 verification succeeds and is used for reconnects.
-    authentication: Option<Authentication>,
+    /// Reflects the progress in the login/reconnect flows, only changed through `transition`.
+    state: ClientState,
 }
 
 impl Client {
@@ -102,34 +101,51 @@ impl Client {
     pub fn new(connection: Connection) -> Self {
         Self {
             connection,
-            state: Some(ClientState::Connected),
-            authentication: None,
+            state: ClientState::Connected,
         }
     }
 
-    /// Mark the client as authenticated and store SRP context for future reconnects.
-    pub fn authenticate(&mut self, srp_server: SrpServer, username: String) {
-        self.authentication = Some(Authentication { srp_server, username });
-        self.state = Some(ClientState::LogOnProof);
+    /// Move the client to the state handling `step` led to, failing if the protocol doesn't allow it.
+    pub fn transition(&mut self, step: AuthStep, next: ClientState) -> Result<()> {
+        check_transition(self.state.kind(), step, next.kind())?;
+        self.state = next;
+        Ok(())
+    }
+
+    /// Take the data of the current state, leaving the client `Connected` until the handler transitions it.
+    fn take_state(&mut self) -> ClientState {
+        std::mem::replace(&mut self.state, ClientState::Connected)
     }
 }
 
-/// Holds SRP6 context and associated username for an authenticated session.
-pub struct Authentication {
+/// An account's last successful authentication, kept after the connection closes so the client can reconnect.
+pub struct Session {
+    /// Holds the session key and verifies reconnect proofs.
     srp_server: SrpServer,
-    username: String,
+
+    /// The connection currently using this session, if it is still open.
+    address: SocketAddr,
+
+    expires_at: Instant,
+}
+
+impl Session {
+    fn is_expired(&self) -> bool {
+        self.expires_at <= Instant::now()
+    }
 }
 
 /// Manages connected clients and drives authentication and realm list flows.
 pub struct ClientManager {
-    /// Clients in the process of connecting
+    /// Open connections, authenticated or not
     connected_clients: HashMap<SocketAddr, Client>,
 
-    /// Addresses that are fully connected and authenticated
-    authenticated_addresses: HashMap<String, SocketAddr>,
+    /// Sessions by account username
+    sessions: HashMap<String, Session>,
 
     auth_reconnect_lifetime: Duration,
     auth_database: Arc<AuthDatabase>,
+    features: FeatureFlags,
 }
 
 impl ClientManager {
@@ -138,9 +154,10 @@ impl ClientManager {
         let auth_reconnect_lifetime = get_auth_reconnect_lifetime();
         Self {
             connected_clients: HashMap::new(),
-            authenticated_addresses: HashMap::new(),
+            sessions: HashMap::new(),
             auth_reconnect_lifetime,
             auth_database,
+            features: FeatureFlags::from_env(),
         }
     }
 
@@ -156,9 +173,9 @@ impl ClientManager {
                     ClientEvent::Message { addr, packet } => {
                         if let Err(e) = self.handle_message(&addr, packet).await {
                             error!("Error handling message from {addr}: {e}");
-                            let server_event = ServerEvent::Disconnect;
-                            let client = self.connected_clients.remove(&addr).unwrap();
-                            client.connection.sender.send_async(server_event).await.unwrap();
+                            if let Some(client) = self.connected_clients.remove(&addr) {
+                                let _ = client.connection.sender.send_async(ServerEvent::Disconnect).await;
+                            }
                         }
                     }
                 },
@@ -172,22 +189,19 @@ impl ClientManager {
         }
     }
 
-    /// Prune stale connections and authenticated addresses beyond the reconnect lifetime.
+    /// Prune stale connections and expired sessions beyond the reconnect lifetime.
     async fn reconnect_clients_cleaner(&mut self) {
-        self.authenticated_addresses.retain(|_, addr| {
-            if let Some(client) = self.connected_clients.get(addr) {
-                client.connection.created_at.elapsed() < self.auth_reconnect_lifetime
-            } else {
-                // No connected client associated to this authentication, just remove it
-                false
-            }
-        });
+        self.sessions.retain(|_, session| !session.is_expired());
         self.connected_clients
             .retain(|_, client| client.connection.created_at.elapsed() < self.auth_reconnect_lifetime);
     }
 
     /// Dispatch a client opcode to the appropriate handler based on the login protocol.
+    /// Packets the client isn't allowed to send in its current state are rejected here, before any handler runs.
     async fn handle_message(&mut self, addr: &SocketAddr, packet: ClientOpcodeMessage) -> Result<()> {
+        let client = self.connected_clients.get(addr).ok_or_else(|| anyhow!("Message from unknown client"))?;
+        check_step(client.state.kind(), auth_step(&packet))?;
+
         match packet {
             ClientOpcodeMessage::CMD_AUTH_LOGON_CHALLENGE(challenge) => {
                 self.handle_auth_logon_challenge(addr, challenge).await?;
@@ -217,26 +231,41 @@ impl ClientManager {
 
         let account = match self.auth_database.get_account_by_username(&challenge.account_name).await? {
             Some(acc) if acc.banned != 0 => {
-                client.state.replace(ClientState::Connected);
+                client.transition(AuthStep::LogonChallenge, ClientState::Connected)?;
                 self.reject_logon_challenge(addr, CMD_AUTH_LOGON_CHALLENGE_Server_LoginResult::FailBanned)
                     .await?;
                 return Ok(());
             }
             Some(acc) if acc.v.is_empty() || acc.s.is_empty() => {
-                client.state.replace(ClientState::Connected);
+                client.transition(AuthStep::LogonChallenge, ClientState::Connected)?;
                 self.reject_logon_challenge(addr, CMD_AUTH_LOGON_CHALLENGE_Server_LoginResult::FailUnknownAccount)
                     .await?;
                 return Ok(());
             }
             Some(acc) => acc,
+            None if self.features.auto_create_accounts => {
+                let Some(account) = auto_create_account(&self.auth_database, &challenge.account_name).await? else {
+                    client.transition(AuthStep::LogonChallenge, ClientState::Connected)?;
+                    self.reject_logon_challenge(addr, CMD_AUTH_LOGON_CHALLENGE_Server_LoginResult::FailUnknownAccount)
+                        .await?;
+                    return Ok(());
+                };
+                account
+            }
             None => {
-                client.state.replace(ClientState::Connected);
+                client.transition(AuthStep::LogonChallenge, ClientState::Connected)?;
                 self.reject_logon_challenge(addr, CMD_AUTH_LOGON_CHALLENGE_Server_LoginResult::FailUnknownAccount)
                     .await?;
                 return Ok(());
             }
         };
 
+        //The world server serves translated texts based on this
+        let locale = locale_code(challenge.locale);
+        if locale != account.locale {
+            self.auth_database.set_account_locale(&account.username, &locale).await?;
+        }
+
         let username = NormalizedString::from(&account.username)?;
         let mut password_verifier: [u8; PASSWORD_VERIFIER_LENGTH as usize] = Default::default();
         let mut salt: [u8; SALT_LENGTH as usize] = Default::default();
@@ -266,17 +295,20 @@ impl ClientManager {
             .sender
             .send_async(ServerEvent::AuthLogonChallenge(auth_logon_challenge))
             .await?;
-        client.state.replace(ClientState::ChallengeProof {
-            srp_proof,
-            username: account.username,
-        });
+        client.transition(
+            AuthStep::LogonChallenge,
+            ClientState::ChallengeProof {
+                srp_proof,
+                username: account.username,
+            },
+        )?;
 
         Ok(())
     }
 
     /// Handle `CMD_AUTH_LOGON_PROOF`:
     /// - Validates state and parses client public key.
-    /// - Verifies the SRP proof; on success, stores session key in the auth DB.
+    /// - Verifies the SRP proof; on success, stores session key and client address in the auth DB.
     /// - Marks the client authenticated and allows subsequent realm list requests.
     async fn handle_auth_logon_proof(&mut self, addr: &SocketAddr, logon_proof: CMD_AUTH_LOGON_PROOF_Client) -> Result<()> {
         let client_public_key = match PublicKey::from_le_bytes(logon_proof.client_public_key) {
@@ -290,10 +322,7 @@ impl ClientManager {
 
         let client = self.connected_clients.get_mut(addr).unwrap();
 
-        // Verify its state
-        let Some(ClientState::ChallengeProof { srp_proof, username }) = client.state.take() else {
-            self.reject_logon_proof(addr, CMD_AUTH_LOGON_PROOF_Server_LoginResult::FailUnknownAccount)
-                .await?;
+        let ClientState::ChallengeProof { srp_proof, username } = client.take_state() else {
             return Err(anyhow!("Client is not in ChallengeProof state."));
         };
 
@@ -307,7 +336,7 @@ impl ClientManager {
         };
 
         self.auth_database
-            .set_account_sessionkey(&username, &hex::encode(srp_server.session_key()))
+            .set_account_sessionkey(&username, &session_key::encode(srp_server.session_key()), &addr.ip().to_string())
             .await?;
 
         let auth_logon_proof = CMD_AUTH_LOGON_PROOF_Server {
@@ -320,18 +349,30 @@ impl ClientManager {
         };
 
         client.connection.sender.send_async(ServerEvent::AuthLogonProof(auth_logon_proof)).await?;
-        client.authenticate(srp_server, username.clone());
+        client.transition(AuthStep::LogonProof, ClientState::Authenticated { username: username.clone() })?;
 
-        if let Some(other_address) = self.authenticated_addresses.insert(username, *addr) {
-            // Disconnect the other client that was connected with this account
-            if let Some(other_client) = self.connected_clients.remove(&other_address) {
-                other_client.connection.sender.send_async(ServerEvent::Disconnect).await?;
-            }
+        let session = Session {
+            srp_server,
+            address: *addr,
+            expires_at: Instant::now() + self.auth_reconnect_lifetime,
+        };
+        if let Some(previous_session) = self.sessions.insert(username, session) {
+            self.disconnect_previous_connection(previous_session.address, addr).await;
         }
 
         Ok(())
     }
 
+    /// Disconnect the other client that was connected with the same account, if it's still around.
+    async fn disconnect_previous_connection(&mut self, previous_address: SocketAddr, addr: &SocketAddr) {
+        if previous_address == *addr {
+            return;
+        }
+        if let Some(other_client) = self.connected_clients.remove(&previous_address) {
+            let _ = other_client.connection.sender.send_async(ServerEvent::Disconnect).await;
+        }
+    }
+
     /// Send a failed logon proof result to the client.
     async fn reject_logon_proof(&mut self, addr: &SocketAddr, result: CMD_AUTH_LOGON_PROOF_Server_LoginResult) -> Result<()> {
         let client = self.connected_clients.get(addr).unwrap();
@@ -353,35 +394,28 @@ impl ClientManager {
     }
 
     /// Handle `CMD_AUTH_RECONNECT_CHALLENGE`:
-    /// - Looks up an existing authenticated session for the username.
-    /// - If found, sends to the client the reconnect challenge data bound to the stored `SrpServer`.
+    /// - Looks up the session of the account, which outlives the connection that created it.
+    /// - If found, sends to the client the reconnect challenge data bound to the session's `SrpServer`.
     async fn handle_reconnect_challenge(&mut self, addr: &SocketAddr, challenge: CMD_AUTH_RECONNECT_CHALLENGE_Client) -> Result<()> {
-        // When this command is received, there should be a corresponding client that is authenticated.
-        let authenticated_address = self.authenticated_addresses.get(&challenge.account_name);
-
-        if authenticated_address.is_none() {
-            error!("Failed to find authenticated address for account {}", challenge.account_name);
+        let challenge_data = self
+            .sessions
+            .get(&challenge.account_name)
+            .filter(|session| !session.is_expired())
+            .map(|session| *session.srp_server.reconnect_challenge_data());
 
+        let reconnecting_client = self.connected_clients.get_mut(addr).unwrap();
+        let Some(challenge_data) = challenge_data else {
+            info!("No session to reconnect to for account {}", challenge.account_name);
             let auth_reconnect_challenge = CMD_AUTH_RECONNECT_CHALLENGE_Server {
                 result: CMD_AUTH_RECONNECT_CHALLENGE_Server_LoginResult::FailUnknown0,
             };
-
-            let reconnecting_client = self.connected_clients.get(addr).unwrap();
             reconnecting_client
                 .connection
                 .sender
                 .send_async(ServerEvent::AuthReconnectChallenge(auth_reconnect_challenge))
                 .await?;
-        }
-
-        let authenticated_address = authenticated_address.unwrap();
-        let authenticated_client = self.connected_clients.get(authenticated_address).unwrap();
-        let challenge_data = *authenticated_client
-            .authentication
-            .as_ref()
-            .unwrap()
-            .srp_server
-            .reconnect_challenge_data();
+            return reconnecting_client.transition(AuthStep::ReconnectChallenge, ClientState::Connected);
+        };
 
         let auth_reconnect_challenge = CMD_AUTH_RECONNECT_CHALLENGE_Server {
             result: CMD_AUTH_RECONNECT_CHALLENGE_Server_LoginResult::Success {
@@ -391,86 +425,73 @@ impl ClientManager {
                 ],
             },
         };
-        let server_event = ServerEvent::AuthReconnectChallenge(auth_reconnect_challenge);
-
-        // Response should go to the reconnecting client, not to the authenticated one which might be stale
-        let reconnecting_client = self.connected_clients.get_mut(addr).unwrap();
-        reconnecting_client.connection.sender.send_async(server_event).await?;
-        reconnecting_client.state.replace(ClientState::ReconnectProof {
-            username: challenge.account_name.to_string(),
-        });
-        Ok(())
+        reconnecting_client
+            .connection
+            .sender
+            .send_async(ServerEvent::AuthReconnectChallenge(auth_reconnect_challenge))
+            .await?;
+        reconnecting_client.transition(
+            AuthStep::ReconnectChallenge,
+            ClientState::ReconnectProof {
+                username: challenge.account_name.to_string(),
+            },
+        )
     }
 
     /// Handle `CMD_AUTH_RECONNECT_PROOF`:
-    /// - Verifies the reconnect proof against the stored `SrpServer` of the authenticated client.
-    /// - On success, transfers authentication to the reconnecting connection and refreshes state.
+    /// - Verifies the reconnect proof against the account's session.
+    /// - On success, moves the session to the reconnecting connection and renews it.
     pub async fn handle_reconnect_proof(&mut self, addr: &SocketAddr, reconnect_proof: CMD_AUTH_RECONNECT_PROOF_Client) -> Result<()> {
-        // Verify state
-        let username = {
-            let reconnecting_client = self.connected_clients.get(addr).unwrap();
-            let Some(ClientState::ReconnectProof { username }) = &reconnecting_client.state else {
-                self.reject_logon_proof(addr, CMD_AUTH_LOGON_PROOF_Server_LoginResult::FailUnknownAccount)
-                    .await?;
-                return Err(anyhow!("Client is not in ReconnectProof state."));
-            };
-            username.clone()
+        let reconnecting_client = self.connected_clients.get_mut(addr).unwrap();
+        let ClientState::ReconnectProof { username } = reconnecting_client.take_state() else {
+            return Err(anyhow!("Client is not in ReconnectProof state."));
         };
 
-        // Verify against the authenticated client
-        let result = {
-            let authenticated_address = self.authenticated_addresses.get_mut(&username).unwrap();
-            let authenticated_client = self.connected_clients.get_mut(authenticated_address).unwrap();
-            authenticated_client
-                .authentication
-                .as_mut()
-                .unwrap()
-                .srp_server
-                .verify_reconnection_attempt(reconnect_proof.proof_data, reconnect_proof.client_proof)
+        //The session can expire between the challenge and the proof
+        let previous_address = match self.sessions.get_mut(&username).filter(|session| !session.is_expired()) {
+            Some(session)
+                if session
+                    .srp_server
+                    .verify_reconnection_attempt(reconnect_proof.proof_data, reconnect_proof.client_proof) =>
+            {
+                let previous_address = session.address;
+                session.address = *addr;
+                session.expires_at = Instant::now() + self.auth_reconnect_lifetime;
+                Some(previous_address)
+            }
+            _ => None,
         };
 
         let auth_reconnect_proof = CMD_AUTH_RECONNECT_PROOF_Server {
-            result: if result {
+            result: if previous_address.is_some() {
                 LoginResult::Success
             } else {
                 LoginResult::FailIncorrectPassword
             },
         };
+        reconnecting_client
+            .connection
+            .sender
+            .send_async(ServerEvent::AuthReconnectProof(auth_reconnect_proof))
+            .await?;
 
-        {
-            let reconnecting_client = self.connected_clients.get_mut(addr).unwrap();
-            reconnecting_client
-                .connection
-                .sender
-                .send_async(ServerEvent::AuthReconnectProof(auth_reconnect_proof))
-                .await?;
-        }
-
-        if result {
-            // Update reconnecting client
-            let authenticated_address = self.authenticated_addresses.get_mut(&username).unwrap();
-            let stale_client = self.connected_clients.remove(authenticated_address).unwrap();
-            let reconnecting_client = self.connected_clients.get_mut(addr).unwrap();
-            reconnecting_client.authentication = stale_client.authentication;
-            reconnecting_client.state.replace(ClientState::LogOnProof);
-        } else {
-            let reconnecting_client = self.connected_clients.get_mut(addr).unwrap();
-            reconnecting_client.state.replace(ClientState::Connected);
-        }
+        let Some(previous_address) = previous_address else {
+            return reconnecting_client.transition(AuthStep::ReconnectProof, ClientState::Connected);
+        };
+        //The world server checks game connections against the address of the latest logon
+        self.auth_database.set_account_last_ip(&username, &addr.ip().to_string()).await?;
+        reconnecting_client.transition(AuthStep::ReconnectProof, ClientState::Authenticated { username })?;
+        self.disconnect_previous_connection(previous_address, addr).await;
         Ok(())
     }
 
     /// Handle `CMD_REALM_LIST` for an authenticated client and send the realm list.
     pub async fn handle_realm_list(&mut self, addr: &SocketAddr) -> Result<()> {
         let client = self.connected_clients.get_mut(addr).unwrap();
-
-        // Verify state
-        if !matches!(client.state, Some(ClientState::LogOnProof)) {
-            client.connection.sender.send_async(ServerEvent::Disconnect).await?;
-            return Err(anyhow!("Client is not in LogOnProof state."));
-        }
-
-        let username = client.authentication.as_ref().unwrap().username.clone();
+        let ClientState::Authenticated { username } = &client.state else {
+            return Err(anyhow!("Client is not in Authenticated state."));
+        };
+        let username = username.clone();
 
         let account = match self.auth_database.get_account_by_username(&username).await? {
             Some(acc) => acc,
@@ -481,11 +502,38 @@ impl ClientManager {
         let realm_list = CMD_REALM_LIST_Server { realms };
         let server_message = ServerEvent::RealmList(realm_list);
         client.connection.sender.send_async(server_message).await?;
-        client.state.replace(ClientState::LogOnProof);
-        Ok(())
+        client.transition(AuthStep::RealmList, ClientState::Authenticated { username })
     }
 }
 
+fn auth_step(packet: &ClientOpcodeMessage) -> AuthStep {
+    match packet {
+        ClientOpcodeMessage::CMD_AUTH_LOGON_CHALLENGE(_) => AuthStep::LogonChallenge,
+        ClientOpcodeMessage::CMD_AUTH_LOGON_PROOF(_) => AuthStep::LogonProof,
+        ClientOpcodeMessage::CMD_AUTH_RECONNECT_CHALLENGE(_) => AuthStep::ReconnectChallenge,
+        ClientOpcodeMessage::CMD_AUTH_RECONNECT_PROOF(_) => AuthStep::ReconnectProof,
+        ClientOpcodeMessage::CMD_REALM_LIST(_) => AuthStep::RealmList,
+    }
+}
+
+/// Create an unknown account on its first logon (see `FeatureFlags::auto_create_accounts`), the password is the
+/// username. Returns `None` for usernames that can't be an account.
+async fn auto_create_account(auth_database: &AuthDatabase, account_name: &str) -> Result<Option<DBAccount>> {
+    let (Ok(username), Ok(password)) = (NormalizedString::from(account_name), NormalizedString::from(account_name)) else {
+        return Ok(None);
+    };
+    let verifier = SrpVerifier::from_username_and_password(username, password);
+    auth_database
+        .create_account(
+            verifier.username(),
+            &hex::encode(verifier.password_verifier()),
+            &hex::encode(verifier.salt()),
+        )
+        .await?;
+    info!("Account {} created on its first logon", account_name);
+    auth_database.get_account_by_username(account_name).await
+}
+
 /// Receive the next `ClientEvent` or signal closure.
 async fn receive_messages(receiver: &Receiver<ClientEvent>) -> ClientManagerEvent {
     match receiver.recv_async().await {
@@ -506,6 +554,10 @@ async fn message_timeout() -> ClientManagerEvent {
 /// Read `AUTH_RECONNECT_LIFETIME` from the environment and convert to `Duration`.
 /// Defaults to 500 seconds if missing or invalid.
 fn get_auth_reconnect_lifetime() -> Duration {
-    let secs = env::var("AUTH_RECONNECT_LIFETIME").map_or(500, |x| x.parse::<u64>().unwrap_or(500));
-    Duration::from_secs(secs)
+    Duration::from_secs(config::or_default("AUTH_RECONNECT_LIFETIME", 500))
+}
+
+//Locales travel as four reversed ASCII characters, as_int() puts them back in reading order ("enUS")
+fn locale_code(locale: Locale) -> String {
+    locale.as_int().to_be_bytes().iter().map(|&b| b as char).collect()
 }
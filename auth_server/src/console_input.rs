@@ -4,12 +4,14 @@ use std::io::{self, BufRead};
 use tracing::{info, warn};
 use wow_srp::{normalized_string::NormalizedString, server::SrpVerifier};
 use wrath_auth_db::AuthDatabase;
+use wrath_common::GmLevel;
 
 #[derive(Debug, PartialEq, Eq, Parsable)]
 enum WrathConsoleCommand {
     CreateAccount(String, String),
     Ban(String),
     Unban(String),
+    SetGmLevel(String, u8),
 }
 
 pub async fn process_console_commands(auth_db: std::sync::Arc<AuthDatabase>) -> Result<()> {
@@ -36,6 +38,7 @@ async fn handle_command(cmd: WrathConsoleCommand, auth_db: std::sync::Arc<AuthDa
         WrathConsoleCommand::CreateAccount(username, password) => handle_create_account(&username, &password, &auth_db).await,
         WrathConsoleCommand::Ban(username) => handle_ban(&username, &auth_db).await,
         WrathConsoleCommand::Unban(username) => handle_unban(&username, &auth_db).await,
+        WrathConsoleCommand::SetGmLevel(username, level) => handle_set_gm_level(&username, level, &auth_db).await,
     };
 
     if let Err(e) = result {
@@ -68,3 +71,13 @@ async fn handle_unban(username: &str, auth_db: &std::sync::Arc<AuthDatabase>) ->
     info!("Account {} unbanned", username);
     Ok(())
 }
+
+async fn handle_set_gm_level(username: &str, level: u8, auth_db: &std::sync::Arc<AuthDatabase>) -> Result<()> {
+    let gm_level = GmLevel::from_db(level);
+    if auth_db.set_account_gm_level(username, gm_level.as_db()).await? {
+        info!("Account {} is now {:?}, effective on its next world login", username, gm_level);
+    } else {
+        warn!("No account named {}", username);
+    }
+    Ok(())
+}
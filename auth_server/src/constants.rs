@@ -27,16 +27,3 @@ pub enum AuthResult {
     FailConversionRequired = 0x20,
     FailDisconnected = 0xFF,
 }
-
-#[allow(dead_code)]
-pub enum RealmFlags {
-    None = 0x00,
-    Invalid = 0x01,
-    Offline = 0x02,
-    SpecificBuild = 0x04,
-    Unknown1 = 0x08,
-    Unknown2 = 0x10,
-    Recommended = 0x20,
-    New = 0x40,
-    Full = 0x80,
-}
@@ -17,12 +17,13 @@ use smol::net::{TcpListener, TcpStream};
 use smol_macros::main;
 use std::time::Duration;
 use time::macros::format_description;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::{fmt::time::UtcTime, EnvFilter};
 use wow_login_messages::ServerMessage;
 
 use wow_login_messages::version_8::opcodes::ClientOpcodeMessage;
 use wrath_auth_db::AuthDatabase;
+use wrath_common::{config, health, Health, SocketOptions};
 
 //mod auth;
 mod client_manager;
@@ -45,10 +46,22 @@ async fn main() -> Result<()> {
         .init();
 
     info!("Auth server starting");
+    let health = Health::from_env(&["database", "listener"]);
+    if let Some(bind) = health::bind_address_from_env() {
+        let health = health.clone();
+        smol::spawn(async move {
+            if let Err(e) = health::serve_probes(bind, health).await {
+                error!("Health probe endpoint stopped: {e}");
+            }
+        })
+        .detach();
+    }
+
     info!("Connecting to auth database");
-    let db_connect_timeout = Duration::from_secs(std::env::var("DB_CONNECT_TIMEOUT_SECONDS")?.parse()?);
-    let connect_string = std::env::var("AUTH_DATABASE_URL")?;
+    let db_connect_timeout = Duration::from_secs(config::required("DB_CONNECT_TIMEOUT_SECONDS")?);
+    let connect_string: String = config::required("AUTH_DATABASE_URL")?;
     let auth_db = std::sync::Arc::new(AuthDatabase::new(&connect_string, db_connect_timeout).await?);
+    health.pass_check("database");
 
     let (client_manager_sender, client_manager_receiver) = flume::unbounded();
     let client_manager = ClientManager::new(auth_db.clone());
@@ -61,8 +74,21 @@ async fn main() -> Result<()> {
     smol::spawn(console_input::process_console_commands(auth_db.clone())).detach();
 
     let tcp_listener = TcpListener::bind("127.0.0.1:3724").await?;
+    health.pass_check("listener");
+    // There is no game loop here, the heartbeat shows the executor still gets to run tasks.
+    smol::spawn(async move {
+        loop {
+            health.heartbeat();
+            async_io::Timer::after(Duration::from_secs(1)).await;
+        }
+    })
+    .detach();
+    let socket_options = SocketOptions::from_env();
     loop {
         let (stream, _) = tcp_listener.accept().await?;
+        if let Err(e) = socket_options.apply(&stream) {
+            warn!("Could not apply socket options to connection: {e}");
+        }
         smol::spawn(handle_incoming_connection(stream, client_manager_sender.clone())).detach();
     }
 }
@@ -1,8 +1,245 @@
+//! Login protocol state machine.
+//!
+//! Every packet of the login protocol is an `AuthStep`. A step is only accepted in one state, and each step
+//! can only lead to a fixed set of states. The client manager checks both through `Client::transition`,
+//! so a client sending packets out of order is rejected in one place instead of by each handler.
+//!
+//! ```text
+//! Connected --LogonChallenge--> ChallengeProof --LogonProof--> Authenticated --RealmList--> Authenticated
+//! Connected --ReconnectChallenge--> ReconnectProof --ReconnectProof--> Authenticated
+//! ```
+//!
+//! Failed challenges and proofs fall back to `Connected`.
+
+use std::fmt;
+
 use wow_srp::server::SrpProof;
 
 pub enum ClientState {
     Connected,
     ChallengeProof { srp_proof: SrpProof, username: String },
     ReconnectProof { username: String },
-    LogOnProof,
+    Authenticated { username: String },
+}
+
+impl ClientState {
+    pub fn kind(&self) -> StateKind {
+        match self {
+            ClientState::Connected => StateKind::Connected,
+            ClientState::ChallengeProof { .. } => StateKind::ChallengeProof,
+            ClientState::ReconnectProof { .. } => StateKind::ReconnectProof,
+            ClientState::Authenticated { .. } => StateKind::Authenticated,
+        }
+    }
+}
+
+/// `ClientState` without the data it carries, for checking transitions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StateKind {
+    Connected,
+    ChallengeProof,
+    ReconnectProof,
+    Authenticated,
+}
+
+/// A packet of the login protocol sent by the client.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AuthStep {
+    LogonChallenge,
+    LogonProof,
+    ReconnectChallenge,
+    ReconnectProof,
+    RealmList,
+}
+
+impl AuthStep {
+    pub const ALL: [AuthStep; 5] = [
+        AuthStep::LogonChallenge,
+        AuthStep::LogonProof,
+        AuthStep::ReconnectChallenge,
+        AuthStep::ReconnectProof,
+        AuthStep::RealmList,
+    ];
+
+    /// The only state in which the client may send this packet.
+    pub fn required_state(self) -> StateKind {
+        match self {
+            AuthStep::LogonChallenge | AuthStep::ReconnectChallenge => StateKind::Connected,
+            AuthStep::LogonProof => StateKind::ChallengeProof,
+            AuthStep::ReconnectProof => StateKind::ReconnectProof,
+            AuthStep::RealmList => StateKind::Authenticated,
+        }
+    }
+
+    /// The states handling this packet may leave the client in.
+    pub fn next_states(self) -> &'static [StateKind] {
+        match self {
+            AuthStep::LogonChallenge => &[StateKind::ChallengeProof, StateKind::Connected],
+            AuthStep::LogonProof => &[StateKind::Authenticated, StateKind::Connected],
+            AuthStep::ReconnectChallenge => &[StateKind::ReconnectProof, StateKind::Connected],
+            AuthStep::ReconnectProof => &[StateKind::Authenticated, StateKind::Connected],
+            AuthStep::RealmList => &[StateKind::Authenticated],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InvalidTransition {
+    /// The client sent a packet that isn't allowed in its current state.
+    UnexpectedStep { state: StateKind, step: AuthStep },
+    /// A handler tried to move the client into a state the packet can't lead to. This is a server bug.
+    UnexpectedState { step: AuthStep, next: StateKind },
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidTransition::UnexpectedStep { state, step } => write!(f, "{step:?} is not allowed in state {state:?}"),
+            InvalidTransition::UnexpectedState { step, next } => write!(f, "{step:?} can not lead to state {next:?}"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidTransition {}
+
+pub fn check_step(state: StateKind, step: AuthStep) -> Result<(), InvalidTransition> {
+    if step.required_state() == state {
+        Ok(())
+    } else {
+        Err(InvalidTransition::UnexpectedStep { state, step })
+    }
+}
+
+pub fn check_transition(state: StateKind, step: AuthStep, next: StateKind) -> Result<(), InvalidTransition> {
+    check_step(state, step)?;
+    if step.next_states().contains(&next) {
+        Ok(())
+    } else {
+        Err(InvalidTransition::UnexpectedState { step, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_STATES: [StateKind; 4] = [
+        StateKind::Connected,
+        StateKind::ChallengeProof,
+        StateKind::ReconnectProof,
+        StateKind::Authenticated,
+    ];
+
+    //Runs a sequence of (step, next state) from Connected and returns the final state
+    fn run_flow(flow: &[(AuthStep, StateKind)]) -> Result<StateKind, InvalidTransition> {
+        flow.iter().try_fold(StateKind::Connected, |state, &(step, next)| {
+            check_transition(state, step, next)?;
+            Ok(next)
+        })
+    }
+
+    #[test]
+    fn every_step_is_accepted_in_exactly_one_state() {
+        for step in AuthStep::ALL {
+            let accepting: Vec<StateKind> = ALL_STATES.into_iter().filter(|&state| check_step(state, step).is_ok()).collect();
+            assert_eq!(accepting, vec![step.required_state()], "{step:?}");
+        }
+    }
+
+    #[test]
+    fn logon_flow() {
+        let flow = [
+            (AuthStep::LogonChallenge, StateKind::ChallengeProof),
+            (AuthStep::LogonProof, StateKind::Authenticated),
+            (AuthStep::RealmList, StateKind::Authenticated),
+            (AuthStep::RealmList, StateKind::Authenticated),
+        ];
+        assert_eq!(run_flow(&flow), Ok(StateKind::Authenticated));
+    }
+
+    #[test]
+    fn failed_logon_can_be_retried() {
+        let flow = [
+            (AuthStep::LogonChallenge, StateKind::ChallengeProof),
+            (AuthStep::LogonProof, StateKind::Connected),
+            (AuthStep::LogonChallenge, StateKind::ChallengeProof),
+            (AuthStep::LogonProof, StateKind::Authenticated),
+        ];
+        assert_eq!(run_flow(&flow), Ok(StateKind::Authenticated));
+
+        //Banned and unknown accounts stay connected without getting a challenge
+        assert_eq!(run_flow(&[(AuthStep::LogonChallenge, StateKind::Connected)]), Ok(StateKind::Connected));
+    }
+
+    #[test]
+    fn reconnect_flow() {
+        let flow = [
+            (AuthStep::ReconnectChallenge, StateKind::ReconnectProof),
+            (AuthStep::ReconnectProof, StateKind::Authenticated),
+            (AuthStep::RealmList, StateKind::Authenticated),
+        ];
+        assert_eq!(run_flow(&flow), Ok(StateKind::Authenticated));
+
+        let failed = [
+            (AuthStep::ReconnectChallenge, StateKind::ReconnectProof),
+            (AuthStep::ReconnectProof, StateKind::Connected),
+        ];
+        assert_eq!(run_flow(&failed), Ok(StateKind::Connected));
+    }
+
+    #[test]
+    fn realm_list_requires_authentication() {
+        for state in [StateKind::Connected, StateKind::ChallengeProof, StateKind::ReconnectProof] {
+            assert_eq!(
+                check_transition(state, AuthStep::RealmList, StateKind::Authenticated),
+                Err(InvalidTransition::UnexpectedStep {
+                    state,
+                    step: AuthStep::RealmList
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn proofs_require_their_challenge() {
+        assert!(run_flow(&[(AuthStep::LogonProof, StateKind::Authenticated)]).is_err());
+        assert!(run_flow(&[(AuthStep::ReconnectProof, StateKind::Authenticated)]).is_err());
+
+        //A reconnect proof can't answer a logon challenge, and the other way around
+        let mixed = [
+            (AuthStep::LogonChallenge, StateKind::ChallengeProof),
+            (AuthStep::ReconnectProof, StateKind::Authenticated),
+        ];
+        assert!(run_flow(&mixed).is_err());
+        let mixed = [
+            (AuthStep::ReconnectChallenge, StateKind::ReconnectProof),
+            (AuthStep::LogonProof, StateKind::Authenticated),
+        ];
+        assert!(run_flow(&mixed).is_err());
+    }
+
+    #[test]
+    fn authenticated_clients_can_not_start_over() {
+        for step in [
+            AuthStep::LogonChallenge,
+            AuthStep::ReconnectChallenge,
+            AuthStep::LogonProof,
+            AuthStep::ReconnectProof,
+        ] {
+            assert!(check_step(StateKind::Authenticated, step).is_err(), "{step:?}");
+        }
+    }
+
+    #[test]
+    fn handlers_can_not_skip_ahead() {
+        assert_eq!(
+            check_transition(StateKind::Connected, AuthStep::LogonChallenge, StateKind::Authenticated),
+            Err(InvalidTransition::UnexpectedState {
+                step: AuthStep::LogonChallenge,
+                next: StateKind::Authenticated
+            })
+        );
+        assert!(check_transition(StateKind::Connected, AuthStep::ReconnectChallenge, StateKind::Authenticated).is_err());
+        assert!(check_transition(StateKind::Authenticated, AuthStep::RealmList, StateKind::Connected).is_err());
+    }
 }
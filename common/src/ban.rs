@@ -0,0 +1,54 @@
+//! Whether an account may log in, from the permanent `banned` flag on the account and its rows in
+//! `account_bans`. A ban row with an unban time is a suspension, one without never runs out.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BanStatus {
+    NotBanned,
+    Banned,
+    Suspended { until: u64 },
+}
+
+impl BanStatus {
+    //Unban times are unix timestamps, 0 for bans that never end. A permanent ban wins over any
+    //suspension, otherwise the suspension that ends last counts.
+    pub fn from_bans(banned_flag: bool, unban_times: impl IntoIterator<Item = u64>, now: u64) -> Self {
+        if banned_flag {
+            return BanStatus::Banned;
+        }
+
+        let mut status = BanStatus::NotBanned;
+        for unban_time in unban_times {
+            status = match (status, unban_time) {
+                (_, 0) => return BanStatus::Banned,
+                (_, until) if until <= now => status,
+                (BanStatus::Suspended { until: current }, until) if current >= until => status,
+                (_, until) => BanStatus::Suspended { until },
+            };
+        }
+        status
+    }
+
+    pub fn is_banned(self) -> bool {
+        self != BanStatus::NotBanned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permanent_bans_win_over_suspensions() {
+        assert_eq!(BanStatus::from_bans(false, [], 100), BanStatus::NotBanned);
+        assert_eq!(BanStatus::from_bans(true, [], 100), BanStatus::Banned);
+        assert_eq!(BanStatus::from_bans(false, [500, 0], 100), BanStatus::Banned);
+    }
+
+    #[test]
+    fn the_longest_running_suspension_counts() {
+        assert_eq!(BanStatus::from_bans(false, [500, 900, 300], 100), BanStatus::Suspended { until: 900 });
+        //Expired suspensions don't keep anyone out
+        assert_eq!(BanStatus::from_bans(false, [50, 100], 100), BanStatus::NotBanned);
+        assert!(!BanStatus::from_bans(false, [50], 100).is_banned());
+    }
+}
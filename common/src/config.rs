@@ -0,0 +1,35 @@
+//! Reading settings from the environment, which both servers fill from their `.env` file on startup.
+//!
+//! Optional settings fall back to a default when they are missing or can't be parsed, so a typo in an
+//! optional setting never keeps a server from starting. Settings a server can't run without go through
+//! `required`, which names the variable in its error.
+
+use std::str::FromStr;
+
+use crate::ConfigError;
+
+pub fn required<T: FromStr>(name: &str) -> Result<T, ConfigError> {
+    let value = std::env::var(name).map_err(|_| ConfigError::Missing { name: name.to_string() })?;
+    value.parse().map_err(|_| ConfigError::Invalid {
+        name: name.to_string(),
+        value,
+    })
+}
+
+pub fn optional<T: FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+pub fn or_default<T: FromStr>(name: &str, default: T) -> T {
+    optional(name).unwrap_or(default)
+}
+
+//Zero counts as missing, for settings where zero means "off"
+pub fn positive(name: &str) -> Option<u64> {
+    optional::<u64>(name).filter(|&v| v > 0)
+}
+
+//Flags are on unless set to 0
+pub fn flag(name: &str, default: bool) -> bool {
+    optional::<u8>(name).map_or(default, |v| v != 0)
+}
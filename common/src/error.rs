@@ -0,0 +1,35 @@
+use std::fmt;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ConfigError {
+    Missing { name: String },
+    Invalid { name: String, value: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Missing { name } => write!(f, "Environment variable {name} is not set"),
+            ConfigError::Invalid { name, value } => write!(f, "Environment variable {name} has invalid value '{value}'"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SessionKeyError {
+    NotHex,
+    InvalidLength(usize),
+}
+
+impl fmt::Display for SessionKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionKeyError::NotHex => write!(f, "Session key is not a hex string"),
+            SessionKeyError::InvalidLength(length) => write!(f, "Session key is {length} bytes long"),
+        }
+    }
+}
+
+impl std::error::Error for SessionKeyError {}
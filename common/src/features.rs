@@ -0,0 +1,85 @@
+//! Feature flags, with a "solo mode" preset for running a server for yourself.
+//!
+//! `SOLO_MODE=1` turns on everything that makes single player testing painless: accounts are created on
+//! first logon, every account is an administrator that enters the world in GM mode, and experience, money
+//! and drop rates are boosted. Each setting can still be given on its own, which overrides the preset, so
+//! e.g. solo mode with normal rates is `SOLO_MODE=1` plus `RATE_EXPERIENCE=1`.
+
+use crate::config;
+use crate::GmLevel;
+
+const SOLO_MODE_RATE: f32 = 5.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rates {
+    pub experience: f32,
+    pub money: f32,
+    pub drop: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeatureFlags {
+    //Unknown usernames get an account with the username as password
+    pub auto_create_accounts: bool,
+    //Accounts below this level are treated as having it
+    pub minimum_gm_level: GmLevel,
+    //Staff enter the world with the GM tag
+    pub gm_mode_on_login: bool,
+    pub rates: Rates,
+}
+
+impl FeatureFlags {
+    pub fn preset(solo_mode: bool) -> Self {
+        let rate = if solo_mode { SOLO_MODE_RATE } else { 1.0 };
+        Self {
+            auto_create_accounts: solo_mode,
+            minimum_gm_level: if solo_mode { GmLevel::Administrator } else { GmLevel::Player },
+            gm_mode_on_login: true,
+            rates: Rates {
+                experience: rate,
+                money: rate,
+                drop: rate,
+            },
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let preset = Self::preset(config::flag("SOLO_MODE", false));
+        Self {
+            auto_create_accounts: config::flag("AUTO_CREATE_ACCOUNTS", preset.auto_create_accounts),
+            minimum_gm_level: config::optional::<u8>("MINIMUM_GM_LEVEL").map_or(preset.minimum_gm_level, GmLevel::from_db),
+            gm_mode_on_login: config::flag("GM_MODE_ON_LOGIN", preset.gm_mode_on_login),
+            rates: Rates {
+                experience: rate_from_env("RATE_EXPERIENCE", preset.rates.experience),
+                money: rate_from_env("RATE_MONEY", preset.rates.money),
+                drop: rate_from_env("RATE_DROP", preset.rates.drop),
+            },
+        }
+    }
+
+    pub fn effective_gm_level(&self, account_level: GmLevel) -> GmLevel {
+        account_level.max(self.minimum_gm_level)
+    }
+}
+
+fn rate_from_env(name: &str, default: f32) -> f32 {
+    config::optional::<f32>(name).filter(|rate| *rate >= 0.0).unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solo_mode_makes_everyone_an_administrator() {
+        let solo = FeatureFlags::preset(true);
+        assert!(solo.auto_create_accounts);
+        assert_eq!(solo.effective_gm_level(GmLevel::Player), GmLevel::Administrator);
+        assert_eq!(solo.rates.experience, SOLO_MODE_RATE);
+
+        let normal = FeatureFlags::preset(false);
+        assert!(!normal.auto_create_accounts);
+        assert_eq!(normal.effective_gm_level(GmLevel::Moderator), GmLevel::Moderator);
+        assert_eq!(normal.rates.drop, 1.0);
+    }
+}
@@ -0,0 +1,46 @@
+/// How much an account is trusted with, from regular players up to server administrators.
+/// Levels are ordered, so a command needing `GameMaster` is also allowed for `Administrator`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum GmLevel {
+    #[default]
+    Player = 0,
+    Moderator = 1,
+    GameMaster = 2,
+    Administrator = 3,
+}
+
+impl GmLevel {
+    //Unknown levels from the database are treated as the highest level below them
+    pub fn from_db(level: u8) -> Self {
+        match level {
+            0 => GmLevel::Player,
+            1 => GmLevel::Moderator,
+            2 => GmLevel::GameMaster,
+            _ => GmLevel::Administrator,
+        }
+    }
+
+    pub fn as_db(self) -> u8 {
+        self as u8
+    }
+
+    pub fn is_staff(self) -> bool {
+        self > GmLevel::Player
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_round_trip_and_are_ordered() {
+        for level in [GmLevel::Player, GmLevel::Moderator, GmLevel::GameMaster, GmLevel::Administrator] {
+            assert_eq!(GmLevel::from_db(level.as_db()), level);
+        }
+        assert_eq!(GmLevel::from_db(200), GmLevel::Administrator);
+        assert!(GmLevel::Administrator > GmLevel::GameMaster);
+        assert!(!GmLevel::Player.is_staff());
+        assert!(GmLevel::Moderator.is_staff());
+    }
+}
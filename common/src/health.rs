@@ -0,0 +1,115 @@
+//! Readiness and liveness probes for container orchestration.
+//!
+//! A server is ready once every startup check it registered has passed (databases connected, listener
+//! bound, data loaded), and alive as long as its main loop keeps calling `heartbeat`. Both are served
+//! over plain HTTP on `/ready` and `/live`, answering 200 or 503, so Docker and Kubernetes can hold back
+//! traffic until startup is done and restart a server whose loop hung.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use smol::io::{AsyncReadExt, AsyncWriteExt};
+use smol::net::{TcpListener, TcpStream};
+
+use crate::config;
+
+const DEFAULT_LIVENESS_TIMEOUT_SECONDS: u64 = 30;
+
+pub struct Health {
+    pending_checks: Mutex<Vec<&'static str>>,
+    last_heartbeat: Mutex<Instant>,
+    liveness_timeout: Duration,
+}
+
+impl Health {
+    pub fn new(checks: &[&'static str], liveness_timeout: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            pending_checks: Mutex::new(checks.to_vec()),
+            last_heartbeat: Mutex::new(Instant::now()),
+            liveness_timeout,
+        })
+    }
+
+    pub fn from_env(checks: &[&'static str]) -> Arc<Self> {
+        let timeout = config::or_default("HEALTH_LIVENESS_TIMEOUT_SECONDS", DEFAULT_LIVENESS_TIMEOUT_SECONDS);
+        Self::new(checks, Duration::from_secs(timeout))
+    }
+
+    pub fn pass_check(&self, check: &str) {
+        self.pending_checks.lock().unwrap().retain(|&pending| pending != check);
+    }
+
+    pub fn heartbeat(&self) {
+        *self.last_heartbeat.lock().unwrap() = Instant::now();
+    }
+
+    //The checks that haven't passed yet, ready when empty
+    pub fn pending_checks(&self) -> Vec<&'static str> {
+        self.pending_checks.lock().unwrap().clone()
+    }
+
+    pub fn is_alive(&self, now: Instant) -> bool {
+        now.saturating_duration_since(*self.last_heartbeat.lock().unwrap()) <= self.liveness_timeout
+    }
+
+    fn probe(&self, path: &str) -> (&'static str, String) {
+        match path {
+            "/ready" => match self.pending_checks().as_slice() {
+                [] => ("200 OK", "ready".into()),
+                pending => ("503 Service Unavailable", format!("waiting for {}", pending.join(", "))),
+            },
+            "/live" if self.is_alive(Instant::now()) => ("200 OK", "alive".into()),
+            "/live" => ("503 Service Unavailable", "main loop stalled".into()),
+            _ => ("404 Not Found", "use /ready or /live".into()),
+        }
+    }
+}
+
+//The bind address comes from HEALTH_BIND, the probes are off when it's empty
+pub fn bind_address_from_env() -> Option<String> {
+    config::optional::<String>("HEALTH_BIND").filter(|bind| !bind.is_empty())
+}
+
+pub async fn serve_probes(bind: String, health: Arc<Health>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(bind).await?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        smol::spawn(answer_probe(stream, health.clone())).detach();
+    }
+}
+
+async fn answer_probe(mut stream: TcpStream, health: Arc<Health>) -> std::io::Result<()> {
+    //Only the request line matters, probes don't send bodies
+    let mut buf = [0u8; 512];
+    let read = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+
+    let (status, body) = health.probe(path);
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ready_once_all_checks_pass_and_alive_while_beating() {
+        let health = Health::new(&["database", "listener"], Duration::from_secs(10));
+        assert_eq!(health.probe("/ready").0, "503 Service Unavailable");
+        health.pass_check("database");
+        assert_eq!(health.probe("/ready").1, "waiting for listener");
+        health.pass_check("listener");
+        assert_eq!(health.probe("/ready").0, "200 OK");
+
+        let now = Instant::now();
+        health.heartbeat();
+        assert!(health.is_alive(now));
+        assert!(!health.is_alive(now + Duration::from_secs(11)));
+    }
+}
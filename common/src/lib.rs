@@ -0,0 +1,19 @@
+//! Types and conventions shared by the auth and world servers.
+
+pub mod ban;
+pub mod config;
+pub mod error;
+pub mod features;
+pub mod gm_level;
+pub mod health;
+pub mod realm;
+pub mod session_key;
+pub mod socket_options;
+
+pub use ban::BanStatus;
+pub use error::{ConfigError, SessionKeyError};
+pub use features::FeatureFlags;
+pub use gm_level::GmLevel;
+pub use health::Health;
+pub use realm::RealmFlags;
+pub use socket_options::SocketOptions;
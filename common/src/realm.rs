@@ -0,0 +1,19 @@
+/// Bits of the `flags` column of the realms table, as the client reads them in the realm list.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RealmFlags {
+    None = 0x00,
+    Invalid = 0x01,
+    Offline = 0x02,
+    SpecificBuild = 0x04,
+    Unknown1 = 0x08,
+    Unknown2 = 0x10,
+    Recommended = 0x20,
+    New = 0x40,
+    Full = 0x80,
+}
+
+impl RealmFlags {
+    pub fn is_set(self, flags: u8) -> bool {
+        flags & self as u8 != 0
+    }
+}
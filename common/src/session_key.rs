@@ -0,0 +1,38 @@
+//! The SRP6 session key is agreed on by the auth server and stored on the account as a hex string,
+//! the world server reads it back to set up header encryption for the client.
+
+use crate::SessionKeyError;
+
+pub const SESSION_KEY_LENGTH: usize = 40;
+
+pub type SessionKey = [u8; SESSION_KEY_LENGTH];
+
+pub fn encode(session_key: &SessionKey) -> String {
+    hex::encode(session_key)
+}
+
+pub fn decode(encoded: &str) -> Result<SessionKey, SessionKeyError> {
+    let bytes = hex::decode(encoded).map_err(|_| SessionKeyError::NotHex)?;
+    let length = bytes.len();
+    bytes.try_into().map_err(|_| SessionKeyError::InvalidLength(length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_keys_round_trip() {
+        let mut session_key = [0u8; SESSION_KEY_LENGTH];
+        session_key.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8 + 1);
+        assert_eq!(decode(&encode(&session_key)), Ok(session_key));
+    }
+
+    #[test]
+    fn invalid_session_keys_are_rejected() {
+        assert_eq!(decode("not hex"), Err(SessionKeyError::NotHex));
+        //Accounts that never logged in have an empty session key
+        assert_eq!(decode(""), Err(SessionKeyError::InvalidLength(0)));
+        assert_eq!(decode("abcd"), Err(SessionKeyError::InvalidLength(2)));
+    }
+}
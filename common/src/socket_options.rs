@@ -0,0 +1,50 @@
+//! Socket options applied to every accepted client connection.
+//!
+//! Nagle's algorithm is turned off by default: movement packets are small and latency sensitive, and
+//! holding them back until the previous segment is acknowledged makes other players visibly stutter.
+
+use std::time::Duration;
+
+use smol::net::TcpStream;
+use socket2::{SockRef, TcpKeepalive};
+
+use crate::config;
+
+pub struct SocketOptions {
+    nodelay: bool,
+    keepalive: Option<Duration>,
+    send_buffer_size: Option<usize>,
+    recv_buffer_size: Option<usize>,
+}
+
+impl SocketOptions {
+    //Zero or missing values keep the operating system defaults
+    pub fn from_env() -> Self {
+        Self {
+            nodelay: config::flag("SOCKET_NODELAY", true),
+            keepalive: config::positive("SOCKET_KEEPALIVE_SECONDS").map(Duration::from_secs),
+            send_buffer_size: config::positive("SOCKET_SEND_BUFFER_BYTES").map(|v| v as usize),
+            recv_buffer_size: config::positive("SOCKET_RECV_BUFFER_BYTES").map(|v| v as usize),
+        }
+    }
+
+    pub fn apply(&self, stream: &TcpStream) -> std::io::Result<()> {
+        stream.set_nodelay(self.nodelay)?;
+
+        let socket = SockRef::from(stream);
+        if let Some(keepalive) = self.keepalive {
+            //Probes start after the connection was idle this long and repeat at the same interval
+            let keepalive_options = TcpKeepalive::new().with_time(keepalive);
+            #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+            let keepalive_options = keepalive_options.with_interval(keepalive);
+            socket.set_tcp_keepalive(&keepalive_options)?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        Ok(())
+    }
+}
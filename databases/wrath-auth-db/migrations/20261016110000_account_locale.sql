@@ -0,0 +1,2 @@
+-- Locale the client reported in its logon challenge (enUS, deDE, ...), used by the world server to pick translated texts
+ALTER TABLE `accounts` ADD COLUMN `locale` varchar(4) NOT NULL DEFAULT 'enUS';
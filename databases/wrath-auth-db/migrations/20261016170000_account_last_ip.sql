@@ -0,0 +1,2 @@
+-- Address the last successful logon came from, the world server can require game connections to come from the same address
+ALTER TABLE `accounts` ADD COLUMN `last_ip` varchar(45) NOT NULL DEFAULT '';
@@ -0,0 +1,14 @@
+-- Bans issued in game. Rows with an unban_time are suspensions, 0 means the ban never ends.
+-- Lifted bans stay in the table with active = 0 so an account's history can be looked up.
+CREATE TABLE `account_bans` (
+	`id` int unsigned NOT NULL AUTO_INCREMENT,
+	`account_id` int unsigned NOT NULL,
+	`ban_time` bigint unsigned NOT NULL,
+	`unban_time` bigint unsigned NOT NULL DEFAULT '0',
+	`banned_by` varchar(32) NOT NULL DEFAULT '',
+	`reason` varchar(255) NOT NULL DEFAULT '',
+	`active` tinyint unsigned NOT NULL DEFAULT '1',
+	PRIMARY KEY (`id`),
+	KEY `FK_ACCOUNT_BANS_ACCOUNT` (`account_id`),
+	CONSTRAINT `FK_ACCOUNT_BANS_ACCOUNT` FOREIGN KEY (`account_id`) REFERENCES `accounts` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,2 @@
+-- 0 = player, 1 = moderator, 2 = game master, 3 = administrator. Decides which GM commands the account's characters can use.
+ALTER TABLE `accounts` ADD COLUMN `gm_level` tinyint unsigned NOT NULL DEFAULT '0';
@@ -3,7 +3,7 @@ use std::time::Duration;
 use anyhow::Result;
 use sqlx::Row;
 mod structs;
-pub use structs::{DBAccount, DBAccountData, DBRealm, DBRealmWithNumCharacters};
+pub use structs::{DBAccount, DBAccountBan, DBAccountBanCreate, DBAccountData, DBRealm, DBRealmWithNumCharacters};
 
 pub struct AuthDatabase {
     connection_pool: sqlx::MySqlPool,
@@ -20,6 +20,12 @@ impl AuthDatabase {
         Ok(Self { connection_pool: pool })
     }
 
+    //Doesn't connect until the first query, for tests that never touch the database
+    pub fn new_lazy(conn_string: &str) -> Result<Self> {
+        let pool = sqlx::mysql::MySqlPoolOptions::new().max_connections(1).connect_lazy(conn_string)?;
+        Ok(Self { connection_pool: pool })
+    }
+
     pub async fn get_realm_bind_ip(&self, realm_id: i32) -> Result<String> {
         let bind_ip = sqlx::query("SELECT ip FROM realms WHERE id = ?")
             .bind(realm_id)
@@ -78,13 +84,39 @@ impl AuthDatabase {
         Ok(acc)
     }
 
-    pub async fn set_account_sessionkey(&self, username: &str, session_key: &str) -> Result<()> {
-        sqlx::query!("UPDATE accounts SET sessionkey = ? WHERE username = ?;", session_key, username)
+    pub async fn set_account_sessionkey(&self, username: &str, session_key: &str, ip: &str) -> Result<()> {
+        sqlx::query!(
+            "UPDATE accounts SET sessionkey = ?, last_ip = ? WHERE username = ?;",
+            session_key,
+            ip,
+            username
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_account_last_ip(&self, username: &str, ip: &str) -> Result<()> {
+        sqlx::query!("UPDATE accounts SET last_ip = ? WHERE username = ?;", ip, username)
             .execute(&self.connection_pool)
             .await?;
         Ok(())
     }
 
+    pub async fn set_account_locale(&self, username: &str, locale: &str) -> Result<()> {
+        sqlx::query!("UPDATE accounts SET locale = ? WHERE username = ?;", locale, username)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_account_gm_level(&self, username: &str, gm_level: u8) -> Result<bool> {
+        let res = sqlx::query!("UPDATE accounts SET gm_level = ? WHERE username = ?;", gm_level, username)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
     pub async fn set_account_ban_status(&self, username: &str, banned: bool) -> Result<()> {
         let banned_int = banned as u8;
         sqlx::query!("UPDATE `accounts` SET banned = ? WHERE username = ?;", banned_int, username)
@@ -93,6 +125,41 @@ impl AuthDatabase {
         Ok(())
     }
 
+    //Bans that are still running at the given unix time
+    pub async fn get_active_account_bans(&self, account_id: u32, now: u64) -> Result<Vec<DBAccountBan>> {
+        let bans = sqlx::query_as!(
+            DBAccountBan,
+            "SELECT * FROM account_bans WHERE account_id = ? AND active = 1 AND (unban_time = 0 OR unban_time > ?)",
+            account_id,
+            now
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(bans)
+    }
+
+    pub async fn add_account_ban(&self, ban: &DBAccountBanCreate) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO account_bans (account_id, ban_time, unban_time, banned_by, reason) VALUES (?, ?, ?, ?, ?)",
+            ban.account_id,
+            ban.ban_time,
+            ban.unban_time,
+            ban.banned_by,
+            ban.reason
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    //Returns whether there was anything to lift
+    pub async fn lift_account_bans(&self, account_id: u32) -> Result<bool> {
+        let res = sqlx::query!("UPDATE account_bans SET active = 0 WHERE account_id = ? AND active = 1", account_id)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
     pub async fn get_account_data(&self, account_id: u32) -> Result<Vec<DBAccountData>> {
         let acc_data = sqlx::query_as!(DBAccountData, "SELECT * FROM account_data WHERE account_id = ?", account_id)
             .fetch_all(&self.connection_pool)
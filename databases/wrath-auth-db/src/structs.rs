@@ -28,6 +28,9 @@ pub struct DBAccount {
     pub v: String,
     pub s: String,
     pub banned: u8,
+    pub locale: String,
+    pub last_ip: String,
+    pub gm_level: u8,
 }
 
 pub struct DBAccountData {
@@ -37,3 +40,21 @@ pub struct DBAccountData {
     pub decompressed_size: u32,
     pub data: Option<Vec<u8>>,
 }
+
+pub struct DBAccountBan {
+    pub id: u32,
+    pub account_id: u32,
+    pub ban_time: u64,
+    pub unban_time: u64,
+    pub banned_by: String,
+    pub reason: String,
+    pub active: u8,
+}
+
+pub struct DBAccountBanCreate {
+    pub account_id: u32,
+    pub ban_time: u64,
+    pub unban_time: u64,
+    pub banned_by: String,
+    pub reason: String,
+}
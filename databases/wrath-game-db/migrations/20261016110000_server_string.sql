@@ -0,0 +1,30 @@
+-- Translations of texts the server generates itself. Entries match the ServerString enum in the world server,
+-- which also holds the enUS fallback. Placeholders are written as {}.
+CREATE TABLE `server_string` (
+`entry` int(10) unsigned NOT NULL,
+`locale` varchar(4) NOT NULL,
+`content` text NOT NULL,
+PRIMARY KEY (`entry`, `locale`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+INSERT INTO `server_string` (`entry`, `locale`, `content`) VALUES
+(1, 'deDE', 'Geschwindigkeit auf {} gesetzt'),
+(2, 'deDE', 'Gegenstand {} hinzugefügt'),
+(3, 'deDE', 'GM-Modus ist {}'),
+(4, 'deDE', 'GM-Sichtbarkeit ist {}'),
+(5, 'deDE', 'GM-Modus ist {}, GM-Sichtbarkeit ist {}'),
+(6, 'deDE', 'Gottmodus ist {}'),
+(7, 'deDE', 'Aktuelle Nachricht des Tages: {}'),
+(8, 'deDE', 'Nachricht des Tages aktualisiert'),
+(9, 'deDE', 'an'),
+(10, 'deDE', 'aus'),
+(1, 'frFR', 'Vitesse réglée sur {}'),
+(2, 'frFR', 'Objet {} ajouté'),
+(3, 'frFR', 'Le mode MJ est {}'),
+(4, 'frFR', 'La visibilité MJ est {}'),
+(5, 'frFR', 'Le mode MJ est {}, la visibilité MJ est {}'),
+(6, 'frFR', 'Le mode dieu est {}'),
+(7, 'frFR', 'Message du jour actuel : {}'),
+(8, 'frFR', 'Message du jour mis à jour'),
+(9, 'frFR', 'activé'),
+(10, 'frFR', 'désactivé');
@@ -0,0 +1,16 @@
+-- World bosses and rares with respawn timers of hours or days. The next respawn time is kept in the realm
+-- database 'rare_spawn_respawn' table so restarts don't reset the timer.
+CREATE TABLE `rare_spawn` (
+`id` int(10) unsigned NOT NULL,
+`creature_entry` int(10) unsigned NOT NULL,
+`map` smallint(5) unsigned NOT NULL,
+`position_x` float NOT NULL,
+`position_y` float NOT NULL,
+`position_z` float NOT NULL,
+`orientation` float NOT NULL DEFAULT '0',
+`respawn_min_seconds` int(10) unsigned NOT NULL,
+`respawn_max_seconds` int(10) unsigned NOT NULL,
+-- Sent to everyone online when the creature spawns, nothing is announced if NULL
+`spawn_emote` text DEFAULT NULL,
+PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
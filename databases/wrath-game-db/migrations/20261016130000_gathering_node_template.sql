@@ -0,0 +1,22 @@
+-- Mining veins, herbs and other gameobjects that are gathered with a profession skill
+CREATE TABLE `gathering_node_template` (
+`entry` int(10) unsigned NOT NULL,
+-- Skill line, 186 = mining, 182 = herbalism, 393 = skinning
+`skill` smallint(5) unsigned NOT NULL,
+`required_skill_value` smallint(5) unsigned NOT NULL DEFAULT 0,
+-- The node disappears after a random number of gathers within this range
+`min_gathers` tinyint(3) unsigned NOT NULL DEFAULT 1,
+`max_gathers` tinyint(3) unsigned NOT NULL DEFAULT 1,
+`respawn_min_seconds` int(10) unsigned NOT NULL,
+`respawn_max_seconds` int(10) unsigned NOT NULL,
+`loot_item` int(10) unsigned NOT NULL,
+`loot_min_count` tinyint(3) unsigned NOT NULL DEFAULT 1,
+`loot_max_count` tinyint(3) unsigned NOT NULL DEFAULT 1,
+PRIMARY KEY (`entry`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+INSERT INTO `gathering_node_template` (`entry`, `skill`, `required_skill_value`, `min_gathers`, `max_gathers`, `respawn_min_seconds`, `respawn_max_seconds`, `loot_item`, `loot_min_count`, `loot_max_count`) VALUES
+(1731, 186, 1, 2, 4, 300, 600, 2770, 1, 3),
+(1732, 186, 65, 2, 4, 300, 600, 2771, 1, 3),
+(1617, 182, 1, 1, 1, 300, 600, 765, 1, 3),
+(1618, 182, 1, 1, 1, 300, 600, 2447, 1, 3);
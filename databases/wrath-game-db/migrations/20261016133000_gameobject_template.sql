@@ -0,0 +1,19 @@
+-- Only the fields the server needs for interaction. The meaning of the data columns depends on the type:
+-- 8 (spell focus): data0 = focus id, data1 = radius in yards
+-- 10 (goober): data0 = lock id, data1 = quest the player needs to have to use it
+CREATE TABLE `gameobject_template` (
+`entry` int(10) unsigned NOT NULL,
+`type` tinyint(3) unsigned NOT NULL,
+`display_id` int(10) unsigned NOT NULL DEFAULT 0,
+`name` varchar(100) NOT NULL DEFAULT '',
+`data0` int(10) unsigned NOT NULL DEFAULT 0,
+`data1` int(10) unsigned NOT NULL DEFAULT 0,
+`data2` int(10) unsigned NOT NULL DEFAULT 0,
+`data3` int(10) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`entry`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+INSERT INTO `gameobject_template` (`entry`, `type`, `display_id`, `name`, `data0`, `data1`, `data2`, `data3`) VALUES
+(1685, 8, 233, 'Forge', 3, 10, 0, 0),
+(1561, 8, 192, 'Anvil', 1, 10, 0, 0),
+(147283, 8, 2770, 'Moonwell', 1424, 10, 0, 0);
@@ -0,0 +1,12 @@
+-- Markers placed on the client's world map, e.g. by a gossip option asking for directions.
+-- icon is the client's map marker icon id, importance decides which marker wins when they overlap.
+CREATE TABLE `points_of_interest` (
+`id` int(10) unsigned NOT NULL,
+`position_x` float NOT NULL,
+`position_y` float NOT NULL,
+`icon` int(10) unsigned NOT NULL DEFAULT 0,
+`flags` int(10) unsigned NOT NULL DEFAULT 0,
+`importance` int(10) unsigned NOT NULL DEFAULT 0,
+`name` varchar(255) NOT NULL DEFAULT '',
+PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
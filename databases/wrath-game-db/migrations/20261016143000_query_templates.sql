@@ -0,0 +1,45 @@
+-- The parts of a creature template the client asks for with CMSG_CREATURE_QUERY
+CREATE TABLE `creature_template` (
+`entry` int(10) unsigned NOT NULL,
+`name` varchar(100) NOT NULL DEFAULT '',
+`subname` varchar(100) NOT NULL DEFAULT '',
+-- Cursor shown when hovering the creature, e.g. 'Speak' or 'Taxi'
+`icon_name` varchar(100) NOT NULL DEFAULT '',
+`type_flags` int(10) unsigned NOT NULL DEFAULT 0,
+`creature_type` int(10) unsigned NOT NULL DEFAULT 0,
+`family` int(10) unsigned NOT NULL DEFAULT 0,
+`creature_rank` int(10) unsigned NOT NULL DEFAULT 0,
+`kill_credit1` int(10) unsigned NOT NULL DEFAULT 0,
+`kill_credit2` int(10) unsigned NOT NULL DEFAULT 0,
+`display_id1` int(10) unsigned NOT NULL DEFAULT 0,
+`display_id2` int(10) unsigned NOT NULL DEFAULT 0,
+`display_id3` int(10) unsigned NOT NULL DEFAULT 0,
+`display_id4` int(10) unsigned NOT NULL DEFAULT 0,
+`health_modifier` float NOT NULL DEFAULT 1,
+`mana_modifier` float NOT NULL DEFAULT 1,
+`racial_leader` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`movement_id` int(10) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`entry`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- Translated names, templates without a row for the client's locale are sent in English
+CREATE TABLE `creature_template_locale` (
+`entry` int(10) unsigned NOT NULL,
+`locale` varchar(4) NOT NULL,
+`name` varchar(100) NOT NULL,
+`subname` varchar(100) NOT NULL DEFAULT '',
+PRIMARY KEY (`entry`, `locale`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+ALTER TABLE `gameobject_template`
+ADD COLUMN `icon_name` varchar(100) NOT NULL DEFAULT '' AFTER `name`,
+ADD COLUMN `cast_bar_caption` varchar(100) NOT NULL DEFAULT '' AFTER `icon_name`,
+ADD COLUMN `size` float NOT NULL DEFAULT 1 AFTER `data3`;
+
+CREATE TABLE `gameobject_template_locale` (
+`entry` int(10) unsigned NOT NULL,
+`locale` varchar(4) NOT NULL,
+`name` varchar(100) NOT NULL,
+`cast_bar_caption` varchar(100) NOT NULL DEFAULT '',
+PRIMARY KEY (`entry`, `locale`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,12 @@
+-- Only the fields the quest offer and quest log need so far
+CREATE TABLE `quest_template` (
+`id` int(10) unsigned NOT NULL,
+`min_level` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`quest_level` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`flags` int(10) unsigned NOT NULL DEFAULT 0,
+`suggested_players` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`title` varchar(255) NOT NULL DEFAULT '',
+`details` text NOT NULL,
+`objectives` text NOT NULL,
+PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
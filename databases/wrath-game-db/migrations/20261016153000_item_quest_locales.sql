@@ -0,0 +1,17 @@
+-- Translated item and quest texts, anything without a row for the client's locale is sent in English
+CREATE TABLE `item_template_locale` (
+`entry` int(10) unsigned NOT NULL,
+`locale` varchar(4) NOT NULL,
+`name` varchar(255) NOT NULL,
+`description` varchar(255) NOT NULL DEFAULT '',
+PRIMARY KEY (`entry`, `locale`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+CREATE TABLE `quest_template_locale` (
+`id` int(10) unsigned NOT NULL,
+`locale` varchar(4) NOT NULL,
+`title` varchar(255) NOT NULL DEFAULT '',
+`details` text NOT NULL,
+`objectives` text NOT NULL,
+PRIMARY KEY (`id`, `locale`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
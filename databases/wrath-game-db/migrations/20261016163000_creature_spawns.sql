@@ -0,0 +1,26 @@
+-- What a spawned creature looks like in the world, the query template columns only cover the client's tooltip
+ALTER TABLE `creature_template`
+ADD COLUMN `min_level` tinyint(3) unsigned NOT NULL DEFAULT 1,
+ADD COLUMN `max_level` tinyint(3) unsigned NOT NULL DEFAULT 1,
+ADD COLUMN `faction` int(10) unsigned NOT NULL DEFAULT 35,
+ADD COLUMN `npc_flags` int(10) unsigned NOT NULL DEFAULT 0,
+ADD COLUMN `unit_flags` int(10) unsigned NOT NULL DEFAULT 0,
+-- Health at the template's level before health_modifier is applied
+ADD COLUMN `base_health` int(10) unsigned NOT NULL DEFAULT 1,
+ADD COLUMN `scale` float NOT NULL DEFAULT 1;
+
+-- Creatures placed in the world. Every row is spawned when its map is created and comes back
+-- spawn_time_seconds after it died.
+CREATE TABLE `creature` (
+`guid` int(10) unsigned NOT NULL,
+`entry` int(10) unsigned NOT NULL,
+`map` smallint(5) unsigned NOT NULL,
+`position_x` float NOT NULL,
+`position_y` float NOT NULL,
+`position_z` float NOT NULL,
+`orientation` float NOT NULL DEFAULT 0,
+`phase_mask` int(10) unsigned NOT NULL DEFAULT 1,
+`spawn_time_seconds` int(10) unsigned NOT NULL DEFAULT 300,
+PRIMARY KEY (`guid`),
+KEY `idx_map` (`map`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,26 @@
+-- Items a creature's corpse can drop, every row is rolled on its own when the creature dies
+CREATE TABLE `creature_loot_template` (
+`entry` int(10) unsigned NOT NULL,
+`item` int(10) unsigned NOT NULL,
+-- In percent, 100 always drops
+`chance` float NOT NULL DEFAULT 100,
+`min_count` tinyint(3) unsigned NOT NULL DEFAULT 1,
+`max_count` tinyint(3) unsigned NOT NULL DEFAULT 1,
+PRIMARY KEY (`entry`, `item`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- Copper on a creature's corpse, split between everyone that may loot it
+CREATE TABLE `creature_loot_money` (
+`entry` int(10) unsigned NOT NULL,
+`min_money` int(10) unsigned NOT NULL DEFAULT 0,
+`max_money` int(10) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`entry`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- Young Wolf
+INSERT INTO `creature_loot_template` (`entry`, `item`, `chance`, `min_count`, `max_count`) VALUES
+(299, 750, 80, 1, 1),
+(299, 2672, 40, 1, 2);
+
+INSERT INTO `creature_loot_money` (`entry`, `min_money`, `max_money`) VALUES
+(299, 1, 6);
@@ -0,0 +1,33 @@
+-- What turning a quest in pays out. Experience and money are for a character at the quest's level,
+-- the world server scales them down for higher levels and applies the realm's rates.
+ALTER TABLE `quest_template`
+ADD COLUMN `offer_reward_text` text NULL,
+ADD COLUMN `reward_xp` int(10) unsigned NOT NULL DEFAULT 0,
+ADD COLUMN `reward_money` int(10) unsigned NOT NULL DEFAULT 0,
+-- Offered right away once this quest is turned in
+ADD COLUMN `next_quest_id` int(10) unsigned NULL;
+
+-- Items handed out on turn in. Of the choice items the character picks one, the others are all given.
+CREATE TABLE `quest_reward_item` (
+`quest_id` int(10) unsigned NOT NULL,
+`item` int(10) unsigned NOT NULL,
+`count` tinyint(3) unsigned NOT NULL DEFAULT 1,
+`is_choice` tinyint(1) NOT NULL DEFAULT 0,
+PRIMARY KEY (`quest_id`, `item`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- Reputation gained on turn in, faction is an id from Faction.dbc
+CREATE TABLE `quest_reward_reputation` (
+`quest_id` int(10) unsigned NOT NULL,
+`faction` int(10) unsigned NOT NULL,
+`value` int(11) NOT NULL DEFAULT 0,
+PRIMARY KEY (`quest_id`, `faction`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- Items the character has to bring, they're taken away on turn in
+CREATE TABLE `quest_required_item` (
+`quest_id` int(10) unsigned NOT NULL,
+`item` int(10) unsigned NOT NULL,
+`count` tinyint(3) unsigned NOT NULL DEFAULT 1,
+PRIMARY KEY (`quest_id`, `item`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
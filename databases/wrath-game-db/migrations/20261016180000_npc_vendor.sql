@@ -0,0 +1,11 @@
+-- What vendor creatures sell. Items with a max_count are limited, every copy of the vendor in the world has
+-- its own stock, which refills restock_seconds after the first one was bought.
+CREATE TABLE `npc_vendor` (
+`entry` int(10) unsigned NOT NULL,
+`slot` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`item` int(10) unsigned NOT NULL,
+-- 0 is unlimited
+`max_count` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`restock_seconds` int(10) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`entry`, `item`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,11 @@
+-- The bosses of dungeons and raids. Pulling the boss starts its encounter and killing it saves everyone
+-- present to the instance; scripts registered for the encounter add the boss mechanics on top.
+CREATE TABLE `instance_encounter` (
+`id` int(10) unsigned NOT NULL,
+`map` int(10) unsigned NOT NULL,
+-- Bit in the instance's completed encounter mask, unique per map
+`encounter_index` tinyint(3) unsigned NOT NULL,
+`boss_entry` int(10) unsigned NOT NULL,
+PRIMARY KEY (`id`),
+UNIQUE KEY `idx_instance_encounter_map_index` (`map`, `encounter_index`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,14 @@
+-- Gameobjects placed in the world. They are registered with the systems that reason about them (gathering
+-- nodes, spell foci, quest objects) when the world is loaded.
+CREATE TABLE `gameobject` (
+`guid` int(10) unsigned NOT NULL,
+`entry` int(10) unsigned NOT NULL,
+`map` smallint(5) unsigned NOT NULL,
+`position_x` float NOT NULL,
+`position_y` float NOT NULL,
+`position_z` float NOT NULL,
+`orientation` float NOT NULL DEFAULT 0,
+`phase_mask` int(10) unsigned NOT NULL DEFAULT 1,
+PRIMARY KEY (`guid`),
+KEY `idx_map` (`map`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,7 @@
+-- Gameobjects the character has to use, like a goober that has to be clicked a number of times
+CREATE TABLE `quest_required_gameobject` (
+`quest_id` int(10) unsigned NOT NULL,
+`gameobject_entry` int(10) unsigned NOT NULL,
+`count` tinyint(3) unsigned NOT NULL DEFAULT 1,
+PRIMARY KEY (`quest_id`, `gameobject_entry`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
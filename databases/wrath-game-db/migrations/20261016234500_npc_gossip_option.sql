@@ -0,0 +1,11 @@
+-- Options in the gossip window of creatures with the gossip npc flag, listed by option_id.
+-- Picking an option with a poi_id puts that points_of_interest marker on the character's map.
+CREATE TABLE `npc_gossip_option` (
+`entry` int(10) unsigned NOT NULL,
+`option_id` int(10) unsigned NOT NULL,
+`icon` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`text` varchar(255) NOT NULL DEFAULT '',
+-- 0 for options without a map marker
+`poi_id` int(10) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`entry`, `option_id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,33 @@
+use anyhow::Result;
+
+#[derive(Debug)]
+pub struct DBCreatureLootItem {
+    pub entry: u32,
+    pub item: u32,
+    pub chance: f32,
+    pub min_count: u8,
+    pub max_count: u8,
+}
+
+#[derive(Debug)]
+pub struct DBCreatureLootMoney {
+    pub entry: u32,
+    pub min_money: u32,
+    pub max_money: u32,
+}
+
+impl super::GameDatabase {
+    pub async fn get_all_creature_loot_items(&self) -> Result<Vec<DBCreatureLootItem>> {
+        let res = sqlx::query_as!(DBCreatureLootItem, "SELECT * FROM creature_loot_template")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn get_all_creature_loot_money(&self) -> Result<Vec<DBCreatureLootMoney>> {
+        let res = sqlx::query_as!(DBCreatureLootMoney, "SELECT * FROM creature_loot_money")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+
+#[derive(Debug)]
+pub struct DBCreatureTemplate {
+    pub entry: u32,
+    pub name: String,
+    pub subname: String,
+    pub icon_name: String,
+    pub type_flags: u32,
+    pub creature_type: u32,
+    pub family: u32,
+    pub creature_rank: u32,
+    pub kill_credit1: u32,
+    pub kill_credit2: u32,
+    pub display_id1: u32,
+    pub display_id2: u32,
+    pub display_id3: u32,
+    pub display_id4: u32,
+    pub health_modifier: f32,
+    pub mana_modifier: f32,
+    pub racial_leader: u8,
+    pub movement_id: u32,
+    pub min_level: u8,
+    pub max_level: u8,
+    pub faction: u32,
+    pub npc_flags: u32,
+    pub unit_flags: u32,
+    pub base_health: u32,
+    pub scale: f32,
+}
+
+#[derive(Debug)]
+pub struct DBCreatureSpawn {
+    pub guid: u32,
+    pub entry: u32,
+    pub map: u16,
+    pub position_x: f32,
+    pub position_y: f32,
+    pub position_z: f32,
+    pub orientation: f32,
+    pub phase_mask: u32,
+    pub spawn_time_seconds: u32,
+}
+
+#[derive(Debug)]
+pub struct DBCreatureTemplateLocale {
+    pub entry: u32,
+    pub locale: String,
+    pub name: String,
+    pub subname: String,
+}
+
+impl super::GameDatabase {
+    pub async fn get_all_creature_templates(&self) -> Result<Vec<DBCreatureTemplate>> {
+        let res = sqlx::query_as!(DBCreatureTemplate, "SELECT * FROM creature_template")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn get_all_creature_spawns(&self) -> Result<Vec<DBCreatureSpawn>> {
+        let res = sqlx::query_as!(DBCreatureSpawn, "SELECT * FROM creature")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn get_all_creature_template_locales(&self) -> Result<Vec<DBCreatureTemplateLocale>> {
+        let res = sqlx::query_as!(
+            DBCreatureTemplateLocale,
+            "SELECT entry, locale, name, subname FROM creature_template_locale"
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+
+#[derive(Debug)]
+pub struct DBGameObjectTemplate {
+    pub entry: u32,
+    pub gameobject_type: u8,
+    pub display_id: u32,
+    pub name: String,
+    pub icon_name: String,
+    pub cast_bar_caption: String,
+    pub data0: u32,
+    pub data1: u32,
+    pub data2: u32,
+    pub data3: u32,
+    pub size: f32,
+}
+
+#[derive(Debug)]
+pub struct DBGameObjectSpawn {
+    pub guid: u32,
+    pub entry: u32,
+    pub map: u16,
+    pub position_x: f32,
+    pub position_y: f32,
+    pub position_z: f32,
+    pub orientation: f32,
+    pub phase_mask: u32,
+}
+
+#[derive(Debug)]
+pub struct DBGameObjectTemplateLocale {
+    pub entry: u32,
+    pub locale: String,
+    pub name: String,
+    pub cast_bar_caption: String,
+}
+
+impl super::GameDatabase {
+    pub async fn get_all_gameobject_templates(&self) -> Result<Vec<DBGameObjectTemplate>> {
+        let res = sqlx::query_as!(
+            DBGameObjectTemplate,
+            "SELECT entry, type AS gameobject_type, display_id, name, icon_name, cast_bar_caption, data0, data1, data2, data3, size FROM gameobject_template"
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn get_all_gameobject_spawns(&self) -> Result<Vec<DBGameObjectSpawn>> {
+        let res = sqlx::query_as!(DBGameObjectSpawn, "SELECT * FROM gameobject")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn get_all_gameobject_template_locales(&self) -> Result<Vec<DBGameObjectTemplateLocale>> {
+        let res = sqlx::query_as!(
+            DBGameObjectTemplateLocale,
+            "SELECT entry, locale, name, cast_bar_caption FROM gameobject_template_locale"
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+
+#[derive(Debug)]
+pub struct DBGatheringNodeTemplate {
+    pub entry: u32,
+    pub skill: u16,
+    pub required_skill_value: u16,
+    pub min_gathers: u8,
+    pub max_gathers: u8,
+    pub respawn_min_seconds: u32,
+    pub respawn_max_seconds: u32,
+    pub loot_item: u32,
+    pub loot_min_count: u8,
+    pub loot_max_count: u8,
+}
+
+impl super::GameDatabase {
+    pub async fn get_all_gathering_node_templates(&self) -> Result<Vec<DBGatheringNodeTemplate>> {
+        let res = sqlx::query_as!(DBGatheringNodeTemplate, "SELECT * FROM gathering_node_template")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+}
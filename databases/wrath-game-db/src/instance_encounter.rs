@@ -0,0 +1,18 @@
+use anyhow::Result;
+
+#[derive(Debug)]
+pub struct DBInstanceEncounter {
+    pub id: u32,
+    pub map: u32,
+    pub encounter_index: u8,
+    pub boss_entry: u32,
+}
+
+impl super::GameDatabase {
+    pub async fn get_all_instance_encounters(&self) -> Result<Vec<DBInstanceEncounter>> {
+        let res = sqlx::query_as!(DBInstanceEncounter, "SELECT id, map, encounter_index, boss_entry FROM instance_encounter")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+}
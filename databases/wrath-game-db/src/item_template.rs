@@ -144,7 +144,22 @@ pub struct DBItemTemplate {
     pub extra_flags: u8,
 }
 
+#[derive(Debug)]
+pub struct DBItemTemplateLocale {
+    pub entry: u32,
+    pub locale: String,
+    pub name: String,
+    pub description: String,
+}
+
 impl super::GameDatabase {
+    pub async fn get_all_item_template_locales(&self) -> Result<Vec<DBItemTemplateLocale>> {
+        let res = sqlx::query_as!(DBItemTemplateLocale, "SELECT entry, locale, name, description FROM item_template_locale")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+
     pub async fn get_item_template(&self, item_id: u32) -> Result<DBItemTemplate> {
         let res = sqlx::query!("SELECT * FROM item_template WHERE id = ?", item_id,)
             .fetch_one(&self.connection_pool)
@@ -3,13 +3,39 @@ use std::time::Duration;
 
 mod areatrigger_restedzone;
 mod areatrigger_teleport;
+mod creature_loot;
+mod creature_template;
+mod gameobject_template;
+mod gathering_node_template;
+mod instance_encounter;
 mod item_template;
+mod npc_gossip_option;
+mod npc_vendor;
 mod player_create_info;
+mod point_of_interest;
+mod quest_template;
+mod rare_spawn;
+mod server_string;
+mod table_update_time;
 
 pub use areatrigger_restedzone::DBAreaTriggerRestedZone;
 pub use areatrigger_teleport::DBAreaTriggerTeleport;
-pub use item_template::DBItemTemplate;
+pub use creature_loot::{DBCreatureLootItem, DBCreatureLootMoney};
+pub use creature_template::{DBCreatureSpawn, DBCreatureTemplate, DBCreatureTemplateLocale};
+pub use gameobject_template::{DBGameObjectSpawn, DBGameObjectTemplate, DBGameObjectTemplateLocale};
+pub use gathering_node_template::DBGatheringNodeTemplate;
+pub use instance_encounter::DBInstanceEncounter;
+pub use item_template::{DBItemTemplate, DBItemTemplateLocale};
+pub use npc_gossip_option::DBNpcGossipOption;
+pub use npc_vendor::DBNpcVendorItem;
 pub use player_create_info::DBPlayerCreateInfo;
+pub use point_of_interest::DBPointOfInterest;
+pub use quest_template::{
+    DBQuestRequiredGameObject, DBQuestRequiredItem, DBQuestRewardItem, DBQuestRewardReputation, DBQuestTemplate, DBQuestTemplateLocale,
+};
+pub use rare_spawn::DBRareSpawn;
+pub use server_string::DBServerString;
+pub use table_update_time::DBTableUpdateTime;
 
 pub struct GameDatabase {
     connection_pool: sqlx::MySqlPool,
@@ -25,4 +51,10 @@ impl GameDatabase {
 
         Ok(Self { connection_pool: pool })
     }
+
+    //Doesn't connect until the first query, for tests that never touch the database
+    pub fn new_lazy(conn_string: &str) -> Result<Self> {
+        let pool = sqlx::mysql::MySqlPoolOptions::new().max_connections(1).connect_lazy(conn_string)?;
+        Ok(Self { connection_pool: pool })
+    }
 }
@@ -0,0 +1,19 @@
+use anyhow::Result;
+
+#[derive(Clone, Debug)]
+pub struct DBNpcGossipOption {
+    pub entry: u32,
+    pub option_id: u32,
+    pub icon: u8,
+    pub text: String,
+    pub poi_id: u32,
+}
+
+impl super::GameDatabase {
+    pub async fn get_all_npc_gossip_options(&self) -> Result<Vec<DBNpcGossipOption>> {
+        let res = sqlx::query_as!(DBNpcGossipOption, "SELECT * FROM npc_gossip_option ORDER BY entry, option_id")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,19 @@
+use anyhow::Result;
+
+#[derive(Clone, Debug)]
+pub struct DBNpcVendorItem {
+    pub entry: u32,
+    pub slot: u8,
+    pub item: u32,
+    pub max_count: u8,
+    pub restock_seconds: u32,
+}
+
+impl super::GameDatabase {
+    pub async fn get_all_npc_vendor_items(&self) -> Result<Vec<DBNpcVendorItem>> {
+        let res = sqlx::query_as!(DBNpcVendorItem, "SELECT * FROM npc_vendor ORDER BY entry, slot")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 
+#[derive(Clone)]
 pub struct DBPlayerCreateInfo {
     pub race: u8,
     pub class: u8,
@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+#[derive(Debug)]
+pub struct DBPointOfInterest {
+    pub id: u32,
+    pub position_x: f32,
+    pub position_y: f32,
+    pub icon: u32,
+    pub flags: u32,
+    pub importance: u32,
+    pub name: String,
+}
+
+impl super::GameDatabase {
+    pub async fn get_all_points_of_interest(&self) -> Result<Vec<DBPointOfInterest>> {
+        let res = sqlx::query_as!(DBPointOfInterest, "SELECT * FROM points_of_interest")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+
+#[derive(Debug)]
+pub struct DBQuestTemplate {
+    pub id: u32,
+    pub min_level: u8,
+    pub quest_level: u8,
+    pub flags: u32,
+    pub suggested_players: u8,
+    pub title: String,
+    pub details: String,
+    pub objectives: String,
+    pub offer_reward_text: Option<String>,
+    pub reward_xp: u32,
+    pub reward_money: u32,
+    pub next_quest_id: Option<u32>,
+}
+
+#[derive(Debug)]
+pub struct DBQuestTemplateLocale {
+    pub title: String,
+    pub details: String,
+    pub objectives: String,
+}
+
+#[derive(Debug)]
+pub struct DBQuestRewardItem {
+    pub item: u32,
+    pub count: u8,
+    pub is_choice: bool,
+}
+
+#[derive(Debug)]
+pub struct DBQuestRewardReputation {
+    pub faction: u32,
+    pub value: i32,
+}
+
+#[derive(Debug)]
+pub struct DBQuestRequiredItem {
+    pub item: u32,
+    pub count: u8,
+}
+
+#[derive(Debug)]
+pub struct DBQuestRequiredGameObject {
+    pub gameobject_entry: u32,
+    pub count: u8,
+}
+
+impl super::GameDatabase {
+    pub async fn get_quest_template(&self, quest_id: u32) -> Result<Option<DBQuestTemplate>> {
+        let res = sqlx::query_as!(
+            DBQuestTemplate,
+            "SELECT id, min_level, quest_level, flags, suggested_players, title, details, objectives, offer_reward_text, reward_xp, reward_money, next_quest_id FROM quest_template WHERE id = ?",
+            quest_id
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn get_quest_template_locale(&self, quest_id: u32, locale: &str) -> Result<Option<DBQuestTemplateLocale>> {
+        let res = sqlx::query_as!(
+            DBQuestTemplateLocale,
+            "SELECT title, details, objectives FROM quest_template_locale WHERE id = ? AND locale = ?",
+            quest_id,
+            locale
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn get_quest_reward_items(&self, quest_id: u32) -> Result<Vec<DBQuestRewardItem>> {
+        let res = sqlx::query_as!(
+            DBQuestRewardItem,
+            "SELECT item, count, is_choice AS `is_choice: bool` FROM quest_reward_item WHERE quest_id = ?",
+            quest_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn get_quest_reward_reputations(&self, quest_id: u32) -> Result<Vec<DBQuestRewardReputation>> {
+        let res = sqlx::query_as!(
+            DBQuestRewardReputation,
+            "SELECT faction, value FROM quest_reward_reputation WHERE quest_id = ?",
+            quest_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn get_quest_required_items(&self, quest_id: u32) -> Result<Vec<DBQuestRequiredItem>> {
+        let res = sqlx::query_as!(
+            DBQuestRequiredItem,
+            "SELECT item, count FROM quest_required_item WHERE quest_id = ?",
+            quest_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn get_quest_required_gameobjects(&self, quest_id: u32) -> Result<Vec<DBQuestRequiredGameObject>> {
+        let res = sqlx::query_as!(
+            DBQuestRequiredGameObject,
+            "SELECT gameobject_entry, count FROM quest_required_gameobject WHERE quest_id = ?",
+            quest_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+}
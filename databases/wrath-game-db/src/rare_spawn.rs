@@ -0,0 +1,24 @@
+use anyhow::Result;
+
+#[derive(Debug)]
+pub struct DBRareSpawn {
+    pub id: u32,
+    pub creature_entry: u32,
+    pub map: u16,
+    pub position_x: f32,
+    pub position_y: f32,
+    pub position_z: f32,
+    pub orientation: f32,
+    pub respawn_min_seconds: u32,
+    pub respawn_max_seconds: u32,
+    pub spawn_emote: Option<String>,
+}
+
+impl super::GameDatabase {
+    pub async fn get_all_rare_spawns(&self) -> Result<Vec<DBRareSpawn>> {
+        let res = sqlx::query_as!(DBRareSpawn, "SELECT * FROM rare_spawn")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+}
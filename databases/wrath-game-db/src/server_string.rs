@@ -0,0 +1,17 @@
+use anyhow::Result;
+
+#[derive(Debug)]
+pub struct DBServerString {
+    pub entry: u32,
+    pub locale: String,
+    pub content: String,
+}
+
+impl super::GameDatabase {
+    pub async fn get_all_server_strings(&self) -> Result<Vec<DBServerString>> {
+        let res = sqlx::query_as!(DBServerString, "SELECT entry, locale, content FROM server_string")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+}
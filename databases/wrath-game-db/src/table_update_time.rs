@@ -0,0 +1,19 @@
+use anyhow::Result;
+
+pub struct DBTableUpdateTime {
+    pub table_name: String,
+    //Unix time of the last write, NULL for tables that weren't written to since the MySQL server started
+    pub update_time: Option<u64>,
+}
+
+impl super::GameDatabase {
+    pub async fn get_table_update_times(&self) -> Result<Vec<DBTableUpdateTime>> {
+        let res = sqlx::query_as!(
+            DBTableUpdateTime,
+            "SELECT TABLE_NAME AS table_name, CAST(UNIX_TIMESTAMP(UPDATE_TIME) AS UNSIGNED) AS update_time FROM information_schema.TABLES WHERE TABLE_SCHEMA = DATABASE()"
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+}
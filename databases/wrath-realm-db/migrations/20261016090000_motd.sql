@@ -0,0 +1,7 @@
+CREATE TABLE `motd` (
+`id` tinyint(3) unsigned NOT NULL DEFAULT '0',
+`message` text NOT NULL,
+PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+INSERT INTO `motd` (`id`, `message`) VALUES (0, 'Welcome to Wrath-rs!');
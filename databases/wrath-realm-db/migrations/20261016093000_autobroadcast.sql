@@ -0,0 +1,5 @@
+CREATE TABLE `autobroadcast` (
+`id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+`message` text NOT NULL,
+PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,17 @@
+-- Append-only log of economy events. There is deliberately no foreign key to `characters`,
+-- the trail has to outlive deleted characters.
+CREATE TABLE `character_audit` (
+`id` bigint(20) unsigned NOT NULL AUTO_INCREMENT,
+`time` bigint(20) unsigned NOT NULL DEFAULT '0',
+`account_id` int(10) unsigned NOT NULL DEFAULT '0',
+`character_id` int(10) unsigned NOT NULL DEFAULT '0',
+`event_type` tinyint(3) unsigned NOT NULL DEFAULT '0' COMMENT '0 item created, 1 item destroyed, 2 item traded, 3 item mailed, 4 money gained, 5 money spent',
+`item_id` int(10) unsigned NOT NULL DEFAULT '0',
+`item_count` int(10) unsigned NOT NULL DEFAULT '0',
+`money` int(10) unsigned NOT NULL DEFAULT '0' COMMENT 'Amount of copper gained or spent',
+`source_type` tinyint(3) unsigned NOT NULL DEFAULT '0' COMMENT '0 unknown, 1 loot, 2 GM command, 3 trade, 4 mail, 5 vendor, 6 quest, 7 player',
+`source_id` bigint(20) unsigned NOT NULL DEFAULT '0' COMMENT 'Trade partner or mail sender guid, creature entry, etc.',
+PRIMARY KEY (`id`),
+KEY `IDX_CHARACTER_AUDIT_CHARACTER` (`character_id`),
+KEY `IDX_CHARACTER_AUDIT_ACCOUNT` (`account_id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,14 @@
+CREATE TABLE `account_session_log` (
+`id` bigint(20) unsigned NOT NULL AUTO_INCREMENT,
+`account_id` int(10) unsigned NOT NULL DEFAULT '0',
+`character_id` int(10) unsigned NOT NULL DEFAULT '0',
+`character_name` varchar(25) NOT NULL DEFAULT '',
+`ip` varchar(45) NOT NULL DEFAULT '',
+`client_build` int(10) unsigned NOT NULL DEFAULT '0',
+`login_time` bigint(20) unsigned NOT NULL DEFAULT '0',
+`logout_time` bigint(20) unsigned DEFAULT NULL,
+`duration` int(10) unsigned DEFAULT NULL COMMENT 'Session length in seconds, NULL while the session is still open',
+PRIMARY KEY (`id`),
+KEY `IDX_ACCOUNT_SESSION_LOG_ACCOUNT` (`account_id`),
+KEY `IDX_ACCOUNT_SESSION_LOG_IP` (`ip`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,3 @@
+-- Translated versions of a message share its id, the enUS row is what clients without a translation get
+ALTER TABLE `motd` ADD COLUMN `locale` varchar(4) NOT NULL DEFAULT 'enUS' AFTER `id`, DROP PRIMARY KEY, ADD PRIMARY KEY (`id`, `locale`);
+ALTER TABLE `autobroadcast` ADD COLUMN `locale` varchar(4) NOT NULL DEFAULT 'enUS' AFTER `id`, DROP PRIMARY KEY, ADD PRIMARY KEY (`id`, `locale`);
@@ -0,0 +1,13 @@
+-- Player chat, only written to when CHAT_LOG_TARGET is set to database on the world server
+CREATE TABLE `chat_log` (
+`id` bigint(20) unsigned NOT NULL AUTO_INCREMENT,
+`time` bigint(20) unsigned NOT NULL,
+`chat_type` varchar(16) NOT NULL,
+`sender_id` int(10) unsigned NOT NULL,
+`sender_name` varchar(12) NOT NULL,
+-- Whisper target or channel name, NULL for chat that goes to everyone nearby
+`receiver` varchar(64) DEFAULT NULL,
+`message` text NOT NULL,
+PRIMARY KEY (`id`),
+KEY `idx_chat_log_sender` (`sender_id`, `time`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
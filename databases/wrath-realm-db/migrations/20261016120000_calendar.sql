@@ -0,0 +1,32 @@
+CREATE TABLE `calendar_event` (
+`id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+`creator_id` int(10) unsigned NOT NULL,
+-- Set for guild-wide events, every member of the guild can see and sign up for these
+`guild_id` int(10) unsigned DEFAULT NULL,
+`title` varchar(32) NOT NULL,
+`description` varchar(256) NOT NULL DEFAULT '',
+`event_type` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`dungeon_id` int(10) NOT NULL DEFAULT -1,
+-- Packed date as sent by the client (minute, hour, weekday, day, month, year bit fields)
+`event_time` int(10) unsigned NOT NULL,
+`flags` int(10) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`id`),
+KEY `idx_calendar_event_guild` (`guild_id`),
+CONSTRAINT `FK_CALENDAR_EVENT_CREATOR` FOREIGN KEY (`creator_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+CREATE TABLE `calendar_invite` (
+`id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+`event_id` int(10) unsigned NOT NULL,
+`invitee_id` int(10) unsigned NOT NULL,
+`sender_id` int(10) unsigned NOT NULL,
+-- 0 invited, 1 accepted, 2 declined, 3 confirmed, 4 out, 5 standby, 6 signed up, 7 not signed up, 8 tentative
+`status` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`moderator_rank` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`invite_time` bigint(20) unsigned NOT NULL,
+PRIMARY KEY (`id`),
+UNIQUE KEY `idx_calendar_invite_event_invitee` (`event_id`, `invitee_id`),
+KEY `idx_calendar_invite_invitee` (`invitee_id`, `status`),
+CONSTRAINT `FK_CALENDAR_INVITE_EVENT` FOREIGN KEY (`event_id`) REFERENCES `calendar_event` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT,
+CONSTRAINT `FK_CALENDAR_INVITE_INVITEE` FOREIGN KEY (`invitee_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
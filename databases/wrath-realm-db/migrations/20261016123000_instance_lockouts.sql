@@ -0,0 +1,19 @@
+CREATE TABLE `instance` (
+`id` int(10) unsigned NOT NULL,
+`map_id` int(10) unsigned NOT NULL,
+`difficulty` tinyint(3) unsigned NOT NULL DEFAULT 0,
+-- Bit per encounter index, set once the boss has been killed in this instance
+`completed_encounters` int(10) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+CREATE TABLE `character_instance_bind` (
+`character_id` int(10) unsigned NOT NULL,
+`map_id` int(10) unsigned NOT NULL,
+`difficulty` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`instance_id` int(10) unsigned NOT NULL,
+PRIMARY KEY (`character_id`, `map_id`, `difficulty`),
+KEY `idx_character_instance_bind_instance` (`instance_id`),
+CONSTRAINT `FK_CHARACTER_INSTANCE_BIND_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT,
+CONSTRAINT `FK_CHARACTER_INSTANCE_BIND_INSTANCE` FOREIGN KEY (`instance_id`) REFERENCES `instance` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,6 @@
+-- Next respawn time of the game database 'rare_spawn' entries, rows are written when the creature is killed
+CREATE TABLE `rare_spawn_respawn` (
+`spawn_id` int(10) unsigned NOT NULL,
+`respawn_time` bigint(20) unsigned NOT NULL,
+PRIMARY KEY (`spawn_id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
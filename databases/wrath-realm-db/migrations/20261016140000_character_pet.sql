@@ -0,0 +1,32 @@
+CREATE TABLE `character_pet` (
+`id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+`owner_id` int(10) unsigned NOT NULL,
+`entry` int(10) unsigned NOT NULL,
+-- 0 = summoned (warlock, death knight), 1 = hunter
+`pet_type` tinyint(3) unsigned NOT NULL DEFAULT 0,
+`name` varchar(21) NOT NULL,
+`level` tinyint(3) unsigned NOT NULL DEFAULT 1,
+-- Hunter pets only, 0 - 1050000 like the client expects in the power field
+`happiness` int(10) unsigned NOT NULL DEFAULT 0,
+-- 0 = the pet that is summoned with Call Pet, stable slots come after it
+`slot` tinyint(3) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`id`),
+KEY `idx_character_pet_owner` (`owner_id`),
+CONSTRAINT `FK_CHARACTER_PET_OWNER` FOREIGN KEY (`owner_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+CREATE TABLE `character_pet_talent` (
+`pet_id` int(10) unsigned NOT NULL,
+`talent_id` int(10) unsigned NOT NULL,
+`rank` tinyint(3) unsigned NOT NULL,
+PRIMARY KEY (`pet_id`, `talent_id`),
+CONSTRAINT `FK_CHARACTER_PET_TALENT_PET` FOREIGN KEY (`pet_id`) REFERENCES `character_pet` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+CREATE TABLE `character_pet_spell` (
+`pet_id` int(10) unsigned NOT NULL,
+`spell_id` int(10) unsigned NOT NULL,
+`autocast` tinyint(3) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`pet_id`, `spell_id`),
+CONSTRAINT `FK_CHARACTER_PET_SPELL_PET` FOREIGN KEY (`pet_id`) REFERENCES `character_pet` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,20 @@
+-- The id doubles as the set guid the client uses to refer to the set
+CREATE TABLE `character_equipmentsets` (
+`id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+`character_id` int(10) unsigned NOT NULL,
+`set_index` tinyint(3) unsigned NOT NULL,
+`name` varchar(31) NOT NULL,
+`icon_name` varchar(100) NOT NULL,
+PRIMARY KEY (`id`),
+UNIQUE KEY `idx_character_equipmentsets_index` (`character_id`, `set_index`),
+CONSTRAINT `FK_CHARACTER_EQUIPMENTSETS_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- Items are stored by entry, item guids are derived from the slot they're in and change when the item moves
+CREATE TABLE `character_equipmentset_items` (
+`set_id` int(10) unsigned NOT NULL,
+`slot_id` tinyint(3) unsigned NOT NULL,
+`item` int(10) unsigned NOT NULL,
+PRIMARY KEY (`set_id`, `slot_id`),
+CONSTRAINT `FK_CHARACTER_EQUIPMENTSET_ITEMS_SET` FOREIGN KEY (`set_id`) REFERENCES `character_equipmentsets` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
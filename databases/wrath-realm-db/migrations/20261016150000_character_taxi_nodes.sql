@@ -0,0 +1,7 @@
+-- One row per flight path node the character has discovered, loaded into the known taxi nodes mask on login
+CREATE TABLE `character_taxi_nodes` (
+`character_id` int(10) unsigned NOT NULL,
+`node_id` int(10) unsigned NOT NULL,
+PRIMARY KEY (`character_id`, `node_id`),
+CONSTRAINT `FK_CHARACTER_TAXI_NODES_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
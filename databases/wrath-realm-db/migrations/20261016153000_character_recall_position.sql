@@ -0,0 +1,12 @@
+-- Where the character was before its last GM teleport, used by .recall
+CREATE TABLE `character_recall_position` (
+`character_id` int(10) unsigned NOT NULL,
+`map` smallint(5) unsigned NOT NULL,
+`zone` smallint(5) unsigned NOT NULL,
+`position_x` float NOT NULL,
+`position_y` float NOT NULL,
+`position_z` float NOT NULL,
+`orientation` float NOT NULL,
+PRIMARY KEY (`character_id`),
+CONSTRAINT `FK_CHARACTER_RECALL_POSITION_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
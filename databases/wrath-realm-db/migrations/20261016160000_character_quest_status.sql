@@ -0,0 +1,8 @@
+-- Quests a character has taken or finished. Status 0 is in the quest log, 1 is rewarded.
+CREATE TABLE `character_quest_status` (
+`character_id` int(10) unsigned NOT NULL,
+`quest_id` int(10) unsigned NOT NULL,
+`status` tinyint(3) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`character_id`, `quest_id`),
+CONSTRAINT `FK_CHARACTER_QUEST_STATUS_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,14 @@
+-- Intent records for item moves that span several writes (trades, mail with items, auction house sales).
+-- A row is written before the item leaves its owner and deleted in the same transaction that delivers it,
+-- so any row left over at startup belongs to an operation that was cut short by a crash.
+CREATE TABLE `item_journal` (
+`id` bigint(20) unsigned NOT NULL AUTO_INCREMENT,
+`operation` tinyint(3) unsigned NOT NULL DEFAULT '0' COMMENT '0 trade, 1 mail, 2 auction house sale',
+`stage` tinyint(3) unsigned NOT NULL DEFAULT '0' COMMENT '0 intent recorded, 1 item taken from its owner',
+`character_id` int(10) unsigned NOT NULL DEFAULT '0',
+`slot_id` tinyint(3) unsigned NOT NULL DEFAULT '0',
+`item_id` int(10) unsigned NOT NULL DEFAULT '0',
+`enchant` int(10) unsigned DEFAULT NULL,
+`created_at` bigint(20) unsigned NOT NULL DEFAULT '0',
+PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
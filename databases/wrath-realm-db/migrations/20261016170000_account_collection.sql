@@ -0,0 +1,9 @@
+-- Things every character of an account shares once one of them has it: vanity pets and mounts by the spell
+-- that summons them, and achievements flagged as account wide
+CREATE TABLE `account_collection` (
+`account_id` int(10) unsigned NOT NULL,
+`kind` tinyint(3) unsigned NOT NULL COMMENT '0 vanity pet, 1 mount, 2 account wide achievement',
+`entry` int(10) unsigned NOT NULL COMMENT 'Spell id for pets and mounts, achievement id for achievements',
+`added_at` bigint(20) unsigned NOT NULL DEFAULT '0',
+PRIMARY KEY (`account_id`, `kind`, `entry`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
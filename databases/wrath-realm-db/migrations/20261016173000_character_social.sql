@@ -0,0 +1,11 @@
+-- Friends and ignored players of a character, one row per other character with the relation flags
+CREATE TABLE `character_social` (
+`character_id` int(10) unsigned NOT NULL,
+`friend_id` int(10) unsigned NOT NULL,
+`flags` tinyint(3) unsigned NOT NULL DEFAULT '0' COMMENT '1 friend, 2 ignored',
+`note` varchar(48) NOT NULL DEFAULT '',
+PRIMARY KEY (`character_id`, `friend_id`),
+KEY `IDX_CHARACTER_SOCIAL_FRIEND` (`friend_id`),
+CONSTRAINT `FK_CHARACTER_SOCIAL_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT,
+CONSTRAINT `FK_CHARACTER_SOCIAL_FRIEND` FOREIGN KEY (`friend_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
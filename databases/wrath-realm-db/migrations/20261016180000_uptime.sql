@@ -0,0 +1,9 @@
+-- One row per world server run. The server writes its uptime and the most players that were online at the same
+-- time every few minutes, so a crash loses at most one interval.
+CREATE TABLE `uptime` (
+`start_time` bigint(20) unsigned NOT NULL,
+`uptime` bigint(20) unsigned NOT NULL DEFAULT '0',
+`max_players` int(10) unsigned NOT NULL DEFAULT '0',
+`revision` varchar(255) NOT NULL DEFAULT '',
+PRIMARY KEY (`start_time`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,8 @@
+-- Reputation a character earned with a faction from Faction.dbc, on top of the faction's base reputation
+CREATE TABLE `character_reputation` (
+`character_id` int(10) unsigned NOT NULL,
+`faction` int(10) unsigned NOT NULL,
+`standing` int(11) NOT NULL DEFAULT 0,
+PRIMARY KEY (`character_id`, `faction`),
+CONSTRAINT `FK_CHARACTER_REPUTATION_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
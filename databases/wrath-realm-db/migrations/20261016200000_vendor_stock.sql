@@ -0,0 +1,10 @@
+-- Limited vendor items that were bought from a vendor spawn and haven't restocked yet. A row is removed once
+-- the vendor has its full stock again.
+CREATE TABLE `vendor_stock` (
+`spawn_guid` int(10) unsigned NOT NULL,
+`item` int(10) unsigned NOT NULL,
+`count` tinyint(3) unsigned NOT NULL,
+-- Unix time the stock refills
+`restock_time` bigint(20) unsigned NOT NULL,
+PRIMARY KEY (`spawn_guid`, `item`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
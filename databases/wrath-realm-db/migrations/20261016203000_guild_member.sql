@@ -0,0 +1,12 @@
+-- A character's membership in a guild. characters.guild_id stays what the world server reads on login,
+-- this row keeps what only matters to guild members.
+-- notifications is a bitmask of what the member is told about others in the guild:
+-- 1 members logging in and out, 2 members levelling up, 4 members earning achievements
+CREATE TABLE `guild_member` (
+`character_id` int(10) unsigned NOT NULL,
+`guild_id` int(10) unsigned NOT NULL,
+`notifications` tinyint(3) unsigned NOT NULL DEFAULT 7,
+PRIMARY KEY (`character_id`),
+KEY `idx_guild_member_guild` (`guild_id`),
+CONSTRAINT `FK_GUILD_MEMBER_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
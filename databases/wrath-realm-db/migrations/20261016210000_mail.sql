@@ -0,0 +1,30 @@
+CREATE TABLE `mail` (
+`id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+-- No foreign key, mail from a deleted character still arrives
+`sender_id` int(10) unsigned NOT NULL,
+`receiver_id` int(10) unsigned NOT NULL,
+`subject` varchar(128) NOT NULL,
+`body` text NOT NULL,
+`money` int(10) unsigned NOT NULL DEFAULT 0,
+-- What the receiver pays the sender before taking the attached items, 0 when it isn't cash on delivery
+`cash_on_delivery` int(10) unsigned NOT NULL DEFAULT 0,
+-- Unix time
+`expire_time` bigint(20) unsigned NOT NULL,
+-- 1 read, 2 returned to the sender
+`flags` tinyint(3) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`id`),
+KEY `idx_mail_receiver` (`receiver_id`),
+KEY `idx_mail_expire_time` (`expire_time`),
+CONSTRAINT `FK_MAIL_RECEIVER` FOREIGN KEY (`receiver_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- Items attached to a mail, they leave the sender's inventory when the mail is sent
+CREATE TABLE `mail_item` (
+`id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+`mail_id` int(10) unsigned NOT NULL,
+`item` int(10) unsigned NOT NULL,
+`enchant` int(10) unsigned DEFAULT NULL,
+PRIMARY KEY (`id`),
+KEY `idx_mail_item_mail` (`mail_id`),
+CONSTRAINT `FK_MAIL_ITEM_MAIL` FOREIGN KEY (`mail_id`) REFERENCES `mail` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
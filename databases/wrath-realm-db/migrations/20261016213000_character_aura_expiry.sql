@@ -0,0 +1,9 @@
+-- Auras that have to keep running while the character is logged out, like the deserter debuff.
+-- expire_time is a unix time, rows that are past it are dropped when the character logs in.
+CREATE TABLE `character_aura_expiry` (
+`character_id` int(10) unsigned NOT NULL,
+`spell_id` int(10) unsigned NOT NULL,
+`expire_time` bigint(20) unsigned NOT NULL,
+PRIMARY KEY (`character_id`, `spell_id`),
+CONSTRAINT `FK_CHARACTER_AURA_EXPIRY_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,29 @@
+CREATE TABLE `guild` (
+`id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+`name` varchar(24) NOT NULL,
+`leader_id` int(10) unsigned NOT NULL,
+`motd` varchar(128) NOT NULL DEFAULT '',
+`created_time` bigint(20) unsigned NOT NULL,
+PRIMARY KEY (`id`),
+UNIQUE KEY `idx_guild_name` (`name`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- Rank 0 is the guild master, higher ranks have fewer rights
+CREATE TABLE `guild_rank` (
+`guild_id` int(10) unsigned NOT NULL,
+`rank_id` tinyint(3) unsigned NOT NULL,
+`name` varchar(16) NOT NULL,
+-- Bitmask: 0x1 listen to guild chat, 0x2 speak in guild chat, 0x4 listen to officer chat, 0x8 speak in officer chat,
+-- 0x10 invite, 0x20 remove members, 0x1000 set the message of the day
+`rights` int(10) unsigned NOT NULL,
+PRIMARY KEY (`guild_id`, `rank_id`),
+CONSTRAINT `FK_GUILD_RANK_GUILD` FOREIGN KEY (`guild_id`) REFERENCES `guild` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- Nobody could found a guild before this, so any guild a character claims to be in doesn't exist
+UPDATE `characters` SET `guild_id` = 0;
+DELETE FROM `guild_member`;
+
+ALTER TABLE `guild_member`
+ADD `rank_id` tinyint(3) unsigned NOT NULL AFTER `guild_id`,
+ADD CONSTRAINT `FK_GUILD_MEMBER_GUILD` FOREIGN KEY (`guild_id`) REFERENCES `guild` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT;
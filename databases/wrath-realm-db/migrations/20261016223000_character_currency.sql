@@ -0,0 +1,9 @@
+-- Honor, arena points and the tokens shown in the currency tab (emblems, marks, shards).
+-- currency is the item id the client knows the currency by, 43308 for honor and 43307 for arena points.
+CREATE TABLE `character_currency` (
+`character_id` int(10) unsigned NOT NULL,
+`currency` int(10) unsigned NOT NULL,
+`count` int(10) unsigned NOT NULL,
+PRIMARY KEY (`character_id`, `currency`),
+CONSTRAINT `FK_CHARACTER_CURRENCY_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
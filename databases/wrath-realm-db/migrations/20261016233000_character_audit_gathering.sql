@@ -0,0 +1,3 @@
+-- Items gathered from mining veins and herbs, source_id is the gameobject entry
+ALTER TABLE `character_audit`
+MODIFY COLUMN `source_type` tinyint(3) unsigned NOT NULL DEFAULT '0' COMMENT '0 unknown, 1 loot, 2 GM command, 3 trade, 4 mail, 5 vendor, 6 quest, 7 player, 8 gathering';
@@ -0,0 +1,10 @@
+-- How far a character got with the objectives of the quests in its quest log. objective_entry is what was
+-- counted, the gameobject entry for used gameobjects.
+CREATE TABLE `character_quest_objective` (
+`character_id` int(10) unsigned NOT NULL,
+`quest_id` int(10) unsigned NOT NULL,
+`objective_entry` int(10) unsigned NOT NULL,
+`count` tinyint(3) unsigned NOT NULL DEFAULT 0,
+PRIMARY KEY (`character_id`, `quest_id`, `objective_entry`),
+CONSTRAINT `FK_CHARACTER_QUEST_OBJECTIVE_CHARACTER` FOREIGN KEY (`character_id`) REFERENCES `characters` (`id`) ON DELETE CASCADE ON UPDATE RESTRICT
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,4 @@
+-- GM teleports and bans go into the trail too, target_id is the destination map or the banned account
+ALTER TABLE `character_audit`
+MODIFY COLUMN `event_type` tinyint(3) unsigned NOT NULL DEFAULT '0' COMMENT '0 item created, 1 item destroyed, 2 item traded, 3 item mailed, 4 money gained, 5 money spent, 6 teleported, 7 account banned, 8 account unbanned',
+ADD COLUMN `target_id` int(10) unsigned NOT NULL DEFAULT '0' COMMENT 'Map of a teleport, account of a ban' AFTER `money`;
@@ -0,0 +1,34 @@
+use anyhow::Result;
+
+pub struct DBAccountCollectionEntry {
+    pub kind: u8,
+    pub entry: u32,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_account_collection(&self, account_id: u32) -> Result<Vec<DBAccountCollectionEntry>> {
+        let res = sqlx::query_as!(
+            DBAccountCollectionEntry,
+            "SELECT kind, entry FROM account_collection WHERE account_id = ?",
+            account_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn add_account_collection_entry(&self, account_id: u32, kind: u8, entry: u32, added_at: u64) -> Result<()> {
+        sqlx::query!(
+            "INSERT IGNORE INTO account_collection (account_id, kind, entry, added_at) VALUES (?, ?, ?, ?)",
+            account_id,
+            kind,
+            entry,
+            added_at
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(())
+    }
+}
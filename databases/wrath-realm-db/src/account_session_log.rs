@@ -0,0 +1,78 @@
+use anyhow::Result;
+
+pub struct DBAccountSessionLog {
+    pub id: u64,
+    pub account_id: u32,
+    pub character_id: u32,
+    pub character_name: String,
+    pub ip: String,
+    pub client_build: u32,
+    pub login_time: u64,
+    pub logout_time: Option<u64>,
+    pub duration: Option<u32>,
+}
+
+pub struct DBAccountSessionStart {
+    pub account_id: u32,
+    pub character_id: u32,
+    pub character_name: String,
+    pub ip: String,
+    pub client_build: u32,
+    pub login_time: u64,
+}
+
+impl super::RealmDatabase {
+    pub async fn start_account_session(&self, session: &DBAccountSessionStart) -> Result<u64> {
+        let result = sqlx::query!(
+            "INSERT INTO account_session_log (account_id, character_id, character_name, ip, client_build, login_time) VALUES (?, ?, ?, ?, ?, ?)",
+            session.account_id,
+            session.character_id,
+            session.character_name,
+            session.ip,
+            session.client_build,
+            session.login_time
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(result.last_insert_id())
+    }
+
+    pub async fn end_account_session(&self, session_id: u64, logout_time: u64) -> Result<()> {
+        sqlx::query!(
+            "UPDATE account_session_log SET logout_time = ?, duration = ? - login_time WHERE id = ? AND logout_time IS NULL",
+            logout_time,
+            logout_time,
+            session_id
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_recent_account_sessions(&self, account_id: u32, limit: u32) -> Result<Vec<DBAccountSessionLog>> {
+        let res = sqlx::query_as!(
+            DBAccountSessionLog,
+            "SELECT id, account_id, character_id, character_name, ip, client_build, login_time, logout_time, duration FROM account_session_log WHERE account_id = ? ORDER BY id DESC LIMIT ?",
+            account_id,
+            limit
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    //Other accounts that have logged in from any IP this account has used
+    pub async fn get_accounts_sharing_ip(&self, account_id: u32) -> Result<Vec<u32>> {
+        let res = sqlx::query!(
+            "SELECT DISTINCT account_id FROM account_session_log WHERE ip IN (SELECT ip FROM account_session_log WHERE account_id = ?) AND account_id <> ?",
+            account_id,
+            account_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res.into_iter().map(|row| row.account_id).collect())
+    }
+}
@@ -0,0 +1,45 @@
+//! Read only queries for armory websites. They return what a character shows to other players anyway, nothing
+//! about accounts, positions or inventories makes it out of here.
+
+use anyhow::Result;
+
+pub struct DBArmoryCharacter {
+    pub id: u32,
+    pub name: String,
+    pub race: u8,
+    pub class: u8,
+    pub gender: u8,
+    pub level: u8,
+}
+
+pub struct DBArmoryItem {
+    pub slot_id: u8,
+    pub item: Option<u32>,
+    pub enchant: Option<u32>,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_armory_character(&self, name: &str) -> Result<Option<DBArmoryCharacter>> {
+        let res = sqlx::query_as!(
+            DBArmoryCharacter,
+            "SELECT id, name, race, class, gender, level FROM characters WHERE name = ?",
+            name
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn get_armory_equipment(&self, character_id: u32) -> Result<Vec<DBArmoryItem>> {
+        let res = sqlx::query_as!(
+            DBArmoryItem,
+            "SELECT slot_id, item, enchant FROM character_equipment WHERE character_id = ? AND item IS NOT NULL ORDER BY slot_id",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+
+pub struct DBCharacterAuraExpiry {
+    pub spell_id: u32,
+    pub expire_time: u64,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_character_aura_expiries(&self, character_id: u32) -> Result<Vec<DBCharacterAuraExpiry>> {
+        let res = sqlx::query_as!(
+            DBCharacterAuraExpiry,
+            "SELECT spell_id, expire_time FROM character_aura_expiry WHERE character_id = ?",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn set_character_aura_expiry(&self, character_id: u32, spell_id: u32, expire_time: u64) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO character_aura_expiry (character_id, spell_id, expire_time) VALUES (?, ?, ?) ON DUPLICATE KEY UPDATE expire_time = VALUES(expire_time)",
+            character_id,
+            spell_id,
+            expire_time
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_character_aura_expiry(&self, character_id: u32, spell_id: u32) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM character_aura_expiry WHERE character_id = ? AND spell_id = ?",
+            character_id,
+            spell_id
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use anyhow::Result;
+
+pub struct DBAutoBroadcast {
+    pub id: u32,
+    pub locale: String,
+    pub message: String,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_autobroadcasts(&self) -> Result<Vec<DBAutoBroadcast>> {
+        let res = sqlx::query_as!(DBAutoBroadcast, "SELECT id, locale, message FROM autobroadcast ORDER BY id")
+            .fetch_all(&self.connection_pool)
+            .await?;
+
+        Ok(res)
+    }
+}
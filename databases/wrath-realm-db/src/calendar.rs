@@ -0,0 +1,185 @@
+use anyhow::Result;
+
+pub const CALENDAR_INVITE_STATUS_INVITED: u8 = 0;
+
+pub struct DBCalendarEvent {
+    pub id: u32,
+    pub creator_id: u32,
+    pub guild_id: Option<u32>,
+    pub title: String,
+    pub description: String,
+    pub event_type: u8,
+    pub dungeon_id: i32,
+    pub event_time: u32,
+    pub flags: u32,
+}
+
+pub struct DBCalendarEventCreateParameters {
+    pub creator_id: u32,
+    pub guild_id: Option<u32>,
+    pub title: String,
+    pub description: String,
+    pub event_type: u8,
+    pub dungeon_id: i32,
+    pub event_time: u32,
+    pub flags: u32,
+}
+
+pub struct DBCalendarInvite {
+    pub invitee_id: u32,
+    pub status: u8,
+    pub moderator_rank: u8,
+}
+
+//An invite as it's stored, with the id the client uses to refer to it
+pub struct DBCalendarEventInvite {
+    pub id: u32,
+    pub event_id: u32,
+    pub invitee_id: u32,
+    pub status: u8,
+    pub moderator_rank: u8,
+    pub invite_time: u64,
+}
+
+impl super::RealmDatabase {
+    //The event and its initial invite list are written together, a half created event would show up without its invitees
+    pub async fn create_calendar_event(
+        &self,
+        params: &DBCalendarEventCreateParameters,
+        invites: &[DBCalendarInvite],
+        invite_time: u64,
+    ) -> Result<u32> {
+        let mut transaction = self.begin_transaction().await?;
+        let res = sqlx::query!(
+            "INSERT INTO calendar_event (creator_id, guild_id, title, description, event_type, dungeon_id, event_time, flags) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params.creator_id,
+            params.guild_id,
+            params.title,
+            params.description,
+            params.event_type,
+            params.dungeon_id,
+            params.event_time,
+            params.flags
+        )
+        .execute(&mut *transaction)
+        .await?;
+        let event_id = res.last_insert_id() as u32;
+
+        for invite in invites {
+            sqlx::query!(
+                "INSERT INTO calendar_invite (event_id, invitee_id, sender_id, status, moderator_rank, invite_time) VALUES (?, ?, ?, ?, ?, ?)",
+                event_id,
+                invite.invitee_id,
+                params.creator_id,
+                invite.status,
+                invite.moderator_rank,
+                invite_time
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(event_id)
+    }
+
+    pub async fn get_calendar_event(&self, event_id: u32) -> Result<Option<DBCalendarEvent>> {
+        let res = sqlx::query_as!(
+            DBCalendarEvent,
+            "SELECT id, creator_id, guild_id, title, description, event_type, dungeon_id, event_time, flags FROM calendar_event WHERE id = ?",
+            event_id
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    //Events the character was invited to, plus every event of their guild
+    pub async fn get_calendar_events_for_character(&self, character_id: u32, guild_id: Option<u32>) -> Result<Vec<DBCalendarEvent>> {
+        let res = sqlx::query_as!(
+            DBCalendarEvent,
+            "SELECT id, creator_id, guild_id, title, description, event_type, dungeon_id, event_time, flags FROM calendar_event WHERE id IN (SELECT event_id FROM calendar_invite WHERE invitee_id = ?) OR guild_id = ? ORDER BY event_time",
+            character_id,
+            guild_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn get_calendar_event_invites(&self, event_id: u32) -> Result<Vec<DBCalendarEventInvite>> {
+        let res = sqlx::query_as!(
+            DBCalendarEventInvite,
+            "SELECT id, event_id, invitee_id, status, moderator_rank, invite_time FROM calendar_invite WHERE event_id = ?",
+            event_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn get_calendar_invites_for_character(&self, character_id: u32) -> Result<Vec<DBCalendarEventInvite>> {
+        let res = sqlx::query_as!(
+            DBCalendarEventInvite,
+            "SELECT id, event_id, invitee_id, status, moderator_rank, invite_time FROM calendar_invite WHERE invitee_id = ?",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    //Returns false if the character was already invited to the event
+    pub async fn add_calendar_invite(
+        &self,
+        event_id: u32,
+        invitee_id: u32,
+        sender_id: u32,
+        invite: &DBCalendarInvite,
+        invite_time: u64,
+    ) -> Result<bool> {
+        let res = sqlx::query!(
+            "INSERT IGNORE INTO calendar_invite (event_id, invitee_id, sender_id, status, moderator_rank, invite_time) VALUES (?, ?, ?, ?, ?, ?)",
+            event_id,
+            invitee_id,
+            sender_id,
+            invite.status,
+            invite.moderator_rank,
+            invite_time
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    //Returns false if the character has no invite for the event
+    pub async fn set_calendar_invite_status(&self, event_id: u32, invitee_id: u32, status: u8) -> Result<bool> {
+        let res = sqlx::query!(
+            "UPDATE calendar_invite SET status = ? WHERE event_id = ? AND invitee_id = ?",
+            status,
+            event_id,
+            invitee_id
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn get_num_pending_calendar_invites(&self, character_id: u32) -> Result<u32> {
+        let res = sqlx::query!(
+            "SELECT count(*) AS cnt FROM calendar_invite WHERE invitee_id = ? AND status = ?",
+            character_id,
+            CALENDAR_INVITE_STATUS_INVITED
+        )
+        .fetch_one(&self.connection_pool)
+        .await?;
+
+        Ok(res.cnt as u32)
+    }
+}
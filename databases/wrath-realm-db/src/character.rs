@@ -33,6 +33,15 @@ pub struct DBCharacter {
     pub playtime_level: u32,
 }
 
+pub struct DBCharacterInfo {
+    pub id: u32,
+    pub name: String,
+    pub race: u8,
+    pub class: u8,
+    pub gender: u8,
+    pub level: u8,
+}
+
 pub struct DBCharacterCreateParameters {
     pub account_id: u32,
     pub name: String,
@@ -97,6 +106,22 @@ impl super::RealmDatabase {
         }
     }
 
+    pub async fn get_account_id_for_character_name(&self, name: &str) -> Result<Option<u32>> {
+        let res = sqlx::query!("SELECT account_id FROM characters WHERE name = ?", name)
+            .fetch_optional(&self.connection_pool)
+            .await?;
+
+        Ok(res.map(|row| row.account_id))
+    }
+
+    pub async fn get_character_id_for_character_name(&self, name: &str) -> Result<Option<u32>> {
+        let res = sqlx::query!("SELECT id FROM characters WHERE name = ?", name)
+            .fetch_optional(&self.connection_pool)
+            .await?;
+
+        Ok(res.map(|row| row.id))
+    }
+
     pub async fn create_character(&self, params: &DBCharacterCreateParameters) -> Result<u64> {
         let empty_tutorial_data: Vec<u8> = Vec::new();
 
@@ -137,17 +162,49 @@ impl super::RealmDatabase {
         Ok(res)
     }
 
+    pub async fn get_all_character_infos(&self) -> Result<Vec<DBCharacterInfo>> {
+        let res = sqlx::query_as!(DBCharacterInfo, "SELECT id, name, race, class, gender, level FROM characters")
+            .fetch_all(&self.connection_pool)
+            .await?;
+
+        Ok(res)
+    }
+
+    pub async fn get_character_info(&self, character_id: u32) -> Result<Option<DBCharacterInfo>> {
+        let res = sqlx::query_as!(
+            DBCharacterInfo,
+            "SELECT id, name, race, class, gender, level FROM characters WHERE id = ?",
+            character_id
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    //Rows that belong to the character go with it: tables keyed on the character cascade on its delete,
+    //the ones without a foreign key are cleaned up here in the same transaction
     pub async fn delete_character(&self, character_id: u32, account_id: u32) -> Result<bool> {
+        let mut transaction = self.begin_transaction().await?;
         let res = sqlx::query_as!(
             DBCharacter,
             "DELETE FROM characters WHERE id = ? AND account_id = ?",
             character_id,
             account_id
         )
-        .execute(&self.connection_pool)
+        .execute(&mut *transaction)
         .await?;
+        if res.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        //Interrupted item moves would otherwise be given back to a character that no longer exists
+        sqlx::query!("DELETE FROM item_journal WHERE character_id = ?", character_id)
+            .execute(&mut *transaction)
+            .await?;
 
-        Ok(res.rows_affected() > 0)
+        transaction.commit().await?;
+        Ok(true)
     }
 
     pub async fn update_character_position(&self, update: &DBCharacterUpdate) -> Result<()> {
@@ -0,0 +1,35 @@
+use anyhow::Result;
+
+pub struct DBCharacterAuditEntry {
+    pub time: u64,
+    pub account_id: u32,
+    pub character_id: u32,
+    pub event_type: u8,
+    pub item_id: u32,
+    pub item_count: u32,
+    pub money: u32,
+    pub target_id: u32,
+    pub source_type: u8,
+    pub source_id: u64,
+}
+
+impl super::RealmDatabase {
+    pub async fn insert_character_audit_entry(&self, entry: &DBCharacterAuditEntry) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO character_audit (time, account_id, character_id, event_type, item_id, item_count, money, target_id, source_type, source_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            entry.time,
+            entry.account_id,
+            entry.character_id,
+            entry.event_type,
+            entry.item_id,
+            entry.item_count,
+            entry.money,
+            entry.target_id,
+            entry.source_type,
+            entry.source_id
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+}
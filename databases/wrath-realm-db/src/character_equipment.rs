@@ -48,35 +48,30 @@ impl super::RealmDatabase {
         Ok(result)
     }
 
-    pub async fn give_character_start_equipment(
-        &self,
-        character_id: u32,
-        item_ids: [i32; 24],
-        slot_ids: impl IntoIterator<Item = i32> + Clone,
-    ) -> Result<()> {
+    //Items are (slot_id, item_id) pairs, slots can be equipment slots as well as backpack slots
+    pub async fn give_character_start_equipment(&self, character_id: u32, items: impl IntoIterator<Item = (u8, u32)>) -> Result<()> {
         #[cfg(debug_assertions)]
         {
             //Cannot already have starting equipment
             assert_eq!(self.get_all_character_equipment(character_id).await?.len(), 0);
-            assert_eq!(slot_ids.clone().into_iter().count(), 24);
+        }
+
+        let mut insert_iter = items
+            .into_iter()
+            .map(|(slot_id, item)| DBItemInstance {
+                character_id,
+                slot_id,
+                item: Some(item),
+                enchant: None,
+            })
+            .peekable();
+        if insert_iter.peek().is_none() {
+            return Ok(());
         }
 
         //Have to use slightly more complicated query builder syntax to bulk-insert.
         //Bulk insert is vastly faster than for-looping each item and "regular" inserting the items
         //one by one.
-        let insert_iter = item_ids.iter().zip(slot_ids).filter_map(|(&item, slot_id)| {
-            if item != -1 && slot_id != -1 {
-                Some(DBItemInstance {
-                    character_id,
-                    slot_id: slot_id as u8,
-                    item: Some(item as u32),
-                    enchant: None,
-                })
-            } else {
-                None
-            }
-        });
-
         let mut query_builder: QueryBuilder<MySql> = QueryBuilder::new("INSERT INTO character_equipment (character_id, slot_id, item, enchant) ");
         query_builder.push_values(insert_iter, |mut b, item| {
             b.push_bind(item.character_id)
@@ -0,0 +1,27 @@
+use anyhow::Result;
+
+use crate::{character::DBCharacter, character_account_data::DBCharacterAccountData, item_instance::DBItemInstance};
+
+pub struct DBCharacterLoginData {
+    pub character: DBCharacter,
+    pub account_data: Vec<DBCharacterAccountData>,
+    pub equipment: Vec<DBItemInstance>,
+}
+
+impl super::RealmDatabase {
+    //Everything the world server needs to put a character in the world. None of these queries
+    //depend on each other, so they run concurrently on the pool instead of one after the other.
+    pub async fn get_character_login_data(&self, character_id: u32) -> Result<DBCharacterLoginData> {
+        let (character, account_data, equipment) = futures::try_join!(
+            self.get_character(character_id),
+            self.get_character_account_data(character_id),
+            self.get_all_character_equipment(character_id),
+        )?;
+
+        Ok(DBCharacterLoginData {
+            character,
+            account_data,
+            equipment,
+        })
+    }
+}
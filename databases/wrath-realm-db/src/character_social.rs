@@ -0,0 +1,47 @@
+use anyhow::Result;
+
+pub struct DBCharacterSocial {
+    pub friend_id: u32,
+    pub flags: u8,
+    pub note: String,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_character_social(&self, character_id: u32) -> Result<Vec<DBCharacterSocial>> {
+        let res = sqlx::query_as!(
+            DBCharacterSocial,
+            "SELECT friend_id, flags, note FROM character_social WHERE character_id = ?",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn set_character_social(&self, character_id: u32, friend_id: u32, flags: u8, note: &str) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO character_social (character_id, friend_id, flags, note) VALUES (?, ?, ?, ?) ON DUPLICATE KEY UPDATE flags = VALUES(flags), note = VALUES(note)",
+            character_id,
+            friend_id,
+            flags,
+            note
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_character_social(&self, character_id: u32, friend_id: u32) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM character_social WHERE character_id = ? AND friend_id = ?",
+            character_id,
+            friend_id
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use anyhow::Result;
+
+pub struct DBChatLogEntry {
+    pub time: u64,
+    pub chat_type: String,
+    pub sender_id: u32,
+    pub sender_name: String,
+    pub receiver: Option<String>,
+    pub message: String,
+}
+
+impl super::RealmDatabase {
+    pub async fn insert_chat_log_entry(&self, entry: &DBChatLogEntry) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO chat_log (time, chat_type, sender_id, sender_name, receiver, message) VALUES (?, ?, ?, ?, ?, ?)",
+            entry.time,
+            entry.chat_type,
+            entry.sender_id,
+            entry.sender_name,
+            entry.receiver,
+            entry.message
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+
+pub struct DBCharacterCurrency {
+    pub currency: u32,
+    pub count: u32,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_character_currencies(&self, character_id: u32) -> Result<Vec<DBCharacterCurrency>> {
+        let res = sqlx::query_as!(
+            DBCharacterCurrency,
+            "SELECT currency, count FROM character_currency WHERE character_id = ?",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn set_character_currency(&self, character_id: u32, currency: u32, count: u32) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO character_currency (character_id, currency, count) VALUES (?, ?, ?) ON DUPLICATE KEY UPDATE count = VALUES(count)",
+            character_id,
+            currency,
+            count
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+
+pub struct DBEquipmentSet {
+    pub id: u32,
+    pub set_index: u8,
+    pub name: String,
+    pub icon_name: String,
+}
+
+pub struct DBEquipmentSetItem {
+    pub set_id: u32,
+    pub slot_id: u8,
+    pub item: u32,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_character_equipment_sets(&self, character_id: u32) -> Result<Vec<DBEquipmentSet>> {
+        let res = sqlx::query_as!(
+            DBEquipmentSet,
+            "SELECT id, set_index, name, icon_name FROM character_equipmentsets WHERE character_id = ? ORDER BY set_index",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn get_character_equipment_set_items(&self, character_id: u32) -> Result<Vec<DBEquipmentSetItem>> {
+        let res = sqlx::query_as!(
+            DBEquipmentSetItem,
+            "SELECT i.set_id, i.slot_id, i.item FROM character_equipmentset_items i JOIN character_equipmentsets s ON s.id = i.set_id WHERE s.character_id = ?",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    //Saving over an existing index keeps the set id, the client still refers to it by its old guid.
+    //Items are (slot, item entry) pairs, slots that aren't part of the set are left out.
+    pub async fn save_equipment_set(&self, character_id: u32, set_index: u8, name: &str, icon_name: &str, items: &[(u8, u32)]) -> Result<u32> {
+        let mut transaction = self.begin_transaction().await?;
+        let res = sqlx::query!(
+            "INSERT INTO character_equipmentsets (character_id, set_index, name, icon_name) VALUES (?, ?, ?, ?) ON DUPLICATE KEY UPDATE name = VALUES(name), icon_name = VALUES(icon_name), id = LAST_INSERT_ID(id)",
+            character_id,
+            set_index,
+            name,
+            icon_name
+        )
+        .execute(&mut *transaction)
+        .await?;
+        let set_id = res.last_insert_id() as u32;
+
+        sqlx::query!("DELETE FROM character_equipmentset_items WHERE set_id = ?", set_id)
+            .execute(&mut *transaction)
+            .await?;
+        for (slot_id, item) in items {
+            sqlx::query!(
+                "INSERT INTO character_equipmentset_items (set_id, slot_id, item) VALUES (?, ?, ?)",
+                set_id,
+                slot_id,
+                item
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(set_id)
+    }
+
+    pub async fn delete_equipment_set(&self, character_id: u32, set_id: u32) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM character_equipmentsets WHERE id = ? AND character_id = ?",
+            set_id,
+            character_id
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::Result;
+
+pub struct DBGuild {
+    pub id: u32,
+    pub name: String,
+    pub leader_id: u32,
+    pub motd: String,
+}
+
+pub struct DBGuildRank {
+    pub guild_id: u32,
+    pub rank_id: u8,
+    pub name: String,
+    pub rights: u32,
+}
+
+pub struct DBGuildMember {
+    pub character_id: u32,
+    pub guild_id: u32,
+    pub rank_id: u8,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_all_guilds(&self) -> Result<Vec<DBGuild>> {
+        let res = sqlx::query_as!(DBGuild, "SELECT id, name, leader_id, motd FROM guild")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn get_all_guild_ranks(&self) -> Result<Vec<DBGuildRank>> {
+        let res = sqlx::query_as!(
+            DBGuildRank,
+            "SELECT guild_id, rank_id, name, rights FROM guild_rank ORDER BY guild_id, rank_id"
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn get_all_guild_members(&self) -> Result<Vec<DBGuildMember>> {
+        let res = sqlx::query_as!(DBGuildMember, "SELECT character_id, guild_id, rank_id FROM guild_member")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+
+    //The guild, its ranks (by rank id, from the guild master down) and its founder as the guild master go in together
+    pub async fn create_guild(&self, name: &str, leader_id: u32, ranks: &[(&str, u32)], created_time: u64) -> Result<u32> {
+        let mut transaction = self.begin_transaction().await?;
+        let res = sqlx::query!(
+            "INSERT INTO guild (name, leader_id, created_time) VALUES (?, ?, ?)",
+            name,
+            leader_id,
+            created_time
+        )
+        .execute(&mut *transaction)
+        .await?;
+        let guild_id = res.last_insert_id() as u32;
+
+        for (rank_id, &(rank_name, rights)) in ranks.iter().enumerate() {
+            sqlx::query!(
+                "INSERT INTO guild_rank (guild_id, rank_id, name, rights) VALUES (?, ?, ?, ?)",
+                guild_id,
+                rank_id as u8,
+                rank_name,
+                rights
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+        sqlx::query!(
+            "INSERT INTO guild_member (character_id, guild_id, rank_id) VALUES (?, ?, ?)",
+            leader_id,
+            guild_id,
+            0u8
+        )
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query!("UPDATE characters SET guild_id = ? WHERE id = ?", guild_id, leader_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+        Ok(guild_id)
+    }
+
+    //characters.guild_id is what the character screen and login read, it's kept in step with the member row
+    pub async fn add_guild_member(&self, guild_id: u32, character_id: u32, rank_id: u8) -> Result<()> {
+        let mut transaction = self.begin_transaction().await?;
+        sqlx::query!(
+            "INSERT INTO guild_member (character_id, guild_id, rank_id) VALUES (?, ?, ?)",
+            character_id,
+            guild_id,
+            rank_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query!("UPDATE characters SET guild_id = ? WHERE id = ?", guild_id, character_id)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn remove_guild_member(&self, character_id: u32) -> Result<()> {
+        let mut transaction = self.begin_transaction().await?;
+        sqlx::query!("DELETE FROM guild_member WHERE character_id = ?", character_id)
+            .execute(&mut *transaction)
+            .await?;
+        sqlx::query!("UPDATE characters SET guild_id = 0 WHERE id = ?", character_id)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn set_guild_motd(&self, guild_id: u32, motd: &str) -> Result<()> {
+        sqlx::query!("UPDATE guild SET motd = ? WHERE id = ?", motd, guild_id)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    //Ranks and member rows go with the guild
+    pub async fn delete_guild(&self, guild_id: u32) -> Result<()> {
+        let mut transaction = self.begin_transaction().await?;
+        sqlx::query!("UPDATE characters SET guild_id = 0 WHERE guild_id = ?", guild_id)
+            .execute(&mut *transaction)
+            .await?;
+        sqlx::query!("DELETE FROM guild WHERE id = ?", guild_id)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use anyhow::Result;
+
+impl super::RealmDatabase {
+    //None when the character has no guild member row, which means it never changed its notifications
+    pub async fn get_guild_member_notifications(&self, character_id: u32) -> Result<Option<u8>> {
+        let res = sqlx::query!("SELECT notifications FROM guild_member WHERE character_id = ?", character_id)
+            .fetch_optional(&self.connection_pool)
+            .await?;
+        Ok(res.map(|row| row.notifications))
+    }
+
+    //Only members have a row, leaving the guild takes the preferences with it
+    pub async fn set_guild_member_notifications(&self, character_id: u32, notifications: u8) -> Result<()> {
+        sqlx::query!(
+            "UPDATE guild_member SET notifications = ? WHERE character_id = ?",
+            notifications,
+            character_id
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+
+pub struct DBInstance {
+    pub id: u32,
+    pub map_id: u32,
+    pub difficulty: u8,
+    pub completed_encounters: u32,
+}
+
+pub struct DBCharacterInstanceBind {
+    pub character_id: u32,
+    pub map_id: u32,
+    pub difficulty: u8,
+    pub instance_id: u32,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_instance(&self, instance_id: u32) -> Result<Option<DBInstance>> {
+        let res = sqlx::query_as!(
+            DBInstance,
+            "SELECT id, map_id, difficulty, completed_encounters FROM instance WHERE id = ?",
+            instance_id
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    //New instances are numbered after the ones that were saved, so they never take over someone's lockout
+    pub async fn get_max_instance_id(&self) -> Result<u32> {
+        let res = sqlx::query!("SELECT MAX(id) AS max_id FROM instance")
+            .fetch_one(&self.connection_pool)
+            .await?;
+
+        Ok(res.max_id.unwrap_or(0))
+    }
+
+    pub async fn get_character_instance_binds(&self, character_id: u32) -> Result<Vec<DBCharacterInstanceBind>> {
+        let res = sqlx::query_as!(
+            DBCharacterInstanceBind,
+            "SELECT character_id, map_id, difficulty, instance_id FROM character_instance_bind WHERE character_id = ?",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    //Saves the boss kill and locks everyone who was present to the instance. Characters already bound to
+    //another instance of the same map and difficulty keep their existing lockout.
+    pub async fn save_instance_encounter_kill(&self, instance: &DBInstance, character_ids: &[u32]) -> Result<()> {
+        let mut transaction = self.begin_transaction().await?;
+        sqlx::query!(
+            "INSERT INTO instance (id, map_id, difficulty, completed_encounters) VALUES (?, ?, ?, ?) ON DUPLICATE KEY UPDATE completed_encounters = VALUES(completed_encounters)",
+            instance.id,
+            instance.map_id,
+            instance.difficulty,
+            instance.completed_encounters
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        for character_id in character_ids {
+            sqlx::query!(
+                "INSERT IGNORE INTO character_instance_bind (character_id, map_id, difficulty, instance_id) VALUES (?, ?, ?, ?)",
+                character_id,
+                instance.map_id,
+                instance.difficulty,
+                instance.id
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+}
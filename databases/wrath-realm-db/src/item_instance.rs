@@ -1,4 +1,5 @@
 use anyhow::Result;
+use sqlx::{MySql, QueryBuilder};
 
 pub struct DBItemInstance {
     pub character_id: u32,
@@ -7,6 +8,15 @@ pub struct DBItemInstance {
     pub enchant: Option<u32>,
 }
 
+//New content of a single slot, item None means the slot is now empty
+#[derive(Clone, Copy, Debug)]
+pub struct DBCharacterItemChange {
+    pub character_id: u32,
+    pub slot_id: u8,
+    pub item: Option<u32>,
+    pub enchant: Option<u32>,
+}
+
 impl super::RealmDatabase {
     pub async fn get_all_character_equipment(&self, character_id: u32) -> Result<Vec<DBItemInstance>> {
         let res = sqlx::query_as!(DBItemInstance, "SELECT * FROM character_equipment WHERE character_id = ?", character_id)
@@ -38,4 +48,37 @@ impl super::RealmDatabase {
         .await?;
         Ok(())
     }
+
+    //Applies a whole batch of slot changes with a single DELETE and a single INSERT.
+    //Both run in one transaction so a failed batch can be retried without losing the deleted rows.
+    pub async fn apply_character_item_changes(&self, changes: &[DBCharacterItemChange]) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut transaction = self.begin_transaction().await?;
+
+        let mut delete_builder: QueryBuilder<MySql> = QueryBuilder::new("DELETE FROM character_equipment WHERE (character_id, slot_id) IN ");
+        delete_builder.push_tuples(changes, |mut b, change| {
+            b.push_bind(change.character_id).push_bind(change.slot_id);
+        });
+        delete_builder.build().execute(&mut *transaction).await?;
+
+        let mut inserts = changes.iter().filter(|change| change.item.is_some()).peekable();
+        if inserts.peek().is_none() {
+            transaction.commit().await?;
+            return Ok(());
+        }
+
+        let mut insert_builder: QueryBuilder<MySql> = QueryBuilder::new("INSERT INTO character_equipment (character_id, slot_id, item, enchant) ");
+        insert_builder.push_values(inserts, |mut b, change| {
+            b.push_bind(change.character_id)
+                .push_bind(change.slot_id)
+                .push_bind(change.item)
+                .push_bind(change.enchant);
+        });
+        insert_builder.build().execute(&mut *transaction).await?;
+        transaction.commit().await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,101 @@
+use anyhow::Result;
+
+pub const ITEM_JOURNAL_STAGE_INTENT: u8 = 0;
+pub const ITEM_JOURNAL_STAGE_TAKEN: u8 = 1;
+
+pub struct DBItemJournalEntry {
+    pub id: u64,
+    pub operation: u8,
+    pub stage: u8,
+    pub character_id: u32,
+    pub slot_id: u8,
+    pub item_id: u32,
+    pub enchant: Option<u32>,
+    pub created_at: u64,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_item_journal_entries(&self) -> Result<Vec<DBItemJournalEntry>> {
+        let res = sqlx::query_as!(
+            DBItemJournalEntry,
+            "SELECT id, operation, stage, character_id, slot_id, item_id, enchant, created_at FROM item_journal"
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn insert_item_journal_entry(
+        &self,
+        operation: u8,
+        character_id: u32,
+        slot_id: u8,
+        item_id: u32,
+        enchant: Option<u32>,
+        created_at: u64,
+    ) -> Result<u64> {
+        let res = sqlx::query!(
+            "INSERT INTO item_journal (operation, stage, character_id, slot_id, item_id, enchant, created_at) VALUES (?, 0, ?, ?, ?, ?, ?)",
+            operation,
+            character_id,
+            slot_id,
+            item_id,
+            enchant,
+            created_at
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(res.last_insert_id())
+    }
+
+    pub async fn delete_item_journal_entry(&self, id: u64) -> Result<()> {
+        sqlx::query!("DELETE FROM item_journal WHERE id = ?", id)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    //Removes the item from its owner and records that it did, the journal stage always matches the inventory
+    pub async fn take_journaled_item(&self, id: u64, character_id: u32, slot_id: u8) -> Result<()> {
+        let mut transaction = self.begin_transaction().await?;
+        sqlx::query!(
+            "DELETE FROM character_equipment WHERE character_id = ? AND slot_id = ?",
+            character_id,
+            slot_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query!("UPDATE item_journal SET stage = 1 WHERE id = ?", id)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    //Puts a journaled item into a character slot and closes the journal entry. Used both to deliver the
+    //item and to hand it back to its owner when an interrupted operation is rolled back.
+    pub async fn place_journaled_item(&self, id: u64, character_id: u32, slot_id: u8, item_id: u32, enchant: Option<u32>) -> Result<()> {
+        let mut transaction = self.begin_transaction().await?;
+        sqlx::query!(
+            "DELETE FROM character_equipment WHERE character_id = ? AND slot_id = ?",
+            character_id,
+            slot_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query!(
+            "INSERT INTO character_equipment (character_id, slot_id, item, enchant) VALUES (?, ?, ?, ?)",
+            character_id,
+            slot_id,
+            item_id,
+            enchant
+        )
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query!("DELETE FROM item_journal WHERE id = ?", id)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+}
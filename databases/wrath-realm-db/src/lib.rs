@@ -1,10 +1,37 @@
 use anyhow::Result;
+use sqlx::{MySql, Transaction};
 use std::time::Duration;
 
+pub mod account_collection;
+pub mod account_session_log;
+pub mod armory;
+pub mod aura_expiry;
+pub mod autobroadcast;
+pub mod calendar;
 pub mod character;
 pub mod character_account_data;
+pub mod character_audit;
 pub mod character_equipment;
+pub mod character_login;
+pub mod character_social;
+pub mod chat_log;
+pub mod currency;
+pub mod equipment_set;
+pub mod guild;
+pub mod guild_member;
+pub mod instance;
 pub mod item_instance;
+pub mod item_journal;
+pub mod mail;
+pub mod motd;
+pub mod pet;
+pub mod quest_status;
+pub mod rare_spawn_respawn;
+pub mod recall_position;
+pub mod reputation;
+pub mod taxi;
+pub mod uptime;
+pub mod vendor_stock;
 
 pub use wrath_game_db::{DBAreaTriggerRestedZone, DBAreaTriggerTeleport, DBItemTemplate, DBPlayerCreateInfo};
 
@@ -22,4 +49,16 @@ impl RealmDatabase {
 
         Ok(Self { connection_pool: pool })
     }
+
+    //Doesn't connect until the first query, for tests that never touch the database
+    pub fn new_lazy(conn_string: &str) -> Result<Self> {
+        let pool = sqlx::mysql::MySqlPoolOptions::new().max_connections(1).connect_lazy(conn_string)?;
+        Ok(Self { connection_pool: pool })
+    }
+
+    //Multi-step writes that move items around must go through a transaction, otherwise a crash between
+    //the steps can leave an item duplicated or gone. Dropping the transaction without committing rolls it back.
+    pub async fn begin_transaction(&self) -> Result<Transaction<'static, MySql>> {
+        Ok(self.connection_pool.begin().await?)
+    }
 }
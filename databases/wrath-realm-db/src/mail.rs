@@ -0,0 +1,186 @@
+use anyhow::Result;
+use sqlx::{MySql, Transaction};
+
+pub const MAIL_FLAG_READ: u8 = 0x1;
+pub const MAIL_FLAG_RETURNED: u8 = 0x2;
+
+pub struct DBMail {
+    pub id: u32,
+    pub sender_id: u32,
+    pub receiver_id: u32,
+    pub subject: String,
+    pub body: String,
+    pub money: u32,
+    pub cash_on_delivery: u32,
+    pub expire_time: u64,
+    pub flags: u8,
+}
+
+pub struct DBMailCreateParameters {
+    pub sender_id: u32,
+    pub receiver_id: u32,
+    pub subject: String,
+    pub body: String,
+    pub money: u32,
+    pub cash_on_delivery: u32,
+    pub expire_time: u64,
+}
+
+pub struct DBMailItem {
+    pub id: u32,
+    pub mail_id: u32,
+    pub item: u32,
+    pub enchant: Option<u32>,
+}
+
+impl super::RealmDatabase {
+    //The mail and its attachments are written together, the items are already gone from the sender's inventory
+    pub async fn create_mail(&self, params: &DBMailCreateParameters, items: &[(u32, Option<u32>)]) -> Result<u32> {
+        let mut transaction = self.begin_transaction().await?;
+        let mail_id = Self::insert_mail_in_transaction(&mut transaction, params, items).await?;
+        transaction.commit().await?;
+        Ok(mail_id)
+    }
+
+    //Same as create_mail for items that were taken through the item journal, their journal entries are closed
+    //in the same transaction so the items are either in the mail or still journaled, never both or neither
+    pub async fn create_mail_from_journal(&self, params: &DBMailCreateParameters, items: &[(u32, Option<u32>)], journal_ids: &[u64]) -> Result<u32> {
+        let mut transaction = self.begin_transaction().await?;
+        let mail_id = Self::insert_mail_in_transaction(&mut transaction, params, items).await?;
+        for &id in journal_ids {
+            sqlx::query!("DELETE FROM item_journal WHERE id = ?", id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(mail_id)
+    }
+
+    async fn insert_mail_in_transaction(
+        transaction: &mut Transaction<'static, MySql>,
+        params: &DBMailCreateParameters,
+        items: &[(u32, Option<u32>)],
+    ) -> Result<u32> {
+        let res = sqlx::query!(
+            "INSERT INTO mail (sender_id, receiver_id, subject, body, money, cash_on_delivery, expire_time) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params.sender_id,
+            params.receiver_id,
+            params.subject,
+            params.body,
+            params.money,
+            params.cash_on_delivery,
+            params.expire_time
+        )
+        .execute(&mut **transaction)
+        .await?;
+        let mail_id = res.last_insert_id() as u32;
+
+        for &(item, enchant) in items {
+            sqlx::query!("INSERT INTO mail_item (mail_id, item, enchant) VALUES (?, ?, ?)", mail_id, item, enchant)
+                .execute(&mut **transaction)
+                .await?;
+        }
+        Ok(mail_id)
+    }
+
+    pub async fn get_mail(&self, mail_id: u32) -> Result<Option<DBMail>> {
+        let res = sqlx::query_as!(
+            DBMail,
+            "SELECT id, sender_id, receiver_id, subject, body, money, cash_on_delivery, expire_time, flags FROM mail WHERE id = ?",
+            mail_id
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    //Newest first, the way the client lists them
+    pub async fn get_mails_for_character(&self, character_id: u32) -> Result<Vec<DBMail>> {
+        let res = sqlx::query_as!(
+            DBMail,
+            "SELECT id, sender_id, receiver_id, subject, body, money, cash_on_delivery, expire_time, flags FROM mail WHERE receiver_id = ? ORDER BY id DESC",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn get_mail_items_for_character(&self, character_id: u32) -> Result<Vec<DBMailItem>> {
+        let res = sqlx::query_as!(
+            DBMailItem,
+            "SELECT mail_item.id, mail_item.mail_id, mail_item.item, mail_item.enchant FROM mail_item INNER JOIN mail ON mail.id = mail_item.mail_id WHERE mail.receiver_id = ?",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn get_mail_items(&self, mail_id: u32) -> Result<Vec<DBMailItem>> {
+        let res = sqlx::query_as!(DBMailItem, "SELECT id, mail_id, item, enchant FROM mail_item WHERE mail_id = ?", mail_id)
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn get_expired_mails(&self, now: u64) -> Result<Vec<DBMail>> {
+        let res = sqlx::query_as!(
+            DBMail,
+            "SELECT id, sender_id, receiver_id, subject, body, money, cash_on_delivery, expire_time, flags FROM mail WHERE expire_time <= ?",
+            now
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn take_mail_money(&self, mail_id: u32) -> Result<()> {
+        sqlx::query!("UPDATE mail SET money = 0 WHERE id = ?", mail_id)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    //Paying the cash on delivery once is enough for every item on the mail
+    pub async fn take_mail_item(&self, mail_item_id: u32, mail_id: u32) -> Result<()> {
+        let mut transaction = self.begin_transaction().await?;
+        sqlx::query!("DELETE FROM mail_item WHERE id = ?", mail_item_id)
+            .execute(&mut *transaction)
+            .await?;
+        sqlx::query!("UPDATE mail SET cash_on_delivery = 0 WHERE id = ?", mail_id)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn set_mail_flags(&self, mail_id: u32, flags: u8) -> Result<()> {
+        sqlx::query!("UPDATE mail SET flags = ? WHERE id = ?", flags, mail_id)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    //Sends the mail back with whatever is still attached, the cash on delivery is dropped
+    pub async fn return_mail(&self, mail: &DBMail, expire_time: u64) -> Result<()> {
+        sqlx::query!(
+            "UPDATE mail SET sender_id = ?, receiver_id = ?, cash_on_delivery = 0, flags = ?, expire_time = ? WHERE id = ?",
+            mail.receiver_id,
+            mail.sender_id,
+            MAIL_FLAG_RETURNED,
+            expire_time,
+            mail.id
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_mail(&self, mail_id: u32) -> Result<()> {
+        sqlx::query!("DELETE FROM mail WHERE id = ?", mail_id)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+}
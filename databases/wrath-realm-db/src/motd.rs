@@ -0,0 +1,26 @@
+use anyhow::Result;
+
+impl super::RealmDatabase {
+    //Returns the translation for the locale if there is one, the enUS message otherwise
+    pub async fn get_motd(&self, locale: &str) -> Result<Option<String>> {
+        let res = sqlx::query!(
+            "SELECT message FROM motd WHERE id = 0 AND locale IN (?, 'enUS') ORDER BY locale = 'enUS' LIMIT 1",
+            locale
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?;
+
+        Ok(res.map(|row| row.message))
+    }
+
+    //Translations of the previous message no longer match, so they are dropped together with it
+    pub async fn set_motd(&self, message: &str) -> Result<()> {
+        let mut transaction = self.begin_transaction().await?;
+        sqlx::query!("DELETE FROM motd WHERE id = 0").execute(&mut *transaction).await?;
+        sqlx::query!("INSERT INTO motd (id, locale, message) VALUES (0, 'enUS', ?)", message)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+}
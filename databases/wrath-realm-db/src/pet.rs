@@ -0,0 +1,117 @@
+use anyhow::Result;
+
+pub const PET_TYPE_SUMMON: u8 = 0;
+pub const PET_TYPE_HUNTER: u8 = 1;
+
+pub struct DBCharacterPet {
+    pub id: u32,
+    pub owner_id: u32,
+    pub entry: u32,
+    pub pet_type: u8,
+    pub name: String,
+    pub level: u8,
+    pub happiness: u32,
+    pub slot: u8,
+}
+
+pub struct DBPetTalent {
+    pub talent_id: u32,
+    pub rank: u8,
+}
+
+pub struct DBPetSpell {
+    pub spell_id: u32,
+    pub autocast: u8,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_character_pets(&self, owner_id: u32) -> Result<Vec<DBCharacterPet>> {
+        let res = sqlx::query_as!(
+            DBCharacterPet,
+            "SELECT id, owner_id, entry, pet_type, name, level, happiness, slot FROM character_pet WHERE owner_id = ? ORDER BY slot",
+            owner_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn get_pet_talents(&self, pet_id: u32) -> Result<Vec<DBPetTalent>> {
+        let res = sqlx::query_as!(DBPetTalent, "SELECT talent_id, `rank` FROM character_pet_talent WHERE pet_id = ?", pet_id)
+            .fetch_all(&self.connection_pool)
+            .await?;
+
+        Ok(res)
+    }
+
+    pub async fn get_pet_spells(&self, pet_id: u32) -> Result<Vec<DBPetSpell>> {
+        let res = sqlx::query_as!(DBPetSpell, "SELECT spell_id, autocast FROM character_pet_spell WHERE pet_id = ?", pet_id)
+            .fetch_all(&self.connection_pool)
+            .await?;
+
+        Ok(res)
+    }
+
+    pub async fn create_character_pet(&self, owner_id: u32, entry: u32, pet_type: u8, name: &str, level: u8, happiness: u32) -> Result<u32> {
+        let res = sqlx::query!(
+            "INSERT INTO character_pet (owner_id, entry, pet_type, name, level, happiness, slot) VALUES (?, ?, ?, ?, ?, ?, 0)",
+            owner_id,
+            entry,
+            pet_type,
+            name,
+            level,
+            happiness
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(res.last_insert_id() as u32)
+    }
+
+    //Talents and spells are replaced as a whole, the pet only ever has a handful of each
+    pub async fn save_character_pet(&self, pet: &DBCharacterPet, talents: &[DBPetTalent], spells: &[DBPetSpell]) -> Result<()> {
+        let mut transaction = self.begin_transaction().await?;
+        sqlx::query!(
+            "UPDATE character_pet SET name = ?, level = ?, happiness = ?, slot = ? WHERE id = ?",
+            pet.name,
+            pet.level,
+            pet.happiness,
+            pet.slot,
+            pet.id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!("DELETE FROM character_pet_talent WHERE pet_id = ?", pet.id)
+            .execute(&mut *transaction)
+            .await?;
+        for talent in talents {
+            sqlx::query!(
+                "INSERT INTO character_pet_talent (pet_id, talent_id, `rank`) VALUES (?, ?, ?)",
+                pet.id,
+                talent.talent_id,
+                talent.rank
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        sqlx::query!("DELETE FROM character_pet_spell WHERE pet_id = ?", pet.id)
+            .execute(&mut *transaction)
+            .await?;
+        for spell in spells {
+            sqlx::query!(
+                "INSERT INTO character_pet_spell (pet_id, spell_id, autocast) VALUES (?, ?, ?)",
+                pet.id,
+                spell.spell_id,
+                spell.autocast
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+
+pub struct DBCharacterQuestStatus {
+    pub quest_id: u32,
+    pub status: u8,
+}
+
+pub struct DBCharacterQuestObjective {
+    pub quest_id: u32,
+    pub objective_entry: u32,
+    pub count: u8,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_character_quest_statuses(&self, character_id: u32) -> Result<Vec<DBCharacterQuestStatus>> {
+        let res = sqlx::query_as!(
+            DBCharacterQuestStatus,
+            "SELECT quest_id, status FROM character_quest_status WHERE character_id = ?",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn set_character_quest_status(&self, character_id: u32, quest_id: u32, status: u8) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO character_quest_status (character_id, quest_id, status) VALUES (?, ?, ?) ON DUPLICATE KEY UPDATE status = VALUES(status)",
+            character_id,
+            quest_id,
+            status
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_character_quest_objectives(&self, character_id: u32) -> Result<Vec<DBCharacterQuestObjective>> {
+        let res = sqlx::query_as!(
+            DBCharacterQuestObjective,
+            "SELECT quest_id, objective_entry, count FROM character_quest_objective WHERE character_id = ?",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn set_character_quest_objective(&self, character_id: u32, quest_id: u32, objective_entry: u32, count: u8) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO character_quest_objective (character_id, quest_id, objective_entry, count) VALUES (?, ?, ?, ?) ON DUPLICATE KEY UPDATE count = VALUES(count)",
+            character_id,
+            quest_id,
+            objective_entry,
+            count
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_character_quest_objectives(&self, character_id: u32, quest_id: u32) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM character_quest_objective WHERE character_id = ? AND quest_id = ?",
+            character_id,
+            quest_id
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use anyhow::Result;
+
+pub struct DBRareSpawnRespawn {
+    pub spawn_id: u32,
+    pub respawn_time: u64,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_rare_spawn_respawn_times(&self) -> Result<Vec<DBRareSpawnRespawn>> {
+        let res = sqlx::query_as!(DBRareSpawnRespawn, "SELECT spawn_id, respawn_time FROM rare_spawn_respawn")
+            .fetch_all(&self.connection_pool)
+            .await?;
+
+        Ok(res)
+    }
+
+    pub async fn set_rare_spawn_respawn_time(&self, spawn_id: u32, respawn_time: u64) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO rare_spawn_respawn (spawn_id, respawn_time) VALUES (?, ?) ON DUPLICATE KEY UPDATE respawn_time = VALUES(respawn_time)",
+            spawn_id,
+            respawn_time
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(())
+    }
+}
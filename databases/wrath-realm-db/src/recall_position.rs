@@ -0,0 +1,41 @@
+use anyhow::Result;
+
+pub struct DBRecallPosition {
+    pub map: u16,
+    pub zone: u16,
+    pub position_x: f32,
+    pub position_y: f32,
+    pub position_z: f32,
+    pub orientation: f32,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_character_recall_position(&self, character_id: u32) -> Result<Option<DBRecallPosition>> {
+        let res = sqlx::query_as!(
+            DBRecallPosition,
+            "SELECT map, zone, position_x, position_y, position_z, orientation FROM character_recall_position WHERE character_id = ?",
+            character_id
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?;
+
+        Ok(res)
+    }
+
+    pub async fn set_character_recall_position(&self, character_id: u32, position: &DBRecallPosition) -> Result<()> {
+        sqlx::query!(
+            "REPLACE INTO character_recall_position (character_id, map, zone, position_x, position_y, position_z, orientation) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            character_id,
+            position.map,
+            position.zone,
+            position.position_x,
+            position.position_y,
+            position.position_z,
+            position.orientation
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(())
+    }
+}
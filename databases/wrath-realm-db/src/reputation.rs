@@ -0,0 +1,31 @@
+use anyhow::Result;
+
+pub struct DBCharacterReputation {
+    pub faction: u32,
+    pub standing: i32,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_character_reputations(&self, character_id: u32) -> Result<Vec<DBCharacterReputation>> {
+        let res = sqlx::query_as!(
+            DBCharacterReputation,
+            "SELECT faction, standing FROM character_reputation WHERE character_id = ?",
+            character_id
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(res)
+    }
+
+    pub async fn set_character_reputation(&self, character_id: u32, faction: u32, standing: i32) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO character_reputation (character_id, faction, standing) VALUES (?, ?, ?) ON DUPLICATE KEY UPDATE standing = VALUES(standing)",
+            character_id,
+            faction,
+            standing
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use anyhow::Result;
+
+impl super::RealmDatabase {
+    pub async fn get_character_taxi_nodes(&self, character_id: u32) -> Result<Vec<u32>> {
+        let res = sqlx::query!("SELECT node_id FROM character_taxi_nodes WHERE character_id = ?", character_id)
+            .fetch_all(&self.connection_pool)
+            .await?;
+
+        Ok(res.into_iter().map(|row| row.node_id).collect())
+    }
+
+    //Nodes the character already knows are skipped, so learning the same node twice is harmless
+    pub async fn add_character_taxi_nodes(&self, character_id: u32, node_ids: &[u32]) -> Result<()> {
+        let mut transaction = self.begin_transaction().await?;
+        for node_id in node_ids {
+            sqlx::query!(
+                "INSERT IGNORE INTO character_taxi_nodes (character_id, node_id) VALUES (?, ?)",
+                character_id,
+                node_id
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+
+impl super::RealmDatabase {
+    pub async fn start_uptime(&self, start_time: u64, revision: &str) -> Result<()> {
+        sqlx::query!("INSERT INTO uptime (start_time, revision) VALUES (?, ?)", start_time, revision)
+            .execute(&self.connection_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_uptime(&self, start_time: u64, uptime: u64, max_players: u32) -> Result<()> {
+        sqlx::query!(
+            "UPDATE uptime SET uptime = ?, max_players = ? WHERE start_time = ?",
+            uptime,
+            max_players,
+            start_time
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    //The most players that were ever online at the same time, over every run of the server
+    pub async fn get_max_players_ever(&self) -> Result<u32> {
+        let res = sqlx::query!("SELECT MAX(max_players) AS max_players FROM uptime")
+            .fetch_one(&self.connection_pool)
+            .await?;
+
+        Ok(res.max_players.unwrap_or(0))
+    }
+}
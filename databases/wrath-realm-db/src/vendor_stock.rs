@@ -0,0 +1,38 @@
+use anyhow::Result;
+
+#[derive(Debug, PartialEq)]
+pub struct DBVendorStock {
+    pub spawn_guid: u32,
+    pub item: u32,
+    pub count: u8,
+    pub restock_time: u64,
+}
+
+impl super::RealmDatabase {
+    pub async fn get_all_vendor_stock(&self) -> Result<Vec<DBVendorStock>> {
+        let res = sqlx::query_as!(DBVendorStock, "SELECT spawn_guid, item, count, restock_time FROM vendor_stock")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn set_vendor_stock(&self, stock: &DBVendorStock) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO vendor_stock (spawn_guid, item, count, restock_time) VALUES (?, ?, ?, ?) ON DUPLICATE KEY UPDATE count = VALUES(count), restock_time = VALUES(restock_time)",
+            stock.spawn_guid,
+            stock.item,
+            stock.count,
+            stock.restock_time
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_vendor_stock(&self, spawn_guid: u32, item: u32) -> Result<()> {
+        sqlx::query!("DELETE FROM vendor_stock WHERE spawn_guid = ? AND item = ?", spawn_guid, item)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+}
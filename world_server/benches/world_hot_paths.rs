@@ -0,0 +1,125 @@
+//! Benchmarks for the code every world tick runs for every character on a map.
+//!
+//! Run with `cargo bench -p wrath-worldserver`. Populations are spread over a square that grows with the
+//! population, so every character has about the same number of others in visibility range and the results
+//! show how the per-character cost scales with the number of characters on the map.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use wow_world_messages::wrath::Vector3d;
+use wow_world_messages::Guid;
+use wrath_worldserver::character::character_manager::CharacterManager;
+use wrath_worldserver::character::Character;
+use wrath_worldserver::connection::events::ServerEvent;
+use wrath_worldserver::world::prelude::{build_create_update_block_for_player, build_values_update_block, MapManager};
+
+const POPULATIONS: [usize; 3] = [100, 1_000, 5_000];
+//Same as the map manager's visibility range
+const VISIBILITY_RANGE: f32 = 5_000.0;
+const AVERAGE_CHARACTERS_IN_RANGE: f32 = 100.0;
+
+struct Population {
+    character_manager: CharacterManager,
+    guids: Vec<Guid>,
+    //Keeps the connection channel open, the map tick fails when nobody is listening
+    _connection_receiver: flume::Receiver<ServerEvent>,
+}
+
+fn create_population(size: usize) -> Population {
+    let mut rng = StdRng::seed_from_u64(size as u64);
+    let visibility_area = std::f32::consts::PI * VISIBILITY_RANGE * VISIBILITY_RANGE;
+    let half_size = (size as f32 * visibility_area / AVERAGE_CHARACTERS_IN_RANGE).sqrt() / 2.0;
+    let (connection_sender, connection_receiver) = flume::unbounded();
+    let mut character_manager = CharacterManager::new();
+    let mut guids = Vec::with_capacity(size);
+
+    for i in 0..size {
+        let guid = Guid::new(i as u64 + 1);
+        let mut character = Character::new(connection_sender.clone(), guid);
+        character.name = format!("Bench{i}");
+        character.movement_info.position = Vector3d {
+            x: rng.gen_range(-half_size..half_size),
+            y: rng.gen_range(-half_size..half_size),
+            z: 0.0,
+        };
+        character.gameplay_data.set_unit_level(80);
+        character.gameplay_data.set_unit_health(100);
+        character.gameplay_data.set_unit_maxhealth(100);
+        character_manager.add_character(character);
+        guids.push(guid);
+    }
+
+    Population {
+        character_manager,
+        guids,
+        _connection_receiver: connection_receiver,
+    }
+}
+
+//A map that already went through one tick, so everyone knows everyone in range
+fn create_populated_map(population: &mut Population) -> MapManager {
+    let mut map = MapManager::new(0);
+    for &guid in &population.guids {
+        map.push_character(population.character_manager.get_character(guid).unwrap());
+    }
+    smol::block_on(map.tick(0.0, &mut population.character_manager)).unwrap();
+    map
+}
+
+fn bench_create_update_block(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_create_update_block_for_player");
+    for size in POPULATIONS {
+        let population = create_population(size);
+        let player = population.character_manager.get_character(population.guids[0]).unwrap();
+
+        //A player logging in next to the whole population gets a create block for every one of them
+        group.bench_with_input(BenchmarkId::from_parameter(size), &population, |b, population| {
+            b.iter(|| {
+                for &guid in &population.guids {
+                    let object = population.character_manager.get_character(guid).unwrap();
+                    criterion::black_box(build_create_update_block_for_player(player, object).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_values_update_block(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_values_update_block");
+    for size in POPULATIONS {
+        let population = create_population(size);
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &population, |b, population| {
+            b.iter(|| {
+                for &guid in &population.guids {
+                    let character = population.character_manager.get_character(guid).unwrap();
+                    criterion::black_box(build_values_update_block(character).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_update_in_range_set(c: &mut Criterion) {
+    let mut group = c.benchmark_group("MapManager::update_in_range_set");
+    group.sample_size(10);
+    for size in POPULATIONS {
+        //Nobody moved since the last tick, which is what most ticks look like
+        let mut population = create_population(size);
+        let mut map = create_populated_map(&mut population);
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| {
+                for &guid in &population.guids {
+                    smol::block_on(map.update_in_range_set(guid, &mut population.character_manager)).unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_create_update_block, bench_values_update_block, bench_update_in_range_set);
+criterion_main!(benches);
@@ -0,0 +1,208 @@
+//! Addon negotiation at login.
+//!
+//! `CMSG_AUTH_SESSION` carries the addons the client has enabled, each with the CRC of the public key it
+//! was signed with. Addons not signed with Blizzard's key get that key sent back in `SMSG_ADDON_INFO` so
+//! the client can verify the secure (Blizzard) addons. After the addon entries comes the list of banned
+//! addons, which the client refuses to load.
+//!
+//! wow_world_messages' `SMSG_ADDON_INFO` writes an addon count the client doesn't expect and has nowhere
+//! to put the public key, so the packet is serialized here and sent with a hand written header.
+
+use std::io::BufRead;
+
+use md5::{Digest, Md5};
+use podio::{LittleEndian, ReadPodExt};
+use smol::io::AsyncWriteExt;
+
+use crate::connection::Connection;
+use crate::prelude::*;
+
+const SMSG_ADDON_INFO_OPCODE: u16 = 0x2EF;
+
+//CRC of the public key Blizzard signs its own addons with
+const BLIZZARD_ADDON_CRC: u32 = 0x4C1C776D;
+
+const BLIZZARD_ADDON_PUBLIC_KEY: [u8; 256] = [
+    0xC3, 0x5B, 0x50, 0x84, 0xB9, 0x3E, 0x32, 0x42, 0x8C, 0xD0, 0xC7, 0x48, 0xFA, 0x0E, 0x5D, 0x54, 0x5A, 0xA3, 0x0E, 0x14, 0xBA, 0x9E, 0x0D, 0xB9,
+    0x5D, 0x8B, 0xEE, 0xB6, 0x84, 0x93, 0x45, 0x75, 0xFF, 0x31, 0xFE, 0x2F, 0x64, 0x3F, 0x3D, 0x6D, 0x07, 0xD9, 0x44, 0x9B, 0x40, 0x85, 0x59, 0x34,
+    0x4E, 0x10, 0xE1, 0xE7, 0x43, 0x69, 0xEF, 0x7C, 0x16, 0xFC, 0xB4, 0xED, 0x1B, 0x95, 0x28, 0xA8, 0x23, 0x76, 0x51, 0x31, 0x57, 0x30, 0x2B, 0x79,
+    0x08, 0x50, 0x10, 0x1C, 0x4A, 0x1A, 0x2C, 0xC8, 0x8B, 0x8F, 0x05, 0x2D, 0x22, 0x3D, 0xDB, 0x5A, 0x24, 0x7A, 0x0F, 0x13, 0x50, 0x37, 0x8F, 0x5A,
+    0xCC, 0x9E, 0x04, 0x44, 0x0E, 0x87, 0x01, 0xD4, 0xA3, 0x15, 0x94, 0x16, 0x34, 0xC6, 0xC2, 0xC3, 0xFB, 0x49, 0xFE, 0xE1, 0xF9, 0xDA, 0x8C, 0x50,
+    0x3C, 0xBE, 0x2C, 0xBB, 0x57, 0xED, 0x46, 0xB9, 0xAD, 0x8B, 0xC6, 0xDF, 0x0E, 0xD6, 0x0F, 0xBE, 0x80, 0xB3, 0x8B, 0x1E, 0x77, 0xCF, 0xAD, 0x22,
+    0xCF, 0xB7, 0x4B, 0xCF, 0xFB, 0xF0, 0x6B, 0x11, 0x45, 0x2D, 0x7A, 0x81, 0x18, 0xF2, 0x92, 0x7E, 0x98, 0x56, 0x5D, 0x5E, 0x69, 0x72, 0x0A, 0x0D,
+    0x03, 0x0A, 0x85, 0xA2, 0x85, 0x9C, 0xCB, 0xFB, 0x56, 0x6E, 0x8F, 0x44, 0xBB, 0x8F, 0x02, 0x22, 0x68, 0x63, 0x97, 0xBC, 0x85, 0xBA, 0xA8, 0xF7,
+    0xB5, 0x40, 0x68, 0x3C, 0x77, 0x86, 0x6F, 0x4B, 0xD7, 0x88, 0xCA, 0x8A, 0xD7, 0xCE, 0x36, 0xF0, 0x45, 0x6E, 0xD5, 0x64, 0x79, 0x0F, 0x17, 0xFC,
+    0x64, 0xDD, 0x10, 0x6F, 0xF3, 0xF5, 0xE0, 0xA6, 0xC3, 0xFB, 0x1B, 0x8C, 0x29, 0xEF, 0x8E, 0xE5, 0x34, 0xCB, 0xD1, 0x2A, 0xCE, 0x79, 0xC3, 0x9A,
+    0x0D, 0x36, 0xEA, 0x01, 0xE0, 0xAA, 0x91, 0x20, 0x54, 0xF0, 0x72, 0xD8, 0x1E, 0xC7, 0x89, 0xD2,
+];
+
+//Addon state the client expects for every addon it reported
+const ADDON_STATE_ENABLED: u8 = 2;
+
+pub struct ClientAddon {
+    pub name: String,
+    pub crc: u32,
+}
+
+impl ClientAddon {
+    fn uses_blizzard_public_key(&self) -> bool {
+        self.crc == BLIZZARD_ADDON_CRC
+    }
+}
+
+//The addon block of CMSG_AUTH_SESSION: a count, then a name, signature flag and two CRCs per addon
+pub fn parse_client_addons(addon_info: &[u8]) -> Result<Vec<ClientAddon>> {
+    let mut reader = std::io::Cursor::new(addon_info);
+    let num_addons = reader.read_u32::<LittleEndian>()?;
+    let mut addons = Vec::with_capacity(num_addons.min(256) as usize);
+
+    for _ in 0..num_addons {
+        let mut name = Vec::new();
+        reader.read_until(0, &mut name)?;
+        if name.pop() != Some(0) {
+            bail!("Addon name is not null terminated");
+        }
+        let _has_signature = reader.read_u8()? == 1;
+        let crc = reader.read_u32::<LittleEndian>()?;
+        let _extra_crc = reader.read_u32::<LittleEndian>()?;
+        addons.push(ClientAddon {
+            name: String::from_utf8(name)?,
+            crc,
+        });
+    }
+    Ok(addons)
+}
+
+pub struct BannedAddon {
+    name: String,
+    version: String,
+}
+
+//Read from BANNED_ADDONS as comma separated "name" or "name:version" entries
+pub struct BannedAddons {
+    addons: Vec<BannedAddon>,
+}
+
+impl BannedAddons {
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("BANNED_ADDONS").unwrap_or_default())
+    }
+
+    fn parse(list: &str) -> Self {
+        let addons = list
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (name, version) = entry.split_once(':').unwrap_or((entry, ""));
+                BannedAddon {
+                    name: name.trim().to_string(),
+                    version: version.trim().to_string(),
+                }
+            })
+            .collect();
+        Self { addons }
+    }
+
+    fn is_banned(&self, name: &str) -> bool {
+        self.addons.iter().any(|banned| banned.name.eq_ignore_ascii_case(name))
+    }
+}
+
+fn addon_info_body(addons: &[ClientAddon], banned: &BannedAddons) -> Vec<u8> {
+    let mut body = Vec::new();
+    for addon in addons {
+        let uses_different_public_key = !addon.uses_blizzard_public_key();
+        body.push(ADDON_STATE_ENABLED);
+        body.push(1); //Uses CRC
+        body.push(uses_different_public_key as u8);
+        if uses_different_public_key {
+            body.extend_from_slice(&BLIZZARD_ADDON_PUBLIC_KEY);
+        }
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.push(0); //No update URL
+    }
+
+    //The client identifies banned addons by the MD5 of their name and version
+    body.extend_from_slice(&(banned.addons.len() as u32).to_le_bytes());
+    for (id, addon) in banned.addons.iter().enumerate() {
+        body.extend_from_slice(&(id as u32 + 1).to_le_bytes());
+        body.extend_from_slice(&Md5::digest(addon.name.as_bytes()));
+        body.extend_from_slice(&Md5::digest(addon.version.as_bytes()));
+        body.extend_from_slice(&0u32.to_le_bytes()); //Ban timestamp, unused by the client
+        body.extend_from_slice(&1u32.to_le_bytes()); //Banned
+    }
+    body
+}
+
+//Server header: big endian size including the opcode, 3 bytes with the top bit set when it doesn't fit 15 bits
+fn server_packet(opcode: u16, body: &[u8]) -> (Vec<u8>, usize) {
+    let size = body.len() + 2;
+    let mut packet = Vec::with_capacity(size + 3);
+    if size > 0x7FFF {
+        packet.push(0x80 | (size >> 16) as u8);
+    }
+    packet.extend_from_slice(&[(size >> 8) as u8, size as u8]);
+    packet.extend_from_slice(&opcode.to_le_bytes());
+    let header_size = packet.len();
+    packet.extend_from_slice(body);
+    (packet, header_size)
+}
+
+pub async fn send_addon_info(connection: &mut Connection, addon_info: &[u8]) -> Result<()> {
+    let addons = parse_client_addons(addon_info)?;
+    let banned = BannedAddons::from_env();
+    for addon in addons.iter().filter(|addon| banned.is_banned(&addon.name)) {
+        info!("Client has banned addon {} enabled, it will be disabled", addon.name);
+    }
+
+    let (mut packet, header_size) = server_packet(SMSG_ADDON_INFO_OPCODE, &addon_info_body(&addons, &banned));
+    let encryption = connection
+        .encryption
+        .as_mut()
+        .ok_or_else(|| anyhow!("Addon info has to be sent after authentication"))?;
+    encryption.encrypt(&mut packet[..header_size]);
+    connection.stream.write_all(&packet).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_blizzard_addons_get_the_public_key_and_bans_follow() {
+        let mut addon_info = 2u32.to_le_bytes().to_vec();
+        for (name, crc) in [("Blizzard_AchievementUI", BLIZZARD_ADDON_CRC), ("Recount", 0x12345678)] {
+            addon_info.extend_from_slice(name.as_bytes());
+            addon_info.extend_from_slice(&[0, 1]);
+            addon_info.extend_from_slice(&crc.to_le_bytes());
+            addon_info.extend_from_slice(&0u32.to_le_bytes());
+        }
+        let addons = parse_client_addons(&addon_info).unwrap();
+        assert_eq!(addons[1].name, "Recount");
+
+        let banned = BannedAddons::parse("Recount:1.0, ,Carbonite");
+        assert!(banned.is_banned("recount"));
+        assert!(!banned.is_banned("Blizzard_AchievementUI"));
+
+        let body = addon_info_body(&addons, &banned);
+        let blizzard_entry = 1 + 1 + 1 + 4 + 1;
+        let other_entry = blizzard_entry + BLIZZARD_ADDON_PUBLIC_KEY.len();
+        let banned_entry = 4 + 16 + 16 + 4 + 4;
+        assert_eq!(body.len(), blizzard_entry + other_entry + 4 + 2 * banned_entry);
+        assert_eq!(&body[blizzard_entry + 3..blizzard_entry + 3 + 256], &BLIZZARD_ADDON_PUBLIC_KEY);
+        assert_eq!(&body[blizzard_entry + other_entry..][..4], &2u32.to_le_bytes());
+    }
+
+    #[test]
+    fn large_packets_get_a_three_byte_size() {
+        let (packet, header_size) = server_packet(SMSG_ADDON_INFO_OPCODE, &[0; 10]);
+        assert_eq!(header_size, 4);
+        assert_eq!(&packet[..4], &[0, 12, 0xEF, 0x02]);
+
+        let (packet, header_size) = server_packet(SMSG_ADDON_INFO_OPCODE, &[0; 0x8000]);
+        assert_eq!(header_size, 5);
+        assert_eq!(&packet[..3], &[0x80, 0x80, 0x02]);
+    }
+}
@@ -0,0 +1,138 @@
+//! Optional HTTP endpoint for community armory websites. `GET /character/<name>` answers with the character's
+//! public profile as JSON: level, race, class and equipment with enchants. Websites get what they need to show
+//! a character without access to the realm database. Off unless ARMORY_BIND is set.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use smol::io::{AsyncReadExt, AsyncWriteExt};
+use smol::net::{TcpListener, TcpStream};
+use wrath_realm_db::RealmDatabase;
+
+use crate::prelude::*;
+
+#[derive(Serialize)]
+pub struct ArmoryProfile {
+    pub name: String,
+    pub level: u8,
+    pub race: u8,
+    pub class: u8,
+    pub gender: u8,
+    pub equipment: Vec<ArmoryItem>,
+}
+
+#[derive(Serialize)]
+pub struct ArmoryItem {
+    pub slot: u8,
+    pub item: u32,
+    pub enchant: Option<u32>,
+}
+
+pub async fn load_profile(realm_db: &RealmDatabase, name: &str) -> Result<Option<ArmoryProfile>> {
+    let Some(character) = realm_db.get_armory_character(name).await? else {
+        return Ok(None);
+    };
+
+    let equipment = realm_db
+        .get_armory_equipment(character.id)
+        .await?
+        .into_iter()
+        .filter_map(|equipped| {
+            Some(ArmoryItem {
+                slot: equipped.slot_id,
+                item: equipped.item?,
+                enchant: equipped.enchant.filter(|&enchant| enchant != 0),
+            })
+        })
+        .collect();
+
+    Ok(Some(ArmoryProfile {
+        name: character.name,
+        level: character.level,
+        race: character.race,
+        class: character.class,
+        gender: character.gender,
+        equipment,
+    }))
+}
+
+pub fn bind_address_from_env() -> Option<String> {
+    std::env::var("ARMORY_BIND").ok().filter(|bind| !bind.is_empty())
+}
+
+pub async fn serve_armory(bind: String, realm_db: Arc<RealmDatabase>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(&bind).await?;
+    info!("Armory endpoint listening on {}", bind);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let realm_db = realm_db.clone();
+        smol::spawn(async move {
+            if let Err(e) = answer_request(stream, &realm_db).await {
+                warn!("Failed to answer armory request: {}", e);
+            }
+        })
+        .detach();
+    }
+}
+
+async fn answer_request(mut stream: TcpStream, realm_db: &RealmDatabase) -> Result<()> {
+    //Only the request line matters, armory requests are plain GETs
+    let mut buf = [0u8; 1024];
+    let read = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+    let mut request_line = request.split_whitespace();
+    let (method, path) = (request_line.next().unwrap_or(""), request_line.next().unwrap_or(""));
+
+    let (status, body) = match (method, character_name_from_path(path)) {
+        ("GET", Some(name)) => match load_profile(realm_db, &name).await {
+            Ok(Some(profile)) => ("200 OK", serde_json::to_string(&profile)?),
+            Ok(None) => ("404 Not Found", r#"{"error":"no such character"}"#.to_string()),
+            Err(e) => {
+                warn!("Armory lookup of {} failed: {}", name, e);
+                ("500 Internal Server Error", r#"{"error":"lookup failed"}"#.to_string())
+            }
+        },
+        ("GET", None) => ("404 Not Found", r#"{"error":"use /character/<name>"}"#.to_string()),
+        _ => ("405 Method Not Allowed", r#"{"error":"only GET is supported"}"#.to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+//Names with accents arrive percent-encoded, e.g. /character/J%C3%A4ina
+fn character_name_from_path(path: &str) -> Option<String> {
+    let encoded = path.strip_prefix("/character/")?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' && tail.len() >= 2 {
+            let hex = std::str::from_utf8(&tail[..2]).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok().filter(|name| !name.is_empty() && !name.contains('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn character_names_are_decoded_from_the_path() {
+        assert_eq!(character_name_from_path("/character/Arthas").as_deref(), Some("Arthas"));
+        assert_eq!(character_name_from_path("/character/J%C3%A4ina").as_deref(), Some("Jäina"));
+        assert_eq!(character_name_from_path("/character/"), None);
+        assert_eq!(character_name_from_path("/character/a/b"), None);
+        assert_eq!(character_name_from_path("/guild/Horde"), None);
+    }
+}
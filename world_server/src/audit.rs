@@ -0,0 +1,107 @@
+//! Economy audit trail. Every event that creates, destroys or moves items or money between
+//! characters ends up in the append-only `character_audit` table, so that dupes can be traced
+//! back to where they came from. GM commands that teleport or ban are recorded alongside them.
+use crate::prelude::*;
+use wrath_realm_db::{character_audit::DBCharacterAuditEntry, RealmDatabase};
+
+#[derive(Clone, Copy, Debug)]
+pub enum AuditEvent {
+    ItemCreated { item_id: u32, count: u32 },
+    ItemDestroyed { item_id: u32, count: u32 },
+    ItemTraded { item_id: u32, count: u32 },
+    ItemMailed { item_id: u32, count: u32 },
+    MoneyGained(u32),
+    MoneySpent(u32),
+    Teleported { map: u32 },
+    AccountBanned { account_id: u32 },
+    AccountUnbanned { account_id: u32 },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum AuditSource {
+    Unknown,
+    Loot { creature_entry: u32 },
+    GmCommand,
+    Trade { partner: Guid },
+    Mail { other_party: Guid },
+    Vendor { creature_entry: u32 },
+    Quest { quest_id: u32 },
+    Player,
+    Gathering { gameobject_entry: u32 },
+}
+
+impl AuditEvent {
+    fn event_type(&self) -> u8 {
+        match self {
+            AuditEvent::ItemCreated { .. } => 0,
+            AuditEvent::ItemDestroyed { .. } => 1,
+            AuditEvent::ItemTraded { .. } => 2,
+            AuditEvent::ItemMailed { .. } => 3,
+            AuditEvent::MoneyGained(_) => 4,
+            AuditEvent::MoneySpent(_) => 5,
+            AuditEvent::Teleported { .. } => 6,
+            AuditEvent::AccountBanned { .. } => 7,
+            AuditEvent::AccountUnbanned { .. } => 8,
+        }
+    }
+
+    //(item_id, item_count, money, target_id)
+    fn amounts(&self) -> (u32, u32, u32, u32) {
+        match *self {
+            AuditEvent::ItemCreated { item_id, count }
+            | AuditEvent::ItemDestroyed { item_id, count }
+            | AuditEvent::ItemTraded { item_id, count }
+            | AuditEvent::ItemMailed { item_id, count } => (item_id, count, 0, 0),
+            AuditEvent::MoneyGained(money) | AuditEvent::MoneySpent(money) => (0, 0, money, 0),
+            AuditEvent::Teleported { map } => (0, 0, 0, map),
+            AuditEvent::AccountBanned { account_id } | AuditEvent::AccountUnbanned { account_id } => (0, 0, 0, account_id),
+        }
+    }
+}
+
+impl AuditSource {
+    fn source_type_and_id(&self) -> (u8, u64) {
+        match *self {
+            AuditSource::Unknown => (0, 0),
+            AuditSource::Loot { creature_entry } => (1, creature_entry as u64),
+            AuditSource::GmCommand => (2, 0),
+            AuditSource::Trade { partner } => (3, partner.guid()),
+            AuditSource::Mail { other_party } => (4, other_party.guid()),
+            AuditSource::Vendor { creature_entry } => (5, creature_entry as u64),
+            AuditSource::Quest { quest_id } => (6, quest_id as u64),
+            AuditSource::Player => (7, 0),
+            AuditSource::Gathering { gameobject_entry } => (8, gameobject_entry as u64),
+        }
+    }
+}
+
+pub async fn log_audit_event(realm_db: &RealmDatabase, account_id: u32, character_guid: Guid, event: AuditEvent, source: AuditSource) -> Result<()> {
+    let (item_id, item_count, money, target_id) = event.amounts();
+    let (source_type, source_id) = source.source_type_and_id();
+    let time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+
+    trace!("Audit: account {} character {} {:?} from {:?}", account_id, character_guid, event, source);
+
+    realm_db
+        .insert_character_audit_entry(&DBCharacterAuditEntry {
+            time,
+            account_id,
+            character_id: character_guid.guid() as u32,
+            event_type: event.event_type(),
+            item_id,
+            item_count,
+            money,
+            target_id,
+            source_type,
+            source_id,
+        })
+        .await
+}
+
+//GM commands are audited before they take effect, and a failed audit write is logged instead of
+//aborting the command halfway through
+pub async fn log_gm_command(realm_db: &RealmDatabase, account_id: u32, character_guid: Guid, event: AuditEvent) {
+    if let Err(e) = log_audit_event(realm_db, account_id, character_guid, event, AuditSource::GmCommand).await {
+        error!("Failed to audit {:?} by character {}: {}", event, character_guid, e);
+    }
+}
@@ -1,19 +1,21 @@
 use crate::prelude::*;
 use podio::{BigEndian, WritePodExt};
 use smol::net::UdpSocket;
+use wrath_common::config;
 
 pub async fn auth_server_heartbeats() -> Result<()> {
     let socket = UdpSocket::bind("127.0.0.1:0").await?;
     socket.connect("127.0.0.1:1234").await?;
     let num_players_online = 10u32;
 
-    info!("My realm ID = {}", std::env::var("REALM_ID")?);
+    let realm_id: u8 = config::required("REALM_ID")?;
+    info!("My realm ID = {}", realm_id);
     loop {
         std::thread::sleep(std::time::Duration::from_secs(5));
         let buf = Vec::<u8>::new();
         let mut writer = std::io::Cursor::new(buf);
         writer.write_u8(0u8)?; //HEARTBEAT
-        writer.write_u8(std::env::var("REALM_ID")?.parse()?)?; //Realm ID
+        writer.write_u8(realm_id)?; //Realm ID
         writer.write_u32::<BigEndian>(num_players_online)?;
 
         socket.send(&writer.into_inner()).await?;
@@ -0,0 +1,109 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::{character::character_manager::CharacterManager, localization::ClientLocale, prelude::*};
+use wrath_realm_db::{autobroadcast::DBAutoBroadcast, RealmDatabase};
+
+const DEFAULT_INTERVAL_SECONDS: f32 = 600.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AutoBroadcastDisplay {
+    Chat,
+    Notification,
+    Both,
+}
+
+impl From<u8> for AutoBroadcastDisplay {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => AutoBroadcastDisplay::Notification,
+            2 => AutoBroadcastDisplay::Both,
+            _ => AutoBroadcastDisplay::Chat,
+        }
+    }
+}
+
+//Periodically sends the messages from the autobroadcast table, one after the other
+pub struct AutoBroadcaster {
+    enabled: Arc<AtomicBool>,
+    interval_seconds: f32,
+    display: AutoBroadcastDisplay,
+    cooldown: f32,
+    next_index: usize,
+}
+
+impl AutoBroadcaster {
+    pub fn from_env() -> Self {
+        let enabled = std::env::var("AUTOBROADCAST_ENABLED")
+            .ok()
+            .and_then(|v| v.parse::<u8>().ok())
+            .is_some_and(|v| v == 1);
+        let interval_seconds = std::env::var("AUTOBROADCAST_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<f32>().ok())
+            .filter(|&v| v > 0.0)
+            .unwrap_or(DEFAULT_INTERVAL_SECONDS);
+        let display = std::env::var("AUTOBROADCAST_DISPLAY")
+            .ok()
+            .and_then(|v| v.parse::<u8>().ok())
+            .map_or(AutoBroadcastDisplay::Chat, AutoBroadcastDisplay::from);
+
+        Self {
+            enabled: Arc::new(AtomicBool::new(enabled)),
+            interval_seconds,
+            display,
+            cooldown: interval_seconds,
+            next_index: 0,
+        }
+    }
+
+    //Shared with the console so broadcasts can be toggled at runtime
+    pub fn get_enabled_flag(&self) -> Arc<AtomicBool> {
+        self.enabled.clone()
+    }
+
+    pub async fn tick(&mut self, delta_time: f32, character_manager: &CharacterManager, realm_db: &RealmDatabase) -> Result<()> {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        self.cooldown -= delta_time;
+        if self.cooldown > 0.0 {
+            return Ok(());
+        }
+        self.cooldown = self.interval_seconds;
+
+        //Read the table every time, so edits don't require a restart
+        let broadcasts = realm_db.get_autobroadcasts().await?;
+        //Translations share the id of the message they translate, rows come sorted by id
+        let mut ids: Vec<u32> = broadcasts.iter().map(|broadcast| broadcast.id).collect();
+        ids.dedup();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let id = ids[self.next_index % ids.len()];
+        self.next_index = self.next_index.wrapping_add(1);
+        let translations: Vec<&DBAutoBroadcast> = broadcasts.iter().filter(|broadcast| broadcast.id == id).collect();
+
+        for character in character_manager.get_all_characters() {
+            let locale = character.get_client_locale().code();
+            let Some(broadcast) = translations
+                .iter()
+                .find(|broadcast| broadcast.locale == locale)
+                .or_else(|| translations.iter().find(|broadcast| broadcast.locale == ClientLocale::EnUs.code()))
+            else {
+                continue;
+            };
+
+            if self.display != AutoBroadcastDisplay::Notification {
+                handlers::send_system_message_to_character(character, &broadcast.message).await?;
+            }
+            if self.display != AutoBroadcastDisplay::Chat {
+                handlers::send_notification_to_character(character, &broadcast.message).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+//! Optional JSON over WebSocket gateway for headless clients.
+//!
+//! Integration tests and community tools can drive a character through this without implementing the
+//! WoW packet framing, header encryption and SRP handshake. Each bot connection is registered with the
+//! client manager exactly like a game client: requests are turned into `ClientOpcodeMessage`s and every
+//! `ServerEvent` the manager sends back is written out as a JSON object.
+//!
+//! Bots authenticate with the shared `BOT_GATEWAY_TOKEN` instead of a password, so the gateway is off
+//! unless `BOT_GATEWAY_BIND` is set and should only ever listen on a trusted interface.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use async_tungstenite::tungstenite::Message;
+use async_tungstenite::WebSocketStream;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use smol::net::{TcpListener, TcpStream};
+use wow_world_messages::wrath::opcodes::ClientOpcodeMessage;
+use wow_world_messages::wrath::{CMSG_MESSAGECHAT_ChatType, Language, MSG_MOVE_TELEPORT_ACK_Client, CMSG_MESSAGECHAT, CMSG_PING, CMSG_PLAYER_LOGIN};
+use wrath_auth_db::AuthDatabase;
+use wrath_common::GmLevel;
+
+use crate::connection::events::{ClientEvent, ServerEvent};
+use crate::localization::ClientLocale;
+use crate::prelude::*;
+
+//Bots claim to be the 3.3.5a client, the only build the server supports
+const BOT_CLIENT_BUILD: u32 = 12340;
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BotRequest {
+    Login { token: String, account: String },
+    CharEnum,
+    PlayerLogin { guid: u64 },
+    //Messages starting with a dot are GM commands, same as for a real client
+    Say { message: String },
+    Yell { message: String },
+    Whisper { target: String, message: String },
+    Ping { sequence_id: u32 },
+    TeleportAck { movement_counter: u32 },
+    WorldportAck,
+    Logout,
+}
+
+enum GatewayEvent {
+    Bot(Option<Message>),
+    Server(ServerEvent),
+}
+
+pub struct BotGatewayConfig {
+    bind_address: String,
+    token: String,
+}
+
+impl BotGatewayConfig {
+    //None unless both the bind address and the token are set, an open gateway would let anyone play any account
+    pub fn from_env() -> Option<Self> {
+        let bind_address = std::env::var("BOT_GATEWAY_BIND").ok().filter(|v| !v.is_empty())?;
+        let token = std::env::var("BOT_GATEWAY_TOKEN").ok().filter(|v| !v.is_empty());
+        let Some(token) = token else {
+            warn!("BOT_GATEWAY_BIND is set without a BOT_GATEWAY_TOKEN, not starting the bot gateway");
+            return None;
+        };
+        Some(Self { bind_address, token })
+    }
+}
+
+pub async fn accept_bot_connections(config: BotGatewayConfig, auth_db: Arc<AuthDatabase>, client_manager_sender: flume::Sender<ClientEvent>) {
+    if let Err(e) = accept_bot_connections_impl(config, auth_db, client_manager_sender).await {
+        error!("Error in bot_gateway::accept_bot_connections: {e:?}");
+    }
+}
+
+async fn accept_bot_connections_impl(
+    config: BotGatewayConfig,
+    auth_db: Arc<AuthDatabase>,
+    client_manager_sender: flume::Sender<ClientEvent>,
+) -> Result<()> {
+    let listener = TcpListener::bind(&config.bind_address).await?;
+    info!("Bot gateway listening on {}", config.bind_address);
+    let token: Arc<str> = config.token.into();
+
+    let mut incoming = listener.incoming();
+    while let Some(stream) = incoming.next().await {
+        let stream = stream?;
+        let addr = stream.peer_addr()?;
+        let (auth_db, sender, token) = (auth_db.clone(), client_manager_sender.clone(), token.clone());
+        smol::spawn(async move {
+            if let Err(e) = run_bot_connection(stream, addr, &token, auth_db, sender.clone()).await {
+                warn!("Bot connection {addr} closed: {e:?}");
+            }
+            sender.send_async(ClientEvent::Disconnected { addr }).await.unwrap_or_else(|e| {
+                error!("Error disconnecting bot {addr}: {e:?}");
+            });
+        })
+        .detach();
+    }
+    Ok(())
+}
+
+async fn run_bot_connection(
+    stream: TcpStream,
+    addr: SocketAddr,
+    token: &str,
+    auth_db: Arc<AuthDatabase>,
+    client_manager_sender: flume::Sender<ClientEvent>,
+) -> Result<()> {
+    let mut websocket = async_tungstenite::accept_async(stream).await?;
+    info!("New bot connection from {addr}");
+
+    //The first request has to be the login, nothing reaches the client manager before that
+    let request = match websocket.next().await {
+        Some(message) => parse_request(&message?)?,
+        None => return Ok(()),
+    };
+    let BotRequest::Login { token: given_token, account } = request else {
+        bail!("Bot sent {:?} before logging in", request);
+    };
+    if given_token != token {
+        send_json(&mut websocket, json!({ "type": "login_failed", "reason": "invalid token" })).await?;
+        bail!("Bot tried to log in to {} with an invalid token", account);
+    }
+    let Some(db_account) = auth_db.get_account_by_username(&account).await? else {
+        send_json(&mut websocket, json!({ "type": "login_failed", "reason": "unknown account" })).await?;
+        bail!("Bot tried to log in to unknown account {}", account);
+    };
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+    if crate::handlers::get_account_ban_status(&auth_db, &db_account, now).await?.is_banned() {
+        send_json(&mut websocket, json!({ "type": "login_failed", "reason": "banned account" })).await?;
+        bail!("Bot tried to log in to banned or suspended account {}", account);
+    }
+
+    let (connection_sender, receiver) = flume::unbounded();
+    client_manager_sender
+        .send_async(ClientEvent::Connected {
+            addr,
+            account_id: db_account.id,
+            client_build: BOT_CLIENT_BUILD,
+            locale: ClientLocale::from_code(&db_account.locale),
+            gm_level: GmLevel::from_db(db_account.gm_level),
+            connection_sender,
+        })
+        .await?;
+    send_json(&mut websocket, json!({ "type": "login_ok", "account_id": db_account.id })).await?;
+
+    //Movement acks have to name the character, bots only say which one once when entering the world
+    let mut active_character = None;
+    loop {
+        let event = smol::future::race(async { GatewayEvent::Bot(websocket.next().await.transpose().ok().flatten()) }, async {
+            receiver.recv_async().await.map_or(GatewayEvent::Bot(None), GatewayEvent::Server)
+        })
+        .await;
+
+        match event {
+            GatewayEvent::Bot(None) => break,
+            GatewayEvent::Bot(Some(Message::Close(_))) => break,
+            GatewayEvent::Bot(Some(message @ Message::Text(_))) => {
+                let packet = match parse_request(&message).and_then(|request| into_client_packet(request, &mut active_character)) {
+                    Ok(packet) => packet,
+                    Err(e) => {
+                        send_json(&mut websocket, json!({ "type": "error", "reason": e.to_string() })).await?;
+                        continue;
+                    }
+                };
+                client_manager_sender.send_async(ClientEvent::Message { addr, packet }).await?;
+            }
+            GatewayEvent::Bot(Some(_)) => {}
+            GatewayEvent::Server(ServerEvent::Disconnect) => break,
+            GatewayEvent::Server(server_event) => send_json(&mut websocket, server_event_to_json(&server_event)).await?,
+        }
+    }
+
+    websocket.close(None).await.ok();
+    Ok(())
+}
+
+fn parse_request(message: &Message) -> Result<BotRequest> {
+    let text = message.to_text()?;
+    Ok(serde_json::from_str(text)?)
+}
+
+fn into_client_packet(request: BotRequest, active_character: &mut Option<Guid>) -> Result<ClientOpcodeMessage> {
+    let chat = |chat_type, message| {
+        ClientOpcodeMessage::CMSG_MESSAGECHAT(CMSG_MESSAGECHAT {
+            chat_type,
+            language: Language::Universal,
+            message,
+        })
+    };
+
+    Ok(match request {
+        BotRequest::Login { .. } => bail!("Already logged in"),
+        BotRequest::CharEnum => ClientOpcodeMessage::CMSG_CHAR_ENUM,
+        BotRequest::PlayerLogin { guid } => {
+            *active_character = Some(Guid::new(guid));
+            ClientOpcodeMessage::CMSG_PLAYER_LOGIN(CMSG_PLAYER_LOGIN { guid: Guid::new(guid) })
+        }
+        BotRequest::Say { message } => chat(CMSG_MESSAGECHAT_ChatType::Say, message),
+        BotRequest::Yell { message } => chat(CMSG_MESSAGECHAT_ChatType::Yell, message),
+        BotRequest::Whisper { target, message } => chat(CMSG_MESSAGECHAT_ChatType::Whisper { target_player: target }, message),
+        BotRequest::Ping { sequence_id } => ClientOpcodeMessage::CMSG_PING(CMSG_PING {
+            sequence_id,
+            round_time_in_ms: 0,
+        }),
+        BotRequest::TeleportAck { movement_counter } => {
+            let Some(guid) = *active_character else {
+                bail!("No character has entered the world yet");
+            };
+            ClientOpcodeMessage::MSG_MOVE_TELEPORT_ACK(MSG_MOVE_TELEPORT_ACK_Client {
+                guid,
+                movement_counter,
+                time: 0,
+            })
+        }
+        BotRequest::WorldportAck => ClientOpcodeMessage::MSG_MOVE_WORLDPORT_ACK,
+        BotRequest::Logout => ClientOpcodeMessage::CMSG_LOGOUT_REQUEST,
+    })
+}
+
+//Every event is reported by its opcode name. The few that a bot has to act on or that tests usually
+//assert on carry their contents as well.
+fn server_event_to_json(event: &ServerEvent) -> serde_json::Value {
+    let opcode = event.to_string();
+    match event {
+        ServerEvent::CharEnum(msg) => json!({
+            "type": "event",
+            "opcode": opcode,
+            "characters": msg.characters.iter().map(|character| json!({
+                "guid": character.guid.guid(),
+                "name": character.name,
+                "level": character.level,
+            })).collect::<Vec<_>>(),
+        }),
+        ServerEvent::MessageChat(msg) => json!({
+            "type": "event",
+            "opcode": opcode,
+            "sender": msg.sender.guid(),
+            "message": msg.message,
+        }),
+        ServerEvent::MoveTeleportAck(msg) => json!({
+            "type": "event",
+            "opcode": opcode,
+            "movement_counter": msg.movement_counter,
+            "x": msg.info.position.x,
+            "y": msg.info.position.y,
+            "z": msg.info.position.z,
+        }),
+        ServerEvent::NewWorld(msg) => json!({
+            "type": "event",
+            "opcode": opcode,
+            "map": msg.map.as_int(),
+            "x": msg.position.x,
+            "y": msg.position.y,
+            "z": msg.position.z,
+        }),
+        _ => json!({ "type": "event", "opcode": opcode }),
+    }
+}
+
+async fn send_json(websocket: &mut WebSocketStream<TcpStream>, value: serde_json::Value) -> Result<()> {
+    websocket.send(Message::Text(value.to_string().into())).await?;
+    Ok(())
+}
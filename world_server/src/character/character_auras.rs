@@ -0,0 +1,197 @@
+//! Auras the character carries, put there by spells with an apply aura effect. Durations and periodic
+//! effects tick with the character. What happens on a periodic tick and the packets that show auras to the
+//! client are handled by `spell::auras`, this only keeps the state.
+
+use crate::prelude::*;
+
+//The client has this many aura slots per unit
+const MAX_AURA_SLOTS: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PeriodicKind {
+    Damage,
+    Heal,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Periodic {
+    pub kind: PeriodicKind,
+    //Seconds between ticks
+    pub period: f32,
+    timer: f32,
+}
+
+impl Periodic {
+    pub fn new(kind: PeriodicKind, period: f32) -> Self {
+        Self { kind, period, timer: period }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Aura {
+    pub slot: u8,
+    pub spell_id: u32,
+    pub caster: Guid,
+    pub caster_level: u8,
+    //The aura type from Spell.dbc, decides what the aura does
+    pub aura_type: u32,
+    pub amount: i32,
+    pub negative: bool,
+    pub periodic: Option<Periodic>,
+    //Both None for auras that last until they are cancelled
+    pub duration: Option<f32>,
+    pub remaining: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AuraTick {
+    pub spell_id: u32,
+    pub caster: Guid,
+    pub kind: PeriodicKind,
+    pub amount: i32,
+}
+
+#[derive(Default)]
+pub(super) struct AuraState {
+    auras: Vec<Aura>,
+    //Filled by the tick, emptied by the spell code once it handled them
+    pending_ticks: Vec<AuraTick>,
+    expired: Vec<Aura>,
+    //Characters that just came into view, we still need to be told about their auras
+    pending_snapshots: Vec<Guid>,
+}
+
+pub struct AuraApplication {
+    pub spell_id: u32,
+    pub caster: Guid,
+    pub caster_level: u8,
+    pub aura_type: u32,
+    pub amount: i32,
+    pub negative: bool,
+    pub periodic: Option<Periodic>,
+    pub duration: Option<f32>,
+}
+
+impl super::Character {
+    //The same spell from the same caster refreshes the aura in its old slot instead of stacking it.
+    //Returns None if every slot is taken.
+    pub fn apply_aura(&mut self, application: AuraApplication) -> Option<Aura> {
+        let auras = &mut self.aura_state.auras;
+        let existing = auras
+            .iter()
+            .position(|aura| aura.spell_id == application.spell_id && aura.caster == application.caster);
+        let slot = match existing {
+            Some(index) => auras.remove(index).slot,
+            None => (0..MAX_AURA_SLOTS as u8).find(|&slot| auras.iter().all(|aura| aura.slot != slot))?,
+        };
+
+        let aura = Aura {
+            slot,
+            spell_id: application.spell_id,
+            caster: application.caster,
+            caster_level: application.caster_level,
+            aura_type: application.aura_type,
+            amount: application.amount,
+            negative: application.negative,
+            periodic: application.periodic,
+            duration: application.duration,
+            remaining: application.duration,
+        };
+        auras.push(aura);
+        Some(aura)
+    }
+
+    //Returns the auras that were removed, their slots are free again
+    pub fn remove_auras_from_spell(&mut self, spell_id: u32) -> Vec<Aura> {
+        let auras = &mut self.aura_state.auras;
+        let removed = auras.iter().filter(|aura| aura.spell_id == spell_id).copied().collect();
+        auras.retain(|aura| aura.spell_id != spell_id);
+        removed
+    }
+
+    pub fn get_auras(&self) -> &[Aura] {
+        &self.aura_state.auras
+    }
+
+    pub fn has_aura(&self, spell_id: u32) -> bool {
+        self.aura_state.auras.iter().any(|aura| aura.spell_id == spell_id)
+    }
+
+    pub fn has_pending_aura_events(&self) -> bool {
+        let state = &self.aura_state;
+        !state.pending_ticks.is_empty() || !state.expired.is_empty()
+    }
+
+    pub fn take_aura_ticks(&mut self) -> Vec<AuraTick> {
+        std::mem::take(&mut self.aura_state.pending_ticks)
+    }
+
+    pub fn take_expired_auras(&mut self) -> Vec<Aura> {
+        std::mem::take(&mut self.aura_state.expired)
+    }
+
+    pub(super) fn queue_aura_snapshot(&mut self, unit: Guid) {
+        self.aura_state.pending_snapshots.push(unit);
+    }
+
+    pub fn take_pending_aura_snapshots(&mut self) -> Vec<Guid> {
+        std::mem::take(&mut self.aura_state.pending_snapshots)
+    }
+
+    pub(super) fn tick_auras(&mut self, delta_time: f32) {
+        let state = &mut self.aura_state;
+        for aura in state.auras.iter_mut() {
+            if let Some(periodic) = aura.periodic.as_mut() {
+                periodic.timer -= delta_time;
+                //A long frame can be worth more than one tick
+                while periodic.timer <= 0.0 && periodic.period > 0.0 {
+                    periodic.timer += periodic.period;
+                    state.pending_ticks.push(AuraTick {
+                        spell_id: aura.spell_id,
+                        caster: aura.caster,
+                        kind: periodic.kind,
+                        amount: aura.amount,
+                    });
+                }
+            }
+            if let Some(remaining) = aura.remaining.as_mut() {
+                *remaining -= delta_time;
+                if *remaining <= 0.0 {
+                    state.expired.push(*aura);
+                }
+            }
+        }
+        state.auras.retain(|aura| aura.remaining.map_or(true, |remaining| remaining > 0.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Character;
+    use super::*;
+
+    #[test]
+    fn periodic_auras_tick_until_they_expire() {
+        let (sender, _receiver) = flume::unbounded();
+        let mut character = Character::new(sender, Guid::new(1));
+        let application = AuraApplication {
+            spell_id: 172,
+            caster: Guid::new(2),
+            caster_level: 4,
+            aura_type: 3,
+            amount: 10,
+            negative: true,
+            periodic: Some(Periodic::new(PeriodicKind::Damage, 3.0)),
+            duration: Some(12.0),
+        };
+        let slot = character.apply_aura(application).unwrap().slot;
+
+        character.tick_auras(6.0);
+        assert_eq!(character.take_aura_ticks().len(), 2);
+        character.tick_auras(6.0);
+        assert_eq!(character.take_aura_ticks().len(), 2);
+        let expired: Vec<u8> = character.take_expired_auras().iter().map(|aura| aura.slot).collect();
+        assert_eq!(expired, vec![slot]);
+        assert!(!character.has_aura(172));
+    }
+}
@@ -0,0 +1,141 @@
+use wow_world_messages::wrath::SpellCastTargets;
+
+//Every hit taken while casting adds this much to the cast time, at most MAX_PUSHBACKS times per cast
+const CAST_PUSHBACK_SECONDS: f32 = 0.5;
+//Channeled spells lose this part of their full duration per hit instead
+const CHANNEL_PUSHBACK_FRACTION: f32 = 0.25;
+const MAX_PUSHBACKS: u8 = 2;
+const NUM_SPELL_SCHOOLS: usize = 7;
+
+struct ActiveCast {
+    spell_id: u32,
+    cast_count: u8,
+    targets: SpellCastTargets,
+    school_mask: u32,
+    duration: f32,
+    remaining: f32,
+    channeled: bool,
+    pushbacks: u8,
+}
+
+#[derive(Default)]
+pub(super) struct CastingState {
+    current: Option<ActiveCast>,
+    //Seconds left on the lockout of every school, indexed by the school's bit in the school mask
+    school_lockouts: [f32; NUM_SPELL_SCHOOLS],
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CastPushback {
+    //The cast bar grows by this many milliseconds
+    Delayed { delay_ms: u32 },
+    //The channel now ends after this many milliseconds
+    ChannelShortened { remaining_ms: u32 },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InterruptedCast {
+    pub spell_id: u32,
+    pub cast_count: u8,
+}
+
+pub struct CompletedCast {
+    pub spell_id: u32,
+    pub cast_count: u8,
+    pub targets: SpellCastTargets,
+}
+
+impl super::Character {
+    //The spell pipeline calls this once the cast passed validation and has a cast time
+    pub fn start_cast(&mut self, spell_id: u32, cast_count: u8, targets: SpellCastTargets, school_mask: u32, duration: f32, channeled: bool) {
+        self.casting_state.current = Some(ActiveCast {
+            spell_id,
+            cast_count,
+            targets,
+            school_mask,
+            duration,
+            remaining: duration,
+            channeled,
+            pushbacks: 0,
+        });
+    }
+
+    pub fn is_casting(&self) -> bool {
+        self.casting_state.current.is_some()
+    }
+
+    //A spell is locked out if any of its schools is, multi-school spells can't sneak past an interrupt
+    pub fn is_spell_school_locked(&self, school_mask: u32) -> bool {
+        self.casting_state
+            .school_lockouts
+            .iter()
+            .enumerate()
+            .any(|(school, &remaining)| remaining > 0.0 && school_mask & (1 << school) != 0)
+    }
+
+    pub fn apply_cast_pushback(&mut self) -> Option<CastPushback> {
+        let cast = self.casting_state.current.as_mut()?;
+        if cast.pushbacks >= MAX_PUSHBACKS {
+            return None;
+        }
+        cast.pushbacks += 1;
+
+        if cast.channeled {
+            cast.remaining = (cast.remaining - cast.duration * CHANNEL_PUSHBACK_FRACTION).max(0.0);
+            Some(CastPushback::ChannelShortened {
+                remaining_ms: (cast.remaining * 1000.0) as u32,
+            })
+        } else {
+            //Pushback can't make the cast take longer than it would have from the start
+            let delay = CAST_PUSHBACK_SECONDS.min(cast.duration - cast.remaining);
+            cast.remaining += delay;
+            Some(CastPushback::Delayed {
+                delay_ms: (delay * 1000.0) as u32,
+            })
+        }
+    }
+
+    //Interrupt effects lock the schools of the interrupted spell, plain interrupts (moving, stuns) pass no lockout
+    pub fn interrupt_cast(&mut self, lockout_seconds: Option<f32>) -> Option<InterruptedCast> {
+        let cast = self.casting_state.current.take()?;
+        if let Some(lockout) = lockout_seconds {
+            for (school, remaining) in self.casting_state.school_lockouts.iter_mut().enumerate() {
+                if cast.school_mask & (1 << school) != 0 {
+                    *remaining = remaining.max(lockout);
+                }
+            }
+        }
+
+        Some(InterruptedCast {
+            spell_id: cast.spell_id,
+            cast_count: cast.cast_count,
+        })
+    }
+
+    fn has_completed_cast(&self) -> bool {
+        self.casting_state.current.as_ref().is_some_and(|cast| cast.remaining <= 0.0)
+    }
+
+    //Hands the cast back to the spell pipeline once its cast time has run out
+    pub fn take_completed_cast(&mut self) -> Option<CompletedCast> {
+        if !self.has_completed_cast() {
+            return None;
+        }
+        let cast = self.casting_state.current.take()?;
+        Some(CompletedCast {
+            spell_id: cast.spell_id,
+            cast_count: cast.cast_count,
+            targets: cast.targets,
+        })
+    }
+
+    pub(super) fn tick_casting(&mut self, delta_time: f32) {
+        let state = &mut self.casting_state;
+        if let Some(cast) = state.current.as_mut() {
+            cast.remaining = (cast.remaining - delta_time).max(0.0);
+        }
+        for remaining in state.school_lockouts.iter_mut() {
+            *remaining = (*remaining - delta_time).max(0.0);
+        }
+    }
+}
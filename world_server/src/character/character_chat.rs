@@ -0,0 +1,18 @@
+use std::time::{Duration, Instant};
+
+use crate::chat::moderation::{ChatModeration, ChatVerdict};
+
+impl super::Character {
+    pub fn moderate_chat_message(&mut self, moderation: &ChatModeration, message: &str) -> ChatVerdict {
+        moderation.moderate(&mut self.chat_moderation_state, message, Instant::now())
+    }
+
+    //Hooks for GM mutes, these share the state with spam mutes
+    pub fn mute_chat(&mut self, duration: Duration) {
+        self.chat_moderation_state.mute(duration);
+    }
+
+    pub fn unmute_chat(&mut self) {
+        self.chat_moderation_state.unmute();
+    }
+}
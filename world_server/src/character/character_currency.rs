@@ -0,0 +1,167 @@
+//! Honor, arena points and the tokens shown in the currency tab, like emblems and marks. Honor and arena
+//! points have update fields of their own. Tokens are item stacks in hidden inventory slots, one slot per
+//! currency, which the client counts for the currency tab; the counts themselves live in character_currency.
+
+use std::collections::HashMap;
+
+use wow_world_messages::wrath::{NewItemChatAlert, NewItemCreationType, NewItemSource, Object, UpdateItemBuilder, SMSG_ITEM_PUSH_RESULT};
+use wrath_realm_db::RealmDatabase;
+
+use super::character_inventory::{item_create_objects, INVENTORY_SLOT_BAG_0};
+use crate::connection::events::ServerEvent;
+use crate::data::DataStorage;
+use crate::item::Item;
+use crate::prelude::*;
+use crate::world::prelude::inventory::{CURRENCY_TOKEN_SLOTS_END, CURRENCY_TOKEN_SLOTS_START};
+
+//The items the client knows honor and arena points by
+pub const HONOR_POINTS: u32 = 43308;
+pub const ARENA_POINTS: u32 = 43307;
+
+const MAX_HONOR_POINTS: u32 = 75000;
+const MAX_ARENA_POINTS: u32 = 5000;
+
+#[derive(Default)]
+pub(super) struct CurrencyState {
+    //By currency item
+    counts: HashMap<u32, u32>,
+    //By token slot, only for tokens the character has any of
+    tokens: HashMap<u8, Item>,
+    //Bits from CurrencyTypes.dbc of every token the character ever had, they stay in the tab at 0
+    known_currencies: u64,
+}
+
+fn currency_cap(currency: u32) -> u32 {
+    match currency {
+        HONOR_POINTS => MAX_HONOR_POINTS,
+        ARENA_POINTS => MAX_ARENA_POINTS,
+        _ => u32::MAX,
+    }
+}
+
+fn token_slot(bit_index: u32) -> Option<u8> {
+    let slot = CURRENCY_TOKEN_SLOTS_START.checked_add(u8::try_from(bit_index).ok()?)?;
+    (slot <= CURRENCY_TOKEN_SLOTS_END).then_some(slot)
+}
+
+fn token_item(character_id: u32, slot: u8, currency: u32, count: u32) -> Item {
+    Item {
+        update_state: UpdateItemBuilder::new()
+            .set_object_guid(((character_id as u64) << 32 | slot as u64).into())
+            .set_object_entry(currency as i32)
+            .set_object_scale_x(1.0)
+            .set_item_owner(Guid::new(character_id as u64))
+            .set_item_contained(Guid::new(character_id as u64))
+            .set_item_stack_count(count as i32)
+            .finalize(),
+        permanent_enchant: 0,
+    }
+}
+
+impl super::Character {
+    pub(super) async fn load_currencies(&mut self, realm_db: &RealmDatabase, data_storage: &DataStorage) -> Result<()> {
+        let character_id = self.get_guid().guid() as u32;
+        for row in realm_db.get_character_currencies(character_id).await? {
+            if let Err(e) = self.set_currency(data_storage, row.currency, row.count) {
+                warn!("Character {} has currency it can't hold: {}", self.name, e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_currency(&self, currency: u32) -> u32 {
+        self.currency_state.counts.get(&currency).copied().unwrap_or(0)
+    }
+
+    //Adds amount, or takes it away when negative, within the currency's limits. Returns the new count.
+    pub async fn modify_currency(&mut self, realm_db: &RealmDatabase, data_storage: &DataStorage, currency: u32, amount: i64) -> Result<u32> {
+        let old_count = self.get_currency(currency);
+        let new_count = (old_count as i64 + amount).clamp(0, currency_cap(currency) as i64) as u32;
+        if new_count == old_count {
+            return Ok(old_count);
+        }
+
+        let token_slot = self.set_currency(data_storage, currency, new_count)?;
+        let character_id = self.get_guid().guid() as u32;
+        realm_db.set_character_currency(character_id, currency, new_count).await?;
+
+        //Honor and arena points only need their update fields, the client shows those changes by itself
+        let Some(slot) = token_slot else {
+            return Ok(new_count);
+        };
+        if let Some(item) = self.currency_state.tokens.get(&slot) {
+            Self::send_item_update(item, &self.connection_sender).await;
+        }
+        if new_count > old_count {
+            ServerEvent::ItemPushResult(SMSG_ITEM_PUSH_RESULT {
+                guid: self.get_guid(),
+                source: NewItemSource::FromNpc,
+                creation_type: NewItemCreationType::Received,
+                alert_chat: NewItemChatAlert::Show,
+                bag_slot: INVENTORY_SLOT_BAG_0,
+                item_slot: slot as u32,
+                item: currency,
+                item_suffix_factor: 0,
+                item_random_property_id: 0,
+                item_count: new_count - old_count,
+                item_count_in_inventory: new_count,
+            })
+            .send_to_character(self)
+            .await?;
+        }
+        Ok(new_count)
+    }
+
+    //Returns the token slot for tokens, None for honor and arena points
+    fn set_currency(&mut self, data_storage: &DataStorage, currency: u32, count: u32) -> Result<Option<u8>> {
+        match currency {
+            HONOR_POINTS => {
+                self.gameplay_data.set_player_field_honor_currency(count);
+                self.currency_state.counts.insert(currency, count);
+                return Ok(None);
+            }
+            ARENA_POINTS => {
+                self.gameplay_data.set_player_field_arena_currency(count);
+                self.currency_state.counts.insert(currency, count);
+                return Ok(None);
+            }
+            _ => {}
+        }
+
+        let bit_index = data_storage
+            .get_currency_bit_index(currency)
+            .ok_or_else(|| anyhow!("Item {} is not a currency", currency))?;
+        let slot = token_slot(bit_index).ok_or_else(|| anyhow!("Currency {} has no token slot", currency))?;
+        self.currency_state.counts.insert(currency, count);
+        self.currency_state.known_currencies |= 1 << bit_index;
+        self.gameplay_data.set_player_field_known_currencies(self.currency_state.known_currencies);
+
+        if count == 0 {
+            self.currency_state.tokens.remove(&slot);
+            self.update_inventory_field(slot, Guid::zero());
+        } else {
+            let character_id = self.get_guid().guid() as u32;
+            let item = token_item(character_id, slot, currency, count);
+            self.update_inventory_field(slot, item.update_state.object_guid().unwrap());
+            self.currency_state.tokens.insert(slot, item);
+        }
+        Ok(Some(slot))
+    }
+
+    pub(super) fn get_currency_token_create_objects(&self) -> Vec<Object> {
+        item_create_objects(self.currency_state.tokens.values())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_get_a_slot_by_their_bit() {
+        assert_eq!(token_slot(0), Some(CURRENCY_TOKEN_SLOTS_START));
+        assert_eq!(token_slot(31), Some(CURRENCY_TOKEN_SLOTS_END));
+        assert_eq!(token_slot(32), None);
+        assert_eq!(currency_cap(HONOR_POINTS), MAX_HONOR_POINTS);
+    }
+}
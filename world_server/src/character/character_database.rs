@@ -17,7 +17,10 @@ impl super::Character {
         let character_id = self.get_guid().guid() as u32;
         let realm_database = world.get_realm_database();
 
-        let db_entry = realm_database.get_character(character_id).await?;
+        let load_started = std::time::Instant::now();
+        let login_data = realm_database.get_character_login_data(character_id).await?;
+        trace!("Fetched database rows for character {} in {:?}", character_id, load_started.elapsed());
+        let db_entry = &login_data.character;
 
         //We don't properly store this in the DB, so try_from will fail because it's always 0
         let bind_area = Area::try_from(db_entry.bind_zone as u32).unwrap_or(Area::NorthshireAbbey);
@@ -49,11 +52,12 @@ impl super::Character {
         };
 
         self.name = db_entry.name.clone();
+        self.account_id = db_entry.account_id;
+        self.guild_id = Some(db_entry.guild_id).filter(|&guild_id| guild_id != 0);
 
-        self.tutorial_flags = TutorialFlags::from_database_entry(&db_entry)?;
-        let character_account_data = realm_database.get_character_account_data(character_id).await?;
+        self.tutorial_flags = TutorialFlags::from_database_entry(db_entry)?;
 
-        if character_account_data.is_empty() {
+        if login_data.account_data.is_empty() {
             handlers::create_empty_character_account_data_rows(&realm_database, character_id).await?;
         }
 
@@ -86,8 +90,10 @@ impl super::Character {
         self.gameplay_data.set_unit_health(100);
         self.gameplay_data.set_unit_maxhealth(100);
         self.gameplay_data.set_unit_level(1);
+        self.init_experience();
         self.gameplay_data.set_unit_factiontemplate(1);
         self.gameplay_data.set_object_scale_x(1.0f32);
+        self.init_class_power();
 
         //No playtime means it's our very first login
         self.needs_first_login = self.seconds_played_total == 0;
@@ -101,22 +107,31 @@ impl super::Character {
         //TODO: learning some skills might learn spells, those need to be checked too?
 
         //TODO: this should be loaded from the DB, it's a placeholder
+        //Pets and mounts are learned for the whole account
+        let account_spells = world
+            .get_account_data()
+            .get_collection(self.account_id)
+            .into_iter()
+            .flat_map(|collection| collection.known_spells());
+        let initial_spells: Vec<u32> = race_class.starter_spells().iter().copied().chain(account_spells).collect();
+        for &spell_id in &initial_spells {
+            self.learn_spell(spell_id);
+        }
         let msg = SMSG_INITIAL_SPELLS {
             unknown1: 0,
-            initial_spells: race_class
-                .starter_spells()
-                .iter()
-                .map(|x| InitialSpell { spell_id: *x, unknown1: 0 })
+            initial_spells: initial_spells
+                .into_iter()
+                .map(|spell_id| InitialSpell { spell_id, unknown1: 0 })
                 .collect(),
             cooldowns: vec![],
         };
         let event = ServerEvent::InitialSpells(msg);
         self.connection_sender.send_async(event).await?;
 
-        // Load items from DB (equipment + backpack); use None to avoid DB writes and send one bulk update afterward.
-        for equipment_item in realm_database.get_all_character_equipment(character_id).await? {
+        // Load items from DB (equipment, backpack and keyring); use None to avoid DB writes and send one bulk update afterward.
+        for equipment_item in &login_data.equipment {
             self.set_item(
-                Some(Item::from(&equipment_item)),
+                Some(Item::from(equipment_item)),
                 (equipment_item.slot_id, INVENTORY_SLOT_BAG_0),
                 None,
                 None,
@@ -125,6 +140,16 @@ impl super::Character {
             .expect("This should never fail in this context");
         }
 
+        self.load_equipment_sets(&realm_database).await?;
+        self.load_taxi_nodes(&realm_database).await?;
+        self.load_social(&realm_database).await?;
+        self.load_quests(&realm_database).await?;
+        self.load_reputations(&realm_database, data_storage).await?;
+        self.load_guild_notifications(&realm_database).await?;
+        self.load_queue_punishments(&realm_database).await?;
+        self.load_currencies(&realm_database, data_storage).await?;
+        self.load_recall_location(&realm_database).await?;
+
         // Collect equipment items
         let char_equipment = self.equipped_items.get_all_equipment();
         let mut all_items: Vec<Object> = char_equipment
@@ -142,8 +167,10 @@ impl super::Character {
             })
             .collect();
 
-        // Collect backpack items
+        // Collect backpack, keyring and currency items
         all_items.extend(self.bag_items.get_create_objects());
+        all_items.extend(self.keyring_items.get_create_objects());
+        all_items.extend(self.get_currency_token_create_objects());
 
         let msg = SMSG_UPDATE_OBJECT { objects: all_items };
         let event = ServerEvent::UpdateObject(msg);
@@ -0,0 +1,146 @@
+//! Debuffs that keep a character out of the battleground queue for a while. They are tracked by their expiry
+//! time rather than by the aura, so dying or logging out doesn't get rid of them, and the time is stored so the
+//! punishment keeps running while the character is offline.
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use wrath_realm_db::RealmDatabase;
+
+use super::character_auras::{Aura, AuraApplication};
+use crate::prelude::*;
+
+//The aura only shows the debuff, it doesn't do anything by itself
+const AURA_DUMMY: u32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum QueuePunishment {
+    //Left a battleground or arena before it ended
+    Deserter,
+    //Declined a battleground invite, or let it run out
+    QueueCooldown,
+}
+
+impl QueuePunishment {
+    pub const ALL: [QueuePunishment; 2] = [QueuePunishment::Deserter, QueuePunishment::QueueCooldown];
+
+    pub fn spell_id(self) -> u32 {
+        match self {
+            QueuePunishment::Deserter => 26013,
+            QueuePunishment::QueueCooldown => 71328,
+        }
+    }
+
+    fn duration_seconds(self) -> u64 {
+        match self {
+            QueuePunishment::Deserter => 15 * 60,
+            QueuePunishment::QueueCooldown => 5 * 60,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "deserter" => Some(Self::Deserter),
+            "cooldown" => Some(Self::QueueCooldown),
+            _ => None,
+        }
+    }
+
+    fn from_spell_id(spell_id: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|punishment| punishment.spell_id() == spell_id)
+    }
+}
+
+#[derive(Default)]
+pub(super) struct DeserterState {
+    //Unix time the punishment runs out
+    expire_times: HashMap<QueuePunishment, u64>,
+}
+
+fn current_unix_time() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+impl super::Character {
+    //Punishments that ran out while the character was offline are dropped, the rest come back as auras
+    pub(super) async fn load_queue_punishments(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        let character_id = self.get_guid().guid() as u32;
+        let now = current_unix_time()?;
+        for expiry in realm_db.get_character_aura_expiries(character_id).await? {
+            let Some(punishment) = QueuePunishment::from_spell_id(expiry.spell_id) else {
+                continue;
+            };
+            if expiry.expire_time <= now {
+                realm_db.delete_character_aura_expiry(character_id, expiry.spell_id).await?;
+                continue;
+            }
+            self.deserter_state.expire_times.insert(punishment, expiry.expire_time);
+            self.apply_punishment_aura(punishment, expiry.expire_time - now);
+        }
+        Ok(())
+    }
+
+    fn apply_punishment_aura(&mut self, punishment: QueuePunishment, seconds: u64) -> Option<Aura> {
+        let guid = self.get_guid();
+        let caster_level = self.gameplay_data.unit_level().unwrap_or(1) as u8;
+        self.apply_aura(AuraApplication {
+            spell_id: punishment.spell_id(),
+            caster: guid,
+            caster_level,
+            aura_type: AURA_DUMMY,
+            amount: 0,
+            negative: true,
+            periodic: None,
+            duration: Some(seconds as f32),
+        })
+    }
+
+    //A second punishment of the same kind starts the timer over. Returns the aura for the client.
+    pub async fn add_queue_punishment(&mut self, realm_db: &RealmDatabase, punishment: QueuePunishment) -> Result<Option<Aura>> {
+        let expire_time = current_unix_time()? + punishment.duration_seconds();
+        let character_id = self.get_guid().guid() as u32;
+        realm_db
+            .set_character_aura_expiry(character_id, punishment.spell_id(), expire_time)
+            .await?;
+        self.deserter_state.expire_times.insert(punishment, expire_time);
+        Ok(self.apply_punishment_aura(punishment, punishment.duration_seconds()))
+    }
+
+    //Returns the aura slots that were freed
+    pub async fn remove_queue_punishment(&mut self, realm_db: &RealmDatabase, punishment: QueuePunishment) -> Result<Vec<u8>> {
+        let character_id = self.get_guid().guid() as u32;
+        realm_db.delete_character_aura_expiry(character_id, punishment.spell_id()).await?;
+        self.deserter_state.expire_times.remove(&punishment);
+        Ok(self.remove_auras_from_spell(punishment.spell_id()).iter().map(|aura| aura.slot).collect())
+    }
+
+    //The punishment keeping the character out of the queue, if any. For the battleground queue to check.
+    #[allow(dead_code)]
+    pub fn get_active_queue_punishment(&self) -> Result<Option<QueuePunishment>> {
+        let now = current_unix_time()?;
+        Ok(self
+            .deserter_state
+            .expire_times
+            .iter()
+            .find(|(_, &expire_time)| expire_time > now)
+            .map(|(&punishment, _)| punishment))
+    }
+}
+
+pub fn is_queue_punishment_aura(spell_id: u32) -> bool {
+    QueuePunishment::from_spell_id(spell_id).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn punishment_auras_are_recognized_by_spell() {
+        for punishment in QueuePunishment::ALL {
+            assert!(is_queue_punishment_aura(punishment.spell_id()));
+            assert_eq!(QueuePunishment::from_spell_id(punishment.spell_id()), Some(punishment));
+        }
+        assert!(!is_queue_punishment_aura(172));
+    }
+}
@@ -0,0 +1,232 @@
+use wrath_realm_db::RealmDatabase;
+
+use super::character_inventory::INVENTORY_SLOT_BAG_0;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::world::persistence_queue::RealmPersistenceQueue;
+use crate::world::prelude::inventory::EquipmentSlot;
+
+pub const NUM_EQUIPMENT_SET_SLOTS: usize = 19;
+const MAX_EQUIPMENT_SETS: u32 = 10;
+
+//The equipment manager sends this raw guid for slots the set doesn't touch
+pub const IGNORED_SLOT_GUID: u64 = 1;
+
+pub(super) struct EquipmentSet {
+    id: u32,
+    index: u8,
+    name: String,
+    icon_name: String,
+    //None means the set leaves the slot alone, Some(0) means the slot is emptied
+    items: [Option<u32>; NUM_EQUIPMENT_SET_SLOTS],
+}
+
+#[derive(Default)]
+pub(super) struct EquipmentSets {
+    sets: Vec<EquipmentSet>,
+}
+
+//What putting on a set has to do with one equipment slot
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EquipmentSetSlotAction {
+    Ignore,
+    Unequip,
+    //Moves the item with this entry from another slot into the equipment slot, whatever was there takes its place
+    EquipFrom { slot: u8, item: u32 },
+}
+
+//Set info as the client expects it in SMSG_EQUIPMENT_SET_LIST
+pub struct EquipmentSetInfo<'a> {
+    pub id: u32,
+    pub index: u8,
+    pub name: &'a str,
+    pub icon_name: &'a str,
+    pub item_guids: [Guid; NUM_EQUIPMENT_SET_SLOTS],
+}
+
+impl super::Character {
+    pub(super) async fn load_equipment_sets(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        let character_id = self.get_guid().guid() as u32;
+        let mut sets: Vec<EquipmentSet> = realm_db
+            .get_character_equipment_sets(character_id)
+            .await?
+            .into_iter()
+            .map(|set| EquipmentSet {
+                id: set.id,
+                index: set.set_index,
+                name: set.name,
+                icon_name: set.icon_name,
+                items: [None; NUM_EQUIPMENT_SET_SLOTS],
+            })
+            .collect();
+
+        for row in realm_db.get_character_equipment_set_items(character_id).await? {
+            let set = sets.iter_mut().find(|set| set.id == row.set_id);
+            if let Some(slot) = set.and_then(|set| set.items.get_mut(row.slot_id as usize)) {
+                *slot = Some(row.item);
+            }
+        }
+
+        self.equipment_sets.sets = sets;
+        Ok(())
+    }
+
+    pub fn get_equipment_sets(&self) -> Vec<EquipmentSetInfo> {
+        self.equipment_sets
+            .sets
+            .iter()
+            .map(|set| EquipmentSetInfo {
+                id: set.id,
+                index: set.index,
+                name: &set.name,
+                icon_name: &set.icon_name,
+                item_guids: std::array::from_fn(|slot| match set.items[slot] {
+                    None => Guid::new(IGNORED_SLOT_GUID),
+                    Some(0) => Guid::zero(),
+                    Some(item) => self.find_item_guid_by_entry(item, slot as u8),
+                }),
+            })
+            .collect()
+    }
+
+    //Item guids are turned into entries before saving, because guids change whenever an item moves
+    pub async fn save_equipment_set(
+        &mut self,
+        realm_db: &RealmDatabase,
+        index: u32,
+        name: &str,
+        icon_name: &str,
+        item_guids: &[Guid; NUM_EQUIPMENT_SET_SLOTS],
+    ) -> Result<u32> {
+        if index >= MAX_EQUIPMENT_SETS {
+            bail!("Equipment set index {} is out of range", index);
+        }
+
+        let mut items = [None; NUM_EQUIPMENT_SET_SLOTS];
+        for (slot, &guid) in item_guids.iter().enumerate() {
+            items[slot] = match guid.guid() {
+                IGNORED_SLOT_GUID => None,
+                0 => Some(0),
+                _ => Some(
+                    self.get_item_entry_by_guid(guid)
+                        .ok_or_else(|| anyhow!("Equipment set contains item {} which the character doesn't have", guid))?,
+                ),
+            };
+        }
+
+        let character_id = self.get_guid().guid() as u32;
+        let rows: Vec<(u8, u32)> = items.iter().enumerate().filter_map(|(slot, item)| Some((slot as u8, (*item)?))).collect();
+        let id = realm_db.save_equipment_set(character_id, index as u8, name, icon_name, &rows).await?;
+
+        self.equipment_sets.sets.retain(|set| set.index != index as u8);
+        self.equipment_sets.sets.push(EquipmentSet {
+            id,
+            index: index as u8,
+            name: name.to_string(),
+            icon_name: icon_name.to_string(),
+            items,
+        });
+        self.equipment_sets.sets.sort_by_key(|set| set.index);
+        Ok(id)
+    }
+
+    pub async fn delete_equipment_set(&mut self, realm_db: &RealmDatabase, set_id: u32) -> Result<()> {
+        let character_id = self.get_guid().guid() as u32;
+        realm_db.delete_equipment_set(character_id, set_id).await?;
+        self.equipment_sets.sets.retain(|set| set.id != set_id);
+        Ok(())
+    }
+
+    //Returns false without moving anything if the backpack can't hold the items the set takes off
+    pub async fn use_equipment_set(
+        &mut self,
+        actions: &[EquipmentSetSlotAction; NUM_EQUIPMENT_SET_SLOTS],
+        persistence_queue: Option<&RealmPersistenceQueue>,
+        connection_sender: Option<&flume::Sender<ServerEvent>>,
+    ) -> Result<bool> {
+        let items_to_unequip = actions
+            .iter()
+            .enumerate()
+            .filter(|&(slot, &action)| action == EquipmentSetSlotAction::Unequip && self.get_equipped_item_entry(slot as u8).is_some())
+            .count();
+        if items_to_unequip > self.get_free_backpack_slots().len() {
+            return Ok(false);
+        }
+
+        for (slot, &action) in actions.iter().enumerate() {
+            let slot = slot as u8;
+            match action {
+                EquipmentSetSlotAction::Ignore => {}
+                EquipmentSetSlotAction::Unequip => {
+                    let Some(item) = self
+                        .set_item(None, (slot, INVENTORY_SLOT_BAG_0), persistence_queue, connection_sender)
+                        .await?
+                    else {
+                        continue;
+                    };
+                    let free_slot = self.get_free_backpack_slots()[0];
+                    self.set_item(Some(item), (free_slot, INVENTORY_SLOT_BAG_0), persistence_queue, connection_sender)
+                        .await?;
+                }
+                EquipmentSetSlotAction::EquipFrom { slot: source_slot, item } => {
+                    //An earlier swap in the same set (two rings trading places) may already have put it here
+                    if self.get_equipped_item_entry(slot) == Some(item) {
+                        continue;
+                    }
+                    self.swap_item_slots(slot, source_slot, persistence_queue, connection_sender).await?;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    async fn swap_item_slots(
+        &mut self,
+        first: u8,
+        second: u8,
+        persistence_queue: Option<&RealmPersistenceQueue>,
+        connection_sender: Option<&flume::Sender<ServerEvent>>,
+    ) -> Result<()> {
+        let first_item = self
+            .set_item(None, (first, INVENTORY_SLOT_BAG_0), persistence_queue, connection_sender)
+            .await?;
+        let second_item = self
+            .set_item(first_item, (second, INVENTORY_SLOT_BAG_0), persistence_queue, connection_sender)
+            .await?;
+        self.set_item(second_item, (first, INVENTORY_SLOT_BAG_0), persistence_queue, connection_sender)
+            .await?;
+        Ok(())
+    }
+
+    fn get_equipped_item_entry(&self, slot: u8) -> Option<u32> {
+        let slot = EquipmentSlot::try_from(slot).ok()?;
+        let item = self.equipped_items.get_item(slot)?;
+        item.update_state.object_entry().map(|entry| entry as u32)
+    }
+
+    pub fn get_item_entry_by_guid(&self, guid: Guid) -> Option<u32> {
+        self.equipped_items
+            .get_all_equipment()
+            .into_iter()
+            .flatten()
+            .chain(self.bag_items.iter())
+            .find(|item| item.update_state.object_guid() == Some(guid))
+            .and_then(|item| item.update_state.object_entry())
+            .map(|entry| entry as u32)
+    }
+
+    //Prefers the item that's already in the set's slot, so two identical rings don't both point at the same one
+    fn find_item_guid_by_entry(&self, entry: u32, preferred_slot: u8) -> Guid {
+        let has_entry = |item: &&crate::item::Item| item.update_state.object_entry() == Some(entry as i32);
+        let preferred = EquipmentSlot::try_from(preferred_slot)
+            .ok()
+            .and_then(|slot| self.equipped_items.get_item(slot))
+            .filter(has_entry);
+
+        preferred
+            .or_else(|| self.equipped_items.get_all_equipment().into_iter().flatten().find(has_entry))
+            .or_else(|| self.bag_items.iter().find(has_entry))
+            .and_then(|item| item.update_state.object_guid())
+            .unwrap_or(Guid::zero())
+    }
+}
@@ -0,0 +1,93 @@
+//! Experience and levelling up. Levels aren't stored yet, every login starts over at level 1.
+
+//Nothing past the level cap, experience stops counting there
+pub const MAX_LEVEL: u8 = 80;
+
+//Experience from one level to the next, the formula of the original game carried on past level 60
+pub fn experience_for_level(level: u8) -> u32 {
+    let level = level as u32;
+    let difficulty = match level {
+        0..=28 => 0,
+        29 => 1,
+        30 => 3,
+        31 => 6,
+        _ => 5 * (level - 30),
+    };
+    let base = 45 + 5 * level;
+    ((8 * level + difficulty) * base + 50) / 100 * 100
+}
+
+//Quests far below the character's level are worth less and less, down to a tenth
+pub fn scale_quest_experience(experience: u32, character_level: u8, quest_level: u8, rate: f32) -> u32 {
+    let percent = match character_level.saturating_sub(quest_level) {
+        0..=5 => 100,
+        6 => 80,
+        7 => 60,
+        8 => 40,
+        9 => 20,
+        _ => 10,
+    };
+    (experience as f32 * percent as f32 / 100.0 * rate) as u32
+}
+
+impl super::Character {
+    pub(super) fn init_experience(&mut self) {
+        let level = self.gameplay_data.unit_level().unwrap_or(1) as u8;
+        self.set_level_and_experience(level, 0);
+    }
+
+    //Returns how many levels the character went up
+    pub fn give_experience(&mut self, amount: u32) -> u8 {
+        let start_level = self.gameplay_data.unit_level().unwrap_or(1) as u8;
+        let mut level = start_level;
+        let mut experience = self.gameplay_data.player_xp().unwrap_or(0).max(0) as u32 + amount;
+        while level < MAX_LEVEL && experience >= experience_for_level(level) {
+            experience -= experience_for_level(level);
+            level += 1;
+        }
+        if level >= MAX_LEVEL {
+            experience = 0;
+        }
+
+        self.set_level_and_experience(level, experience);
+        if level > start_level {
+            self.seconds_played_at_level = 0;
+        }
+        level - start_level
+    }
+
+    fn set_level_and_experience(&mut self, level: u8, experience: u32) {
+        self.gameplay_data.set_unit_level(level as i32);
+        self.gameplay_data.set_player_xp(experience as i32);
+        let next_level = if level < MAX_LEVEL { experience_for_level(level) } else { 0 };
+        self.gameplay_data.set_player_next_level_xp(next_level as i32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Character;
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn experience_carries_over_into_the_next_levels() {
+        assert_eq!(experience_for_level(1), 400);
+        assert_eq!(experience_for_level(2), 900);
+        assert_eq!(scale_quest_experience(1000, 20, 10, 1.0), 100);
+        assert_eq!(scale_quest_experience(1000, 16, 10, 2.0), 1600);
+
+        let (sender, _receiver) = flume::unbounded();
+        let mut character = Character::new(sender, Guid::new(1));
+        character.gameplay_data.set_unit_level(1);
+        character.init_experience();
+
+        assert_eq!(character.give_experience(1400), 2);
+        assert_eq!(character.gameplay_data.unit_level(), Some(3));
+        assert_eq!(character.gameplay_data.player_xp(), Some(100));
+
+        character.gameplay_data.set_unit_level(MAX_LEVEL as i32 - 1);
+        assert_eq!(character.give_experience(u32::MAX / 2), 1);
+        assert_eq!(character.gameplay_data.player_xp(), Some(0));
+    }
+}
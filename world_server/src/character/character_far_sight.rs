@@ -0,0 +1,85 @@
+use wow_world_messages::wrath::Vector3d;
+
+use crate::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FarSightTarget {
+    //Mind vision and eagle eye style effects follow a unit around
+    Unit(Guid),
+    //Far sight puts a dynamic object at a fixed point
+    DynamicObject { guid: Guid, position: Vector3d },
+}
+
+impl FarSightTarget {
+    fn guid(&self) -> Guid {
+        match *self {
+            FarSightTarget::Unit(guid) | FarSightTarget::DynamicObject { guid, .. } => guid,
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub(super) struct FarSightState {
+    target: Option<FarSightTarget>,
+    //Seconds left on the effect that grants the sight, None if it lasts until it's cancelled
+    remaining: Option<f32>,
+}
+
+impl super::Character {
+    //Called by the aura or spell effect that grants the sight, the camera moves as soon as the field reaches the client
+    pub fn start_far_sight(&mut self, target: FarSightTarget, duration: Option<f32>) {
+        self.gameplay_data.set_player_farsight(target.guid());
+        self.request_visibility_update();
+        self.far_sight_state = FarSightState {
+            target: Some(target),
+            remaining: duration,
+        };
+    }
+
+    //Called when the granting aura ends, the sight is cancelled or the target disappears
+    pub fn end_far_sight(&mut self) {
+        if self.far_sight_state.target.take().is_some() {
+            self.far_sight_state.remaining = None;
+            self.gameplay_data.set_player_farsight(Guid::zero());
+            self.request_visibility_update();
+        }
+    }
+
+    pub fn get_far_sight_target(&self) -> Option<FarSightTarget> {
+        self.far_sight_state.target
+    }
+
+    pub(super) fn tick_far_sight(&mut self, delta_time: f32) {
+        if let Some(remaining) = self.far_sight_state.remaining.as_mut() {
+            *remaining -= delta_time;
+            if *remaining <= 0.0 {
+                self.end_far_sight();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Character;
+
+    #[test]
+    fn far_sight_ends_with_its_effect() {
+        let (sender, _receiver) = flume::unbounded();
+        let mut character = Character::new(sender, Guid::new(1));
+        let target = FarSightTarget::DynamicObject {
+            guid: Guid::new(77),
+            position: Vector3d { x: 10.0, y: 20.0, z: 0.0 },
+        };
+
+        character.start_far_sight(target, Some(1.0));
+        assert_eq!(character.gameplay_data.player_farsight(), Some(Guid::new(77)));
+        character.tick_far_sight(0.5);
+        assert_eq!(character.get_far_sight_target(), Some(target));
+
+        character.tick_far_sight(0.5);
+        assert_eq!(character.get_far_sight_target(), None);
+        assert_eq!(character.gameplay_data.player_farsight(), Some(Guid::zero()));
+    }
+}
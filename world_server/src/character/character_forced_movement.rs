@@ -0,0 +1,225 @@
+use wow_world_messages::wrath::{
+    MonsterMoveType, SplineFlag, Vector2d, Vector3d, SMSG_FORCE_MOVE_ROOT, SMSG_FORCE_MOVE_UNROOT, SMSG_FORCE_RUN_BACK_SPEED_CHANGE,
+    SMSG_FORCE_RUN_SPEED_CHANGE, SMSG_MONSTER_MOVE, SMSG_MOVE_FEATHER_FALL, SMSG_MOVE_KNOCK_BACK, SMSG_MOVE_LAND_WALK, SMSG_MOVE_NORMAL_FALL,
+    SMSG_MOVE_SET_CAN_FLY, SMSG_MOVE_SET_HOVER, SMSG_MOVE_UNSET_CAN_FLY, SMSG_MOVE_UNSET_HOVER, SMSG_MOVE_WATER_WALK,
+};
+
+use super::character_movement_acks::MovementChange;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::world::prelude::GameObject;
+
+//Same speeds and gravity the client uses, so its own simulation of the movement ends where the server put the character
+const CHARGE_SPEED: f32 = 42.0;
+const GRAVITY: f32 = 19.291105;
+
+//Direction and speeds of a knockback, the others in range need the same values to show it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct KnockBack {
+    pub v_cos: f32,
+    pub v_sin: f32,
+    pub horizontal_speed: f32,
+    pub vertical_speed: f32,
+}
+
+//Movement the client only allows once the server has granted it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MovementCapability {
+    Fly = 0,
+    WaterWalk = 1,
+    FeatherFall = 2,
+    Hover = 3,
+}
+
+const NUM_MOVEMENT_CAPABILITIES: usize = 4;
+
+#[derive(Default, Debug)]
+pub(super) struct ForcedMovementState {
+    //Roots from different sources (auras, logging out) stack, the character is only freed once they are all gone
+    root_count: u32,
+    //Same for capabilities, a flight form and a GM command can both grant flying
+    capability_counts: [u32; NUM_MOVEMENT_CAPABILITIES],
+    spline_id: u32,
+}
+
+impl super::Character {
+    pub fn is_rooted(&self) -> bool {
+        self.forced_movement_state.root_count > 0
+    }
+
+    pub async fn add_root(&mut self) -> Result<()> {
+        self.forced_movement_state.root_count += 1;
+        if self.forced_movement_state.root_count == 1 {
+            let counter = self.queue_movement_change(MovementChange::Root);
+            ServerEvent::ForceMoveRoot(SMSG_FORCE_MOVE_ROOT {
+                guid: self.get_guid(),
+                counter,
+            })
+            .send_to_character(self)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn remove_root(&mut self) -> Result<()> {
+        let state = &mut self.forced_movement_state;
+        if state.root_count == 0 {
+            return Ok(());
+        }
+        state.root_count -= 1;
+        if state.root_count == 0 {
+            let counter = self.queue_movement_change(MovementChange::Unroot);
+            ServerEvent::ForceMoveUnroot(SMSG_FORCE_MOVE_UNROOT {
+                guid: self.get_guid(),
+                counter,
+            })
+            .send_to_character(self)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub fn has_movement_capability(&self, capability: MovementCapability) -> bool {
+        self.forced_movement_state.capability_counts[capability as usize] > 0
+    }
+
+    //Auras granting the capability call this when applied and revoke_movement_capability when they fade
+    pub async fn grant_movement_capability(&mut self, capability: MovementCapability) -> Result<()> {
+        let count = &mut self.forced_movement_state.capability_counts[capability as usize];
+        *count += 1;
+        if *count == 1 {
+            self.send_movement_capability(capability, true).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn revoke_movement_capability(&mut self, capability: MovementCapability) -> Result<()> {
+        let count = &mut self.forced_movement_state.capability_counts[capability as usize];
+        if *count == 0 {
+            return Ok(());
+        }
+        *count -= 1;
+        if *count == 0 {
+            self.send_movement_capability(capability, false).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_movement_capability(&mut self, capability: MovementCapability, enabled: bool) -> Result<()> {
+        let guid = self.get_guid();
+        let counter = self.queue_movement_change(MovementChange::Capability(capability, enabled));
+
+        let event = match (capability, enabled) {
+            (MovementCapability::Fly, true) => ServerEvent::MoveSetCanFly(SMSG_MOVE_SET_CAN_FLY { guid, counter }),
+            (MovementCapability::Fly, false) => ServerEvent::MoveUnsetCanFly(SMSG_MOVE_UNSET_CAN_FLY { guid, counter }),
+            (MovementCapability::WaterWalk, true) => ServerEvent::MoveWaterWalk(SMSG_MOVE_WATER_WALK { guid, counter }),
+            (MovementCapability::WaterWalk, false) => ServerEvent::MoveLandWalk(SMSG_MOVE_LAND_WALK { guid, counter }),
+            (MovementCapability::FeatherFall, true) => ServerEvent::MoveFeatherFall(SMSG_MOVE_FEATHER_FALL { guid, counter }),
+            (MovementCapability::FeatherFall, false) => ServerEvent::MoveNormalFall(SMSG_MOVE_NORMAL_FALL { guid, counter }),
+            (MovementCapability::Hover, true) => ServerEvent::MoveSetHover(SMSG_MOVE_SET_HOVER { guid, counter }),
+            (MovementCapability::Hover, false) => ServerEvent::MoveUnsetHover(SMSG_MOVE_UNSET_HOVER { guid, counter }),
+        };
+        event.send_to_character(self).await
+    }
+
+    //Pushes the character away from the origin. The client moves itself and tells the server where it
+    //ended up in its ack, which is also when the others in range get to see the knockback.
+    pub async fn knock_back(&mut self, origin: Vector2d, horizontal_speed: f32, vertical_speed: f32) -> Result<()> {
+        let position = self.movement_info.position;
+        let (dx, dy) = (position.x - origin.x, position.y - origin.y);
+        let distance = (dx * dx + dy * dy).sqrt();
+        //Knockbacks centered on the character itself push it backwards
+        let (v_cos, v_sin) = if distance < 0.001 {
+            let angle = self.movement_info.orientation + std::f32::consts::PI;
+            (angle.cos(), angle.sin())
+        } else {
+            (dx / distance, dy / distance)
+        };
+
+        let knock_back = KnockBack {
+            v_cos,
+            v_sin,
+            horizontal_speed,
+            vertical_speed,
+        };
+        let movement_counter = self.queue_movement_change(MovementChange::KnockBack(knock_back));
+        ServerEvent::MoveKnockBack(SMSG_MOVE_KNOCK_BACK {
+            guid: self.get_guid(),
+            movement_counter,
+            v_cos,
+            v_sin,
+            horizontal_speed,
+            vertical_speed,
+        })
+        .send_to_character(self)
+        .await
+    }
+
+    //Speed changes come in pairs, running backwards is always at half the forward speed
+    pub async fn set_run_speed(&mut self, speed: f32) -> Result<()> {
+        let guid = self.get_guid();
+        let move_event = self.queue_movement_change(MovementChange::RunSpeed(speed));
+        ServerEvent::ForceRunSpeedChange(SMSG_FORCE_RUN_SPEED_CHANGE {
+            guid,
+            move_event,
+            speed,
+            unknown: 0,
+        })
+        .send_to_character(self)
+        .await?;
+
+        let back_speed = speed * 0.5;
+        let move_event = self.queue_movement_change(MovementChange::RunBackSpeed(back_speed));
+        ServerEvent::ForceRunBackSpeedChange(SMSG_FORCE_RUN_BACK_SPEED_CHANGE {
+            guid,
+            move_event,
+            speed: back_speed,
+        })
+        .send_to_character(self)
+        .await
+    }
+
+    //Charge: a straight line to the target at charge speed. Like jump_to, returns the move for everyone in range to see.
+    pub fn charge_to(&mut self, destination: Vector3d) -> SMSG_MONSTER_MOVE {
+        let duration = distance(self.movement_info.position, destination) / CHARGE_SPEED;
+        self.move_spline(destination, duration, SplineFlag::empty(), 0.0)
+    }
+
+    //Jumps like Heroic Leap: an arc to the destination, the vertical speed decides how high the arc goes
+    pub fn jump_to(&mut self, destination: Vector3d, horizontal_speed: f32, vertical_speed: f32) -> SMSG_MONSTER_MOVE {
+        let start = self.movement_info.position;
+        let horizontal_distance = ((destination.x - start.x).powi(2) + (destination.y - start.y).powi(2)).sqrt();
+        let duration = if horizontal_speed > 0.0 {
+            horizontal_distance / horizontal_speed
+        } else {
+            2.0 * vertical_speed / GRAVITY
+        };
+        self.move_spline(destination, duration, SplineFlag::empty().set_parabolic(), GRAVITY)
+    }
+
+    fn move_spline(&mut self, destination: Vector3d, duration: f32, spline_flags: SplineFlag, vertical_acceleration: f32) -> SMSG_MONSTER_MOVE {
+        let start = self.movement_info.position;
+        self.forced_movement_state.spline_id = self.forced_movement_state.spline_id.wrapping_add(1);
+
+        let msg = SMSG_MONSTER_MOVE {
+            guid: self.get_guid(),
+            unknown1: 0,
+            spline_point: start,
+            spline_id: self.forced_movement_state.spline_id,
+            move_type: MonsterMoveType::Normal,
+            spline_flags,
+            duration: (duration * 1000.0) as u32,
+            vertical_acceleration,
+            effect_start_time: 0,
+            splines: vec![destination],
+        };
+
+        //The server doesn't simulate the path, the character is where the spline ends as soon as it starts
+        self.movement_info.position = destination;
+        msg
+    }
+}
+
+fn distance(a: Vector3d, b: Vector3d) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
@@ -0,0 +1,140 @@
+use super::character_forced_movement::MovementCapability;
+use crate::data::WorldZoneLocation;
+use crate::handlers::movement_handler::TeleportationDistance;
+use crate::prelude::*;
+use crate::world::prelude::unit_flags::UnitFlagIndex;
+use bit_field::BitField;
+use wow_world_base::wrath::PlayerChatTag;
+use wow_world_messages::wrath::{Area, Map, Vector3d};
+use wrath_realm_db::recall_position::DBRecallPosition;
+use wrath_realm_db::RealmDatabase;
+
+//PLAYER_FLAGS_GM, shows the <GM> tag above the character and in the chat frame
+const PLAYER_FLAG_GM_BIT: usize = 3;
+
+#[derive(Default, Debug)]
+pub(super) struct GmState {
+    gm_mode: bool,
+    invisible: bool,
+    god_mode: bool,
+    fly: bool,
+    //Where the character was before its last GM teleport
+    recall_location: Option<WorldZoneLocation>,
+}
+
+impl super::Character {
+    pub fn set_gm_mode(&mut self, enabled: bool) {
+        self.gm_state.gm_mode = enabled;
+
+        let mut player_flags: i32 = self.gameplay_data.player_flags().unwrap_or(0);
+        player_flags.set_bit(PLAYER_FLAG_GM_BIT, enabled);
+        self.gameplay_data.set_player_flags(player_flags);
+    }
+
+    pub fn is_gm_mode_enabled(&self) -> bool {
+        self.gm_state.gm_mode
+    }
+
+    pub fn set_gm_visible(&mut self, visible: bool) {
+        self.gm_state.invisible = !visible;
+        self.update_gm_attackable_flag();
+    }
+
+    pub fn is_gm_invisible(&self) -> bool {
+        self.gm_state.invisible
+    }
+
+    pub fn set_god_mode(&mut self, enabled: bool) {
+        self.gm_state.god_mode = enabled;
+        self.update_gm_attackable_flag();
+    }
+
+    pub fn is_god_mode_enabled(&self) -> bool {
+        self.gm_state.god_mode
+    }
+
+    //Invisible GMs see everyone, everyone else only sees characters that aren't GM-invisible.
+    //Stealthed characters additionally have to be detected.
+    pub fn can_see_character(&self, other: &super::Character) -> bool {
+        let gm_visible = !other.is_gm_invisible() || self.is_gm_mode_enabled() || self.is_gm_invisible();
+        gm_visible && self.can_detect_stealth_of(other)
+    }
+
+    pub fn get_chat_tag(&self) -> PlayerChatTag {
+        if self.is_gm_mode_enabled() {
+            PlayerChatTag::Gm
+        } else {
+            PlayerChatTag::None
+        }
+    }
+
+    pub fn is_gm_fly_enabled(&self) -> bool {
+        self.gm_state.fly
+    }
+
+    //Goes through the movement capability stacking, so turning it off doesn't ground a character in flight form
+    pub async fn set_gm_fly(&mut self, enabled: bool) -> Result<()> {
+        if self.gm_state.fly == enabled {
+            return Ok(());
+        }
+        self.gm_state.fly = enabled;
+        if enabled {
+            self.grant_movement_capability(MovementCapability::Fly).await
+        } else {
+            self.revoke_movement_capability(MovementCapability::Fly).await
+        }
+    }
+
+    pub(super) async fn load_recall_location(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        let character_id = self.get_guid().guid() as u32;
+        let Some(recall) = realm_db.get_character_recall_position(character_id).await? else {
+            return Ok(());
+        };
+
+        self.gm_state.recall_location = Some(WorldZoneLocation {
+            map: Map::try_from(recall.map as u32)?,
+            area: Area::try_from(recall.zone as u32).unwrap_or(Area::NorthshireAbbey),
+            position: Vector3d {
+                x: recall.position_x,
+                y: recall.position_y,
+                z: recall.position_z,
+            },
+            orientation: recall.orientation,
+        });
+        Ok(())
+    }
+
+    pub fn get_recall_location(&self) -> Option<&WorldZoneLocation> {
+        self.gm_state.recall_location.as_ref()
+    }
+
+    //Remembers the current location for .recall before teleporting, it's saved right away so it survives a relog
+    pub async fn gm_teleport(&mut self, realm_db: &RealmDatabase, destination: WorldZoneLocation) -> Result<()> {
+        let current = WorldZoneLocation {
+            map: self.map,
+            area: self.area,
+            position: self.movement_info.position,
+            orientation: self.movement_info.orientation,
+        };
+
+        let character_id = self.get_guid().guid() as u32;
+        let recall = DBRecallPosition {
+            map: current.map.as_int() as u16,
+            zone: current.area.as_int() as u16,
+            position_x: current.position.x,
+            position_y: current.position.y,
+            position_z: current.position.z,
+            orientation: current.orientation,
+        };
+        realm_db.set_character_recall_position(character_id, &recall).await?;
+
+        self.gm_state.recall_location = Some(current);
+        self.teleport_to(TeleportationDistance::Far(destination));
+        Ok(())
+    }
+
+    fn update_gm_attackable_flag(&mut self) {
+        let non_attackable = self.gm_state.invisible || self.gm_state.god_mode;
+        self.set_unit_flag_byte(UnitFlagIndex::NonAttackable, non_attackable);
+    }
+}
@@ -0,0 +1,76 @@
+use wrath_realm_db::RealmDatabase;
+
+use crate::prelude::*;
+
+//What a guild member can be told about the others in its guild, bits of guild_member.notifications
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuildNotification {
+    Logins = 0x1,
+    LevelUps = 0x2,
+    Achievements = 0x4,
+}
+
+impl GuildNotification {
+    //The names players use with .guildnotify
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "logins" => Some(Self::Logins),
+            "levels" => Some(Self::LevelUps),
+            "achievements" => Some(Self::Achievements),
+            _ => None,
+        }
+    }
+}
+
+const ALL_NOTIFICATIONS: u8 = GuildNotification::Logins as u8 | GuildNotification::LevelUps as u8 | GuildNotification::Achievements as u8;
+
+pub(super) struct GuildState {
+    notifications: u8,
+}
+
+impl Default for GuildState {
+    fn default() -> Self {
+        Self {
+            notifications: ALL_NOTIFICATIONS,
+        }
+    }
+}
+
+impl super::Character {
+    pub(super) async fn load_guild_notifications(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        let character_id = self.get_guid().guid() as u32;
+        if let Some(notifications) = realm_db.get_guild_member_notifications(character_id).await? {
+            self.guild_state.notifications = notifications;
+        }
+        Ok(())
+    }
+
+    //The guild manager knows who is in which guild, this keeps the character and the fields the client sees in step
+    pub fn set_guild(&mut self, membership: Option<(u32, u8)>) {
+        self.guild_id = membership.map(|(guild_id, _)| guild_id);
+        let (guild_id, rank) = membership.unwrap_or((0, 0));
+        self.gameplay_data.set_player_guildid(guild_id);
+        self.gameplay_data.set_player_guildrank(rank as u32);
+        if membership.is_none() {
+            self.guild_state = GuildState::default();
+        }
+    }
+
+    pub fn wants_guild_notification(&self, notification: GuildNotification) -> bool {
+        self.guild_state.notifications & notification as u8 != 0
+    }
+
+    pub async fn set_guild_notification(&mut self, realm_db: &RealmDatabase, notification: GuildNotification, enabled: bool) -> Result<()> {
+        if self.get_guild_id().is_none() {
+            bail!("{} is not in a guild", self.name);
+        }
+        match enabled {
+            true => self.guild_state.notifications |= notification as u8,
+            false => self.guild_state.notifications &= !(notification as u8),
+        }
+        let character_id = self.get_guid().guid() as u32;
+        realm_db
+            .set_guild_member_notifications(character_id, self.guild_state.notifications)
+            .await
+    }
+}
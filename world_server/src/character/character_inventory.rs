@@ -1,10 +1,14 @@
+use crate::character::character_keyring::is_keyring_slot;
 use crate::connection::events::ServerEvent;
 use crate::item::item_container::ItemContainer;
+use crate::world::persistence_queue::RealmPersistenceQueue;
 use crate::world::prelude::GameObject;
 use crate::{
     item::Item,
     prelude::*,
-    world::prelude::inventory::{self, get_compatible_equipment_slots_for_inventory_type, BagSlot, EquipmentSlot, BAG_SLOTS_END},
+    world::prelude::inventory::{
+        self, get_compatible_equipment_slots_for_inventory_type, BagSlot, EquipmentSlot, BAG_SLOTS_END, KEYRING_SLOTS_END, KEYRING_SLOTS_START,
+    },
 };
 use std::{
     collections::HashMap,
@@ -14,8 +18,8 @@ use std::{
 use wow_world_base::wrath::ItemSlot;
 use wow_world_messages::wrath::UpdateItem;
 use wow_world_messages::wrath::{
-    InventoryType, MovementBlock, MovementBlock_UpdateFlag, Object, ObjectType, Object_UpdateType, UpdateItemBuilder, UpdateMask, VisibleItem,
-    VisibleItemIndex, SMSG_UPDATE_OBJECT,
+    InventoryType, MovementBlock, MovementBlock_UpdateFlag, Object, ObjectType, Object_UpdateType, SMSG_INVENTORY_CHANGE_FAILURE_InventoryResult,
+    UpdateItemBuilder, UpdateMask, VisibleItem, VisibleItemIndex, SMSG_INVENTORY_CHANGE_FAILURE, SMSG_UPDATE_OBJECT,
 };
 
 //An identifier for the player inventory (the thing ItemSlot models a cell of)
@@ -129,24 +133,30 @@ impl BagInventory {
         }
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = &Item> {
+        self.items.iter().flatten()
+    }
+
     pub fn get_create_objects(&self) -> Vec<Object> {
-        self.items
-            .iter()
-            .flatten()
-            .map(|item| Object {
-                update_type: Object_UpdateType::CreateObject {
-                    guid3: item.update_state.object_guid().unwrap(),
-                    mask2: UpdateMask::Item(item.update_state.clone()),
-                    movement2: MovementBlock {
-                        update_flag: MovementBlock_UpdateFlag::empty(),
-                    },
-                    object_type: ObjectType::Item,
-                },
-            })
-            .collect()
+        item_create_objects(self.items.iter().flatten())
     }
 }
 
+pub fn item_create_objects<'a>(items: impl Iterator<Item = &'a Item>) -> Vec<Object> {
+    items
+        .map(|item| Object {
+            update_type: Object_UpdateType::CreateObject {
+                guid3: item.update_state.object_guid().unwrap(),
+                mask2: UpdateMask::Item(item.update_state.clone()),
+                movement2: MovementBlock {
+                    update_flag: MovementBlock_UpdateFlag::empty(),
+                },
+                object_type: ObjectType::Item,
+            },
+        })
+        .collect()
+}
+
 impl ItemContainer<BagSlot> for BagInventory {
     fn get_items_update_state(&self) -> Vec<UpdateItem> {
         let mut updates = Vec::new();
@@ -157,8 +167,205 @@ impl ItemContainer<BagSlot> for BagInventory {
     }
 }
 
+//Why the server refused to move, equip or destroy an item. Sent back so the client puts the item back and shows the error.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InventoryError {
+    ItemNotFound,
+    SlotIsEmpty,
+    //The item template doesn't allow destroying, like quest items
+    CantDestroy,
+    ItemDoesntGoToSlot,
+    NotABag,
+    //Two-handed weapons and off-hand items can't be equipped at the same time
+    CantEquipWithTwohanded,
+    ItemCantBeEquipped,
+    //Only the backpack is implemented, other bags refuse everything
+    ItemDoesntGoIntoBag,
+    InventoryFull,
+}
+
+impl InventoryError {
+    fn to_result(self, item: Guid) -> SMSG_INVENTORY_CHANGE_FAILURE_InventoryResult {
+        use SMSG_INVENTORY_CHANGE_FAILURE_InventoryResult as InventoryResult;
+        let (bag_type_subclass, item1, item2) = (0, item, Guid::zero());
+        match self {
+            InventoryError::ItemNotFound => InventoryResult::ItemNotFound {
+                bag_type_subclass,
+                item1,
+                item2,
+            },
+            InventoryError::SlotIsEmpty => InventoryResult::SlotIsEmpty {
+                bag_type_subclass,
+                item1,
+                item2,
+            },
+            InventoryError::CantDestroy => InventoryResult::CantDropSoulbound {
+                bag_type_subclass,
+                item1,
+                item2,
+            },
+            InventoryError::ItemDoesntGoToSlot => InventoryResult::ItemDoesntGoToSlot {
+                bag_type_subclass,
+                item1,
+                item2,
+            },
+            InventoryError::NotABag => InventoryResult::NotABag {
+                bag_type_subclass,
+                item1,
+                item2,
+            },
+            InventoryError::CantEquipWithTwohanded => InventoryResult::CantEquipWithTwohanded {
+                bag_type_subclass,
+                item1,
+                item2,
+            },
+            InventoryError::ItemCantBeEquipped => InventoryResult::ItemCantBeEquipped {
+                bag_type_subclass,
+                item1,
+                item2,
+            },
+            InventoryError::ItemDoesntGoIntoBag => InventoryResult::ItemDoesntGoIntoBag {
+                bag_type_subclass,
+                item1,
+                item2,
+            },
+            InventoryError::InventoryFull => InventoryResult::InventoryFull {
+                bag_type_subclass,
+                item1,
+                item2,
+            },
+        }
+    }
+}
+
 impl crate::character::Character {
-    async fn send_item_update(item: &Item, connection_sender: &flume::Sender<ServerEvent>) {
+    pub async fn send_inventory_change_failure(&self, error: InventoryError, item: Guid) -> Result<()> {
+        ServerEvent::InventoryChangeFailure(SMSG_INVENTORY_CHANGE_FAILURE {
+            result: error.to_result(item),
+        })
+        .send_to_character(self)
+        .await
+    }
+
+    //Items in the backpack, on the keyring or equipped, bags aren't implemented yet
+    pub fn get_inventory_item(&self, item_position: (u8, u8)) -> Option<&Item> {
+        let (slot, bag) = item_position;
+        if bag != INVENTORY_SLOT_BAG_0 {
+            return None;
+        }
+
+        if let Ok(equipment_slot) = EquipmentSlot::try_from(slot) {
+            self.equipped_items.get_item(equipment_slot)
+        } else if let Ok(bag_slot) = BagSlot::try_from(slot) {
+            self.bag_items[bag_slot].as_ref()
+        } else if is_keyring_slot(slot) {
+            self.keyring_items.get(slot)
+        } else {
+            None
+        }
+    }
+
+    //Checks that the item at source can be moved to destination, and that whatever is at destination can take its place
+    pub fn check_item_swap(&self, source: (u8, u8), destination: (u8, u8)) -> std::result::Result<(), InventoryError> {
+        Self::check_inventory_position(source)?;
+        Self::check_inventory_position(destination)?;
+
+        let source_item = self.get_inventory_item(source).ok_or(InventoryError::ItemNotFound)?;
+        self.check_item_fits(source_item, destination.0, source.0)?;
+        if let Some(destination_item) = self.get_inventory_item(destination) {
+            self.check_item_fits(destination_item, source.0, destination.0)?;
+        }
+        Ok(())
+    }
+
+    fn check_inventory_position(item_position: (u8, u8)) -> std::result::Result<(), InventoryError> {
+        let (slot, bag) = item_position;
+        if bag != INVENTORY_SLOT_BAG_0 {
+            return Err(InventoryError::ItemDoesntGoIntoBag);
+        }
+        if EquipmentSlot::try_from(slot).is_err() && BagSlot::try_from(slot).is_err() && !is_keyring_slot(slot) {
+            return Err(InventoryError::ItemDoesntGoToSlot);
+        }
+        Ok(())
+    }
+
+    //The item is about to leave vacated_slot, so whatever is equipped there doesn't count
+    fn check_item_fits(&self, item: &Item, slot: u8, vacated_slot: u8) -> std::result::Result<(), InventoryError> {
+        if is_keyring_slot(slot) && !item.is_key() {
+            return Err(InventoryError::ItemDoesntGoToSlot);
+        }
+        let Ok(equipment_slot) = EquipmentSlot::try_from(slot) else {
+            //Anything fits in the backpack, keys fit on the keyring
+            return Ok(());
+        };
+
+        let inventory_type = item.get_inventory_type();
+        if !get_compatible_equipment_slots_for_inventory_type(&inventory_type).contains(&equipment_slot) {
+            return Err(if (inventory::BAG_SLOTS_START..=BAG_SLOTS_END).contains(&slot) {
+                InventoryError::NotABag
+            } else {
+                InventoryError::ItemDoesntGoToSlot
+            });
+        }
+
+        let other_equipped = |other: EquipmentSlot| self.equipped_items.get_item(other).filter(|_| other as u8 != vacated_slot);
+        let blocked = match equipment_slot {
+            EquipmentSlot::MainHand => inventory_type == InventoryType::TwoHandedWeapon && other_equipped(EquipmentSlot::Offhand).is_some(),
+            EquipmentSlot::Offhand => {
+                other_equipped(EquipmentSlot::MainHand).is_some_and(|main_hand| main_hand.get_inventory_type() == InventoryType::TwoHandedWeapon)
+            }
+            _ => false,
+        };
+        if blocked {
+            return Err(InventoryError::CantEquipWithTwohanded);
+        }
+        Ok(())
+    }
+
+    //Swaps the items at both positions, either of which may be empty. Call check_item_swap first.
+    pub async fn swap_items(
+        &mut self,
+        source: (u8, u8),
+        destination: (u8, u8),
+        persistence_queue: Option<&RealmPersistenceQueue>,
+        connection_sender: Option<&flume::Sender<ServerEvent>>,
+    ) -> Result<()> {
+        if source == destination {
+            return Ok(());
+        }
+
+        let destination_item = self.set_item(None, destination, persistence_queue, connection_sender).await?;
+        let source_item = self.set_item(destination_item, source, persistence_queue, connection_sender).await?;
+        self.set_item(source_item, destination, persistence_queue, connection_sender).await?;
+        Ok(())
+    }
+
+    pub fn find_inventory_item(&self, item_guid: Guid) -> Option<(u8, u8)> {
+        (inventory::EQUIPMENT_SLOTS_START..=BagSlot::Item16 as u8)
+            .chain(KEYRING_SLOTS_START..=KEYRING_SLOTS_END)
+            .map(|slot| (slot, INVENTORY_SLOT_BAG_0))
+            .find(|&item_position| self.get_inventory_item(item_position).and_then(|item| item.update_state.object_guid()) == Some(item_guid))
+    }
+
+    pub fn get_free_backpack_slots(&self) -> Vec<u8> {
+        ((BagSlot::Item1 as u8)..=(BagSlot::Item16 as u8))
+            .filter(|&slot| BagSlot::try_from(slot).is_ok_and(|bag_slot| self.bag_items[bag_slot].is_none()))
+            .collect()
+    }
+
+    //Every backpack position holding this item, one item per position since nothing stacks yet
+    pub fn find_backpack_items(&self, item_id: u32) -> Vec<(u8, u8)> {
+        ((BagSlot::Item1 as u8)..=(BagSlot::Item16 as u8))
+            .map(|slot| (slot, INVENTORY_SLOT_BAG_0))
+            .filter(|&item_position| {
+                self.get_inventory_item(item_position)
+                    .and_then(|item| item.update_state.object_entry())
+                    .is_some_and(|entry| entry as u32 == item_id)
+            })
+            .collect()
+    }
+
+    pub(super) async fn send_item_update(item: &Item, connection_sender: &flume::Sender<ServerEvent>) {
         let object = Object {
             update_type: Object_UpdateType::CreateObject {
                 guid3: item.update_state.object_guid().unwrap(),
@@ -181,7 +388,7 @@ impl crate::character::Character {
         &mut self,
         item: Option<Item>,
         item_position: (u8, u8),
-        realm_db: Option<&wrath_realm_db::RealmDatabase>,
+        persistence_queue: Option<&RealmPersistenceQueue>,
         connection_sender: Option<&flume::Sender<ServerEvent>>,
     ) -> Result<Option<Item>> {
         let (slot, bag) = item_position;
@@ -193,10 +400,14 @@ impl crate::character::Character {
         let character_id = self.get_guid().guid() as u32;
 
         if let Ok(equipment_slot) = EquipmentSlot::try_from(slot) {
-            self.set_equipment_item(item, slot, equipment_slot, character_id, realm_db, connection_sender)
+            self.set_equipment_item(item, slot, equipment_slot, character_id, persistence_queue, connection_sender)
                 .await
         } else if let Ok(bag_slot) = inventory::BagSlot::try_from(slot) {
-            self.set_bag_item(item, slot, bag_slot, character_id, realm_db, connection_sender).await
+            self.set_bag_item(item, slot, bag_slot, character_id, persistence_queue, connection_sender)
+                .await
+        } else if is_keyring_slot(slot) {
+            self.set_keyring_item(item, slot, character_id, persistence_queue, connection_sender)
+                .await
         } else {
             todo!("Non-equipment inventory not implemented yet")
         }
@@ -218,7 +429,7 @@ impl crate::character::Character {
     /// Constructed in code as: ((character_id as u64) << 32) | (slot as u64).
     /// Rebuilding preserves other fields (entry, owner, stack, durability) because
     /// `UpdateItem` does not expose setters for those fields.
-    fn set_item_guid(item: &mut Item, guid: Guid) {
+    pub(super) fn set_item_guid(item: &mut Item, guid: Guid) {
         let entry = item.update_state.object_entry().unwrap_or(0);
         let scale = item.update_state.object_scale_x().unwrap_or(1.0);
         let owner = item.update_state.item_owner().unwrap_or(Guid::zero());
@@ -245,7 +456,7 @@ impl crate::character::Character {
         slot: u8,
         equipment_slot: EquipmentSlot,
         character_id: u32,
-        realm_db: Option<&wrath_realm_db::RealmDatabase>,
+        persistence_queue: Option<&RealmPersistenceQueue>,
         connection_sender: Option<&flume::Sender<ServerEvent>>,
     ) -> Result<Option<Item>> {
         let previous_item = self.equipped_items.take_item(equipment_slot);
@@ -253,6 +464,7 @@ impl crate::character::Character {
         match item {
             Some(mut item) => {
                 let item_id = item.update_state.object_entry().unwrap() as u32;
+                let enchant = Some(item.permanent_enchant).filter(|&enchant| enchant != 0);
 
                 let new_guid = ((character_id as u64) << 32 | slot as u64).into();
                 Self::set_item_guid(&mut item, new_guid);
@@ -266,20 +478,20 @@ impl crate::character::Character {
 
                 self.equipped_items.items.insert(equipment_slot, item);
 
-                if let Some(db) = realm_db {
-                    let _ = db.delete_character_item(character_id, slot).await;
-                    let _ = db.insert_character_item(character_id, slot, item_id).await;
+                if let Some(queue) = persistence_queue {
+                    queue.set_character_item(character_id, slot, Some(item_id), enchant);
                 }
             }
             None => {
                 self.clear_visible_item(slot);
                 self.update_inventory_field(slot, Guid::zero());
-                if let Some(db) = realm_db {
-                    let _ = db.delete_character_item(character_id, slot).await;
+                if let Some(queue) = persistence_queue {
+                    queue.set_character_item(character_id, slot, None, None);
                 }
             }
         }
 
+        self.update_combat_ratings();
         Ok(previous_item)
     }
 
@@ -289,13 +501,14 @@ impl crate::character::Character {
         slot: u8,
         bag_slot: BagSlot,
         character_id: u32,
-        realm_db: Option<&wrath_realm_db::RealmDatabase>,
+        persistence_queue: Option<&RealmPersistenceQueue>,
         connection_sender: Option<&flume::Sender<ServerEvent>>,
     ) -> Result<Option<Item>> {
         let previous_item = self.bag_items.take_item(bag_slot);
 
         if let Some(mut item) = item {
             let item_id = item.update_state.object_entry().unwrap() as u32;
+            let enchant = Some(item.permanent_enchant).filter(|&enchant| enchant != 0);
 
             let new_guid = ((character_id as u64) << 32 | slot as u64).into();
             Self::set_item_guid(&mut item, new_guid);
@@ -304,17 +517,16 @@ impl crate::character::Character {
                 Self::send_item_update(&item, sender).await;
             }
 
-            if let Some(db) = realm_db {
-                let _ = db.delete_character_item(character_id, slot).await;
-                let _ = db.insert_character_item(character_id, slot, item_id).await;
+            if let Some(queue) = persistence_queue {
+                queue.set_character_item(character_id, slot, Some(item_id), enchant);
             }
 
             let guid = new_guid;
             self.update_inventory_field(slot, guid);
             self.bag_items[bag_slot] = Some(item);
         } else {
-            if let Some(db) = realm_db {
-                let _ = db.delete_character_item(character_id, slot).await;
+            if let Some(queue) = persistence_queue {
+                queue.set_character_item(character_id, slot, None, None);
             }
             self.update_inventory_field(slot, Guid::zero());
             self.bag_items[bag_slot] = None;
@@ -323,10 +535,13 @@ impl crate::character::Character {
         Ok(previous_item)
     }
 
+    //Every equipment slot has a visible item field, including shirts, tabards and off-hand items that have no stats.
+    //Guild tabards only need the entry here, the client takes the colors from the guild's emblem.
     fn update_visible_item(&mut self, slot: u8, item: &Item) {
         if slot <= inventory::EQUIPMENT_SLOTS_END {
-            //TODO: add display enchants
-            let visible_item = VisibleItem::new(item.update_state.object_entry().unwrap() as u32, [0u16; 2]);
+            //Permanent enchant first, then the temporary one (poisons, oils), which we don't have yet
+            let enchants = [item.permanent_enchant as u16, 0];
+            let visible_item = VisibleItem::new(item.update_state.object_entry().unwrap() as u32, enchants);
             self.gameplay_data
                 .set_player_visible_item(visible_item, VisibleItemIndex::try_from(slot).unwrap());
         }
@@ -339,7 +554,7 @@ impl crate::character::Character {
         }
     }
 
-    fn update_inventory_field(&mut self, slot: u8, guid: Guid) {
+    pub(super) fn update_inventory_field(&mut self, slot: u8, guid: Guid) {
         self.gameplay_data.set_player_field_inv(ItemSlot::try_from(slot).unwrap(), guid);
     }
 
@@ -348,7 +563,7 @@ impl crate::character::Character {
     pub async fn auto_equip_item_from_bag(
         &mut self,
         item_position: (u8, u8),
-        realm_db: Option<&wrath_realm_db::RealmDatabase>,
+        persistence_queue: Option<&RealmPersistenceQueue>,
         connection_sender: Option<&flume::Sender<ServerEvent>>,
     ) -> Result<Option<Item>> {
         let (slot, bag) = item_position;
@@ -364,8 +579,8 @@ impl crate::character::Character {
 
         // Remove previous DB entry for that bag slot since item is being moved
         let character_id = self.get_guid().guid() as u32;
-        if let Some(db) = realm_db {
-            let _ = db.delete_character_item(character_id, bag_slot as u8).await;
+        if let Some(queue) = persistence_queue {
+            queue.set_character_item(character_id, bag_slot as u8, None, None);
         }
 
         let item_inventory = item.get_inventory_type();
@@ -381,8 +596,13 @@ impl crate::character::Character {
             bail!("No compatible equipment slot");
         };
 
-        self.set_item(Some(item), (target_slot as u8, INVENTORY_SLOT_BAG_0), realm_db, connection_sender)
-            .await
+        self.set_item(
+            Some(item),
+            (target_slot as u8, INVENTORY_SLOT_BAG_0),
+            persistence_queue,
+            connection_sender,
+        )
+        .await
     }
 
     // Try to add item to first available backpack slot (BagSlot::Item1-Item16)
@@ -391,11 +611,25 @@ impl crate::character::Character {
         item_id: u32,
         character_id: u32,
         connection_sender: &flume::Sender<ServerEvent>,
-        realm_db: Option<&wrath_realm_db::RealmDatabase>,
+        persistence_queue: Option<&RealmPersistenceQueue>,
+    ) -> Option<u8> {
+        self.try_add_enchanted_item_to_backpack(item_id, 0, character_id, connection_sender, persistence_queue)
+            .await
+    }
+
+    //For items that come back out of storage (mail) with the enchant they had. Keys go on the keyring while it has room.
+    pub async fn try_add_enchanted_item_to_backpack(
+        &mut self,
+        item_id: u32,
+        permanent_enchant: u32,
+        character_id: u32,
+        connection_sender: &flume::Sender<ServerEvent>,
+        persistence_queue: Option<&RealmPersistenceQueue>,
     ) -> Option<u8> {
-        for slot_id in (BagSlot::Item1 as u8)..=(BagSlot::Item16 as u8) {
-            let bag_slot = BagSlot::try_from(slot_id).unwrap();
-            if self.bag_items[bag_slot].is_some() {
+        let keyring_slots = crate::item::is_key_item(item_id).then_some(KEYRING_SLOTS_START..=KEYRING_SLOTS_END);
+        let backpack_slots = (BagSlot::Item1 as u8)..=(BagSlot::Item16 as u8);
+        for slot_id in keyring_slots.into_iter().flatten().chain(backpack_slots) {
+            if self.get_inventory_item((slot_id, INVENTORY_SLOT_BAG_0)).is_some() {
                 continue;
             }
 
@@ -410,10 +644,11 @@ impl crate::character::Character {
                     .set_item_durability(100)
                     .set_item_maxdurability(100)
                     .finalize(),
+                permanent_enchant,
             };
 
             if self
-                .set_item(Some(item), (slot_id, INVENTORY_SLOT_BAG_0), realm_db, Some(connection_sender))
+                .set_item(Some(item), (slot_id, INVENTORY_SLOT_BAG_0), persistence_queue, Some(connection_sender))
                 .await
                 .is_ok()
             {
@@ -0,0 +1,71 @@
+//! The keyring, a range of inventory slots of its own that only takes keys and lockpicks, so they don't
+//! take up room in the backpack.
+
+use super::character_inventory::item_create_objects;
+use crate::connection::events::ServerEvent;
+use crate::item::Item;
+use crate::prelude::*;
+use crate::world::persistence_queue::RealmPersistenceQueue;
+use crate::world::prelude::inventory::{KEYRING_SLOTS_END, KEYRING_SLOTS_START};
+use wow_world_messages::wrath::Object;
+
+const KEYRING_SLOT_COUNT: usize = (KEYRING_SLOTS_END - KEYRING_SLOTS_START + 1) as usize;
+
+pub fn is_keyring_slot(slot: u8) -> bool {
+    (KEYRING_SLOTS_START..=KEYRING_SLOTS_END).contains(&slot)
+}
+
+#[derive(Default)]
+pub struct KeyringInventory {
+    items: [Option<Item>; KEYRING_SLOT_COUNT],
+}
+
+impl KeyringInventory {
+    pub fn get(&self, slot: u8) -> Option<&Item> {
+        self.items.get((slot - KEYRING_SLOTS_START) as usize)?.as_ref()
+    }
+
+    fn take(&mut self, slot: u8) -> Option<Item> {
+        self.items[(slot - KEYRING_SLOTS_START) as usize].take()
+    }
+
+    pub fn get_create_objects(&self) -> Vec<Object> {
+        item_create_objects(self.items.iter().flatten())
+    }
+}
+
+impl super::Character {
+    pub(super) async fn set_keyring_item(
+        &mut self,
+        item: Option<Item>,
+        slot: u8,
+        character_id: u32,
+        persistence_queue: Option<&RealmPersistenceQueue>,
+        connection_sender: Option<&flume::Sender<ServerEvent>>,
+    ) -> Result<Option<Item>> {
+        let previous_item = self.keyring_items.take(slot);
+
+        let Some(mut item) = item else {
+            if let Some(queue) = persistence_queue {
+                queue.set_character_item(character_id, slot, None, None);
+            }
+            self.update_inventory_field(slot, Guid::zero());
+            return Ok(previous_item);
+        };
+
+        let item_id = item.update_state.object_entry().unwrap() as u32;
+        let enchant = Some(item.permanent_enchant).filter(|&enchant| enchant != 0);
+        let new_guid = ((character_id as u64) << 32 | slot as u64).into();
+        Self::set_item_guid(&mut item, new_guid);
+
+        if let Some(sender) = connection_sender {
+            Self::send_item_update(&item, sender).await;
+        }
+        if let Some(queue) = persistence_queue {
+            queue.set_character_item(character_id, slot, Some(item_id), enchant);
+        }
+        self.update_inventory_field(slot, new_guid);
+        self.keyring_items.items[(slot - KEYRING_SLOTS_START) as usize] = Some(item);
+        Ok(previous_item)
+    }
+}
@@ -30,7 +30,7 @@ impl super::Character {
             LogoutState::None if delayed => {
                 self.logout_state = LogoutState::Pending(std::time::Duration::from_secs(20));
                 self.set_stunned(true);
-                self.set_rooted(true).await?;
+                self.add_root().await?;
                 self.set_stand_state(UnitStandState::Sit).await?;
                 (LogoutResult::Success, LogoutSpeed::Delayed)
             }
@@ -45,7 +45,7 @@ impl super::Character {
     pub async fn cancel_logout(&mut self) -> Result<()> {
         if let LogoutState::Pending(_) = self.logout_state {
             self.set_stunned(false);
-            self.set_rooted(false).await?;
+            self.remove_root().await?;
             self.set_stand_state(UnitStandState::Stand).await?;
             self.logout_state = LogoutState::None;
             Ok(())
@@ -54,19 +54,28 @@ impl super::Character {
         }
     }
 
+    //Final state of a character leaving the world, whether it logged out or its client disconnected
+    pub(crate) async fn save_on_leaving_world(&mut self, world: &World) -> Result<()> {
+        self.persist_position_and_playtime(world);
+        self.save_active_pet(&world.get_realm_database()).await
+    }
+
     //This function will trigger every tick as long as the state is LogoutState::Executing
     async fn execute_logout(&mut self, world: &mut World) -> Result<()> {
         if self.teleportation_state != TeleportationState::None {
             return Ok(());
         }
 
-        self.persist_position_and_playtime(world).await?;
+        self.save_on_leaving_world(world).await?;
 
         world
             .get_instance_manager_mut()
             .try_get_map_for_character_mut(self)
             .ok_or_else(|| anyhow!("Invalid map during logout"))?
             .remove_object_by_guid(self.get_guid());
+        world.get_character_info_cache_mut().set_online(self.get_guid(), false);
+        world.on_character_left_world(self.get_guid());
+        world.get_account_data_mut().unload_account(self.account_id);
 
         handlers::send_smsg_logout_complete(self).await?;
 
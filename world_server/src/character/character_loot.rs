@@ -0,0 +1,16 @@
+use crate::prelude::*;
+
+impl super::Character {
+    //The corpse whose loot window is open, the client only names the slot when it takes something
+    pub fn start_looting(&mut self, corpse: Guid) {
+        self.loot_target = Some(corpse);
+    }
+
+    pub fn stop_looting(&mut self) {
+        self.loot_target = None;
+    }
+
+    pub fn get_loot_target(&self) -> Option<Guid> {
+        self.loot_target
+    }
+}
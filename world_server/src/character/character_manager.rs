@@ -1,19 +1,85 @@
 use std::collections::HashMap;
 
 use anyhow::{anyhow, Result};
-use tracing::info;
+use tracing::{info, warn};
 use wow_world_messages::Guid;
 
+use crate::error::GameLogicError;
 use crate::{character::Character, world::prelude::GameObject};
 
-#[derive(Default)]
+//Changes to the characters from code that doesn't hold the manager, applied in order by `process_commands`
+pub enum CharacterCommand {
+    AddCharacter(Box<Character>),
+    RemoveCharacter(Guid),
+    WithCharacterMut(Guid, Box<dyn FnOnce(&mut Character) + Send>),
+}
+
+//Cheap to clone, so clients and other systems can queue character changes without a `&mut CharacterManager`
+#[derive(Clone)]
+pub struct CharacterManagerHandle {
+    sender: flume::Sender<CharacterCommand>,
+}
+
+impl CharacterManagerHandle {
+    pub fn add_character(&self, character: Character) -> Result<()> {
+        self.send(CharacterCommand::AddCharacter(Box::new(character)))
+    }
+
+    #[allow(dead_code)]
+    pub fn remove_character(&self, guid: Guid) -> Result<()> {
+        self.send(CharacterCommand::RemoveCharacter(guid))
+    }
+
+    #[allow(dead_code)]
+    pub fn with_character_mut(&self, guid: Guid, f: impl FnOnce(&mut Character) + Send + 'static) -> Result<()> {
+        self.send(CharacterCommand::WithCharacterMut(guid, Box::new(f)))
+    }
+
+    fn send(&self, command: CharacterCommand) -> Result<()> {
+        self.sender.send(command).map_err(|_| anyhow!("Character manager is gone"))
+    }
+}
+
 pub struct CharacterManager {
     characters: HashMap<Guid, Character>,
+    command_sender: flume::Sender<CharacterCommand>,
+    command_receiver: flume::Receiver<CharacterCommand>,
+}
+
+impl Default for CharacterManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CharacterManager {
     pub fn new() -> Self {
-        Self { characters: HashMap::new() }
+        let (command_sender, command_receiver) = flume::unbounded();
+        Self {
+            characters: HashMap::new(),
+            command_sender,
+            command_receiver,
+        }
+    }
+
+    pub fn get_handle(&self) -> CharacterManagerHandle {
+        CharacterManagerHandle {
+            sender: self.command_sender.clone(),
+        }
+    }
+
+    //Applies the commands queued through handles, called once per tick before anything looks at the characters
+    pub fn process_commands(&mut self) {
+        while let Ok(command) = self.command_receiver.try_recv() {
+            match command {
+                CharacterCommand::AddCharacter(character) => self.add_character(*character),
+                CharacterCommand::RemoveCharacter(guid) => self.remove_character(guid),
+                CharacterCommand::WithCharacterMut(guid, f) => match self.characters.get_mut(&guid) {
+                    Some(character) => f(character),
+                    None => warn!("Dropped a change to character {} that is no longer loaded", guid),
+                },
+            }
+        }
     }
 
     pub fn add_character(&mut self, character: Character) {
@@ -30,15 +96,17 @@ impl CharacterManager {
     }
 
     pub fn get_character(&self, guid: Guid) -> Result<&Character> {
-        self.characters
-            .get(&guid)
-            .ok_or(anyhow!("Character with guid {} not found in character manager", guid))
+        self.characters.get(&guid).ok_or_else(|| GameLogicError::CharacterNotFound(guid).into())
     }
 
     pub fn get_character_mut(&mut self, guid: Guid) -> Result<&mut Character> {
         self.characters
             .get_mut(&guid)
-            .ok_or(anyhow!("Character with guid {} not found in character manager", guid))
+            .ok_or_else(|| GameLogicError::CharacterNotFound(guid).into())
+    }
+
+    pub fn get_all_characters(&self) -> impl Iterator<Item = &Character> {
+        self.characters.values()
     }
 
     pub fn remove_character(&mut self, guid: Guid) {
@@ -46,3 +114,28 @@ impl CharacterManager {
         self.characters.remove(&guid);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queued_commands_apply_in_order() {
+        let mut character_manager = CharacterManager::new();
+        let handle = character_manager.get_handle();
+        let (connection_sender, _receiver) = flume::unbounded();
+        let guid = Guid::new(1);
+
+        handle.add_character(Character::new(connection_sender, guid)).unwrap();
+        handle.with_character_mut(guid, |character| character.name = "Arthas".into()).unwrap();
+        assert!(character_manager.find_character(guid).is_none());
+
+        character_manager.process_commands();
+        assert_eq!(character_manager.get_character(guid).unwrap().name, "Arthas");
+
+        handle.remove_character(guid).unwrap();
+        handle.with_character_mut(guid, |character| character.name = "Uther".into()).unwrap();
+        character_manager.process_commands();
+        assert!(character_manager.find_character(guid).is_none());
+    }
+}
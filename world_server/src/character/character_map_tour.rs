@@ -0,0 +1,79 @@
+//! `.tele tour` takes a character through every map in Map.dbc, one far teleport after the other. Every stop
+//! creates the map in the InstanceManager and leaving it again lets the map shut down, so a bot that runs
+//! the command through the bot gateway smoke-tests the map lifecycle of all maps in one go.
+
+use std::collections::VecDeque;
+
+use crate::data::WorldZoneLocation;
+use crate::handlers::movement_handler::TeleportationDistance;
+use crate::prelude::*;
+
+#[derive(Default)]
+pub(super) struct MapTour {
+    remaining: VecDeque<WorldZoneLocation>,
+    total: usize,
+}
+
+impl MapTour {
+    fn next_stop(&mut self) -> Option<(WorldZoneLocation, usize)> {
+        let stop = self.remaining.pop_front()?;
+        Some((stop, self.total - self.remaining.len()))
+    }
+}
+
+impl super::Character {
+    //The first stop is left to the caller, so it can go through the GM teleport that remembers the recall location
+    pub fn start_map_tour(&mut self, stops: Vec<WorldZoneLocation>) -> Option<WorldZoneLocation> {
+        self.map_tour.total = stops.len();
+        self.map_tour.remaining = stops.into();
+        self.map_tour.next_stop().map(|(stop, _)| stop)
+    }
+
+    pub fn stop_map_tour(&mut self) {
+        self.map_tour = MapTour::default();
+    }
+
+    //Called when a far teleport arrived, moves on to the next map of the tour if there is one
+    pub fn continue_map_tour(&mut self) {
+        if self.map_tour.total == 0 {
+            return;
+        }
+        match self.map_tour.next_stop() {
+            Some((stop, number)) => {
+                info!("Map tour of {}: map {} ({}/{})", self.name, stop.map, number, self.map_tour.total);
+                self.teleport_to(TeleportationDistance::Far(stop));
+            }
+            None => {
+                info!("Map tour of {} visited all {} maps", self.name, self.map_tour.total);
+                self.stop_map_tour();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wow_world_messages::wrath::{Area, Map, Vector3d};
+
+    #[test]
+    fn tour_visits_every_stop_once() {
+        let stop = |map| WorldZoneLocation {
+            map,
+            area: Area::NorthshireValley,
+            position: Vector3d { x: 0.0, y: 0.0, z: 0.0 },
+            orientation: 0.0,
+        };
+        let mut tour = MapTour {
+            remaining: VecDeque::from([stop(Map::EasternKingdoms), stop(Map::Kalimdor), stop(Map::Outland)]),
+            total: 3,
+        };
+
+        assert!(tour
+            .next_stop()
+            .is_some_and(|(stop, number)| stop.map == Map::EasternKingdoms && number == 1));
+        assert!(tour.next_stop().is_some_and(|(stop, number)| stop.map == Map::Kalimdor && number == 2));
+        assert!(tour.next_stop().is_some_and(|(stop, number)| stop.map == Map::Outland && number == 3));
+        assert!(tour.next_stop().is_none());
+    }
+}
@@ -0,0 +1,121 @@
+//! Auto attack. CMSG_ATTACKSWING picks the victim and the swing timer ticks down with the character, once it
+//! runs out the client tick hands the swing to `combat::melee` which rolls it against the victim.
+
+use rand::Rng;
+
+use crate::prelude::*;
+use crate::random;
+use crate::world::prelude::inventory::EquipmentSlot;
+use crate::world::prelude::unit_flags::UnitFlagIndex;
+
+//Bare hands swing every two seconds, same as the client shows in the character sheet
+const UNARMED_ATTACK_TIME_SECONDS: f32 = 2.0;
+//Melee range from the client's "out of range" check, combat reach of two average sized units included
+pub const MELEE_RANGE: f32 = 5.0;
+
+#[derive(Default)]
+pub(super) struct MeleeState {
+    victim: Option<Guid>,
+    //Seconds until the next swing can land, counts down while not attacking too so a swing can't be reset by
+    //stopping and starting again
+    swing_timer: f32,
+    //The client is told once per swing that the victim is too far away, not once every tick
+    reported_out_of_range: bool,
+}
+
+impl super::Character {
+    //Returns false if this was already the victim, the client repeats CMSG_ATTACKSWING when retargeting
+    pub fn start_melee_attack(&mut self, victim: Guid) -> bool {
+        if self.melee_state.victim == Some(victim) {
+            return false;
+        }
+        self.melee_state.victim = Some(victim);
+        self.melee_state.reported_out_of_range = false;
+        self.set_unit_flag_byte(UnitFlagIndex::InCombat, true);
+        true
+    }
+
+    pub fn stop_melee_attack(&mut self) -> Option<Guid> {
+        let victim = self.melee_state.victim.take()?;
+        self.set_unit_flag_byte(UnitFlagIndex::InCombat, false);
+        Some(victim)
+    }
+
+    pub fn get_melee_victim(&self) -> Option<Guid> {
+        self.melee_state.victim
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.gameplay_data.unit_health().unwrap_or(0) > 0
+    }
+
+    //The victim if the swing timer ran out, the timer only restarts once the swing actually happened
+    pub fn get_ready_melee_swing(&self) -> Option<Guid> {
+        self.melee_state.victim.filter(|_| self.melee_state.swing_timer <= 0.0)
+    }
+
+    pub fn finish_melee_swing(&mut self) {
+        self.melee_state.swing_timer = self.get_attack_time();
+        self.melee_state.reported_out_of_range = false;
+    }
+
+    //Returns true the first time per swing, so the caller only sends SMSG_ATTACKSWING_NOTINRANGE once
+    pub fn report_melee_out_of_range(&mut self) -> bool {
+        !std::mem::replace(&mut self.melee_state.reported_out_of_range, true)
+    }
+
+    //Main hand weapon speed in seconds
+    pub fn get_attack_time(&self) -> f32 {
+        self.get_main_hand_weapon()
+            .map(|weapon| weapon.delay() as f32 / 1000.0)
+            .filter(|&delay| delay > 0.0)
+            .unwrap_or(UNARMED_ATTACK_TIME_SECONDS)
+    }
+
+    //Weapon damage, or what a bare fist of this level hits for
+    pub fn roll_melee_damage(&self) -> u32 {
+        let (min, max) = match self.get_main_hand_weapon().and_then(|weapon| weapon.damages().first().copied()) {
+            Some(damage) if damage.damage_maximum > 0.0 => (damage.damage_minimum, damage.damage_maximum),
+            _ => {
+                let level = self.gameplay_data.unit_level().unwrap_or(1).max(1) as f32;
+                (1.0 + level / 2.0, 2.0 + level)
+            }
+        };
+        let damage = random::with_rng(|rng| rng.gen_range(min..=max.max(min)));
+        damage.round().max(1.0) as u32
+    }
+
+    fn get_main_hand_weapon(&self) -> Option<&'static wow_items::wrath::Item> {
+        let item = self.equipped_items.get_item(EquipmentSlot::MainHand)?;
+        wow_items::wrath::lookup_item(item.update_state.object_entry()? as u32)
+    }
+
+    pub(super) fn tick_melee(&mut self, delta_time: f32) {
+        self.melee_state.swing_timer = (self.melee_state.swing_timer - delta_time).max(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Character;
+    use super::*;
+
+    #[test]
+    fn swing_timer_restarts_after_each_swing() {
+        let (sender, _receiver) = flume::unbounded();
+        let mut character = Character::new(sender, Guid::new(1));
+        let victim = Guid::new(2);
+
+        assert!(character.start_melee_attack(victim));
+        assert!(!character.start_melee_attack(victim));
+        assert_eq!(character.get_ready_melee_swing(), Some(victim));
+
+        character.finish_melee_swing();
+        assert_eq!(character.get_ready_melee_swing(), None);
+        character.tick_melee(UNARMED_ATTACK_TIME_SECONDS);
+        assert_eq!(character.get_ready_melee_swing(), Some(victim));
+
+        assert_eq!(character.stop_melee_attack(), Some(victim));
+        assert_eq!(character.get_ready_melee_swing(), None);
+    }
+}
@@ -1,3 +1,4 @@
+use super::character_movement_acks::MovementChange;
 use crate::data::{PositionAndOrientation, WorldZoneLocation};
 use crate::handlers::movement_handler::{TeleportationDistance, TeleportationState};
 use crate::prelude::*;
@@ -9,9 +10,41 @@ impl super::Character {
         self.movement_info = movement_info;
     }
 
+    //Turning on the spot doesn't count, it doesn't stop casts
+    pub fn is_moving(&self) -> bool {
+        let flags = &self.movement_info.flags;
+        flags.is_forward() || flags.is_backward() || flags.is_strafe_left() || flags.is_strafe_right() || flags.get_falling().is_some()
+    }
+
+    //Only used for teleports, walking goes through process_movement
     pub fn set_position(&mut self, position: &PositionAndOrientation) {
         self.movement_info.position = position.position;
         self.movement_info.orientation = position.orientation;
+        self.request_visibility_update();
+    }
+
+    //For sudden jumps in what the character can see, like teleports, which shouldn't wait for the
+    //character's turn in the time-sliced visibility updates
+    pub fn request_visibility_update(&mut self) {
+        self.visibility_update_requested = true;
+    }
+
+    pub fn take_visibility_update_request(&mut self) -> bool {
+        std::mem::take(&mut self.visibility_update_requested)
+    }
+
+    //Heartbeats only repeat what the last movement packet said, so they can be passed on less often than the
+    //client sends them. Returns whether this one goes out, interval is the map's movement relay interval.
+    pub fn take_movement_relay_turn(&mut self, interval: f32) -> bool {
+        if self.movement_relay_cooldown > 0.0 {
+            return false;
+        }
+        self.movement_relay_cooldown = interval;
+        true
+    }
+
+    pub(super) fn tick_movement_relay(&mut self, delta_time: f32) {
+        self.movement_relay_cooldown = (self.movement_relay_cooldown - delta_time).max(0.0);
     }
 
     fn reset_move_flags(&mut self) {
@@ -40,7 +73,8 @@ impl super::Character {
         //The rest of the teleportation is handled when the client sends back this packet
         self.teleportation_state = TeleportationState::Executing(TeleportationDistance::Near(destination.clone()));
 
-        handlers::send_msg_move_teleport_ack(self, &destination).await?;
+        let movement_counter = self.queue_movement_change(MovementChange::Teleport);
+        handlers::send_msg_move_teleport_ack(self, &destination, movement_counter).await?;
         Ok(())
     }
 
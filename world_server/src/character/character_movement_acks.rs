@@ -0,0 +1,122 @@
+use std::collections::VecDeque;
+
+use super::character_forced_movement::{KnockBack, MovementCapability};
+use crate::prelude::*;
+
+//Clients get this long to acknowledge a forced movement change before they count as ignoring it
+const MOVEMENT_ACK_TIMEOUT_SECONDS: f32 = 5.0;
+
+//Server initiated movement changes that the client has to acknowledge
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MovementChange {
+    Root,
+    Unroot,
+    RunSpeed(f32),
+    RunBackSpeed(f32),
+    Teleport,
+    KnockBack(KnockBack),
+    Capability(MovementCapability, bool),
+}
+
+//The kind of ack packet that came in, fly and unfly share one ack packet so capabilities only match on the capability
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MovementAck {
+    Root,
+    Unroot,
+    RunSpeed,
+    RunBackSpeed,
+    Teleport,
+    KnockBack,
+    Capability(MovementCapability),
+}
+
+impl MovementChange {
+    fn is_acknowledged_by(&self, ack: MovementAck) -> bool {
+        match (*self, ack) {
+            (MovementChange::Root, MovementAck::Root)
+            | (MovementChange::Unroot, MovementAck::Unroot)
+            | (MovementChange::RunSpeed(_), MovementAck::RunSpeed)
+            | (MovementChange::RunBackSpeed(_), MovementAck::RunBackSpeed)
+            | (MovementChange::Teleport, MovementAck::Teleport)
+            | (MovementChange::KnockBack(_), MovementAck::KnockBack) => true,
+            (MovementChange::Capability(capability, _), MovementAck::Capability(acked)) => capability == acked,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PendingMovementChange {
+    counter: u32,
+    change: MovementChange,
+    seconds_waiting: f32,
+}
+
+#[derive(Default, Debug)]
+pub(super) struct MovementAckState {
+    //Every change carries a counter that the client echoes back in its ack
+    movement_counter: u32,
+    pending: VecDeque<PendingMovementChange>,
+    //Acks that were ignored, didn't match anything we sent or had the wrong type. An honest client can
+    //collect a few when it lags or disconnects, but a steady stream of them points to a cheat tool.
+    violations: u32,
+}
+
+impl super::Character {
+    //Returns the counter to put in the packet that tells the client about the change
+    pub(super) fn queue_movement_change(&mut self, change: MovementChange) -> u32 {
+        let state = &mut self.movement_ack_state;
+        state.movement_counter = state.movement_counter.wrapping_add(1);
+        state.pending.push_back(PendingMovementChange {
+            counter: state.movement_counter,
+            change,
+            seconds_waiting: 0.0,
+        });
+        state.movement_counter
+    }
+
+    //Returns the change the ack belongs to, or None if the client acked something the server never sent
+    pub fn acknowledge_movement_change(&mut self, ack: MovementAck, counter: u32) -> Option<MovementChange> {
+        let state = &mut self.movement_ack_state;
+        let index = state
+            .pending
+            .iter()
+            .position(|pending| pending.counter == counter && pending.change.is_acknowledged_by(ack));
+        match index.and_then(|index| state.pending.remove(index)) {
+            Some(pending) => Some(pending.change),
+            None => {
+                self.record_movement_ack_violation(&format!("sent a {:?} ack with unknown counter {}", ack, counter));
+                None
+            }
+        }
+    }
+
+    pub fn record_movement_ack_violation(&mut self, reason: &str) {
+        self.movement_ack_state.violations += 1;
+        warn!(
+            "{} {} ({} movement ack violations)",
+            self.name, reason, self.movement_ack_state.violations
+        );
+    }
+
+    pub fn get_movement_ack_violations(&self) -> u32 {
+        self.movement_ack_state.violations
+    }
+
+    pub(super) fn tick_movement_acks(&mut self, delta_time: f32) {
+        let pending = &mut self.movement_ack_state.pending;
+        for change in pending.iter_mut() {
+            change.seconds_waiting += delta_time;
+        }
+
+        //Changes are queued in order, so the ones that timed out are always at the front
+        let num_expired = pending
+            .iter()
+            .take_while(|change| change.seconds_waiting > MOVEMENT_ACK_TIMEOUT_SECONDS)
+            .count();
+        let expired: Vec<PendingMovementChange> = pending.drain(..num_expired).collect();
+        for change in expired {
+            self.record_movement_ack_violation(&format!("never acknowledged {:?} with counter {}", change.change, change.counter));
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use wrath_realm_db::pet::{DBCharacterPet, DBPetSpell, DBPetTalent, PET_TYPE_HUNTER};
+use wrath_realm_db::RealmDatabase;
+
+use crate::prelude::*;
+
+//Happiness is split in three equally sized levels: unhappy, content and happy
+const HAPPINESS_LEVEL_SIZE: u32 = 333000;
+const MAX_HAPPINESS: u32 = HAPPINESS_LEVEL_SIZE * 3;
+const HAPPINESS_DECAY_INTERVAL_SECONDS: f32 = 7.5;
+const HAPPINESS_DECAY_PER_INTERVAL: u32 = 670;
+
+//Pets get their first talent point at level 20 and another one every four levels after that
+const FIRST_TALENT_POINT_LEVEL: u8 = 20;
+const LEVELS_PER_TALENT_POINT: u8 = 4;
+const MAX_PET_TALENT_RANK: u8 = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PetHappiness {
+    Unhappy,
+    Content,
+    Happy,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PetTalentError {
+    NoActivePet,
+    NotEnoughPoints,
+    InvalidRank,
+}
+
+struct ActivePet {
+    info: DBCharacterPet,
+    talents: HashMap<u32, u8>,
+    //Spells the pet knows, with whether they are set to autocast on the pet action bar
+    spells: HashMap<u32, bool>,
+}
+
+impl ActivePet {
+    fn spent_talent_points(&self) -> u8 {
+        self.talents.values().sum()
+    }
+
+    fn total_talent_points(&self) -> u8 {
+        if self.info.level < FIRST_TALENT_POINT_LEVEL {
+            return 0;
+        }
+        1 + (self.info.level - FIRST_TALENT_POINT_LEVEL) / LEVELS_PER_TALENT_POINT
+    }
+}
+
+#[derive(Default)]
+pub(super) struct PetState {
+    active: Option<ActivePet>,
+    happiness_decay_cooldown: f32,
+}
+
+impl super::Character {
+    //Summons the pet in the first slot, with the talents, autocast settings and happiness it had when it was dismissed.
+    //Warlocks keep one of each demon there, entry picks which one, 0 takes whichever pet is there.
+    pub async fn summon_pet(&mut self, realm_db: &RealmDatabase, entry: u32) -> Result<bool> {
+        let character_id = self.get_guid().guid() as u32;
+        let Some(info) = realm_db
+            .get_character_pets(character_id)
+            .await?
+            .into_iter()
+            .find(|pet| pet.slot == 0 && (entry == 0 || pet.entry == entry))
+        else {
+            return Ok(false);
+        };
+        //The pet that was out until now goes back with everything it learned
+        self.save_active_pet(realm_db).await?;
+
+        let talents = realm_db
+            .get_pet_talents(info.id)
+            .await?
+            .into_iter()
+            .map(|talent| (talent.talent_id, talent.rank))
+            .collect();
+        let spells = realm_db
+            .get_pet_spells(info.id)
+            .await?
+            .into_iter()
+            .map(|spell| (spell.spell_id, spell.autocast != 0))
+            .collect();
+
+        self.pet_state.active = Some(ActivePet { info, talents, spells });
+        Ok(true)
+    }
+
+    //New pets start out content, taming or summoning them for the first time shouldn't make them sulk
+    pub async fn create_pet(&mut self, realm_db: &RealmDatabase, entry: u32, pet_type: u8, name: &str) -> Result<()> {
+        let owner_id = self.get_guid().guid() as u32;
+        let level = self.gameplay_data.unit_level().unwrap_or(1) as u8;
+        let happiness = HAPPINESS_LEVEL_SIZE * 2 - 1;
+        realm_db.create_character_pet(owner_id, entry, pet_type, name, level, happiness).await?;
+        self.summon_pet(realm_db, entry).await?;
+        Ok(())
+    }
+
+    pub async fn dismiss_pet(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        self.save_active_pet(realm_db).await?;
+        self.pet_state.active = None;
+        Ok(())
+    }
+
+    pub(super) async fn save_active_pet(&self, realm_db: &RealmDatabase) -> Result<()> {
+        let Some(pet) = &self.pet_state.active else {
+            return Ok(());
+        };
+
+        let talents: Vec<DBPetTalent> = pet.talents.iter().map(|(&talent_id, &rank)| DBPetTalent { talent_id, rank }).collect();
+        let spells: Vec<DBPetSpell> = pet
+            .spells
+            .iter()
+            .map(|(&spell_id, &autocast)| DBPetSpell {
+                spell_id,
+                autocast: autocast as u8,
+            })
+            .collect();
+        realm_db.save_character_pet(&pet.info, &talents, &spells).await
+    }
+
+    pub fn get_pet_happiness(&self) -> Option<PetHappiness> {
+        let pet = self.pet_state.active.as_ref().filter(|pet| pet.info.pet_type == PET_TYPE_HUNTER)?;
+        Some(match pet.info.happiness / HAPPINESS_LEVEL_SIZE {
+            0 => PetHappiness::Unhappy,
+            1 => PetHappiness::Content,
+            _ => PetHappiness::Happy,
+        })
+    }
+
+    //Called by the Feed Pet effect with the level of the food item, returns how much happiness the pet gained.
+    //Food that is too far below the pet's level does nothing.
+    pub fn feed_pet(&mut self, food_item_level: u8) -> u32 {
+        let Some(pet) = self.pet_state.active.as_mut().filter(|pet| pet.info.pet_type == PET_TYPE_HUNTER) else {
+            return 0;
+        };
+
+        let level_difference = pet.info.level as i32 - food_item_level as i32;
+        let gain = match level_difference {
+            ..=5 => 35000,
+            6..=10 => 17000,
+            11..=14 => 8000,
+            _ => 0,
+        };
+        pet.info.happiness = (pet.info.happiness + gain).min(MAX_HAPPINESS);
+        gain
+    }
+
+    pub fn learn_pet_talent(&mut self, talent_id: u32, rank: u8) -> std::result::Result<(), PetTalentError> {
+        let pet = self.pet_state.active.as_mut().ok_or(PetTalentError::NoActivePet)?;
+        if pet.spent_talent_points() >= pet.total_talent_points() {
+            return Err(PetTalentError::NotEnoughPoints);
+        }
+
+        //Ranks are learned one at a time, the client sends the zero based rank it wants next
+        let current_rank = pet.talents.get(&talent_id).copied().unwrap_or(0);
+        if rank != current_rank || rank >= MAX_PET_TALENT_RANK {
+            return Err(PetTalentError::InvalidRank);
+        }
+
+        pet.talents.insert(talent_id, current_rank + 1);
+        Ok(())
+    }
+
+    //Returns false when there is no pet to reset
+    pub fn reset_pet_talents(&mut self) -> bool {
+        let Some(pet) = self.pet_state.active.as_mut() else {
+            return false;
+        };
+        pet.talents.clear();
+        true
+    }
+
+    //Only spells the pet actually knows can be toggled, returns whether anything changed
+    pub fn set_pet_spell_autocast(&mut self, spell_id: u32, autocast: bool) -> bool {
+        let Some(known) = self.pet_state.active.as_mut().and_then(|pet| pet.spells.get_mut(&spell_id)) else {
+            return false;
+        };
+        *known = autocast;
+        true
+    }
+
+    //Returns false when there is no pet to teach
+    pub fn teach_pet_spell(&mut self, spell_id: u32) -> bool {
+        let Some(pet) = self.pet_state.active.as_mut() else {
+            return false;
+        };
+        pet.spells.entry(spell_id).or_insert(false);
+        true
+    }
+
+    pub(super) fn tick_pet(&mut self, delta_time: f32) {
+        let state = &mut self.pet_state;
+        let Some(pet) = state.active.as_mut().filter(|pet| pet.info.pet_type == PET_TYPE_HUNTER) else {
+            return;
+        };
+
+        state.happiness_decay_cooldown -= delta_time;
+        if state.happiness_decay_cooldown <= 0.0 {
+            state.happiness_decay_cooldown += HAPPINESS_DECAY_INTERVAL_SECONDS;
+            pet.info.happiness = pet.info.happiness.saturating_sub(HAPPINESS_DECAY_PER_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use wow_world_messages::wrath::SMSG_SET_PHASE_SHIFT;
+
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+
+//Everything that isn't explicitly phased lives in the first phase
+pub const PHASEMASK_NORMAL: u32 = 1;
+
+#[derive(Debug)]
+pub(super) struct PhaseState {
+    phase_mask: u32,
+}
+
+impl Default for PhaseState {
+    fn default() -> Self {
+        Self {
+            phase_mask: PHASEMASK_NORMAL,
+        }
+    }
+}
+
+impl super::Character {
+    pub fn get_character_phase_mask(&self) -> u32 {
+        self.phase_state.phase_mask
+    }
+
+    //Objects outside the new phases disappear on the next in-range update of the map
+    pub async fn set_phase_mask(&mut self, phase_mask: u32) -> Result<()> {
+        self.phase_state.phase_mask = phase_mask;
+        ServerEvent::SetPhaseShift(SMSG_SET_PHASE_SHIFT { new_phase: phase_mask })
+            .send_to_character(self)
+            .await
+    }
+}
@@ -0,0 +1,327 @@
+use wow_world_messages::wrath::{Power, SMSG_UPDATE_COMBO_POINTS};
+
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+
+//Rage and runic power are stored multiplied by ten, the client divides them again for display
+const MAX_RAGE: i32 = 1000;
+const MAX_RUNIC_POWER: i32 = 1000;
+const MAX_ENERGY: i32 = 100;
+const MAX_COMBO_POINTS: u8 = 5;
+
+//Energy comes in bursts every two seconds, the client smooths it out in between
+const ENERGY_TICK_SECONDS: f32 = 2.0;
+const ENERGY_PER_TICK: i32 = 20;
+
+//Rage only drains once the character has neither dealt nor taken damage for a while
+const RAGE_DECAY_DELAY_SECONDS: f32 = 5.0;
+const RAGE_DECAY_PER_SECOND: f32 = 12.5;
+
+const RUNE_COOLDOWN_SECONDS: f32 = 10.0;
+const RUNIC_POWER_PER_RUNE: i32 = 100;
+const NUM_RUNES: usize = 6;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RuneType {
+    Blood,
+    Unholy,
+    Frost,
+    //Blood, unholy and frost runes can be converted, a death rune pays for any of them
+    Death,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Rune {
+    base_type: RuneType,
+    current_type: RuneType,
+    cooldown: f32,
+}
+
+impl Rune {
+    fn new(rune_type: RuneType) -> Self {
+        Self {
+            base_type: rune_type,
+            current_type: rune_type,
+            cooldown: 0.0,
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        self.cooldown <= 0.0
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RuneCost {
+    pub blood: u8,
+    pub unholy: u8,
+    pub frost: u8,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PowerCost {
+    pub power: Power,
+    pub amount: i32,
+    pub runes: RuneCost,
+    //Finishing moves need combo points on the current target
+    pub requires_combo_points: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PowerCostError {
+    NotEnoughPower(Power),
+    NotEnoughRunes,
+    NoComboPoints,
+}
+
+pub(super) struct ClassPowerState {
+    combo_points: Option<(Guid, u8)>,
+    energy_tick_cooldown: f32,
+    seconds_since_rage_change: f32,
+    rage_decay_remainder: f32,
+    runes: [Rune; NUM_RUNES],
+}
+
+impl Default for ClassPowerState {
+    fn default() -> Self {
+        Self {
+            combo_points: None,
+            energy_tick_cooldown: ENERGY_TICK_SECONDS,
+            seconds_since_rage_change: 0.0,
+            rage_decay_remainder: 0.0,
+            runes: [
+                Rune::new(RuneType::Blood),
+                Rune::new(RuneType::Blood),
+                Rune::new(RuneType::Unholy),
+                Rune::new(RuneType::Unholy),
+                Rune::new(RuneType::Frost),
+                Rune::new(RuneType::Frost),
+            ],
+        }
+    }
+}
+
+impl super::Character {
+    pub(super) fn init_class_power(&mut self) {
+        self.set_max_power(Power::Rage, MAX_RAGE);
+        self.set_power(Power::Rage, 0);
+        self.set_max_power(Power::Energy, MAX_ENERGY);
+        self.set_power(Power::Energy, MAX_ENERGY);
+        self.set_max_power(Power::RunicPower, MAX_RUNIC_POWER);
+        self.set_power(Power::RunicPower, 0);
+    }
+
+    pub fn get_power(&self, power: Power) -> i32 {
+        let data = &self.gameplay_data;
+        match power.as_int() {
+            0 => data.unit_power1(),
+            1 => data.unit_power2(),
+            2 => data.unit_power3(),
+            3 => data.unit_power4(),
+            4 => data.unit_power5(),
+            5 => data.unit_power6(),
+            6 => data.unit_power7(),
+            _ => None,
+        }
+        .unwrap_or(0)
+    }
+
+    pub fn get_max_power(&self, power: Power) -> i32 {
+        let data = &self.gameplay_data;
+        match power.as_int() {
+            0 => data.unit_maxpower1(),
+            1 => data.unit_maxpower2(),
+            2 => data.unit_maxpower3(),
+            3 => data.unit_maxpower4(),
+            4 => data.unit_maxpower5(),
+            5 => data.unit_maxpower6(),
+            6 => data.unit_maxpower7(),
+            _ => None,
+        }
+        .unwrap_or(0)
+    }
+
+    pub fn set_power(&mut self, power: Power, value: i32) {
+        let value = value.clamp(0, self.get_max_power(power));
+        let data = &mut self.gameplay_data;
+        match power.as_int() {
+            0 => data.set_unit_power1(value),
+            1 => data.set_unit_power2(value),
+            2 => data.set_unit_power3(value),
+            3 => data.set_unit_power4(value),
+            4 => data.set_unit_power5(value),
+            5 => data.set_unit_power6(value),
+            6 => data.set_unit_power7(value),
+            _ => {}
+        }
+    }
+
+    fn set_max_power(&mut self, power: Power, value: i32) {
+        let data = &mut self.gameplay_data;
+        match power.as_int() {
+            0 => data.set_unit_maxpower1(value),
+            1 => data.set_unit_maxpower2(value),
+            2 => data.set_unit_maxpower3(value),
+            3 => data.set_unit_maxpower4(value),
+            4 => data.set_unit_maxpower5(value),
+            5 => data.set_unit_maxpower6(value),
+            6 => data.set_unit_maxpower7(value),
+            _ => {}
+        }
+    }
+
+    //Rage conversion value for the character's level, higher levels need more damage for the same rage
+    fn rage_conversion(&self) -> f32 {
+        let level = self.gameplay_data.unit_level().unwrap_or(1) as f32;
+        0.0091107836 * level * level + 3.225598133 * level + 4.2652911
+    }
+
+    //hit_factor is 3.5 for main hand hits, 1.75 for off hand hits and doubled for crits
+    pub fn add_rage_from_damage_dealt(&mut self, damage: u32, weapon_speed: f32, hit_factor: f32) {
+        let conversion = self.rage_conversion();
+        let rage = (7.5 * damage as f32 / conversion + hit_factor * weapon_speed) / 2.0;
+        let rage = rage.min(15.0 * damage as f32 / conversion);
+        self.add_rage(rage);
+    }
+
+    pub fn add_rage_from_damage_taken(&mut self, damage: u32) {
+        let rage = 2.5 * damage as f32 / self.rage_conversion();
+        self.add_rage(rage);
+    }
+
+    fn add_rage(&mut self, rage: f32) {
+        let current = self.get_power(Power::Rage);
+        self.set_power(Power::Rage, current + (rage * 10.0) as i32);
+        self.class_power_state.seconds_since_rage_change = 0.0;
+    }
+
+    pub fn get_combo_points(&self, target: Guid) -> u8 {
+        match self.class_power_state.combo_points {
+            Some((combo_target, points)) if combo_target == target => points,
+            _ => 0,
+        }
+    }
+
+    //Combo points belong to one target, building them on another target starts over
+    pub async fn add_combo_points(&mut self, target: Guid, amount: u8) -> Result<()> {
+        let points = (self.get_combo_points(target) + amount).min(MAX_COMBO_POINTS);
+        self.class_power_state.combo_points = Some((target, points));
+        self.send_combo_points(target, points).await
+    }
+
+    pub async fn clear_combo_points(&mut self) -> Result<()> {
+        if let Some((target, _)) = self.class_power_state.combo_points.take() {
+            self.send_combo_points(target, 0).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_combo_points(&self, target: Guid, combo_points: u8) -> Result<()> {
+        ServerEvent::UpdateComboPoints(SMSG_UPDATE_COMBO_POINTS { target, combo_points })
+            .send_to_character(self)
+            .await
+    }
+
+    pub fn get_ready_runes(&self, rune_type: RuneType) -> u8 {
+        self.class_power_state
+            .runes
+            .iter()
+            .filter(|rune| rune.is_ready() && rune.current_type == rune_type)
+            .count() as u8
+    }
+
+    pub fn check_power_cost(&self, cost: &PowerCost) -> std::result::Result<(), PowerCostError> {
+        if cost.amount > 0 && self.get_power(cost.power) < cost.amount {
+            return Err(PowerCostError::NotEnoughPower(cost.power));
+        }
+
+        if cost.requires_combo_points {
+            let has_points = self.get_selection().is_some_and(|target| self.get_combo_points(target) > 0);
+            if !has_points {
+                return Err(PowerCostError::NoComboPoints);
+            }
+        }
+
+        let runes = cost.runes;
+        let missing = runes.blood.saturating_sub(self.get_ready_runes(RuneType::Blood))
+            + runes.unholy.saturating_sub(self.get_ready_runes(RuneType::Unholy))
+            + runes.frost.saturating_sub(self.get_ready_runes(RuneType::Frost));
+        if missing > self.get_ready_runes(RuneType::Death) {
+            return Err(PowerCostError::NotEnoughRunes);
+        }
+
+        Ok(())
+    }
+
+    //Callers validate with check_power_cost first, this only takes what is there
+    pub async fn spend_power_cost(&mut self, cost: &PowerCost) -> Result<()> {
+        if cost.amount > 0 {
+            let current = self.get_power(cost.power);
+            self.set_power(cost.power, current - cost.amount);
+        }
+
+        if cost.requires_combo_points {
+            self.clear_combo_points().await?;
+        }
+
+        let runes = cost.runes;
+        let spent = self.spend_runes(RuneType::Blood, runes.blood)
+            + self.spend_runes(RuneType::Unholy, runes.unholy)
+            + self.spend_runes(RuneType::Frost, runes.frost);
+        if spent > 0 {
+            let current = self.get_power(Power::RunicPower);
+            self.set_power(Power::RunicPower, current + spent as i32 * RUNIC_POWER_PER_RUNE);
+        }
+        Ok(())
+    }
+
+    //Uses runes of the requested type first and death runes for the rest. Spent death runes turn back into their base type.
+    fn spend_runes(&mut self, rune_type: RuneType, amount: u8) -> u8 {
+        let mut spent = 0;
+        for wanted in [rune_type, RuneType::Death] {
+            for rune in self.class_power_state.runes.iter_mut() {
+                if spent == amount {
+                    return spent;
+                }
+                if rune.is_ready() && rune.current_type == wanted {
+                    rune.cooldown = RUNE_COOLDOWN_SECONDS;
+                    rune.current_type = rune.base_type;
+                    spent += 1;
+                }
+            }
+        }
+        spent
+    }
+
+    pub(super) fn tick_class_power(&mut self, delta_time: f32) {
+        let state = &mut self.class_power_state;
+        for rune in state.runes.iter_mut() {
+            rune.cooldown = (rune.cooldown - delta_time).max(0.0);
+        }
+
+        state.energy_tick_cooldown -= delta_time;
+        let energy_tick = state.energy_tick_cooldown <= 0.0;
+        if energy_tick {
+            state.energy_tick_cooldown += ENERGY_TICK_SECONDS;
+        }
+
+        state.seconds_since_rage_change += delta_time;
+        let rage_decay = if state.seconds_since_rage_change >= RAGE_DECAY_DELAY_SECONDS {
+            state.rage_decay_remainder += RAGE_DECAY_PER_SECOND * delta_time;
+            let decay = state.rage_decay_remainder as i32;
+            state.rage_decay_remainder -= decay as f32;
+            decay
+        } else {
+            0
+        };
+
+        if energy_tick && self.get_power(Power::Energy) < self.get_max_power(Power::Energy) {
+            let current = self.get_power(Power::Energy);
+            self.set_power(Power::Energy, current + ENERGY_PER_TICK);
+        }
+        if rage_decay > 0 && self.get_power(Power::Rage) > 0 {
+            let current = self.get_power(Power::Rage);
+            self.set_power(Power::Rage, current - rage_decay);
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use wrath_game_db::DBQuestTemplate;
+use wrath_realm_db::RealmDatabase;
+
+use crate::prelude::*;
+
+pub const MAX_QUEST_LOG_SIZE: usize = 25;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QuestStatus {
+    InQuestLog,
+    Rewarded,
+}
+
+impl QuestStatus {
+    fn from_db(status: u8) -> Self {
+        match status {
+            0 => QuestStatus::InQuestLog,
+            _ => QuestStatus::Rewarded,
+        }
+    }
+
+    fn as_db(self) -> u8 {
+        match self {
+            QuestStatus::InQuestLog => 0,
+            QuestStatus::Rewarded => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QuestOfferError {
+    AlreadyOnQuest,
+    AlreadyDone,
+    LevelTooLow,
+    QuestLogFull,
+}
+
+#[derive(Default)]
+pub(super) struct QuestState {
+    statuses: HashMap<u32, QuestStatus>,
+    //Quest ids by quest log slot, the client shows them in this order
+    quest_log: [Option<u32>; MAX_QUEST_LOG_SIZE],
+    //A follow-up quest offered by turning in its previous quest, it has no item to be taken from
+    offered_follow_up: Option<u32>,
+    //How often each objective was done by quest and objective entry, for the quests in the quest log
+    objective_counts: HashMap<(u32, u32), u8>,
+}
+
+impl super::Character {
+    pub(super) async fn load_quests(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        let character_id = self.get_guid().guid() as u32;
+        for db_status in realm_db.get_character_quest_statuses(character_id).await? {
+            let status = QuestStatus::from_db(db_status.status);
+            self.quest_state.statuses.insert(db_status.quest_id, status);
+            if status == QuestStatus::InQuestLog && self.put_in_quest_log(db_status.quest_id).is_none() {
+                warn!("Quest log of {} is full, quest {} is not shown", self.name, db_status.quest_id);
+            }
+        }
+        for objective in realm_db.get_character_quest_objectives(character_id).await? {
+            self.quest_state
+                .objective_counts
+                .insert((objective.quest_id, objective.objective_entry), objective.count);
+        }
+        Ok(())
+    }
+
+    //Quest ids in quest log order
+    pub fn get_quests_in_log(&self) -> Vec<u32> {
+        self.quest_state.quest_log.iter().flatten().copied().collect()
+    }
+
+    pub fn get_quest_objective_count(&self, quest_id: u32, objective_entry: u32) -> u8 {
+        self.quest_state.objective_counts.get(&(quest_id, objective_entry)).copied().unwrap_or(0)
+    }
+
+    //Counts the objective once more, unless it's already done required times. Returns the new count if it counted.
+    pub async fn add_quest_objective_credit(
+        &mut self,
+        realm_db: &RealmDatabase,
+        quest_id: u32,
+        objective_entry: u32,
+        required: u8,
+    ) -> Result<Option<u8>> {
+        let count = self.quest_state.objective_counts.entry((quest_id, objective_entry)).or_default();
+        if *count >= required {
+            return Ok(None);
+        }
+        *count += 1;
+        let count = *count;
+        let character_id = self.get_guid().guid() as u32;
+        realm_db
+            .set_character_quest_objective(character_id, quest_id, objective_entry, count)
+            .await?;
+        Ok(Some(count))
+    }
+
+    pub fn get_quest_status(&self, quest_id: u32) -> Option<QuestStatus> {
+        self.quest_state.statuses.get(&quest_id).copied()
+    }
+
+    pub fn check_quest_offer(&self, quest: &DBQuestTemplate) -> std::result::Result<(), QuestOfferError> {
+        match self.get_quest_status(quest.id) {
+            Some(QuestStatus::InQuestLog) => return Err(QuestOfferError::AlreadyOnQuest),
+            Some(QuestStatus::Rewarded) => return Err(QuestOfferError::AlreadyDone),
+            None => {}
+        }
+        if (self.gameplay_data.unit_level().unwrap_or(1) as u8) < quest.min_level {
+            return Err(QuestOfferError::LevelTooLow);
+        }
+        if self.quest_state.quest_log.iter().all(Option::is_some) {
+            return Err(QuestOfferError::QuestLogFull);
+        }
+        Ok(())
+    }
+
+    //Call check_quest_offer first
+    pub async fn add_quest(&mut self, realm_db: &RealmDatabase, quest_id: u32) -> Result<()> {
+        if self.put_in_quest_log(quest_id).is_none() {
+            bail!("Quest log of {} is full", self.name);
+        }
+        self.quest_state.statuses.insert(quest_id, QuestStatus::InQuestLog);
+        let character_id = self.get_guid().guid() as u32;
+        realm_db
+            .set_character_quest_status(character_id, quest_id, QuestStatus::InQuestLog.as_db())
+            .await
+    }
+
+    //Call check_quest_offer first
+    pub fn offer_follow_up_quest(&mut self, quest_id: u32) {
+        self.quest_state.offered_follow_up = Some(quest_id);
+    }
+
+    pub fn take_offered_follow_up_quest(&mut self, quest_id: u32) -> bool {
+        if self.quest_state.offered_follow_up != Some(quest_id) {
+            return false;
+        }
+        self.quest_state.offered_follow_up = None;
+        true
+    }
+
+    //Takes the quest out of the quest log for good, it can't be taken again
+    pub async fn set_quest_rewarded(&mut self, realm_db: &RealmDatabase, quest_id: u32) -> Result<()> {
+        if let Some(slot) = self.quest_state.quest_log.iter().position(|&quest| quest == Some(quest_id)) {
+            self.quest_state.quest_log[slot] = None;
+            self.set_quest_log_field(slot, 0);
+        }
+        self.quest_state.statuses.insert(quest_id, QuestStatus::Rewarded);
+        self.quest_state.objective_counts.retain(|&(quest, _), _| quest != quest_id);
+        let character_id = self.get_guid().guid() as u32;
+        realm_db.delete_character_quest_objectives(character_id, quest_id).await?;
+        realm_db
+            .set_character_quest_status(character_id, quest_id, QuestStatus::Rewarded.as_db())
+            .await
+    }
+
+    fn put_in_quest_log(&mut self, quest_id: u32) -> Option<usize> {
+        let slot = self.quest_state.quest_log.iter().position(Option::is_none)?;
+        self.quest_state.quest_log[slot] = Some(quest_id);
+        self.set_quest_log_field(slot, quest_id as i32);
+        Some(slot)
+    }
+
+    fn set_quest_log_field(&mut self, slot: usize, quest_id: i32) {
+        match slot {
+            0 => self.gameplay_data.set_player_quest_log_1_1(quest_id),
+            1 => self.gameplay_data.set_player_quest_log_2_1(quest_id),
+            2 => self.gameplay_data.set_player_quest_log_3_1(quest_id),
+            3 => self.gameplay_data.set_player_quest_log_4_1(quest_id),
+            4 => self.gameplay_data.set_player_quest_log_5_1(quest_id),
+            5 => self.gameplay_data.set_player_quest_log_6_1(quest_id),
+            6 => self.gameplay_data.set_player_quest_log_7_1(quest_id),
+            7 => self.gameplay_data.set_player_quest_log_8_1(quest_id),
+            8 => self.gameplay_data.set_player_quest_log_9_1(quest_id),
+            9 => self.gameplay_data.set_player_quest_log_10_1(quest_id),
+            10 => self.gameplay_data.set_player_quest_log_11_1(quest_id),
+            11 => self.gameplay_data.set_player_quest_log_12_1(quest_id),
+            12 => self.gameplay_data.set_player_quest_log_13_1(quest_id),
+            13 => self.gameplay_data.set_player_quest_log_14_1(quest_id),
+            14 => self.gameplay_data.set_player_quest_log_15_1(quest_id),
+            15 => self.gameplay_data.set_player_quest_log_16_1(quest_id),
+            16 => self.gameplay_data.set_player_quest_log_17_1(quest_id),
+            17 => self.gameplay_data.set_player_quest_log_18_1(quest_id),
+            18 => self.gameplay_data.set_player_quest_log_19_1(quest_id),
+            19 => self.gameplay_data.set_player_quest_log_20_1(quest_id),
+            20 => self.gameplay_data.set_player_quest_log_21_1(quest_id),
+            21 => self.gameplay_data.set_player_quest_log_22_1(quest_id),
+            22 => self.gameplay_data.set_player_quest_log_23_1(quest_id),
+            23 => self.gameplay_data.set_player_quest_log_24_1(quest_id),
+            24 => self.gameplay_data.set_player_quest_log_25_1(quest_id),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Character;
+
+    fn quest(id: u32, min_level: u8) -> DBQuestTemplate {
+        DBQuestTemplate {
+            id,
+            min_level,
+            quest_level: min_level,
+            flags: 0,
+            suggested_players: 0,
+            title: String::new(),
+            details: String::new(),
+            objectives: String::new(),
+            offer_reward_text: None,
+            reward_xp: 0,
+            reward_money: 0,
+            next_quest_id: None,
+        }
+    }
+
+    #[test]
+    fn quests_are_offered_once() {
+        let (sender, _receiver) = flume::unbounded();
+        let mut character = Character::new(sender, Guid::new(1));
+        character.gameplay_data.set_unit_level(10);
+        assert_eq!(character.check_quest_offer(&quest(123, 5)), Ok(()));
+        assert_eq!(character.check_quest_offer(&quest(124, 20)), Err(QuestOfferError::LevelTooLow));
+
+        character.put_in_quest_log(123);
+        character.quest_state.statuses.insert(123, QuestStatus::InQuestLog);
+        assert_eq!(character.check_quest_offer(&quest(123, 5)), Err(QuestOfferError::AlreadyOnQuest));
+
+        character.quest_state.statuses.insert(123, QuestStatus::Rewarded);
+        assert_eq!(character.check_quest_offer(&quest(123, 5)), Err(QuestOfferError::AlreadyDone));
+
+        for quest_id in 200..200 + MAX_QUEST_LOG_SIZE as u32 {
+            character.put_in_quest_log(quest_id);
+        }
+        assert_eq!(character.check_quest_offer(&quest(125, 1)), Err(QuestOfferError::QuestLogFull));
+    }
+}
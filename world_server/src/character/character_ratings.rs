@@ -0,0 +1,229 @@
+use crate::data::DataStorage;
+
+const NUM_COMBAT_RATINGS: usize = 25;
+
+//Every point of expertise takes a quarter percent off the target's dodge and parry chance
+const DODGE_PARRY_REDUCTION_PER_EXPERTISE: f32 = 0.25;
+//Resilience reduces crit damage taken by twice the crit chance reduction it gives
+const RESILIENCE_CRIT_DAMAGE_FACTOR: f32 = 2.0;
+//Base chances for two combatants of the same level, before any ratings
+const BASE_MELEE_MISS_CHANCE: f32 = 5.0;
+const BASE_AVOIDANCE_CHANCE: f32 = 5.0;
+const BASE_CRIT_CHANCE: f32 = 5.0;
+const BASE_SPELL_MISS_CHANCE: f32 = 4.0;
+//No amount of hit rating makes a spell land every time
+const MIN_SPELL_MISS_CHANCE: f32 = 1.0;
+
+//Indexes into gtCombatRatings and PLAYER_FIELD_COMBAT_RATING_1
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum CombatRating {
+    WeaponSkill = 0,
+    DefenseSkill = 1,
+    Dodge = 2,
+    Parry = 3,
+    Block = 4,
+    HitMelee = 5,
+    HitRanged = 6,
+    HitSpell = 7,
+    CritMelee = 8,
+    CritRanged = 9,
+    CritSpell = 10,
+    HitTakenMelee = 11,
+    HitTakenRanged = 12,
+    HitTakenSpell = 13,
+    //The crit taken ratings are resilience
+    CritTakenMelee = 14,
+    CritTakenRanged = 15,
+    CritTakenSpell = 16,
+    HasteMelee = 17,
+    HasteRanged = 18,
+    HasteSpell = 19,
+    WeaponSkillMainHand = 20,
+    WeaponSkillOffHand = 21,
+    WeaponSkillRanged = 22,
+    Expertise = 23,
+    ArmorPenetration = 24,
+}
+
+//Chances in percent for a melee swing, rolled in this order by the combat engine
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MeleeAttackTable {
+    pub miss: f32,
+    pub dodge: f32,
+    pub parry: f32,
+    pub block: f32,
+    pub crit: f32,
+}
+
+#[derive(Default, Debug)]
+pub(super) struct CombatRatingState {
+    ratings: [i32; NUM_COMBAT_RATINGS],
+}
+
+impl super::Character {
+    //Sums up the rating stats of everything equipped, called whenever the equipment changes
+    pub(super) fn update_combat_ratings(&mut self) {
+        let mut ratings = [0; NUM_COMBAT_RATINGS];
+        for item in self.equipped_items.get_all_equipment().into_iter().flatten() {
+            let Some(template) = item
+                .update_state
+                .object_entry()
+                .and_then(|entry| wow_items::wrath::lookup_item(entry as u32))
+            else {
+                continue;
+            };
+            for stat in template.stats() {
+                for &rating in ratings_for_item_stat(stat.stat_type.as_int() as u32) {
+                    ratings[rating as usize] += stat.value;
+                }
+            }
+        }
+
+        for (index, &value) in ratings.iter().enumerate() {
+            self.set_combat_rating_field(index, value);
+        }
+        self.combat_rating_state.ratings = ratings;
+    }
+
+    pub fn get_combat_rating(&self, rating: CombatRating) -> i32 {
+        self.combat_rating_state.ratings[rating as usize]
+    }
+
+    //The client does the same conversion for its tooltips, so the gtCombatRatings values have to match the client's DBC
+    pub fn get_combat_rating_percent(&self, rating: CombatRating, data_storage: &DataStorage) -> f32 {
+        let level = self.gameplay_data.unit_level().unwrap_or(1) as u32;
+        match data_storage.get_combat_rating_per_percent(rating as u32, level) {
+            Some(per_percent) if per_percent > 0.0 => self.get_combat_rating(rating) as f32 / per_percent,
+            _ => 0.0,
+        }
+    }
+
+    //Dodge, parry and block chances the combat engine adds to the defender's base avoidance
+    pub fn get_avoidance_chance_bonus(&self, rating: CombatRating, data_storage: &DataStorage) -> f32 {
+        match rating {
+            CombatRating::Dodge | CombatRating::Parry | CombatRating::Block => self.get_combat_rating_percent(rating, data_storage),
+            _ => 0.0,
+        }
+    }
+
+    //How much the attacker's expertise lowers the defender's dodge and parry chance, in percent
+    pub fn get_expertise_avoidance_reduction(&self, data_storage: &DataStorage) -> f32 {
+        let expertise = self.get_combat_rating_percent(CombatRating::Expertise, data_storage).floor();
+        expertise * DODGE_PARRY_REDUCTION_PER_EXPERTISE
+    }
+
+    //Resilience lowers the chance to be crit and the damage of the crits that still land
+    pub fn get_resilience_crit_chance_reduction(&self, rating: CombatRating, data_storage: &DataStorage) -> f32 {
+        match rating {
+            CombatRating::CritTakenMelee | CombatRating::CritTakenRanged | CombatRating::CritTakenSpell => {
+                self.get_combat_rating_percent(rating, data_storage)
+            }
+            _ => 0.0,
+        }
+    }
+
+    pub fn get_resilience_crit_damage_reduction(&self, rating: CombatRating, data_storage: &DataStorage) -> f32 {
+        self.get_resilience_crit_chance_reduction(rating, data_storage) * RESILIENCE_CRIT_DAMAGE_FACTOR
+    }
+
+    //Builds the attack table for a melee swing from this character against the defender
+    pub fn get_melee_attack_table(&self, defender: &super::Character, data_storage: &DataStorage) -> MeleeAttackTable {
+        let avoidance_reduction = self.get_expertise_avoidance_reduction(data_storage);
+        let avoidance = |rating| (BASE_AVOIDANCE_CHANCE + defender.get_avoidance_chance_bonus(rating, data_storage) - avoidance_reduction).max(0.0);
+
+        MeleeAttackTable {
+            miss: (BASE_MELEE_MISS_CHANCE - self.get_combat_rating_percent(CombatRating::HitMelee, data_storage)).max(0.0),
+            dodge: avoidance(CombatRating::Dodge),
+            parry: avoidance(CombatRating::Parry),
+            block: (BASE_AVOIDANCE_CHANCE + defender.get_avoidance_chance_bonus(CombatRating::Block, data_storage)).max(0.0),
+            crit: (BASE_CRIT_CHANCE + self.get_combat_rating_percent(CombatRating::CritMelee, data_storage)
+                - defender.get_resilience_crit_chance_reduction(CombatRating::CritTakenMelee, data_storage))
+            .max(0.0),
+        }
+    }
+
+    //Creatures have no ratings, they defend with the base chances
+    pub fn get_melee_attack_table_against_creature(&self, data_storage: &DataStorage) -> MeleeAttackTable {
+        let avoidance = (BASE_AVOIDANCE_CHANCE - self.get_expertise_avoidance_reduction(data_storage)).max(0.0);
+
+        MeleeAttackTable {
+            miss: (BASE_MELEE_MISS_CHANCE - self.get_combat_rating_percent(CombatRating::HitMelee, data_storage)).max(0.0),
+            dodge: avoidance,
+            parry: avoidance,
+            block: BASE_AVOIDANCE_CHANCE,
+            crit: BASE_CRIT_CHANCE + self.get_combat_rating_percent(CombatRating::CritMelee, data_storage),
+        }
+    }
+
+    //Spells can't be dodged, parried or blocked, they either land or miss
+    pub fn get_spell_miss_chance(&self, data_storage: &DataStorage) -> f32 {
+        (BASE_SPELL_MISS_CHANCE - self.get_combat_rating_percent(CombatRating::HitSpell, data_storage)).max(MIN_SPELL_MISS_CHANCE)
+    }
+
+    fn set_combat_rating_field(&mut self, index: usize, value: i32) {
+        let data = &mut self.gameplay_data;
+        match index {
+            0 => data.set_player_field_combat_rating_1(value),
+            1 => data.set_player_field_combat_rating_2(value),
+            2 => data.set_player_field_combat_rating_3(value),
+            3 => data.set_player_field_combat_rating_4(value),
+            4 => data.set_player_field_combat_rating_5(value),
+            5 => data.set_player_field_combat_rating_6(value),
+            6 => data.set_player_field_combat_rating_7(value),
+            7 => data.set_player_field_combat_rating_8(value),
+            8 => data.set_player_field_combat_rating_9(value),
+            9 => data.set_player_field_combat_rating_10(value),
+            10 => data.set_player_field_combat_rating_11(value),
+            11 => data.set_player_field_combat_rating_12(value),
+            12 => data.set_player_field_combat_rating_13(value),
+            13 => data.set_player_field_combat_rating_14(value),
+            14 => data.set_player_field_combat_rating_15(value),
+            15 => data.set_player_field_combat_rating_16(value),
+            16 => data.set_player_field_combat_rating_17(value),
+            17 => data.set_player_field_combat_rating_18(value),
+            18 => data.set_player_field_combat_rating_19(value),
+            19 => data.set_player_field_combat_rating_20(value),
+            20 => data.set_player_field_combat_rating_21(value),
+            21 => data.set_player_field_combat_rating_22(value),
+            22 => data.set_player_field_combat_rating_23(value),
+            23 => data.set_player_field_combat_rating_24(value),
+            24 => data.set_player_field_combat_rating_25(value),
+            _ => {}
+        }
+    }
+}
+
+//Item stat types (ItemModType) that feed into ratings, the generic ones count towards melee, ranged and spell at once
+fn ratings_for_item_stat(stat_type: u32) -> &'static [CombatRating] {
+    use CombatRating::*;
+    match stat_type {
+        12 => &[DefenseSkill],
+        13 => &[Dodge],
+        14 => &[Parry],
+        15 => &[Block],
+        16 => &[HitMelee],
+        17 => &[HitRanged],
+        18 => &[HitSpell],
+        19 => &[CritMelee],
+        20 => &[CritRanged],
+        21 => &[CritSpell],
+        22 => &[HitTakenMelee],
+        23 => &[HitTakenRanged],
+        24 => &[HitTakenSpell],
+        25 => &[CritTakenMelee],
+        26 => &[CritTakenRanged],
+        27 => &[CritTakenSpell],
+        28 => &[HasteMelee],
+        29 => &[HasteRanged],
+        30 => &[HasteSpell],
+        31 => &[HitMelee, HitRanged, HitSpell],
+        32 => &[CritMelee, CritRanged, CritSpell],
+        33 => &[HitTakenMelee, HitTakenRanged, HitTakenSpell],
+        34 | 35 => &[CritTakenMelee, CritTakenRanged, CritTakenSpell],
+        36 => &[HasteMelee, HasteRanged, HasteSpell],
+        37 => &[Expertise],
+        44 => &[ArmorPenetration],
+        _ => &[],
+    }
+}
@@ -0,0 +1,72 @@
+//! Reputation with factions from Faction.dbc. Only what the character earned is kept, the base reputation
+//! of the faction for the character's race isn't added in yet.
+
+use std::collections::HashMap;
+
+use wrath_realm_db::RealmDatabase;
+
+use crate::data::DataStorage;
+use crate::prelude::*;
+
+//Exalted is as far as it goes, hated is as low as it goes
+const MIN_REPUTATION: i32 = -42000;
+const MAX_REPUTATION: i32 = 42999;
+
+#[derive(Clone, Copy, Debug)]
+struct Reputation {
+    //Where the faction is in the client's reputation list
+    reputation_index: u32,
+    standing: i32,
+}
+
+#[derive(Default)]
+pub(super) struct ReputationState {
+    //By faction id
+    reputations: HashMap<u32, Reputation>,
+}
+
+impl super::Character {
+    pub(super) async fn load_reputations(&mut self, realm_db: &RealmDatabase, data_storage: &DataStorage) -> Result<()> {
+        let character_id = self.get_guid().guid() as u32;
+        for db_reputation in realm_db.get_character_reputations(character_id).await? {
+            let Some(reputation_index) = data_storage.get_reputation_index(db_reputation.faction) else {
+                warn!(
+                    "{} has reputation with faction {}, which has no reputation",
+                    self.name, db_reputation.faction
+                );
+                continue;
+            };
+            self.reputation_state.reputations.insert(
+                db_reputation.faction,
+                Reputation {
+                    reputation_index,
+                    standing: db_reputation.standing,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    //The standings by reputation list index, for the faction list the client gets on login
+    pub fn get_reputation_standings(&self) -> HashMap<u32, i32> {
+        self.reputation_state
+            .reputations
+            .values()
+            .map(|reputation| (reputation.reputation_index, reputation.standing))
+            .collect()
+    }
+
+    //Returns the new standing
+    pub async fn add_reputation(&mut self, realm_db: &RealmDatabase, faction: u32, reputation_index: u32, amount: i32) -> Result<i32> {
+        let reputation = self.reputation_state.reputations.entry(faction).or_insert(Reputation {
+            reputation_index,
+            standing: 0,
+        });
+        reputation.standing = reputation.standing.saturating_add(amount).clamp(MIN_REPUTATION, MAX_REPUTATION);
+        let standing = reputation.standing;
+
+        let character_id = self.get_guid().guid() as u32;
+        realm_db.set_character_reputation(character_id, faction, standing).await?;
+        Ok(standing)
+    }
+}
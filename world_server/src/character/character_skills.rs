@@ -0,0 +1,30 @@
+use wow_world_messages::wrath::{SkillInfo, SkillInfoIndex};
+
+use crate::prelude::*;
+
+const MAX_SKILL_SLOTS: u32 = 128;
+
+impl super::Character {
+    pub fn get_skill_value(&self, skill_id: u16) -> Option<u16> {
+        self.find_skill(skill_id).map(|(_, info)| info.minimum)
+    }
+
+    //Returns the new value, the skill never goes past its current maximum
+    pub fn increase_skill(&mut self, skill_id: u16, amount: u16) -> Result<u16> {
+        let (index, mut info) = self
+            .find_skill(skill_id)
+            .ok_or_else(|| anyhow!("{} does not know skill {}", self.name, skill_id))?;
+
+        info.minimum = info.minimum.saturating_add(amount).min(info.maximum);
+        let value = info.minimum;
+        self.gameplay_data.set_player_skill_info(info, index);
+        Ok(value)
+    }
+
+    fn find_skill(&self, skill_id: u16) -> Option<(SkillInfoIndex, SkillInfo)> {
+        (0..MAX_SKILL_SLOTS)
+            .filter_map(|i| SkillInfoIndex::try_from(i).ok())
+            .filter_map(|index| self.gameplay_data.player_skill_info(index).map(|info| (index, info)))
+            .find(|(_, info)| info.skill.as_int() == skill_id)
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use wrath_realm_db::RealmDatabase;
+
+use crate::prelude::*;
+
+pub const RELATION_FRIEND: u8 = 0x01;
+pub const RELATION_IGNORED: u8 = 0x02;
+
+//Limits of the 3.3.5 client's friends and ignore tabs
+const MAX_FRIENDS: usize = 50;
+const MAX_IGNORED: usize = 50;
+
+pub struct SocialRelation {
+    pub flags: u8,
+    pub note: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SocialError {
+    ListFull,
+    AlreadyOnList,
+    NotOnList,
+}
+
+#[derive(Default)]
+pub(super) struct SocialList {
+    relations: HashMap<Guid, SocialRelation>,
+}
+
+impl SocialList {
+    fn count(&self, flag: u8) -> usize {
+        self.relations.values().filter(|relation| relation.flags & flag != 0).count()
+    }
+
+    fn has(&self, guid: Guid, flag: u8) -> bool {
+        self.relations.get(&guid).is_some_and(|relation| relation.flags & flag != 0)
+    }
+
+    fn add(&mut self, guid: Guid, flag: u8, note: &str) -> std::result::Result<&SocialRelation, SocialError> {
+        if self.has(guid, flag) {
+            return Err(SocialError::AlreadyOnList);
+        }
+        let limit = if flag == RELATION_FRIEND { MAX_FRIENDS } else { MAX_IGNORED };
+        if self.count(flag) >= limit {
+            return Err(SocialError::ListFull);
+        }
+
+        let relation = self.relations.entry(guid).or_insert_with(|| SocialRelation {
+            flags: 0,
+            note: String::new(),
+        });
+        relation.flags |= flag;
+        if flag == RELATION_FRIEND {
+            relation.note = note.to_string();
+        }
+        Ok(relation)
+    }
+
+    //Returns the flags that are left, 0 means the other character is off both lists
+    fn remove(&mut self, guid: Guid, flag: u8) -> std::result::Result<u8, SocialError> {
+        if !self.has(guid, flag) {
+            return Err(SocialError::NotOnList);
+        }
+        let relation = self.relations.get_mut(&guid).ok_or(SocialError::NotOnList)?;
+        relation.flags &= !flag;
+        if flag == RELATION_FRIEND {
+            relation.note.clear();
+        }
+        let flags = relation.flags;
+        if flags == 0 {
+            self.relations.remove(&guid);
+        }
+        Ok(flags)
+    }
+}
+
+impl super::Character {
+    pub(super) async fn load_social(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        let character_id = self.get_guid().guid() as u32;
+        for row in realm_db.get_character_social(character_id).await? {
+            let relation = SocialRelation {
+                flags: row.flags,
+                note: row.note,
+            };
+            self.social.relations.insert(Guid::new(row.friend_id as u64), relation);
+        }
+        Ok(())
+    }
+
+    pub fn get_social_relations(&self) -> impl Iterator<Item = (Guid, &SocialRelation)> {
+        self.social.relations.iter().map(|(&guid, relation)| (guid, relation))
+    }
+
+    pub fn is_friend(&self, guid: Guid) -> bool {
+        self.social.has(guid, RELATION_FRIEND)
+    }
+
+    pub fn is_ignoring(&self, guid: Guid) -> bool {
+        self.social.has(guid, RELATION_IGNORED)
+    }
+
+    //Adds the other character to the friends (RELATION_FRIEND) or ignore list (RELATION_IGNORED) and saves it
+    pub async fn add_social_relation(
+        &mut self,
+        realm_db: &RealmDatabase,
+        guid: Guid,
+        flag: u8,
+        note: &str,
+    ) -> Result<std::result::Result<(), SocialError>> {
+        let character_id = self.get_guid().guid() as u32;
+        let relation = match self.social.add(guid, flag, note) {
+            Ok(relation) => relation,
+            Err(e) => return Ok(Err(e)),
+        };
+        realm_db
+            .set_character_social(character_id, guid.guid() as u32, relation.flags, &relation.note)
+            .await?;
+        Ok(Ok(()))
+    }
+
+    pub async fn remove_social_relation(&mut self, realm_db: &RealmDatabase, guid: Guid, flag: u8) -> Result<std::result::Result<(), SocialError>> {
+        let flags_left = match self.social.remove(guid, flag) {
+            Ok(flags) => flags,
+            Err(e) => return Ok(Err(e)),
+        };
+        let (character_id, other_id) = (self.get_guid().guid() as u32, guid.guid() as u32);
+        if flags_left == 0 {
+            realm_db.delete_character_social(character_id, other_id).await?;
+        } else {
+            realm_db.set_character_social(character_id, other_id, flags_left, "").await?;
+        }
+        Ok(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn friends_and_ignores_share_a_row() {
+        let mut social = SocialList::default();
+        let other = Guid::new(2);
+
+        social.add(other, RELATION_FRIEND, "raid leader").unwrap();
+        assert_eq!(social.add(other, RELATION_FRIEND, "").err(), Some(SocialError::AlreadyOnList));
+        assert_eq!(social.add(other, RELATION_IGNORED, "").unwrap().flags, RELATION_FRIEND | RELATION_IGNORED);
+
+        assert_eq!(social.remove(other, RELATION_FRIEND), Ok(RELATION_IGNORED));
+        assert_eq!(social.remove(other, RELATION_FRIEND), Err(SocialError::NotOnList));
+        assert_eq!(social.remove(other, RELATION_IGNORED), Ok(0));
+        assert!(social.relations.is_empty());
+
+        for id in 0..MAX_FRIENDS as u64 {
+            social.add(Guid::new(100 + id), RELATION_FRIEND, "").unwrap();
+        }
+        assert_eq!(social.add(other, RELATION_FRIEND, "").err(), Some(SocialError::ListFull));
+    }
+}
@@ -0,0 +1,66 @@
+//! The spells a character knows and the cooldowns running on them.
+
+#[derive(Default)]
+pub(super) struct SpellBook {
+    known_spells: Vec<u32>,
+    //Seconds left per spell, spells drop out once they are ready again
+    cooldowns: Vec<(u32, f32)>,
+    global_cooldown: f32,
+}
+
+impl super::Character {
+    pub fn learn_spell(&mut self, spell_id: u32) {
+        if !self.spell_book.known_spells.contains(&spell_id) {
+            self.spell_book.known_spells.push(spell_id);
+        }
+    }
+
+    pub fn knows_spell(&self, spell_id: u32) -> bool {
+        self.spell_book.known_spells.contains(&spell_id)
+    }
+
+    pub fn is_spell_on_cooldown(&self, spell_id: u32) -> bool {
+        self.spell_book.global_cooldown > 0.0 || self.spell_book.cooldowns.iter().any(|&(id, _)| id == spell_id)
+    }
+
+    pub fn start_spell_cooldown(&mut self, spell_id: u32, cooldown: f32, global_cooldown: f32) {
+        let book = &mut self.spell_book;
+        book.global_cooldown = book.global_cooldown.max(global_cooldown);
+        if cooldown > 0.0 {
+            book.cooldowns.retain(|&(id, _)| id != spell_id);
+            book.cooldowns.push((spell_id, cooldown));
+        }
+    }
+
+    pub(super) fn tick_spell_cooldowns(&mut self, delta_time: f32) {
+        let book = &mut self.spell_book;
+        book.global_cooldown = (book.global_cooldown - delta_time).max(0.0);
+        for (_, remaining) in book.cooldowns.iter_mut() {
+            *remaining -= delta_time;
+        }
+        book.cooldowns.retain(|&(_, remaining)| remaining > 0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Character;
+    use crate::prelude::*;
+
+    #[test]
+    fn cooldowns_run_out() {
+        let (sender, _receiver) = flume::unbounded();
+        let mut character = Character::new(sender, Guid::new(1));
+        character.learn_spell(133);
+        assert!(character.knows_spell(133));
+        assert!(!character.knows_spell(116));
+
+        character.start_spell_cooldown(133, 8.0, 1.5);
+        assert!(character.is_spell_on_cooldown(116));
+        character.tick_spell_cooldowns(1.5);
+        assert!(!character.is_spell_on_cooldown(116));
+        assert!(character.is_spell_on_cooldown(133));
+        character.tick_spell_cooldowns(6.5);
+        assert!(!character.is_spell_on_cooldown(133));
+    }
+}
@@ -0,0 +1,121 @@
+use std::f32::consts::FRAC_PI_2;
+
+use wow_world_messages::wrath::Race;
+
+//Base range at which a stealthed character of the same level is spotted
+const BASE_STEALTH_DETECTION_RANGE: f32 = 7.5;
+const MAX_STEALTH_DETECTION_RANGE: f32 = 30.0;
+//Characters behind the detector are only noticed when they're practically touching
+const BEHIND_DETECTION_RANGE: f32 = 1.5;
+//Every level counts as this many points of stealth or detection
+const STEALTH_POINTS_PER_LEVEL: i32 = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StealthKind {
+    //Rogue and druid stealth, broken by taking damage
+    Stealth,
+    //Mage invisibility, damage doesn't break it but any action does
+    Invisibility,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StealthBreakReason {
+    DamageTaken,
+    DamageDealt,
+    SpellCast,
+}
+
+#[derive(Default, Debug)]
+pub(super) struct StealthState {
+    active: Option<(StealthKind, i32)>,
+    //Bonus detection from auras and talents, on top of the character level
+    stealth_detection: i32,
+    invisibility_detection: i32,
+}
+
+impl super::Character {
+    //Called by the aura that grants the effect, bonus is the effect's stealth or invisibility amount
+    pub fn apply_stealth(&mut self, kind: StealthKind, bonus: i32) {
+        self.stealth_state.active = Some((kind, bonus));
+    }
+
+    pub fn remove_stealth(&mut self) {
+        self.stealth_state.active = None;
+    }
+
+    pub fn is_stealthed(&self) -> bool {
+        self.stealth_state.active.is_some()
+    }
+
+    pub fn set_stealth_detection(&mut self, stealth_detection: i32, invisibility_detection: i32) {
+        self.stealth_state.stealth_detection = stealth_detection;
+        self.stealth_state.invisibility_detection = invisibility_detection;
+    }
+
+    //Returns whether the character came out of stealth, the aura that granted it has to go as well
+    pub fn handle_stealth_break(&mut self, reason: StealthBreakReason) -> bool {
+        let breaks = match self.stealth_state.active {
+            Some((StealthKind::Stealth, _)) => true,
+            Some((StealthKind::Invisibility, _)) => reason != StealthBreakReason::DamageTaken,
+            None => false,
+        };
+        if breaks {
+            self.remove_stealth();
+        }
+        breaks
+    }
+
+    pub(super) fn can_detect_stealth_of(&self, other: &super::Character) -> bool {
+        let Some((kind, bonus)) = other.stealth_state.active else {
+            return true;
+        };
+        if self.is_gm_mode_enabled() || self.is_same_team(other) {
+            return true;
+        }
+
+        match kind {
+            StealthKind::Invisibility => self.stealth_state.invisibility_detection >= bonus,
+            StealthKind::Stealth => {
+                let detection = self.get_level() * STEALTH_POINTS_PER_LEVEL + self.stealth_state.stealth_detection;
+                let stealth = other.get_level() * STEALTH_POINTS_PER_LEVEL + bonus;
+                let distance = self.distance_2d_to(other);
+
+                if !self.is_facing(other) {
+                    return distance <= BEHIND_DETECTION_RANGE;
+                }
+                let range = (BASE_STEALTH_DETECTION_RANGE + (detection - stealth) as f32 / 5.0).clamp(0.0, MAX_STEALTH_DETECTION_RANGE);
+                distance <= range
+            }
+        }
+    }
+
+    fn get_level(&self) -> i32 {
+        self.gameplay_data.unit_level().unwrap_or(1)
+    }
+
+    fn distance_2d_to(&self, other: &super::Character) -> f32 {
+        let a = self.movement_info.position;
+        let b = other.movement_info.position;
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+    }
+
+    //Whether other is within the 180 degree cone in front of this character
+    fn is_facing(&self, other: &super::Character) -> bool {
+        let a = self.movement_info.position;
+        let b = other.movement_info.position;
+        let angle_to_other = (b.y - a.y).atan2(b.x - a.x);
+        let mut difference = (angle_to_other - self.movement_info.orientation).abs() % std::f32::consts::TAU;
+        if difference > std::f32::consts::PI {
+            difference = std::f32::consts::TAU - difference;
+        }
+        difference <= FRAC_PI_2
+    }
+
+    fn is_same_team(&self, other: &super::Character) -> bool {
+        is_alliance(self.get_race()) == is_alliance(other.get_race())
+    }
+}
+
+fn is_alliance(race: Race) -> bool {
+    matches!(race, Race::Human | Race::Dwarf | Race::NightElf | Race::Gnome | Race::Draenei)
+}
@@ -0,0 +1,138 @@
+//! Summons that are offered to the character, like the group following its leader through an instance portal.
+//! The client shows a dialog and the character is only moved once it accepts.
+
+use crate::data::WorldZoneLocation;
+use crate::prelude::*;
+
+//How long the client shows the summon dialog before declining on its own
+pub const SUMMON_TIMEOUT: f32 = 120.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SummonRefusal {
+    Dead,
+    InCombat,
+    Teleporting,
+    LevelTooLow { required_level: u8 },
+    //Already inside another copy of the destination, it can't be pulled out of its own instance
+    InOtherInstance,
+}
+
+//What decides whether a group member can be brought along
+pub struct SummonConditions {
+    pub is_alive: bool,
+    pub in_combat: bool,
+    pub teleporting: bool,
+    pub level: u8,
+    pub required_level: u8,
+    pub on_destination_map: bool,
+    pub same_instance: bool,
+}
+
+pub fn check_summon(conditions: &SummonConditions) -> std::result::Result<(), SummonRefusal> {
+    if !conditions.is_alive {
+        return Err(SummonRefusal::Dead);
+    }
+    if conditions.in_combat {
+        return Err(SummonRefusal::InCombat);
+    }
+    if conditions.teleporting {
+        return Err(SummonRefusal::Teleporting);
+    }
+    if conditions.level < conditions.required_level {
+        return Err(SummonRefusal::LevelTooLow {
+            required_level: conditions.required_level,
+        });
+    }
+    if conditions.on_destination_map && !conditions.same_instance {
+        return Err(SummonRefusal::InOtherInstance);
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+struct PendingSummon {
+    summoner: Guid,
+    destination: WorldZoneLocation,
+    remaining: f32,
+}
+
+#[derive(Default)]
+pub(super) struct SummonState {
+    pending: Option<PendingSummon>,
+}
+
+impl super::Character {
+    //A newer summon replaces the one the character didn't answer yet, like the client's dialog does
+    pub fn offer_summon(&mut self, summoner: Guid, destination: WorldZoneLocation) {
+        self.summon_state.pending = Some(PendingSummon {
+            summoner,
+            destination,
+            remaining: SUMMON_TIMEOUT,
+        });
+    }
+
+    //The destination if the character was summoned by this summoner and the offer didn't time out yet
+    pub fn take_summon(&mut self, summoner: Guid) -> Option<WorldZoneLocation> {
+        match self.summon_state.pending.take() {
+            Some(summon) if summon.summoner == summoner => Some(summon.destination),
+            other => {
+                self.summon_state.pending = other;
+                None
+            }
+        }
+    }
+
+    pub fn decline_summon(&mut self) {
+        self.summon_state.pending = None;
+    }
+
+    pub(super) fn tick_summon(&mut self, delta_time: f32) {
+        let Some(summon) = self.summon_state.pending.as_mut() else {
+            return;
+        };
+        summon.remaining -= delta_time;
+        if summon.remaining <= 0.0 {
+            self.summon_state.pending = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Character;
+    use super::*;
+    use wow_world_messages::wrath::{Area, Map, Vector3d};
+
+    #[test]
+    fn summons_go_to_the_right_summoner_and_time_out() {
+        let (sender, _receiver) = flume::unbounded();
+        let mut character = Character::new(sender, Guid::new(1));
+        let destination = WorldZoneLocation {
+            map: Map::Kalimdor,
+            area: Area::NorthshireValley,
+            position: Vector3d { x: 1.0, y: 2.0, z: 3.0 },
+            orientation: 0.0,
+        };
+
+        character.offer_summon(Guid::new(2), destination.clone());
+        assert_eq!(character.take_summon(Guid::new(3)), None);
+        assert_eq!(character.take_summon(Guid::new(2)), Some(destination.clone()));
+        assert_eq!(character.take_summon(Guid::new(2)), None);
+
+        character.offer_summon(Guid::new(2), destination);
+        character.tick_summon(SUMMON_TIMEOUT);
+        assert_eq!(character.take_summon(Guid::new(2)), None);
+
+        let conditions = SummonConditions {
+            is_alive: true,
+            in_combat: false,
+            teleporting: false,
+            level: 15,
+            required_level: 17,
+            on_destination_map: false,
+            same_instance: false,
+        };
+        assert_eq!(check_summon(&conditions), Err(SummonRefusal::LevelTooLow { required_level: 17 }));
+        assert_eq!(check_summon(&SummonConditions { level: 17, ..conditions }), Ok(()));
+    }
+}
@@ -0,0 +1,57 @@
+use wrath_realm_db::RealmDatabase;
+
+use crate::prelude::*;
+
+//Size of the known taxi nodes mask in SMSG_SHOWTAXINODES, enough for every node in the 3.3.5 TaxiNodes.dbc
+pub const TAXI_MASK_SIZE: usize = 14;
+
+#[derive(Default)]
+pub(super) struct TaxiState {
+    known_nodes: [u32; TAXI_MASK_SIZE],
+}
+
+impl TaxiState {
+    //Returns false if the node was already known or doesn't fit in the mask
+    fn set_known(&mut self, node_id: u32) -> bool {
+        let (index, bit) = ((node_id / 32) as usize, 1 << (node_id % 32));
+        match self.known_nodes.get_mut(index) {
+            Some(field) if *field & bit == 0 => {
+                *field |= bit;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl super::Character {
+    pub(super) async fn load_taxi_nodes(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        let character_id = self.get_guid().guid() as u32;
+        for node_id in realm_db.get_character_taxi_nodes(character_id).await? {
+            self.taxi_state.set_known(node_id);
+        }
+        Ok(())
+    }
+
+    pub fn knows_taxi_node(&self, node_id: u32) -> bool {
+        let index = (node_id / 32) as usize;
+        self.taxi_state
+            .known_nodes
+            .get(index)
+            .is_some_and(|field| field & (1 << (node_id % 32)) != 0)
+    }
+
+    pub fn get_known_taxi_nodes_mask(&self) -> [u32; TAXI_MASK_SIZE] {
+        self.taxi_state.known_nodes
+    }
+
+    //Marks the nodes as discovered and saves the ones that are new, returns how many were new
+    pub async fn learn_taxi_nodes(&mut self, realm_db: &RealmDatabase, node_ids: &[u32]) -> Result<usize> {
+        let new_nodes: Vec<u32> = node_ids.iter().copied().filter(|&node_id| self.taxi_state.set_known(node_id)).collect();
+        if !new_nodes.is_empty() {
+            let character_id = self.get_guid().guid() as u32;
+            realm_db.add_character_taxi_nodes(character_id, &new_nodes).await?;
+        }
+        Ok(new_nodes.len())
+    }
+}
@@ -0,0 +1,243 @@
+use crate::character::character_inventory::INVENTORY_SLOT_BAG_0;
+use crate::item::Item;
+use crate::prelude::*;
+use crate::world::persistence_queue::RealmPersistenceQueue;
+use crate::world::prelude::inventory::BagSlot;
+use crate::world::prelude::GameObject;
+
+//The buyback window occupies these item slots, between the bank and the keyring
+pub const BUYBACK_SLOT_START: u8 = 74;
+pub const BUYBACK_SLOT_COUNT: usize = 12;
+
+struct BuybackEntry {
+    item: Item,
+    price: u32,
+    sold_at: u32,
+}
+
+//Items sold to vendors this session, they are gone for good on logout
+#[derive(Default)]
+pub(super) struct BuybackState {
+    entries: [Option<BuybackEntry>; BUYBACK_SLOT_COUNT],
+}
+
+impl BuybackState {
+    //The first free slot, or the one that was sold the longest ago when the window is full
+    fn slot_for_new_entry(&self) -> usize {
+        if let Some(free) = self.entries.iter().position(Option::is_none) {
+            return free;
+        }
+        self.entries
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, entry)| entry.as_ref().map_or(0, |entry| entry.sold_at))
+            .map_or(0, |(index, _)| index)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BuybackError {
+    SlotIsEmpty,
+    NotEnoughMoney,
+    BackpackFull,
+}
+
+impl super::Character {
+    pub fn get_money(&self) -> u32 {
+        self.gameplay_data.player_field_coinage().unwrap_or(0) as u32
+    }
+
+    pub fn set_money(&mut self, money: u32) {
+        self.gameplay_data.set_player_field_coinage(money.min(i32::MAX as u32) as i32);
+    }
+
+    pub fn get_buyback_price(&self, buyback_slot: u8) -> Option<u32> {
+        let index = buyback_slot.checked_sub(BUYBACK_SLOT_START)? as usize;
+        self.buyback_state.entries.get(index)?.as_ref().map(|entry| entry.price)
+    }
+
+    //Puts a sold item in the buyback window. When the window is full the oldest entry is pushed out and
+    //its item is destroyed, its guid is returned so the client can be told.
+    pub async fn add_to_buyback(&mut self, mut item: Item, price: u32, sold_at: u32) -> Option<Guid> {
+        let index = self.buyback_state.slot_for_new_entry();
+        let expired_guid = self.buyback_state.entries[index]
+            .take()
+            .and_then(|entry| entry.item.update_state.object_guid());
+
+        let slot = BUYBACK_SLOT_START + index as u8;
+        let guid = ((self.get_guid().guid() as u32 as u64) << 32 | slot as u64).into();
+        Self::set_item_guid(&mut item, guid);
+        Self::send_item_update(&item, &self.connection_sender).await;
+
+        self.set_buyback_fields(index, guid, price, sold_at);
+        self.buyback_state.entries[index] = Some(BuybackEntry { item, price, sold_at });
+        expired_guid
+    }
+
+    //Moves the item in the buyback slot back to the backpack and charges its sell price
+    pub async fn buy_back(
+        &mut self,
+        buyback_slot: u8,
+        persistence_queue: Option<&RealmPersistenceQueue>,
+    ) -> Result<std::result::Result<Guid, BuybackError>> {
+        let Some(index) = buyback_slot
+            .checked_sub(BUYBACK_SLOT_START)
+            .map(usize::from)
+            .filter(|&index| index < BUYBACK_SLOT_COUNT && self.buyback_state.entries[index].is_some())
+        else {
+            return Ok(Err(BuybackError::SlotIsEmpty));
+        };
+
+        let price = self.buyback_state.entries[index].as_ref().map_or(0, |entry| entry.price);
+        if self.get_money() < price {
+            return Ok(Err(BuybackError::NotEnoughMoney));
+        }
+        let Some(backpack_slot) = self.first_free_backpack_slot() else {
+            return Ok(Err(BuybackError::BackpackFull));
+        };
+
+        let entry = self.buyback_state.entries[index].take().unwrap();
+        let buyback_guid = entry.item.update_state.object_guid().unwrap_or_default();
+        self.set_buyback_fields(index, Guid::zero(), 0, 0);
+        self.set_money(self.get_money() - price);
+
+        let connection_sender = self.connection_sender.clone();
+        self.set_item(
+            Some(entry.item),
+            (backpack_slot, INVENTORY_SLOT_BAG_0),
+            persistence_queue,
+            Some(&connection_sender),
+        )
+        .await?;
+        Ok(Ok(buyback_guid))
+    }
+
+    pub fn first_free_backpack_slot(&self) -> Option<u8> {
+        ((BagSlot::Item1 as u8)..=(BagSlot::Item16 as u8)).find(|&slot| self.bag_items[BagSlot::try_from(slot).unwrap()].is_none())
+    }
+
+    fn set_buyback_fields(&mut self, index: usize, guid: Guid, price: u32, timestamp: u32) {
+        let data = &mut self.gameplay_data;
+        let (price, timestamp) = (price as i32, timestamp as i32);
+        match index {
+            0 => {
+                data.set_player_field_vendorbuyback_slot_1(guid);
+                data.set_player_field_buyback_price_1(price);
+                data.set_player_field_buyback_timestamp_1(timestamp);
+            }
+            1 => {
+                data.set_player_field_vendorbuyback_slot_2(guid);
+                data.set_player_field_buyback_price_2(price);
+                data.set_player_field_buyback_timestamp_2(timestamp);
+            }
+            2 => {
+                data.set_player_field_vendorbuyback_slot_3(guid);
+                data.set_player_field_buyback_price_3(price);
+                data.set_player_field_buyback_timestamp_3(timestamp);
+            }
+            3 => {
+                data.set_player_field_vendorbuyback_slot_4(guid);
+                data.set_player_field_buyback_price_4(price);
+                data.set_player_field_buyback_timestamp_4(timestamp);
+            }
+            4 => {
+                data.set_player_field_vendorbuyback_slot_5(guid);
+                data.set_player_field_buyback_price_5(price);
+                data.set_player_field_buyback_timestamp_5(timestamp);
+            }
+            5 => {
+                data.set_player_field_vendorbuyback_slot_6(guid);
+                data.set_player_field_buyback_price_6(price);
+                data.set_player_field_buyback_timestamp_6(timestamp);
+            }
+            6 => {
+                data.set_player_field_vendorbuyback_slot_7(guid);
+                data.set_player_field_buyback_price_7(price);
+                data.set_player_field_buyback_timestamp_7(timestamp);
+            }
+            7 => {
+                data.set_player_field_vendorbuyback_slot_8(guid);
+                data.set_player_field_buyback_price_8(price);
+                data.set_player_field_buyback_timestamp_8(timestamp);
+            }
+            8 => {
+                data.set_player_field_vendorbuyback_slot_9(guid);
+                data.set_player_field_buyback_price_9(price);
+                data.set_player_field_buyback_timestamp_9(timestamp);
+            }
+            9 => {
+                data.set_player_field_vendorbuyback_slot_10(guid);
+                data.set_player_field_buyback_price_10(price);
+                data.set_player_field_buyback_timestamp_10(timestamp);
+            }
+            10 => {
+                data.set_player_field_vendorbuyback_slot_11(guid);
+                data.set_player_field_buyback_price_11(price);
+                data.set_player_field_buyback_timestamp_11(timestamp);
+            }
+            11 => {
+                data.set_player_field_vendorbuyback_slot_12(guid);
+                data.set_player_field_buyback_price_12(price);
+                data.set_player_field_buyback_timestamp_12(timestamp);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Character;
+    use wow_world_messages::wrath::UpdateItemBuilder;
+
+    fn item(entry: i32) -> Item {
+        Item {
+            update_state: UpdateItemBuilder::new().set_object_entry(entry).set_item_stack_count(1).finalize(),
+            permanent_enchant: 0,
+        }
+    }
+
+    #[test]
+    fn full_window_pushes_out_the_oldest_sale() {
+        smol::block_on(async {
+            let (sender, _receiver) = flume::unbounded();
+            let mut character = Character::new(sender, Guid::new(1));
+
+            for i in 0..BUYBACK_SLOT_COUNT as u32 {
+                //Sold out of order, the third sale is the oldest
+                let sold_at = if i == 2 { 10 } else { 100 + i };
+                assert_eq!(character.add_to_buyback(item(i as i32 + 1), i, sold_at).await, None);
+            }
+
+            let expired = character.add_to_buyback(item(99), 500, 1000).await;
+            assert_eq!(expired, Some(Guid::from((1u64 << 32) | (BUYBACK_SLOT_START as u64 + 2))));
+            assert_eq!(character.get_buyback_price(BUYBACK_SLOT_START + 2), Some(500));
+            assert_eq!(character.get_buyback_price(BUYBACK_SLOT_START), Some(0));
+        });
+    }
+
+    #[test]
+    fn buying_back_costs_the_sell_price() {
+        smol::block_on(async {
+            let (sender, _receiver) = flume::unbounded();
+            let mut character = Character::new(sender, Guid::new(1));
+            character.add_to_buyback(item(25), 35, 100).await;
+
+            assert_eq!(
+                character.buy_back(BUYBACK_SLOT_START, None).await.unwrap(),
+                Err(BuybackError::NotEnoughMoney)
+            );
+            assert_eq!(
+                character.buy_back(BUYBACK_SLOT_START + 1, None).await.unwrap(),
+                Err(BuybackError::SlotIsEmpty)
+            );
+
+            character.set_money(50);
+            assert!(character.buy_back(BUYBACK_SLOT_START, None).await.unwrap().is_ok());
+            assert_eq!(character.get_money(), 15);
+            assert_eq!(character.get_buyback_price(BUYBACK_SLOT_START), None);
+            assert!(character.bag_items[BagSlot::Item1].is_some());
+        });
+    }
+}
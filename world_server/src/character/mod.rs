@@ -1,10 +1,12 @@
 use self::character_inventory::{BagInventory, GameplayCharacterInventory};
 
 use super::world::prelude::*;
+use crate::chat::moderation::ChatModerationState;
 use crate::connection::events::ServerEvent;
 use crate::data::{ActionBar, DataStorage, PositionAndOrientation, TutorialFlags, WorldZoneLocation};
 use crate::handlers::login_handler::LogoutState;
 use crate::handlers::movement_handler::TeleportationState;
+use crate::localization::ClientLocale;
 use crate::prelude::*;
 use crate::world::prelude::unit_flags::UnitFlagIndex;
 use bit_field::BitField;
@@ -12,23 +14,54 @@ use smol::lock::RwLock;
 use std::collections::HashMap;
 use std::sync::{Arc, Weak};
 use wow_world_messages::wrath::{
-    ActionButton, Area, Class, Gender, Map, MovementInfo, ObjectType, Power, Race, RelationType, UnitStandState, UpdateMask, UpdatePlayer,
+    ActionButton, Area, Class, Gender, Map, MovementInfo, ObjectType, Power, Race, UnitStandState, UpdateMask, UpdatePlayer,
 };
 use wrath_realm_db::character::DBCharacterUpdate;
 use wrath_realm_db::RealmDatabase;
 
+pub mod character_auras;
+pub mod character_casting;
+mod character_chat;
 mod character_cinematic;
+pub mod character_currency;
 mod character_database;
+pub mod character_deserter;
+pub mod character_equipment_sets;
+pub mod character_experience;
+pub mod character_far_sight;
 mod character_first_login;
+pub mod character_forced_movement;
+mod character_gm;
+pub mod character_guild;
 pub mod character_inventory;
+pub mod character_keyring;
 mod character_logout;
+mod character_loot;
 pub mod character_manager;
+mod character_map_tour;
+pub mod character_melee;
 mod character_movement;
+pub mod character_movement_acks;
+mod character_pet;
+mod character_phase;
+pub mod character_power;
+pub mod character_quests;
+pub mod character_ratings;
+mod character_reputation;
 mod character_rested;
+mod character_skills;
+pub mod character_social;
+mod character_spells;
+pub mod character_stealth;
+pub mod character_summon;
+mod character_taxi;
+pub mod character_vendor;
 
 pub struct Character {
     // Both client and character have a sender to the connection
     pub connection_sender: flume::Sender<ServerEvent>,
+    //Locale of the client that controls this character, decides the language of server texts
+    client_locale: ClientLocale,
 
     /// This sender is cloned an used to send world updates to the character
     pub sender: flume::Sender<wow_world_messages::wrath::Object>,
@@ -42,6 +75,8 @@ pub struct Character {
     pub map: wow_world_messages::wrath::Map,
     pub area: wow_world_messages::wrath::Area,
     pub instance_id: u32,
+    guild_id: Option<u32>,
+    account_id: u32,
     pub bind_location: Option<WorldZoneLocation>,
     pub tutorial_flags: TutorialFlags,
     pub action_bar: ActionBar,
@@ -52,12 +87,16 @@ pub struct Character {
     pub last_playtime_calculation_timestamp: u32,
 
     //required for world updates and implenting ReceiveUpdates trait
-    pending_object_updates: Vec<wow_world_messages::wrath::Object>,
+    pending_object_updates: Vec<SharedObjectUpdate>,
 
     //things required make GameObject working
     in_range_objects: HashMap<Guid, Weak<RwLock<dyn GameObject>>>,
     in_range_characters: Vec<Guid>,
     recently_removed_guids: Vec<Guid>,
+    //The map refreshes the in-range set on its next tick instead of when it's this character's turn
+    visibility_update_requested: bool,
+    //Seconds until the next movement heartbeat is passed on to the characters around us
+    movement_relay_cooldown: f32,
 
     //time sync
     pub time_sync_counter: u32,
@@ -73,9 +112,40 @@ pub struct Character {
 
     cinematic_state: character_cinematic::CharacterCinematicState,
 
+    //GM toggles, not persisted between sessions
+    gm_state: character_gm::GmState,
+    chat_moderation_state: ChatModerationState,
+
+    //Rage, energy, combo points and runes
+    class_power_state: character_power::ClassPowerState,
+    stealth_state: character_stealth::StealthState,
+    far_sight_state: character_far_sight::FarSightState,
+    summon_state: character_summon::SummonState,
+    loot_target: Option<Guid>,
+    combat_rating_state: character_ratings::CombatRatingState,
+    pet_state: character_pet::PetState,
+    map_tour: character_map_tour::MapTour,
+    social: character_social::SocialList,
+    equipment_sets: character_equipment_sets::EquipmentSets,
+    phase_state: character_phase::PhaseState,
+    taxi_state: character_taxi::TaxiState,
+    buyback_state: character_vendor::BuybackState,
+    quest_state: character_quests::QuestState,
+    reputation_state: character_reputation::ReputationState,
+    guild_state: character_guild::GuildState,
+    forced_movement_state: character_forced_movement::ForcedMovementState,
+    movement_ack_state: character_movement_acks::MovementAckState,
+    casting_state: character_casting::CastingState,
+    aura_state: character_auras::AuraState,
+    deserter_state: character_deserter::DeserterState,
+    currency_state: character_currency::CurrencyState,
+    spell_book: character_spells::SpellBook,
+    melee_state: character_melee::MeleeState,
+
     //items
     pub equipped_items: GameplayCharacterInventory,
     pub bag_items: BagInventory,
+    pub keyring_items: character_keyring::KeyringInventory,
 }
 
 impl Character {
@@ -91,6 +161,8 @@ impl Character {
             map: Map::EasternKingdoms,
             area: Area::NorthshireAbbey,
             instance_id: 0,
+            guild_id: None,
+            account_id: 0,
             bind_location: None,
             tutorial_flags: TutorialFlags::default(),
             action_bar: ActionBar::new(),
@@ -101,6 +173,8 @@ impl Character {
             in_range_objects: HashMap::new(),
             in_range_characters: vec![],
             recently_removed_guids: vec![],
+            visibility_update_requested: false,
+            movement_relay_cooldown: 0f32,
             time_sync_counter: 0,
             time_sync_cooldown: 0f32,
             teleportation_state: TeleportationState::None,
@@ -108,8 +182,36 @@ impl Character {
             rested_state: character_rested::RestedState::NotRested,
             needs_first_login: false,
             cinematic_state: character_cinematic::CharacterCinematicState::None,
+            gm_state: character_gm::GmState::default(),
+            chat_moderation_state: ChatModerationState::default(),
+            class_power_state: character_power::ClassPowerState::default(),
+            stealth_state: character_stealth::StealthState::default(),
+            far_sight_state: character_far_sight::FarSightState::default(),
+            summon_state: character_summon::SummonState::default(),
+            loot_target: None,
+            combat_rating_state: character_ratings::CombatRatingState::default(),
+            pet_state: character_pet::PetState::default(),
+            map_tour: character_map_tour::MapTour::default(),
+            social: character_social::SocialList::default(),
+            equipment_sets: character_equipment_sets::EquipmentSets::default(),
+            phase_state: character_phase::PhaseState::default(),
+            taxi_state: character_taxi::TaxiState::default(),
+            buyback_state: character_vendor::BuybackState::default(),
+            quest_state: character_quests::QuestState::default(),
+            reputation_state: character_reputation::ReputationState::default(),
+            guild_state: character_guild::GuildState::default(),
+            forced_movement_state: character_forced_movement::ForcedMovementState::default(),
+            movement_ack_state: character_movement_acks::MovementAckState::default(),
+            casting_state: character_casting::CastingState::default(),
+            aura_state: character_auras::AuraState::default(),
+            deserter_state: character_deserter::DeserterState::default(),
+            currency_state: character_currency::CurrencyState::default(),
+            spell_book: character_spells::SpellBook::default(),
+            melee_state: character_melee::MeleeState::default(),
+            client_locale: ClientLocale::default(),
             equipped_items: GameplayCharacterInventory::new(),
             bag_items: BagInventory::default(),
+            keyring_items: character_keyring::KeyringInventory::default(),
         }
     }
 
@@ -122,23 +224,21 @@ impl Character {
         self.last_playtime_calculation_timestamp = unix_time;
     }
 
-    pub(crate) async fn persist_position_and_playtime(&mut self, world: &World) -> Result<()> {
+    //Written by the persistence queue at the end of the tick, so leaving the world doesn't wait on MySQL
+    pub(crate) fn persist_position_and_playtime(&mut self, world: &World) {
         self.update_playtime_now();
 
-        let realm_database = world.get_realm_database();
-        realm_database
-            .update_character_position(&DBCharacterUpdate {
-                id: self.get_guid().guid() as u32,
-                map: self.map.as_int() as u16,
-                zone: self.area.as_int() as u16,
-                x: self.movement_info.position.x,
-                y: self.movement_info.position.y,
-                z: self.movement_info.position.z,
-                o: self.movement_info.orientation,
-                playtime_total: self.seconds_played_total,
-                playtime_level: self.seconds_played_at_level,
-            })
-            .await
+        world.get_persistence_queue().set_character_position(DBCharacterUpdate {
+            id: self.get_guid().guid() as u32,
+            map: self.map.as_int() as u16,
+            zone: self.area.as_int() as u16,
+            x: self.movement_info.position.x,
+            y: self.movement_info.position.y,
+            z: self.movement_info.position.z,
+            o: self.movement_info.orientation,
+            playtime_total: self.seconds_played_total,
+            playtime_level: self.seconds_played_at_level,
+        });
     }
 
     pub fn get_sender(&self) -> flume::Sender<wow_world_messages::wrath::Object> {
@@ -152,10 +252,10 @@ impl Character {
     }
 
     pub async fn send_packets_before_add_to_map(&self) -> Result<()> {
-        handlers::send_contact_list(self, RelationType::empty().set_friend().set_ignored().set_muted().set_recruitafriend()).await?;
         handlers::send_bind_update(self).await?;
         handlers::send_dungeon_difficulty(self).await?;
         handlers::send_action_buttons(self).await?;
+        handlers::send_equipment_set_list(self).await?;
         handlers::send_initial_world_states(self).await?;
         handlers::send_login_set_time_speed(self).await
     }
@@ -167,6 +267,7 @@ impl Character {
         handlers::send_tutorial_flags(self).await?;
         handlers::send_faction_list(self).await?;
         handlers::send_time_sync(self).await?;
+        handlers::send_motd(&realm_database, self).await?;
         Ok(())
     }
 
@@ -180,6 +281,14 @@ impl Character {
         handlers::send_initial_world_states(self).await
     }
 
+    pub fn get_guild_id(&self) -> Option<u32> {
+        self.guild_id
+    }
+
+    pub fn get_account_id(&self) -> u32 {
+        self.account_id
+    }
+
     pub fn reset_time_sync(&mut self) {
         self.time_sync_cooldown = 0.0;
         self.time_sync_counter = 0;
@@ -189,6 +298,16 @@ impl Character {
         self.try_perform_first_time_login_if_required().await?;
         self.tick_time_sync(delta_time).await?;
         self.tick_logout_state(delta_time, world).await?;
+        self.tick_class_power(delta_time);
+        self.tick_pet(delta_time);
+        self.tick_casting(delta_time);
+        self.tick_spell_cooldowns(delta_time);
+        self.tick_auras(delta_time);
+        self.tick_melee(delta_time);
+        self.tick_movement_acks(delta_time);
+        self.tick_movement_relay(delta_time);
+        self.tick_far_sight(delta_time);
+        self.tick_summon(delta_time);
 
         self.handle_queued_teleport(world)
             .await
@@ -238,14 +357,6 @@ impl Character {
         self.gameplay_data.set_unit_flags(unit_flags);
     }
 
-    async fn set_rooted(&self, rooted: bool) -> Result<()> {
-        if rooted {
-            handlers::send_smsg_force_move_root(self).await
-        } else {
-            handlers::send_smsg_force_move_unroot(self).await
-        }
-    }
-
     fn set_stunned(&mut self, stunned: bool) {
         self.set_unit_flag_byte(UnitFlagIndex::Stunned, stunned)
     }
@@ -270,6 +381,14 @@ impl Character {
         Ok(())
     }
 
+    pub fn set_client_locale(&mut self, locale: ClientLocale) {
+        self.client_locale = locale;
+    }
+
+    pub fn get_client_locale(&self) -> ClientLocale {
+        self.client_locale
+    }
+
     pub fn get_race(&self) -> Race {
         self.gameplay_data.unit_bytes_0().map_or(Race::Human, |(race, _, _, _)| race)
     }
@@ -301,6 +420,10 @@ impl GameObject for Character {
         ObjectType::Player
     }
 
+    fn get_phase_mask(&self) -> u32 {
+        self.get_character_phase_mask()
+    }
+
     fn get_update_mask(&self) -> UpdateMask {
         UpdateMask::Player(self.gameplay_data.clone())
     }
@@ -310,8 +433,11 @@ impl GameObject for Character {
     }
 
     fn on_pushed_to_map(&mut self, _map_manager: &MapManager) -> Result<()> {
+        //Whatever the character was looking at stayed behind on the old map
+        self.end_far_sight();
+        self.request_visibility_update();
         let create_block = build_create_update_block_for_player(self, self)?;
-        self.push_object_update(create_block);
+        self.push_object_update(Arc::new(create_block));
         Ok(())
     }
 
@@ -331,7 +457,7 @@ impl GameObject for Character {
     }
 
     fn is_in_range(&self, guid: Guid) -> bool {
-        self.in_range_objects.contains_key(&guid)
+        self.in_range_objects.contains_key(&guid) || self.in_range_characters.contains(&guid)
     }
 
     fn add_in_range_object(&mut self, guid: Guid, object: Weak<RwLock<dyn GameObject>>) -> Result<()> {
@@ -343,12 +469,13 @@ impl GameObject for Character {
     fn add_in_range_character(&mut self, guid: Guid) -> Result<()> {
         if !self.in_range_characters.contains(&guid) {
             self.in_range_characters.push(guid);
+            self.queue_aura_snapshot(guid);
         }
         Ok(())
     }
 
-    fn get_in_range_guids(&self) -> Vec<Guid> {
-        self.in_range_objects.keys().copied().collect()
+    fn get_in_range_guids(&self) -> impl Iterator<Item = Guid> + '_ {
+        self.in_range_objects.keys().chain(self.in_range_characters.iter()).copied()
     }
 
     fn get_in_range_characters(&self) -> &[Guid] {
@@ -357,12 +484,14 @@ impl GameObject for Character {
 
     fn remove_in_range_object(&mut self, guid: Guid) -> Result<()> {
         self.in_range_objects.remove(&guid);
+        self.in_range_characters.retain(|&g| g != guid);
         self.recently_removed_guids.push(guid);
         Ok(())
     }
 
     fn clear_in_range_objects(&mut self) {
         self.in_range_objects.clear();
+        self.in_range_characters.clear();
     }
 
     fn get_recently_removed_range_guids(&self) -> &[Guid] {
@@ -384,11 +513,11 @@ impl GameObject for Character {
 
 #[async_trait::async_trait]
 impl ReceiveUpdates for Character {
-    fn push_object_update(&mut self, object_update: wow_world_messages::wrath::Object) {
-        self.pending_object_updates.push(object_update);
+    fn push_object_update(&mut self, object_update: SharedObjectUpdate) {
+        coalesce_object_update(&mut self.pending_object_updates, object_update);
     }
 
-    fn get_object_updates(&self) -> &Vec<wow_world_messages::wrath::Object> {
+    fn get_object_updates(&self) -> &[SharedObjectUpdate] {
         &self.pending_object_updates
     }
 
@@ -399,7 +528,9 @@ impl ReceiveUpdates for Character {
     async fn process_pending_updates(&mut self) -> Result<()> {
         let updates = self.get_object_updates();
         if !updates.is_empty() {
-            handlers::send_smsg_update_objects(self, updates.clone()).await?;
+            //Every observer needs its own copy in the packet, this is the only place a shared block is cloned
+            let objects = updates.iter().map(|update| update.as_ref().clone()).collect();
+            handlers::send_smsg_update_objects(self, objects).await?;
             self.clear_object_updates();
         }
         Ok(())
@@ -0,0 +1,178 @@
+//! Chat channels: the built in ones from ChatChannels.dbc (General, Trade, ...) and custom channels players
+//! create by joining a name nobody uses yet.
+//!
+//! Channels are looked up by their lowercased name and only exist while someone is in them. The first
+//! member of a custom channel owns it and can set a password and hand out moderator rights, built in
+//! channels have no owner and no password.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::prelude::*;
+use crate::world::membership_index::MembershipIndex;
+
+struct Channel {
+    //Name as the creator typed it, shown to everyone that joins later
+    name: String,
+    built_in: bool,
+    password: String,
+    owner: Option<Guid>,
+    moderators: HashSet<Guid>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChannelError {
+    WrongPassword,
+    AlreadyMember,
+    NotMember,
+    NotModerator,
+    NotOwner,
+    PlayerNotFound,
+}
+
+#[derive(Default)]
+pub struct ChannelManager {
+    channels: HashMap<String, Channel>,
+    members: MembershipIndex<String>,
+}
+
+fn channel_key(name: &str) -> String {
+    name.to_lowercase()
+}
+
+impl ChannelManager {
+    //Returns the channel's display name
+    pub fn join(&mut self, name: &str, password: &str, built_in: bool, guid: Guid) -> std::result::Result<String, ChannelError> {
+        let key = channel_key(name);
+        let channel = self.channels.entry(key.clone()).or_insert_with(|| Channel {
+            name: name.to_string(),
+            built_in,
+            password: String::new(),
+            owner: (!built_in).then_some(guid),
+            moderators: if built_in { HashSet::new() } else { HashSet::from([guid]) },
+        });
+        if self.members.members(&key).any(|member| member == guid) {
+            return Err(ChannelError::AlreadyMember);
+        }
+        if !channel.password.is_empty() && channel.password != password {
+            return Err(ChannelError::WrongPassword);
+        }
+
+        self.members.join(key, guid);
+        Ok(channel.name.clone())
+    }
+
+    pub fn leave(&mut self, name: &str, guid: Guid) -> std::result::Result<String, ChannelError> {
+        let key = channel_key(name);
+        self.require_member(&key, guid)?;
+        self.members.leave(&key, guid);
+        Ok(self.on_member_left(&key, guid).unwrap_or_else(|| name.to_string()))
+    }
+
+    //On logout the character silently leaves every channel it was in
+    pub fn leave_all(&mut self, guid: Guid) {
+        let keys = self.members.memberships(guid).to_vec();
+        self.members.leave_all(guid);
+        for key in keys {
+            self.on_member_left(&key, guid);
+        }
+    }
+
+    //Passes ownership on to the next member, channels nobody is in anymore are dropped
+    fn on_member_left(&mut self, key: &String, guid: Guid) -> Option<String> {
+        let next_owner = self.members.members(key).next();
+        let channel = self.channels.get_mut(key)?;
+        let name = channel.name.clone();
+        if next_owner.is_none() {
+            self.channels.remove(key);
+            return Some(name);
+        }
+
+        channel.moderators.remove(&guid);
+        if channel.owner == Some(guid) {
+            channel.owner = next_owner;
+            channel.moderators.extend(next_owner);
+        }
+        Some(name)
+    }
+
+    fn require_member(&self, key: &String, guid: Guid) -> std::result::Result<&Channel, ChannelError> {
+        match self.channels.get(key) {
+            Some(channel) if self.members.members(key).any(|member| member == guid) => Ok(channel),
+            _ => Err(ChannelError::NotMember),
+        }
+    }
+
+    //Who hears a message sent to the channel, along with the channel's display name
+    pub fn speak(&self, name: &str, guid: Guid) -> std::result::Result<(String, Vec<Guid>), ChannelError> {
+        let key = channel_key(name);
+        let channel = self.require_member(&key, guid)?;
+        Ok((channel.name.clone(), self.members.members(&key).collect()))
+    }
+
+    pub fn set_password(&mut self, name: &str, guid: Guid, password: &str) -> std::result::Result<String, ChannelError> {
+        let key = channel_key(name);
+        self.require_member(&key, guid)?;
+        let channel = self.channels.get_mut(&key).ok_or(ChannelError::NotMember)?;
+        if channel.built_in || !channel.moderators.contains(&guid) {
+            return Err(ChannelError::NotModerator);
+        }
+        channel.password = password.to_string();
+        Ok(channel.name.clone())
+    }
+
+    //Only the owner hands out and takes away moderator rights, and only to members
+    pub fn set_moderator(&mut self, name: &str, guid: Guid, target: Guid, moderator: bool) -> std::result::Result<String, ChannelError> {
+        let key = channel_key(name);
+        self.require_member(&key, guid)?;
+        if self.require_member(&key, target).is_err() {
+            return Err(ChannelError::PlayerNotFound);
+        }
+        let channel = self.channels.get_mut(&key).ok_or(ChannelError::NotMember)?;
+        if channel.owner != Some(guid) {
+            return Err(ChannelError::NotOwner);
+        }
+        if moderator {
+            channel.moderators.insert(target);
+        } else if target != guid {
+            channel.moderators.remove(&target);
+        }
+        Ok(channel.name.clone())
+    }
+
+    pub fn members(&self) -> &MembershipIndex<String> {
+        &self.members
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_channels_have_an_owner_and_a_password() {
+        let mut channels = ChannelManager::default();
+        let (owner, other) = (Guid::new(1), Guid::new(2));
+
+        assert_eq!(channels.join("Raiders", "", false, owner), Ok("Raiders".to_string()));
+        assert_eq!(channels.join("raiders", "", false, owner), Err(ChannelError::AlreadyMember));
+        assert_eq!(channels.set_password("raiders", other, "secret"), Err(ChannelError::NotMember));
+        channels.set_password("raiders", owner, "secret").unwrap();
+
+        assert_eq!(channels.join("RAIDERS", "wrong", false, other), Err(ChannelError::WrongPassword));
+        assert_eq!(channels.join("RAIDERS", "secret", false, other), Ok("Raiders".to_string()));
+        assert_eq!(channels.speak("raiders", other).unwrap().1.len(), 2);
+
+        //Ownership passes on, and the channel is gone once everyone left
+        channels.leave_all(owner);
+        assert_eq!(channels.set_moderator("raiders", other, other, true), Ok("Raiders".to_string()));
+        channels.leave("raiders", other).unwrap();
+        assert_eq!(channels.speak("raiders", other), Err(ChannelError::NotMember));
+        assert_eq!(channels.join("raiders", "", false, other), Ok("raiders".to_string()));
+
+        channels.join("General - Elwynn Forest", "", true, owner).unwrap();
+        assert_eq!(
+            channels.set_password("general - elwynn forest", owner, "x"),
+            Err(ChannelError::NotModerator)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Hyperlinks in chat messages, the `|cff1eff00|Hitem:2589:0:0:0:0:0:0:0:0:80|h[Linen Cloth]|h|r` the client
+//! builds when shift clicking an item, quest or spell. Clients trust the links they receive and a broken one
+//! can crash everyone who sees it, so messages are only passed on if every link is well formed and points at
+//! something that exists. Any other escape sequence (textures, raw colors without a link) is refused as well,
+//! the client never sends those on its own.
+
+use wow_dbc::Indexable;
+use wrath_game_db::GameDatabase;
+
+use crate::data::DataStorage;
+use crate::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LinkedObject {
+    Item(u32),
+    Quest(u32),
+    Spell(u32),
+    //Talents, achievements and glyphs only get their format checked
+    Other,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HyperlinkError {
+    Malformed,
+    UnknownLinkType,
+    UnknownObject(LinkedObject),
+}
+
+//Everything that's linked in the message, in order
+pub fn parse_hyperlinks(message: &str) -> std::result::Result<Vec<LinkedObject>, HyperlinkError> {
+    let mut linked = Vec::new();
+    let mut rest = message;
+    while let Some(pipe) = rest.find('|') {
+        let escape = &rest[pipe + 1..];
+        rest = if let Some(after) = escape.strip_prefix('|') {
+            //An escaped pipe, shown as a single |
+            after
+        } else if let Some(colored) = escape.strip_prefix('c') {
+            let color = colored.get(..8).filter(|color| color.bytes().all(|b| b.is_ascii_hexdigit()));
+            let link = color.and_then(|_| colored[8..].strip_prefix("|H")).ok_or(HyperlinkError::Malformed)?;
+            let (object, after) = parse_link(link)?;
+            linked.push(object);
+            after.strip_prefix("|r").ok_or(HyperlinkError::Malformed)?
+        } else if let Some(link) = escape.strip_prefix('H') {
+            let (object, after) = parse_link(link)?;
+            linked.push(object);
+            after
+        } else {
+            return Err(HyperlinkError::Malformed);
+        };
+    }
+    Ok(linked)
+}
+
+//"item:2589:...|h[Linen Cloth]|h", returns what's after the closing |h
+fn parse_link(link: &str) -> std::result::Result<(LinkedObject, &str), HyperlinkError> {
+    let (data, rest) = link.split_once("|h").ok_or(HyperlinkError::Malformed)?;
+    let rest = rest.strip_prefix('[').ok_or(HyperlinkError::Malformed)?;
+    let (text, rest) = rest.split_once("]|h").ok_or(HyperlinkError::Malformed)?;
+    if text.is_empty() || text.contains('|') {
+        return Err(HyperlinkError::Malformed);
+    }
+    Ok((parse_link_data(data)?, rest))
+}
+
+fn parse_link_data(data: &str) -> std::result::Result<LinkedObject, HyperlinkError> {
+    let (link_type, fields) = data.split_once(':').ok_or(HyperlinkError::Malformed)?;
+    let fields: Vec<&str> = fields.split(':').collect();
+    let number = |field: &str| field.parse::<i64>().map_err(|_| HyperlinkError::Malformed);
+    let id = |field: &str| field.parse::<u32>().ok().filter(|&id| id != 0).ok_or(HyperlinkError::Malformed);
+    let hex = |field: &str| match !field.is_empty() && field.bytes().all(|b| b.is_ascii_hexdigit()) {
+        true => Ok(()),
+        false => Err(HyperlinkError::Malformed),
+    };
+
+    //Layouts as the 3.3.5 client writes them, the first field is always the id
+    let (expected_fields, object) = match link_type {
+        //id:enchant:gem1:gem2:gem3:gem4:suffix:unique:level
+        "item" => (9..=10, LinkedObject::Item(id(fields[0])?)),
+        //id:level
+        "quest" => (2..=2, LinkedObject::Quest(id(fields[0])?)),
+        "spell" | "enchant" => (1..=1, LinkedObject::Spell(id(fields[0])?)),
+        //spell:skill:max skill:guid:known recipes
+        "trade" => {
+            hex(fields.get(3).copied().unwrap_or(""))?;
+            (5..=5, LinkedObject::Spell(id(fields[0])?))
+        }
+        //id:rank
+        "talent" => (2..=2, LinkedObject::Other),
+        //id:guid:done:month:day:year:criteria1:criteria2:criteria3:criteria4
+        "achievement" => {
+            hex(fields.get(1).copied().unwrap_or(""))?;
+            (10..=10, LinkedObject::Other)
+        }
+        //glyph slot:id
+        "glyph" => (2..=2, LinkedObject::Other),
+        _ => return Err(HyperlinkError::UnknownLinkType),
+    };
+    if !expected_fields.contains(&fields.len()) {
+        return Err(HyperlinkError::Malformed);
+    }
+
+    //The guids and the recipe list are checked above, everything else is a plain number
+    for (index, field) in fields.iter().enumerate() {
+        let is_hex = matches!((link_type, index), ("trade", 3 | 4) | ("achievement", 1));
+        if !is_hex {
+            number(field)?;
+        }
+    }
+    Ok(object)
+}
+
+//Checks the linked ids against the item list, Spell.dbc and the quest templates
+pub async fn validate_hyperlinks(message: &str, data_storage: &DataStorage, game_db: &GameDatabase) -> std::result::Result<(), HyperlinkError> {
+    //Plain messages are by far the most common, they don't need any work
+    if !message.contains('|') {
+        return Ok(());
+    }
+
+    for object in parse_hyperlinks(message)? {
+        let exists = match object {
+            LinkedObject::Item(id) => wow_items::wrath::lookup_item(id).is_some(),
+            LinkedObject::Spell(id) => data_storage.get_dbc_spell().is_ok_and(|spells| spells.get(id).is_some()),
+            LinkedObject::Quest(id) => matches!(game_db.get_quest_template(id).await, Ok(Some(_))),
+            LinkedObject::Other => true,
+        };
+        if !exists {
+            return Err(HyperlinkError::UnknownObject(object));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_well_formed_links_are_accepted() {
+        let item = "|cff1eff00|Hitem:2589:0:0:0:0:0:0:0:0:80|h[Linen Cloth]|h|r";
+        let quest = "|cffffff00|Hquest:783:1|h[A Threat Within]|h|r";
+        assert_eq!(
+            parse_hyperlinks(&format!("wts {item} and {quest} || pst")),
+            Ok(vec![LinkedObject::Item(2589), LinkedObject::Quest(783)])
+        );
+        assert_eq!(parse_hyperlinks("no links here"), Ok(vec![]));
+
+        //Missing |r, broken color, missing text, bad field count, textures and unknown link types
+        assert_eq!(parse_hyperlinks(&item[..item.len() - 2]), Err(HyperlinkError::Malformed));
+        assert_eq!(parse_hyperlinks("|cffzzzzzz|Hspell:133|h[Fireball]|h|r"), Err(HyperlinkError::Malformed));
+        assert_eq!(parse_hyperlinks("|Hspell:133|h[]|h"), Err(HyperlinkError::Malformed));
+        assert_eq!(parse_hyperlinks("|Hquest:783|h[A Threat Within]|h"), Err(HyperlinkError::Malformed));
+        assert_eq!(parse_hyperlinks("|TInterface\\Icons\\Spell_Fire:64|t"), Err(HyperlinkError::Malformed));
+        assert_eq!(parse_hyperlinks("|Hplayer:Arthas|h[Arthas]|h"), Err(HyperlinkError::UnknownLinkType));
+    }
+}
@@ -0,0 +1,95 @@
+//! Spoken languages. Every race speaks its faction's language and its own, characters can only talk in a
+//! language they know and hear gibberish in the ones they don't. Universal and addon messages are
+//! understood by everyone, GMs understand and speak every language.
+//!
+//! Scrambling replaces every word with a made up word of the same length. The same word in the same
+//! language always turns into the same gibberish, so "lol" is recognizable after a while just like on
+//! retail.
+
+use wow_world_base::wrath::{Language, Race};
+
+use crate::character::Character;
+
+const SYLLABLES: [&str; 16] = [
+    "an", "ko", "ru", "gol", "mor", "tha", "zug", "el", "dor", "ash", "ni", "ka", "lo", "ves", "ur", "thi",
+];
+
+pub fn racial_languages(race: Race) -> &'static [Language] {
+    match race {
+        Race::Human => &[Language::Common],
+        Race::Dwarf => &[Language::Common, Language::Dwarvish],
+        Race::NightElf => &[Language::Common, Language::Darnassian],
+        Race::Gnome => &[Language::Common, Language::Gnomish],
+        Race::Draenei => &[Language::Common, Language::Draenei],
+        Race::Orc => &[Language::Orcish],
+        Race::Undead => &[Language::Orcish, Language::Gutterspeak],
+        Race::Tauren => &[Language::Orcish, Language::Taurahe],
+        Race::Troll => &[Language::Orcish, Language::Troll],
+        Race::BloodElf => &[Language::Orcish, Language::Thalassian],
+        _ => &[],
+    }
+}
+
+fn is_understood_by_everyone(language: Language) -> bool {
+    matches!(language, Language::Universal | Language::Addon)
+}
+
+pub fn knows_language(character: &Character, language: Language) -> bool {
+    is_understood_by_everyone(language) || character.is_gm_mode_enabled() || racial_languages(character.get_race()).contains(&language)
+}
+
+//The message as the listener reads it
+pub fn message_for_listener(listener: &Character, language: Language, message: &str) -> String {
+    if knows_language(listener, language) {
+        message.to_string()
+    } else {
+        scramble(message, language)
+    }
+}
+
+pub fn scramble(message: &str, language: Language) -> String {
+    message.split(' ').map(|word| scramble_word(word, language)).collect::<Vec<_>>().join(" ")
+}
+
+fn scramble_word(word: &str, language: Language) -> String {
+    let length = word.chars().count();
+    if length == 0 {
+        return String::new();
+    }
+
+    //FNV-1a over the language and the lowercased word
+    let mut hash: u64 = 0xcbf29ce484222325 ^ language.as_int() as u64;
+    for byte in word.to_lowercase().bytes() {
+        hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+    }
+
+    let mut scrambled = String::with_capacity(length);
+    while scrambled.len() < length {
+        scrambled.push_str(SYLLABLES[(hash % SYLLABLES.len() as u64) as usize]);
+        hash = hash.rotate_right(5).wrapping_mul(0x100000001b3);
+    }
+    scrambled.truncate(length);
+    if word.starts_with(char::is_uppercase) {
+        scrambled[..1].make_ascii_uppercase();
+    }
+    scrambled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrambling_keeps_word_lengths_and_is_stable() {
+        let scrambled = scramble("Hello there friend", Language::Orcish);
+        let lengths: Vec<usize> = scrambled.split(' ').map(str::len).collect();
+        assert_eq!(lengths, [5, 5, 6]);
+        assert_ne!(scrambled, "Hello there friend");
+        assert!(scrambled.starts_with(char::is_uppercase));
+
+        let first_word = scrambled[..5].to_lowercase();
+        assert_eq!(scramble("hello hello", Language::Orcish), format!("{first_word} {first_word}"));
+        assert_ne!(scramble("hello", Language::Common), scramble("hello", Language::Orcish));
+        assert!(racial_languages(Race::Undead).contains(&Language::Gutterspeak));
+    }
+}
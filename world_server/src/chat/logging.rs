@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use smol::io::AsyncWriteExt;
+use wrath_realm_db::{chat_log::DBChatLogEntry, RealmDatabase};
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChatLogType {
+    Say,
+    Yell,
+    Emote,
+    Whisper,
+    Party,
+    Guild,
+    Channel,
+}
+
+impl ChatLogType {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "say" => Some(Self::Say),
+            "yell" => Some(Self::Yell),
+            "emote" => Some(Self::Emote),
+            "whisper" => Some(Self::Whisper),
+            "party" => Some(Self::Party),
+            "guild" => Some(Self::Guild),
+            "channel" => Some(Self::Channel),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ChatLogType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Say => "say",
+            Self::Yell => "yell",
+            Self::Emote => "emote",
+            Self::Whisper => "whisper",
+            Self::Party => "party",
+            Self::Guild => "guild",
+            Self::Channel => "channel",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+enum ChatLogTarget {
+    //One file per day in the given folder
+    File(PathBuf),
+    Database(Arc<RealmDatabase>),
+}
+
+pub struct ChatLogEntry {
+    pub chat_type: ChatLogType,
+    pub sender_id: u32,
+    pub sender_name: String,
+    pub receiver: Option<String>,
+    pub message: String,
+}
+
+//Writes player chat to daily files or the chat_log table for moderation, on a background task
+//so the game loop never waits on the disk or the database
+pub struct ChatLogger {
+    sender: Option<flume::Sender<(chrono::DateTime<chrono::Utc>, ChatLogEntry)>>,
+    logged_types: HashSet<ChatLogType>,
+}
+
+impl ChatLogger {
+    pub fn from_env(realm_db: Arc<RealmDatabase>) -> Self {
+        let target = match std::env::var("CHAT_LOG_TARGET").unwrap_or_default().to_lowercase().as_str() {
+            "file" => Some(ChatLogTarget::File(
+                std::env::var("CHAT_LOG_FOLDER").unwrap_or_else(|_| "logs".to_string()).into(),
+            )),
+            "database" => Some(ChatLogTarget::Database(realm_db)),
+            _ => None,
+        };
+        let logged_types = std::env::var("CHAT_LOG_TYPES")
+            .unwrap_or_else(|_| "say,yell,emote,whisper,party,guild,channel".to_string())
+            .split(',')
+            .filter_map(ChatLogType::from_name)
+            .collect();
+
+        let sender = target.map(|target| {
+            let (sender, receiver) = flume::unbounded();
+            smol::spawn(run_chat_log_writer(receiver, target)).detach();
+            sender
+        });
+
+        Self { sender, logged_types }
+    }
+
+    pub fn log(&self, entry: ChatLogEntry) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        if !self.logged_types.contains(&entry.chat_type) {
+            return;
+        }
+        if sender.send((chrono::Utc::now(), entry)).is_err() {
+            error!("Chat log writer is not running, chat message was not logged");
+        }
+    }
+}
+
+async fn run_chat_log_writer(receiver: flume::Receiver<(chrono::DateTime<chrono::Utc>, ChatLogEntry)>, target: ChatLogTarget) {
+    while let Ok((time, entry)) = receiver.recv_async().await {
+        let result = match &target {
+            ChatLogTarget::File(folder) => write_to_file(folder, time, &entry).await,
+            ChatLogTarget::Database(realm_db) => {
+                realm_db
+                    .insert_chat_log_entry(&DBChatLogEntry {
+                        time: time.timestamp() as u64,
+                        chat_type: entry.chat_type.to_string(),
+                        sender_id: entry.sender_id,
+                        sender_name: entry.sender_name,
+                        receiver: entry.receiver,
+                        message: entry.message,
+                    })
+                    .await
+            }
+        };
+        if let Err(e) = result {
+            warn!("Failed to write chat log entry: {}", e);
+        }
+    }
+}
+
+async fn write_to_file(folder: &Path, time: chrono::DateTime<chrono::Utc>, entry: &ChatLogEntry) -> Result<()> {
+    smol::fs::create_dir_all(folder).await?;
+    let path = folder.join(format!("chat-{}.log", time.format("%Y-%m-%d")));
+    let mut file = smol::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+
+    let line = format!(
+        "{} [{}] {} ({}){}: {}\n",
+        time.format("%H:%M:%S"),
+        entry.chat_type,
+        entry.sender_name,
+        entry.sender_id,
+        entry.receiver.as_ref().map(|r| format!(" -> {}", r)).unwrap_or_default(),
+        entry.message
+    );
+    file.write_all(line.as_bytes()).await?;
+    Ok(())
+}
@@ -0,0 +1,5 @@
+pub mod channels;
+pub mod hyperlinks;
+pub mod language;
+pub mod logging;
+pub mod moderation;
@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const DEFAULT_RATE_LIMIT_MESSAGES: usize = 5;
+const DEFAULT_RATE_LIMIT_WINDOW_SECONDS: f32 = 1.0;
+const DEFAULT_SPAM_MUTE_SECONDS: u64 = 30;
+const DEFAULT_DUPLICATE_WINDOW_SECONDS: f32 = 5.0;
+
+//Per character bookkeeping for the chat moderation
+#[derive(Default, Debug)]
+pub struct ChatModerationState {
+    recent_message_times: VecDeque<Instant>,
+    last_message: Option<(String, Instant)>,
+    muted_until: Option<Instant>,
+}
+
+impl ChatModerationState {
+    pub fn mute(&mut self, duration: Duration) {
+        self.muted_until = Some(Instant::now() + duration);
+    }
+
+    pub fn unmute(&mut self) {
+        self.muted_until = None;
+    }
+
+    pub fn get_mute_remaining(&self, now: Instant) -> Option<Duration> {
+        self.muted_until
+            .and_then(|until| until.checked_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ChatVerdict {
+    //The message may be sent, with filtered words already masked
+    Allow(String),
+    Muted { remaining: Duration },
+    //Sending too fast got the character muted just now
+    RateLimited { mute_duration: Duration },
+    Duplicate,
+}
+
+pub struct ChatModeration {
+    filtered_words: Vec<String>,
+    rate_limit_messages: usize,
+    rate_limit_window: Duration,
+    spam_mute_duration: Duration,
+    duplicate_window: Duration,
+}
+
+impl ChatModeration {
+    pub fn from_env() -> Self {
+        let filtered_words = std::env::var("CHAT_FILTER_WORDS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|word| word.trim().to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+        let rate_limit_messages = std::env::var("CHAT_RATE_LIMIT_MESSAGES")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|&v| v > 0)
+            .unwrap_or(DEFAULT_RATE_LIMIT_MESSAGES);
+        let rate_limit_window_seconds = std::env::var("CHAT_RATE_LIMIT_WINDOW_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<f32>().ok())
+            .filter(|&v| v > 0.0)
+            .unwrap_or(DEFAULT_RATE_LIMIT_WINDOW_SECONDS);
+        let spam_mute_seconds = std::env::var("CHAT_SPAM_MUTE_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_SPAM_MUTE_SECONDS);
+        let duplicate_window_seconds = std::env::var("CHAT_DUPLICATE_WINDOW_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<f32>().ok())
+            .unwrap_or(DEFAULT_DUPLICATE_WINDOW_SECONDS);
+
+        Self {
+            filtered_words,
+            rate_limit_messages,
+            rate_limit_window: Duration::from_secs_f32(rate_limit_window_seconds),
+            spam_mute_duration: Duration::from_secs(spam_mute_seconds),
+            duplicate_window: Duration::from_secs_f32(duplicate_window_seconds.max(0.0)),
+        }
+    }
+
+    //Runs every player message through the mute check, the rate limit, duplicate suppression and
+    //the word filter, in that order
+    pub fn moderate(&self, state: &mut ChatModerationState, message: &str, now: Instant) -> ChatVerdict {
+        if let Some(remaining) = state.get_mute_remaining(now) {
+            return ChatVerdict::Muted { remaining };
+        }
+
+        while state
+            .recent_message_times
+            .front()
+            .is_some_and(|&time| now.duration_since(time) > self.rate_limit_window)
+        {
+            state.recent_message_times.pop_front();
+        }
+        state.recent_message_times.push_back(now);
+        if state.recent_message_times.len() > self.rate_limit_messages {
+            state.recent_message_times.clear();
+            state.muted_until = Some(now + self.spam_mute_duration);
+            return ChatVerdict::RateLimited {
+                mute_duration: self.spam_mute_duration,
+            };
+        }
+
+        let is_duplicate = state
+            .last_message
+            .as_ref()
+            .is_some_and(|(last, time)| last == message && now.duration_since(*time) < self.duplicate_window);
+        state.last_message = Some((message.to_string(), now));
+        if is_duplicate {
+            return ChatVerdict::Duplicate;
+        }
+
+        ChatVerdict::Allow(self.filter_words(message))
+    }
+
+    //Masks every case-insensitive occurrence of a filtered word with asterisks
+    fn filter_words(&self, message: &str) -> String {
+        let mut chars: Vec<char> = message.chars().collect();
+        let lowercase: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+
+        for word in &self.filtered_words {
+            let word: Vec<char> = word.chars().collect();
+            if word.len() > lowercase.len() {
+                continue;
+            }
+            for start in 0..=(lowercase.len() - word.len()) {
+                if lowercase[start..start + word.len()] == word[..] {
+                    chars[start..start + word.len()].fill('*');
+                }
+            }
+        }
+        chars.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moderation() -> ChatModeration {
+        ChatModeration {
+            filtered_words: vec!["darn".to_string()],
+            rate_limit_messages: 2,
+            rate_limit_window: Duration::from_secs(1),
+            spam_mute_duration: Duration::from_secs(30),
+            duplicate_window: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn filters_words_and_limits_rate() {
+        let moderation = moderation();
+        let mut state = ChatModerationState::default();
+        let now = Instant::now();
+
+        assert_eq!(
+            moderation.moderate(&mut state, "Oh DARN it", now),
+            ChatVerdict::Allow("Oh **** it".to_string())
+        );
+        assert_eq!(moderation.moderate(&mut state, "Oh DARN it", now), ChatVerdict::Duplicate);
+        assert!(matches!(moderation.moderate(&mut state, "hello", now), ChatVerdict::RateLimited { .. }));
+        assert!(matches!(
+            moderation.moderate(&mut state, "hello", now + Duration::from_secs(10)),
+            ChatVerdict::Muted { .. }
+        ));
+        assert!(matches!(
+            moderation.moderate(&mut state, "hello", now + Duration::from_secs(31)),
+            ChatVerdict::Allow(_)
+        ));
+    }
+}
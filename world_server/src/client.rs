@@ -1,13 +1,22 @@
 use super::character::*;
-use crate::character::character_manager::CharacterManager;
+use crate::character::character_manager::{CharacterManager, CharacterManagerHandle};
+use crate::combat;
 use crate::connection::events::ServerEvent;
 use crate::data::DataStorage;
+use crate::error::ProtocolError;
+use crate::handlers;
 use crate::handlers::login_handler::LogoutState;
+use crate::localization::ClientLocale;
 use crate::prelude::*;
+use crate::spell;
+use crate::world::prelude::GameObject;
 use crate::world::World;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use wow_world_messages::wrath::RelationType;
 use wow_world_messages::Guid;
+use wrath_common::{FeatureFlags, GmLevel};
+use wrath_realm_db::{account_session_log::DBAccountSessionStart, RealmDatabase};
 
 #[derive(Clone, PartialEq, Eq)]
 pub enum ClientState {
@@ -20,51 +29,88 @@ pub enum ClientState {
 pub struct ClientData {
     pub client_state: ClientState,
     pub account_id: u32,
+    pub client_build: u32,
+    pub locale: ClientLocale,
+    pub gm_level: GmLevel,
     pub active_character: Option<Guid>,
+    //Row in account_session_log for the character that is currently in the world
+    pub session_log_id: Option<u64>,
 }
 
 pub struct Client {
     pub id: SocketAddr,
 
     pub connection_sender: flume::Sender<ServerEvent>,
+    character_manager: CharacterManagerHandle,
 
     pub data: ClientData,
 }
 
 impl Client {
-    pub fn new(id: SocketAddr, account_id: u32, connection_sender: flume::Sender<ServerEvent>) -> Self {
+    pub fn new(
+        id: SocketAddr,
+        account_id: u32,
+        client_build: u32,
+        locale: ClientLocale,
+        gm_level: GmLevel,
+        connection_sender: flume::Sender<ServerEvent>,
+        character_manager: CharacterManagerHandle,
+    ) -> Self {
         Self {
             id,
             connection_sender,
+            character_manager,
 
             data: ClientData {
                 client_state: ClientState::CharacterSelection,
                 account_id,
+                client_build,
+                locale,
+                gm_level,
                 active_character: None,
+                session_log_id: None,
             },
         }
     }
 
-    pub async fn tick(&mut self, delta_time: f32, character_manager: &mut CharacterManager, world: &mut World) -> Result<()> {
-        let mut should_return_to_character_select: bool = false;
-        if let Some(guid) = self.data.active_character {
-            let character = character_manager.get_character_mut(guid)?;
-            character.tick(delta_time, world).await?;
-
-            should_return_to_character_select = character.logout_state == LogoutState::ReturnToCharSelect;
+    pub async fn tick(
+        &mut self,
+        delta_time: f32,
+        data_storage: &DataStorage,
+        character_manager: &mut CharacterManager,
+        world: &mut World,
+    ) -> Result<()> {
+        let Some(guid) = self.data.active_character else {
+            return Ok(());
+        };
+        let character = character_manager.get_character_mut(guid)?;
+        character.tick(delta_time, world).await?;
+        if character.get_ready_melee_swing().is_some() {
+            combat::melee::perform_melee_swing(guid, data_storage, character_manager, world).await?;
         }
-
-        if should_return_to_character_select {
+        if let Some(cast) = character_manager.get_character_mut(guid)?.take_completed_cast() {
+            spell::spell_cast::finish_cast(guid, cast, data_storage, character_manager, world).await?;
+        }
+        spell::auras::process_aura_events(guid, data_storage, character_manager, world).await?;
+
+        let character = character_manager.get_character(guid)?;
+        if character.logout_state == LogoutState::ReturnToCharSelect {
+            //The character was saved and taken off its map when the logout executed
+            let character = character_manager.get_character(guid)?;
+            handlers::notify_friends_of_status(character, character_manager, false).await?;
+            handlers::notify_guild_of_status(character, character_manager, world, false).await?;
+            character_manager.remove_character(guid);
+            handlers::send_group_list_update(guid, character_manager, world).await?;
             let data = &mut self.data;
             data.active_character = None;
             data.client_state = ClientState::CharacterSelection;
+            self.end_session_log(&world.get_realm_database()).await?;
         }
         Ok(())
     }
 
-    //TODO: add try_get_active_character that returns Option<Guid>
-    pub fn get_active_character(&self) -> Guid {
-        self.data.active_character.unwrap()
+    pub fn get_active_character(&self) -> Result<Guid> {
+        self.data.active_character.ok_or_else(|| ProtocolError::NoActiveCharacter.into())
     }
 
     pub fn disconnected_post_cleanup(&mut self) -> Result<()> {
@@ -80,16 +126,15 @@ impl Client {
         self.data.client_state != ClientState::PreLogin
     }
 
-    pub async fn load_and_set_active_character(
-        &mut self,
-        character_manager: &mut CharacterManager,
-        data_storage: &Arc<DataStorage>,
-        world: &World,
-        character_guid: Guid,
-    ) -> Result<()> {
-        // TODO: send a message to the character manager?
-        let character = Character::load(self.connection_sender.clone(), character_guid, world, data_storage).await?;
-        character_manager.add_character(character);
+    //The character shows up in the character manager once it processes its commands
+    pub async fn load_and_set_active_character(&mut self, data_storage: &Arc<DataStorage>, world: &World, character_guid: Guid) -> Result<()> {
+        let mut character = Character::load(self.connection_sender.clone(), character_guid, world, data_storage).await?;
+        character.set_client_locale(self.data.locale);
+        //Staff show up with the GM tag unless they prefer to start out as a regular player
+        if self.data.gm_level.is_staff() && FeatureFlags::from_env().gm_mode_on_login {
+            character.set_gm_mode(true);
+        }
+        self.character_manager.add_character(character)?;
         self.data.active_character.replace(character_guid);
         Ok(())
     }
@@ -98,9 +143,32 @@ impl Client {
         self.data.active_character.replace(character_guid);
     }
 
+    pub async fn start_session_log(&mut self, realm_db: &RealmDatabase, character: &Character) -> Result<()> {
+        let session = DBAccountSessionStart {
+            account_id: self.data.account_id,
+            character_id: character.get_guid().guid() as u32,
+            character_name: character.name.clone(),
+            ip: self.id.ip().to_string(),
+            client_build: self.data.client_build,
+            login_time: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs(),
+        };
+        self.data.session_log_id = Some(realm_db.start_account_session(&session).await?);
+        Ok(())
+    }
+
+    pub async fn end_session_log(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        if let Some(session_id) = self.data.session_log_id.take() {
+            let logout_time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+            realm_db.end_account_session(session_id, logout_time).await?;
+        }
+        Ok(())
+    }
+
     pub async fn login_active_character(&self, world: &mut World, character_manager: &mut CharacterManager) -> Result<()> {
-        let data = &self.data;
-        let character = character_manager.get_character_mut(data.active_character.unwrap())?;
+        let guid = self.data.active_character.unwrap();
+        let map = character_manager.get_character(guid)?.map;
+        world.assign_instance(guid, map, character_manager).await?;
+        let character = character_manager.get_character_mut(guid)?;
         character.send_packets_before_add_to_map().await?;
 
         world
@@ -110,7 +178,13 @@ impl Client {
             .push_character(character);
 
         character.send_packets_after_add_to_map(world.get_realm_database()).await?;
-
-        Ok(())
+        //Pets aren't saved as being out or not, the one in the first slot always comes back with the character
+        character.summon_pet(&world.get_realm_database(), 0).await?;
+
+        //The contact list shows which friends are online, so it goes out once the character is in the world
+        let character = character_manager.get_character(guid)?;
+        let all_relations = RelationType::empty().set_friend().set_ignored().set_muted().set_recruitafriend();
+        handlers::send_contact_list(character, character_manager, all_relations).await?;
+        handlers::notify_friends_of_status(character, character_manager, true).await
     }
 }
@@ -2,9 +2,14 @@ use super::client::*;
 use crate::character::character_manager::CharacterManager;
 use crate::character::Character;
 use crate::connection::events::ClientEvent;
+use crate::connection::events::ServerEvent;
 use crate::data::DataStorage;
+use crate::error::{HandlerError, ProtocolError};
+use crate::handlers;
 use crate::packet_handler::{PacketHandler, PacketToHandle};
 use crate::prelude::*;
+use crate::rate_limiter::RateLimiter;
+use crate::replay::EventJournal;
 use crate::world::prelude::GameObject;
 use crate::world::World;
 use std::collections::HashMap;
@@ -16,6 +21,8 @@ pub struct ClientManager {
     pub auth_db: Arc<AuthDatabase>,
     pub data_storage: Arc<DataStorage>,
     clients: HashMap<SocketAddr, Client>,
+    pub rate_limiter: RateLimiter,
+    journal: Option<EventJournal>,
 
     sender: flume::Sender<ClientEvent>,
     pub receiver: flume::Receiver<ClientEvent>,
@@ -28,6 +35,8 @@ impl ClientManager {
             auth_db,
             data_storage,
             clients: HashMap::new(),
+            rate_limiter: RateLimiter::from_env(),
+            journal: None,
             sender,
             receiver,
         }
@@ -37,12 +46,25 @@ impl ClientManager {
         self.sender.clone()
     }
 
+    pub fn set_journal(&mut self, journal: EventJournal) {
+        self.journal = Some(journal);
+    }
+
+    //Starts a tick in the event journal, if one is being recorded
+    pub fn record_tick(&mut self, delta_time: f32, seed: u64) -> Result<()> {
+        match &mut self.journal {
+            Some(journal) => journal.record_tick(delta_time, seed),
+            None => Ok(()),
+        }
+    }
+
     pub async fn tick(&mut self, delta_time: f32, character_manager: &mut CharacterManager, world: &mut World) -> Result<()> {
         self.cleanup_disconnected_clients(character_manager, world).await?;
         self.handle_connection_events(character_manager, world).await?;
+        character_manager.process_commands();
         let clients = &mut self.clients;
         for (_, client) in clients.iter_mut() {
-            client.tick(delta_time, character_manager, world).await?;
+            client.tick(delta_time, &self.data_storage, character_manager, world).await?;
         }
 
         Ok(())
@@ -50,13 +72,30 @@ impl ClientManager {
 
     async fn handle_connection_events(&mut self, character_manager: &mut CharacterManager, world: &mut World) -> Result<()> {
         while let Ok(event) = self.receiver.try_recv() {
+            if let Some(journal) = &mut self.journal {
+                if let Err(e) = journal.record_event(&event).await {
+                    error!("Failed to record client event, recording stopped: {}", e);
+                    self.journal = None;
+                }
+            }
             match event {
                 ClientEvent::Connected {
                     addr,
                     account_id,
+                    client_build,
+                    locale,
+                    gm_level,
                     connection_sender,
                 } => {
-                    let client = Client::new(addr, account_id, connection_sender);
+                    let client = Client::new(
+                        addr,
+                        account_id,
+                        client_build,
+                        locale,
+                        gm_level,
+                        connection_sender,
+                        character_manager.get_handle(),
+                    );
                     self.clients.insert(addr, client);
                 }
                 ClientEvent::Disconnected { addr } => {
@@ -71,7 +110,9 @@ impl ClientManager {
                         client_id: addr,
                         payload: Box::new(packet),
                     };
-                    PacketHandler::handle_packet(self, character_manager, world, packet_to_handle).await?;
+                    if let Err(e) = PacketHandler::handle_packet(self, character_manager, world, packet_to_handle).await {
+                        self.handle_packet_error(addr, e).await;
+                    }
                 }
             }
         }
@@ -79,6 +120,24 @@ impl ClientManager {
         Ok(())
     }
 
+    //A client sending garbage is disconnected, other errors are the server's fault and the client keeps playing
+    async fn handle_packet_error(&self, addr: SocketAddr, error: HandlerError) {
+        if !error.disconnects_client() {
+            error!("Error handling packet from {}: {}", addr, error);
+            return;
+        }
+
+        warn!("Disconnecting client {}: {}", addr, error);
+        if let Ok(client) = self.get_client(addr) {
+            let _ = client.connection_sender.send_async(ServerEvent::Disconnect).await;
+        }
+    }
+
+    #[cfg(test)]
+    pub fn add_client(&mut self, client: Client) {
+        self.clients.insert(client.id, client);
+    }
+
     pub fn remove_client(&mut self, client_id: SocketAddr) -> Option<Client> {
         self.clients.remove(&client_id)
     }
@@ -96,16 +155,39 @@ impl ClientManager {
                 };
                 if client_state == ClientState::DisconnectPendingCleanup {
                     // Save character data before disconnecting
-                    if let Some(guid) = client.data.active_character {
-                        if let Ok(character) = character_manager.get_character_mut(guid) {
-                            let _ = character.persist_position_and_playtime(world).await;
+                    let active_character = client.data.active_character;
+                    if let Some(guid) = active_character {
+                        if let Some(character) = character_manager.find_character_mut(guid) {
+                            character
+                                .save_on_leaving_world(world)
+                                .await
+                                .unwrap_or_else(|e| error!("Failed to save character {} of disconnected client {}: {}", guid, id, e));
                         }
+                        world.get_character_info_cache_mut().set_online(guid, false);
+                        world.on_character_left_world(guid);
+                        world.get_account_data_mut().unload_account(client.data.account_id);
                     }
+                    client
+                        .end_session_log(&world.get_realm_database())
+                        .await
+                        .unwrap_or_else(|e| warn!("Failed to close session log for client {}: {}", id, e));
 
-                    world
-                        .get_instance_manager_mut()
-                        .handle_client_disconnected(client, character_manager)
-                        .await?;
+                    world.get_instance_manager_mut().handle_client_disconnected(client, character_manager);
+                    //The map tells the characters around it once it processes its remove queue
+                    if let Some(guid) = active_character {
+                        if let Some(character) = character_manager.find_character(guid) {
+                            handlers::notify_friends_of_status(character, character_manager, false)
+                                .await
+                                .unwrap_or_else(|e| warn!("Failed to notify friends of {} going offline: {}", guid, e));
+                            handlers::notify_guild_of_status(character, character_manager, world, false)
+                                .await
+                                .unwrap_or_else(|e| warn!("Failed to notify the guild of {} going offline: {}", guid, e));
+                        }
+                        character_manager.remove_character(guid);
+                        handlers::send_group_list_update(guid, character_manager, world)
+                            .await
+                            .unwrap_or_else(|e| warn!("Failed to tell the group of {} it went offline: {}", guid, e));
+                    }
                     //insert more cleanup actions here
                     client.disconnected_post_cleanup()?;
                 } else if client_state == ClientState::Disconnected {
@@ -120,6 +202,9 @@ impl ClientManager {
             return Ok(());
         }
 
+        for &id in &to_remove {
+            self.rate_limiter.forget_client(id);
+        }
         let write_clients = &mut self.clients;
         write_clients.retain(|id, _| !to_remove.contains(id));
         info!("Cleaned up {} clients, {} clients left online", to_remove.len(), write_clients.len());
@@ -130,7 +215,7 @@ impl ClientManager {
     pub fn get_authenticated_client(&self, id: SocketAddr) -> Result<&Client> {
         let client = self.get_client(id)?;
         if !client.is_authenticated() {
-            bail!("Character isn't authenticated");
+            return Err(ProtocolError::NotAuthenticated.into());
         }
         Ok(client)
     }
@@ -138,14 +223,14 @@ impl ClientManager {
     pub async fn get_authenticated_client_mut(&mut self, id: SocketAddr) -> Result<&mut Client> {
         let client = self.get_client_mut(id).await?;
         if !client.is_authenticated() {
-            bail!("Character isn't authenticated");
+            return Err(ProtocolError::NotAuthenticated.into());
         }
         Ok(client)
     }
 
     pub async fn get_character_from_client(&self, id: SocketAddr) -> Result<Guid> {
         let client = self.get_authenticated_client(id)?;
-        Ok(client.get_active_character())
+        client.data.active_character.ok_or_else(|| ProtocolError::NoActiveCharacter.into())
     }
 
     pub fn get_client(&self, id: SocketAddr) -> Result<&Client> {
@@ -187,7 +272,97 @@ impl ClientManager {
         Err(anyhow!("Failed to find client for character {}", character_guid))
     }
 
+    //Kicks every connection of the account, they go through the normal disconnect cleanup
+    pub async fn disconnect_account(&self, account_id: u32) -> usize {
+        let mut disconnected = 0;
+        for client in self.clients.values().filter(|client| client.data.account_id == account_id) {
+            if client.connection_sender.send_async(ServerEvent::Disconnect).await.is_ok() {
+                disconnected += 1;
+            }
+        }
+        disconnected
+    }
+
     pub fn get_client_from_character(&self, character: &Character) -> Result<&Client> {
         self.find_client_from_active_character_guid(character.get_guid())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::WorldZoneLocation;
+    use crate::handlers::login_handler::LogoutState;
+    use crate::handlers::movement_handler::TeleportationDistance;
+    use crate::test_utils::{TestClient, TestHarness};
+    use wow_world_messages::wrath::{Map, Vector3d};
+
+    const POSITION: Vector3d = Vector3d {
+        x: -8949.0,
+        y: -132.0,
+        z: 83.5,
+    };
+
+    async fn tick_clients(harness: &mut TestHarness) {
+        harness
+            .client_manager
+            .tick(0.0, &mut harness.character_manager, &mut harness.world)
+            .await
+            .expect("Client tick failed");
+    }
+
+    async fn disconnect(harness: &mut TestHarness, client: &TestClient) {
+        harness.client_manager.get_client_mut(client.addr).await.unwrap().data.client_state = ClientState::DisconnectPendingCleanup;
+        tick_clients(harness).await;
+    }
+
+    fn is_evicted(harness: &TestHarness, client: &TestClient) -> bool {
+        harness.character_manager.find_character(client.guid).is_none()
+    }
+
+    #[test]
+    fn characters_are_evicted_when_they_leave_the_world() {
+        smol::block_on(async {
+            let mut harness = TestHarness::new();
+            let logging_out = harness.add_character("Leaver", POSITION).await;
+            let disconnecting = harness.add_character("Dropper", POSITION).await;
+            let teleporting = harness.add_character("Porter", POSITION).await;
+            let watcher = harness.add_character("Watcher", POSITION).await;
+            harness.tick().await;
+
+            //Logout: saved, taken off the map and evicted in the tick it executes
+            harness.character_manager.get_character_mut(logging_out.guid).unwrap().logout_state = LogoutState::Executing;
+            tick_clients(&mut harness).await;
+            assert!(is_evicted(&harness, &logging_out));
+            let client = harness.client_manager.get_client(logging_out.addr).unwrap();
+            assert!(client.data.active_character.is_none() && client.data.client_state == ClientState::CharacterSelection);
+
+            //Disconnect in the middle of a delayed logout
+            harness.character_manager.get_character_mut(disconnecting.guid).unwrap().logout_state =
+                LogoutState::Pending(std::time::Duration::from_secs(20));
+            disconnect(&mut harness, &disconnecting).await;
+            assert!(is_evicted(&harness, &disconnecting));
+
+            //Disconnect after leaving the old map for a far teleport, before arriving on the new one
+            let destination = WorldZoneLocation {
+                map: Map::Kalimdor,
+                ..Default::default()
+            };
+            harness
+                .character_manager
+                .get_character_mut(teleporting.guid)
+                .unwrap()
+                .teleport_to(TeleportationDistance::Far(destination));
+            tick_clients(&mut harness).await;
+            disconnect(&mut harness, &teleporting).await;
+            assert!(is_evicted(&harness, &teleporting));
+
+            //Once the map processes its removals nobody sees the evicted characters anymore
+            harness.tick().await;
+            let watcher_character = harness.get_character(&watcher);
+            for gone in [&logging_out, &disconnecting, &teleporting] {
+                assert!(!watcher_character.is_in_range(gone.guid));
+            }
+        });
+    }
+}
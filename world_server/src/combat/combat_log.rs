@@ -0,0 +1,221 @@
+//! Every damage, heal, miss and dispel goes through here, so the client's combat log and the
+//! damage meter addons reading it see the same numbers the server used.
+use wow_world_messages::wrath::{
+    AuraLog, AuraLog_AuraType, DamageInfo, DispelMethod, DispelledSpell, SMSG_ATTACKERSTATEUPDATE_HitInfo, SpellLogMiss, SpellMissInfo, SpellSchool,
+    VictimState, SMSG_ATTACKERSTATEUPDATE, SMSG_PERIODICAURALOG, SMSG_SPELLDISPELLOG, SMSG_SPELLHEALLOG, SMSG_SPELLLOGMISS,
+    SMSG_SPELLNONMELEEDAMAGELOG,
+};
+
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::world::World;
+
+#[derive(Clone, Copy, Debug)]
+pub struct DamageLogEntry {
+    pub attacker: Guid,
+    pub target: Guid,
+    //None for white melee swings
+    pub spell_id: Option<u32>,
+    pub school: SpellSchool,
+    pub damage: u32,
+    pub overkill: u32,
+    pub absorbed: u32,
+    pub resisted: u32,
+    pub blocked: u32,
+    pub critical: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HealLogEntry {
+    pub caster: Guid,
+    pub target: Guid,
+    pub spell_id: u32,
+    pub amount: u32,
+    pub overheal: u32,
+    pub absorbed: u32,
+    pub critical: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum PeriodicLogEntry {
+    Damage(DamageLogEntry),
+    Heal(HealLogEntry),
+}
+
+//The log is shown to everyone around the observer, usually the target of the effect
+pub async fn log_damage(observer: &Character, character_manager: &CharacterManager, world: &World, entry: &DamageLogEntry) -> Result<()> {
+    let event = match entry.spell_id {
+        None => ServerEvent::AttackerStateUpdate(melee_damage_log(entry)),
+        Some(spell) => ServerEvent::SpellNonMeleeDamageLog(SMSG_SPELLNONMELEEDAMAGELOG {
+            target: entry.target,
+            attacker: entry.attacker,
+            spell,
+            damage: entry.damage,
+            overkill: entry.overkill,
+            school: entry.school,
+            absorbed_damage: entry.absorbed,
+            resisted: entry.resisted,
+            periodic_log: false,
+            unused: 0,
+            blocked: entry.blocked,
+            hit_info: if entry.critical { HIT_INFO_CRITICAL } else { HIT_INFO_NORMAL },
+            extend_flag: 0,
+        }),
+    };
+    event.send_to_all_in_range(observer, character_manager, true, world).await
+}
+
+//White swings that didn't connect, victim_state tells the client whether to show a dodge or parry
+pub async fn log_melee_avoided(
+    observer: &Character,
+    character_manager: &CharacterManager,
+    world: &World,
+    attacker: Guid,
+    target: Guid,
+    victim_state: VictimState,
+) -> Result<()> {
+    let hit_info = match victim_state {
+        VictimState::Intact => SMSG_ATTACKERSTATEUPDATE_HitInfo::empty().set_miss(),
+        _ => SMSG_ATTACKERSTATEUPDATE_HitInfo::empty(),
+    };
+    ServerEvent::AttackerStateUpdate(SMSG_ATTACKERSTATEUPDATE {
+        hit_info,
+        attacker,
+        target,
+        total_damage: 0,
+        overkill: 0,
+        damage_infos: vec![],
+        victim_state,
+        unknown1: 0,
+        unknown2: 0,
+    })
+    .send_to_all_in_range(observer, character_manager, true, world)
+    .await
+}
+
+pub async fn log_heal(observer: &Character, character_manager: &CharacterManager, world: &World, entry: &HealLogEntry) -> Result<()> {
+    ServerEvent::SpellHealLog(SMSG_SPELLHEALLOG {
+        victim: entry.target,
+        caster: entry.caster,
+        id: entry.spell_id,
+        damage: entry.amount,
+        overheal: entry.overheal,
+        absorb: entry.absorbed,
+        critical: entry.critical,
+        unknown: 0,
+    })
+    .send_to_all_in_range(observer, character_manager, true, world)
+    .await
+}
+
+//Damage and heals from auras ticking, the client lists them as "<spell> ticks" instead of separate hits
+pub async fn log_periodic(observer: &Character, character_manager: &CharacterManager, world: &World, entry: &PeriodicLogEntry) -> Result<()> {
+    let (caster, target, spell, aura_type) = match *entry {
+        PeriodicLogEntry::Damage(damage) => (
+            damage.attacker,
+            damage.target,
+            damage.spell_id.unwrap_or(0),
+            AuraLog_AuraType::PeriodicDamage {
+                damage1: damage.damage,
+                overkill_damage: damage.overkill,
+                school: damage.school,
+                absorb1: damage.absorbed,
+                resisted: damage.resisted,
+                critical1: damage.critical,
+            },
+        ),
+        PeriodicLogEntry::Heal(heal) => (
+            heal.caster,
+            heal.target,
+            heal.spell_id,
+            AuraLog_AuraType::PeriodicHeal {
+                damage2: heal.amount,
+                over_damage: heal.overheal,
+                absorb3: heal.absorbed,
+                critical2: heal.critical,
+            },
+        ),
+    };
+
+    ServerEvent::PeriodicAuraLog(SMSG_PERIODICAURALOG {
+        target,
+        caster,
+        spell,
+        auras: vec![AuraLog { aura_type }],
+    })
+    .send_to_all_in_range(observer, character_manager, true, world)
+    .await
+}
+
+//One packet for all targets the spell missed, area spells miss several targets at once
+pub async fn log_spell_miss(
+    observer: &Character,
+    character_manager: &CharacterManager,
+    world: &World,
+    caster: Guid,
+    spell_id: u32,
+    misses: &[(Guid, SpellMissInfo)],
+) -> Result<()> {
+    ServerEvent::SpellLogMiss(SMSG_SPELLLOGMISS {
+        id: spell_id,
+        caster,
+        unknown1: 0,
+        targets: misses.iter().map(|&(target, miss_info)| SpellLogMiss { target, miss_info }).collect(),
+    })
+    .send_to_all_in_range(observer, character_manager, true, world)
+    .await
+}
+
+pub async fn log_dispel(
+    observer: &Character,
+    character_manager: &CharacterManager,
+    world: &World,
+    caster: Guid,
+    target: Guid,
+    dispel_spell_id: u32,
+    dispelled_spell_ids: &[u32],
+) -> Result<()> {
+    ServerEvent::SpellDispelLog(SMSG_SPELLDISPELLOG {
+        victim: target,
+        caster,
+        dispel_spell: dispel_spell_id,
+        unknown: 0,
+        spells: dispelled_spell_ids
+            .iter()
+            .map(|&spell| DispelledSpell {
+                spell,
+                method: DispelMethod::Dispelled,
+            })
+            .collect(),
+    })
+    .send_to_all_in_range(observer, character_manager, true, world)
+    .await
+}
+
+const HIT_INFO_NORMAL: u32 = 0x0;
+const HIT_INFO_CRITICAL: u32 = 0x2;
+
+fn melee_damage_log(entry: &DamageLogEntry) -> SMSG_ATTACKERSTATEUPDATE {
+    let mut hit_info = SMSG_ATTACKERSTATEUPDATE_HitInfo::empty();
+    if entry.critical {
+        hit_info = hit_info.set_critical_hit();
+    }
+
+    SMSG_ATTACKERSTATEUPDATE {
+        hit_info,
+        attacker: entry.attacker,
+        target: entry.target,
+        total_damage: entry.damage,
+        overkill: entry.overkill,
+        damage_infos: vec![DamageInfo {
+            spell_school_mask: 1 << entry.school.as_int(),
+            damage_float: entry.damage as f32,
+            damage_uint: entry.damage,
+        }],
+        victim_state: if entry.blocked > 0 { VictimState::Blocks } else { VictimState::Wounds },
+        unknown1: 0,
+        unknown2: 0,
+    }
+}
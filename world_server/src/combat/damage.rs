@@ -0,0 +1,228 @@
+//! Damage and healing from any source, white swings and spells alike: changing the victim's health, the
+//! combat log, cast pushback and the victim dying.
+
+use wow_world_messages::wrath::Vector3d;
+
+use super::combat_log::{self, DamageLogEntry, HealLogEntry, PeriodicLogEntry};
+use super::melee;
+use crate::character::character_manager::CharacterManager;
+use crate::character::character_stealth::StealthBreakReason;
+use crate::character::Character;
+use crate::prelude::*;
+use crate::spell;
+use crate::world::creature_manager::SharedCreature;
+use crate::world::group_loot;
+use crate::world::prelude::unit_flags::UnitFlags;
+use crate::world::prelude::GameObject;
+use crate::world::World;
+
+pub enum Victim {
+    Character,
+    Creature(SharedCreature),
+}
+
+//Only things the attacker can see are found, characters on other maps or out of sight can't be hit
+pub fn find_victim(attacker: &Character, victim: Guid, character_manager: &CharacterManager, world: &World) -> Option<Victim> {
+    if victim == attacker.get_guid() {
+        return Some(Victim::Character);
+    }
+    if !attacker.is_in_range(victim) {
+        return None;
+    }
+    if character_manager.find_character(victim).is_some() {
+        return Some(Victim::Character);
+    }
+    let map = world.get_instance_manager().try_get_map_for_character(attacker)?;
+    map.get_creature(victim).cloned().map(Victim::Creature)
+}
+
+fn has_unit_flag(unit_flags: Option<i32>, flag: UnitFlags) -> bool {
+    unit_flags.unwrap_or(0) & flag as i32 != 0
+}
+
+//Characters only fight each other when both have PvP enabled, there are no duels or faction checks yet
+pub async fn can_attack(attacker: &Character, victim_guid: Guid, victim: &Victim, character_manager: &CharacterManager) -> Result<bool> {
+    if victim_guid == attacker.get_guid() {
+        return Ok(false);
+    }
+    let unit_flags = match victim {
+        Victim::Character => {
+            let character = character_manager.get_character(victim_guid)?;
+            let pvp = |character: &Character| has_unit_flag(character.gameplay_data.unit_flags(), UnitFlags::Pvp);
+            if !pvp(attacker) || !pvp(character) {
+                return Ok(false);
+            }
+            character.gameplay_data.unit_flags()
+        }
+        Victim::Creature(creature) => creature.read().await.gameplay_data.unit_flags(),
+    };
+    Ok(!has_unit_flag(unit_flags, UnitFlags::NonAttackable) && !has_unit_flag(unit_flags, UnitFlags::NotSelectable))
+}
+
+pub async fn get_victim_position(victim_guid: Guid, victim: &Victim, character_manager: &CharacterManager) -> Result<Vector3d> {
+    Ok(match victim {
+        Victim::Character => character_manager.get_character(victim_guid)?.movement_info.position,
+        Victim::Creature(creature) => creature.read().await.movement_info.position,
+    })
+}
+
+pub async fn is_victim_alive(victim_guid: Guid, victim: &Victim, character_manager: &CharacterManager) -> Result<bool> {
+    Ok(match victim {
+        Victim::Character => character_manager.get_character(victim_guid)?.is_alive(),
+        Victim::Creature(creature) => creature.read().await.is_alive(),
+    })
+}
+
+//entry.damage is what hit the victim before absorbs, returns what was actually taken off its health.
+//Periodic damage comes from auras ticking and shows up differently in the combat log.
+pub async fn deal_damage(
+    mut entry: DamageLogEntry,
+    victim: &Victim,
+    periodic: bool,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+) -> Result<u32> {
+    let victim_guid = entry.target;
+    let (health, absorbed) = match victim {
+        Victim::Character => {
+            let character = character_manager.get_character(victim_guid)?;
+            let absorbed = if character.is_god_mode_enabled() { entry.damage } else { 0 };
+            (character.gameplay_data.unit_health().unwrap_or(0).max(0) as u32, absorbed)
+        }
+        Victim::Creature(creature) => (creature.read().await.gameplay_data.unit_health().unwrap_or(0).max(0) as u32, 0),
+    };
+    entry.damage -= absorbed;
+    entry.absorbed += absorbed;
+    entry.overkill = entry.damage.saturating_sub(health);
+    let remaining_health = health.saturating_sub(entry.damage);
+
+    match victim {
+        Victim::Character => {
+            let character = character_manager.get_character_mut(victim_guid)?;
+            character.gameplay_data.set_unit_health(remaining_health as i32);
+            character.add_rage_from_damage_taken(entry.damage);
+        }
+        Victim::Creature(creature) => creature.write().await.gameplay_data.set_unit_health(remaining_health as i32),
+    }
+
+    let attacker = character_manager.get_character(entry.attacker)?;
+    match periodic {
+        true => combat_log::log_periodic(attacker, character_manager, world, &PeriodicLogEntry::Damage(entry)).await?,
+        false => combat_log::log_damage(attacker, character_manager, world, &entry).await?,
+    }
+
+    //Misses and full absorbs pull a creature all the same
+    if let Victim::Creature(_) = victim {
+        let attacker = character_manager.get_character(entry.attacker)?;
+        if let Some(map) = world.get_instance_manager_mut().try_get_map_for_character_mut(attacker) {
+            map.on_creature_attacked(victim_guid, entry.attacker, character_manager).await?;
+        }
+    }
+    if entry.damage == 0 {
+        return Ok(0);
+    }
+    if let Victim::Character = victim {
+        handlers::handle_caster_damaged(character_manager, world, victim_guid).await?;
+        spell::auras::break_stealth(victim_guid, StealthBreakReason::DamageTaken, character_manager, world).await?;
+    }
+    if remaining_health == 0 {
+        handle_victim_died(entry.attacker, victim_guid, victim, character_manager, world).await?;
+    }
+    Ok(entry.damage)
+}
+
+//entry.amount is the full heal, whatever goes over the target's maximum health ends up as overheal
+pub async fn heal(mut entry: HealLogEntry, target: &Victim, periodic: bool, character_manager: &mut CharacterManager, world: &World) -> Result<()> {
+    let target_guid = entry.target;
+    let (health, max_health) = match target {
+        Victim::Character => {
+            let data = &character_manager.get_character(target_guid)?.gameplay_data;
+            (data.unit_health().unwrap_or(0), data.unit_maxhealth().unwrap_or(0))
+        }
+        Victim::Creature(creature) => {
+            let data = &creature.read().await.gameplay_data;
+            (data.unit_health().unwrap_or(0), data.unit_maxhealth().unwrap_or(0))
+        }
+    };
+    let missing = (max_health - health).max(0) as u32;
+    entry.overheal = entry.amount.saturating_sub(missing);
+    let new_health = health + (entry.amount - entry.overheal) as i32;
+
+    match target {
+        Victim::Character => character_manager
+            .get_character_mut(target_guid)?
+            .gameplay_data
+            .set_unit_health(new_health),
+        Victim::Creature(creature) => creature.write().await.gameplay_data.set_unit_health(new_health),
+    }
+    let caster = character_manager.get_character(entry.caster)?;
+    match periodic {
+        true => combat_log::log_periodic(caster, character_manager, world, &PeriodicLogEntry::Heal(entry)).await,
+        false => combat_log::log_heal(caster, character_manager, world, &entry).await,
+    }
+}
+
+async fn handle_victim_died(
+    killer_guid: Guid,
+    victim_guid: Guid,
+    victim: &Victim,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+) -> Result<()> {
+    //Everyone that was hitting the victim stops
+    let attackers: Vec<Guid> = character_manager
+        .get_all_characters()
+        .filter(|character| character.get_melee_victim() == Some(victim_guid))
+        .map(|character| character.get_guid())
+        .collect();
+    for attacker in attackers {
+        melee::stop_melee_attack(attacker, character_manager, world).await?;
+    }
+
+    match victim {
+        Victim::Character => {
+            melee::stop_melee_attack(victim_guid, character_manager, world).await?;
+            handlers::interrupt_character_cast(character_manager, world, victim_guid, None).await?;
+            spell::auras::remove_all_auras(victim_guid, character_manager, world).await?;
+            info!(
+                "{} was killed by {}",
+                character_manager.get_character(victim_guid)?.name,
+                character_manager.get_character(killer_guid)?.name
+            );
+        }
+        Victim::Creature(creature) => {
+            let killer = character_manager.get_character(killer_guid)?;
+            //Group members only get a share when they were close enough to see the kill
+            let mut recipients = vec![killer_guid];
+            recipients.extend(
+                world
+                    .get_group_members(killer_guid)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|&member| member != killer_guid && killer.is_in_range(member)),
+            );
+            {
+                let mut creature = creature.write().await;
+                let mut loot = world.get_loot_templates().generate(creature.entry, recipients.clone());
+                if let Some(loot) = loot.as_mut() {
+                    group_loot::apply_group_loot(loot, victim_guid, killer, &recipients, character_manager, world).await?;
+                }
+                creature.set_loot(loot);
+            }
+            let realm_db = world.get_realm_database();
+            let mut rare_spawn_id = None;
+            if let Some(map) = world.get_instance_manager_mut().try_get_map_for_character_mut(killer) {
+                rare_spawn_id = map.get_rare_spawn_id(victim_guid);
+                map.on_creature_died(victim_guid, character_manager, &realm_db).await?;
+            }
+            if let Some(rare_spawn_id) = rare_spawn_id {
+                world.on_rare_spawn_killed(rare_spawn_id).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn distance(a: Vector3d, b: Vector3d) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
@@ -0,0 +1,237 @@
+//! White melee swings. The swing timer lives on the character (`character_melee`), this is what happens when
+//! it runs out: the roll on the attack table and the damage it does.
+
+use rand::Rng;
+use wow_world_messages::wrath::{
+    SpellSchool, VictimState, SMSG_ATTACKSTART, SMSG_ATTACKSTOP, SMSG_ATTACKSWING_CANT_ATTACK, SMSG_ATTACKSWING_DEADTARGET,
+    SMSG_ATTACKSWING_NOTINRANGE,
+};
+
+use super::combat_log::{self, DamageLogEntry};
+use super::damage::{self, Victim};
+use crate::character::character_manager::CharacterManager;
+use crate::character::character_melee::MELEE_RANGE;
+use crate::character::character_ratings::{CombatRating, MeleeAttackTable};
+use crate::character::character_stealth::StealthBreakReason;
+use crate::character::Character;
+use crate::connection::events::ServerEvent;
+use crate::data::DataStorage;
+use crate::prelude::*;
+use crate::random;
+use crate::spell;
+use crate::world::prelude::GameObject;
+use crate::world::World;
+
+//A block stops this part of the swing, there are no shield block values yet
+const BLOCKED_DAMAGE_FRACTION: u32 = 2;
+//Rage generated per swing, see Character::add_rage_from_damage_dealt
+const MAIN_HAND_HIT_FACTOR: f32 = 3.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MeleeOutcome {
+    Miss,
+    Dodge,
+    Parry,
+    Block,
+    Crit,
+    Hit,
+}
+
+//One roll over the whole table: every outcome takes up its chance in order and whatever is left is a normal hit
+pub fn roll_melee_outcome(table: &MeleeAttackTable, roll: f32) -> MeleeOutcome {
+    let mut threshold = 0.0;
+    for (chance, outcome) in [
+        (table.miss, MeleeOutcome::Miss),
+        (table.dodge, MeleeOutcome::Dodge),
+        (table.parry, MeleeOutcome::Parry),
+        (table.block, MeleeOutcome::Block),
+        (table.crit, MeleeOutcome::Crit),
+    ] {
+        threshold += chance;
+        if roll < threshold {
+            return outcome;
+        }
+    }
+    MeleeOutcome::Hit
+}
+
+enum AttackSwingError {
+    CantAttack,
+    DeadTarget,
+}
+
+async fn check_victim(
+    attacker: &Character,
+    victim_guid: Guid,
+    character_manager: &CharacterManager,
+    world: &World,
+) -> std::result::Result<Victim, AttackSwingError> {
+    if !attacker.is_alive() || victim_guid == attacker.get_guid() {
+        return Err(AttackSwingError::CantAttack);
+    }
+    let victim = damage::find_victim(attacker, victim_guid, character_manager, world).ok_or(AttackSwingError::CantAttack)?;
+    if !damage::can_attack(attacker, victim_guid, &victim, character_manager)
+        .await
+        .map_err(|_| AttackSwingError::CantAttack)?
+    {
+        return Err(AttackSwingError::CantAttack);
+    }
+    if !damage::is_victim_alive(victim_guid, &victim, character_manager)
+        .await
+        .map_err(|_| AttackSwingError::CantAttack)?
+    {
+        return Err(AttackSwingError::DeadTarget);
+    }
+    Ok(victim)
+}
+
+pub async fn start_melee_attack(attacker_guid: Guid, victim_guid: Guid, character_manager: &mut CharacterManager, world: &World) -> Result<()> {
+    let attacker = character_manager.get_character(attacker_guid)?;
+    match check_victim(attacker, victim_guid, character_manager, world).await {
+        Ok(_) => {}
+        Err(AttackSwingError::CantAttack) => {
+            return ServerEvent::AttackSwingCantAttack(SMSG_ATTACKSWING_CANT_ATTACK)
+                .send_to_character(attacker)
+                .await;
+        }
+        Err(AttackSwingError::DeadTarget) => {
+            return ServerEvent::AttackSwingDeadTarget(SMSG_ATTACKSWING_DEADTARGET)
+                .send_to_character(attacker)
+                .await;
+        }
+    }
+
+    if !character_manager.get_character_mut(attacker_guid)?.start_melee_attack(victim_guid) {
+        return Ok(());
+    }
+    ServerEvent::AttackStart(SMSG_ATTACKSTART {
+        attacker: attacker_guid,
+        victim: victim_guid,
+    })
+    .send_to_all_in_range(character_manager.get_character(attacker_guid)?, character_manager, true, world)
+    .await
+}
+
+pub async fn stop_melee_attack(attacker_guid: Guid, character_manager: &mut CharacterManager, world: &World) -> Result<()> {
+    let Some(victim) = character_manager.get_character_mut(attacker_guid)?.stop_melee_attack() else {
+        return Ok(());
+    };
+    ServerEvent::AttackStop(SMSG_ATTACKSTOP {
+        player: attacker_guid,
+        enemy: victim,
+        unknown1: 0,
+    })
+    .send_to_all_in_range(character_manager.get_character(attacker_guid)?, character_manager, true, world)
+    .await
+}
+
+//Called by the client tick once the character's swing timer ran out
+pub async fn perform_melee_swing(
+    attacker_guid: Guid,
+    data_storage: &DataStorage,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+) -> Result<()> {
+    let attacker = character_manager.get_character(attacker_guid)?;
+    let Some(victim_guid) = attacker.get_ready_melee_swing() else {
+        return Ok(());
+    };
+    //The victim despawned, died to someone else or left
+    let victim = match check_victim(attacker, victim_guid, character_manager, world).await {
+        Ok(victim) => victim,
+        Err(_) => return stop_melee_attack(attacker_guid, character_manager, world).await,
+    };
+
+    let victim_position = damage::get_victim_position(victim_guid, &victim, character_manager).await?;
+    if damage::distance(attacker.movement_info.position, victim_position) > MELEE_RANGE {
+        //The swing stays ready and lands as soon as the attacker gets close enough
+        if character_manager.get_character_mut(attacker_guid)?.report_melee_out_of_range() {
+            ServerEvent::AttackSwingNotInRange(SMSG_ATTACKSWING_NOTINRANGE)
+                .send_to_character(character_manager.get_character(attacker_guid)?)
+                .await?;
+        }
+        return Ok(());
+    }
+
+    let table = match &victim {
+        Victim::Character => attacker.get_melee_attack_table(character_manager.get_character(victim_guid)?, data_storage),
+        Victim::Creature(_) => attacker.get_melee_attack_table_against_creature(data_storage),
+    };
+    let outcome = roll_melee_outcome(&table, random::with_rng(|rng| rng.gen_range(0.0..100.0)));
+    let attack_time = attacker.get_attack_time();
+    let mut swing_damage = attacker.roll_melee_damage();
+    character_manager.get_character_mut(attacker_guid)?.finish_melee_swing();
+    //Swinging gives the attacker away, whether it lands or not
+    spell::auras::break_stealth(attacker_guid, StealthBreakReason::DamageDealt, character_manager, world).await?;
+
+    let avoided = match outcome {
+        MeleeOutcome::Miss => Some(VictimState::Intact),
+        MeleeOutcome::Dodge => Some(VictimState::Dodge),
+        MeleeOutcome::Parry => Some(VictimState::Parry),
+        _ => None,
+    };
+    if let Some(victim_state) = avoided {
+        let attacker = character_manager.get_character(attacker_guid)?;
+        return combat_log::log_melee_avoided(attacker, character_manager, world, attacker_guid, victim_guid, victim_state).await;
+    }
+
+    let critical = outcome == MeleeOutcome::Crit;
+    if critical {
+        swing_damage *= 2;
+        //Resilience takes a cut out of crits against players
+        if let Victim::Character = victim {
+            let reduction = character_manager
+                .get_character(victim_guid)?
+                .get_resilience_crit_damage_reduction(CombatRating::CritTakenMelee, data_storage);
+            swing_damage = (swing_damage as f32 * (1.0 - reduction / 100.0).max(0.0)) as u32;
+        }
+    }
+    let blocked = if outcome == MeleeOutcome::Block {
+        swing_damage / BLOCKED_DAMAGE_FRACTION
+    } else {
+        0
+    };
+    swing_damage -= blocked;
+
+    let entry = DamageLogEntry {
+        attacker: attacker_guid,
+        target: victim_guid,
+        spell_id: None,
+        school: SpellSchool::Normal,
+        damage: swing_damage,
+        overkill: 0,
+        absorbed: 0,
+        resisted: 0,
+        blocked,
+        critical,
+    };
+    let dealt = damage::deal_damage(entry, &victim, false, character_manager, world).await?;
+
+    let hit_factor = if critical { MAIN_HAND_HIT_FACTOR * 2.0 } else { MAIN_HAND_HIT_FACTOR };
+    character_manager
+        .get_character_mut(attacker_guid)?
+        .add_rage_from_damage_dealt(dealt, attack_time, hit_factor);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attack_table_is_rolled_in_order() {
+        let table = MeleeAttackTable {
+            miss: 5.0,
+            dodge: 5.0,
+            parry: 5.0,
+            block: 5.0,
+            crit: 10.0,
+        };
+        assert_eq!(roll_melee_outcome(&table, 0.0), MeleeOutcome::Miss);
+        assert_eq!(roll_melee_outcome(&table, 7.5), MeleeOutcome::Dodge);
+        assert_eq!(roll_melee_outcome(&table, 12.5), MeleeOutcome::Parry);
+        assert_eq!(roll_melee_outcome(&table, 17.5), MeleeOutcome::Block);
+        assert_eq!(roll_melee_outcome(&table, 25.0), MeleeOutcome::Crit);
+        assert_eq!(roll_melee_outcome(&table, 30.0), MeleeOutcome::Hit);
+    }
+}
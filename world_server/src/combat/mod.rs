@@ -0,0 +1,3 @@
+pub mod combat_log;
+pub mod damage;
+pub mod melee;
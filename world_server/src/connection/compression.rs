@@ -0,0 +1,120 @@
+//! zlib compressed variants of the packets that come in large bursts.
+//!
+//! Logging in or walking into a city sends a pile of object updates, and creatures moving around a crowded
+//! area send many small monster moves. Once an update object packet, or a run of monster moves queued in the
+//! same batch, serializes to at least the threshold it goes out as `SMSG_COMPRESSED_UPDATE_OBJECT` or
+//! `SMSG_COMPRESSED_MOVES` instead. wow_world_messages deflates the body of those opcodes while writing
+//! them, this module only decides when that is worth it. Small packets stay uncompressed, deflating them
+//! costs CPU on both ends and barely saves anything.
+
+use anyhow::Result;
+use wow_srp::wrath_header::ServerEncrypterHalf;
+use wow_world_messages::wrath::{
+    CompressedMove, CompressedMove_CompressedMoveOpcode, MonsterMove, ServerMessage, SMSG_COMPRESSED_MOVES, SMSG_COMPRESSED_UPDATE_OBJECT,
+    SMSG_MONSTER_MOVE, SMSG_UPDATE_OBJECT,
+};
+use wrath_common::config;
+
+const DEFAULT_COMPRESSION_THRESHOLD: usize = 1024;
+
+#[derive(Clone, Copy)]
+pub struct PacketCompression {
+    //Uncompressed size in bytes from which packets get compressed, None never compresses
+    threshold: Option<usize>,
+}
+
+impl PacketCompression {
+    //PACKET_COMPRESSION_THRESHOLD of 0 turns compression off
+    pub fn from_env() -> Self {
+        let threshold = config::or_default("PACKET_COMPRESSION_THRESHOLD", DEFAULT_COMPRESSION_THRESHOLD);
+        Self {
+            threshold: (threshold > 0).then_some(threshold),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.threshold.is_some()
+    }
+
+    fn should_compress(&self, uncompressed_size: usize) -> bool {
+        self.threshold.is_some_and(|threshold| uncompressed_size >= threshold)
+    }
+
+    pub async fn write_update_object(&self, message: SMSG_UPDATE_OBJECT, buffer: &mut Vec<u8>, encryption: &mut ServerEncrypterHalf) -> Result<()> {
+        if self.is_enabled() && self.should_compress(uncompressed_size(&message).await?) {
+            SMSG_COMPRESSED_UPDATE_OBJECT { objects: message.objects }
+                .astd_write_encrypted_server(&mut *buffer, encryption)
+                .await?;
+        } else {
+            message.astd_write_encrypted_server(&mut *buffer, encryption).await?;
+        }
+        Ok(())
+    }
+
+    //Writes the monster moves collected from a batch, drained so the caller can keep collecting
+    pub async fn write_monster_moves(
+        &self,
+        moves: &mut Vec<SMSG_MONSTER_MOVE>,
+        buffer: &mut Vec<u8>,
+        encryption: &mut ServerEncrypterHalf,
+    ) -> Result<()> {
+        let mut total_size = 0;
+        if self.is_enabled() && moves.len() > 1 {
+            for message in moves.iter() {
+                total_size += uncompressed_size(message).await?;
+            }
+        }
+
+        if self.should_compress(total_size) {
+            let moves = moves.drain(..).map(compressed_move).collect();
+            SMSG_COMPRESSED_MOVES { moves }
+                .astd_write_encrypted_server(&mut *buffer, encryption)
+                .await?;
+        } else {
+            for message in moves.drain(..) {
+                message.astd_write_encrypted_server(&mut *buffer, encryption).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn uncompressed_size(message: &impl ServerMessage) -> Result<usize> {
+    let mut scratch = Vec::new();
+    message.astd_write_unencrypted_server(&mut scratch).await?;
+    Ok(scratch.len())
+}
+
+fn compressed_move(message: SMSG_MONSTER_MOVE) -> CompressedMove {
+    CompressedMove {
+        guid: message.guid,
+        opcode: CompressedMove_CompressedMoveOpcode::SmsgMonsterMove {
+            monster_move: MonsterMove {
+                spline_point: message.spline_point,
+                spline_id: message.spline_id,
+                move_type: message.move_type,
+                spline_flags: message.spline_flags,
+                duration: message.duration,
+                vertical_acceleration: message.vertical_acceleration,
+                effect_start_time: message.effect_start_time,
+                splines: message.splines,
+            },
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_packets_from_the_threshold_get_compressed() {
+        let compression = PacketCompression { threshold: Some(1024) };
+        assert!(!compression.should_compress(1023));
+        assert!(compression.should_compress(1024));
+
+        let disabled = PacketCompression { threshold: None };
+        assert!(!disabled.is_enabled());
+        assert!(!disabled.should_compress(usize::MAX));
+    }
+}
@@ -1,6 +1,8 @@
 use std::{fmt, net::SocketAddr};
 
+use crate::localization::ClientLocale;
 use wow_world_messages::wrath::{opcodes::ClientOpcodeMessage, *};
+use wrath_common::GmLevel;
 
 /// Events produced by the network/IO layer and consumed by the client manager.
 #[allow(clippy::large_enum_variant)]
@@ -8,6 +10,9 @@ pub enum ClientEvent {
     Connected {
         addr: SocketAddr,
         account_id: u32,
+        client_build: u32,
+        locale: ClientLocale,
+        gm_level: GmLevel,
         // This sender is used to send messages back to the client from the manager
         connection_sender: flume::Sender<ServerEvent>,
     },
@@ -25,32 +30,93 @@ pub enum ClientEvent {
 pub enum ServerEvent {
     AccountDataTimes(SMSG_ACCOUNT_DATA_TIMES),
     ActionButtons(SMSG_ACTION_BUTTONS),
+    AttackStart(SMSG_ATTACKSTART),
+    AttackStop(SMSG_ATTACKSTOP),
+    AttackSwingCantAttack(SMSG_ATTACKSWING_CANT_ATTACK),
+    AttackSwingDeadTarget(SMSG_ATTACKSWING_DEADTARGET),
+    AttackSwingNotInRange(SMSG_ATTACKSWING_NOTINRANGE),
+    AttackerStateUpdate(SMSG_ATTACKERSTATEUPDATE),
+    AuraUpdate(SMSG_AURA_UPDATE),
+    AuraUpdateAll(SMSG_AURA_UPDATE_ALL),
     BindPointUpdate(SMSG_BINDPOINTUPDATE),
+    BuyFailed(SMSG_BUY_FAILED),
+    BuyItem(SMSG_BUY_ITEM),
+    CalendarCommandResult(SMSG_CALENDAR_COMMAND_RESULT),
+    CalendarEventInvite(SMSG_CALENDAR_EVENT_INVITE),
+    CalendarEventInviteAlert(SMSG_CALENDAR_EVENT_INVITE_ALERT),
+    CalendarEventStatus(SMSG_CALENDAR_EVENT_STATUS),
+    CalendarSendCalendar(SMSG_CALENDAR_SEND_CALENDAR),
+    CalendarSendEvent(SMSG_CALENDAR_SEND_EVENT),
     CalendarSendNumPending(SMSG_CALENDAR_SEND_NUM_PENDING),
+    CastFailed(SMSG_CAST_FAILED),
+    ChannelNotify(SMSG_CHANNEL_NOTIFY),
+    ChannelUpdate(MSG_CHANNEL_UPDATE),
     CharCreate(SMSG_CHAR_CREATE),
     CharDelete(SMSG_CHAR_DELETE),
     CharEnum(SMSG_CHAR_ENUM),
     ContactList(SMSG_CONTACT_LIST),
+    CreatureQueryResponse(SMSG_CREATURE_QUERY_RESPONSE),
     DestroyObject(SMSG_DESTROY_OBJECT),
     Disconnect,
+    EquipmentSetList(SMSG_EQUIPMENT_SET_LIST),
+    EquipmentSetSaved(SMSG_EQUIPMENT_SET_SAVED),
+    EquipmentSetUseResult(SMSG_EQUIPMENT_SET_USE_RESULT),
     FeatureSystemStatus(SMSG_FEATURE_SYSTEM_STATUS),
     ForceMoveRoot(SMSG_FORCE_MOVE_ROOT),
     ForceMoveUnroot(SMSG_FORCE_MOVE_UNROOT),
     ForceRunSpeedChange(SMSG_FORCE_RUN_SPEED_CHANGE),
     ForceRunBackSpeedChange(SMSG_FORCE_RUN_BACK_SPEED_CHANGE),
+    FriendStatus(SMSG_FRIEND_STATUS),
     GMTicketGetTicket(SMSG_GMTICKET_GETTICKET),
     GMTicketSystemStatus(SMSG_GMTICKET_SYSTEMSTATUS),
+    GameobjectQueryResponse(SMSG_GAMEOBJECT_QUERY_RESPONSE),
+    GossipComplete(SMSG_GOSSIP_COMPLETE),
+    GossipMessage(SMSG_GOSSIP_MESSAGE),
+    GossipPoi(SMSG_GOSSIP_POI),
+    GroupDecline(SMSG_GROUP_DECLINE),
+    GroupDestroyed(SMSG_GROUP_DESTROYED),
+    GroupInvite(SMSG_GROUP_INVITE),
+    GroupList(SMSG_GROUP_LIST),
+    GroupSetLeader(SMSG_GROUP_SET_LEADER),
+    GuildCommandResult(SMSG_GUILD_COMMAND_RESULT),
+    GuildDecline(SMSG_GUILD_DECLINE),
+    GuildEvent(SMSG_GUILD_EVENT),
+    GuildInvite(SMSG_GUILD_INVITE),
+    GuildQueryResponse(SMSG_GUILD_QUERY_RESPONSE),
+    GuildRoster(SMSG_GUILD_ROSTER),
     InitializeFactions(SMSG_INITIALIZE_FACTIONS),
     InitialSpells(SMSG_INITIAL_SPELLS),
     InitWorldStates(SMSG_INIT_WORLD_STATES),
+    InventoryChangeFailure(SMSG_INVENTORY_CHANGE_FAILURE),
     ItemNameQueryResponse(SMSG_ITEM_NAME_QUERY_RESPONSE),
+    ItemPushResult(SMSG_ITEM_PUSH_RESULT),
     ItemQuerySingleResponse(SMSG_ITEM_QUERY_SINGLE_RESPONSE),
+    ListInventory(SMSG_LIST_INVENTORY),
     LoginSetTimeSpeed(SMSG_LOGIN_SETTIMESPEED),
     LoginVerifyWorld(SMSG_LOGIN_VERIFY_WORLD),
     LogoutCancelAck(SMSG_LOGOUT_CANCEL_ACK),
     LogoutComplete(SMSG_LOGOUT_COMPLETE),
     LogoutResponse(SMSG_LOGOUT_RESPONSE),
+    LootAllPassed(SMSG_LOOT_ALL_PASSED),
+    LootClearMoney(SMSG_LOOT_CLEAR_MONEY),
+    LootMoneyNotify(SMSG_LOOT_MONEY_NOTIFY),
+    LootReleaseResponse(SMSG_LOOT_RELEASE_RESPONSE),
+    LootRemoved(SMSG_LOOT_REMOVED),
+    LootResponse(SMSG_LOOT_RESPONSE),
+    LootRoll(SMSG_LOOT_ROLL),
+    LootRollWon(SMSG_LOOT_ROLL_WON),
+    LootStartRoll(SMSG_LOOT_START_ROLL),
+    MailListResult(SMSG_MAIL_LIST_RESULT),
     MessageChat(SMSG_MESSAGECHAT),
+    MonsterMove(SMSG_MONSTER_MOVE),
+    Motd(SMSG_MOTD),
+    MoveFeatherFall(SMSG_MOVE_FEATHER_FALL),
+    MoveKnockBack(SMSG_MOVE_KNOCK_BACK),
+    MoveKnockBackBroadcast(MSG_MOVE_KNOCK_BACK_Server),
+    MoveLandWalk(SMSG_MOVE_LAND_WALK),
+    MoveNormalFall(SMSG_MOVE_NORMAL_FALL),
+    MoveSetCanFly(SMSG_MOVE_SET_CAN_FLY),
+    MoveSetHover(SMSG_MOVE_SET_HOVER),
     MoveTeleportAck(MSG_MOVE_TELEPORT_ACK_Server),
     MoveStartForward(MSG_MOVE_START_FORWARD),
     MoveStartBackward(MSG_MOVE_START_BACKWARD),
@@ -72,21 +138,54 @@ pub enum ServerEvent {
     MoveStopSwim(MSG_MOVE_STOP_SWIM),
     MoveSetFacing(MSG_MOVE_SET_FACING),
     MoveHeartbeat(MSG_MOVE_HEARTBEAT),
+    MoveUnsetCanFly(SMSG_MOVE_UNSET_CAN_FLY),
+    MoveUnsetHover(SMSG_MOVE_UNSET_HOVER),
+    MoveWaterWalk(SMSG_MOVE_WATER_WALK),
     NameQueryResponse(SMSG_NAME_QUERY_RESPONSE),
+    NewTaxiPath(SMSG_NEW_TAXI_PATH),
     NewWorld(SMSG_NEW_WORLD),
+    Notification(SMSG_NOTIFICATION),
+    PartyCommandResult(SMSG_PARTY_COMMAND_RESULT),
+    PeriodicAuraLog(SMSG_PERIODICAURALOG),
     PlayedTime(SMSG_PLAYED_TIME),
     QueryTimeResponse(SMSG_QUERY_TIME_RESPONSE),
     Pong(SMSG_PONG),
+    QuestGiverOfferReward(SMSG_QUESTGIVER_OFFER_REWARD),
+    QuestGiverQuestComplete(SMSG_QUESTGIVER_QUEST_COMPLETE),
+    QuestGiverQuestDetails(SMSG_QUESTGIVER_QUEST_DETAILS),
+    QuestGiverQuestInvalid(SMSG_QUESTGIVER_QUEST_INVALID),
+    QuestLogFull(SMSG_QUESTLOG_FULL),
+    QuestUpdateAddKill(SMSG_QUESTUPDATE_ADD_KILL),
+    QuestUpdateComplete(SMSG_QUESTUPDATE_COMPLETE),
     RaidInstanceInfo(SMSG_RAID_INSTANCE_INFO),
     RealmSplit(SMSG_REALM_SPLIT),
+    ReceivedMail(SMSG_RECEIVED_MAIL),
+    RespondInspectAchievements(SMSG_RESPOND_INSPECT_ACHIEVEMENTS),
+    SellItem(SMSG_SELL_ITEM),
+    SendMailResult(SMSG_SEND_MAIL_RESULT),
     SetDungeonDifficulty(MSG_SET_DUNGEON_DIFFICULTY_Server),
+    SetFactionStanding(SMSG_SET_FACTION_STANDING),
+    SetPhaseShift(SMSG_SET_PHASE_SHIFT),
+    ShowTaxiNodes(SMSG_SHOWTAXINODES),
+    SpellDelayed(SMSG_SPELL_DELAYED),
+    SpellDispelLog(SMSG_SPELLDISPELLOG),
+    SpellFailure(SMSG_SPELL_FAILURE),
+    SpellGo(SMSG_SPELL_GO),
+    SpellHealLog(SMSG_SPELLHEALLOG),
+    SpellLogMiss(SMSG_SPELLLOGMISS),
+    SpellNonMeleeDamageLog(SMSG_SPELLNONMELEEDAMAGELOG),
+    SpellStart(SMSG_SPELL_START),
     StandStateUpdate(SMSG_STANDSTATE_UPDATE),
+    SummonRequest(SMSG_SUMMON_REQUEST),
+    TaxiNodeStatus(SMSG_TAXINODE_STATUS),
     TimeSyncReq(SMSG_TIME_SYNC_REQ),
     TransferPending(SMSG_TRANSFER_PENDING),
     TriggerCinematic(SMSG_TRIGGER_CINEMATIC),
     TutorialFlags(SMSG_TUTORIAL_FLAGS),
     UpdateAccountData(SMSG_UPDATE_ACCOUNT_DATA),
     UpdateAccountDataComplete(SMSG_UPDATE_ACCOUNT_DATA_COMPLETE),
+    UpdateComboPoints(SMSG_UPDATE_COMBO_POINTS),
+    UpdateInstanceEncounterUnit(SMSG_UPDATE_INSTANCE_ENCOUNTER_UNIT),
     UpdateObject(SMSG_UPDATE_OBJECT),
     UpdateWorldState(SMSG_UPDATE_WORLD_STATE),
     WorldStateUiTimerUpdate(SMSG_WORLD_STATE_UI_TIMER_UPDATE),
@@ -97,32 +196,93 @@ impl fmt::Display for ServerEvent {
         match self {
             ServerEvent::AccountDataTimes(_) => write!(f, "SMSG_ACCOUNT_DATA_TIMES"),
             ServerEvent::ActionButtons(_) => write!(f, "SMSG_ACTION_BUTTONS"),
+            ServerEvent::AttackStart(_) => write!(f, "SMSG_ATTACKSTART"),
+            ServerEvent::AttackStop(_) => write!(f, "SMSG_ATTACKSTOP"),
+            ServerEvent::AttackSwingCantAttack(_) => write!(f, "SMSG_ATTACKSWING_CANT_ATTACK"),
+            ServerEvent::AttackSwingDeadTarget(_) => write!(f, "SMSG_ATTACKSWING_DEADTARGET"),
+            ServerEvent::AttackSwingNotInRange(_) => write!(f, "SMSG_ATTACKSWING_NOTINRANGE"),
+            ServerEvent::AttackerStateUpdate(_) => write!(f, "SMSG_ATTACKERSTATEUPDATE"),
+            ServerEvent::AuraUpdate(_) => write!(f, "SMSG_AURA_UPDATE"),
+            ServerEvent::AuraUpdateAll(_) => write!(f, "SMSG_AURA_UPDATE_ALL"),
             ServerEvent::BindPointUpdate(_) => write!(f, "SMSG_BINDPOINTUPDATE"),
+            ServerEvent::BuyFailed(_) => write!(f, "SMSG_BUY_FAILED"),
+            ServerEvent::BuyItem(_) => write!(f, "SMSG_BUY_ITEM"),
+            ServerEvent::CalendarCommandResult(_) => write!(f, "SMSG_CALENDAR_COMMAND_RESULT"),
+            ServerEvent::CalendarEventInvite(_) => write!(f, "SMSG_CALENDAR_EVENT_INVITE"),
+            ServerEvent::CalendarEventInviteAlert(_) => write!(f, "SMSG_CALENDAR_EVENT_INVITE_ALERT"),
+            ServerEvent::CalendarEventStatus(_) => write!(f, "SMSG_CALENDAR_EVENT_STATUS"),
+            ServerEvent::CalendarSendCalendar(_) => write!(f, "SMSG_CALENDAR_SEND_CALENDAR"),
+            ServerEvent::CalendarSendEvent(_) => write!(f, "SMSG_CALENDAR_SEND_EVENT"),
             ServerEvent::CalendarSendNumPending(_) => write!(f, "SMSG_CALENDAR_SEND_NUM_PENDING"),
+            ServerEvent::CastFailed(_) => write!(f, "SMSG_CAST_FAILED"),
+            ServerEvent::ChannelNotify(_) => write!(f, "SMSG_CHANNEL_NOTIFY"),
+            ServerEvent::ChannelUpdate(_) => write!(f, "MSG_CHANNEL_UPDATE"),
             ServerEvent::CharCreate(_) => write!(f, "SMSG_CHAR_CREATE"),
             ServerEvent::CharDelete(_) => write!(f, "SMSG_CHAR_DELETE"),
             ServerEvent::CharEnum(_) => write!(f, "SMSG_CHAR_ENUM"),
             ServerEvent::ContactList(_) => write!(f, "SMSG_CONTACT_LIST"),
+            ServerEvent::CreatureQueryResponse(_) => write!(f, "SMSG_CREATURE_QUERY_RESPONSE"),
             ServerEvent::DestroyObject(_) => write!(f, "SMSG_DESTROY_OBJECT"),
             ServerEvent::Disconnect => write!(f, "Disconnect"),
+            ServerEvent::EquipmentSetList(_) => write!(f, "SMSG_EQUIPMENT_SET_LIST"),
+            ServerEvent::EquipmentSetSaved(_) => write!(f, "SMSG_EQUIPMENT_SET_SAVED"),
+            ServerEvent::EquipmentSetUseResult(_) => write!(f, "SMSG_EQUIPMENT_SET_USE_RESULT"),
             ServerEvent::FeatureSystemStatus(_) => write!(f, "SMSG_FEATURE_SYSTEM_STATUS"),
             ServerEvent::ForceMoveRoot(_) => write!(f, "SMSG_FORCE_MOVE_ROOT"),
             ServerEvent::ForceMoveUnroot(_) => write!(f, "SMSG_FORCE_MOVE_UNROOT"),
             ServerEvent::ForceRunSpeedChange(_) => write!(f, "SMSG_FORCE_RUN_SPEED_CHANGE"),
             ServerEvent::ForceRunBackSpeedChange(_) => write!(f, "SMSG_FORCE_RUN_BACK_SPEED_CHANGE"),
+            ServerEvent::FriendStatus(_) => write!(f, "SMSG_FRIEND_STATUS"),
             ServerEvent::GMTicketGetTicket(_) => write!(f, "SMSG_GMTICKET_GETTICKET"),
             ServerEvent::GMTicketSystemStatus(_) => write!(f, "SMSG_GMTICKET_SYSTEMSTATUS"),
+            ServerEvent::GameobjectQueryResponse(_) => write!(f, "SMSG_GAMEOBJECT_QUERY_RESPONSE"),
+            ServerEvent::GossipComplete(_) => write!(f, "SMSG_GOSSIP_COMPLETE"),
+            ServerEvent::GossipMessage(_) => write!(f, "SMSG_GOSSIP_MESSAGE"),
+            ServerEvent::GossipPoi(_) => write!(f, "SMSG_GOSSIP_POI"),
+            ServerEvent::GroupDecline(_) => write!(f, "SMSG_GROUP_DECLINE"),
+            ServerEvent::GroupDestroyed(_) => write!(f, "SMSG_GROUP_DESTROYED"),
+            ServerEvent::GroupInvite(_) => write!(f, "SMSG_GROUP_INVITE"),
+            ServerEvent::GroupList(_) => write!(f, "SMSG_GROUP_LIST"),
+            ServerEvent::GroupSetLeader(_) => write!(f, "SMSG_GROUP_SET_LEADER"),
+            ServerEvent::GuildCommandResult(_) => write!(f, "SMSG_GUILD_COMMAND_RESULT"),
+            ServerEvent::GuildDecline(_) => write!(f, "SMSG_GUILD_DECLINE"),
+            ServerEvent::GuildEvent(_) => write!(f, "SMSG_GUILD_EVENT"),
+            ServerEvent::GuildInvite(_) => write!(f, "SMSG_GUILD_INVITE"),
+            ServerEvent::GuildQueryResponse(_) => write!(f, "SMSG_GUILD_QUERY_RESPONSE"),
+            ServerEvent::GuildRoster(_) => write!(f, "SMSG_GUILD_ROSTER"),
             ServerEvent::InitializeFactions(_) => write!(f, "SMSG_INITIALIZE_FACTIONS"),
             ServerEvent::InitialSpells(_) => write!(f, "SMSG_INITIAL_SPELLS"),
             ServerEvent::InitWorldStates(_) => write!(f, "SMSG_INIT_WORLD_STATES"),
+            ServerEvent::InventoryChangeFailure(_) => write!(f, "SMSG_INVENTORY_CHANGE_FAILURE"),
             ServerEvent::ItemNameQueryResponse(_) => write!(f, "SMSG_ITEM_NAME_QUERY_RESPONSE"),
+            ServerEvent::ItemPushResult(_) => write!(f, "SMSG_ITEM_PUSH_RESULT"),
             ServerEvent::ItemQuerySingleResponse(_) => write!(f, "SMSG_ITEM_QUERY_SINGLE_RESPONSE"),
+            ServerEvent::ListInventory(_) => write!(f, "SMSG_LIST_INVENTORY"),
             ServerEvent::LoginSetTimeSpeed(_) => write!(f, "SMSG_LOGIN_SETTIMESPEED"),
             ServerEvent::LoginVerifyWorld(_) => write!(f, "SMSG_LOGIN_VERIFY_WORLD"),
             ServerEvent::LogoutCancelAck(_) => write!(f, "SMSG_LOGOUT_CANCEL_ACK"),
             ServerEvent::LogoutComplete(_) => write!(f, "SMSG_LOGOUT_COMPLETE"),
             ServerEvent::LogoutResponse(_) => write!(f, "SMSG_LOGOUT_RESPONSE"),
+            ServerEvent::LootAllPassed(_) => write!(f, "SMSG_LOOT_ALL_PASSED"),
+            ServerEvent::LootClearMoney(_) => write!(f, "SMSG_LOOT_CLEAR_MONEY"),
+            ServerEvent::LootMoneyNotify(_) => write!(f, "SMSG_LOOT_MONEY_NOTIFY"),
+            ServerEvent::LootReleaseResponse(_) => write!(f, "SMSG_LOOT_RELEASE_RESPONSE"),
+            ServerEvent::LootRemoved(_) => write!(f, "SMSG_LOOT_REMOVED"),
+            ServerEvent::LootResponse(_) => write!(f, "SMSG_LOOT_RESPONSE"),
+            ServerEvent::LootRoll(_) => write!(f, "SMSG_LOOT_ROLL"),
+            ServerEvent::LootRollWon(_) => write!(f, "SMSG_LOOT_ROLL_WON"),
+            ServerEvent::LootStartRoll(_) => write!(f, "SMSG_LOOT_START_ROLL"),
+            ServerEvent::MailListResult(_) => write!(f, "SMSG_MAIL_LIST_RESULT"),
             ServerEvent::MessageChat(_) => write!(f, "SMSG_MESSAGECHAT"),
+            ServerEvent::MonsterMove(_) => write!(f, "SMSG_MONSTER_MOVE"),
+            ServerEvent::Motd(_) => write!(f, "SMSG_MOTD"),
+            ServerEvent::MoveFeatherFall(_) => write!(f, "SMSG_MOVE_FEATHER_FALL"),
+            ServerEvent::MoveKnockBack(_) => write!(f, "SMSG_MOVE_KNOCK_BACK"),
+            ServerEvent::MoveKnockBackBroadcast(_) => write!(f, "MSG_MOVE_KNOCK_BACK"),
+            ServerEvent::MoveLandWalk(_) => write!(f, "SMSG_MOVE_LAND_WALK"),
+            ServerEvent::MoveNormalFall(_) => write!(f, "SMSG_MOVE_NORMAL_FALL"),
+            ServerEvent::MoveSetCanFly(_) => write!(f, "SMSG_MOVE_SET_CAN_FLY"),
+            ServerEvent::MoveSetHover(_) => write!(f, "SMSG_MOVE_SET_HOVER"),
             ServerEvent::MoveTeleportAck(_) => write!(f, "MSG_MOVE_TELEPORT_ACK_Server"),
             ServerEvent::MoveStartForward(_) => write!(f, "MSG_MOVE_START_FORWARD"),
             ServerEvent::MoveStartBackward(_) => write!(f, "MSG_MOVE_START_BACKWARD"),
@@ -144,21 +304,54 @@ impl fmt::Display for ServerEvent {
             ServerEvent::MoveStopSwim(_) => write!(f, "MSG_MOVE_STOP_SWIM"),
             ServerEvent::MoveSetFacing(_) => write!(f, "MSG_MOVE_SET_FACING"),
             ServerEvent::MoveHeartbeat(_) => write!(f, "MSG_MOVE_HEARTBEAT"),
+            ServerEvent::MoveUnsetCanFly(_) => write!(f, "SMSG_MOVE_UNSET_CAN_FLY"),
+            ServerEvent::MoveUnsetHover(_) => write!(f, "SMSG_MOVE_UNSET_HOVER"),
+            ServerEvent::MoveWaterWalk(_) => write!(f, "SMSG_MOVE_WATER_WALK"),
             ServerEvent::NameQueryResponse(_) => write!(f, "SMSG_NAME_QUERY_RESPONSE"),
+            ServerEvent::NewTaxiPath(_) => write!(f, "SMSG_NEW_TAXI_PATH"),
             ServerEvent::NewWorld(_) => write!(f, "SMSG_NEW_WORLD"),
+            ServerEvent::Notification(_) => write!(f, "SMSG_NOTIFICATION"),
+            ServerEvent::PartyCommandResult(_) => write!(f, "SMSG_PARTY_COMMAND_RESULT"),
+            ServerEvent::PeriodicAuraLog(_) => write!(f, "SMSG_PERIODICAURALOG"),
             ServerEvent::PlayedTime(_) => write!(f, "SMSG_PLAYED_TIME"),
             ServerEvent::QueryTimeResponse(_) => write!(f, "SMSG_QUERY_TIME_RESPONSE"),
             ServerEvent::Pong(_) => write!(f, "SMSG_PONG"),
+            ServerEvent::QuestGiverOfferReward(_) => write!(f, "SMSG_QUESTGIVER_OFFER_REWARD"),
+            ServerEvent::QuestGiverQuestComplete(_) => write!(f, "SMSG_QUESTGIVER_QUEST_COMPLETE"),
+            ServerEvent::QuestGiverQuestDetails(_) => write!(f, "SMSG_QUESTGIVER_QUEST_DETAILS"),
+            ServerEvent::QuestGiverQuestInvalid(_) => write!(f, "SMSG_QUESTGIVER_QUEST_INVALID"),
+            ServerEvent::QuestLogFull(_) => write!(f, "SMSG_QUESTLOG_FULL"),
+            ServerEvent::QuestUpdateAddKill(_) => write!(f, "SMSG_QUESTUPDATE_ADD_KILL"),
+            ServerEvent::QuestUpdateComplete(_) => write!(f, "SMSG_QUESTUPDATE_COMPLETE"),
             ServerEvent::RaidInstanceInfo(_) => write!(f, "SMSG_RAID_INSTANCE_INFO"),
             ServerEvent::RealmSplit(_) => write!(f, "SMSG_REALM_SPLIT"),
+            ServerEvent::ReceivedMail(_) => write!(f, "SMSG_RECEIVED_MAIL"),
+            ServerEvent::RespondInspectAchievements(_) => write!(f, "SMSG_RESPOND_INSPECT_ACHIEVEMENTS"),
+            ServerEvent::SellItem(_) => write!(f, "SMSG_SELL_ITEM"),
+            ServerEvent::SendMailResult(_) => write!(f, "SMSG_SEND_MAIL_RESULT"),
             ServerEvent::SetDungeonDifficulty(_) => write!(f, "MSG_SET_DUNGEON_DIFFICULTY_Server"),
+            ServerEvent::SetFactionStanding(_) => write!(f, "SMSG_SET_FACTION_STANDING"),
+            ServerEvent::SetPhaseShift(_) => write!(f, "SMSG_SET_PHASE_SHIFT"),
+            ServerEvent::ShowTaxiNodes(_) => write!(f, "SMSG_SHOWTAXINODES"),
+            ServerEvent::SpellDelayed(_) => write!(f, "SMSG_SPELL_DELAYED"),
+            ServerEvent::SpellDispelLog(_) => write!(f, "SMSG_SPELLDISPELLOG"),
+            ServerEvent::SpellFailure(_) => write!(f, "SMSG_SPELL_FAILURE"),
+            ServerEvent::SpellGo(_) => write!(f, "SMSG_SPELL_GO"),
+            ServerEvent::SpellHealLog(_) => write!(f, "SMSG_SPELLHEALLOG"),
+            ServerEvent::SpellLogMiss(_) => write!(f, "SMSG_SPELLLOGMISS"),
+            ServerEvent::SpellNonMeleeDamageLog(_) => write!(f, "SMSG_SPELLNONMELEEDAMAGELOG"),
+            ServerEvent::SpellStart(_) => write!(f, "SMSG_SPELL_START"),
             ServerEvent::StandStateUpdate(_) => write!(f, "SMSG_STANDSTATE_UPDATE"),
+            ServerEvent::SummonRequest(_) => write!(f, "SMSG_SUMMON_REQUEST"),
+            ServerEvent::TaxiNodeStatus(_) => write!(f, "SMSG_TAXINODE_STATUS"),
             ServerEvent::TimeSyncReq(_) => write!(f, "SMSG_TIME_SYNC_REQ"),
             ServerEvent::TransferPending(_) => write!(f, "SMSG_TRANSFER_PENDING"),
             ServerEvent::TriggerCinematic(_) => write!(f, "SMSG_TRIGGER_CINEMATIC"),
             ServerEvent::TutorialFlags(_) => write!(f, "SMSG_TUTORIAL_FLAGS"),
             ServerEvent::UpdateAccountData(_) => write!(f, "SMSG_UPDATE_ACCOUNT_DATA"),
             ServerEvent::UpdateAccountDataComplete(_) => write!(f, "SMSG_UPDATE_ACCOUNT_DATA_COMPLETE"),
+            ServerEvent::UpdateComboPoints(_) => write!(f, "SMSG_UPDATE_COMBO_POINTS"),
+            ServerEvent::UpdateInstanceEncounterUnit(_) => write!(f, "SMSG_UPDATE_INSTANCE_ENCOUNTER_UNIT"),
             ServerEvent::UpdateObject(_) => write!(f, "SMSG_UPDATE_OBJECT"),
             ServerEvent::UpdateWorldState(_) => write!(f, "SMSG_UPDATE_WORLD_STATE"),
             ServerEvent::WorldStateUiTimerUpdate(_) => write!(f, "SMSG_WORLD_STATE_UI_TIMER_UPDATE"),
@@ -289,13 +482,3 @@ impl IntoServerEvent for MSG_MOVE_HEARTBEAT {
         ServerEvent::MoveHeartbeat(self)
     }
 }
-
-/// Event multiplexing between client (socket) and server (manager) sides.
-#[allow(clippy::large_enum_variant)]
-pub enum ConnectionEvent {
-    /// Incoming message from the client to the server
-    Client(ClientOpcodeMessage),
-
-    /// Outgoing message from the server to the client
-    Server(ServerEvent),
-}
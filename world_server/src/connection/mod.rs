@@ -5,17 +5,20 @@
 //!   the manager owns gameplay/session state so networking stays dumb and testable.
 //! - Uses a private `ServerEvent` channel so the manager can push outbound messages without
 //!   holding a mutable reference to the connection (improves isolation & concurrency).
-//! - Runs an event loop that races incoming client packets against manager-originated server
-//!   events to avoid head-of-line blocking (slow client writing does not delay server pushes).
+//! - Reads client packets on the connection task while a separate writer task batches, encrypts
+//!   and writes manager-originated server events, so heavy outbound load never stalls reading.
 //! - Performs only the authentication handshake locally (seed + `CMSG_AUTH_SESSION`) because
 //!   the handshake needs immediate access to cryptographic material bound to the transport.
 //! - Keeps encryption halves optional until auth succeeds, making the state transition explicit.
 
+mod compression;
 pub mod events;
+mod writer;
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use smol::net::TcpStream;
 use tracing::*;
 use wow_srp::wrath_header::ProofSeed;
@@ -29,8 +32,7 @@ use wow_world_messages::wrath::astd_expect_client_message;
 use wrath_auth_db::AuthDatabase;
 
 use crate::handlers::handle_cmsg_auth_session;
-use crate::packet::ServerMessageExt;
-use events::{ClientEvent, ConnectionEvent, ServerEvent};
+use events::{ClientEvent, ServerEvent};
 
 pub struct ConnectionData {
     pub account_id: Option<u32>,
@@ -111,109 +113,30 @@ impl Connection {
 
         let auth_session_packet = astd_expect_client_message::<CMSG_AUTH_SESSION, _>(&mut self.stream).await?;
 
-        let account_id = handle_cmsg_auth_session(self, proof_seed, &auth_session_packet, auth_db).await?;
+        let account = handle_cmsg_auth_session(self, proof_seed, &auth_session_packet, auth_db).await?;
 
         // Then, advertise the new connection to the client manager
         let addr = self.stream.peer_addr()?;
         let connection_event = ClientEvent::Connected {
             addr,
-            account_id,
+            account_id: account.account_id,
+            client_build: auth_session_packet.client_build,
+            locale: account.locale,
+            gm_level: account.gm_level,
             connection_sender: self.sender.clone(),
         };
         self.client_manager_sender.send_async(connection_event).await?;
 
-        // Then race between receiving from the client and receiving from the manager
-        loop {
-            let event = smol::future::race(
-                receive_from_client(&mut self.stream, self.decryption.as_mut().unwrap()),
-                receive_from_manager(&self.receiver),
-            )
-            .await?;
-
-            match event {
-                ConnectionEvent::Client(packet) => {
-                    info!("Handling packet {packet} from client {addr}");
-                    let client_message = ClientEvent::Message { addr, packet };
-                    self.client_manager_sender.send_async(client_message).await?;
-                }
-                ConnectionEvent::Server(server_event) => {
-                    info!("Sending {server_event} from server to client {addr}");
-                    match server_event {
-                        ServerEvent::AccountDataTimes(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::ActionButtons(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::BindPointUpdate(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::CalendarSendNumPending(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::CharCreate(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::CharDelete(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::CharEnum(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::ContactList(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::DestroyObject(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::FeatureSystemStatus(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::ForceMoveRoot(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::ForceMoveUnroot(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::ForceRunSpeedChange(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::ForceRunBackSpeedChange(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::GMTicketGetTicket(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::GMTicketSystemStatus(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::InitialSpells(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::InitializeFactions(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::InitWorldStates(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::ItemNameQueryResponse(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::ItemQuerySingleResponse(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::LoginVerifyWorld(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::LoginSetTimeSpeed(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::LogoutComplete(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::LogoutCancelAck(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::LogoutResponse(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MessageChat(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveFallLand(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveHeartbeat(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveJump(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveSetFacing(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveSetRunMode(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveSetWalkMode(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStartBackward(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStartForward(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStartPitchDown(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStartPitchUp(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStartStrafeLeft(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStartStrafeRight(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStop(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStartSwim(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStartTurnLeft(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStartTurnRight(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStopPitch(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStopStrafe(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStopSwim(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveStopTurn(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::MoveTeleportAck(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::NameQueryResponse(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::NewWorld(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::PlayedTime(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::WorldStateUiTimerUpdate(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::QueryTimeResponse(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::Pong(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::RaidInstanceInfo(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::RealmSplit(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::SetDungeonDifficulty(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::StandStateUpdate(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::TimeSyncReq(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::TransferPending(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::TriggerCinematic(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::TutorialFlags(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::UpdateAccountDataComplete(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::UpdateAccountData(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::UpdateObject(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::UpdateWorldState(m) => m.astd_send_to_connection(self).await?,
-                        ServerEvent::Disconnect => {
-                            break;
-                        }
-                    }
-                }
-            }
-        }
+        // Outbound events are written by their own task, this one only reads from the client
+        let encryption = self
+            .encryption
+            .take()
+            .ok_or_else(|| anyhow!("Connection has no encryption after authenticating"))?;
+        let writer = smol::spawn(writer::write_server_events(self.stream.clone(), encryption, self.receiver.clone(), addr));
+        let reader = read_client_packets(&mut self.stream, self.decryption.as_mut().unwrap(), &self.client_manager_sender, addr);
 
-        Ok(())
+        // Whichever side stops first ends the connection, the writer stops when the manager disconnects the client
+        smol::future::race(reader, writer).await
     }
 
     /// Send initial auth challenge; seeds later key derivation and establishes crypto context.
@@ -231,13 +154,16 @@ impl Connection {
     }
 }
 
-/// Read & decrypt the next client packet; minimal framing logic kept near the transport boundary.
-async fn receive_from_client(stream: &mut TcpStream, decrypter: &mut ServerDecrypterHalf) -> Result<ConnectionEvent> {
-    let packet = ClientOpcodeMessage::astd_read_encrypted(stream, decrypter).await?;
-    Ok(ConnectionEvent::Client(packet))
-}
-
-/// Await the next manager-originated server event for this connection.
-async fn receive_from_manager(receiver: &flume::Receiver<ServerEvent>) -> Result<ConnectionEvent> {
-    Ok(ConnectionEvent::Server(receiver.recv_async().await?))
+/// Read & decrypt client packets and hand them to the client manager until the client goes away.
+async fn read_client_packets(
+    stream: &mut TcpStream,
+    decrypter: &mut ServerDecrypterHalf,
+    client_manager_sender: &flume::Sender<ClientEvent>,
+    addr: SocketAddr,
+) -> Result<()> {
+    loop {
+        let packet = ClientOpcodeMessage::astd_read_encrypted(stream, decrypter).await?;
+        info!("Handling packet {packet} from client {addr}");
+        client_manager_sender.send_async(ClientEvent::Message { addr, packet }).await?;
+    }
 }
@@ -0,0 +1,237 @@
+//! Write side of a connection.
+//!
+//! Once the connection is authenticated a writer task owns the encryption half and a handle to the socket.
+//! It pulls every `ServerEvent` that is already queued, serializes and encrypts them into one buffer and
+//! writes that with a single call, so crowded areas cost one socket write per batch instead of one per
+//! packet and the read loop never waits on outbound traffic. Large update bursts are compressed on the
+//! way out, see `compression`.
+
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use smol::io::AsyncWriteExt;
+use smol::net::TcpStream;
+use tracing::*;
+use wow_srp::wrath_header::ServerEncrypterHalf;
+use wow_world_messages::wrath::ServerMessage;
+
+use super::compression::PacketCompression;
+use super::events::ServerEvent;
+
+//Upper bound on the events encrypted before the buffer is flushed to the socket
+const MAX_BATCH_EVENTS: usize = 64;
+
+/// Write queued server events to the client until the manager asks for a disconnect.
+pub async fn write_server_events(
+    mut stream: TcpStream,
+    mut encryption: ServerEncrypterHalf,
+    receiver: flume::Receiver<ServerEvent>,
+    addr: SocketAddr,
+) -> Result<()> {
+    let compression = PacketCompression::from_env();
+    let mut buffer = Vec::new();
+    let mut monster_moves = Vec::new();
+    loop {
+        let first_event = receiver.recv_async().await?;
+        let mut disconnect = false;
+        buffer.clear();
+
+        for server_event in std::iter::once(first_event).chain(receiver.try_iter().take(MAX_BATCH_EVENTS - 1)) {
+            if let ServerEvent::Disconnect = server_event {
+                disconnect = true;
+                break;
+            }
+            info!("Sending {server_event} from server to client {addr}");
+            //Consecutive monster moves may go out together as one compressed packet
+            if let ServerEvent::MonsterMove(m) = server_event {
+                monster_moves.push(m);
+                continue;
+            }
+            compression.write_monster_moves(&mut monster_moves, &mut buffer, &mut encryption).await?;
+            write_server_event(server_event, &compression, &mut buffer, &mut encryption).await?;
+        }
+        compression.write_monster_moves(&mut monster_moves, &mut buffer, &mut encryption).await?;
+
+        if !buffer.is_empty() {
+            stream.write_all(&buffer).await?;
+            stream.flush().await?;
+        }
+        if disconnect {
+            return Ok(());
+        }
+    }
+}
+
+/// Serialize and encrypt a single event into the batch buffer. Header encryption is a stream cipher, so
+/// events have to be encrypted in the order they are written.
+async fn write_server_event(
+    server_event: ServerEvent,
+    compression: &PacketCompression,
+    buffer: &mut Vec<u8>,
+    encryption: &mut ServerEncrypterHalf,
+) -> Result<()> {
+    match server_event {
+        ServerEvent::AccountDataTimes(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ActionButtons(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::AttackStart(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::AttackStop(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::AttackSwingCantAttack(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::AttackSwingDeadTarget(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::AttackSwingNotInRange(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::AttackerStateUpdate(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::AuraUpdate(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::AuraUpdateAll(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::BindPointUpdate(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::BuyFailed(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::BuyItem(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CalendarCommandResult(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CalendarEventInvite(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CalendarEventInviteAlert(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CalendarEventStatus(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CalendarSendCalendar(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CalendarSendEvent(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CalendarSendNumPending(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CastFailed(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ChannelNotify(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ChannelUpdate(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CharCreate(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CharDelete(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CharEnum(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ContactList(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::CreatureQueryResponse(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::DestroyObject(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::EquipmentSetList(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::EquipmentSetSaved(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::EquipmentSetUseResult(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::FeatureSystemStatus(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ForceMoveRoot(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ForceMoveUnroot(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ForceRunSpeedChange(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ForceRunBackSpeedChange(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::FriendStatus(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GMTicketGetTicket(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GMTicketSystemStatus(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GameobjectQueryResponse(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GossipComplete(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GossipMessage(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GossipPoi(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GroupDecline(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GroupDestroyed(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GroupInvite(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GroupList(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GroupSetLeader(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GuildCommandResult(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GuildDecline(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GuildEvent(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GuildInvite(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GuildQueryResponse(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::GuildRoster(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::InitialSpells(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::InitializeFactions(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::InitWorldStates(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::InventoryChangeFailure(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ItemNameQueryResponse(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ItemPushResult(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ItemQuerySingleResponse(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ListInventory(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LoginVerifyWorld(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LoginSetTimeSpeed(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LogoutComplete(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LogoutCancelAck(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LogoutResponse(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LootAllPassed(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LootClearMoney(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LootMoneyNotify(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LootReleaseResponse(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LootRemoved(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LootResponse(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LootRoll(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LootRollWon(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::LootStartRoll(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MailListResult(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MessageChat(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MonsterMove(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::Motd(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveFallLand(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveFeatherFall(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveHeartbeat(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveJump(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveKnockBack(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveKnockBackBroadcast(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveLandWalk(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveNormalFall(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveSetCanFly(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveSetFacing(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveSetHover(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveSetRunMode(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveSetWalkMode(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStartBackward(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStartForward(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStartPitchDown(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStartPitchUp(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStartStrafeLeft(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStartStrafeRight(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStop(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStartSwim(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStartTurnLeft(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStartTurnRight(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStopPitch(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStopStrafe(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStopSwim(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveStopTurn(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveTeleportAck(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveUnsetCanFly(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveUnsetHover(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::MoveWaterWalk(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::NameQueryResponse(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::NewTaxiPath(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::NewWorld(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::Notification(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::PartyCommandResult(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::PeriodicAuraLog(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::PlayedTime(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::QuestGiverOfferReward(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::QuestGiverQuestComplete(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::QuestGiverQuestDetails(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::QuestGiverQuestInvalid(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::QuestLogFull(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::QuestUpdateAddKill(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::QuestUpdateComplete(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ReceivedMail(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::RespondInspectAchievements(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SellItem(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SendMailResult(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SetFactionStanding(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SetPhaseShift(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::ShowTaxiNodes(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SpellDelayed(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SpellDispelLog(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SpellFailure(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SpellGo(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SpellHealLog(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SpellLogMiss(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SpellNonMeleeDamageLog(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SpellStart(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SummonRequest(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::TaxiNodeStatus(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::UpdateComboPoints(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::UpdateInstanceEncounterUnit(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::WorldStateUiTimerUpdate(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::QueryTimeResponse(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::Pong(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::RaidInstanceInfo(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::RealmSplit(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::SetDungeonDifficulty(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::StandStateUpdate(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::TimeSyncReq(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::TransferPending(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::TriggerCinematic(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::TutorialFlags(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::UpdateAccountDataComplete(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::UpdateAccountData(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::UpdateObject(m) => compression.write_update_object(m, buffer, encryption).await?,
+        ServerEvent::UpdateWorldState(m) => m.astd_write_encrypted_server(&mut *buffer, encryption).await?,
+        ServerEvent::Disconnect => {}
+    }
+    Ok(())
+}
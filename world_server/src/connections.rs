@@ -24,28 +24,39 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use smol::{net::TcpListener, stream::StreamExt};
-use tracing::error;
+use tracing::{error, warn};
 use wrath_auth_db::AuthDatabase;
+use wrath_common::{config, Health, SocketOptions};
 
 use crate::connection::{events::ClientEvent, Connection};
 
 /// Public entry point that launches the realm connection accept loop and
 /// centralizes error reporting.
-pub async fn accept_realm_connections(auth_db: Arc<AuthDatabase>, client_manager_sender: flume::Sender<ClientEvent>) {
-    if let Err(e) = accept_realm_connections_impl(auth_db, client_manager_sender).await {
+pub async fn accept_realm_connections(auth_db: Arc<AuthDatabase>, client_manager_sender: flume::Sender<ClientEvent>, health: Arc<Health>) {
+    if let Err(e) = accept_realm_connections_impl(auth_db, client_manager_sender, health).await {
         error!("Error in realm_socket::accept_realm_connections: {e:?}");
     }
 }
 
 /// Internal implementation of the accept loop.
-async fn accept_realm_connections_impl(auth_db: Arc<AuthDatabase>, client_manager_sender: flume::Sender<ClientEvent>) -> Result<()> {
-    let realm_id: i32 = std::env::var("REALM_ID")?.parse()?;
+async fn accept_realm_connections_impl(
+    auth_db: Arc<AuthDatabase>,
+    client_manager_sender: flume::Sender<ClientEvent>,
+    health: Arc<Health>,
+) -> Result<()> {
+    let realm_id: i32 = config::required("REALM_ID")?;
     let bind_ip = auth_db.get_realm_bind_ip(realm_id).await?;
     let tcp_listener = TcpListener::bind(bind_ip).await?;
+    health.pass_check("listener");
+    let socket_options = SocketOptions::from_env();
     let mut incoming_connections = tcp_listener.incoming();
 
     while let Some(tcp_stream) = incoming_connections.next().await {
-        let connection = Connection::new(tcp_stream?, client_manager_sender.clone());
+        let tcp_stream = tcp_stream?;
+        if let Err(e) = socket_options.apply(&tcp_stream) {
+            warn!("Could not apply socket options to connection: {e}");
+        }
+        let connection = Connection::new(tcp_stream, client_manager_sender.clone());
         smol::spawn(connection.run(auth_db.clone())).detach();
     }
 
@@ -3,13 +3,33 @@ use cmdparse::{parse, Parsable};
 use std::io::{self, BufRead};
 use std::sync::{atomic::AtomicBool, Arc};
 use tracing::{info, warn};
+use wrath_game_db::GameDatabase;
+use wrath_realm_db::RealmDatabase;
+
+use crate::data::{DataStorage, DataStorageReload, DATA_STORAGE_DB_TABLES};
 
 #[derive(Debug, PartialEq, Eq, Parsable)]
 enum WrathRealmConsoleCommand {
     Exit,
+    SetMotd(String),
+    EnableAutobroadcast,
+    DisableAutobroadcast,
+    Reload(ReloadTarget),
+}
+
+#[derive(Debug, PartialEq, Eq, Parsable)]
+enum ReloadTarget {
+    Dbc,
+    Db(String),
 }
 
-pub async fn process_console_commands(running_bool: Arc<AtomicBool>) -> Result<()> {
+pub async fn process_console_commands(
+    running_bool: Arc<AtomicBool>,
+    realm_db: Arc<RealmDatabase>,
+    game_db: Arc<GameDatabase>,
+    autobroadcast_enabled: Arc<AtomicBool>,
+    data_storage_sender: flume::Sender<DataStorageReload>,
+) -> Result<()> {
     let stdin = io::stdin();
     for line in stdin.lock().lines() {
         match line {
@@ -17,7 +37,15 @@ pub async fn process_console_commands(running_bool: Arc<AtomicBool>) -> Result<(
                 let cmd = parse::<_, WrathRealmConsoleCommand>(&string, ());
                 match cmd {
                     Ok(parsed_cmd) => {
-                        smol::spawn(handle_command(parsed_cmd, running_bool.clone())).detach();
+                        smol::spawn(handle_command(
+                            parsed_cmd,
+                            running_bool.clone(),
+                            realm_db.clone(),
+                            game_db.clone(),
+                            autobroadcast_enabled.clone(),
+                            data_storage_sender.clone(),
+                        ))
+                        .detach();
                     }
                     Err(e) => warn!("Could not parse command. {}", e),
                 }
@@ -28,9 +56,20 @@ pub async fn process_console_commands(running_bool: Arc<AtomicBool>) -> Result<(
     Ok(())
 }
 
-async fn handle_command(cmd: WrathRealmConsoleCommand, running_bool: Arc<AtomicBool>) -> Result<()> {
+async fn handle_command(
+    cmd: WrathRealmConsoleCommand,
+    running_bool: Arc<AtomicBool>,
+    realm_db: Arc<RealmDatabase>,
+    game_db: Arc<GameDatabase>,
+    autobroadcast_enabled: Arc<AtomicBool>,
+    data_storage_sender: flume::Sender<DataStorageReload>,
+) -> Result<()> {
     let result = match cmd {
         WrathRealmConsoleCommand::Exit => handle_exit(running_bool).await,
+        WrathRealmConsoleCommand::SetMotd(message) => handle_set_motd(&message, &realm_db).await,
+        WrathRealmConsoleCommand::EnableAutobroadcast => handle_set_autobroadcast(autobroadcast_enabled, true).await,
+        WrathRealmConsoleCommand::DisableAutobroadcast => handle_set_autobroadcast(autobroadcast_enabled, false).await,
+        WrathRealmConsoleCommand::Reload(target) => handle_reload(target, game_db, data_storage_sender).await,
     };
 
     if let Err(e) = result {
@@ -44,3 +83,39 @@ async fn handle_exit(running_bool: Arc<AtomicBool>) -> Result<()> {
     running_bool.store(false, std::sync::atomic::Ordering::Relaxed);
     Ok(())
 }
+
+async fn handle_set_motd(message: &str, realm_db: &RealmDatabase) -> Result<()> {
+    realm_db.set_motd(message).await?;
+    info!("Message of the day changed to: {}", message);
+    Ok(())
+}
+
+async fn handle_set_autobroadcast(autobroadcast_enabled: Arc<AtomicBool>, enabled: bool) -> Result<()> {
+    autobroadcast_enabled.store(enabled, std::sync::atomic::Ordering::Relaxed);
+    info!("Autobroadcast {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+//The new data is loaded here and handed to the main loop, which swaps it in between ticks.
+//Anything still holding the old DataStorage keeps working with it until it lets go.
+async fn handle_reload(target: ReloadTarget, game_db: Arc<GameDatabase>, data_storage_sender: flume::Sender<DataStorageReload>) -> Result<()> {
+    let reload = match target {
+        ReloadTarget::Dbc => {
+            info!("Reloading data storage");
+            DataStorageReload::Full(DataStorage::load_validated(game_db).await?)
+        }
+        ReloadTarget::Db(table) => {
+            let Some(store) = DataStorage::load_store(&table, game_db).await? else {
+                anyhow::bail!(
+                    "Table {} is not used by data storage, known tables: {}",
+                    table,
+                    DATA_STORAGE_DB_TABLES.join(", ")
+                );
+            };
+            info!("Reloaded the data storage store of table {}", table);
+            DataStorageReload::Store(store)
+        }
+    };
+    data_storage_sender.send_async(reload).await?;
+    Ok(())
+}
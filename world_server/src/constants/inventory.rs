@@ -2,8 +2,20 @@ use wow_world_messages::wrath::InventoryType;
 
 pub const EQUIPMENT_SLOTS_START: u8 = 0;
 pub const EQUIPMENT_SLOTS_END: u8 = 18;
-pub const _BAG_SLOTS_START: u8 = 19;
+pub const BAG_SLOTS_START: u8 = 19;
 pub const BAG_SLOTS_END: u8 = 22;
+//Keys and lockpicks only, they don't take up bag space
+pub const KEYRING_SLOTS_START: u8 = 86;
+pub const KEYRING_SLOTS_END: u8 = 117;
+//Hidden slots that hold the tokens shown in the currency tab, one stack per currency
+pub const CURRENCY_TOKEN_SLOTS_START: u8 = 118;
+pub const CURRENCY_TOKEN_SLOTS_END: u8 = 149;
+
+//item_template.flags, the item can't be destroyed by the player
+pub const ITEM_FLAG_NO_USER_DESTROY: u32 = 0x20;
+//item_template.bonding
+pub const BIND_QUEST_ITEM: u8 = 4;
+
 #[derive(Eq, PartialEq, Hash, Debug, Clone, Copy)]
 pub enum EquipmentSlot {
     Head = 0,
@@ -79,6 +91,7 @@ pub const fn get_compatible_equipment_slots_for_inventory_type(inventory_type: &
         InventoryType::Holdable => &[EquipmentSlot::Offhand],
         InventoryType::Ammo => &[],
         InventoryType::Thrown => &[EquipmentSlot::Ranged],
+        InventoryType::Quiver => &[EquipmentSlot::Bag1, EquipmentSlot::Bag2, EquipmentSlot::Bag3, EquipmentSlot::Bag4],
         InventoryType::RangedRight => &[EquipmentSlot::Ranged],
         InventoryType::Relic => &[EquipmentSlot::Ranged],
         _ => unimplemented!(),
@@ -70,6 +70,30 @@ pub enum UnitFlagIndex {
     //Unk31 = 0x80000000,
 }
 
+//UNIT_DYNAMIC_FLAGS, how the client draws the unit
+#[allow(dead_code)]
+pub enum UnitDynamicFlags {
+    Lootable = 0x0001,
+    TrackUnit = 0x0002,
+    Tapped = 0x0004,
+    TappedByPlayer = 0x0008,
+    SpecialInfo = 0x0010,
+    Dead = 0x0020,
+}
+
+//UNIT_NPC_FLAGS, what the client lets players do with a creature
+#[allow(dead_code)]
+pub enum UnitNpcFlags {
+    Gossip = 0x00000001,
+    QuestGiver = 0x00000002,
+    Trainer = 0x00000010,
+    Vendor = 0x00000080,
+    Repair = 0x00001000,
+    FlightMaster = 0x00002000,
+    Innkeeper = 0x00010000,
+    Banker = 0x00020000,
+}
+
 #[test]
 fn test_unit_flags_indices() {
     assert_eq!(1 << (UnitFlagIndex::Unk0 as usize), UnitFlags::Unk0 as usize);
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use wrath_game_db::{DBPlayerCreateInfo, GameDatabase};
+
+use super::{AreaTrigger, DataStorage};
+use crate::prelude::*;
+
+//Game data that handlers look up. The server answers from DataStorage and the game database,
+//unit tests use InMemoryDataProvider so handler logic can run without MySQL or DBC files.
+#[async_trait]
+pub trait DataProvider: Send + Sync {
+    fn get_area_trigger(&self, trigger_id: u32) -> Option<&AreaTrigger>;
+    fn is_instance_map(&self, map: u32) -> bool;
+    async fn has_item_template(&self, item_id: u32) -> Result<bool>;
+    async fn get_player_create_info(&self, race: u8, class: u8) -> Result<DBPlayerCreateInfo>;
+}
+
+pub struct GameDataProvider<'a> {
+    data_storage: &'a DataStorage,
+    game_db: &'a GameDatabase,
+}
+
+impl<'a> GameDataProvider<'a> {
+    pub fn new(data_storage: &'a DataStorage, game_db: &'a GameDatabase) -> Self {
+        Self { data_storage, game_db }
+    }
+}
+
+#[async_trait]
+impl DataProvider for GameDataProvider<'_> {
+    fn get_area_trigger(&self, trigger_id: u32) -> Option<&AreaTrigger> {
+        self.data_storage.get_area_trigger(trigger_id as i32)
+    }
+
+    fn is_instance_map(&self, map: u32) -> bool {
+        self.data_storage.is_instance_map(map)
+    }
+
+    async fn has_item_template(&self, item_id: u32) -> Result<bool> {
+        Ok(self
+            .game_db
+            .get_multiple_item_templates(&[item_id])
+            .await?
+            .iter()
+            .any(|template| template.id == item_id))
+    }
+
+    async fn get_player_create_info(&self, race: u8, class: u8) -> Result<DBPlayerCreateInfo> {
+        self.game_db.get_player_create_info(race, class).await
+    }
+}
+
+#[cfg(test)]
+#[derive(Default)]
+pub struct InMemoryDataProvider {
+    pub area_triggers: std::collections::HashMap<u32, AreaTrigger>,
+    pub instance_maps: std::collections::HashSet<u32>,
+    pub item_templates: std::collections::HashSet<u32>,
+    pub player_create_info: Vec<DBPlayerCreateInfo>,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl DataProvider for InMemoryDataProvider {
+    fn get_area_trigger(&self, trigger_id: u32) -> Option<&AreaTrigger> {
+        self.area_triggers.get(&trigger_id)
+    }
+
+    fn is_instance_map(&self, map: u32) -> bool {
+        self.instance_maps.contains(&map)
+    }
+
+    async fn has_item_template(&self, item_id: u32) -> Result<bool> {
+        Ok(self.item_templates.contains(&item_id))
+    }
+
+    async fn get_player_create_info(&self, race: u8, class: u8) -> Result<DBPlayerCreateInfo> {
+        self.player_create_info
+            .iter()
+            .find(|info| info.race == race && info.class == class)
+            .cloned()
+            .ok_or_else(|| anyhow!("No player create info for race {} and class {}", race, class))
+    }
+}
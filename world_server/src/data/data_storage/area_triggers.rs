@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use wow_dbc::{
@@ -29,6 +30,8 @@ pub enum AreaTriggerPurpose {
     Unknown,
 }
 
+pub type AreaTriggers = HashMap<AreaTriggerKey, AreaTrigger>;
+
 #[derive(Debug)]
 pub struct AreaTrigger {
     pub id: AreaTriggerKey,
@@ -41,7 +44,8 @@ pub struct AreaTrigger {
 }
 
 impl super::DataStorage {
-    pub(super) async fn load_area_triggers(&mut self, dbc_path: impl Into<&str>, game_db: Arc<GameDatabase>) -> Result<()> {
+    pub(super) async fn load_area_triggers(dbc_path: impl Into<&str>, game_db: Arc<GameDatabase>) -> Result<AreaTriggers> {
+        let mut area_triggers = AreaTriggers::new();
         let mut area_triggers_local: Option<wow_dbc::wrath_tables::area_trigger::AreaTrigger> = None;
         super::load_standard_dbc(dbc_path, &mut area_triggers_local).await?;
 
@@ -76,10 +80,10 @@ impl super::DataStorage {
                     purpose,
                 };
 
-                self.area_triggers.insert(areatrigger_final.id, areatrigger_final);
+                area_triggers.insert(areatrigger_final.id, areatrigger_final);
             }
         }
 
-        Ok(())
+        Ok(area_triggers)
     }
 }
@@ -1,19 +1,74 @@
+use crate::data::PositionAndOrientation;
 use crate::prelude::*;
 use smol::io::{AsyncReadExt, BufReader};
-use std::{path::PathBuf, sync::Arc};
-use wow_dbc::wrath_tables::{area_trigger::AreaTriggerKey, chr_classes::ChrClasses, chr_races::ChrRaces};
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
+use wow_dbc::wrath_tables::{
+    area_trigger::AreaTriggerKey, chr_classes::ChrClasses, chr_races::ChrRaces, currency_types::CurrencyTypes, faction::Faction,
+    gt_combat_ratings::GtCombatRatings, spell::Spell, spell_cast_times::SpellCastTimes, spell_duration::SpellDuration, spell_range::SpellRange,
+    spell_rune_cost::SpellRuneCost, taxi_nodes::TaxiNodes,
+};
+use wow_world_messages::wrath::Vector3d;
 use wrath_game_db::GameDatabase;
 
 mod area_triggers;
 pub use area_triggers::*;
 
-#[derive(Default)]
+mod start_outfits;
+pub use start_outfits::*;
+
+mod query_templates;
+pub use query_templates::*;
+
+mod server_strings;
+use server_strings::ServerStrings;
+
+mod validation;
+pub use validation::*;
+
+//Game database tables that DataStorage reads from, see DataStorage::load_store for the store each one belongs to
+pub const DATA_STORAGE_DB_TABLES: &[&str] = &[
+    "areatrigger_teleport",
+    "areatrigger_restedzones",
+    "server_string",
+    "creature_template",
+    "creature_template_locale",
+    "gameobject_template",
+    "gameobject_template_locale",
+    "item_template_locale",
+];
+
+//Copies share the DBC tables and the game database stores, so replacing one store only copies the handles
+#[derive(Default, Clone)]
 pub struct DataStorage {
-    dbc_chr_races: Option<ChrRaces>,
-    dbc_chr_classes: Option<ChrClasses>,
-    dbc_chr_map: Option<wow_dbc::wrath_tables::map::Map>,
-    dbc_char_start_outfit: Option<wow_dbc::wrath_tables::char_start_outfit::CharStartOutfit>,
-    area_triggers: std::collections::hash_map::HashMap<AreaTriggerKey, AreaTrigger>,
+    dbc_chr_races: Option<Arc<ChrRaces>>,
+    dbc_chr_classes: Option<Arc<ChrClasses>>,
+    dbc_chr_map: Option<Arc<wow_dbc::wrath_tables::map::Map>>,
+    dbc_faction: Option<Arc<Faction>>,
+    dbc_currency_types: Option<Arc<CurrencyTypes>>,
+    dbc_gt_combat_ratings: Option<Arc<GtCombatRatings>>,
+    dbc_taxi_nodes: Option<Arc<TaxiNodes>>,
+    dbc_spell: Option<Arc<Spell>>,
+    dbc_spell_cast_times: Option<Arc<SpellCastTimes>>,
+    dbc_spell_duration: Option<Arc<SpellDuration>>,
+    dbc_spell_range: Option<Arc<SpellRange>>,
+    dbc_spell_rune_cost: Option<Arc<SpellRuneCost>>,
+    start_outfits: StartOutfits,
+    area_triggers: Arc<AreaTriggers>,
+    server_strings: Arc<ServerStrings>,
+    query_templates: Arc<QueryTemplates>,
+}
+
+//One of the parts of DataStorage that come from the game database, `reload db <table>` replaces just the one its table belongs to
+pub enum DataStore {
+    AreaTriggers(AreaTriggers),
+    ServerStrings(ServerStrings),
+    QueryTemplates(QueryTemplates),
+}
+
+//What the main loop swaps in between ticks
+pub enum DataStorageReload {
+    Full(DataStorage),
+    Store(DataStore),
 }
 
 async fn load_standard_dbc<T: wow_dbc::DbcTable>(folder_path: impl Into<&str>, table: &mut Option<T>) -> Result<()> {
@@ -39,39 +94,180 @@ async fn load_standard_dbc<T: wow_dbc::DbcTable>(folder_path: impl Into<&str>, t
     Ok(())
 }
 
+async fn load_shared_dbc<T: wow_dbc::DbcTable>(folder_path: &str, table: &mut Option<Arc<T>>) -> Result<()> {
+    let mut loaded = None;
+    load_standard_dbc(folder_path, &mut loaded).await?;
+    *table = loaded.map(Arc::new);
+    Ok(())
+}
+
 macro_rules! define_dbc_getter {
     ($typename:path,$propname:ident,$fnname:ident) => {
         pub fn $fnname(&self) -> Result<&$typename> {
             self.$propname
-                .as_ref()
+                .as_deref()
                 .ok_or_else(|| anyhow::anyhow!("DBC {} is not loaded yet", stringify!($typename)))
         }
     };
 }
 
 impl DataStorage {
+    //Builds a complete new DataStorage and logs any validation issues, used at startup and for every reload
+    pub async fn load_validated(game_db: Arc<GameDatabase>) -> Result<Self> {
+        let mut data_storage = DataStorage::default();
+        data_storage.load(game_db.clone()).await?;
+        data_storage.validate(&game_db).await?.log();
+        Ok(data_storage)
+    }
+
     pub async fn load(&mut self, game_db: Arc<GameDatabase>) -> Result<()> {
         let dbc_path = &*std::env::var("DBC_FOLDER_PATH")?;
         info!("Loading DBC files from folder: {}", dbc_path);
-        load_standard_dbc(dbc_path, &mut self.dbc_chr_races).await?;
-        load_standard_dbc(dbc_path, &mut self.dbc_chr_classes).await?;
-        load_standard_dbc(dbc_path, &mut self.dbc_chr_map).await?;
-        load_standard_dbc(dbc_path, &mut self.dbc_char_start_outfit).await?;
-        self.load_area_triggers(dbc_path, game_db).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_chr_races).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_chr_classes).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_chr_map).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_faction).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_currency_types).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_gt_combat_ratings).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_taxi_nodes).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_spell).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_spell_cast_times).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_spell_duration).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_spell_range).await?;
+        load_shared_dbc(dbc_path, &mut self.dbc_spell_rune_cost).await?;
+        self.load_start_outfits(dbc_path).await?;
+        self.area_triggers = Arc::new(Self::load_area_triggers(dbc_path, game_db.clone()).await?);
         info!("Finished loading DBC files");
         info!("Loading SQL data");
+        info!("Loading server strings");
+        self.server_strings = Arc::new(Self::load_server_strings(game_db.clone()).await?);
+        info!("Loading creature and gameobject templates");
+        self.query_templates = Arc::new(Self::load_query_templates(game_db).await?);
         info!("Loading item templates");
         Ok(())
     }
 
+    //Loads the store the table belongs to, None for tables DataStorage doesn't read
+    pub async fn load_store(table: &str, game_db: Arc<GameDatabase>) -> Result<Option<DataStore>> {
+        Ok(Some(match table {
+            "areatrigger_teleport" | "areatrigger_restedzones" => {
+                //Area triggers are the DBC rows combined with both tables
+                let dbc_path = &*std::env::var("DBC_FOLDER_PATH")?;
+                DataStore::AreaTriggers(Self::load_area_triggers(dbc_path, game_db).await?)
+            }
+            "server_string" => DataStore::ServerStrings(Self::load_server_strings(game_db).await?),
+            "creature_template" | "creature_template_locale" | "gameobject_template" | "gameobject_template_locale" | "item_template_locale" => {
+                DataStore::QueryTemplates(Self::load_query_templates(game_db).await?)
+            }
+            _ => return Ok(None),
+        }))
+    }
+
+    pub fn replace_store(&mut self, store: DataStore) {
+        match store {
+            DataStore::AreaTriggers(area_triggers) => self.area_triggers = Arc::new(area_triggers),
+            DataStore::ServerStrings(server_strings) => self.server_strings = Arc::new(server_strings),
+            DataStore::QueryTemplates(query_templates) => self.query_templates = Arc::new(query_templates),
+        }
+    }
+
     define_dbc_getter!(ChrRaces, dbc_chr_races, get_dbc_chr_races);
     define_dbc_getter!(ChrClasses, dbc_chr_classes, get_dbc_chr_classes);
     define_dbc_getter!(wow_dbc::wrath_tables::map::Map, dbc_chr_map, get_dbc_chr_map);
-    define_dbc_getter!(
-        wow_dbc::wrath_tables::char_start_outfit::CharStartOutfit,
-        dbc_char_start_outfit,
-        get_dbc_char_start_outfit
-    );
+    define_dbc_getter!(Spell, dbc_spell, get_dbc_spell);
+    define_dbc_getter!(SpellCastTimes, dbc_spell_cast_times, get_dbc_spell_cast_times);
+    define_dbc_getter!(SpellDuration, dbc_spell_duration, get_dbc_spell_duration);
+    define_dbc_getter!(SpellRange, dbc_spell_range, get_dbc_spell_range);
+    define_dbc_getter!(SpellRuneCost, dbc_spell_rune_cost, get_dbc_spell_rune_cost);
+
+    //gtCombatRatings has no keys, it's 100 levels worth of values for every rating laid out after each other
+    pub fn get_combat_rating_per_percent(&self, rating: u32, level: u32) -> Option<f32> {
+        let index = rating * 100 + level.clamp(1, 100) - 1;
+        let table = self.dbc_gt_combat_ratings.as_ref()?;
+        table.rows().get(index as usize).map(|row| row.data)
+    }
+
+    pub fn get_taxi_node_ids(&self) -> Vec<u32> {
+        self.dbc_taxi_nodes
+            .iter()
+            .flat_map(|table| table.rows())
+            .map(|node| node.id.id as u32)
+            .collect()
+    }
+
+    //Flight masters stand on top of their node, so the closest node on the map within range is theirs
+    pub fn get_nearest_taxi_node(&self, map: u32, position: Vector3d, max_distance: f32) -> Option<u32> {
+        let distance = |node_position: [f32; 3]| {
+            ((node_position[0] - position.x).powi(2) + (node_position[1] - position.y).powi(2) + (node_position[2] - position.z).powi(2)).sqrt()
+        };
+
+        self.dbc_taxi_nodes
+            .iter()
+            .flat_map(|table| table.rows())
+            .filter(|node| node.continent_id.id as u32 == map)
+            .map(|node| (node.id.id as u32, distance(node.pos)))
+            .filter(|&(_, node_distance)| node_distance <= max_distance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)
+    }
+
+    pub fn get_map_ids(&self) -> Vec<u32> {
+        self.dbc_chr_map
+            .iter()
+            .flat_map(|table| table.rows())
+            .map(|map| map.id.id as u32)
+            .collect()
+    }
+
+    //Dungeons, raids, battlegrounds and arenas, everything that isn't one shared copy for the whole realm
+    pub fn is_instance_map(&self, map: u32) -> bool {
+        self.dbc_chr_map
+            .iter()
+            .flat_map(|table| table.rows())
+            .any(|row| row.id.id as u32 == map && row.instance_type != 0)
+    }
+
+    pub fn get_instance_map_ids(&self) -> HashSet<u32> {
+        self.dbc_chr_map
+            .iter()
+            .flat_map(|table| table.rows())
+            .filter(|row| row.instance_type != 0)
+            .map(|row| row.id.id as u32)
+            .collect()
+    }
+
+    //Where the faction is in the client's reputation list, None for factions nobody can have reputation with
+    pub fn get_reputation_index(&self, faction: u32) -> Option<u32> {
+        self.dbc_faction
+            .iter()
+            .flat_map(|table| table.rows())
+            .find(|row| row.id.id as u32 == faction)
+            .and_then(|row| u32::try_from(row.reputation_index).ok())
+    }
+
+    //Which bit of the known currencies field the currency item has, None for items that aren't a currency
+    pub fn get_currency_bit_index(&self, item_id: u32) -> Option<u32> {
+        self.dbc_currency_types
+            .iter()
+            .flat_map(|table| table.rows())
+            .find(|row| row.item_id as u32 == item_id)
+            .and_then(|row| u32::try_from(row.bit_index).ok())
+    }
+
+    //Where the first area trigger that teleports onto the map drops characters off, Map.dbc itself has no coordinates
+    pub fn get_map_entrance(&self, map: u32) -> Option<PositionAndOrientation> {
+        self.area_triggers.values().find_map(|area_trigger| match &area_trigger.purpose {
+            AreaTriggerPurpose::Teleport(teleport) if teleport.target_map as u32 == map => Some(PositionAndOrientation {
+                position: Vector3d {
+                    x: teleport.target_position_x,
+                    y: teleport.target_position_y,
+                    z: teleport.target_position_z,
+                },
+                orientation: teleport.target_orientation,
+            }),
+            _ => None,
+        })
+    }
 
     //Area triggers need special treatment from joint DBC and Mysql data sources, so they don't use
     //forward_dbc_getter
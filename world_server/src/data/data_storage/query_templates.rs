@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use wrath_game_db::{DBCreatureTemplate, DBGameObjectTemplate, GameDatabase};
+
+use crate::localization::ClientLocale;
+use crate::prelude::*;
+
+//Localized name and subname/cast bar caption of a template
+#[derive(Debug)]
+pub struct LocalizedTemplateText {
+    pub name: String,
+    pub secondary: String,
+}
+
+//Creature and gameobject templates are kept in memory, clients query them every time something new comes into view
+#[derive(Default)]
+pub struct QueryTemplates {
+    creature_templates: HashMap<u32, DBCreatureTemplate>,
+    creature_template_locales: HashMap<(u32, ClientLocale), LocalizedTemplateText>,
+    gameobject_templates: HashMap<u32, DBGameObjectTemplate>,
+    gameobject_template_locales: HashMap<(u32, ClientLocale), LocalizedTemplateText>,
+    item_template_locales: HashMap<(u32, ClientLocale), LocalizedTemplateText>,
+}
+
+impl super::DataStorage {
+    pub(super) async fn load_query_templates(game_db: Arc<GameDatabase>) -> Result<QueryTemplates> {
+        let mut templates = QueryTemplates::default();
+        templates.creature_templates = game_db
+            .get_all_creature_templates()
+            .await?
+            .into_iter()
+            .map(|template| (template.entry, template))
+            .collect();
+        for row in game_db.get_all_creature_template_locales().await? {
+            if let Some(locale) = supported_locale(&row.locale) {
+                let text = LocalizedTemplateText {
+                    name: row.name,
+                    secondary: row.subname,
+                };
+                templates.creature_template_locales.insert((row.entry, locale), text);
+            }
+        }
+
+        templates.gameobject_templates = game_db
+            .get_all_gameobject_templates()
+            .await?
+            .into_iter()
+            .map(|template| (template.entry, template))
+            .collect();
+        for row in game_db.get_all_gameobject_template_locales().await? {
+            if let Some(locale) = supported_locale(&row.locale) {
+                let text = LocalizedTemplateText {
+                    name: row.name,
+                    secondary: row.cast_bar_caption,
+                };
+                templates.gameobject_template_locales.insert((row.entry, locale), text);
+            }
+        }
+
+        //Item templates themselves come with wow_items, only their translations are in the database
+        for row in game_db.get_all_item_template_locales().await? {
+            if let Some(locale) = supported_locale(&row.locale) {
+                let text = LocalizedTemplateText {
+                    name: row.name,
+                    secondary: row.description,
+                };
+                templates.item_template_locales.insert((row.entry, locale), text);
+            }
+        }
+
+        info!(
+            "Loaded {} creature and {} gameobject templates",
+            templates.creature_templates.len(),
+            templates.gameobject_templates.len()
+        );
+        Ok(templates)
+    }
+
+    pub fn get_creature_template(&self, entry: u32) -> Option<&DBCreatureTemplate> {
+        self.query_templates.creature_templates.get(&entry)
+    }
+
+    pub fn get_gameobject_template(&self, entry: u32) -> Option<&DBGameObjectTemplate> {
+        self.query_templates.gameobject_templates.get(&entry)
+    }
+
+    //Returns the name and subname in the client's language, or the template's English text if there is no translation
+    pub fn get_localized_creature_text<'a>(&'a self, template: &'a DBCreatureTemplate, locale: ClientLocale) -> (&'a str, &'a str) {
+        match self.query_templates.creature_template_locales.get(&(template.entry, locale)) {
+            Some(text) => (&text.name, &text.secondary),
+            None => (&template.name, &template.subname),
+        }
+    }
+
+    pub fn get_localized_gameobject_text<'a>(&'a self, template: &'a DBGameObjectTemplate, locale: ClientLocale) -> (&'a str, &'a str) {
+        match self.query_templates.gameobject_template_locales.get(&(template.entry, locale)) {
+            Some(text) => (&text.name, &text.secondary),
+            None => (&template.name, &template.cast_bar_caption),
+        }
+    }
+
+    //Returns the translated name and description of an item, None means the English text should be used
+    pub fn get_localized_item_text(&self, entry: u32, locale: ClientLocale) -> Option<&LocalizedTemplateText> {
+        self.query_templates.item_template_locales.get(&(entry, locale))
+    }
+}
+
+fn supported_locale(code: &str) -> Option<ClientLocale> {
+    let locale = ClientLocale::from_code(code);
+    if locale.code() != code {
+        warn!("Template translation has unsupported locale {}", code);
+        return None;
+    }
+    Some(locale)
+}
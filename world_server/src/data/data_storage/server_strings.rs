@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use wrath_game_db::GameDatabase;
+
+use crate::localization::{format_server_string, ClientLocale, ServerString};
+use crate::prelude::*;
+
+pub type ServerStrings = HashMap<(u32, ClientLocale), String>;
+
+impl super::DataStorage {
+    pub(super) async fn load_server_strings(game_db: Arc<GameDatabase>) -> Result<ServerStrings> {
+        let mut server_strings = ServerStrings::new();
+        for row in game_db.get_all_server_strings().await? {
+            let locale = ClientLocale::from_code(&row.locale);
+            if locale.code() != row.locale {
+                warn!("Server string {} has unsupported locale {}", row.entry, row.locale);
+                continue;
+            }
+            server_strings.insert((row.entry, locale), row.content);
+        }
+        Ok(server_strings)
+    }
+
+    //Falls back to the enUS row and then to the built-in English text
+    pub fn get_server_string(&self, string: ServerString, locale: ClientLocale) -> &str {
+        let entry = string as u32;
+        self.server_strings
+            .get(&(entry, locale))
+            .or_else(|| self.server_strings.get(&(entry, ClientLocale::EnUs)))
+            .map_or(string.default_text(), |text| text.as_str())
+    }
+
+    pub fn localize(&self, locale: ClientLocale, string: ServerString, args: &[&dyn Display]) -> String {
+        format_server_string(self.get_server_string(string, locale), args)
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use wow_dbc::{wrath_tables::char_start_outfit::CharStartOutfit, DbcTable};
+use wow_world_messages::wrath::{Class, Gender, InventoryType, Race};
+
+use crate::character::character_inventory::{SimpleCharacterInventory, SimpleItemDescription};
+use crate::constants::inventory::BagSlot;
+use crate::prelude::*;
+
+//Race, class and gender, in the form CharStartOutfit.dbc stores them
+type StartOutfitKey = (u8, u8, u8);
+pub(super) type StartOutfits = HashMap<StartOutfitKey, Vec<StartOutfitItem>>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct StartOutfitItem {
+    pub item_id: u32,
+    pub slot_id: u8,
+}
+
+impl super::DataStorage {
+    pub(super) async fn load_start_outfits(&mut self, dbc_path: impl Into<&str>) -> Result<()> {
+        let mut start_outfits_local: Option<CharStartOutfit> = None;
+        super::load_standard_dbc(dbc_path, &mut start_outfits_local).await?;
+
+        let Some(start_outfits_local) = start_outfits_local else {
+            return Ok(());
+        };
+
+        for row in start_outfits_local.rows().iter() {
+            let key: StartOutfitKey = (row.race_id.id as u8, row.class_id.id as u8, row.sex_id as u8);
+            let items = assign_start_outfit_slots(row.item_id.iter().zip(row.inventory_type.iter()));
+            if self.start_outfits.insert(key, items).is_some() {
+                warn!("Duplicate start outfit for race {}, class {}, gender {}", key.0, key.1, key.2);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_start_outfit(&self, race: Race, class: Class, gender: Gender) -> Option<&[StartOutfitItem]> {
+        self.start_outfits
+            .get(&(race.as_int(), class.as_int(), gender.as_int()))
+            .map(|items| items.as_slice())
+    }
+}
+
+//Equippable items go into the first free compatible equipment slot, everything else
+//(hearthstone, food, ammo, items that don't fit anymore) goes into the backpack
+fn assign_start_outfit_slots<'a>(items: impl Iterator<Item = (&'a i32, &'a i32)>) -> Vec<StartOutfitItem> {
+    let mut equipment = SimpleCharacterInventory::new();
+    let mut backpack_slots = (BagSlot::Item1 as u8)..=(BagSlot::Item16 as u8);
+    let mut result = vec![];
+
+    for (&item_id, &inventory_type) in items {
+        if item_id <= 0 {
+            continue;
+        }
+
+        let inventory_type = InventoryType::try_from(inventory_type as u8).unwrap_or(InventoryType::NonEquip);
+        let description = SimpleItemDescription {
+            item_id: item_id as u32,
+            inventory_type,
+        };
+
+        let slot_id = match equipment.try_insert_item(description) {
+            Ok(equipment_slot) => Some(equipment_slot as u8),
+            Err(_) => backpack_slots.next(),
+        };
+
+        match slot_id {
+            Some(slot_id) => result.push(StartOutfitItem {
+                item_id: item_id as u32,
+                slot_id,
+            }),
+            None => warn!("No room left in the backpack for start item {}", item_id),
+        }
+    }
+    result
+}
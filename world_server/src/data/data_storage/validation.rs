@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use wow_dbc::DbcTable;
+use wrath_game_db::GameDatabase;
+
+use super::AreaTriggerPurpose;
+use crate::prelude::*;
+
+#[derive(Debug)]
+pub enum DataValidationIssue {
+    AreaTriggerUnknownMap { area_trigger_id: i32, map_id: i32 },
+    TeleportTargetUnknownMap { area_trigger_id: i32, target_map: u16 },
+    StartOutfitUnknownItem { item_id: u32 },
+    ItemMissingDisplayId { item_id: u32 },
+}
+
+impl DataValidationIssue {
+    fn category(&self) -> &'static str {
+        match self {
+            DataValidationIssue::AreaTriggerUnknownMap { .. } => "area triggers on unknown maps",
+            DataValidationIssue::TeleportTargetUnknownMap { .. } => "teleports to unknown maps",
+            DataValidationIssue::StartOutfitUnknownItem { .. } => "start outfit items missing from item_template",
+            DataValidationIssue::ItemMissingDisplayId { .. } => "start outfit items without display id",
+        }
+    }
+}
+
+impl Display for DataValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataValidationIssue::AreaTriggerUnknownMap { area_trigger_id, map_id } => {
+                write!(f, "Area trigger {} is on map {} which is not in Map.dbc", area_trigger_id, map_id)
+            }
+            DataValidationIssue::TeleportTargetUnknownMap { area_trigger_id, target_map } => write!(
+                f,
+                "Area trigger {} teleports to map {} which is not in Map.dbc",
+                area_trigger_id, target_map
+            ),
+            DataValidationIssue::StartOutfitUnknownItem { item_id } => write!(f, "Start outfit item {} has no item_template row", item_id),
+            DataValidationIssue::ItemMissingDisplayId { item_id } => write!(f, "Item {} has display id 0", item_id),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DataValidationReport {
+    pub issues: Vec<DataValidationIssue>,
+}
+
+impl DataValidationReport {
+    pub fn log(&self) {
+        if self.issues.is_empty() {
+            info!("Data validation found no problems");
+            return;
+        }
+
+        warn!("Data validation found {} problems", self.issues.len());
+        let mut categories: Vec<&'static str> = vec![];
+        for issue in &self.issues {
+            if !categories.contains(&issue.category()) {
+                categories.push(issue.category());
+            }
+        }
+        for category in categories {
+            let issues: Vec<&DataValidationIssue> = self.issues.iter().filter(|issue| issue.category() == category).collect();
+            warn!("  {} {}:", issues.len(), category);
+            for issue in issues {
+                warn!("    {}", issue);
+            }
+        }
+    }
+}
+
+impl super::DataStorage {
+    //Cross-checks IDs that one data source references in another, so that missing rows show up at startup
+    //instead of as a failure the first time a player runs into them
+    pub async fn validate(&self, game_db: &GameDatabase) -> Result<DataValidationReport> {
+        let mut report = DataValidationReport::default();
+
+        let map_ids: HashSet<i32> = self.get_dbc_chr_map()?.rows().iter().map(|map| map.id.id).collect();
+        let mut area_triggers: Vec<_> = self.area_triggers.values().collect();
+        area_triggers.sort_by_key(|area_trigger| area_trigger.id.id);
+        for area_trigger in area_triggers {
+            if !map_ids.contains(&area_trigger.map_id.id) {
+                report.issues.push(DataValidationIssue::AreaTriggerUnknownMap {
+                    area_trigger_id: area_trigger.id.id,
+                    map_id: area_trigger.map_id.id,
+                });
+            }
+            if let AreaTriggerPurpose::Teleport(teleport) = &area_trigger.purpose {
+                if !map_ids.contains(&(teleport.target_map as i32)) {
+                    report.issues.push(DataValidationIssue::TeleportTargetUnknownMap {
+                        area_trigger_id: area_trigger.id.id,
+                        target_map: teleport.target_map,
+                    });
+                }
+            }
+        }
+
+        let mut start_item_ids: Vec<u32> = self.start_outfits.values().flatten().map(|item| item.item_id).collect();
+        start_item_ids.sort_unstable();
+        start_item_ids.dedup();
+        let item_templates = game_db.get_multiple_item_templates(&start_item_ids).await?;
+        for item_id in start_item_ids {
+            match item_templates.iter().find(|template| template.id == item_id) {
+                None => report.issues.push(DataValidationIssue::StartOutfitUnknownItem { item_id }),
+                Some(template) if template.displayid == 0 => report.issues.push(DataValidationIssue::ItemMissingDisplayId { item_id }),
+                Some(_) => {}
+            }
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use wrath_game_db::GameDatabase;
+
+use super::{DataStorage, DataStorageReload, DATA_STORAGE_DB_TABLES};
+use crate::prelude::*;
+
+pub struct HotReloadConfig {
+    interval: Duration,
+}
+
+impl HotReloadConfig {
+    pub fn from_env() -> Option<Self> {
+        let interval_seconds = std::env::var("DATA_HOT_RELOAD_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|&v| v > 0)?;
+        Some(Self {
+            interval: Duration::from_secs(interval_seconds),
+        })
+    }
+}
+
+//Polls the last write time MySQL keeps for every table and rebuilds DataStorage when one of the tables
+//it caches changed. The new DataStorage goes through the same channel as the `reload` console command.
+//item_template isn't cached, item queries read it straight from the database so edits show up right away.
+pub async fn watch_game_database(config: HotReloadConfig, game_db: Arc<GameDatabase>, data_storage_sender: flume::Sender<DataStorageReload>) {
+    info!("Watching the game database for changes every {} seconds", config.interval.as_secs());
+    let mut last_update_times = match get_watched_update_times(&game_db).await {
+        Ok(update_times) => update_times,
+        Err(e) => {
+            error!("Could not read game database table update times, hot reloading is disabled: {}", e);
+            return;
+        }
+    };
+
+    loop {
+        async_io::Timer::after(config.interval).await;
+
+        let update_times = match get_watched_update_times(&game_db).await {
+            Ok(update_times) => update_times,
+            Err(e) => {
+                warn!("Could not read game database table update times: {}", e);
+                continue;
+            }
+        };
+        let mut changed_tables: Vec<&str> = update_times
+            .iter()
+            .filter(|&(table, time)| last_update_times.get(table) != Some(time))
+            .map(|(table, _)| table.as_str())
+            .collect();
+        if changed_tables.is_empty() {
+            continue;
+        }
+        changed_tables.sort_unstable();
+        info!("Game database tables changed ({}), reloading data storage", changed_tables.join(", "));
+
+        match DataStorage::load_validated(game_db.clone()).await {
+            Ok(data_storage) => {
+                if data_storage_sender.send_async(DataStorageReload::Full(data_storage)).await.is_err() {
+                    return;
+                }
+            }
+            //The old times are kept, so the reload is tried again on the next poll
+            Err(e) => {
+                warn!("Failed to reload data storage: {}", e);
+                continue;
+            }
+        }
+        last_update_times = update_times;
+    }
+}
+
+async fn get_watched_update_times(game_db: &GameDatabase) -> Result<HashMap<String, Option<u64>>> {
+    Ok(game_db
+        .get_table_update_times()
+        .await?
+        .into_iter()
+        .filter(|row| DATA_STORAGE_DB_TABLES.contains(&row.table_name.as_str()))
+        .map(|row| (row.table_name, row.update_time))
+        .collect())
+}
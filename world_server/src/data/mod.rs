@@ -7,6 +7,12 @@ pub use action_bar::ActionBar;
 mod data_storage;
 pub use data_storage::*;
 
+mod hot_reload;
+pub use hot_reload::*;
+
+mod data_provider;
+pub use data_provider::*;
+
 use wow_world_messages::wrath::{Area, Map, Vector3d};
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct WorldZoneLocation {
@@ -0,0 +1,105 @@
+//! Errors at the boundary between the connection loop and the packet handlers.
+//!
+//! Handlers use anyhow internally and return these typed errors where the cause is known. The packet
+//! handler sorts whatever comes back into a `HandlerError`, and the client manager decides on that:
+//! a client sending garbage gets disconnected, database trouble and server bugs are logged and the
+//! client keeps playing.
+
+use wow_world_messages::Guid;
+
+use crate::rate_limiter::OpcodeClass;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProtocolError {
+    #[error("Client sent a packet before authenticating")]
+    NotAuthenticated,
+    #[error("Client sent a world packet without a character in the world")]
+    NoActiveCharacter,
+    #[error("Client sent an invalid packet: {0}")]
+    InvalidPacket(String),
+    #[error("Client kept sending {0} packets over its rate limit")]
+    Flooding(OpcodeClass),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DatabaseError {
+    #[error("Database query failed: {0}")]
+    Query(anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GameLogicError {
+    #[error("Character with guid {0} not found in character manager")]
+    CharacterNotFound(Guid),
+    #[error("Not a valid map: {0}")]
+    InvalidMap(u32),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HandlerError {
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+    #[error(transparent)]
+    GameLogic(#[from] GameLogicError),
+}
+
+impl HandlerError {
+    pub fn disconnects_client(&self) -> bool {
+        matches!(self, HandlerError::Protocol(_))
+    }
+}
+
+//Errors without a type of their own count as server bugs, unless sqlx is somewhere in their chain
+impl From<anyhow::Error> for HandlerError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<ProtocolError>() {
+            Ok(protocol_error) => return protocol_error.into(),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<DatabaseError>() {
+            Ok(database_error) => return database_error.into(),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<GameLogicError>() {
+            Ok(game_logic_error) => return game_logic_error.into(),
+            Err(error) => error,
+        };
+        if error.chain().any(|cause| cause.is::<sqlx::Error>()) {
+            return DatabaseError::Query(error).into();
+        }
+        GameLogicError::Other(error).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{anyhow, Context};
+
+    use super::*;
+
+    #[test]
+    fn only_protocol_errors_disconnect() {
+        let error = HandlerError::from(anyhow::Error::from(ProtocolError::NoActiveCharacter));
+        assert!(matches!(error, HandlerError::Protocol(ProtocolError::NoActiveCharacter)));
+        assert!(error.disconnects_client());
+
+        let error = HandlerError::from(anyhow!("Something the server didn't expect"));
+        assert!(matches!(error, HandlerError::GameLogic(GameLogicError::Other(_))));
+        assert!(!error.disconnects_client());
+
+        let error = HandlerError::from(anyhow::Error::from(sqlx::Error::PoolTimedOut).context("Loading character"));
+        assert!(matches!(error, HandlerError::Database(_)));
+        assert!(!error.disconnects_client());
+    }
+
+    #[test]
+    fn context_does_not_hide_protocol_errors() {
+        let result: Result<(), ProtocolError> = Err(ProtocolError::NotAuthenticated);
+        let error = HandlerError::from(result.context("Handling CMSG_PING").unwrap_err());
+        assert!(error.disconnects_client());
+    }
+}
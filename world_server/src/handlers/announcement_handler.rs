@@ -0,0 +1,75 @@
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::world::World;
+use wow_world_messages::wrath::{Area, Language, Map, PlayerChatTag, SMSG_MESSAGECHAT_ChatType, SMSG_MESSAGECHAT, SMSG_MOTD, SMSG_NOTIFICATION};
+use wrath_realm_db::RealmDatabase;
+
+pub async fn send_motd(realm_database: &RealmDatabase, character: &Character) -> Result<()> {
+    let Some(motd) = realm_database.get_motd(character.get_client_locale().code()).await? else {
+        return Ok(());
+    };
+
+    //The client shows every entry as a separate line
+    let msg = SMSG_MOTD {
+        motds: motd.lines().map(|line| line.to_string()).collect(),
+    };
+    ServerEvent::Motd(msg).send_to_character(character).await
+}
+
+//Who hears an .announce or .notify: everyone, or only the map or zone the GM is in
+#[derive(Clone, Copy, Debug)]
+pub enum AnnouncementScope {
+    Server,
+    Map(Map),
+    Zone(Area),
+}
+
+impl AnnouncementScope {
+    async fn send(self, event: &ServerEvent, character_manager: &CharacterManager, world: &World) -> Result<()> {
+        match self {
+            AnnouncementScope::Server => event.send_to_all_characters(character_manager).await,
+            AnnouncementScope::Map(map) => world.broadcast_to_map(map, event, character_manager).await,
+            AnnouncementScope::Zone(zone) => world.broadcast_to_zone(zone, event, character_manager).await,
+        }
+    }
+}
+
+pub async fn send_server_announcement(character_manager: &CharacterManager, world: &World, scope: AnnouncementScope, message: &str) -> Result<()> {
+    let event = system_message_event(&format!("[Server Announcement]: {}", message));
+    scope.send(&event, character_manager, world).await
+}
+
+pub async fn send_system_message_to_all(character_manager: &CharacterManager, message: &str) -> Result<()> {
+    system_message_event(message).send_to_all_characters(character_manager).await
+}
+
+pub async fn send_system_message_to_character(character: &Character, message: &str) -> Result<()> {
+    system_message_event(message).send_to_character(character).await
+}
+
+pub async fn send_server_notification(character_manager: &CharacterManager, world: &World, scope: AnnouncementScope, message: &str) -> Result<()> {
+    scope.send(&notification_event(message), character_manager, world).await
+}
+
+pub async fn send_notification_to_character(character: &Character, message: &str) -> Result<()> {
+    notification_event(message).send_to_character(character).await
+}
+
+fn system_message_event(message: &str) -> ServerEvent {
+    ServerEvent::MessageChat(SMSG_MESSAGECHAT {
+        chat_type: SMSG_MESSAGECHAT_ChatType::System { target6: Guid::zero() },
+        language: Language::Universal,
+        sender: Guid::zero(),
+        flags: 0,
+        message: message.to_string(),
+        tag: PlayerChatTag::None,
+    })
+}
+
+fn notification_event(message: &str) -> ServerEvent {
+    ServerEvent::Notification(SMSG_NOTIFICATION {
+        notification: message.to_string(),
+    })
+}
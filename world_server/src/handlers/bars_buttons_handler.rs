@@ -13,7 +13,7 @@ pub async fn handle_cmsg_set_actionbar_toggles(
     packet: &CMSG_SET_ACTIONBAR_TOGGLES,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character = character_manager.get_character_mut(client.get_active_character())?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
     let action_bar = packet.action_bar;
 
     character.set_visible_actionbar_mask(action_bar);
@@ -27,7 +27,7 @@ pub async fn handle_cmsg_set_action_button(
     packet: &CMSG_SET_ACTION_BUTTON,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character = character_manager.get_character_mut(client.get_active_character())?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
     let button_slot = packet.button;
     let action_button = ActionButton {
         action: packet.action,
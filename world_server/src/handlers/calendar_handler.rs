@@ -0,0 +1,424 @@
+use std::net::SocketAddr;
+
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::client_manager::ClientManager;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::world::character_info_cache::CharacterLookup;
+use crate::world::World;
+use chrono::{Datelike, Timelike};
+use std::time::Instant;
+use wow_world_messages::wrath::{
+    CalendarSendInvitee, SendCalendarEvent, SendCalendarInvite, CMSG_CALENDAR_ADD_EVENT, CMSG_CALENDAR_EVENT_INVITE, CMSG_CALENDAR_EVENT_RSVP,
+    CMSG_CALENDAR_GET_EVENT, SMSG_CALENDAR_COMMAND_RESULT, SMSG_CALENDAR_EVENT_INVITE, SMSG_CALENDAR_EVENT_INVITE_ALERT, SMSG_CALENDAR_EVENT_STATUS,
+    SMSG_CALENDAR_SEND_CALENDAR, SMSG_CALENDAR_SEND_EVENT, SMSG_CALENDAR_SEND_NUM_PENDING,
+};
+use wow_world_messages::DateTime;
+use wrath_realm_db::calendar::{
+    DBCalendarEvent, DBCalendarEventCreateParameters, DBCalendarEventInvite, DBCalendarInvite, CALENDAR_INVITE_STATUS_INVITED,
+};
+
+const CALENDAR_INVITE_STATUS_CONFIRMED: u8 = 3;
+const CALENDAR_MODERATOR_RANK_OWNER: u8 = 2;
+const CALENDAR_FLAG_GUILD_EVENT: u32 = 0x400;
+const CALENDAR_MAX_INVITES: u32 = 100;
+
+//How SMSG_CALENDAR_SEND_EVENT tells the client why it gets the event
+const CALENDAR_SEND_EVENT_TYPE_GET: u8 = 0;
+const CALENDAR_SEND_EVENT_TYPE_ADD: u8 = 1;
+
+const CALENDAR_ERROR_PERMISSIONS: u32 = 5;
+const CALENDAR_ERROR_EVENT_INVALID: u32 = 6;
+const CALENDAR_ERROR_NOT_INVITED: u32 = 7;
+const CALENDAR_ERROR_ALREADY_INVITED: u32 = 10;
+const CALENDAR_ERROR_PLAYER_NOT_FOUND: u32 = 11;
+
+pub async fn handle_cmsg_calendar_get_num_pending(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    send_num_pending_calendar_invites(world, character).await
+}
+
+pub async fn handle_cmsg_calendar_get_calendar(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let character_id = character.get_guid().guid() as u32;
+    let realm_db = world.get_realm_database();
+
+    let events = realm_db.get_calendar_events_for_character(character_id, character.get_guild_id()).await?;
+    let invites = realm_db
+        .get_calendar_invites_for_character(character_id)
+        .await?
+        .into_iter()
+        .filter_map(|invite| {
+            let event = events.iter().find(|event| event.id == invite.event_id)?;
+            Some(SendCalendarInvite {
+                event_id: Guid::new(event.id as u64),
+                invite_id: Guid::new(invite.id as u64),
+                status: invite.status,
+                rank: invite.moderator_rank,
+                is_guild_event: event.guild_id.is_some(),
+                creator: Guid::new(event.creator_id as u64),
+            })
+        })
+        .collect();
+    let events = events
+        .iter()
+        .map(|event| {
+            Ok(SendCalendarEvent {
+                event_id: Guid::new(event.id as u64),
+                title: event.title.clone(),
+                event_type: event.event_type as u32,
+                event_time: DateTime::try_from(event.event_time)?,
+                flags: event.flags,
+                dungeon_id: event.dungeon_id as u32,
+                creator: Guid::new(event.creator_id as u64),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let now = current_unix_time()?;
+    let msg = SMSG_CALENDAR_SEND_CALENDAR {
+        invites,
+        events,
+        current_time: now as u32,
+        zone_time: packed_time(now)?,
+        //Instance locks, raid resets and holidays aren't shown on the calendar yet
+        instances: Vec::new(),
+        relative_time: 0,
+        reset_times: Vec::new(),
+        holidays: Vec::new(),
+    };
+    ServerEvent::CalendarSendCalendar(msg).send_to_character(character).await
+}
+
+pub async fn handle_cmsg_calendar_get_event(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_CALENDAR_GET_EVENT,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let character_id = character.get_guid().guid() as u32;
+    let realm_db = world.get_realm_database();
+
+    let event_id = packet.event.guid() as u32;
+    let Some(event) = realm_db.get_calendar_event(event_id).await? else {
+        return send_command_result(character, "", CALENDAR_ERROR_EVENT_INVALID).await;
+    };
+    let invites = realm_db.get_calendar_event_invites(event_id).await?;
+    let is_guild_member = event.guild_id.is_some() && event.guild_id == character.get_guild_id();
+    if !is_guild_member && !invites.iter().any(|invite| invite.invitee_id == character_id) {
+        return send_command_result(character, "", CALENDAR_ERROR_NOT_INVITED).await;
+    }
+
+    send_event(world, character, &event, &invites, CALENDAR_SEND_EVENT_TYPE_GET).await
+}
+
+pub async fn handle_cmsg_calendar_add_event(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_CALENDAR_ADD_EVENT,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let creator_id = character.get_guid().guid() as u32;
+
+    //Guild events of characters without a guild end up as regular events with only the creator on them
+    let guild_id = if packet.flags & CALENDAR_FLAG_GUILD_EVENT != 0 {
+        let guild_id = character.get_guild_id();
+        if guild_id.is_none() {
+            warn!("{} created a guild calendar event without being in a guild", character.name);
+        }
+        guild_id
+    } else {
+        None
+    };
+
+    let params = DBCalendarEventCreateParameters {
+        creator_id,
+        guild_id,
+        title: packet.title.clone(),
+        description: packet.description.clone(),
+        event_type: packet.event_type,
+        dungeon_id: packet.dungeon_id as i32,
+        event_time: packet.event_time.as_int(),
+        flags: packet.flags,
+    };
+
+    //An invitee that doesn't exist would fail the whole event, the client is told which one it was instead
+    let now = Instant::now();
+    for invitee in packet.invitees.iter() {
+        if !matches!(world.get_character_info_cache().lookup(invitee.guid, now), CharacterLookup::Known(_)) {
+            return send_command_result(character, "", CALENDAR_ERROR_PLAYER_NOT_FOUND).await;
+        }
+    }
+
+    //The creator always ends up on their own event as the owner
+    let mut invites = vec![DBCalendarInvite {
+        invitee_id: creator_id,
+        status: CALENDAR_INVITE_STATUS_CONFIRMED,
+        moderator_rank: CALENDAR_MODERATOR_RANK_OWNER,
+    }];
+    invites.extend(
+        packet
+            .invitees
+            .iter()
+            .filter(|invitee| invitee.guid.guid() as u32 != creator_id)
+            .map(|invitee| DBCalendarInvite {
+                invitee_id: invitee.guid.guid() as u32,
+                status: CALENDAR_INVITE_STATUS_INVITED,
+                moderator_rank: invitee.rank,
+            }),
+    );
+
+    let realm_db = world.get_realm_database();
+    let event_id = realm_db.create_calendar_event(&params, &invites, current_unix_time()?).await?;
+    info!("{} created calendar event {} '{}'", character.name, event_id, params.title);
+
+    let Some(event) = realm_db.get_calendar_event(event_id).await? else {
+        bail!("Calendar event {} is gone right after it was created", event_id);
+    };
+    let invites = realm_db.get_calendar_event_invites(event_id).await?;
+    send_event(world, character, &event, &invites, CALENDAR_SEND_EVENT_TYPE_ADD).await?;
+    for invite in invites.iter().filter(|invite| invite.invitee_id != creator_id) {
+        notify_invitee(client_manager, character_manager, world, &event, invite).await?;
+    }
+    Ok(())
+}
+
+pub async fn handle_cmsg_calendar_event_invite(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_CALENDAR_EVENT_INVITE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let realm_db = world.get_realm_database();
+
+    let Some(invitee) = world.get_character_info_cache().find_by_name(&packet.name) else {
+        return send_command_result(character, &packet.name, CALENDAR_ERROR_PLAYER_NOT_FOUND).await;
+    };
+    let invitee_id = invitee.guid.guid() as u32;
+
+    //Invites made while the event is still being set up are sent along with CMSG_CALENDAR_ADD_EVENT
+    if packet.pre_event {
+        return Ok(());
+    }
+
+    let event_id = packet.event.guid() as u32;
+    let Some(event) = realm_db.get_calendar_event(event_id).await? else {
+        return send_command_result(character, "", CALENDAR_ERROR_EVENT_INVALID).await;
+    };
+    if event.creator_id != character.get_guid().guid() as u32 {
+        return send_command_result(character, "", CALENDAR_ERROR_PERMISSIONS).await;
+    }
+
+    let invite = DBCalendarInvite {
+        invitee_id,
+        status: CALENDAR_INVITE_STATUS_INVITED,
+        moderator_rank: 0,
+    };
+    if !realm_db
+        .add_calendar_invite(event_id, invitee_id, event.creator_id, &invite, current_unix_time()?)
+        .await?
+    {
+        return send_command_result(character, &packet.name, CALENDAR_ERROR_ALREADY_INVITED).await;
+    }
+    let Some(invite) = realm_db
+        .get_calendar_event_invites(event_id)
+        .await?
+        .into_iter()
+        .find(|invite| invite.invitee_id == invitee_id)
+    else {
+        bail!(
+            "Calendar invite of {} to event {} is gone right after it was added",
+            packet.name,
+            event_id
+        );
+    };
+
+    let msg = SMSG_CALENDAR_EVENT_INVITE {
+        invitee: invitee.guid,
+        event_id: packet.event,
+        invite_id: Guid::new(invite.id as u64),
+        level: invitee.level,
+        invite_status: invite.status,
+        status_time: packed_time(invite.invite_time)?,
+        is_sign_up: false,
+    };
+    ServerEvent::CalendarEventInvite(msg).send_to_character(character).await?;
+    notify_invitee(client_manager, character_manager, world, &event, &invite).await
+}
+
+pub async fn handle_cmsg_calendar_event_rsvp(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_CALENDAR_EVENT_RSVP,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let character_id = character.get_guid().guid() as u32;
+    let event_id = packet.event.guid() as u32;
+
+    let status = u8::try_from(packet.status)?;
+    let realm_db = world.get_realm_database();
+    if !realm_db.set_calendar_invite_status(event_id, character_id, status).await? {
+        return send_command_result(character, "", CALENDAR_ERROR_NOT_INVITED).await;
+    }
+    let Some(event) = realm_db.get_calendar_event(event_id).await? else {
+        return send_command_result(character, "", CALENDAR_ERROR_EVENT_INVALID).await;
+    };
+    let invites = realm_db.get_calendar_event_invites(event_id).await?;
+    let Some(invite) = invites.iter().find(|invite| invite.invitee_id == character_id) else {
+        bail!("{} responded to calendar event {} and lost their invite", character.name, event_id);
+    };
+
+    //Everyone on the event that is online sees the new answer
+    let msg = SMSG_CALENDAR_EVENT_STATUS {
+        invitee: character.get_guid(),
+        event_id: packet.event,
+        event_time: DateTime::try_from(event.event_time)?,
+        flags: event.flags,
+        status: invite.status,
+        rank: invite.moderator_rank,
+        status_time: packed_time(current_unix_time()?)?,
+    };
+    for other in invites.iter() {
+        if let Some(other) = find_online_character(client_manager, character_manager, other.invitee_id) {
+            ServerEvent::CalendarEventStatus(msg.clone()).send_to_character(other).await?;
+        }
+    }
+
+    send_num_pending_calendar_invites(world, character).await
+}
+
+fn find_online_character<'a>(client_manager: &ClientManager, character_manager: &'a CharacterManager, character_id: u32) -> Option<&'a Character> {
+    let guid = Guid::new(character_id as u64);
+    client_manager.find_client_from_active_character_guid(guid).ok()?;
+    character_manager.find_character(guid)
+}
+
+//Invitees that are offline find the invite the next time they open their calendar
+async fn notify_invitee(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    event: &DBCalendarEvent,
+    invite: &DBCalendarEventInvite,
+) -> Result<()> {
+    let Some(invitee) = find_online_character(client_manager, character_manager, invite.invitee_id) else {
+        return Ok(());
+    };
+    let msg = SMSG_CALENDAR_EVENT_INVITE_ALERT {
+        event_id: Guid::new(event.id as u64),
+        title: event.title.clone(),
+        event_time: DateTime::try_from(event.event_time)?,
+        flags: event.flags,
+        event_type: event.event_type as u32,
+        dungeon_id: event.dungeon_id as u32,
+        invite_id: Guid::new(invite.id as u64),
+        status: invite.status,
+        rank: invite.moderator_rank,
+        event_creator: Guid::new(event.creator_id as u64),
+        invite_sender: Guid::new(event.creator_id as u64),
+    };
+    ServerEvent::CalendarEventInviteAlert(msg).send_to_character(invitee).await?;
+    send_num_pending_calendar_invites(world, invitee).await
+}
+
+async fn send_event(world: &World, character: &Character, event: &DBCalendarEvent, invites: &[DBCalendarEventInvite], send_type: u8) -> Result<()> {
+    let now = Instant::now();
+    let invitees = invites
+        .iter()
+        .map(|invite| {
+            let guid = Guid::new(invite.invitee_id as u64);
+            let level = match world.get_character_info_cache().lookup(guid, now) {
+                CharacterLookup::Known(info) => info.level,
+                _ => 0,
+            };
+            Ok(CalendarSendInvitee {
+                guid,
+                level,
+                status: invite.status,
+                rank: invite.moderator_rank,
+                guild_member: event.guild_id.is_some(),
+                invite_id: Guid::new(invite.id as u64),
+                status_time: packed_time(invite.invite_time)?,
+                text: String::new(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let msg = SMSG_CALENDAR_SEND_EVENT {
+        send_type,
+        creator: Guid::new(event.creator_id as u64),
+        event_id: Guid::new(event.id as u64),
+        title: event.title.clone(),
+        description: event.description.clone(),
+        event_type: event.event_type,
+        repeatable: false,
+        max_invitees: CALENDAR_MAX_INVITES,
+        dungeon_id: event.dungeon_id,
+        flags: event.flags,
+        event_time: DateTime::try_from(event.event_time)?,
+        time_zone_time: packed_time(current_unix_time()?)?,
+        guild_id: event.guild_id.unwrap_or(0),
+        invitees,
+    };
+    ServerEvent::CalendarSendEvent(msg).send_to_character(character).await
+}
+
+async fn send_command_result(character: &Character, name: &str, result: u32) -> Result<()> {
+    let msg = SMSG_CALENDAR_COMMAND_RESULT {
+        unknown1: 0,
+        unknown2: 0,
+        name: name.to_string(),
+        result,
+    };
+    ServerEvent::CalendarCommandResult(msg).send_to_character(character).await
+}
+
+async fn send_num_pending_calendar_invites(world: &World, character: &Character) -> Result<()> {
+    let character_id = character.get_guid().guid() as u32;
+    let pending_events = world.get_realm_database().get_num_pending_calendar_invites(character_id).await?;
+    let msg = SMSG_CALENDAR_SEND_NUM_PENDING { pending_events };
+    ServerEvent::CalendarSendNumPending(msg).send_to_character(character).await
+}
+
+fn current_unix_time() -> Result<u64> {
+    Ok(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs())
+}
+
+//The client wants times packed into minutes, hours, weekday, day, month and years since 2000
+fn packed_time(unix_time: u64) -> Result<DateTime> {
+    let Some(time) = chrono::DateTime::from_timestamp(unix_time as i64, 0) else {
+        bail!("{} is not a valid timestamp", unix_time);
+    };
+    let packed = time.minute()
+        | time.hour() << 6
+        | time.weekday().num_days_from_sunday() << 11
+        | time.day0() << 14
+        | time.month0() << 20
+        | ((time.year() - 2000).max(0) as u32) << 24;
+    Ok(DateTime::try_from(packed)?)
+}
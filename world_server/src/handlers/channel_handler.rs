@@ -0,0 +1,196 @@
+use std::net::SocketAddr;
+
+use wow_world_messages::wrath::{
+    ChatNotify, Language, SMSG_MESSAGECHAT_ChatType, CMSG_CHANNEL_MODERATOR, CMSG_CHANNEL_PASSWORD, CMSG_CHANNEL_UNMODERATOR, CMSG_JOIN_CHANNEL,
+    CMSG_LEAVE_CHANNEL, SMSG_CHANNEL_NOTIFY, SMSG_MESSAGECHAT,
+};
+
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::chat::channels::ChannelError;
+use crate::chat::language::message_for_listener;
+use crate::client_manager::ClientManager;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::world::prelude::GameObject;
+use crate::world::World;
+
+impl ChannelError {
+    fn notify_type(&self) -> ChatNotify {
+        match self {
+            ChannelError::WrongPassword => ChatNotify::WrongPasswordNotice,
+            ChannelError::AlreadyMember => ChatNotify::PlayerAlreadyMemberNotice,
+            ChannelError::NotMember => ChatNotify::NotMemberNotice,
+            ChannelError::NotModerator => ChatNotify::NotModeratorNotice,
+            ChannelError::NotOwner => ChatNotify::NotOwnerNotice,
+            ChannelError::PlayerNotFound => ChatNotify::PlayerNotFoundNotice,
+        }
+    }
+}
+
+async fn send_channel_notify(character: &Character, notify_type: ChatNotify, channel_name: &str) -> Result<()> {
+    ServerEvent::ChannelNotify(SMSG_CHANNEL_NOTIFY {
+        notify_type,
+        channel_name: channel_name.to_string(),
+    })
+    .send_to_character(character)
+    .await
+}
+
+//Successful changes are confirmed with the notice, failures get the notice matching the error
+async fn send_channel_result(
+    character: &Character,
+    result: std::result::Result<String, ChannelError>,
+    notify_type: ChatNotify,
+    channel_name: &str,
+) -> Result<()> {
+    match result {
+        Ok(display_name) => send_channel_notify(character, notify_type, &display_name).await,
+        Err(e) => send_channel_notify(character, e.notify_type(), channel_name).await,
+    }
+}
+
+pub async fn handle_cmsg_join_channel(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    packet: &CMSG_JOIN_CHANNEL,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    //Channels from ChatChannels.dbc come with their id, custom channels with 0
+    let built_in = packet.channel_id != 0;
+    let result = world
+        .get_channels_mut()
+        .join(&packet.channel_name, &packet.channel_password, built_in, character.get_guid());
+    send_channel_result(character, result, ChatNotify::YouJoinedNotice, &packet.channel_name).await
+}
+
+pub async fn handle_cmsg_leave_channel(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    packet: &CMSG_LEAVE_CHANNEL,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    let result = world.get_channels_mut().leave(&packet.channel_name, character.get_guid());
+    send_channel_result(character, result, ChatNotify::YouLeftNotice, &packet.channel_name).await
+}
+
+pub async fn handle_cmsg_channel_password(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    packet: &CMSG_CHANNEL_PASSWORD,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    let result = world
+        .get_channels_mut()
+        .set_password(&packet.channel_name, character.get_guid(), &packet.channel_password);
+    send_channel_result(character, result, ChatNotify::PasswordChangedNotice, &packet.channel_name).await
+}
+
+pub async fn handle_cmsg_channel_moderator(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    packet: &CMSG_CHANNEL_MODERATOR,
+) -> Result<()> {
+    set_channel_moderator(
+        client_manager,
+        character_manager,
+        world,
+        client_id,
+        &packet.channel_name,
+        &packet.player_name,
+        true,
+    )
+    .await
+}
+
+pub async fn handle_cmsg_channel_unmoderator(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    packet: &CMSG_CHANNEL_UNMODERATOR,
+) -> Result<()> {
+    set_channel_moderator(
+        client_manager,
+        character_manager,
+        world,
+        client_id,
+        &packet.channel_name,
+        &packet.player_name,
+        false,
+    )
+    .await
+}
+
+async fn set_channel_moderator(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    channel_name: &str,
+    player_name: &str,
+    moderator: bool,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    let target = world
+        .get_character_info_cache()
+        .find_by_name(player_name)
+        .filter(|info| info.online)
+        .map(|info| info.guid);
+    let result = match target {
+        Some(target) => world
+            .get_channels_mut()
+            .set_moderator(channel_name, character.get_guid(), target, moderator),
+        None => Err(ChannelError::PlayerNotFound),
+    };
+    send_channel_result(character, result, ChatNotify::ModeChangeNotice, channel_name).await
+}
+
+//Sends a chat message to everyone in the channel, including the sender
+pub async fn send_channel_message(
+    sender: &Character,
+    character_manager: &CharacterManager,
+    world: &World,
+    channel_name: &str,
+    language: Language,
+    message: &str,
+) -> Result<()> {
+    let (display_name, members) = match world.get_channels().speak(channel_name, sender.get_guid()) {
+        Ok(result) => result,
+        Err(e) => return send_channel_notify(sender, e.notify_type(), channel_name).await,
+    };
+
+    for guid in members {
+        let member = character_manager.get_character(guid)?;
+        ServerEvent::MessageChat(SMSG_MESSAGECHAT {
+            chat_type: SMSG_MESSAGECHAT_ChatType::Channel {
+                channel_name: display_name.clone(),
+                target5: sender.get_guid(),
+            },
+            language,
+            sender: sender.get_guid(),
+            flags: 0,
+            message: message_for_listener(member, language, message),
+            tag: sender.get_chat_tag(),
+        })
+        .send_to_character(member)
+        .await?;
+    }
+    Ok(())
+}
@@ -1,27 +1,29 @@
-use crate::character::character_inventory::SimpleCharacterInventory;
-use crate::character::character_inventory::SimpleItemDescription;
-use crate::character::character_inventory::INVENTORY_SLOT_BAG_0;
+use crate::audit::{log_audit_event, AuditEvent, AuditSource};
+use crate::character::character_inventory::{InventoryError, InventoryStorable, INVENTORY_SLOT_BAG_0};
 use crate::character::character_manager::CharacterManager;
 use crate::character::Character;
 use crate::client_manager::ClientManager;
 use crate::connection::events::ServerEvent;
 use crate::constants::inventory::*;
 use crate::data::DataStorage;
+use crate::handlers;
 use crate::prelude::*;
+use crate::world::character_info_cache::CharacterInfo;
 use crate::world::prelude::GameObject;
 use crate::world::World;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::convert::TryInto;
 use std::net::SocketAddr;
-use wow_dbc::DbcTable;
 use wow_world_messages::wrath::WorldResult;
 use wow_world_messages::wrath::CMSG_AUTOEQUIP_ITEM;
 use wow_world_messages::wrath::CMSG_CHAR_CREATE;
 use wow_world_messages::wrath::CMSG_CHAR_DELETE;
+use wow_world_messages::wrath::CMSG_DESTROYITEM;
 use wow_world_messages::wrath::CMSG_PLAYER_LOGIN;
 use wow_world_messages::wrath::CMSG_STANDSTATECHANGE;
 use wow_world_messages::wrath::CMSG_SWAP_INV_ITEM;
+use wow_world_messages::wrath::CMSG_SWAP_ITEM;
 use wow_world_messages::wrath::SMSG_ACTION_BUTTONS;
 use wow_world_messages::wrath::SMSG_BINDPOINTUPDATE;
 use wow_world_messages::wrath::SMSG_CHAR_CREATE;
@@ -116,7 +118,12 @@ pub async fn handle_cmsg_char_enum(client_manager: &ClientManager, world: &World
     Ok(())
 }
 
-pub async fn handle_cmsg_char_create(client_manager: &ClientManager, client_id: SocketAddr, world: &World, data: &CMSG_CHAR_CREATE) -> Result<()> {
+pub async fn handle_cmsg_char_create(
+    client_manager: &ClientManager,
+    client_id: SocketAddr,
+    world: &mut World,
+    data: &CMSG_CHAR_CREATE,
+) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
     let account_id = client.data.account_id;
     let game_db = world.get_game_database();
@@ -175,8 +182,17 @@ pub async fn handle_cmsg_char_create(client_manager: &ClientManager, client_id:
 
     //Safe to unwrap since we caught is_err() just above
     let inserted_character_id = insert_result.unwrap();
-
-    let realm_id = std::env::var("REALM_ID")?.parse()?;
+    world.get_character_info_cache_mut().insert(CharacterInfo {
+        guid: Guid::new(inserted_character_id),
+        name: create_params.name.clone(),
+        race: data.race,
+        class: data.class,
+        gender: data.gender,
+        level: 1,
+        online: false,
+    });
+
+    let realm_id = wrath_common::config::required("REALM_ID")?;
     let num_chars = realm_db.get_num_characters_for_account(account_id).await?;
     client_manager
         .auth_db
@@ -201,17 +217,22 @@ pub async fn handle_cmsg_char_create(client_manager: &ClientManager, client_id:
     Ok(())
 }
 
-pub async fn handle_cmsg_char_delete(client_manager: &ClientManager, client_id: SocketAddr, world: &World, data: &CMSG_CHAR_DELETE) -> Result<()> {
+pub async fn handle_cmsg_char_delete(
+    client_manager: &ClientManager,
+    client_id: SocketAddr,
+    world: &mut World,
+    data: &CMSG_CHAR_DELETE,
+) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
     let account_id = client.data.account_id;
-    let realm_db = world.get_realm_database();
-
-    let character_id: u32 = data.guid.guid() as u32;
 
-    let result = match realm_db.delete_character(character_id, account_id).await {
-        Ok(_) => WorldResult::CharDeleteSuccess,
-        // TODO: Handle guild leader and arena captain failure cases.
-        Err(_) => WorldResult::CharDeleteFailed,
+    //Guild masters have to hand over or disband their guild first. Characters with auctions up will be refused
+    //here once auctions exist.
+    let is_guild_master = world.get_guilds().get_guild_of(data.guid).is_some_and(|guild| guild.leader == data.guid);
+    let result = if is_guild_master {
+        WorldResult::CharDeleteFailedGuildLeader
+    } else {
+        delete_character(world, account_id, data.guid).await
     };
 
     let msg = SMSG_CHAR_DELETE { result };
@@ -220,6 +241,25 @@ pub async fn handle_cmsg_char_delete(client_manager: &ClientManager, client_id:
     Ok(())
 }
 
+async fn delete_character(world: &mut World, account_id: u32, guid: Guid) -> WorldResult {
+    match world.get_realm_database().delete_character(guid.guid() as u32, account_id).await {
+        Ok(true) => {
+            world.get_character_info_cache_mut().invalidate(guid);
+            world.get_guilds_mut().on_character_deleted(guid);
+            info!("Account {} deleted character {}", account_id, guid);
+            WorldResult::CharDeleteSuccess
+        }
+        Ok(false) => {
+            warn!("Account {} tried to delete character {} which it doesn't own", account_id, guid);
+            WorldResult::CharDeleteFailed
+        }
+        Err(e) => {
+            error!("Failed to delete character {}: {}", guid, e);
+            WorldResult::CharDeleteFailed
+        }
+    }
+}
+
 async fn give_character_start_equipment(
     character_id: u32,
     race: Race,
@@ -228,33 +268,12 @@ async fn give_character_start_equipment(
     data_storage: &DataStorage,
     realm_db: &RealmDatabase,
 ) -> Result<()> {
-    let start_outfit_info = data_storage
-        .get_dbc_char_start_outfit()?
-        .rows()
-        .iter()
-        .find(|row| row.class_id.id == class.as_int() as i32 && row.race_id.id == race.as_int() as i32 && row.sex_id == gender.as_int() as i8)
+    let start_outfit = data_storage
+        .get_start_outfit(race, class, gender)
         .ok_or_else(|| anyhow!("Class/Race/Gender combination not found for starting outfit"))?;
 
-    let mut dummy_inventory = SimpleCharacterInventory::new();
-    let slot_ids: Vec<i32> = start_outfit_info
-        .inventory_type
-        .iter()
-        .zip(start_outfit_info.item_id)
-        .map(|(&inv_type, item_id)| {
-            let inventory_type: InventoryType = (inv_type as u8).try_into()?;
-
-            let sid = SimpleItemDescription {
-                item_id: item_id as u32,
-                inventory_type,
-            };
-
-            dummy_inventory.try_insert_item(sid).map(|res| res.to_owned())
-        })
-        .map(|res: anyhow::Result<EquipmentSlot, anyhow::Error>| if let Ok(s) = res { s as i32 } else { -1 })
-        .collect();
-
     realm_db
-        .give_character_start_equipment(character_id, start_outfit_info.item_id, slot_ids)
+        .give_character_start_equipment(character_id, start_outfit.iter().map(|item| (item.slot_id, item.item_id)))
         .await
 }
 
@@ -265,12 +284,25 @@ pub async fn handle_cmsg_player_login(
     client_id: SocketAddr,
     data: &CMSG_PLAYER_LOGIN,
 ) -> Result<()> {
-    let connection_sender = client_manager.get_authenticated_client(client_id)?.connection_sender.clone();
-    let character = Character::load(connection_sender, data.guid, world, &client_manager.data_storage).await?;
-    character_manager.add_character(character);
+    let data_storage = client_manager.data_storage.clone();
     let client = client_manager.get_authenticated_client_mut(client_id).await?;
-    client.set_active_character(data.guid);
-    client.login_active_character(world, character_manager).await
+    world.get_account_data_mut().load_account(client.data.account_id).await?;
+    client.load_and_set_active_character(&data_storage, world, data.guid).await?;
+    //Entering the world can't wait for the next tick, the character is needed right away
+    character_manager.process_commands();
+    let guild_membership = world.get_guilds().get_membership(data.guid);
+    character_manager.get_character_mut(data.guid)?.set_guild(guild_membership);
+    client.login_active_character(world, character_manager).await?;
+
+    let character = character_manager.get_character(data.guid)?;
+    world.get_character_info_cache_mut().insert(CharacterInfo::of_character(character));
+    world.on_character_entered_world(data.guid, character.get_guild_id());
+    handlers::notify_guild_of_status(character, character_manager, world, true).await?;
+    handlers::send_guild_motd(character, world).await?;
+    //Groups outlive logouts, the party sees the character come back online
+    handlers::send_group_list_update(data.guid, character_manager, world).await?;
+    crate::spell::auras::send_own_auras(character).await?;
+    client.start_session_log(&world.get_realm_database(), character).await
 }
 
 pub async fn handle_cmsg_player_logout(
@@ -279,7 +311,7 @@ pub async fn handle_cmsg_player_logout(
     client_id: SocketAddr,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character_id = client.get_active_character();
+    let character_id = client.get_active_character()?;
     let character = character_manager.get_character_mut(character_id)?;
     character.try_logout().await?;
     Ok(())
@@ -292,7 +324,7 @@ pub async fn handle_cmsg_standstate_change(
     data: &CMSG_STANDSTATECHANGE,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character = character_manager.get_character_mut(client.get_active_character())?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
     character.set_stand_state(data.animation_state).await
 }
 
@@ -331,32 +363,93 @@ pub async fn send_action_buttons(character: &Character) -> Result<()> {
     ServerEvent::ActionButtons(msg).send_to_character(character).await
 }
 
+//Dragging within the backpack and equipment
 pub async fn handle_cmsg_swap_inv_item(
     client_manager: &ClientManager,
     character_manager: &mut CharacterManager,
     world: &World,
     client_id: SocketAddr,
     data: &CMSG_SWAP_INV_ITEM,
+) -> Result<()> {
+    let source = (data.source_slot.as_int(), INVENTORY_SLOT_BAG_0);
+    let destination = (data.destination_slot.as_int(), INVENTORY_SLOT_BAG_0);
+    swap_items(client_manager, character_manager, world, client_id, source, destination).await
+}
+
+//Dragging between bags, which is the same as CMSG_SWAP_INV_ITEM with bag 255 for the backpack
+pub async fn handle_cmsg_swap_item(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_SWAP_ITEM,
+) -> Result<()> {
+    let source = (data.source_slot, data.source_bag);
+    let destination = (data.destionation_slot, data.destination_bag);
+    swap_items(client_manager, character_manager, world, client_id, source, destination).await
+}
+
+async fn swap_items(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    source: (u8, u8),
+    destination: (u8, u8),
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character = character_manager.get_character_mut(client.get_active_character())?;
-    let realm_db = world.get_realm_database();
-    let connection_sender = &client.connection_sender;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    if let Err(error) = character.check_item_swap(source, destination) {
+        let item_guid = character
+            .get_inventory_item(source)
+            .and_then(|item| item.update_state.object_guid())
+            .unwrap_or_default();
+        return character.send_inventory_change_failure(error, item_guid).await;
+    }
 
-    //TODO: Add checks here
-    let src = data.destination_slot.as_int();
-    let dst = data.source_slot.as_int();
-    let dst_item = character
-        .set_item(None, (dst, INVENTORY_SLOT_BAG_0), Some(&realm_db), Some(connection_sender))
-        .await?;
-    let src_item = character
-        .set_item(dst_item, (src, INVENTORY_SLOT_BAG_0), Some(&realm_db), Some(connection_sender))
-        .await?;
     character
-        .set_item(src_item, (dst, INVENTORY_SLOT_BAG_0), Some(&realm_db), Some(connection_sender))
-        .await?;
+        .swap_items(source, destination, Some(world.get_persistence_queue()), Some(&client.connection_sender))
+        .await
+}
 
-    Ok(())
+pub async fn handle_cmsg_destroyitem(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_DESTROYITEM,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    let item_position = (data.slot, data.bag);
+
+    let Some(item) = character.get_inventory_item(item_position) else {
+        return character.send_inventory_change_failure(InventoryError::ItemNotFound, Guid::zero()).await;
+    };
+    let item_guid = item.update_state.object_guid().unwrap_or_default();
+    let item_id = item.update_state.object_entry().unwrap_or(0) as u32;
+    let count = item.update_state.item_stack_count().unwrap_or(1) as u32;
+
+    let template = world.get_game_database().get_item_template(item_id).await?;
+    if template.flags & ITEM_FLAG_NO_USER_DESTROY != 0 || template.bonding == BIND_QUEST_ITEM {
+        return character.send_inventory_change_failure(InventoryError::CantDestroy, item_guid).await;
+    }
+
+    character
+        .set_item(None, item_position, Some(world.get_persistence_queue()), Some(&client.connection_sender))
+        .await?;
+    handlers::send_destroy_object(character, item_guid, false).await?;
+    info!("{} destroyed {}x item {}", character.name, count, item_id);
+
+    log_audit_event(
+        &world.get_realm_database(),
+        client.data.account_id,
+        character.get_guid(),
+        AuditEvent::ItemDestroyed { item_id, count },
+        AuditSource::Player,
+    )
+    .await
 }
 
 pub async fn handle_cmsg_autoequip_item(
@@ -367,12 +460,23 @@ pub async fn handle_cmsg_autoequip_item(
     data: &CMSG_AUTOEQUIP_ITEM,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character = character_manager.get_character_mut(client.get_active_character())?;
-    let realm_db = world.get_realm_database();
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    let persistence_queue = world.get_persistence_queue();
     let connection_sender = &client.connection_sender;
 
+    let source = (data.source_slot, data.source_bag);
+    let Some(item) = character.get_inventory_item(source) else {
+        return character.send_inventory_change_failure(InventoryError::ItemNotFound, Guid::zero()).await;
+    };
+    if get_compatible_equipment_slots_for_inventory_type(&item.get_inventory_type()).is_empty() {
+        let item_guid = item.update_state.object_guid().unwrap_or_default();
+        return character
+            .send_inventory_change_failure(InventoryError::ItemCantBeEquipped, item_guid)
+            .await;
+    }
+
     let previously_equipped_item = character
-        .auto_equip_item_from_bag((data.source_slot, data.source_bag), Some(&realm_db), Some(connection_sender))
+        .auto_equip_item_from_bag((data.source_slot, data.source_bag), Some(persistence_queue), Some(connection_sender))
         .await?;
 
     //The item that we had equipped (may be None) now goes into that slot
@@ -380,9 +484,150 @@ pub async fn handle_cmsg_autoequip_item(
         .set_item(
             previously_equipped_item,
             (data.source_slot, data.source_bag),
-            Some(&realm_db),
+            Some(persistence_queue),
             Some(connection_sender),
         )
         .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestClient, TestHarness};
+    use wow_world_base::wrath::ItemSlot;
+    use wow_world_messages::wrath::opcodes::ClientOpcodeMessage;
+    use wow_world_messages::wrath::{SMSG_INVENTORY_CHANGE_FAILURE_InventoryResult, Vector3d};
+
+    const WORN_SHORTSWORD: u32 = 25;
+    const WORN_WOODEN_SHIELD: u32 = 2362;
+    const BATTLEWORN_HAMMER: u32 = 2361;
+    const APPRENTICES_SHIRT: u32 = 6096;
+
+    const MAIN_HAND: u8 = EquipmentSlot::MainHand as u8;
+    const OFF_HAND: u8 = EquipmentSlot::Offhand as u8;
+
+    async fn character_with_items(harness: &mut TestHarness, item_ids: &[u32]) -> (TestClient, Vec<u8>) {
+        let position = Vector3d {
+            x: -8949.0,
+            y: -132.0,
+            z: 83.5,
+        };
+        let client = harness.add_character("Packrat", position).await;
+        let mut slots = Vec::new();
+        for &item_id in item_ids {
+            slots.push(harness.give_item(&client, item_id).await);
+        }
+        client.take_events();
+        (client, slots)
+    }
+
+    fn swap(source: u8, destination: u8) -> ClientOpcodeMessage {
+        ClientOpcodeMessage::CMSG_SWAP_INV_ITEM(CMSG_SWAP_INV_ITEM {
+            source_slot: ItemSlot::try_from(source).unwrap(),
+            destination_slot: ItemSlot::try_from(destination).unwrap(),
+        })
+    }
+
+    fn item_at(harness: &TestHarness, client: &TestClient, slot: u8) -> Option<u32> {
+        let item = harness.get_character(client).get_inventory_item((slot, INVENTORY_SLOT_BAG_0))?;
+        item.update_state.object_entry().map(|entry| entry as u32)
+    }
+
+    fn failures(client: &TestClient) -> Vec<SMSG_INVENTORY_CHANGE_FAILURE_InventoryResult> {
+        client
+            .take_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                ServerEvent::InventoryChangeFailure(msg) => Some(msg.result),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn items_only_go_to_slots_that_fit_them() {
+        smol::block_on(async {
+            let mut harness = TestHarness::new();
+            let (client, slots) = character_with_items(&mut harness, &[WORN_SHORTSWORD, APPRENTICES_SHIRT]).await;
+            let (sword, shirt) = (slots[0], slots[1]);
+
+            harness.handle_packet(&client, swap(shirt, MAIN_HAND)).await.unwrap();
+            assert!(matches!(
+                failures(&client)[..],
+                [SMSG_INVENTORY_CHANGE_FAILURE_InventoryResult::ItemDoesntGoToSlot { .. }]
+            ));
+            assert_eq!(item_at(&harness, &client, shirt), Some(APPRENTICES_SHIRT));
+
+            harness.handle_packet(&client, swap(sword, MAIN_HAND)).await.unwrap();
+            assert!(failures(&client).is_empty());
+            assert_eq!(item_at(&harness, &client, MAIN_HAND), Some(WORN_SHORTSWORD));
+            assert_eq!(item_at(&harness, &client, sword), None);
+
+            //Swapping the equipped sword with the shirt would put the shirt in the main hand
+            harness.handle_packet(&client, swap(MAIN_HAND, shirt)).await.unwrap();
+            assert_eq!(failures(&client).len(), 1);
+            assert_eq!(item_at(&harness, &client, MAIN_HAND), Some(WORN_SHORTSWORD));
+            assert_eq!(item_at(&harness, &client, shirt), Some(APPRENTICES_SHIRT));
+        });
+    }
+
+    #[test]
+    fn two_handed_weapons_need_a_free_off_hand() {
+        smol::block_on(async {
+            let mut harness = TestHarness::new();
+            let (client, slots) = character_with_items(&mut harness, &[WORN_SHORTSWORD, WORN_WOODEN_SHIELD, BATTLEWORN_HAMMER]).await;
+            let (sword, shield, hammer) = (slots[0], slots[1], slots[2]);
+
+            harness.handle_packet(&client, swap(sword, MAIN_HAND)).await.unwrap();
+            harness.handle_packet(&client, swap(shield, OFF_HAND)).await.unwrap();
+            assert!(failures(&client).is_empty());
+
+            harness.handle_packet(&client, swap(hammer, MAIN_HAND)).await.unwrap();
+            assert!(matches!(
+                failures(&client)[..],
+                [SMSG_INVENTORY_CHANGE_FAILURE_InventoryResult::CantEquipWithTwohanded { .. }]
+            ));
+
+            //Once the shield is put away the hammer takes the sword's place and the sword goes where the hammer was
+            harness.handle_packet(&client, swap(OFF_HAND, shield)).await.unwrap();
+            harness.handle_packet(&client, swap(hammer, MAIN_HAND)).await.unwrap();
+            assert!(failures(&client).is_empty());
+            assert_eq!(item_at(&harness, &client, MAIN_HAND), Some(BATTLEWORN_HAMMER));
+            assert_eq!(item_at(&harness, &client, hammer), Some(WORN_SHORTSWORD));
+
+            harness.handle_packet(&client, swap(shield, OFF_HAND)).await.unwrap();
+            assert!(matches!(
+                failures(&client)[..],
+                [SMSG_INVENTORY_CHANGE_FAILURE_InventoryResult::CantEquipWithTwohanded { .. }]
+            ));
+        });
+    }
+
+    #[test]
+    fn moving_from_an_empty_slot_or_into_a_bag_fails() {
+        smol::block_on(async {
+            let mut harness = TestHarness::new();
+            let (client, slots) = character_with_items(&mut harness, &[WORN_SHORTSWORD]).await;
+
+            harness.handle_packet(&client, swap(slots[0] + 1, MAIN_HAND)).await.unwrap();
+            assert!(matches!(
+                failures(&client)[..],
+                [SMSG_INVENTORY_CHANGE_FAILURE_InventoryResult::ItemNotFound { .. }]
+            ));
+
+            let into_bag = ClientOpcodeMessage::CMSG_SWAP_ITEM(CMSG_SWAP_ITEM {
+                destination_bag: EquipmentSlot::Bag1 as u8,
+                destionation_slot: 0,
+                source_bag: INVENTORY_SLOT_BAG_0,
+                source_slot: slots[0],
+            });
+            harness.handle_packet(&client, into_bag).await.unwrap();
+            assert!(matches!(
+                failures(&client)[..],
+                [SMSG_INVENTORY_CHANGE_FAILURE_InventoryResult::ItemDoesntGoIntoBag { .. }]
+            ));
+            assert_eq!(item_at(&harness, &client, slots[0]), Some(WORN_SHORTSWORD));
+        });
+    }
+}
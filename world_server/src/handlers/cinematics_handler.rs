@@ -19,7 +19,7 @@ pub async fn handle_cmsg_next_cinematic_camera(
     client_id: SocketAddr,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character = character_manager.get_character_mut(client.get_active_character())?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
     character.handle_cinematic_next_camera()
 }
 
@@ -29,6 +29,6 @@ pub async fn handle_cmsg_complete_cinematic(
     client_id: SocketAddr,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character = character_manager.get_character_mut(client.get_active_character())?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
     character.handle_cinematic_ended()
 }
@@ -0,0 +1,30 @@
+use crate::character::character_manager::CharacterManager;
+use crate::client_manager::ClientManager;
+use crate::combat::melee;
+use crate::prelude::*;
+use crate::world::World;
+use std::net::SocketAddr;
+use wow_world_messages::wrath::CMSG_ATTACKSWING;
+
+pub async fn handle_cmsg_attackswing(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_ATTACKSWING,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    melee::start_melee_attack(guid, packet.guid, character_manager, world).await
+}
+
+pub async fn handle_cmsg_attackstop(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    melee::stop_melee_attack(guid, character_manager, world).await
+}
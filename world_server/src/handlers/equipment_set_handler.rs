@@ -0,0 +1,130 @@
+use std::net::SocketAddr;
+
+use wow_world_messages::wrath::{
+    EquipmentSetListItem, CMSG_EQUIPMENT_SET_DELETE, CMSG_EQUIPMENT_SET_SAVE, CMSG_EQUIPMENT_SET_USE, SMSG_EQUIPMENT_SET_LIST,
+    SMSG_EQUIPMENT_SET_SAVED, SMSG_EQUIPMENT_SET_USE_RESULT,
+};
+
+use crate::character::character_equipment_sets::{EquipmentSetSlotAction, IGNORED_SLOT_GUID};
+use crate::character::character_inventory::INVENTORY_SLOT_BAG_0;
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::client_manager::ClientManager;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::world::World;
+
+const EQUIPMENT_SET_USE_SUCCESS: u8 = 0;
+//The client shows "Equipment swap failed - inventory is full" for this one
+const EQUIPMENT_SET_USE_INVENTORY_FULL: u8 = 4;
+
+pub async fn send_equipment_set_list(character: &Character) -> Result<()> {
+    let equipment_sets = character
+        .get_equipment_sets()
+        .into_iter()
+        .map(|set| EquipmentSetListItem {
+            guid: Guid::new(set.id as u64),
+            id: set.index as u32,
+            name: set.name.to_string(),
+            icon_name: set.icon_name.to_string(),
+            equipment: set.item_guids,
+        })
+        .collect();
+
+    ServerEvent::EquipmentSetList(SMSG_EQUIPMENT_SET_LIST { equipment_sets })
+        .send_to_character(character)
+        .await
+}
+
+pub async fn handle_cmsg_equipment_set_save(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_EQUIPMENT_SET_SAVE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    let set_id = character
+        .save_equipment_set(
+            &world.get_realm_database(),
+            packet.index,
+            &packet.name,
+            &packet.icon_name,
+            &packet.equipment,
+        )
+        .await?;
+
+    ServerEvent::EquipmentSetSaved(SMSG_EQUIPMENT_SET_SAVED {
+        set: packet.index,
+        guid: Guid::new(set_id as u64),
+    })
+    .send_to_character(character)
+    .await
+}
+
+pub async fn handle_cmsg_equipment_set_delete(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_EQUIPMENT_SET_DELETE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    character
+        .delete_equipment_set(&world.get_realm_database(), packet.guid.guid() as u32)
+        .await
+}
+
+pub async fn handle_cmsg_equipment_set_use(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_EQUIPMENT_SET_USE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    //The client tells us where it thinks every item of the set currently is
+    let mut actions = [EquipmentSetSlotAction::Ignore; 19];
+    for (action, set_item) in actions.iter_mut().zip(packet.sets.iter()) {
+        *action = match set_item.item.guid() {
+            IGNORED_SLOT_GUID => EquipmentSetSlotAction::Ignore,
+            0 => EquipmentSetSlotAction::Unequip,
+            _ => {
+                let item = character.get_item_entry_by_guid(set_item.item);
+                match item {
+                    //Items in bags other than the backpack can't be moved yet
+                    Some(item) if set_item.source_bag == INVENTORY_SLOT_BAG_0 => EquipmentSetSlotAction::EquipFrom {
+                        slot: set_item.source_slot,
+                        item,
+                    },
+                    _ => {
+                        warn!(
+                            "{} tried to equip item {} which is not where the client says it is",
+                            character.name, set_item.item
+                        );
+                        EquipmentSetSlotAction::Ignore
+                    }
+                }
+            }
+        };
+    }
+
+    let used = character
+        .use_equipment_set(&actions, Some(world.get_persistence_queue()), Some(&client.connection_sender))
+        .await?;
+    let result = if used {
+        EQUIPMENT_SET_USE_SUCCESS
+    } else {
+        EQUIPMENT_SET_USE_INVENTORY_FULL
+    };
+
+    ServerEvent::EquipmentSetUseResult(SMSG_EQUIPMENT_SET_USE_RESULT { result })
+        .send_to_character(character)
+        .await
+}
@@ -2,13 +2,36 @@ use crate::character::*;
 use crate::connection::events::ServerEvent;
 use crate::prelude::*;
 use wow_world_messages::wrath::FactionInitializer;
-use wow_world_messages::wrath::SMSG_INITIALIZE_FACTIONS;
+use wow_world_messages::wrath::{FactionFlag, FactionStanding, SMSG_INITIALIZE_FACTIONS, SMSG_SET_FACTION_STANDING};
 
 const NUM_FACTIONS: u32 = 128;
 
+//Factions the character has reputation with show up in its reputation tab
 pub async fn send_faction_list(character: &Character) -> Result<()> {
-    let factions = (0..NUM_FACTIONS).map(|_| FactionInitializer::default()).collect();
+    let standings = character.get_reputation_standings();
+    let factions = (0..NUM_FACTIONS)
+        .map(|reputation_index| match standings.get(&reputation_index) {
+            Some(&standing) => FactionInitializer {
+                flag: FactionFlag::empty().set_visible(),
+                standing: standing as u32,
+            },
+            None => FactionInitializer::default(),
+        })
+        .collect();
     ServerEvent::InitializeFactions(SMSG_INITIALIZE_FACTIONS { factions })
         .send_to_character(character)
         .await
 }
+
+pub async fn send_faction_standing(character: &Character, reputation_index: u32, standing: i32) -> Result<()> {
+    ServerEvent::SetFactionStanding(SMSG_SET_FACTION_STANDING {
+        refer_a_friend_bonus: 0.0,
+        any_rank: true,
+        faction_standings: vec![FactionStanding {
+            faction: reputation_index,
+            standing: standing as u32,
+        }],
+    })
+    .send_to_character(character)
+    .await
+}
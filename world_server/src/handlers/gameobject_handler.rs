@@ -0,0 +1,73 @@
+use std::net::SocketAddr;
+
+use wow_world_messages::wrath::CMSG_GAMEOBJ_USE;
+
+use crate::character::character_manager::CharacterManager;
+use crate::character::character_quests::QuestStatus;
+use crate::client_manager::ClientManager;
+use crate::handlers::{credit_gameobject_use, send_loot_window, send_system_message_to_character};
+use crate::localization::ServerString;
+use crate::prelude::*;
+use crate::world::gathering::GatherResult;
+use crate::world::interactive_objects::ObjectUseResult;
+use crate::world::World;
+
+pub async fn handle_cmsg_gameobj_use(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    packet: &CMSG_GAMEOBJ_USE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+
+    //Mining veins and herbs are gathered, the loot window opens with what the node gave
+    if world.get_gathering_nodes().is_node(packet.guid) {
+        let character = character_manager.get_character_mut(guid)?;
+        match world.get_gathering_nodes_mut().gather(packet.guid, character)? {
+            GatherResult::Gathered { new_skill_value, depleted } => {
+                trace!(
+                    "{} gathered {} (new skill value {:?}, depleted: {})",
+                    character.name,
+                    packet.guid,
+                    new_skill_value,
+                    depleted
+                );
+                if let Some(loot) = world.get_gathering_nodes().get_loot(packet.guid) {
+                    send_loot_window(character, packet.guid, loot).await?;
+                    character.start_looting(packet.guid);
+                }
+            }
+            GatherResult::SkillTooLow { required } => {
+                let message = client_manager
+                    .data_storage
+                    .localize(client.data.locale, ServerString::GatheringSkillTooLow, &[&required]);
+                send_system_message_to_character(character, &message).await?;
+            }
+            GatherResult::Depleted => trace!("{} tried to gather depleted node {}", character.name, packet.guid),
+            GatherResult::OutOfRange => warn!("{} tried to gather {} from too far away", character.name, packet.guid),
+        }
+        return Ok(());
+    }
+
+    let character = character_manager.get_character_mut(guid)?;
+    match world.get_interactive_objects().use_object(packet.guid, character) {
+        ObjectUseResult::QuestCredit { gameobject_entry, quest_id } => {
+            //Some objects can only be used while the quest they belong to is in the quest log
+            if quest_id.is_some_and(|quest_id| character.get_quest_status(quest_id) != Some(QuestStatus::InQuestLog)) {
+                trace!(
+                    "{} used gameobject {} without having quest {:?}",
+                    character.name,
+                    gameobject_entry,
+                    quest_id
+                );
+                return Ok(());
+            }
+            credit_gameobject_use(character, packet.guid, gameobject_entry, world).await?;
+        }
+        ObjectUseResult::OutOfRange => warn!("{} tried to use gameobject {} from too far away", character.name, packet.guid),
+        ObjectUseResult::NotUsable => trace!("{} used gameobject {} which has no server side interaction", character.name, packet.guid),
+    }
+    Ok(())
+}
@@ -1,13 +1,46 @@
 use std::{net::SocketAddr, sync::Arc};
 
 use crate::{
-    character::character_manager::CharacterManager, client_manager::ClientManager, connection::events::ServerEvent, prelude::*,
-    world::prelude::GameObject,
+    audit::{log_gm_command, AuditEvent},
+    character::character_deserter::QueuePunishment,
+    character::character_manager::CharacterManager,
+    character::character_summon::check_summon,
+    character::Character,
+    client::Client,
+    client_manager::ClientManager,
+    connection::events::ServerEvent,
+    data::{DataProvider, DataStorage, WorldZoneLocation},
+    handlers::movement_handler::TeleportationDistance,
+    localization::ServerString,
+    notifications::Notification,
+    prelude::*,
+    random, spell,
+    world::{
+        prelude::GameObject,
+        uptime::{format_uptime, revision},
+        World,
+    },
 };
+use rand::seq::SliceRandom;
 use wow_world_messages::wrath::{
-    Language, PlayerChatTag, SMSG_MESSAGECHAT_ChatType, CMSG_GMTICKET_CREATE, SMSG_FORCE_RUN_BACK_SPEED_CHANGE, SMSG_FORCE_RUN_SPEED_CHANGE,
+    Area, CMSG_COMPLAIN_SpamType, Language, Map, PlayerChatTag, SMSG_MESSAGECHAT_ChatType, Vector3d, CMSG_COMPLAIN, CMSG_GMTICKET_CREATE,
     SMSG_GMTICKET_GETTICKET, SMSG_GMTICKET_SYSTEMSTATUS, SMSG_MESSAGECHAT,
 };
+use wrath_common::GmLevel;
+
+//The lowest account level that may use a chat command, None for commands that don't exist
+pub fn required_gm_level(command: &str, text_argument: &str) -> Option<GmLevel> {
+    Some(match command {
+        "start" | "guildnotify" => GmLevel::Player,
+        "server" if text_argument.eq_ignore_ascii_case("info") => GmLevel::Player,
+        "motd" if text_argument.is_empty() => GmLevel::Player,
+        "announce" | "notify" | "lookup" | "mute" | "unmute" => GmLevel::Moderator,
+        "speed" | "gm" | "god" | "fly" | "modify" | "taxi" | "recall" | "gmisland" | "tele" | "additem" | "ban" | "unban" | "groupsummon"
+        | "deserter" => GmLevel::GameMaster,
+        "motd" => GmLevel::Administrator,
+        _ => return None,
+    })
+}
 
 async fn send_system_message(
     client_manager: &ClientManager,
@@ -16,7 +49,7 @@ async fn send_system_message(
     message: &str,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
+    let guid = client.get_active_character()?;
     let character = character_manager.get_character(guid)?;
 
     let msg = SMSG_MESSAGECHAT {
@@ -60,11 +93,49 @@ pub async fn handle_cmsg_gmticket_getticket(client_manager: &ClientManager, clie
     Ok(())
 }
 
-pub async fn handle_cmsg_gmticket_create(client_manager: &ClientManager, client_id: SocketAddr, _packet: &CMSG_GMTICKET_CREATE) -> Result<()> {
-    let _client = client_manager.get_authenticated_client(client_id)?;
+pub async fn handle_cmsg_gmticket_create(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_GMTICKET_CREATE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    //Tickets aren't stored or answered yet, there is no ticketing system in place. Staff still get to
+    //hear about them through the notification sinks.
+    world.get_notifier().notify(Notification::GmTicketCreated {
+        character_name: character.name.clone(),
+        message: packet.message.clone(),
+    });
+    Ok(())
+}
+
+//Sent when a player uses "Report Player" on someone's chat message or mail
+pub async fn handle_cmsg_complain(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_COMPLAIN,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let reporter = character_manager.get_character(client.get_active_character()?)?;
+    let offender_name = match character_manager.get_character(packet.offender) {
+        Ok(offender) => offender.name.clone(),
+        Err(_) => format!("offline character {}", packet.offender),
+    };
+    let reason = match &packet.complaint_type {
+        CMSG_COMPLAIN_SpamType::Mail { .. } => "spam mail".to_string(),
+        CMSG_COMPLAIN_SpamType::Chat { description, .. } => format!("spam chat: {}", description),
+    };
 
-    //Creating GM tickets is unhandled, there is no system in place. This function exists to
-    //prevent warning spam until a GM ticketing system is made
+    world.get_notifier().notify(Notification::PlayerReported {
+        reporter_name: reporter.name.clone(),
+        offender_name,
+        reason,
+    });
     Ok(())
 }
 
@@ -81,62 +152,682 @@ pub async fn handle_cmsg_gmticket_system_status(client_manager: &ClientManager,
 
 pub async fn handle_speed_command(
     client_manager: &ClientManager,
-    character_manager: &CharacterManager,
+    character_manager: &mut CharacterManager,
     client_id: SocketAddr,
     speed: f32,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
-    let character = character_manager.get_character(guid)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
 
     let clamped_speed = speed.clamp(0.1, 50.0);
+    character.set_run_speed(clamped_speed).await?;
 
-    let msg = SMSG_FORCE_RUN_SPEED_CHANGE {
-        guid: character.get_guid(),
-        move_event: 0,
-        speed: clamped_speed,
-        unknown: 0,
-    };
-    let event = ServerEvent::ForceRunSpeedChange(msg);
-    client.connection_sender.send_async(event).await?;
-
-    // Set run back speed to half of run speed
-    let back_msg = SMSG_FORCE_RUN_BACK_SPEED_CHANGE {
-        guid: character.get_guid(),
-        move_event: 0,
-        speed: clamped_speed * 0.5,
-    };
-    let back_event = ServerEvent::ForceRunBackSpeedChange(back_msg);
-    client.connection_sender.send_async(back_event).await?;
-
-    send_system_message(client_manager, character_manager, client_id, &format!("Speed set to {}", clamped_speed)).await?;
+    let reply = client_manager
+        .data_storage
+        .localize(client.data.locale, ServerString::SpeedSet, &[&clamped_speed]);
+    send_system_message(client_manager, character_manager, client_id, &reply).await?;
     Ok(())
 }
 
 pub async fn handle_additem_command(
+    data: &impl DataProvider,
     client_manager: &ClientManager,
     character_manager: &mut CharacterManager,
-    game_db: Arc<wrath_game_db::GameDatabase>,
-    realm_db: Arc<wrath_realm_db::RealmDatabase>,
+    world: &World,
     client_id: SocketAddr,
     item_id: u32,
 ) -> Result<()> {
-    if game_db.get_item_template(item_id).await.is_err() {
+    let realm_db = world.get_realm_database();
+    if !data.has_item_template(item_id).await? {
         return Ok(());
     }
 
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
+    let guid = client.get_active_character()?;
     let character = character_manager.get_character_mut(guid)?;
     let character_id = guid.guid() as u32;
 
     let Some(_slot_id) = character
-        .try_add_item_to_backpack(item_id, character_id, &client.connection_sender, Some(&realm_db))
+        .try_add_item_to_backpack(item_id, character_id, &client.connection_sender, Some(world.get_persistence_queue()))
         .await
     else {
         return Ok(());
     };
 
-    send_system_message(client_manager, character_manager, client_id, &format!("Added item {}", item_id)).await?;
+    //The backpack can be full, so this one is only known after the fact
+    log_gm_command(&realm_db, client.data.account_id, guid, AuditEvent::ItemCreated { item_id, count: 1 }).await;
+
+    let reply = client_manager
+        .data_storage
+        .localize(client.data.locale, ServerString::ItemAdded, &[&item_id]);
+    send_system_message(client_manager, character_manager, client_id, &reply).await?;
+    Ok(())
+}
+
+pub(super) fn parse_on_off(arg: Option<&str>) -> Option<bool> {
+    match arg.map(|a| a.to_lowercase()).as_deref() {
+        Some("on") => Some(true),
+        Some("off") => Some(false),
+        _ => None,
+    }
+}
+
+pub async fn handle_gm_mode_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    args: &[&str],
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
+    let data_storage = &client_manager.data_storage;
+    let locale = client.data.locale;
+    let on_off = |enabled: bool| data_storage.get_server_string(ServerString::on_off(enabled), locale);
+
+    let reply = match args {
+        [visibility, value] if visibility.eq_ignore_ascii_case("visible") => match parse_on_off(Some(value)) {
+            Some(visible) => {
+                character.set_gm_visible(visible);
+                data_storage.localize(locale, ServerString::GmVisibilityState, &[&on_off(visible)])
+            }
+            None => "Usage: .gm visible on/off".to_string(),
+        },
+        [value] => match parse_on_off(Some(value)) {
+            Some(enabled) => {
+                character.set_gm_mode(enabled);
+                data_storage.localize(locale, ServerString::GmModeState, &[&on_off(enabled)])
+            }
+            None => "Usage: .gm on/off".to_string(),
+        },
+        [] => data_storage.localize(
+            locale,
+            ServerString::GmStatus,
+            &[&on_off(character.is_gm_mode_enabled()), &on_off(!character.is_gm_invisible())],
+        ),
+        _ => "Usage: .gm on/off or .gm visible on/off".to_string(),
+    };
+
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+pub async fn handle_god_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    arg: Option<&str>,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
+
+    //Without an argument the command toggles god mode
+    let enabled = parse_on_off(arg).unwrap_or(!character.is_god_mode_enabled());
+    character.set_god_mode(enabled);
+
+    let data_storage = &client_manager.data_storage;
+    let locale = client.data.locale;
+    let state = data_storage.get_server_string(ServerString::on_off(enabled), locale);
+    let reply = data_storage.localize(locale, ServerString::GodModeState, &[&state]);
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+pub async fn handle_fly_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    arg: Option<&str>,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    let enabled = parse_on_off(arg).unwrap_or(!character.is_gm_fly_enabled());
+    character.set_gm_fly(enabled).await?;
+
+    let data_storage = &client_manager.data_storage;
+    let locale = client.data.locale;
+    let state = data_storage.get_server_string(ServerString::on_off(enabled), locale);
+    let reply = data_storage.localize(locale, ServerString::FlyState, &[&state]);
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+//Phases are a bit mask, so a GM can look at several phases at once
+pub async fn handle_modify_phase_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    phase_mask: u32,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    character.set_phase_mask(phase_mask).await?;
+
+    let reply = client_manager
+        .data_storage
+        .localize(client.data.locale, ServerString::PhaseSet, &[&phase_mask]);
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+//.modify currency <honor|arena|item id> <amount>, a negative amount takes currency away
+pub async fn handle_modify_currency_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    currency: u32,
+    amount: i64,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    let data_storage = &client_manager.data_storage;
+    let realm_db = world.get_realm_database();
+
+    //Currencies are items, so they are audited like any other item the command hands out or takes away
+    let count = u32::try_from(amount.unsigned_abs()).unwrap_or(u32::MAX);
+    let event = if amount >= 0 {
+        AuditEvent::ItemCreated { item_id: currency, count }
+    } else {
+        AuditEvent::ItemDestroyed { item_id: currency, count }
+    };
+    log_gm_command(&realm_db, client.data.account_id, character.get_guid(), event).await;
+
+    let reply = match character.modify_currency(&realm_db, data_storage, currency, amount).await {
+        Ok(count) => data_storage.localize(client.data.locale, ServerString::CurrencySet, &[&currency, &count]),
+        Err(e) => {
+            warn!("Failed to modify currency {} of {}: {}", currency, character.name, e);
+            data_storage.localize(client.data.locale, ServerString::NotACurrency, &[&currency])
+        }
+    };
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+//Testing helper, the character knows every flight path afterwards and keeps them after relogging
+pub async fn handle_taxi_all_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    let all_nodes = client_manager.data_storage.get_taxi_node_ids();
+    let unlocked = character.learn_taxi_nodes(&world.get_realm_database(), &all_nodes).await?;
+
+    let reply = client_manager
+        .data_storage
+        .localize(client.data.locale, ServerString::TaxiNodesUnlocked, &[&unlocked]);
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+//Where .gmisland takes you, the island in the far north east corner of Kalimdor
+const GM_ISLAND_AREA: u32 = 876;
+const GM_ISLAND_POSITION: Vector3d = Vector3d {
+    x: 16222.1,
+    y: 16252.1,
+    z: 12.5872,
+};
+const GM_ISLAND_ORIENTATION: f32 = 1.35;
+
+async fn audited_gm_teleport(client: &Client, character: &mut Character, world: &World, destination: WorldZoneLocation) -> Result<()> {
+    let realm_db = world.get_realm_database();
+    let event = AuditEvent::Teleported {
+        map: destination.map.as_int(),
+    };
+    log_gm_command(&realm_db, client.data.account_id, character.get_guid(), event).await;
+    character.gm_teleport(&realm_db, destination).await
+}
+
+pub async fn handle_recall_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    //Recalling doesn't overwrite the recall location, so after jumping around it still leads back to where the GM started
+    if let Some(location) = character.get_recall_location().cloned() {
+        let event = AuditEvent::Teleported { map: location.map.as_int() };
+        log_gm_command(&world.get_realm_database(), client.data.account_id, character.get_guid(), event).await;
+        character.teleport_to(TeleportationDistance::Far(location));
+        return Ok(());
+    }
+
+    let reply = client_manager
+        .data_storage
+        .localize(client.data.locale, ServerString::NoRecallLocation, &[]);
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+pub async fn handle_start_command(
+    data: &impl DataProvider,
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    let destination = get_start_location(data, character.get_race().as_int(), character.get_class().as_int(), character.area).await?;
+    audited_gm_teleport(client, character, world, destination).await
+}
+
+//Where new characters of the race and class enter the world, unknown zones fall back to the given area
+pub async fn get_start_location(data: &impl DataProvider, race: u8, class: u8, fallback_area: Area) -> Result<WorldZoneLocation> {
+    let create_info = data.get_player_create_info(race, class).await?;
+    Ok(WorldZoneLocation {
+        map: Map::try_from(create_info.map as u32)?,
+        area: Area::try_from(create_info.zone as u32).unwrap_or(fallback_area),
+        position: Vector3d {
+            x: create_info.position_x,
+            y: create_info.position_y,
+            z: create_info.position_z,
+        },
+        orientation: create_info.orientation,
+    })
+}
+
+pub async fn handle_gmisland_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    let destination = WorldZoneLocation {
+        map: Map::Kalimdor,
+        area: Area::try_from(GM_ISLAND_AREA).unwrap_or(character.area),
+        position: GM_ISLAND_POSITION,
+        orientation: GM_ISLAND_ORIENTATION,
+    };
+    audited_gm_teleport(client, character, world, destination).await
+}
+
+//Every map in Map.dbc as a teleport destination, at the entrance of its first area trigger or else the map's origin
+fn map_destinations(data_storage: &DataStorage, area: Area) -> Vec<WorldZoneLocation> {
+    data_storage
+        .get_map_ids()
+        .into_iter()
+        .filter_map(|map_id| {
+            let Ok(map) = Map::try_from(map_id) else {
+                warn!("Map {} from Map.dbc is not known to wow_messages, skipping it", map_id);
+                return None;
+            };
+            let entrance = data_storage.get_map_entrance(map_id).unwrap_or_default();
+            Some(WorldZoneLocation {
+                map,
+                area,
+                position: entrance.position,
+                orientation: entrance.orientation,
+            })
+        })
+        .collect()
+}
+
+pub async fn handle_tele_map_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    map_id: u32,
+    position: Vector3d,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let known_map = client_manager
+        .data_storage
+        .get_map_ids()
+        .contains(&map_id)
+        .then(|| Map::try_from(map_id).ok())
+        .flatten();
+    let Some(map) = known_map else {
+        let reply = client_manager
+            .data_storage
+            .localize(client.data.locale, ServerString::UnknownMap, &[&map_id]);
+        return send_system_message(client_manager, character_manager, client_id, &reply).await;
+    };
+
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    let destination = WorldZoneLocation {
+        map,
+        area: character.area,
+        position,
+        orientation: character.movement_info.orientation,
+    };
+    audited_gm_teleport(client, character, world, destination).await
+}
+
+pub async fn handle_tele_random_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    let destinations = map_destinations(&client_manager.data_storage, character.area);
+    let Some(destination) = random::with_rng(|rng| destinations.choose(rng).cloned()) else {
+        return Ok(());
+    };
+    audited_gm_teleport(client, character, world, destination).await
+}
+
+//Smoke test for map creation and teardown, see character_map_tour
+pub async fn handle_tele_tour_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    start: bool,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    if !start {
+        character.stop_map_tour();
+        return Ok(());
+    }
+
+    let destinations = map_destinations(&client_manager.data_storage, character.area);
+    let map_count = destinations.len();
+    if let Some(first_stop) = character.start_map_tour(destinations) {
+        audited_gm_teleport(client, character, world, first_stop).await?;
+    }
+
+    let reply = client_manager
+        .data_storage
+        .localize(client.data.locale, ServerString::MapTourStarted, &[&map_count]);
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+pub async fn handle_server_info_command(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let uptime = world.get_uptime();
+    let max_players_ever = world.get_realm_database().get_max_players_ever().await?;
+    //The row of the current run is only written every few minutes, it may not have caught up yet
+    let max_players_ever = max_players_ever.max(uptime.max_players());
+
+    let reply = client_manager.data_storage.localize(
+        client.data.locale,
+        ServerString::ServerInfo,
+        &[
+            &revision(),
+            &format_uptime(uptime.uptime_seconds()),
+            &character_manager.get_all_characters().count(),
+            &uptime.max_players(),
+            &max_players_ever,
+        ],
+    );
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+pub async fn handle_motd_command(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    realm_db: Arc<wrath_realm_db::RealmDatabase>,
+    client_id: SocketAddr,
+    new_motd: &str,
+) -> Result<()> {
+    let locale = client_manager.get_authenticated_client(client_id)?.data.locale;
+    let reply = if new_motd.is_empty() {
+        let motd = realm_db.get_motd(locale.code()).await?.unwrap_or_default();
+        client_manager.data_storage.localize(locale, ServerString::CurrentMotd, &[&motd])
+    } else {
+        realm_db.set_motd(new_motd).await?;
+        info!("Message of the day changed to: {}", new_motd);
+        client_manager.data_storage.localize(locale, ServerString::MotdUpdated, &[])
+    };
+
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+pub async fn handle_lookup_player_command(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    realm_db: Arc<wrath_realm_db::RealmDatabase>,
+    client_id: SocketAddr,
+    character_name: &str,
+) -> Result<()> {
+    let Some(account_id) = realm_db.get_account_id_for_character_name(character_name).await? else {
+        return send_system_message(
+            client_manager,
+            character_manager,
+            client_id,
+            &format!("No character named {}", character_name),
+        )
+        .await;
+    };
+
+    let mut lines = vec![format!("Character {} belongs to account {}", character_name, account_id)];
+
+    for session in realm_db.get_recent_account_sessions(account_id, 5).await? {
+        let login = chrono::DateTime::from_timestamp(session.login_time as i64, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let duration = match session.duration {
+            Some(seconds) => format!("{}m{}s", seconds / 60, seconds % 60),
+            None => "still online".to_string(),
+        };
+        lines.push(format!(
+            "{} {} from {} (build {}), {}",
+            login, session.character_name, session.ip, session.client_build, duration
+        ));
+    }
+
+    //Ack violations are counted per session, a character with many of them is likely running a movement cheat
+    if let Ok(online_client) = client_manager.find_client_from_active_character_name(character_name, character_manager) {
+        let online = character_manager.get_character(online_client.get_active_character()?)?;
+        lines.push(format!("Online with {} movement ack violations", online.get_movement_ack_violations()));
+    }
+
+    let shared_ip_accounts = realm_db.get_accounts_sharing_ip(account_id).await?;
+    if !shared_ip_accounts.is_empty() {
+        let accounts: Vec<String> = shared_ip_accounts.iter().map(|id| id.to_string()).collect();
+        lines.push(format!("Accounts sharing an IP: {}", accounts.join(", ")));
+    }
+
+    for line in lines {
+        send_system_message(client_manager, character_manager, client_id, &line).await?;
+    }
     Ok(())
 }
+
+//Mutes the named character for the given number of seconds, or unmutes it when no duration is given
+pub async fn handle_mute_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    character_name: &str,
+    seconds: Option<u64>,
+) -> Result<()> {
+    let data_storage = &client_manager.data_storage;
+    let locale = client_manager.get_authenticated_client(client_id)?.data.locale;
+
+    let Ok(target_client) = client_manager.find_client_from_active_character_name(character_name, character_manager) else {
+        let reply = data_storage.localize(locale, ServerString::PlayerNotFound, &[&character_name]);
+        return send_system_message(client_manager, character_manager, client_id, &reply).await;
+    };
+    let target = character_manager.get_character_mut(target_client.get_active_character()?)?;
+
+    let reply = match seconds {
+        Some(seconds) => {
+            target.mute_chat(std::time::Duration::from_secs(seconds));
+            info!("{} was muted for {} seconds", target.name, seconds);
+            data_storage.localize(locale, ServerString::PlayerMuted, &[&target.name, &seconds])
+        }
+        None => {
+            target.unmute_chat();
+            info!("{} was unmuted", target.name);
+            data_storage.localize(locale, ServerString::PlayerUnmuted, &[&target.name])
+        }
+    };
+
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+//Brings the named character's whole group to the GM, without asking. Members that are dead, fighting or inside
+//another copy of the GM's instance are left behind.
+pub async fn handle_groupsummon_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    character_name: &str,
+) -> Result<()> {
+    let data_storage = &client_manager.data_storage;
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let locale = client.data.locale;
+    let Ok(target_client) = client_manager.find_client_from_active_character_name(character_name, character_manager) else {
+        let reply = data_storage.localize(locale, ServerString::PlayerNotFound, &[&character_name]);
+        return send_system_message(client_manager, character_manager, client_id, &reply).await;
+    };
+    let Some(members) = world.get_group_members(target_client.get_active_character()?) else {
+        let reply = data_storage.localize(locale, ServerString::NotInGroup, &[&character_name]);
+        return send_system_message(client_manager, character_manager, client_id, &reply).await;
+    };
+
+    let gm_guid = client.get_active_character()?;
+    let gm = character_manager.get_character(gm_guid)?;
+    let destination = WorldZoneLocation {
+        map: gm.map,
+        area: gm.area,
+        position: gm.movement_info.position,
+        orientation: gm.movement_info.orientation,
+    };
+    let instance_id = gm.instance_id;
+
+    let mut summoned = 0;
+    for member_guid in members.into_iter().filter(|&guid| guid != gm_guid) {
+        let Some(member) = character_manager.find_character_mut(member_guid) else {
+            continue;
+        };
+        //GMs don't care about the level requirements of portals
+        if check_summon(&crate::handlers::get_summon_conditions(member, &destination, 0, instance_id)).is_ok() {
+            member.teleport_to(TeleportationDistance::Far(destination.clone()));
+            summoned += 1;
+        }
+    }
+
+    let reply = data_storage.localize(locale, ServerString::GroupSummoned, &[&summoned, &character_name]);
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+//.deserter <add|remove> <deserter|cooldown> <name>, for when a battleground punished someone it shouldn't have
+pub async fn handle_deserter_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    add: bool,
+    punishment: QueuePunishment,
+    character_name: &str,
+) -> Result<()> {
+    let data_storage = &client_manager.data_storage;
+    let locale = client_manager.get_authenticated_client(client_id)?.data.locale;
+    let Ok(target_client) = client_manager.find_client_from_active_character_name(character_name, character_manager) else {
+        let reply = data_storage.localize(locale, ServerString::PlayerNotFound, &[&character_name]);
+        return send_system_message(client_manager, character_manager, client_id, &reply).await;
+    };
+    let target_guid = target_client.get_active_character()?;
+    let realm_db = world.get_realm_database();
+
+    let target = character_manager.get_character_mut(target_guid)?;
+    let reply = if add {
+        if let Some(aura) = target.add_queue_punishment(&realm_db, punishment).await? {
+            spell::auras::send_applied_aura(target_guid, &aura, character_manager, world).await?;
+        }
+        data_storage.localize(locale, ServerString::QueuePunishmentAdded, &[&character_name, &punishment.spell_id()])
+    } else {
+        let slots = target.remove_queue_punishment(&realm_db, punishment).await?;
+        spell::auras::send_removed_aura_slots(target_guid, slots, character_manager, world).await?;
+        data_storage.localize(locale, ServerString::QueuePunishmentRemoved, &[&punishment.spell_id(), &character_name])
+    };
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+//Bans the account the named character belongs to, for the given duration or for good, and kicks it out of the world right away
+pub async fn handle_ban_command(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    realm_db: Arc<wrath_realm_db::RealmDatabase>,
+    client_id: SocketAddr,
+    character_name: &str,
+    duration: Option<std::time::Duration>,
+    reason: &str,
+) -> Result<()> {
+    let data_storage = &client_manager.data_storage;
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let locale = client.data.locale;
+    let Some(account_id) = realm_db.get_account_id_for_character_name(character_name).await? else {
+        let reply = format!("No character named {}", character_name);
+        return send_system_message(client_manager, character_manager, client_id, &reply).await;
+    };
+
+    let gm_guid = client.get_active_character()?;
+    log_gm_command(&realm_db, client.data.account_id, gm_guid, AuditEvent::AccountBanned { account_id }).await;
+
+    let banned_by = character_manager.get_character(gm_guid)?.name.clone();
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+    client_manager
+        .auth_db
+        .add_account_ban(&wrath_auth_db::DBAccountBanCreate {
+            account_id,
+            ban_time: now,
+            unban_time: duration.map_or(0, |duration| now + duration.as_secs()),
+            banned_by: banned_by.clone(),
+            reason: reason.to_string(),
+        })
+        .await?;
+    let kicked = client_manager.disconnect_account(account_id).await;
+    info!(
+        "{} banned account {} of {} ({:?}), {} connections kicked: {}",
+        banned_by, account_id, character_name, duration, kicked, reason
+    );
+
+    let reply = match duration {
+        Some(duration) => data_storage.localize(locale, ServerString::AccountSuspended, &[&character_name, &duration.as_secs()]),
+        None => data_storage.localize(locale, ServerString::AccountBanned, &[&character_name]),
+    };
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
+
+//Lifts the in-game bans of the account the named character belongs to. Console bans are lifted from the auth server console.
+pub async fn handle_unban_command(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    realm_db: Arc<wrath_realm_db::RealmDatabase>,
+    client_id: SocketAddr,
+    character_name: &str,
+) -> Result<()> {
+    let data_storage = &client_manager.data_storage;
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let locale = client.data.locale;
+    let Some(account_id) = realm_db.get_account_id_for_character_name(character_name).await? else {
+        let reply = format!("No character named {}", character_name);
+        return send_system_message(client_manager, character_manager, client_id, &reply).await;
+    };
+    log_gm_command(
+        &realm_db,
+        client.data.account_id,
+        client.get_active_character()?,
+        AuditEvent::AccountUnbanned { account_id },
+    )
+    .await;
+
+    let reply = if client_manager.auth_db.lift_account_bans(account_id).await? {
+        info!("Bans of account {} of {} lifted", account_id, character_name);
+        data_storage.localize(locale, ServerString::AccountUnbanned, &[&character_name])
+    } else {
+        data_storage.localize(locale, ServerString::AccountNotBanned, &[&character_name])
+    };
+    send_system_message(client_manager, character_manager, client_id, &reply).await
+}
@@ -0,0 +1,125 @@
+use std::net::SocketAddr;
+
+use wow_world_messages::wrath::{
+    GossipItem, CMSG_GOSSIP_HELLO, CMSG_GOSSIP_SELECT_OPTION, SMSG_GOSSIP_COMPLETE, SMSG_GOSSIP_MESSAGE, SMSG_GOSSIP_POI,
+};
+
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::client_manager::ClientManager;
+use crate::combat::damage;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::world::creature_manager::SharedCreature;
+use crate::world::points_of_interest::PointOfInterest;
+use crate::world::prelude::unit_flags::UnitNpcFlags;
+use crate::world::World;
+
+//A little more than the client's interaction range, positions lag behind a bit
+const GOSSIP_DISTANCE: f32 = 10.0;
+
+//There is no npc_text yet, the client shows its generic greeting for this id
+const DEFAULT_GOSSIP_TEXT: u32 = 0x00FF_FFFF;
+
+//Living gossip creatures close enough to talk to
+async fn find_gossip_npc(character: &Character, npc: Guid, world: &World) -> Option<SharedCreature> {
+    let map = world.get_instance_manager().try_get_map_for_character(character)?;
+    let creature = map.get_creature(npc)?.clone();
+    {
+        let npc = creature.read().await;
+        let npc_flags = npc.gameplay_data.unit_npc_flags().unwrap_or(0);
+        if !npc.is_alive() || npc_flags & UnitNpcFlags::Gossip as i32 == 0 {
+            return None;
+        }
+        if damage::distance(character.movement_info.position, npc.movement_info.position) > GOSSIP_DISTANCE {
+            return None;
+        }
+    }
+    Some(creature)
+}
+
+pub async fn handle_cmsg_gossip_hello(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_GOSSIP_HELLO,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let Some(npc) = find_gossip_npc(character, data.guid, world).await else {
+        return Ok(());
+    };
+    let entry = npc.read().await.entry;
+
+    let gossips = world
+        .get_gossip_menus()
+        .get_options(entry)
+        .iter()
+        .map(|option| GossipItem {
+            id: option.option_id,
+            item_icon: option.icon,
+            coded: false,
+            money_required: 0,
+            message: option.text.clone(),
+            accept_text: String::new(),
+        })
+        .collect();
+
+    //Every creature entry has one menu, so the entry doubles as the menu id
+    ServerEvent::GossipMessage(SMSG_GOSSIP_MESSAGE {
+        guid: data.guid,
+        menu_id: entry,
+        title_text_id: DEFAULT_GOSSIP_TEXT,
+        gossips,
+        quests: vec![],
+    })
+    .send_to_character(character)
+    .await
+}
+
+//Options only point at a map marker for now, picking one closes the window either way
+pub async fn handle_cmsg_gossip_select_option(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_GOSSIP_SELECT_OPTION,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let Some(npc) = find_gossip_npc(character, data.guid, world).await else {
+        return Ok(());
+    };
+    let entry = npc.read().await.entry;
+
+    let poi_id = world
+        .get_gossip_menus()
+        .get_option(entry, data.gossip_list_id)
+        .map_or(0, |option| option.poi_id);
+    if poi_id != 0 {
+        send_point_of_interest(world, character, poi_id).await?;
+    }
+    ServerEvent::GossipComplete(SMSG_GOSSIP_COMPLETE).send_to_character(character).await
+}
+
+//Puts a marker on the character's world map, the client replaces any marker it got before
+pub async fn send_gossip_poi(character: &Character, point: &PointOfInterest) -> Result<()> {
+    ServerEvent::GossipPoi(SMSG_GOSSIP_POI {
+        flags: point.flags,
+        position: point.position,
+        icon: point.icon,
+        data: point.importance,
+        location_name: point.name.clone(),
+    })
+    .send_to_character(character)
+    .await
+}
+
+pub async fn send_point_of_interest(world: &World, character: &Character, poi_id: u32) -> Result<()> {
+    let point = world
+        .get_points_of_interest()
+        .get(poi_id)
+        .ok_or_else(|| anyhow!("Point of interest {} does not exist", poi_id))?;
+    send_gossip_poi(character, point).await
+}
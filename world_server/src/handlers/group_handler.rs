@@ -1,8 +1,29 @@
 use std::net::SocketAddr;
+use std::time::{Duration, Instant};
 
-use wow_world_messages::wrath::SMSG_RAID_INSTANCE_INFO;
+use wow_world_base::wrath::Language;
+use wow_world_messages::wrath::{
+    DungeonDifficulty, GroupListMember, GroupLootSetting, GroupType, ItemQuality, PartyOperation, PartyResult, PlayerInviteStatus, RaidDifficulty,
+    SMSG_GROUP_LIST_group_not_empty, SMSG_MESSAGECHAT_ChatType, CMSG_GROUP_INVITE, CMSG_LOOT_METHOD, CMSG_LOOT_ROLL, CMSG_SUMMON_RESPONSE,
+    SMSG_GROUP_DECLINE, SMSG_GROUP_DESTROYED, SMSG_GROUP_INVITE, SMSG_GROUP_LIST, SMSG_GROUP_SET_LEADER, SMSG_MESSAGECHAT, SMSG_PARTY_COMMAND_RESULT,
+    SMSG_RAID_INSTANCE_INFO, SMSG_SUMMON_REQUEST,
+};
 
-use crate::{client_manager::ClientManager, connection::events::ServerEvent, prelude::*};
+use crate::{
+    character::character_manager::CharacterManager,
+    character::character_summon::{check_summon, SummonConditions, SUMMON_TIMEOUT},
+    character::Character,
+    chat::language,
+    client_manager::ClientManager,
+    connection::events::ServerEvent,
+    data::WorldZoneLocation,
+    handlers::movement_handler::{TeleportationDistance, TeleportationState},
+    prelude::*,
+    world::character_info_cache::CharacterLookup,
+    world::group_loot::LootMethod,
+    world::groups::{GroupError, GroupLeft},
+    world::World,
+};
 
 pub async fn handle_cmsg_request_raid_info(client_manager: &ClientManager, client_id: SocketAddr) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
@@ -12,3 +33,404 @@ pub async fn handle_cmsg_request_raid_info(client_manager: &ClientManager, clien
     client.connection_sender.send_async(event).await?;
     Ok(())
 }
+
+pub async fn handle_cmsg_loot_roll(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    data: &CMSG_LOOT_ROLL,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+
+    //The client only offers the buttons it's allowed to press, anything else is a stale or forged packet
+    if let Err(error) = world
+        .get_loot_rolls_mut()
+        .vote(data.item, data.item_slot, guid, data.vote, character_manager)
+        .await
+    {
+        warn!("{} couldn't vote {:?} on loot slot {}: {:?}", guid, data.vote, data.item_slot, error);
+    }
+    Ok(())
+}
+
+//Only the leader picks how the group loots, every member sees the new rules in the group list
+pub async fn handle_cmsg_loot_method(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    data: &CMSG_LOOT_METHOD,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character(guid)?;
+    let Some(group_id) = world.get_groups().get_group_id(guid) else {
+        return send_party_command_result(character, PartyOperation::Leave, "", PartyResult::NotInGroup).await;
+    };
+    let Some(group) = world.get_groups_mut().get_group_mut(group_id) else {
+        return Ok(());
+    };
+    if group.leader != guid {
+        return send_party_command_result(character, PartyOperation::Leave, "", PartyResult::NotLeader).await;
+    }
+    //The master looter has to be in the group
+    let master_looter = Some(data.loot_master).filter(|&master| master != Guid::zero() && group.get_members().contains(&master));
+    group
+        .loot_rules
+        .set(loot_method(data.loot_setting), master_looter, data.loot_threshold as u8);
+    send_group_list_update(guid, character_manager, world).await
+}
+
+//instance_id is the copy of the destination the group is going to
+pub fn get_summon_conditions(member: &Character, destination: &WorldZoneLocation, required_level: u8, instance_id: u32) -> SummonConditions {
+    SummonConditions {
+        is_alive: member.is_alive(),
+        in_combat: member.get_melee_victim().is_some(),
+        teleporting: member.teleportation_state != TeleportationState::None,
+        level: member.gameplay_data.unit_level().unwrap_or(1) as u8,
+        required_level,
+        on_destination_map: member.map == destination.map,
+        same_instance: member.instance_id == instance_id,
+    }
+}
+
+//When the leader takes an instance portal the rest of the group is asked to follow, members that can't come along
+//aren't asked at all
+pub async fn offer_group_follow(
+    leader_guid: Guid,
+    destination: &WorldZoneLocation,
+    required_level: u8,
+    character_manager: &mut CharacterManager,
+    world: &World,
+) -> Result<()> {
+    let Some(members) = world.get_group_members(leader_guid) else {
+        return Ok(());
+    };
+    if members.first() != Some(&leader_guid) {
+        return Ok(());
+    }
+
+    let instance_id = character_manager.get_character(leader_guid)?.instance_id;
+    for member_guid in members.into_iter().skip(1) {
+        let Some(member) = character_manager.find_character_mut(member_guid) else {
+            continue;
+        };
+        if let Err(refusal) = check_summon(&get_summon_conditions(member, destination, required_level, instance_id)) {
+            trace!(
+                "{} can't follow {} into map {:?}: {:?}",
+                member.name,
+                leader_guid,
+                destination.map,
+                refusal
+            );
+            continue;
+        }
+
+        member.offer_summon(leader_guid, destination.clone());
+        ServerEvent::SummonRequest(SMSG_SUMMON_REQUEST {
+            summoner: leader_guid,
+            area: destination.area,
+            auto_decline_time: Duration::from_secs_f32(SUMMON_TIMEOUT),
+        })
+        .send_to_character(member)
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn handle_cmsg_summon_response(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    data: &CMSG_SUMMON_RESPONSE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    if !data.agree {
+        character.decline_summon();
+        return Ok(());
+    }
+
+    //Nothing to accept if the summon timed out or was never offered
+    let Some(destination) = character.take_summon(data.summoner) else {
+        return Ok(());
+    };
+    //The dialog may have been open for a while, the character can have died or started a fight since
+    if !character.is_alive() || character.get_melee_victim().is_some() || character.teleportation_state != TeleportationState::None {
+        return Ok(());
+    }
+    character.teleport_to(TeleportationDistance::Far(destination));
+    Ok(())
+}
+
+async fn send_party_command_result(character: &Character, operation: PartyOperation, name: &str, result: PartyResult) -> Result<()> {
+    ServerEvent::PartyCommandResult(SMSG_PARTY_COMMAND_RESULT {
+        operation,
+        member: name.to_string(),
+        result,
+    })
+    .send_to_character(character)
+    .await
+}
+
+fn party_result(error: GroupError) -> PartyResult {
+    match error {
+        GroupError::AlreadyInGroup | GroupError::AlreadyInvited => PartyResult::AlreadyInGroup,
+        GroupError::GroupFull => PartyResult::GroupFull,
+        GroupError::NotLeader => PartyResult::NotLeader,
+        GroupError::NotInvited => PartyResult::NotInGroup,
+        GroupError::InviteSelf => PartyResult::BadPlayerName,
+    }
+}
+
+fn loot_method(setting: GroupLootSetting) -> LootMethod {
+    match setting {
+        GroupLootSetting::FreeForAll => LootMethod::FreeForAll,
+        GroupLootSetting::RoundRobin => LootMethod::RoundRobin,
+        GroupLootSetting::MasterLoot => LootMethod::MasterLoot,
+        GroupLootSetting::GroupLoot => LootMethod::GroupLoot,
+        GroupLootSetting::NeedBeforeGreed => LootMethod::NeedBeforeGreed,
+    }
+}
+
+fn loot_setting(method: LootMethod) -> GroupLootSetting {
+    match method {
+        LootMethod::FreeForAll => GroupLootSetting::FreeForAll,
+        LootMethod::RoundRobin => GroupLootSetting::RoundRobin,
+        LootMethod::MasterLoot => GroupLootSetting::MasterLoot,
+        LootMethod::GroupLoot => GroupLootSetting::GroupLoot,
+        LootMethod::NeedBeforeGreed => GroupLootSetting::NeedBeforeGreed,
+    }
+}
+
+//Members can be offline, their names come from the character info cache
+fn member_name(world: &World, guid: Guid) -> String {
+    match world.get_character_info_cache().lookup(guid, Instant::now()) {
+        CharacterLookup::Known(info) => info.name.clone(),
+        _ => String::new(),
+    }
+}
+
+//What a character that isn't in a group gets, the client closes its party frames
+fn empty_group_list() -> SMSG_GROUP_LIST {
+    SMSG_GROUP_LIST {
+        group_type: GroupType::Normal,
+        group_id: 0,
+        flags: 0,
+        roles: 0,
+        group: Guid::zero(),
+        counter: 0,
+        members: vec![],
+        leader: Guid::zero(),
+        group_not_empty: None,
+    }
+}
+
+//Every online member gets the group as seen by them, the list a member gets leaves that member out
+pub async fn send_group_list_update(guid: Guid, character_manager: &CharacterManager, world: &mut World) -> Result<()> {
+    let Some(group_id) = world.get_groups().get_group_id(guid) else {
+        return Ok(());
+    };
+    let Some(group) = world.get_groups_mut().get_group_mut(group_id) else {
+        return Ok(());
+    };
+    let counter = group.next_update_counter();
+    let members = group.get_members();
+    let (leader, loot_rules) = (group.leader, &group.loot_rules);
+    let group_not_empty = SMSG_GROUP_LIST_group_not_empty {
+        loot_setting: loot_setting(loot_rules.method),
+        master_loot: loot_rules.master_looter.unwrap_or(Guid::zero()),
+        loot_threshold: ItemQuality::try_from(loot_rules.threshold).unwrap_or(ItemQuality::Uncommon),
+        difficulty: DungeonDifficulty::Normal,
+        raid_difficulty: RaidDifficulty::Normal10Man,
+        heroic: 0,
+    };
+
+    let list_members: Vec<GroupListMember> = members
+        .iter()
+        .map(|&member| GroupListMember {
+            name: member_name(world, member),
+            guid: member,
+            is_online: character_manager.find_character(member).is_some(),
+            group_id: 0,
+            flags: 0,
+            roles: 0,
+        })
+        .collect();
+    for receiver in members.iter().filter_map(|&member| character_manager.find_character(member)) {
+        ServerEvent::GroupList(SMSG_GROUP_LIST {
+            group_type: GroupType::Normal,
+            group_id: 0,
+            flags: 0,
+            roles: 0,
+            group: Guid::new(group_id as u64),
+            counter,
+            members: list_members.iter().filter(|member| member.guid != receiver.get_guid()).cloned().collect(),
+            leader,
+            group_not_empty: Some(group_not_empty.clone()),
+        })
+        .send_to_character(receiver)
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn handle_cmsg_group_invite(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    data: &CMSG_GROUP_INVITE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    //Invites are answered in game, only characters that are online can get one
+    let invitee = world
+        .get_character_info_cache()
+        .find_by_name(&data.name)
+        .filter(|info| info.online)
+        .and_then(|info| character_manager.find_character(info.guid));
+    let Some(invitee) = invitee else {
+        return send_party_command_result(character, PartyOperation::Invite, &data.name, PartyResult::BadPlayerName).await;
+    };
+    if super::social_handler::is_alliance(invitee.get_race()) != super::social_handler::is_alliance(character.get_race()) {
+        return send_party_command_result(character, PartyOperation::Invite, &invitee.name, PartyResult::PlayerWrongFaction).await;
+    }
+    if invitee.is_ignoring(character.get_guid()) {
+        return send_party_command_result(character, PartyOperation::Invite, &invitee.name, PartyResult::IgnoringYou).await;
+    }
+
+    if let Err(e) = world.get_groups_mut().invite(character.get_guid(), invitee.get_guid()) {
+        //The invitee still hears about it, so it knows someone wanted it along
+        if e == GroupError::AlreadyInGroup {
+            ServerEvent::GroupInvite(SMSG_GROUP_INVITE {
+                status: PlayerInviteStatus::InGroup,
+                name: character.name.clone(),
+                unknown1: 0,
+                count: 0,
+                unknown2: 0,
+            })
+            .send_to_character(invitee)
+            .await?;
+        }
+        return send_party_command_result(character, PartyOperation::Invite, &invitee.name, party_result(e)).await;
+    }
+    ServerEvent::GroupInvite(SMSG_GROUP_INVITE {
+        status: PlayerInviteStatus::NotInGroup,
+        name: character.name.clone(),
+        unknown1: 0,
+        count: 0,
+        unknown2: 0,
+    })
+    .send_to_character(invitee)
+    .await?;
+    send_party_command_result(character, PartyOperation::Invite, &invitee.name, PartyResult::Success).await
+}
+
+pub async fn handle_cmsg_group_accept(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    if let Err(e) = world.get_groups_mut().accept_invite(character.get_guid()) {
+        return send_party_command_result(character, PartyOperation::Invite, "", party_result(e)).await;
+    }
+    send_group_list_update(character.get_guid(), character_manager, world).await
+}
+
+pub async fn handle_cmsg_group_decline(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    let Some(inviter) = world.get_groups_mut().decline_invite(character.get_guid()) else {
+        return Ok(());
+    };
+    let Some(inviter) = character_manager.find_character(inviter) else {
+        return Ok(());
+    };
+    ServerEvent::GroupDecline(SMSG_GROUP_DECLINE {
+        name: character.name.clone(),
+    })
+    .send_to_character(inviter)
+    .await
+}
+
+//Leaving through the party frame, the leader's disband option sends the same. Only the sender leaves, the rest carry on
+//under a new leader unless too few are left to be a group.
+pub async fn handle_cmsg_group_disband(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    match world.get_groups_mut().leave(character.get_guid()) {
+        None => send_party_command_result(character, PartyOperation::Leave, "", PartyResult::NotInGroup).await,
+        Some(GroupLeft::Disbanded { members }) => {
+            for member in members.into_iter().filter_map(|member| character_manager.find_character(member)) {
+                ServerEvent::GroupDestroyed(SMSG_GROUP_DESTROYED {}).send_to_character(member).await?;
+                ServerEvent::GroupList(empty_group_list()).send_to_character(member).await?;
+            }
+            Ok(())
+        }
+        Some(GroupLeft::Left { group_id, new_leader }) => {
+            ServerEvent::GroupList(empty_group_list()).send_to_character(character).await?;
+            let Some(remaining) = world.get_groups().get_group(group_id).map(|group| group.get_members()) else {
+                return Ok(());
+            };
+            if let Some(new_leader) = new_leader {
+                let name = member_name(world, new_leader);
+                let event = ServerEvent::GroupSetLeader(SMSG_GROUP_SET_LEADER { name });
+                for member in remaining.iter().filter_map(|&member| character_manager.find_character(member)) {
+                    event.send_to_character(member).await?;
+                }
+            }
+            send_group_list_update(remaining[0], character_manager, world).await
+        }
+    }
+}
+
+//Party chat goes to the online members of the sender's group, the sender included
+pub async fn send_party_chat(
+    sender: &Character,
+    character_manager: &CharacterManager,
+    world: &World,
+    language: Language,
+    message: &str,
+) -> Result<()> {
+    let Some(members) = world.get_group_members(sender.get_guid()) else {
+        return send_party_command_result(sender, PartyOperation::Invite, "", PartyResult::NotInGroup).await;
+    };
+
+    let listeners = members
+        .into_iter()
+        .filter_map(|member| character_manager.find_character(member))
+        .filter(|listener| !listener.is_ignoring(sender.get_guid()));
+    for listener in listeners {
+        ServerEvent::MessageChat(SMSG_MESSAGECHAT {
+            chat_type: SMSG_MESSAGECHAT_ChatType::Party { target6: sender.get_guid() },
+            language,
+            sender: sender.get_guid(),
+            flags: 0,
+            message: language::message_for_listener(listener, language, message),
+            tag: sender.get_chat_tag(),
+        })
+        .send_to_character(listener)
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,513 @@
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use crate::character::character_guild::GuildNotification;
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::chat::language;
+use crate::client_manager::ClientManager;
+use crate::connection::events::ServerEvent;
+use crate::data::DataStorage;
+use crate::localization::ServerString;
+use crate::prelude::*;
+use crate::world::character_info_cache::CharacterLookup;
+use crate::world::guilds::{
+    GuildError, GUILD_RIGHT_CHAT_LISTEN, GUILD_RIGHT_CHAT_SPEAK, GUILD_RIGHT_OFFICER_CHAT_LISTEN, GUILD_RIGHT_OFFICER_CHAT_SPEAK,
+};
+use crate::world::World;
+use wow_world_base::wrath::Language;
+use wow_world_messages::wrath::{
+    Area, Gold, GuildBankRights, GuildCommand, GuildCommandResult, GuildEvent, GuildMember, GuildMember_GuildMemberStatus, GuildRights, Level,
+    SMSG_MESSAGECHAT_ChatType, CMSG_GUILD_CREATE, CMSG_GUILD_INVITE, CMSG_GUILD_MOTD, CMSG_GUILD_QUERY, SMSG_GUILD_COMMAND_RESULT,
+    SMSG_GUILD_DECLINE, SMSG_GUILD_EVENT, SMSG_GUILD_INVITE, SMSG_GUILD_QUERY_RESPONSE, SMSG_GUILD_ROSTER, SMSG_MESSAGECHAT,
+};
+
+//The client always expects room for ten ranks
+const MAX_GUILD_RANKS: usize = 10;
+//Result 0, the client's way of saying the command went through
+const GUILD_COMMAND_SUCCESS: GuildCommandResult = GuildCommandResult::PlayerNoMoreInGuild;
+
+async fn send_command_result(character: &Character, command: GuildCommand, name: &str, result: GuildCommandResult) -> Result<()> {
+    ServerEvent::GuildCommandResult(SMSG_GUILD_COMMAND_RESULT {
+        command,
+        string: name.to_string(),
+        result,
+    })
+    .send_to_character(character)
+    .await
+}
+
+fn command_result(error: GuildError) -> GuildCommandResult {
+    match error {
+        GuildError::NameInvalid => GuildCommandResult::GuildNameInvalid,
+        GuildError::NameTaken => GuildCommandResult::GuildNameExistsS,
+        GuildError::AlreadyInGuild => GuildCommandResult::AlreadyInGuild,
+        GuildError::TargetAlreadyInGuild => GuildCommandResult::AlreadyInGuildS,
+        GuildError::TargetAlreadyInvited => GuildCommandResult::AlreadyInvitedToGuildS,
+        GuildError::NotInGuild => GuildCommandResult::GuildPlayerNotInGuild,
+        GuildError::NotInvited => GuildCommandResult::GuildInternal,
+        GuildError::NoPermission => GuildCommandResult::GuildPermissions,
+        GuildError::LeaderCannotLeave => GuildCommandResult::GuildLeaderLeave,
+    }
+}
+
+//To every online member of the guild
+async fn broadcast_guild_event(
+    guild_id: u32,
+    event: GuildEvent,
+    descriptions: Vec<String>,
+    character_manager: &CharacterManager,
+    world: &World,
+) -> Result<()> {
+    let event = ServerEvent::GuildEvent(SMSG_GUILD_EVENT {
+        event,
+        event_descriptions: descriptions,
+    });
+    world.get_guild_members().send_to_members(&guild_id, &event, character_manager).await
+}
+
+//Shown in the chat frame when a member logs in
+pub async fn send_guild_motd(character: &Character, world: &World) -> Result<()> {
+    let Some(guild) = world.get_guilds().get_guild_of(character.get_guid()) else {
+        return Ok(());
+    };
+    if guild.motd.is_empty() {
+        return Ok(());
+    }
+    ServerEvent::GuildEvent(SMSG_GUILD_EVENT {
+        event: GuildEvent::Motd,
+        event_descriptions: vec![guild.motd.clone()],
+    })
+    .send_to_character(character)
+    .await
+}
+
+pub async fn handle_cmsg_guild_create(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    data: &CMSG_GUILD_CREATE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let realm_db = world.get_realm_database();
+
+    let guild_id = match world.get_guilds_mut().create_guild(&realm_db, &data.guild_name, guid).await? {
+        Ok(guild_id) => guild_id,
+        Err(e) => {
+            let character = character_manager.get_character(guid)?;
+            return send_command_result(character, GuildCommand::Create, &data.guild_name, command_result(e)).await;
+        }
+    };
+    info!("Guild {} was founded by {}", data.guild_name, guid);
+    world.get_guild_members_mut().join(guild_id, guid);
+    let membership = world.get_guilds().get_membership(guid);
+    let character = character_manager.get_character_mut(guid)?;
+    character.set_guild(membership);
+    send_command_result(character, GuildCommand::Create, &data.guild_name, GUILD_COMMAND_SUCCESS).await
+}
+
+pub async fn handle_cmsg_guild_invite(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    data: &CMSG_GUILD_INVITE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    //Invites are answered in game, only characters that are online can get one
+    let invitee = world
+        .get_character_info_cache()
+        .find_by_name(&data.invited_player)
+        .filter(|info| info.online)
+        .and_then(|info| character_manager.find_character(info.guid));
+    let Some(invitee) = invitee else {
+        return send_command_result(
+            character,
+            GuildCommand::Invite,
+            &data.invited_player,
+            GuildCommandResult::GuildPlayerNotFoundS,
+        )
+        .await;
+    };
+    if super::social_handler::is_alliance(invitee.get_race()) != super::social_handler::is_alliance(character.get_race()) {
+        return send_command_result(character, GuildCommand::Invite, &invitee.name, GuildCommandResult::GuildNotAllied).await;
+    }
+    if invitee.is_ignoring(character.get_guid()) {
+        return Ok(());
+    }
+
+    let guild_id = match world.get_guilds_mut().invite(character.get_guid(), invitee.get_guid()) {
+        Ok(guild_id) => guild_id,
+        Err(e) => return send_command_result(character, GuildCommand::Invite, &invitee.name, command_result(e)).await,
+    };
+    let guild_name = world.get_guilds().get_guild(guild_id).map(|guild| guild.name.clone()).unwrap_or_default();
+    ServerEvent::GuildInvite(SMSG_GUILD_INVITE {
+        player_name: character.name.clone(),
+        guild_name,
+    })
+    .send_to_character(invitee)
+    .await?;
+    send_command_result(character, GuildCommand::Invite, &invitee.name, GUILD_COMMAND_SUCCESS).await
+}
+
+pub async fn handle_cmsg_guild_accept(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let realm_db = world.get_realm_database();
+
+    let (guild_id, rank) = match world.get_guilds_mut().accept_invite(&realm_db, guid).await? {
+        Ok(membership) => membership,
+        Err(e) => {
+            let character = character_manager.get_character(guid)?;
+            return send_command_result(character, GuildCommand::Invite, "", command_result(e)).await;
+        }
+    };
+    world.get_guild_members_mut().join(guild_id, guid);
+    let character = character_manager.get_character_mut(guid)?;
+    character.set_guild(Some((guild_id, rank)));
+    let name = character.name.clone();
+    broadcast_guild_event(guild_id, GuildEvent::Joined, vec![name], character_manager, world).await?;
+    send_guild_motd(character_manager.get_character(guid)?, world).await
+}
+
+pub async fn handle_cmsg_guild_decline(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    let Some(inviter) = world.get_guilds_mut().decline_invite(character.get_guid()) else {
+        return Ok(());
+    };
+    let Some(inviter) = character_manager.find_character(inviter) else {
+        return Ok(());
+    };
+    ServerEvent::GuildDecline(SMSG_GUILD_DECLINE {
+        player: character.name.clone(),
+    })
+    .send_to_character(inviter)
+    .await
+}
+
+pub async fn handle_cmsg_guild_leave(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let realm_db = world.get_realm_database();
+    let guild_name = world.get_guilds().get_guild_of(guid).map(|guild| guild.name.clone()).unwrap_or_default();
+
+    let guild_id = match world.get_guilds_mut().leave(&realm_db, guid).await? {
+        Ok(guild_id) => guild_id,
+        Err(e) => {
+            let character = character_manager.get_character(guid)?;
+            return send_command_result(character, GuildCommand::Quit, &guild_name, command_result(e)).await;
+        }
+    };
+    world.get_guild_members_mut().leave(&guild_id, guid);
+    let character = character_manager.get_character_mut(guid)?;
+    character.set_guild(None);
+    let name = character.name.clone();
+    send_command_result(character, GuildCommand::Quit, &guild_name, GUILD_COMMAND_SUCCESS).await?;
+
+    //The guild master can only leave a guild of one, which disbands it
+    if world.get_guilds().get_guild(guild_id).is_none() {
+        info!("Guild {} was disbanded by {}", guild_name, guid);
+        return Ok(());
+    }
+    broadcast_guild_event(guild_id, GuildEvent::Left, vec![name], character_manager, world).await
+}
+
+pub async fn handle_cmsg_guild_roster(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let Some(guild) = world.get_guilds().get_guild_of(character.get_guid()) else {
+        return Ok(());
+    };
+
+    let now = Instant::now();
+    let members = guild
+        .get_members()
+        .filter_map(|(guid, rank)| {
+            let CharacterLookup::Known(info) = world.get_character_info_cache().lookup(guid, now) else {
+                return None;
+            };
+            //The last zone of offline members isn't kept anywhere, they show up in the default zone
+            let (status, area) = match character_manager.find_character(guid) {
+                Some(member) => (GuildMember_GuildMemberStatus::Online, member.area),
+                None => (GuildMember_GuildMemberStatus::Offline { time_offline: 0.0 }, Area::NorthshireAbbey),
+            };
+            Some(GuildMember {
+                guid,
+                status,
+                name: info.name.clone(),
+                rank: rank as u32,
+                level: Level::new(info.level),
+                class: info.class,
+                gender: info.gender,
+                area,
+                public_note: String::new(),
+                officer_note: String::new(),
+            })
+        })
+        .collect();
+    //There is no guild bank, nobody gets to withdraw from it
+    let rights = guild
+        .ranks
+        .iter()
+        .map(|rank| GuildRights {
+            rights: rank.rights,
+            money_per_day: Gold::new(0),
+            bank_tab_rights: std::array::from_fn(|_| GuildBankRights { rights: 0, slots_per_day: 0 }),
+        })
+        .collect();
+
+    ServerEvent::GuildRoster(SMSG_GUILD_ROSTER {
+        motd: guild.motd.clone(),
+        guild_info: String::new(),
+        rights,
+        members,
+    })
+    .send_to_character(character)
+    .await
+}
+
+pub async fn handle_cmsg_guild_motd(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    data: &CMSG_GUILD_MOTD,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let realm_db = world.get_realm_database();
+
+    let guild_id = match world
+        .get_guilds_mut()
+        .set_motd(&realm_db, character.get_guid(), &data.message_of_the_day)
+        .await?
+    {
+        Ok(guild_id) => guild_id,
+        //The client only shows the text of the result, the command doesn't matter
+        Err(e) => return send_command_result(character, GuildCommand::Create, "", command_result(e)).await,
+    };
+    let motd = world.get_guilds().get_guild(guild_id).map(|guild| guild.motd.clone()).unwrap_or_default();
+    broadcast_guild_event(guild_id, GuildEvent::Motd, vec![motd], character_manager, world).await
+}
+
+//The client asks for the name and rank names of every guild id it sees on a character
+pub async fn handle_cmsg_guild_query(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_GUILD_QUERY,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let Some(guild) = world.get_guilds().get_guild(data.guild_id) else {
+        return Ok(());
+    };
+
+    let mut rank_names: [String; MAX_GUILD_RANKS] = Default::default();
+    for (name, rank) in rank_names.iter_mut().zip(&guild.ranks) {
+        *name = rank.name.clone();
+    }
+    ServerEvent::GuildQueryResponse(SMSG_GUILD_QUERY_RESPONSE {
+        id: guild.id,
+        name: guild.name.clone(),
+        rank_names,
+        emblem_style: 0,
+        emblem_color: 0,
+        border_style: 0,
+        border_color: 0,
+        background_color: 0,
+        amount_of_ranks: guild.ranks.len() as u32,
+    })
+    .send_to_character(character)
+    .await
+}
+
+//Guild and officer chat go to the online members whose rank may listen to it
+pub async fn send_guild_chat(
+    sender: &Character,
+    character_manager: &CharacterManager,
+    world: &World,
+    officer: bool,
+    language: Language,
+    message: &str,
+) -> Result<()> {
+    let guilds = world.get_guilds();
+    let (speak, listen) = match officer {
+        true => (GUILD_RIGHT_OFFICER_CHAT_SPEAK, GUILD_RIGHT_OFFICER_CHAT_LISTEN),
+        false => (GUILD_RIGHT_CHAT_SPEAK, GUILD_RIGHT_CHAT_LISTEN),
+    };
+    let Some(guild_id) = sender.get_guild_id() else {
+        return send_command_result(sender, GuildCommand::Create, "", GuildCommandResult::GuildPlayerNotInGuild).await;
+    };
+    if !guilds.has_right(sender.get_guid(), speak) {
+        return send_command_result(sender, GuildCommand::Create, "", GuildCommandResult::GuildPermissions).await;
+    }
+
+    let chat_type = match officer {
+        true => SMSG_MESSAGECHAT_ChatType::Officer { target6: sender.get_guid() },
+        false => SMSG_MESSAGECHAT_ChatType::Guild { target6: sender.get_guid() },
+    };
+    let listeners = world
+        .get_guild_members()
+        .members(&guild_id)
+        .filter(|&member| guilds.has_right(member, listen))
+        .filter_map(|member| character_manager.find_character(member))
+        .filter(|listener| !listener.is_ignoring(sender.get_guid()));
+    for listener in listeners {
+        ServerEvent::MessageChat(SMSG_MESSAGECHAT {
+            chat_type: chat_type.clone(),
+            language,
+            sender: sender.get_guid(),
+            flags: 0,
+            message: language::message_for_listener(listener, language, message),
+            tag: sender.get_chat_tag(),
+        })
+        .send_to_character(listener)
+        .await?;
+    }
+    Ok(())
+}
+
+//The online members of the character's guild, other than the character, that want to hear about it
+fn guild_members_to_notify<'a>(
+    character: &Character,
+    character_manager: &'a CharacterManager,
+    world: &'a World,
+    notification: GuildNotification,
+) -> Vec<&'a Character> {
+    let Some(guild_id) = character.get_guild_id() else {
+        return vec![];
+    };
+    let guid = character.get_guid();
+    world
+        .get_guild_members()
+        .members(&guild_id)
+        .filter(|&member| member != guid)
+        .filter_map(|member| character_manager.find_character(member))
+        .filter(|member| member.wants_guild_notification(notification))
+        .collect()
+}
+
+pub async fn notify_guild_of_status(character: &Character, character_manager: &CharacterManager, world: &World, online: bool) -> Result<()> {
+    let event = ServerEvent::GuildEvent(SMSG_GUILD_EVENT {
+        event: if online { GuildEvent::SignedOn } else { GuildEvent::SignedOff },
+        event_descriptions: vec![character.name.clone()],
+    });
+    for member in guild_members_to_notify(character, character_manager, world, GuildNotification::Logins) {
+        event.send_to_character(member).await?;
+    }
+    Ok(())
+}
+
+//The client has no guild event for level-ups or achievements, so those are system messages in each member's language
+async fn announce_to_guild(
+    character: &Character,
+    data_storage: &DataStorage,
+    character_manager: &CharacterManager,
+    world: &World,
+    notification: GuildNotification,
+    text: ServerString,
+    argument: &dyn std::fmt::Display,
+) -> Result<()> {
+    for member in guild_members_to_notify(character, character_manager, world, notification) {
+        let message = data_storage.localize(member.get_client_locale(), text, &[&character.name, argument]);
+        handlers::send_system_message_to_character(member, &message).await?;
+    }
+    Ok(())
+}
+
+pub async fn announce_guild_level_up(guid: Guid, data_storage: &DataStorage, character_manager: &CharacterManager, world: &World) -> Result<()> {
+    let character = character_manager.get_character(guid)?;
+    let level = character.gameplay_data.unit_level().unwrap_or(1);
+    announce_to_guild(
+        character,
+        data_storage,
+        character_manager,
+        world,
+        GuildNotification::LevelUps,
+        ServerString::GuildMemberLevelUp,
+        &level,
+    )
+    .await
+}
+
+//Nothing earns achievements yet, this is for when something does
+#[allow(dead_code)]
+pub async fn announce_guild_achievement(
+    guid: Guid,
+    achievement_name: &str,
+    data_storage: &DataStorage,
+    character_manager: &CharacterManager,
+    world: &World,
+) -> Result<()> {
+    let character = character_manager.get_character(guid)?;
+    announce_to_guild(
+        character,
+        data_storage,
+        character_manager,
+        world,
+        GuildNotification::Achievements,
+        ServerString::GuildMemberAchievement,
+        &achievement_name,
+    )
+    .await
+}
+
+//.guildnotify <logins|levels|achievements> <on|off>
+pub async fn handle_guildnotify_command(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    args: &[&str],
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    let locale = client.data.locale;
+    let data_storage = &client_manager.data_storage;
+
+    let notification = args.first().and_then(|name| GuildNotification::from_name(name));
+    let enabled = super::gm_handler::parse_on_off(args.get(1).copied());
+    let (Some(notification), Some(enabled)) = (notification, enabled) else {
+        let reply = data_storage.localize(locale, ServerString::GuildNotifyUsage, &[]);
+        return handlers::send_system_message_to_character(character, &reply).await;
+    };
+    if character.get_guild_id().is_none() {
+        let reply = data_storage.localize(locale, ServerString::NotInGuild, &[]);
+        return handlers::send_system_message_to_character(character, &reply).await;
+    }
+
+    character
+        .set_guild_notification(&world.get_realm_database(), notification, enabled)
+        .await?;
+    let state = data_storage.get_server_string(ServerString::on_off(enabled), locale);
+    let reply = data_storage.localize(locale, ServerString::GuildNotificationSet, &[&args[0].to_lowercase(), &state]);
+    handlers::send_system_message_to_character(character, &reply).await
+}
@@ -0,0 +1,50 @@
+use std::net::SocketAddr;
+
+use wow_world_messages::wrath::{
+    AchievementDoneArray, AchievementInProgressArray, MSG_INSPECT_ARENA_TEAMS_Client, CMSG_QUERY_INSPECT_ACHIEVEMENTS,
+    SMSG_RESPOND_INSPECT_ACHIEVEMENTS,
+};
+
+use crate::character::character_manager::CharacterManager;
+use crate::client_manager::ClientManager;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+
+//The achievements tab of the inspect frame waits for this answer, so it has to be sent even when
+//there is nothing in it. Achievements aren't tracked yet, every character has an empty list.
+pub async fn handle_cmsg_query_inspect_achievements(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    client_id: SocketAddr,
+    packet: &CMSG_QUERY_INSPECT_ACHIEVEMENTS,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let Some(target) = character_manager.find_character(packet.guid) else {
+        return Ok(());
+    };
+
+    ServerEvent::RespondInspectAchievements(SMSG_RESPOND_INSPECT_ACHIEVEMENTS {
+        player: target.get_guid(),
+        done: AchievementDoneArray { done: vec![] },
+        in_progress: AchievementInProgressArray { in_progress: vec![] },
+    })
+    .send_to_character(character)
+    .await
+}
+
+//The client sends one MSG_INSPECT_ARENA_TEAMS back per team the target is in and shows no teams if
+//nothing comes back, so without arena teams there is nothing to answer
+pub async fn handle_msg_inspect_arena_teams(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    client_id: SocketAddr,
+    packet: &MSG_INSPECT_ARENA_TEAMS_Client,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    if character_manager.find_character(packet.player).is_none() {
+        trace!("{} inspected arena teams of {} who is not online", character.name, packet.player);
+    }
+    Ok(())
+}
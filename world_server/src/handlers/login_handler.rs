@@ -1,28 +1,42 @@
+use crate::addons;
 use crate::character::character_manager::CharacterManager;
 use crate::character::Character;
 use crate::client_manager::ClientManager;
 use crate::connection::events::ServerEvent;
 use crate::connection::Connection;
+use crate::localization::ClientLocale;
 use crate::packet::*;
 use crate::prelude::*;
-use podio::{LittleEndian, ReadPodExt};
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use wow_srp::normalized_string::NormalizedString;
 use wow_srp::wrath_header::ProofSeed;
 use wow_world_messages::wrath::{
-    Addon, BillingPlanFlags, RealmSplitState, SMSG_AUTH_RESPONSE_WorldResult, CMSG_AUTH_SESSION, CMSG_PING, CMSG_REALM_SPLIT, SMSG_ADDON_INFO,
-    SMSG_AUTH_RESPONSE, SMSG_CLIENTCACHE_VERSION, SMSG_LOGIN_SETTIMESPEED, SMSG_LOGOUT_CANCEL_ACK, SMSG_LOGOUT_COMPLETE, SMSG_LOGOUT_RESPONSE,
-    SMSG_PONG, SMSG_REALM_SPLIT, SMSG_TUTORIAL_FLAGS,
+    BillingPlanFlags, RealmSplitState, SMSG_AUTH_RESPONSE_WorldResult, CMSG_AUTH_SESSION, CMSG_PING, CMSG_REALM_SPLIT, SMSG_AUTH_RESPONSE,
+    SMSG_CLIENTCACHE_VERSION, SMSG_LOGIN_SETTIMESPEED, SMSG_LOGOUT_CANCEL_ACK, SMSG_LOGOUT_COMPLETE, SMSG_LOGOUT_RESPONSE, SMSG_PONG,
+    SMSG_REALM_SPLIT, SMSG_TUTORIAL_FLAGS,
 };
-use wrath_auth_db::AuthDatabase;
+use wrath_auth_db::{AuthDatabase, DBAccount};
+use wrath_common::{config, session_key, BanStatus, FeatureFlags, GmLevel};
+
+pub struct AuthenticatedAccount {
+    pub account_id: u32,
+    pub locale: ClientLocale,
+    pub gm_level: GmLevel,
+}
+
+//Game clients and the bot gateway both go through this, so a ban keeps the account out however it connects
+pub async fn get_account_ban_status(auth_db: &AuthDatabase, db_account: &DBAccount, now: u64) -> Result<BanStatus> {
+    let bans = auth_db.get_active_account_bans(db_account.id, now).await?;
+    Ok(BanStatus::from_bans(db_account.banned != 0, bans.iter().map(|ban| ban.unban_time), now))
+}
 
 pub async fn handle_cmsg_auth_session(
     connection: &mut Connection,
     proof_seed: ProofSeed,
     packet: &CMSG_AUTH_SESSION,
     auth_db: Arc<AuthDatabase>,
-) -> Result<u32> {
+) -> Result<AuthenticatedAccount> {
     if connection.is_authenticated() {
         connection.disconnect().await?;
         warn!("duplicate login rejected!");
@@ -31,37 +45,52 @@ pub async fn handle_cmsg_auth_session(
 
     info!("User {} connecting with buildnumber {}", packet.username, packet.client_build);
 
-    let db_account = match auth_db.get_account_by_username(&packet.username).await? {
-        Some(c) => c,
-        None => return Err(anyhow!("Account doesnt exist!")),
+    let Ok(username) = NormalizedString::new(&packet.username) else {
+        return reject_auth_session(connection, SMSG_AUTH_RESPONSE_WorldResult::AuthReject, "invalid username").await;
+    };
+    let Some(db_account) = auth_db.get_account_by_username(&packet.username).await? else {
+        return reject_auth_session(connection, SMSG_AUTH_RESPONSE_WorldResult::AuthReject, "account doesn't exist").await;
     };
 
-    let mut sess_key: [u8; 40] = [0u8; 40];
-    let db_session_key = hex::decode(db_account.sessionkey)?;
-    assert_eq!(db_session_key.len(), 40);
-    sess_key.copy_from_slice(db_session_key.as_slice());
-
-    let client_encryption = proof_seed.into_header_crypto(
-        &NormalizedString::new(&packet.username).unwrap(),
-        sess_key,
-        packet.client_proof,
-        packet.client_seed,
-    );
-
-    if client_encryption.is_err() {
-        SMSG_AUTH_RESPONSE {
-            result: SMSG_AUTH_RESPONSE_WorldResult::AuthReject,
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+    match get_account_ban_status(&auth_db, &db_account, now).await? {
+        BanStatus::NotBanned => {}
+        BanStatus::Banned => {
+            let reason = format!("{} is banned", packet.username);
+            return reject_auth_session(connection, SMSG_AUTH_RESPONSE_WorldResult::AuthBanned, &reason).await;
+        }
+        BanStatus::Suspended { until } => {
+            let reason = format!("{} is suspended for {} more seconds", packet.username, until - now);
+            return reject_auth_session(connection, SMSG_AUTH_RESPONSE_WorldResult::AuthSuspended, &reason).await;
+        }
+    }
+
+    //Accounts that never logged on, or whose session key was tampered with, can't have a valid proof
+    let sess_key = match session_key::decode(&db_account.sessionkey) {
+        Ok(sess_key) => sess_key,
+        Err(e) => {
+            let reason = format!("no usable session key for {}: {}", packet.username, e);
+            return reject_auth_session(connection, SMSG_AUTH_RESPONSE_WorldResult::AuthReject, &reason).await;
         }
-        .astd_send_to_connection(connection)
-        .await?;
+    };
 
-        async_io::Timer::after(std::time::Duration::from_secs(2)).await;
-        bail!("Failed auth attempt, rejecting");
+    let peer_ip = connection.stream.peer_addr()?.ip();
+    if config::flag("AUTH_REQUIRE_MATCHING_IP", true) && !ip_matches_logon(&db_account.last_ip, peer_ip) {
+        let reason = format!(
+            "{} connected from {} but logged on from '{}'",
+            packet.username, peer_ip, db_account.last_ip
+        );
+        return reject_auth_session(connection, SMSG_AUTH_RESPONSE_WorldResult::AuthReject, &reason).await;
     }
 
+    let Ok(client_encryption) = proof_seed.into_header_crypto(&username, sess_key, packet.client_proof, packet.client_seed) else {
+        let reason = format!("wrong session proof for {}", packet.username);
+        return reject_auth_session(connection, SMSG_AUTH_RESPONSE_WorldResult::AuthReject, &reason).await;
+    };
+
     //Set the crypto of the client for use from now on
     {
-        let (encrypt, decrypt) = client_encryption.unwrap().split();
+        let (encrypt, decrypt) = client_encryption.split();
         connection.set_crypto(encrypt, decrypt);
     }
 
@@ -78,47 +107,32 @@ pub async fn handle_cmsg_auth_session(
 
     //Handle full world queuing here
 
-    let addon_info = &packet.addon_info;
-    let mut addon_reader = std::io::Cursor::new(addon_info);
-    let num_addons = addon_reader.read_u32::<LittleEndian>()?;
-    info!("num addons = {}", num_addons);
-    let mut addons: Vec<Addon> = Vec::with_capacity(num_addons as usize);
-
-    for _ in 0..num_addons {
-        use std::io::BufRead;
-
-        let mut addon_name_buf = Vec::new();
-        addon_reader.read_until(0, &mut addon_name_buf)?;
-        addon_name_buf.truncate(addon_name_buf.len() - 1);
-        let addon_name = String::from_utf8(addon_name_buf)?;
-        let _addon_has_signature = addon_reader.read_u8()? == 1;
-        let addon_crc = addon_reader.read_u32::<LittleEndian>()?;
-        let _addon_extra_crc = addon_reader.read_u32::<LittleEndian>()?;
-        let uses_diffent_public_key = addon_crc != 0x4C1C776D; //Blizzard addon CRC
-
-        addons.push(Addon {
-            addon_type: 2,
-            uses_crc: 1,
-            uses_diffent_public_key,
-            unknown1: 0,
-            unknown2: 0,
-        });
-
-        if uses_diffent_public_key {
-            warn!("Unhandled non-blizzard addon: {}", addon_name);
-            //Write blizzard public key
-        }
-    }
-
-    //TODO: wow_world_messages needs changes to NOT write the size of the addon vec before writing
-    //the addon vec, it corrupts the packet. Probably a skip-serialize tag that can be added to the
-    //wowm file to the number_of_addons field to indicate the array size, but NOT write it into the final packet
-    SMSG_ADDON_INFO { addons }.astd_send_to_connection(connection).await?;
+    addons::send_addon_info(connection, &packet.addon_info).await?;
     SMSG_CLIENTCACHE_VERSION { version: 0 }.astd_send_to_connection(connection).await?;
 
     send_tutorial_flags(connection).await?;
 
-    Ok(db_account.id)
+    Ok(AuthenticatedAccount {
+        account_id: db_account.id,
+        locale: ClientLocale::from_code(&db_account.locale),
+        gm_level: FeatureFlags::from_env().effective_gm_level(GmLevel::from_db(db_account.gm_level)),
+    })
+}
+
+//The delay keeps a client from guessing in a tight loop
+async fn reject_auth_session(connection: &mut Connection, result: SMSG_AUTH_RESPONSE_WorldResult, reason: &str) -> Result<AuthenticatedAccount> {
+    SMSG_AUTH_RESPONSE { result }.astd_send_to_connection(connection).await?;
+
+    async_io::Timer::after(std::time::Duration::from_secs(2)).await;
+    bail!("Failed auth attempt, rejecting: {}", reason);
+}
+
+//A session key is only good for the address that did the SRP exchange with the auth server.
+//Accounts without a recorded address logged on before addresses were stored and have to log on again.
+fn ip_matches_logon(logon_ip: &str, peer_ip: IpAddr) -> bool {
+    logon_ip
+        .parse::<IpAddr>()
+        .is_ok_and(|logon_ip| logon_ip.to_canonical() == peer_ip.to_canonical())
 }
 
 async fn send_tutorial_flags(connection: &mut Connection) -> Result<()> {
@@ -174,7 +188,7 @@ pub async fn handle_cmsg_logout_request(
     let client = client_manager.get_authenticated_client(client_id)?;
 
     let (result, speed) = {
-        let character = character_manager.get_character_mut(client.get_active_character())?;
+        let character = character_manager.get_character_mut(client.get_active_character()?)?;
         character.try_logout().await?
     };
 
@@ -190,7 +204,7 @@ pub async fn handle_cmsg_logout_cancel(
     client_id: SocketAddr,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character = character_manager.get_character_mut(client.get_active_character())?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
     character.cancel_logout().await?;
     let msg = SMSG_LOGOUT_CANCEL_ACK {};
     let event = ServerEvent::LogoutCancelAck(msg);
@@ -201,3 +215,18 @@ pub async fn handle_cmsg_logout_cancel(
 pub async fn send_smsg_logout_complete(character: &Character) -> Result<()> {
     ServerEvent::LogoutComplete(SMSG_LOGOUT_COMPLETE {}).send_to_character(character).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_logon_address_may_use_the_session() {
+        let peer_ip: IpAddr = "203.0.113.7".parse().unwrap();
+        assert!(ip_matches_logon("203.0.113.7", peer_ip));
+        assert!(!ip_matches_logon("198.51.100.20", peer_ip));
+        //Dual stack sockets see IPv4 clients as mapped IPv6 addresses
+        assert!(ip_matches_logon("203.0.113.7", "::ffff:203.0.113.7".parse().unwrap()));
+        assert!(!ip_matches_logon("", peer_ip));
+    }
+}
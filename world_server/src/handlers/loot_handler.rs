@@ -0,0 +1,272 @@
+use std::net::SocketAddr;
+
+use wow_world_messages::wrath::{
+    Gold, LootItem, LootMethodError, LootSlotType, SMSG_LOOT_RESPONSE_LootMethod, CMSG_AUTOSTORE_LOOT_ITEM, CMSG_LOOT, CMSG_LOOT_RELEASE,
+    SMSG_LOOT_CLEAR_MONEY, SMSG_LOOT_MONEY_NOTIFY, SMSG_LOOT_RELEASE_RESPONSE, SMSG_LOOT_REMOVED, SMSG_LOOT_RESPONSE,
+};
+
+use crate::audit::{log_audit_event, AuditEvent};
+use crate::character::character_inventory::InventoryError;
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::client_manager::ClientManager;
+use crate::combat::damage;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::world::creature_manager::SharedCreature;
+use crate::world::group_loot::LootRight;
+use crate::world::loot::{self, Loot};
+use crate::world::World;
+
+//A little more than the client's interaction range, positions lag behind a bit
+const LOOT_DISTANCE: f32 = 10.0;
+
+fn find_corpse(character: &Character, corpse: Guid, world: &World) -> Option<SharedCreature> {
+    let map = world.get_instance_manager().try_get_map_for_character(character)?;
+    map.get_creature(corpse).cloned()
+}
+
+//How the item shows up in the loot window of looter, the group loot method may keep it from them
+fn slot_type(right: LootRight, looter: Guid) -> LootSlotType {
+    match right {
+        LootRight::Anyone => LootSlotType::AllowLoot,
+        LootRight::Looter(owner) if owner == looter => LootSlotType::Owner,
+        LootRight::MasterLooter(master) if master == looter => LootSlotType::Master,
+        LootRight::Looter(_) | LootRight::MasterLooter(_) => LootSlotType::Locked,
+        LootRight::Roll => LootSlotType::RollOngoing,
+    }
+}
+
+async fn send_loot_error(character: &Character, corpse: Guid, loot_error: LootMethodError) -> Result<()> {
+    ServerEvent::LootResponse(SMSG_LOOT_RESPONSE {
+        guid: corpse,
+        loot_method: SMSG_LOOT_RESPONSE_LootMethod::Error { loot_error },
+        gold: Gold::new(0),
+        items: vec![],
+    })
+    .send_to_character(character)
+    .await
+}
+
+//Everyone with this corpse's loot window open sees the same window
+async fn send_to_looters(corpse: Guid, event: ServerEvent, character_manager: &CharacterManager) -> Result<()> {
+    for character in character_manager
+        .get_all_characters()
+        .filter(|character| character.get_loot_target() == Some(corpse))
+    {
+        event.clone().send_to_character(character).await?;
+    }
+    Ok(())
+}
+
+//Opens the loot window for what lies on loot_target, the character has to be marked as looting it afterwards
+pub async fn send_loot_window(character: &Character, loot_target: Guid, loot: &Loot) -> Result<()> {
+    let items = loot
+        .get_items()
+        .map(|(slot, item)| LootItem {
+            index: slot,
+            item: item.item_id,
+            ty: slot_type(loot.get_item_right(slot), character.get_guid()),
+        })
+        .collect();
+    ServerEvent::LootResponse(SMSG_LOOT_RESPONSE {
+        guid: loot_target,
+        loot_method: SMSG_LOOT_RESPONSE_LootMethod::Corpse,
+        gold: Gold::new(loot.get_money()),
+        items,
+    })
+    .send_to_character(character)
+    .await
+}
+
+pub async fn handle_cmsg_loot(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_LOOT,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character(guid)?;
+
+    let Some(corpse) = find_corpse(character, data.guid, world) else {
+        return send_loot_error(character, data.guid, LootMethodError::DidntKill).await;
+    };
+    let corpse = corpse.read().await;
+    let Some(loot) = corpse.get_loot().filter(|loot| loot.may_loot(guid)) else {
+        return send_loot_error(character, data.guid, LootMethodError::DidntKill).await;
+    };
+    if damage::distance(character.movement_info.position, corpse.movement_info.position) > LOOT_DISTANCE {
+        return send_loot_error(character, data.guid, LootMethodError::TooFar).await;
+    }
+
+    send_loot_window(character, data.guid, loot).await?;
+    drop(corpse);
+
+    character_manager.get_character_mut(guid)?.start_looting(data.guid);
+    Ok(())
+}
+
+pub async fn handle_cmsg_autostore_loot_item(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    data: &CMSG_AUTOSTORE_LOOT_ITEM,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character(guid)?;
+    let Some(loot_target) = character.get_loot_target() else {
+        return Ok(());
+    };
+
+    //Loot is kept on corpses and on gathering nodes that were just gathered
+    let corpse = find_corpse(character, loot_target, world);
+    let (item, source) = {
+        let corpse = match &corpse {
+            Some(corpse) => Some(corpse.read().await),
+            None => None,
+        };
+        let loot = match &corpse {
+            Some(corpse) => corpse.get_loot(),
+            None => world.get_gathering_nodes().get_loot(loot_target),
+        };
+        let Some(loot) = loot.filter(|loot| loot.may_take_item(data.item_slot, guid)) else {
+            return Ok(());
+        };
+        let Ok(item) = loot.get_item(data.item_slot) else {
+            return Ok(());
+        };
+        (item, loot.source)
+    };
+
+    //Items don't stack in the backpack yet, every one takes its own slot
+    let character = character_manager.get_character_mut(guid)?;
+    let mut stored = 0;
+    for _ in 0..item.count {
+        let added = character
+            .try_add_item_to_backpack(
+                item.item_id,
+                guid.guid() as u32,
+                &client.connection_sender,
+                Some(world.get_persistence_queue()),
+            )
+            .await;
+        if added.is_none() {
+            break;
+        }
+        stored += 1;
+    }
+    if stored == 0 {
+        return character.send_inventory_change_failure(InventoryError::InventoryFull, Guid::zero()).await;
+    }
+
+    let slot_emptied = match corpse {
+        Some(corpse) => {
+            let mut corpse = corpse.write().await;
+            let slot_emptied = take_stored_item(corpse.get_loot_mut(), data.item_slot, stored);
+            corpse.clear_loot_if_empty();
+            slot_emptied
+        }
+        None => {
+            let nodes = world.get_gathering_nodes_mut();
+            let slot_emptied = take_stored_item(nodes.get_loot_mut(loot_target), data.item_slot, stored);
+            nodes.clear_loot_if_empty(loot_target);
+            slot_emptied
+        }
+    };
+
+    if slot_emptied {
+        let removed = ServerEvent::LootRemoved(SMSG_LOOT_REMOVED { slot: data.item_slot });
+        send_to_looters(loot_target, removed, character_manager).await?;
+    }
+    log_audit_event(
+        &world.get_realm_database(),
+        client.data.account_id,
+        guid,
+        AuditEvent::ItemCreated {
+            item_id: item.item_id,
+            count: stored as u32,
+        },
+        source.audit_source(),
+    )
+    .await
+}
+
+//Returns whether the slot is empty now
+fn take_stored_item(loot: Option<&mut Loot>, slot: u8, stored: u8) -> bool {
+    let Some(loot) = loot else {
+        return false;
+    };
+    loot.take_item(slot, stored).ok();
+    loot.get_item(slot).is_err()
+}
+
+//The money is split between every character that may loot the corpse and is close enough to see it
+pub async fn handle_cmsg_loot_money(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character(guid)?;
+    let Some(corpse_guid) = character.get_loot_target() else {
+        return Ok(());
+    };
+    let Some(corpse) = find_corpse(character, corpse_guid, world) else {
+        return Ok(());
+    };
+
+    let mut corpse = corpse.write().await;
+    let Some(loot) = corpse.get_loot_mut().filter(|loot| loot.may_loot(guid)) else {
+        return Ok(());
+    };
+    let mut recipients: Vec<Guid> = loot
+        .get_recipients()
+        .iter()
+        .copied()
+        .filter(|&recipient| recipient != guid && character.is_in_range(recipient) && character_manager.find_character(recipient).is_some())
+        .collect();
+    recipients.insert(0, guid);
+    let money = loot.take_money();
+    corpse.clear_loot_if_empty();
+    drop(corpse);
+    if money == 0 {
+        return Ok(());
+    }
+
+    let (share, remainder) = loot::split_money(money, recipients.len());
+    let alone = recipients.len() == 1;
+    for (index, &recipient) in recipients.iter().enumerate() {
+        let amount = if index == 0 { share + remainder } else { share };
+        let recipient = character_manager.get_character_mut(recipient)?;
+        recipient.set_money(recipient.get_money().saturating_add(amount));
+        ServerEvent::LootMoneyNotify(SMSG_LOOT_MONEY_NOTIFY { amount, alone })
+            .send_to_character(recipient)
+            .await?;
+    }
+    send_to_looters(corpse_guid, ServerEvent::LootClearMoney(SMSG_LOOT_CLEAR_MONEY {}), character_manager).await
+}
+
+pub async fn handle_cmsg_loot_release(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    data: &CMSG_LOOT_RELEASE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    if character.get_loot_target() == Some(data.guid) {
+        character.stop_looting();
+    }
+    ServerEvent::LootReleaseResponse(SMSG_LOOT_RELEASE_RESPONSE {
+        guid: data.guid,
+        unknown1: 1,
+    })
+    .send_to_character(character)
+    .await
+}
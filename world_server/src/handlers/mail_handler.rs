@@ -0,0 +1,429 @@
+use std::net::SocketAddr;
+
+use wow_world_messages::wrath::{
+    Gold, Mail, MailAction, MailListItem, MailListItemEnchant, MailResult, Mail_MailType, CMSG_GET_MAIL_LIST, CMSG_MAIL_DELETE,
+    CMSG_MAIL_MARK_AS_READ, CMSG_MAIL_RETURN_TO_SENDER, CMSG_MAIL_TAKE_ITEM, CMSG_MAIL_TAKE_MONEY, CMSG_SEND_MAIL, SMSG_MAIL_LIST_RESULT,
+    SMSG_RECEIVED_MAIL, SMSG_SEND_MAIL_RESULT,
+};
+use wrath_realm_db::mail::{DBMail, DBMailCreateParameters, MAIL_FLAG_READ};
+use wrath_realm_db::RealmDatabase;
+
+use crate::audit::{log_audit_event, AuditEvent, AuditSource};
+use crate::character::character_inventory::INVENTORY_SLOT_BAG_0;
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::client_manager::ClientManager;
+use crate::connection::events::ServerEvent;
+use crate::handlers;
+use crate::item_journal::{ItemMove, JournaledOperation};
+use crate::prelude::*;
+use crate::world::mail::{self, MAX_ATTACHMENTS};
+use crate::world::prelude::inventory::BagSlot;
+use crate::world::World;
+
+//The plain parchment, the client picks its own stationery for anything else
+const DEFAULT_STATIONERY: u32 = 41;
+
+//Mailboxes aren't spawned as gameobjects yet, so the mailbox guid in the mail packets isn't checked
+
+async fn send_mail_result(character: &Character, mail_id: u32, action: MailAction, result: MailResult) -> Result<()> {
+    ServerEvent::SendMailResult(SMSG_SEND_MAIL_RESULT { mail_id, action, result })
+        .send_to_character(character)
+        .await
+}
+
+//Lights up the mail icon on the minimap of the receiver, if it's online
+pub async fn notify_mail_received(receiver: Guid, character_manager: &CharacterManager) -> Result<()> {
+    let Some(character) = character_manager.find_character(receiver) else {
+        return Ok(());
+    };
+    ServerEvent::ReceivedMail(SMSG_RECEIVED_MAIL { unknown1: 0 })
+        .send_to_character(character)
+        .await
+}
+
+//Only the receiver gets to do anything with a mail
+async fn find_own_mail(realm_db: &RealmDatabase, character: &Character, mail_id: u32) -> Result<Option<DBMail>> {
+    let character_id = character.get_guid().guid() as u32;
+    Ok(realm_db.get_mail(mail_id).await?.filter(|mail| mail.receiver_id == character_id))
+}
+
+pub async fn handle_cmsg_get_mail_list(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    _data: &CMSG_GET_MAIL_LIST,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let realm_db = world.get_realm_database();
+    let character_id = character.get_guid().guid() as u32;
+
+    let now = mail::current_unix_time()?;
+    let items = realm_db.get_mail_items_for_character(character_id).await?;
+    let mails = realm_db
+        .get_mails_for_character(character_id)
+        .await?
+        .into_iter()
+        .map(|mail| Mail {
+            message_id: mail.id,
+            message_type: Mail_MailType::Normal {
+                sender: Guid::new(mail.sender_id as u64),
+            },
+            cash_on_delivery: mail.cash_on_delivery,
+            unknown1: 0,
+            stationery: DEFAULT_STATIONERY,
+            money: Gold::new(mail.money),
+            flags: mail.flags as u32,
+            expiration_time: mail.expire_time.saturating_sub(now) as f32 / (24 * 60 * 60) as f32,
+            mail_template_id: 0,
+            subject: mail.subject,
+            message: mail.body,
+            items: items
+                .iter()
+                .filter(|item| item.mail_id == mail.id)
+                .enumerate()
+                .map(|(index, item)| MailListItem {
+                    item_index: index as u8,
+                    low_guid: item.id,
+                    item: item.item,
+                    enchants: std::array::from_fn(|slot| MailListItemEnchant {
+                        enchant_id: if slot == 0 { item.enchant.unwrap_or(0) } else { 0 },
+                        duration: 0,
+                        charges: 0,
+                    }),
+                    item_random_property_id: 0,
+                    item_suffix_factor: 0,
+                    item_stack_size: 1,
+                    spell_charges: 0,
+                    max_durability: 100,
+                    durability: 100,
+                    unknown: 0,
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+
+    ServerEvent::MailListResult(SMSG_MAIL_LIST_RESULT {
+        real_mail_amount: mails.len() as u32,
+        mails,
+    })
+    .send_to_character(character)
+    .await
+}
+
+//Every attachment leaves the sender through the item journal, so the items are only gone from the database
+//once the mail that holds them is written. A failure on the way hands back whatever was already taken.
+async fn create_mail_with_attachments(
+    realm_db: &RealmDatabase,
+    character: &Character,
+    item_positions: &[(u8, u8)],
+    params: &DBMailCreateParameters,
+) -> Result<u32> {
+    let character_id = character.get_guid().guid() as u32;
+    let mut moves = Vec::with_capacity(item_positions.len());
+    let mut taken = Ok(());
+    for &item_position in item_positions {
+        let Some(item) = character.get_inventory_item(item_position) else {
+            continue;
+        };
+        let item_id = item.update_state.object_entry().unwrap_or(0) as u32;
+        let enchant = Some(item.permanent_enchant).filter(|&enchant| enchant != 0);
+        let item_move = match ItemMove::begin(realm_db, JournaledOperation::Mail, character_id, item_position.0, item_id, enchant).await {
+            Ok(item_move) => item_move,
+            Err(e) => {
+                taken = Err(e);
+                break;
+            }
+        };
+        if let Err(e) = item_move.take(realm_db).await {
+            //The entry is still at the intent stage, the startup reconciliation drops it if this doesn't
+            item_move
+                .cancel(realm_db)
+                .await
+                .unwrap_or_else(|e| warn!("Failed to cancel mail item move: {}", e));
+            taken = Err(e);
+            break;
+        }
+        moves.push(item_move);
+    }
+
+    let mail = match taken {
+        Ok(()) => ItemMove::deliver_by_mail(&moves, realm_db, params).await,
+        Err(e) => Err(e),
+    };
+    if mail.is_err() {
+        for item_move in moves {
+            //Left in the journal, the startup reconciliation gives it back then
+            if let Err(e) = item_move.return_to_owner(realm_db).await {
+                error!("Failed to give back a mail attachment of character {}: {}", character_id, e);
+            }
+        }
+    }
+    mail
+}
+
+pub async fn handle_cmsg_send_mail(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_SEND_MAIL,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
+    let realm_db = world.get_realm_database();
+
+    let Some(receiver) = world.get_character_info_cache().find_by_name(&data.receiver).map(|info| info.guid) else {
+        return send_mail_result(character, 0, MailAction::Send, MailResult::ErrRecipientNotFound).await;
+    };
+    if receiver == guid {
+        return send_mail_result(character, 0, MailAction::Send, MailResult::ErrCannotSendToSelf).await;
+    }
+    if data.items.len() > MAX_ATTACHMENTS {
+        return send_mail_result(character, 0, MailAction::Send, MailResult::ErrTooManyAttachments).await;
+    }
+    let cash_on_delivery = data.cash_on_delivery_amount.as_int();
+    if cash_on_delivery > 0 && data.items.is_empty() {
+        return send_mail_result(character, 0, MailAction::Send, MailResult::ErrMailAttachmentInvalid).await;
+    }
+
+    //Equipped items have to be taken off before they can be sent
+    let mut item_positions = vec![];
+    for mail_item in &data.items {
+        let item_position = character
+            .find_inventory_item(mail_item.item)
+            .filter(|&(slot, _)| BagSlot::try_from(slot).is_ok());
+        match item_position {
+            Some(item_position) if !item_positions.contains(&item_position) => item_positions.push(item_position),
+            _ => return send_mail_result(character, 0, MailAction::Send, MailResult::ErrMailAttachmentInvalid).await,
+        }
+    }
+
+    let money = data.money.as_int();
+    let cost = mail::postage(item_positions.len()).saturating_add(money);
+    if character.get_money() < cost {
+        return send_mail_result(character, 0, MailAction::Send, MailResult::ErrNotEnoughMoney).await;
+    }
+
+    let now = mail::current_unix_time()?;
+    let params = DBMailCreateParameters {
+        sender_id: guid.guid() as u32,
+        receiver_id: receiver.guid() as u32,
+        subject: data.subject.clone(),
+        body: data.body.clone(),
+        money,
+        cash_on_delivery,
+        expire_time: now + mail::mail_lifetime(cash_on_delivery),
+    };
+    let mail_id = match create_mail_with_attachments(&realm_db, character, &item_positions, &params).await {
+        Ok(mail_id) => mail_id,
+        Err(e) => {
+            warn!("Failed to send mail from {}: {}", character.name, e);
+            return send_mail_result(character, 0, MailAction::Send, MailResult::ErrInternalError).await;
+        }
+    };
+
+    //The mail is stored, only now do the items and the money leave the sender
+    let mut items = vec![];
+    for item_position in item_positions {
+        let Some(item) = character
+            .set_item(None, item_position, Some(world.get_persistence_queue()), Some(&client.connection_sender))
+            .await?
+        else {
+            continue;
+        };
+        if let Some(item_guid) = item.update_state.object_guid() {
+            handlers::send_destroy_object(character, item_guid, false).await?;
+        }
+        items.push(item.update_state.object_entry().unwrap_or(0) as u32);
+    }
+    character.set_money(character.get_money() - cost);
+
+    let source = AuditSource::Mail { other_party: receiver };
+    log_audit_event(&realm_db, client.data.account_id, guid, AuditEvent::MoneySpent(cost), source).await?;
+    for &item_id in &items {
+        log_audit_event(
+            &realm_db,
+            client.data.account_id,
+            guid,
+            AuditEvent::ItemMailed { item_id, count: 1 },
+            source,
+        )
+        .await?;
+    }
+
+    send_mail_result(character, mail_id, MailAction::Send, MailResult::Ok).await?;
+    notify_mail_received(receiver, character_manager).await
+}
+
+pub async fn handle_cmsg_mail_take_money(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_MAIL_TAKE_MONEY,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
+    let realm_db = world.get_realm_database();
+
+    let Some(mail) = find_own_mail(&realm_db, character, data.mail_id).await?.filter(|mail| mail.money > 0) else {
+        return send_mail_result(character, data.mail_id, MailAction::MoneyTaken, MailResult::ErrInternalError).await;
+    };
+    realm_db.take_mail_money(mail.id).await?;
+    character.set_money(character.get_money().saturating_add(mail.money));
+
+    let source = AuditSource::Mail {
+        other_party: Guid::new(mail.sender_id as u64),
+    };
+    log_audit_event(&realm_db, client.data.account_id, guid, AuditEvent::MoneyGained(mail.money), source).await?;
+    send_mail_result(character, mail.id, MailAction::MoneyTaken, MailResult::Ok).await
+}
+
+//Taking the first item off a cash on delivery mail pays the sender, who gets the money by mail
+pub async fn handle_cmsg_mail_take_item(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_MAIL_TAKE_ITEM,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
+    let realm_db = world.get_realm_database();
+
+    let Some(mail) = find_own_mail(&realm_db, character, data.mail_id).await? else {
+        return send_mail_result(character, data.mail_id, MailAction::ItemTaken, MailResult::ErrInternalError).await;
+    };
+    let Some(mail_item) = realm_db.get_mail_items(mail.id).await?.into_iter().find(|item| item.id == data.item) else {
+        return send_mail_result(character, mail.id, MailAction::ItemTaken, MailResult::ErrInternalError).await;
+    };
+    //Nobody is left to pay when the sender was deleted in the meantime
+    let sender_exists = realm_db.get_character_info(mail.sender_id).await?.is_some();
+    let cash_on_delivery = if sender_exists { mail.cash_on_delivery } else { 0 };
+    if character.get_money() < cash_on_delivery {
+        return send_mail_result(character, mail.id, MailAction::ItemTaken, MailResult::ErrNotEnoughMoney).await;
+    }
+
+    //The item is placed first, the attachment only goes away once it has somewhere to be
+    let Some(slot_id) = character
+        .try_add_enchanted_item_to_backpack(
+            mail_item.item,
+            mail_item.enchant.unwrap_or(0),
+            guid.guid() as u32,
+            &client.connection_sender,
+            Some(world.get_persistence_queue()),
+        )
+        .await
+    else {
+        return send_mail_result(character, mail.id, MailAction::ItemTaken, MailResult::ErrEquipError).await;
+    };
+    if let Err(e) = realm_db.take_mail_item(mail_item.id, mail.id).await {
+        let position = (slot_id, INVENTORY_SLOT_BAG_0);
+        let placed = character
+            .set_item(None, position, Some(world.get_persistence_queue()), Some(&client.connection_sender))
+            .await?;
+        if let Some(item_guid) = placed.and_then(|item| item.update_state.object_guid()) {
+            handlers::send_destroy_object(character, item_guid, false).await?;
+        }
+        return Err(e);
+    }
+
+    let sender = Guid::new(mail.sender_id as u64);
+    let source = AuditSource::Mail { other_party: sender };
+    let event = AuditEvent::ItemMailed {
+        item_id: mail_item.item,
+        count: 1,
+    };
+    log_audit_event(&realm_db, client.data.account_id, guid, event, source).await?;
+
+    if cash_on_delivery > 0 {
+        character.set_money(character.get_money() - cash_on_delivery);
+        log_audit_event(&realm_db, client.data.account_id, guid, AuditEvent::MoneySpent(cash_on_delivery), source).await?;
+        let now = mail::current_unix_time()?;
+        realm_db
+            .create_mail(
+                &DBMailCreateParameters {
+                    sender_id: guid.guid() as u32,
+                    receiver_id: mail.sender_id,
+                    subject: mail.subject.clone(),
+                    body: String::new(),
+                    money: cash_on_delivery,
+                    cash_on_delivery: 0,
+                    expire_time: now + mail::mail_lifetime(0),
+                },
+                &[],
+            )
+            .await?;
+    }
+
+    send_mail_result(character, mail.id, MailAction::ItemTaken, MailResult::Ok).await?;
+    if cash_on_delivery > 0 {
+        notify_mail_received(sender, character_manager).await?;
+    }
+    Ok(())
+}
+
+pub async fn handle_cmsg_mail_mark_as_read(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_MAIL_MARK_AS_READ,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let realm_db = world.get_realm_database();
+
+    if let Some(mail) = find_own_mail(&realm_db, character, data.mail_id).await? {
+        realm_db.set_mail_flags(mail.id, mail.flags | MAIL_FLAG_READ).await?;
+    }
+    Ok(())
+}
+
+//Whatever is still attached is deleted with the mail, the client asks the player to confirm that
+pub async fn handle_cmsg_mail_delete(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_MAIL_DELETE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let realm_db = world.get_realm_database();
+
+    let Some(mail) = find_own_mail(&realm_db, character, data.mail_id).await? else {
+        return send_mail_result(character, data.mail_id, MailAction::Deleted, MailResult::ErrInternalError).await;
+    };
+    realm_db.delete_mail(mail.id).await?;
+    send_mail_result(character, mail.id, MailAction::Deleted, MailResult::Ok).await
+}
+
+pub async fn handle_cmsg_mail_return_to_sender(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_MAIL_RETURN_TO_SENDER,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let realm_db = world.get_realm_database();
+
+    let Some(mail) = find_own_mail(&realm_db, character, data.mail_id).await? else {
+        return send_mail_result(character, data.mail_id, MailAction::ReturnedToSender, MailResult::ErrInternalError).await;
+    };
+    if realm_db.get_character_info(mail.sender_id).await?.is_none() {
+        return send_mail_result(character, mail.id, MailAction::ReturnedToSender, MailResult::ErrRecipientNotFound).await;
+    }
+    let now = mail::current_unix_time()?;
+    realm_db.return_mail(&mail, now + mail::mail_lifetime(0)).await?;
+    send_mail_result(character, mail.id, MailAction::ReturnedToSender, MailResult::Ok).await?;
+    notify_mail_received(Guid::new(mail.sender_id as u64), character_manager).await
+}
@@ -1,4 +1,5 @@
 pub mod login_handler;
+pub use login_handler::get_account_ban_status;
 pub use login_handler::handle_cmsg_auth_session;
 pub use login_handler::handle_cmsg_logout_cancel;
 pub use login_handler::handle_cmsg_logout_request;
@@ -14,37 +15,162 @@ pub use account_data_handler::handle_cmsg_request_account_data;
 pub use account_data_handler::handle_cmsg_update_account_data;
 pub use account_data_handler::send_character_account_data_times;
 
+mod announcement_handler;
+pub use announcement_handler::send_motd;
+pub use announcement_handler::send_notification_to_character;
+pub use announcement_handler::send_server_announcement;
+pub use announcement_handler::send_server_notification;
+pub use announcement_handler::send_system_message_to_all;
+pub use announcement_handler::send_system_message_to_character;
+pub use announcement_handler::AnnouncementScope;
+
 mod bars_buttons_handler;
 pub use bars_buttons_handler::handle_cmsg_set_action_button;
 pub use bars_buttons_handler::handle_cmsg_set_actionbar_toggles;
 
+mod calendar_handler;
+pub use calendar_handler::handle_cmsg_calendar_add_event;
+pub use calendar_handler::handle_cmsg_calendar_event_invite;
+pub use calendar_handler::handle_cmsg_calendar_event_rsvp;
+pub use calendar_handler::handle_cmsg_calendar_get_calendar;
+pub use calendar_handler::handle_cmsg_calendar_get_event;
+pub use calendar_handler::handle_cmsg_calendar_get_num_pending;
+
+mod channel_handler;
+pub use channel_handler::handle_cmsg_channel_moderator;
+pub use channel_handler::handle_cmsg_channel_password;
+pub use channel_handler::handle_cmsg_channel_unmoderator;
+pub use channel_handler::handle_cmsg_join_channel;
+pub use channel_handler::handle_cmsg_leave_channel;
+pub use channel_handler::send_channel_message;
+
 mod character_handler;
 pub use character_handler::handle_cmsg_autoequip_item;
 pub use character_handler::handle_cmsg_char_create;
 pub use character_handler::handle_cmsg_char_delete;
 pub use character_handler::handle_cmsg_char_enum;
+pub use character_handler::handle_cmsg_destroyitem;
 pub use character_handler::handle_cmsg_player_login;
 pub use character_handler::handle_cmsg_player_logout;
 pub use character_handler::handle_cmsg_standstate_change;
 pub use character_handler::handle_cmsg_swap_inv_item;
+pub use character_handler::handle_cmsg_swap_item;
 pub use character_handler::send_action_buttons;
 pub use character_handler::send_bind_update;
 pub use character_handler::send_verify_world;
 
+mod combat_handler;
+pub use combat_handler::handle_cmsg_attackstop;
+pub use combat_handler::handle_cmsg_attackswing;
+
 mod cinematics_handler;
 pub use cinematics_handler::handle_cmsg_complete_cinematic;
 pub use cinematics_handler::handle_cmsg_next_cinematic_camera;
 pub use cinematics_handler::send_trigger_cinematic;
 
+mod gossip_handler;
+pub use gossip_handler::handle_cmsg_gossip_hello;
+pub use gossip_handler::handle_cmsg_gossip_select_option;
+pub use gossip_handler::send_gossip_poi;
+pub use gossip_handler::send_point_of_interest;
+
 mod group_handler;
+pub use group_handler::get_summon_conditions;
+pub use group_handler::handle_cmsg_group_accept;
+pub use group_handler::handle_cmsg_group_decline;
+pub use group_handler::handle_cmsg_group_disband;
+pub use group_handler::handle_cmsg_group_invite;
+pub use group_handler::handle_cmsg_loot_method;
+pub use group_handler::handle_cmsg_loot_roll;
 pub use group_handler::handle_cmsg_request_raid_info;
+pub use group_handler::handle_cmsg_summon_response;
+pub use group_handler::offer_group_follow;
+pub use group_handler::send_group_list_update;
+pub use group_handler::send_party_chat;
+
+mod equipment_set_handler;
+pub use equipment_set_handler::handle_cmsg_equipment_set_delete;
+pub use equipment_set_handler::handle_cmsg_equipment_set_save;
+pub use equipment_set_handler::handle_cmsg_equipment_set_use;
+pub use equipment_set_handler::send_equipment_set_list;
+
+mod gameobject_handler;
+pub use gameobject_handler::handle_cmsg_gameobj_use;
+
+mod pet_handler;
+pub use pet_handler::feed_pet;
+pub use pet_handler::handle_cmsg_pet_action;
+pub use pet_handler::handle_cmsg_pet_learn_talent;
+pub use pet_handler::handle_cmsg_pet_spell_autocast;
+pub use pet_handler::handle_cmsg_pet_unlearn;
+pub use pet_handler::summon_pet;
+pub use pet_handler::teach_pet_spell;
 
 mod gm_handler;
 pub use gm_handler::handle_additem_command;
+pub use gm_handler::handle_ban_command;
+pub use gm_handler::handle_cmsg_complain;
 pub use gm_handler::handle_cmsg_gmticket_create;
 pub use gm_handler::handle_cmsg_gmticket_getticket;
 pub use gm_handler::handle_cmsg_gmticket_system_status;
+pub use gm_handler::handle_deserter_command;
+pub use gm_handler::handle_fly_command;
+pub use gm_handler::handle_gm_mode_command;
+pub use gm_handler::handle_gmisland_command;
+pub use gm_handler::handle_god_command;
+pub use gm_handler::handle_groupsummon_command;
+pub use gm_handler::handle_lookup_player_command;
+pub use gm_handler::handle_modify_currency_command;
+pub use gm_handler::handle_modify_phase_command;
+pub use gm_handler::handle_motd_command;
+pub use gm_handler::handle_mute_command;
+pub use gm_handler::handle_recall_command;
+pub use gm_handler::handle_server_info_command;
 pub use gm_handler::handle_speed_command;
+pub use gm_handler::handle_start_command;
+pub use gm_handler::handle_taxi_all_command;
+pub use gm_handler::handle_tele_map_command;
+pub use gm_handler::handle_tele_random_command;
+pub use gm_handler::handle_tele_tour_command;
+pub use gm_handler::handle_unban_command;
+pub use gm_handler::required_gm_level;
+
+mod guild_handler;
+pub use guild_handler::announce_guild_achievement;
+pub use guild_handler::announce_guild_level_up;
+pub use guild_handler::handle_cmsg_guild_accept;
+pub use guild_handler::handle_cmsg_guild_create;
+pub use guild_handler::handle_cmsg_guild_decline;
+pub use guild_handler::handle_cmsg_guild_invite;
+pub use guild_handler::handle_cmsg_guild_leave;
+pub use guild_handler::handle_cmsg_guild_motd;
+pub use guild_handler::handle_cmsg_guild_query;
+pub use guild_handler::handle_cmsg_guild_roster;
+pub use guild_handler::handle_guildnotify_command;
+pub use guild_handler::notify_guild_of_status;
+pub use guild_handler::send_guild_chat;
+pub use guild_handler::send_guild_motd;
+
+mod mail_handler;
+pub use mail_handler::handle_cmsg_get_mail_list;
+pub use mail_handler::handle_cmsg_mail_delete;
+pub use mail_handler::handle_cmsg_mail_mark_as_read;
+pub use mail_handler::handle_cmsg_mail_return_to_sender;
+pub use mail_handler::handle_cmsg_mail_take_item;
+pub use mail_handler::handle_cmsg_mail_take_money;
+pub use mail_handler::handle_cmsg_send_mail;
+pub use mail_handler::notify_mail_received;
+
+mod loot_handler;
+pub use loot_handler::handle_cmsg_autostore_loot_item;
+pub use loot_handler::handle_cmsg_loot;
+pub use loot_handler::handle_cmsg_loot_money;
+pub use loot_handler::handle_cmsg_loot_release;
+pub use loot_handler::send_loot_window;
+
+mod inspect_handler;
+pub use inspect_handler::handle_cmsg_query_inspect_achievements;
+pub use inspect_handler::handle_msg_inspect_arena_teams;
 
 mod instance_handler;
 pub use instance_handler::send_dungeon_difficulty;
@@ -52,15 +178,36 @@ pub use instance_handler::send_dungeon_difficulty;
 mod voice_chat_handler;
 pub use voice_chat_handler::send_voice_chat_status;
 
+mod spell_handler;
+pub use spell_handler::handle_caster_damaged;
+pub use spell_handler::handle_cmsg_cancel_aura;
+pub use spell_handler::handle_cmsg_cancel_cast;
+pub use spell_handler::handle_cmsg_cast_spell;
+pub use spell_handler::interrupt_character_cast;
+pub use spell_handler::send_cast_failed;
+pub use spell_handler::send_spell_failure;
+
+mod taxi_handler;
+pub use taxi_handler::handle_cmsg_taxinode_status_query;
+pub use taxi_handler::handle_cmsg_taxiqueryavailablenodes;
+
 mod tutorial_handler;
 pub use tutorial_handler::handle_cmsg_tutorial_flag;
 pub use tutorial_handler::handle_cmsg_tutorial_reset;
 pub use tutorial_handler::send_tutorial_flags;
 
+mod vendor_handler;
+pub use vendor_handler::handle_cmsg_buy_item;
+pub use vendor_handler::handle_cmsg_buyback_item;
+pub use vendor_handler::handle_cmsg_list_inventory;
+pub use vendor_handler::handle_cmsg_sell_item;
+
 mod faction_handler;
 pub use faction_handler::send_faction_list;
+pub use faction_handler::send_faction_standing;
 
 mod world_handler;
+pub use world_handler::handle_cmsg_far_sight;
 pub use world_handler::handle_cmsg_time_sync_resp;
 pub use world_handler::handle_cmsg_zoneupdate;
 pub use world_handler::send_destroy_object;
@@ -70,14 +217,19 @@ pub use world_handler::send_time_sync;
 pub use world_handler::send_world_state_update;
 
 mod social_handler;
-pub use social_handler::handle_cmsg_calendar_get_num_pending;
+pub use social_handler::handle_cmsg_add_friend;
+pub use social_handler::handle_cmsg_add_ignore;
 pub use social_handler::handle_cmsg_contact_list;
-pub use social_handler::handle_cmsg_join_channel;
+pub use social_handler::handle_cmsg_del_friend;
+pub use social_handler::handle_cmsg_del_ignore;
 pub use social_handler::handle_cmsg_messagechat;
 pub use social_handler::handle_cmsg_set_selection;
+pub use social_handler::notify_friends_of_status;
 pub use social_handler::send_contact_list;
 
 mod queries_handler;
+pub use queries_handler::handle_cmsg_creature_query;
+pub use queries_handler::handle_cmsg_gameobject_query;
 pub use queries_handler::handle_cmsg_item_name_query;
 pub use queries_handler::handle_cmsg_item_query_single;
 pub use queries_handler::handle_cmsg_name_query;
@@ -85,16 +237,23 @@ pub use queries_handler::handle_cmsg_played_time;
 pub use queries_handler::handle_cmsg_query_time;
 pub use queries_handler::handle_cmsg_world_state_ui_timer_update;
 
+mod quest_handler;
+pub use quest_handler::credit_gameobject_use;
+pub use quest_handler::handle_cmsg_questgiver_accept_quest;
+pub use quest_handler::handle_cmsg_questgiver_choose_reward;
+pub use quest_handler::handle_cmsg_questgiver_complete_quest;
+pub use quest_handler::handle_cmsg_use_item;
+
 pub mod movement_handler;
 pub use movement_handler::handle_cmsg_areatrigger;
+pub use movement_handler::handle_cmsg_move_knock_back_ack;
 pub use movement_handler::handle_cmsg_set_active_mover;
+pub use movement_handler::handle_movement_change_ack;
 pub use movement_handler::handle_movement_generic;
 pub use movement_handler::handle_msg_move_teleport_ack;
 pub use movement_handler::handle_msg_move_worldport_ack;
 pub use movement_handler::handle_msg_world_teleport;
 pub use movement_handler::send_msg_move_teleport_ack;
-pub use movement_handler::send_smsg_force_move_root;
-pub use movement_handler::send_smsg_force_move_unroot;
 pub use movement_handler::send_smsg_new_world;
 pub use movement_handler::send_smsg_stand_state_update;
 pub use movement_handler::send_smsg_transfer_pending;
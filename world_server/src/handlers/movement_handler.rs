@@ -1,29 +1,40 @@
+use crate::character::character_forced_movement::MovementCapability;
 use crate::character::character_manager::CharacterManager;
+use crate::character::character_movement_acks::{MovementAck, MovementChange};
 use crate::character::Character;
 use crate::client_manager::ClientManager;
 use crate::connection::events::{IntoServerEvent, ServerEvent};
-use crate::data::{AreaTriggerPurpose, PositionAndOrientation, WorldZoneLocation};
+use crate::data::{AreaTriggerPurpose, DataProvider, PositionAndOrientation, WorldZoneLocation};
 use crate::prelude::*;
 use crate::world::prelude::GameObject;
 use crate::world::World;
 use std::net::SocketAddr;
 use wow_world_messages::wrath::{
-    Area, ClientMessage, MSG_MOVE_TELEPORT_ACK_Client, MSG_MOVE_TELEPORT_ACK_Server, Map, MovementInfo, ServerMessage, UnitStandState, Vector3d,
-    CMSG_AREATRIGGER, CMSG_SET_ACTIVE_MOVER, CMSG_WORLD_TELEPORT, MSG_MOVE_FALL_LAND, MSG_MOVE_HEARTBEAT, MSG_MOVE_JUMP, MSG_MOVE_SET_FACING,
+    Area, ClientMessage, MSG_MOVE_KNOCK_BACK_Server, MSG_MOVE_TELEPORT_ACK_Client, MSG_MOVE_TELEPORT_ACK_Server, Map, MovementInfo, ServerMessage,
+    UnitStandState, Vector3d, CMSG_AREATRIGGER, CMSG_FORCE_MOVE_ROOT_ACK, CMSG_FORCE_MOVE_UNROOT_ACK, CMSG_FORCE_RUN_BACK_SPEED_CHANGE_ACK,
+    CMSG_FORCE_RUN_SPEED_CHANGE_ACK, CMSG_MOVE_FEATHER_FALL_ACK, CMSG_MOVE_HOVER_ACK, CMSG_MOVE_KNOCK_BACK_ACK, CMSG_MOVE_SET_CAN_FLY_ACK,
+    CMSG_MOVE_WATER_WALK_ACK, CMSG_SET_ACTIVE_MOVER, CMSG_WORLD_TELEPORT, MSG_MOVE_FALL_LAND, MSG_MOVE_HEARTBEAT, MSG_MOVE_JUMP, MSG_MOVE_SET_FACING,
     MSG_MOVE_SET_RUN_MODE, MSG_MOVE_SET_WALK_MODE, MSG_MOVE_START_BACKWARD, MSG_MOVE_START_FORWARD, MSG_MOVE_START_PITCH_DOWN,
     MSG_MOVE_START_PITCH_UP, MSG_MOVE_START_STRAFE_LEFT, MSG_MOVE_START_STRAFE_RIGHT, MSG_MOVE_START_SWIM, MSG_MOVE_START_TURN_LEFT,
-    MSG_MOVE_START_TURN_RIGHT, MSG_MOVE_STOP, MSG_MOVE_STOP_PITCH, MSG_MOVE_STOP_STRAFE, MSG_MOVE_STOP_SWIM, MSG_MOVE_STOP_TURN,
-    SMSG_FORCE_MOVE_ROOT, SMSG_FORCE_MOVE_UNROOT, SMSG_NEW_WORLD, SMSG_STANDSTATE_UPDATE, SMSG_TRANSFER_PENDING,
+    MSG_MOVE_START_TURN_RIGHT, MSG_MOVE_STOP, MSG_MOVE_STOP_PITCH, MSG_MOVE_STOP_STRAFE, MSG_MOVE_STOP_SWIM, MSG_MOVE_STOP_TURN, SMSG_NEW_WORLD,
+    SMSG_STANDSTATE_UPDATE, SMSG_TRANSFER_PENDING,
 };
 
 pub trait MovementMessage: Sync + ServerMessage + ClientMessage + IntoServerEvent {
+    //Heartbeats are sent while moving without anything changing, maps may pass on only some of them
+    const IS_HEARTBEAT: bool = false;
     fn get_guid(&self) -> Guid;
     fn get_movement_info(&self) -> MovementInfo;
 }
 
 macro_rules! define_movement_packet {
     ($packet_type:ty) => {
+        define_movement_packet!($packet_type, false);
+    };
+    ($packet_type:ty, $is_heartbeat:expr) => {
         impl MovementMessage for $packet_type {
+            const IS_HEARTBEAT: bool = $is_heartbeat;
+
             fn get_guid(&self) -> Guid {
                 self.guid
             }
@@ -54,7 +65,7 @@ define_movement_packet!(MSG_MOVE_FALL_LAND);
 define_movement_packet!(MSG_MOVE_START_SWIM);
 define_movement_packet!(MSG_MOVE_STOP_SWIM);
 define_movement_packet!(MSG_MOVE_SET_FACING);
-define_movement_packet!(MSG_MOVE_HEARTBEAT);
+define_movement_packet!(MSG_MOVE_HEARTBEAT, true);
 
 pub async fn handle_movement_generic<T: MovementMessage>(
     client_manager: &ClientManager,
@@ -64,7 +75,7 @@ pub async fn handle_movement_generic<T: MovementMessage>(
     packet: T,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
+    let guid = client.get_active_character()?;
     {
         let character = character_manager.get_character_mut(guid)?;
         if character.teleportation_state != TeleportationState::None {
@@ -75,14 +86,136 @@ pub async fn handle_movement_generic<T: MovementMessage>(
         let _guid = packet.get_guid();
         let movement_info = packet.get_movement_info();
 
+        //Flying needs an aura or a GM to grant it first, a client that flies on its own is cheating
+        if movement_info.flags.is_flying() && !character.has_movement_capability(MovementCapability::Fly) {
+            character.record_movement_ack_violation("flew without being allowed to");
+        }
         character.process_movement(movement_info);
     }
 
+    //Everything but heartbeats always goes out, those start the movement the others then extrapolate
+    let relay_interval = world
+        .get_instance_manager()
+        .try_get_map_for_character(character_manager.get_character(guid)?)
+        .map_or(0.0, |map| map.get_movement_relay_interval());
+    let character = character_manager.get_character_mut(guid)?;
+    let relay = character.take_movement_relay_turn(relay_interval) || !T::IS_HEARTBEAT;
+
+    if relay {
+        let character = character_manager.get_character(guid)?;
+        packet
+            .into_server_event()
+            .send_to_all_in_range(character, character_manager, false, world)
+            .await?;
+    }
+
+    //The client stops its own cast bar when it starts moving, everyone else has to be told
     let character = character_manager.get_character(guid)?;
-    packet
-        .into_server_event()
-        .send_to_all_in_range(character, character_manager, false, world)
-        .await
+    if character.is_casting() && character.is_moving() {
+        handlers::interrupt_character_cast(character_manager, world, guid, None).await?;
+    }
+    Ok(())
+}
+
+//Acks the client sends for forced movement changes: roots, speed changes and capabilities
+pub trait MovementChangeAck {
+    const ACK: MovementAck;
+    fn get_guid(&self) -> Guid;
+    fn get_counter(&self) -> u32;
+    fn get_movement_info(&self) -> MovementInfo;
+    //The speed the client says it switched to, for speed change acks
+    fn get_new_speed(&self) -> Option<f32> {
+        None
+    }
+}
+
+macro_rules! define_movement_change_ack {
+    ($packet_type:ty, $ack:expr, $counter:ident) => {
+        impl MovementChangeAck for $packet_type {
+            const ACK: MovementAck = $ack;
+
+            fn get_guid(&self) -> Guid {
+                self.guid
+            }
+
+            fn get_counter(&self) -> u32 {
+                self.$counter
+            }
+
+            fn get_movement_info(&self) -> MovementInfo {
+                self.info.clone()
+            }
+        }
+    };
+    ($packet_type:ty, $ack:expr, $counter:ident, $speed:ident) => {
+        impl MovementChangeAck for $packet_type {
+            const ACK: MovementAck = $ack;
+
+            fn get_guid(&self) -> Guid {
+                self.guid
+            }
+
+            fn get_counter(&self) -> u32 {
+                self.$counter
+            }
+
+            fn get_movement_info(&self) -> MovementInfo {
+                self.info.clone()
+            }
+
+            fn get_new_speed(&self) -> Option<f32> {
+                Some(self.$speed)
+            }
+        }
+    };
+}
+
+define_movement_change_ack!(CMSG_MOVE_SET_CAN_FLY_ACK, MovementAck::Capability(MovementCapability::Fly), counter);
+define_movement_change_ack!(CMSG_MOVE_WATER_WALK_ACK, MovementAck::Capability(MovementCapability::WaterWalk), counter);
+define_movement_change_ack!(
+    CMSG_MOVE_FEATHER_FALL_ACK,
+    MovementAck::Capability(MovementCapability::FeatherFall),
+    counter
+);
+define_movement_change_ack!(CMSG_MOVE_HOVER_ACK, MovementAck::Capability(MovementCapability::Hover), counter);
+define_movement_change_ack!(CMSG_FORCE_MOVE_ROOT_ACK, MovementAck::Root, movement_counter);
+define_movement_change_ack!(CMSG_FORCE_MOVE_UNROOT_ACK, MovementAck::Unroot, movement_counter);
+define_movement_change_ack!(CMSG_FORCE_RUN_SPEED_CHANGE_ACK, MovementAck::RunSpeed, counter, new_speed);
+define_movement_change_ack!(CMSG_FORCE_RUN_BACK_SPEED_CHANGE_ACK, MovementAck::RunBackSpeed, counter, new_speed);
+
+//The movement info in the ack already has the new movement flags, so it replaces what the server had.
+//Acks that don't match a pending change or claim a different speed than we sent are counted against the client.
+pub async fn handle_movement_change_ack<T: MovementChangeAck>(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    packet: &T,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
+
+    if packet.get_guid() != guid {
+        warn!("{} acknowledged a movement change for {}", character.name, packet.get_guid());
+        return Ok(());
+    }
+    let Some(change) = character.acknowledge_movement_change(T::ACK, packet.get_counter()) else {
+        return Ok(());
+    };
+
+    let expected_speed = match change {
+        MovementChange::RunSpeed(speed) | MovementChange::RunBackSpeed(speed) => Some(speed),
+        _ => None,
+    };
+    if let (Some(expected), Some(reported)) = (expected_speed, packet.get_new_speed()) {
+        if (expected - reported).abs() > 0.01 {
+            character.record_movement_ack_violation(&format!("claimed speed {} after being set to {}", reported, expected));
+            return Ok(());
+        }
+    }
+
+    character.process_movement(packet.get_movement_info());
+    Ok(())
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -98,14 +231,14 @@ pub enum TeleportationDistance {
     Far(WorldZoneLocation),
 }
 
-pub async fn send_msg_move_teleport_ack(character: &Character, destination: &PositionAndOrientation) -> Result<()> {
+pub async fn send_msg_move_teleport_ack(character: &Character, destination: &PositionAndOrientation, movement_counter: u32) -> Result<()> {
     let mut movement_info = character.get_movement_info().clone();
     movement_info.position = destination.position;
     movement_info.orientation = destination.orientation;
 
     ServerEvent::MoveTeleportAck(MSG_MOVE_TELEPORT_ACK_Server {
         guid: character.get_guid(),
-        movement_counter: 0, //TODO: Value should increment with every teleport?
+        movement_counter,
         info: movement_info,
     })
     .send_to_character(character)
@@ -132,12 +265,21 @@ pub async fn handle_msg_move_teleport_ack(
     client_manager: &ClientManager,
     character_manager: &mut CharacterManager,
     client_id: SocketAddr,
-    _packet: &MSG_MOVE_TELEPORT_ACK_Client,
+    packet: &MSG_MOVE_TELEPORT_ACK_Client,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
+    let guid = client.get_active_character()?;
     let character = character_manager.get_character_mut(guid)?;
 
+    //Acks for teleports that were never sent don't get to move the character anywhere
+    if packet.guid != guid
+        || character
+            .acknowledge_movement_change(MovementAck::Teleport, packet.movement_counter)
+            .is_none()
+    {
+        return Ok(());
+    }
+
     if let TeleportationState::Executing(TeleportationDistance::Near(destination)) = character.teleportation_state.clone() {
         character.set_position(&destination);
         character.teleportation_state = TeleportationState::None;
@@ -154,7 +296,7 @@ pub async fn handle_msg_move_worldport_ack(
 ) -> Result<()> {
     let teleportation_state = {
         let client = client_manager.get_authenticated_client(client_id)?;
-        let guid = client.get_active_character();
+        let guid = client.get_active_character()?;
         let character = character_manager.get_character_mut(guid)?;
         character.teleportation_state.clone()
     };
@@ -163,6 +305,7 @@ pub async fn handle_msg_move_worldport_ack(
         let map = destination.map;
         {
             let guid = client_manager.get_character_from_client(client_id).await?;
+            world.assign_instance(guid, map, character_manager).await?;
             let character = character_manager.get_character_mut(guid)?;
             let _ = world.get_instance_manager_mut().get_or_create_map(character, map).await?;
             character.map = map;
@@ -171,7 +314,7 @@ pub async fn handle_msg_move_worldport_ack(
         }
 
         let client = client_manager.get_authenticated_client(client_id)?;
-        let guid = client.get_active_character();
+        let guid = client.get_active_character()?;
         let character = character_manager.get_character(guid)?;
         character.send_packets_before_add_to_map().await?;
 
@@ -182,6 +325,7 @@ pub async fn handle_msg_move_worldport_ack(
             let guid = client_manager.get_character_from_client(client_id).await?;
             let character = character_manager.get_character_mut(guid)?;
             character.teleportation_state = TeleportationState::None;
+            character.continue_map_tour();
         }
     }
 
@@ -195,7 +339,7 @@ pub async fn handle_msg_world_teleport(
     packet: &CMSG_WORLD_TELEPORT,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
+    let guid = client.get_active_character()?;
     let character = character_manager.get_character_mut(guid)?;
 
     info!("Teleporting character {} to {} ({:?})", character.name, packet.map, packet.position);
@@ -217,21 +361,40 @@ pub async fn send_smsg_stand_state_update(character: &Character, stand_state: Un
         .await
 }
 
-pub async fn send_smsg_force_move_root(character: &Character) -> Result<()> {
-    ServerEvent::ForceMoveRoot(SMSG_FORCE_MOVE_ROOT {
-        guid: character.get_guid(),
-        counter: 0,
-    })
-    .send_to_character(character)
-    .await
-}
+//Movement info in the ack is where the client's own simulation of the knockback starts, everyone else
+//in range gets it so they can play the same arc
+pub async fn handle_cmsg_move_knock_back_ack(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    world: &World,
+    packet: &CMSG_MOVE_KNOCK_BACK_ACK,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let knock_back = {
+        let character = character_manager.get_character_mut(guid)?;
+        if packet.guid != guid {
+            return Ok(());
+        }
+        let Some(MovementChange::KnockBack(knock_back)) = character.acknowledge_movement_change(MovementAck::KnockBack, packet.movement_counter)
+        else {
+            return Ok(());
+        };
+        character.process_movement(packet.info.clone());
+        knock_back
+    };
 
-pub async fn send_smsg_force_move_unroot(character: &Character) -> Result<()> {
-    ServerEvent::ForceMoveUnroot(SMSG_FORCE_MOVE_UNROOT {
-        guid: character.get_guid(),
-        counter: 0,
+    let character = character_manager.get_character(guid)?;
+    ServerEvent::MoveKnockBackBroadcast(MSG_MOVE_KNOCK_BACK_Server {
+        guid,
+        info: packet.info.clone(),
+        sin_angle: knock_back.v_sin,
+        cos_angle: knock_back.v_cos,
+        x_y_speed: knock_back.horizontal_speed,
+        velocity: knock_back.vertical_speed,
     })
-    .send_to_character(character)
+    .send_to_all_in_range(character, character_manager, false, world)
     .await
 }
 
@@ -241,7 +404,7 @@ pub async fn handle_cmsg_set_active_mover(client_manager: &ClientManager, client
     //but I have a feeling the actual server does more with this...
 
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character_guid = client.get_active_character();
+    let character_guid = client.get_active_character()?;
 
     let mover_guid = packet.guid;
     //TODO: check against the character->mover, but since moving anything other than the character
@@ -254,38 +417,173 @@ pub async fn handle_cmsg_set_active_mover(client_manager: &ClientManager, client
     Ok(())
 }
 
+#[derive(Debug, PartialEq)]
+pub enum AreaTriggerAction {
+    Teleport(WorldZoneLocation),
+    //Instance portals, the group may follow the leader through them
+    EnterInstance { destination: WorldZoneLocation, required_level: u8 },
+    EnterInn,
+    None,
+}
+
+pub fn resolve_area_trigger(data: &impl DataProvider, trigger_id: u32) -> Result<AreaTriggerAction> {
+    let trigger_data = data
+        .get_area_trigger(trigger_id)
+        .ok_or_else(|| anyhow!("Character entered area trigger that isn't known to the server"))?;
+
+    Ok(match &trigger_data.purpose {
+        AreaTriggerPurpose::Teleport(teleport_data) => {
+            let destination = WorldZoneLocation {
+                position: Vector3d {
+                    x: teleport_data.target_position_x,
+                    y: teleport_data.target_position_y,
+                    z: teleport_data.target_position_z,
+                },
+                orientation: teleport_data.target_orientation,
+                map: (teleport_data.target_map as u32).try_into()?,
+                area: Area::NorthshireValley, //TODO
+            };
+            match data.is_instance_map(teleport_data.target_map as u32) {
+                true => AreaTriggerAction::EnterInstance {
+                    destination,
+                    required_level: teleport_data.required_level,
+                },
+                false => AreaTriggerAction::Teleport(destination),
+            }
+        }
+        AreaTriggerPurpose::RestedArea => AreaTriggerAction::EnterInn,
+        AreaTriggerPurpose::Unknown => AreaTriggerAction::None,
+    })
+}
+
 pub async fn handle_cmsg_areatrigger(
+    data: &impl DataProvider,
     client_manager: &ClientManager,
     character_manager: &mut CharacterManager,
+    world: &World,
     client_id: SocketAddr,
     packet: &CMSG_AREATRIGGER,
 ) -> Result<()> {
-    let area_trigger_id = packet.trigger_id;
+    let action = resolve_area_trigger(data, packet.trigger_id)?;
+    if action == AreaTriggerAction::None {
+        return Ok(());
+    }
 
-    let trigger_data = client_manager
-        .data_storage
-        .get_area_trigger(area_trigger_id as i32)
-        .ok_or_else(|| anyhow!("Character entered area trigger that isn't known to the server"))?;
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
+    match action {
+        AreaTriggerAction::Teleport(destination) => character.teleport_to(TeleportationDistance::Far(destination)),
+        AreaTriggerAction::EnterInstance { destination, required_level } => {
+            character.teleport_to(TeleportationDistance::Far(destination.clone()));
+            handlers::offer_group_follow(guid, &destination, required_level, character_manager, world).await?;
+        }
+        AreaTriggerAction::EnterInn => character.handle_enter_inn()?,
+        AreaTriggerAction::None => {}
+    }
+    Ok(())
+}
 
-    if let AreaTriggerPurpose::Teleport(teleport_data) = &trigger_data.purpose {
-        let destination = WorldZoneLocation {
-            position: Vector3d {
-                x: teleport_data.target_position_x,
-                y: teleport_data.target_position_y,
-                z: teleport_data.target_position_z,
-            },
-            orientation: teleport_data.target_orientation,
-            map: (teleport_data.target_map as u32).try_into()?,
-            area: Area::NorthshireValley, //TODO
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{AreaTrigger, AreaTriggerShape, InMemoryDataProvider};
+    use crate::test_utils::TestHarness;
+    use wow_dbc::wrath_tables::{area_trigger::AreaTriggerKey, map::MapKey};
+    use wow_world_messages::wrath::opcodes::ClientOpcodeMessage;
+    use wrath_game_db::DBAreaTriggerTeleport;
+
+    fn area_trigger(id: u32, purpose: AreaTriggerPurpose) -> AreaTrigger {
+        AreaTrigger {
+            id: AreaTriggerKey::new(id as i32),
+            map_id: MapKey::new(0),
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            shape: AreaTriggerShape::Sphere(5.0),
+            purpose,
+        }
+    }
+
+    #[test]
+    fn teleport_trigger_resolves_to_its_target() {
+        let mut data = InMemoryDataProvider::default();
+        let teleport = DBAreaTriggerTeleport {
+            id: 45,
+            name: None,
+            required_level: 0,
+            required_item: 0,
+            required_item2: 0,
+            heroic_key: 0,
+            heroic_key2: 0,
+            required_quest_done: 0,
+            required_quest_done_heroic: 0,
+            target_map: 1,
+            target_position_x: 1.0,
+            target_position_y: 2.0,
+            target_position_z: 3.0,
+            target_orientation: 0.5,
         };
+        data.area_triggers.insert(45, area_trigger(45, AreaTriggerPurpose::Teleport(teleport)));
 
-        let client = client_manager.get_authenticated_client(client_id)?;
-        let character = character_manager.get_character_mut(client.get_active_character())?;
-        character.teleport_to(TeleportationDistance::Far(destination))
-    } else if let AreaTriggerPurpose::RestedArea = &trigger_data.purpose {
-        let client = client_manager.get_authenticated_client(client_id)?;
-        let character = character_manager.get_character_mut(client.get_active_character())?;
-        character.handle_enter_inn()?;
+        let Ok(AreaTriggerAction::Teleport(destination)) = resolve_area_trigger(&data, 45) else {
+            panic!("Teleport trigger did not resolve to a teleport");
+        };
+        assert_eq!(destination.map, Map::Kalimdor);
+        assert_eq!(destination.position, Vector3d { x: 1.0, y: 2.0, z: 3.0 });
+
+        //Portals into dungeons let the group follow
+        data.instance_maps.insert(1);
+        assert!(matches!(
+            resolve_area_trigger(&data, 45),
+            Ok(AreaTriggerAction::EnterInstance { required_level: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn rested_and_unknown_triggers() {
+        let mut data = InMemoryDataProvider::default();
+        data.area_triggers.insert(1, area_trigger(1, AreaTriggerPurpose::RestedArea));
+        data.area_triggers.insert(2, area_trigger(2, AreaTriggerPurpose::Unknown));
+
+        assert_eq!(resolve_area_trigger(&data, 1).unwrap(), AreaTriggerAction::EnterInn);
+        assert_eq!(resolve_area_trigger(&data, 2).unwrap(), AreaTriggerAction::None);
+        assert!(resolve_area_trigger(&data, 3).is_err());
+    }
+
+    #[test]
+    fn heartbeat_moves_character_and_reaches_nearby_characters() {
+        smol::block_on(async {
+            let mut harness = TestHarness::new();
+            let position = Vector3d {
+                x: -8949.0,
+                y: -132.0,
+                z: 83.5,
+            };
+            let mover = harness.add_character("Mover", position).await;
+            let watcher = harness.add_character("Watcher", position).await;
+            harness.tick().await;
+            mover.take_events();
+            watcher.take_events();
+
+            let mut info = harness.get_character(&mover).movement_info.clone();
+            info.position.x += 5.0;
+            let heartbeat = MSG_MOVE_HEARTBEAT {
+                guid: mover.guid,
+                info: info.clone(),
+            };
+            harness
+                .handle_packet(&mover, ClientOpcodeMessage::MSG_MOVE_HEARTBEAT(heartbeat))
+                .await
+                .unwrap();
+
+            assert_eq!(harness.get_character(&mover).movement_info.position, info.position);
+            assert!(watcher
+                .take_events()
+                .iter()
+                .any(|event| matches!(event, ServerEvent::MoveHeartbeat(msg) if msg.guid == mover.guid && msg.info.position == info.position)));
+            //The mover's own client already knows where it is
+            assert!(mover.take_events().is_empty());
+        });
     }
-    Ok(())
 }
@@ -0,0 +1,147 @@
+use std::net::SocketAddr;
+
+use wow_world_messages::wrath::{CMSG_PET_ACTION, CMSG_PET_LEARN_TALENT, CMSG_PET_SPELL_AUTOCAST, CMSG_PET_UNLEARN};
+use wrath_realm_db::pet::PET_TYPE_SUMMON;
+
+use crate::character::character_manager::CharacterManager;
+use crate::client_manager::ClientManager;
+use crate::handlers;
+use crate::prelude::*;
+use crate::world::World;
+
+//Pet action bar buttons carry their kind in the top byte and the command or spell in the rest
+const PET_ACTION_TYPE_COMMAND: u32 = 0x07;
+const PET_COMMAND_DISMISS: u32 = 3;
+
+pub async fn handle_cmsg_pet_spell_autocast(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    packet: &CMSG_PET_SPELL_AUTOCAST,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    if !character.set_pet_spell_autocast(packet.id, packet.autocast_enabled) {
+        warn!(
+            "{} tried to toggle autocast of spell {} which their pet doesn't know",
+            character.name, packet.id
+        );
+    }
+    Ok(())
+}
+
+pub async fn handle_cmsg_pet_learn_talent(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    packet: &CMSG_PET_LEARN_TALENT,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    //The client greys out talents it can't learn, so a failure here means it's out of sync or cheating
+    if let Err(e) = character.learn_pet_talent(packet.talent, packet.rank as u8) {
+        warn!(
+            "{} could not learn pet talent {} rank {}: {:?}",
+            character.name, packet.talent, packet.rank, e
+        );
+    }
+    Ok(())
+}
+
+pub async fn handle_cmsg_pet_unlearn(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    _packet: &CMSG_PET_UNLEARN,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    if !character.reset_pet_talents() {
+        warn!("{} tried to reset the talents of a pet they don't have out", character.name);
+    }
+    Ok(())
+}
+
+//Pets don't exist as creatures in the world yet, so the only command that does anything is sending the pet away
+pub async fn handle_cmsg_pet_action(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_PET_ACTION,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    let action_type = packet.data >> 24;
+    let action = packet.data & 0x00FF_FFFF;
+    match (action_type, action) {
+        (PET_ACTION_TYPE_COMMAND, PET_COMMAND_DISMISS) => character.dismiss_pet(&world.get_realm_database()).await,
+        _ => {
+            trace!("{} used pet action {:#x} which isn't supported yet", character.name, packet.data);
+            Ok(())
+        }
+    }
+}
+
+//Called by the Summon Pet effect, demons that were never summoned before are created on the spot
+pub async fn summon_pet(caster_guid: Guid, entry: u32, character_manager: &mut CharacterManager, world: &World) -> Result<()> {
+    let realm_db = world.get_realm_database();
+    let character = character_manager.get_character_mut(caster_guid)?;
+    if character.summon_pet(&realm_db, entry).await? {
+        return Ok(());
+    }
+    if entry == 0 {
+        trace!("{} has no pet to call", character.name);
+        return Ok(());
+    }
+    let Some(template) = world.get_instance_manager().get_creature_template(entry) else {
+        warn!("{} tried to summon pet {} which has no creature template", character.name, entry);
+        return Ok(());
+    };
+    character.create_pet(&realm_db, entry, PET_TYPE_SUMMON, &template.name).await
+}
+
+//Called by the Feed Pet effect with the food the hunter clicked, the food is eaten whether the pet liked it or not
+pub async fn feed_pet(caster_guid: Guid, food: Guid, character_manager: &mut CharacterManager, world: &World) -> Result<()> {
+    let character = character_manager.get_character_mut(caster_guid)?;
+    let Some(item_position) = character.find_inventory_item(food) else {
+        return Ok(());
+    };
+    let item_id = character
+        .get_inventory_item(item_position)
+        .and_then(|item| item.update_state.object_entry())
+        .unwrap_or(0) as u32;
+    let template = world.get_game_database().get_item_template(item_id).await?;
+    if template.food_type == 0 {
+        warn!("{} tried to feed item {} to their pet, which isn't pet food", character.name, item_id);
+        return Ok(());
+    }
+
+    let gain = character.feed_pet(template.item_level.min(u8::MAX as u16) as u8);
+    let connection_sender = character.connection_sender.clone();
+    character
+        .set_item(None, item_position, Some(world.get_persistence_queue()), Some(&connection_sender))
+        .await?;
+    handlers::send_destroy_object(character, food, false).await?;
+    trace!(
+        "{} fed item {} to their pet for {} happiness, it is now {:?}",
+        character.name,
+        item_id,
+        gain,
+        character.get_pet_happiness()
+    );
+    Ok(())
+}
+
+//Called by the Learn Pet Spell effect of pet training books
+pub fn teach_pet_spell(caster_guid: Guid, spell_id: u32, character_manager: &mut CharacterManager) -> Result<()> {
+    let character = character_manager.get_character_mut(caster_guid)?;
+    if !character.teach_pet_spell(spell_id) {
+        warn!("{} tried to teach spell {} without a pet out", character.name, spell_id);
+    }
+    Ok(())
+}
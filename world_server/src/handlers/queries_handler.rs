@@ -3,13 +3,14 @@ use crate::client::Client;
 use crate::client_manager::ClientManager;
 use crate::connection::events::ServerEvent;
 use crate::prelude::*;
+use crate::world::character_info_cache::CharacterInfo;
 use crate::world::World;
-use crate::{character::Character, world::prelude::GameObject};
 use std::net::SocketAddr;
 use std::time::{SystemTime, UNIX_EPOCH};
 use wow_world_messages::wrath::{
-    CMSG_ITEM_NAME_QUERY, CMSG_ITEM_QUERY_SINGLE, CMSG_NAME_QUERY, CMSG_PLAYED_TIME, SMSG_ITEM_QUERY_SINGLE_RESPONSE, SMSG_NAME_QUERY_RESPONSE,
-    SMSG_PLAYED_TIME, SMSG_QUERY_TIME_RESPONSE, SMSG_WORLD_STATE_UI_TIMER_UPDATE,
+    CreatureFamily, SMSG_CREATURE_QUERY_RESPONSE_found, SMSG_GAMEOBJECT_QUERY_RESPONSE_found, CMSG_CREATURE_QUERY, CMSG_GAMEOBJECT_QUERY,
+    CMSG_ITEM_NAME_QUERY, CMSG_ITEM_QUERY_SINGLE, CMSG_NAME_QUERY, CMSG_PLAYED_TIME, SMSG_CREATURE_QUERY_RESPONSE, SMSG_GAMEOBJECT_QUERY_RESPONSE,
+    SMSG_ITEM_QUERY_SINGLE_RESPONSE, SMSG_NAME_QUERY_RESPONSE, SMSG_PLAYED_TIME, SMSG_QUERY_TIME_RESPONSE, SMSG_WORLD_STATE_UI_TIMER_UPDATE,
 };
 
 pub async fn handle_cmsg_played_time(
@@ -19,7 +20,7 @@ pub async fn handle_cmsg_played_time(
     packet: &CMSG_PLAYED_TIME,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
+    let guid = client.get_active_character()?;
     let character = character_manager.get_character_mut(guid)?;
 
     let (total_played_time, level_played_time) = {
@@ -66,46 +67,32 @@ pub async fn handle_cmsg_name_query(
     client_manager: &ClientManager,
     character_manager: &CharacterManager,
     client_id: SocketAddr,
-    world: &World,
+    world: &mut World,
     packet: &CMSG_NAME_QUERY,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
-    let character = character_manager.get_character(guid)?;
 
-    //Stop early if we are requesting our own information
-    if character.get_guid() == packet.guid {
-        return send_name_query_response(client, character).await;
+    //Online characters answer for themselves, everyone else comes from the character info cache
+    if let Some(character) = character_manager.find_character(packet.guid) {
+        return send_name_query_response(client, packet.guid, &CharacterInfo::of_character(character)).await;
     }
 
-    //We are requesting somebody else. Search the map
-    if let Some(map) = world.get_instance_manager().try_get_map_for_character(character) {
-        if map.find_character(packet.guid) {
-            let found_character = character_manager.get_character(packet.guid)?;
-            send_name_query_response(client, found_character).await?;
-        } else {
-            //This character is not on the same map as whoever requested it, so we do a lookup via
-            //the client manager.
-            if let Ok(found_client) = client_manager.find_client_from_active_character_guid(packet.guid) {
-                let guid = found_client.get_active_character();
-                let character = character_manager.get_character(guid)?;
-                send_name_query_response(client, character).await?;
-            }
-        }
-    } else {
-        bail!("Character that requested cmsg_name_query has invalid instance_id");
+    let realm_db = world.get_realm_database();
+    match world.get_character_info_cache_mut().resolve(packet.guid, &realm_db).await? {
+        Some(info) => send_name_query_response(client, packet.guid, info).await,
+        //The client keeps showing "Unknown" for guids that don't exist
+        None => Ok(()),
     }
-    Ok(())
 }
 
-async fn send_name_query_response(receiver: &Client, target_character: &Character) -> Result<()> {
+async fn send_name_query_response(receiver: &Client, guid: Guid, info: &CharacterInfo) -> Result<()> {
     let msg = SMSG_NAME_QUERY_RESPONSE {
-        guid: target_character.get_guid(),
-        character_name: target_character.name.clone(),
+        guid,
+        character_name: info.name.clone(),
         realm_name: String::new(),
-        race: target_character.get_race(),
-        class: target_character.get_class(),
-        gender: target_character.get_gender(),
+        race: info.race,
+        class: info.class,
+        gender: info.gender,
         has_declined_names: wow_world_messages::wrath::SMSG_NAME_QUERY_RESPONSE_DeclinedNames::No,
     };
     let event = ServerEvent::NameQueryResponse(msg);
@@ -127,7 +114,17 @@ pub async fn handle_cmsg_item_query_single(
             item: packet.item | 0x80000000,
             found: None,
         },
-        Some(item) => wow_world_messages::wrath::item_to_query_response(item),
+        Some(item) => {
+            let mut msg = wow_world_messages::wrath::item_to_query_response(item);
+            if let (Some(found), Some(text)) = (
+                msg.found.as_mut(),
+                client_manager.data_storage.get_localized_item_text(packet.item, client.data.locale),
+            ) {
+                found.name1 = text.name.clone();
+                found.description = text.secondary.clone();
+            }
+            msg
+        }
     };
     let event = ServerEvent::ItemQuerySingleResponse(msg);
     client.connection_sender.send_async(event).await?;
@@ -145,7 +142,10 @@ pub async fn handle_cmsg_item_name_query(
     let client = client_manager.get_client(client_id)?;
     match item {
         Some(item) => {
-            let msg = wow_world_messages::wrath::item_to_name_query_response(item);
+            let mut msg = wow_world_messages::wrath::item_to_name_query_response(item);
+            if let Some(text) = client_manager.data_storage.get_localized_item_text(packet.item, client.data.locale) {
+                msg.item_name = text.name.clone();
+            }
             let event = ServerEvent::ItemNameQueryResponse(msg);
             client.connection_sender.send_async(event).await?;
             Ok(())
@@ -153,3 +153,89 @@ pub async fn handle_cmsg_item_name_query(
         None => Err(anyhow!("Item {} not found for client {}", packet.item, client_id)),
     }
 }
+
+//Unknown entries get the entry back with the high bit set, which tells the client to stop asking
+const QUERY_NOT_FOUND_FLAG: u32 = 0x80000000;
+
+pub async fn handle_cmsg_creature_query(client_manager: &ClientManager, client_id: SocketAddr, packet: &CMSG_CREATURE_QUERY) -> Result<()> {
+    let client = client_manager.get_client(client_id)?;
+    let data_storage = &client_manager.data_storage;
+
+    let msg = match data_storage.get_creature_template(packet.creature) {
+        None => SMSG_CREATURE_QUERY_RESPONSE {
+            creature_entry: packet.creature | QUERY_NOT_FOUND_FLAG,
+            found: None,
+        },
+        Some(template) => {
+            let (name, subname) = data_storage.get_localized_creature_text(template, client.data.locale);
+            SMSG_CREATURE_QUERY_RESPONSE {
+                creature_entry: template.entry,
+                found: Some(SMSG_CREATURE_QUERY_RESPONSE_found {
+                    name1: name.to_string(),
+                    name2: String::new(),
+                    name3: String::new(),
+                    name4: String::new(),
+                    sub_name: subname.to_string(),
+                    //Despite the name this is the cursor icon
+                    description: template.icon_name.clone(),
+                    type_flags: template.type_flags,
+                    creature_type: template.creature_type,
+                    creature_family: CreatureFamily::try_from(template.family).unwrap_or(CreatureFamily::None),
+                    creature_rank: template.creature_rank,
+                    kill_credit1: template.kill_credit1,
+                    kill_credit2: template.kill_credit2,
+                    display_ids: [template.display_id1, template.display_id2, template.display_id3, template.display_id4],
+                    health_multiplier: template.health_modifier,
+                    mana_multiplier: template.mana_modifier,
+                    racial_leader: template.racial_leader,
+                    //Quest drops aren't implemented yet
+                    quest_items: [0; 6],
+                    movement_id: template.movement_id,
+                }),
+            }
+        }
+    };
+
+    let event = ServerEvent::CreatureQueryResponse(msg);
+    client.connection_sender.send_async(event).await?;
+    Ok(())
+}
+
+pub async fn handle_cmsg_gameobject_query(client_manager: &ClientManager, client_id: SocketAddr, packet: &CMSG_GAMEOBJECT_QUERY) -> Result<()> {
+    let client = client_manager.get_client(client_id)?;
+    let data_storage = &client_manager.data_storage;
+
+    let msg = match data_storage.get_gameobject_template(packet.entry_id) {
+        None => SMSG_GAMEOBJECT_QUERY_RESPONSE {
+            entry_id: packet.entry_id | QUERY_NOT_FOUND_FLAG,
+            found: None,
+        },
+        Some(template) => {
+            let (name, cast_bar_caption) = data_storage.get_localized_gameobject_text(template, client.data.locale);
+            //Only the first four data fields are stored, the rest only matter for types the server doesn't handle yet
+            let mut raw_data = [0; 24];
+            raw_data[..4].copy_from_slice(&[template.data0, template.data1, template.data2, template.data3]);
+            SMSG_GAMEOBJECT_QUERY_RESPONSE {
+                entry_id: template.entry,
+                found: Some(SMSG_GAMEOBJECT_QUERY_RESPONSE_found {
+                    info_type: template.gameobject_type as u32,
+                    display_id: template.display_id,
+                    name1: name.to_string(),
+                    name2: String::new(),
+                    name3: String::new(),
+                    name4: String::new(),
+                    icon_name: template.icon_name.clone(),
+                    cast_bar_caption: cast_bar_caption.to_string(),
+                    unknown: String::new(),
+                    raw_data,
+                    gameobject_size: template.size,
+                    gameobject_quest_items: [0; 6],
+                }),
+            }
+        }
+    };
+
+    let event = ServerEvent::GameobjectQueryResponse(msg);
+    client.connection_sender.send_async(event).await?;
+    Ok(())
+}
@@ -0,0 +1,451 @@
+use std::net::SocketAddr;
+
+use wow_world_messages::wrath::{
+    Gold, QuestFailedReason, QuestGiverReward, CMSG_QUESTGIVER_ACCEPT_QUEST, CMSG_QUESTGIVER_CHOOSE_REWARD, CMSG_QUESTGIVER_COMPLETE_QUEST,
+    CMSG_USE_ITEM, SMSG_QUESTGIVER_OFFER_REWARD, SMSG_QUESTGIVER_QUEST_COMPLETE, SMSG_QUESTGIVER_QUEST_DETAILS, SMSG_QUESTGIVER_QUEST_INVALID,
+    SMSG_QUESTLOG_FULL, SMSG_QUESTUPDATE_ADD_KILL, SMSG_QUESTUPDATE_COMPLETE,
+};
+use wrath_common::FeatureFlags;
+use wrath_game_db::{DBQuestRequiredItem, DBQuestRewardItem, DBQuestTemplate};
+
+use crate::audit::{log_audit_event, AuditEvent, AuditSource};
+use crate::character::character_experience::{self, MAX_LEVEL};
+use crate::character::character_inventory::InventoryError;
+use crate::character::character_manager::CharacterManager;
+use crate::character::character_quests::{QuestOfferError, QuestStatus};
+use crate::character::Character;
+use crate::client_manager::ClientManager;
+use crate::connection::events::ServerEvent;
+use crate::handlers;
+use crate::localization::ClientLocale;
+use crate::prelude::*;
+use crate::world::World;
+
+//Items with on-use spells need the spell system, the only items that do something when used so far are the
+//ones that start a quest
+pub async fn handle_cmsg_use_item(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_USE_ITEM,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    let Some((_, quest)) = find_quest_started_by_item(world, character, data.item).await? else {
+        return Ok(());
+    };
+
+    match character.check_quest_offer(&quest) {
+        Ok(()) => send_quest_details(world, character, data.item, &quest).await,
+        Err(error) => send_quest_offer_error(character, error).await,
+    }
+}
+
+//Quests can only be taken from items so far, there are no quest giving creatures yet
+pub async fn handle_cmsg_questgiver_accept_quest(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_QUESTGIVER_ACCEPT_QUEST,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    let (item_position, quest) = match find_quest_started_by_item(world, character, data.guid).await? {
+        Some((item_position, quest)) => (Some(item_position), quest),
+        None if character.take_offered_follow_up_quest(data.quest_id) => match world.get_game_database().get_quest_template(data.quest_id).await? {
+            Some(quest) => (None, quest),
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+    if quest.id != data.quest_id {
+        warn!(
+            "{} tried to take quest {} from an item that starts quest {}",
+            character.name, data.quest_id, quest.id
+        );
+        return Ok(());
+    }
+    if let Err(error) = character.check_quest_offer(&quest) {
+        return send_quest_offer_error(character, error).await;
+    }
+
+    character.add_quest(&world.get_realm_database(), quest.id).await?;
+
+    //The item has done its job once the quest is taken
+    let Some(item_position) = item_position else {
+        info!("{} took follow-up quest {}", character.name, quest.id);
+        return Ok(());
+    };
+    character
+        .set_item(None, item_position, Some(world.get_persistence_queue()), Some(&client.connection_sender))
+        .await?;
+    handlers::send_destroy_object(character, data.guid, false).await?;
+    info!("{} took quest {} from an item", character.name, quest.id);
+    Ok(())
+}
+
+//There are no creatures that end quests yet, so quests are turned in with whatever quest giver the client names
+pub async fn handle_cmsg_questgiver_complete_quest(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_QUESTGIVER_COMPLETE_QUEST,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+
+    let Some((quest, _)) = find_quest_to_turn_in(world, character, data.quest_id).await? else {
+        return ServerEvent::QuestGiverQuestInvalid(SMSG_QUESTGIVER_QUEST_INVALID {
+            msg: QuestFailedReason::QuestFailedMissingItems,
+        })
+        .send_to_character(character)
+        .await;
+    };
+    let reward_items = world.get_game_database().get_quest_reward_items(quest.id).await?;
+    send_offer_reward(world, character, data.guid, &quest, &reward_items).await
+}
+
+pub async fn handle_cmsg_questgiver_choose_reward(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_QUESTGIVER_CHOOSE_REWARD,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
+    let game_db = world.get_game_database();
+    let realm_db = world.get_realm_database();
+
+    let Some((quest, required_items)) = find_quest_to_turn_in(world, character, data.quest_id).await? else {
+        return Ok(());
+    };
+    let reward_items = game_db.get_quest_reward_items(quest.id).await?;
+    let Some(rewards) = choose_reward_items(&reward_items, data.reward) else {
+        warn!(
+            "{} picked reward {} of quest {}, which doesn't have it",
+            character.name, data.reward, quest.id
+        );
+        return Ok(());
+    };
+
+    //The required items are taken first, their backpack slots can hold the rewards
+    let freed_slots: usize = required_items.iter().map(|item| item.count as usize).sum();
+    let needed_slots: usize = rewards.iter().map(|&(_, count)| count as usize).sum();
+    if character.get_free_backpack_slots().len() + freed_slots < needed_slots {
+        return character.send_inventory_change_failure(InventoryError::InventoryFull, Guid::zero()).await;
+    }
+
+    let source = AuditSource::Quest { quest_id: quest.id };
+    for required in &required_items {
+        for item_position in character.find_backpack_items(required.item).into_iter().take(required.count as usize) {
+            let item_guid = character
+                .get_inventory_item(item_position)
+                .and_then(|item| item.update_state.object_guid());
+            character
+                .set_item(None, item_position, Some(world.get_persistence_queue()), Some(&client.connection_sender))
+                .await?;
+            if let Some(item_guid) = item_guid {
+                handlers::send_destroy_object(character, item_guid, false).await?;
+            }
+        }
+        let event = AuditEvent::ItemDestroyed {
+            item_id: required.item,
+            count: required.count as u32,
+        };
+        log_audit_event(&realm_db, client.data.account_id, guid, event, source).await?;
+    }
+    for &(item_id, count) in &rewards {
+        for _ in 0..count {
+            character
+                .try_add_item_to_backpack(
+                    item_id,
+                    guid.guid() as u32,
+                    &client.connection_sender,
+                    Some(world.get_persistence_queue()),
+                )
+                .await;
+        }
+        let event = AuditEvent::ItemCreated {
+            item_id,
+            count: count as u32,
+        };
+        log_audit_event(&realm_db, client.data.account_id, guid, event, source).await?;
+    }
+
+    let (experience, money) = get_quest_payout(character, &quest);
+    let levels = character.give_experience(experience);
+    if levels > 0 {
+        info!("{} reached level {}", character.name, character.gameplay_data.unit_level().unwrap_or(1));
+        handlers::announce_guild_level_up(guid, &client_manager.data_storage, character_manager, world).await?;
+    }
+    let character = character_manager.get_character_mut(guid)?;
+    if money > 0 {
+        character.set_money(character.get_money().saturating_add(money));
+        log_audit_event(&realm_db, client.data.account_id, guid, AuditEvent::MoneyGained(money), source).await?;
+    }
+    for reputation in game_db.get_quest_reward_reputations(quest.id).await? {
+        let Some(reputation_index) = client_manager.data_storage.get_reputation_index(reputation.faction) else {
+            warn!(
+                "Quest {} rewards reputation with faction {}, which has no reputation",
+                quest.id, reputation.faction
+            );
+            continue;
+        };
+        let standing = character
+            .add_reputation(&realm_db, reputation.faction, reputation_index, reputation.value)
+            .await?;
+        handlers::send_faction_standing(character, reputation_index, standing).await?;
+    }
+
+    character.set_quest_rewarded(&realm_db, quest.id).await?;
+    ServerEvent::QuestGiverQuestComplete(SMSG_QUESTGIVER_QUEST_COMPLETE {
+        quest_id: quest.id,
+        experience_reward: experience,
+        money_reward: Gold::new(money),
+        honor_reward: 0,
+        talent_reward: 0,
+        arena_point_reward: 0,
+        item_rewards: vec![],
+    })
+    .send_to_character(character)
+    .await?;
+    info!("{} turned in quest {}", character.name, quest.id);
+
+    //Quest chains go on right away with the same quest giver
+    let Some(next_quest_id) = quest.next_quest_id else {
+        return Ok(());
+    };
+    let Some(next_quest) = game_db.get_quest_template(next_quest_id).await? else {
+        warn!("Quest {} is followed by quest {}, which doesn't exist", quest.id, next_quest_id);
+        return Ok(());
+    };
+    if character.check_quest_offer(&next_quest).is_err() {
+        return Ok(());
+    }
+    character.offer_follow_up_quest(next_quest.id);
+    send_quest_details(world, character, data.guid, &next_quest).await
+}
+
+//The client tells creature and gameobject objectives apart by this bit of the objective entry
+const GAMEOBJECT_OBJECTIVE_FLAG: u32 = 0x80000000;
+
+//A used gameobject counts towards every quest in the quest log that asks for it
+pub async fn credit_gameobject_use(character: &mut Character, gameobject: Guid, gameobject_entry: u32, world: &World) -> Result<()> {
+    let game_db = world.get_game_database();
+    let realm_db = world.get_realm_database();
+    for quest_id in character.get_quests_in_log() {
+        let required_gameobjects = game_db.get_quest_required_gameobjects(quest_id).await?;
+        let Some(required) = required_gameobjects.iter().find(|required| required.gameobject_entry == gameobject_entry) else {
+            continue;
+        };
+        let Some(count) = character
+            .add_quest_objective_credit(&realm_db, quest_id, gameobject_entry, required.count)
+            .await?
+        else {
+            continue;
+        };
+        ServerEvent::QuestUpdateAddKill(SMSG_QUESTUPDATE_ADD_KILL {
+            quest_id,
+            create_id: gameobject_entry | GAMEOBJECT_OBJECTIVE_FLAG,
+            kill_count: count as u32,
+            required_kill_count: required.count as u32,
+            guid: gameobject,
+        })
+        .send_to_character(character)
+        .await?;
+        if find_quest_to_turn_in(world, character, quest_id).await?.is_some() {
+            ServerEvent::QuestUpdateComplete(SMSG_QUESTUPDATE_COMPLETE { quest_id })
+                .send_to_character(character)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+//The quest, if it's in the quest log, every objective is done and everything it asks for is in the backpack, with
+//the items it asks for
+async fn find_quest_to_turn_in(world: &World, character: &Character, quest_id: u32) -> Result<Option<(DBQuestTemplate, Vec<DBQuestRequiredItem>)>> {
+    if character.get_quest_status(quest_id) != Some(QuestStatus::InQuestLog) {
+        return Ok(None);
+    }
+    let game_db = world.get_game_database();
+    let Some(quest) = game_db.get_quest_template(quest_id).await? else {
+        return Ok(None);
+    };
+    let objectives_done = game_db
+        .get_quest_required_gameobjects(quest_id)
+        .await?
+        .iter()
+        .all(|required| character.get_quest_objective_count(quest_id, required.gameobject_entry) >= required.count);
+    let required_items = game_db.get_quest_required_items(quest_id).await?;
+    let has_required_items = required_items
+        .iter()
+        .all(|required| character.find_backpack_items(required.item).len() >= required.count as usize);
+    Ok((objectives_done && has_required_items).then_some((quest, required_items)))
+}
+
+//Every item that isn't a choice, plus the chosen one. The choice is ignored when there is nothing to choose from.
+fn choose_reward_items(reward_items: &[DBQuestRewardItem], choice: u32) -> Option<Vec<(u32, u8)>> {
+    let choices: Vec<&DBQuestRewardItem> = reward_items.iter().filter(|item| item.is_choice).collect();
+    let mut rewards: Vec<(u32, u8)> = reward_items
+        .iter()
+        .filter(|item| !item.is_choice)
+        .map(|item| (item.item, item.count))
+        .collect();
+    if !choices.is_empty() {
+        let chosen = choices.get(choice as usize)?;
+        rewards.push((chosen.item, chosen.count));
+    }
+    Some(rewards)
+}
+
+//Experience and money after the character's level and the realm's rates, no experience at the level cap
+fn get_quest_payout(character: &Character, quest: &DBQuestTemplate) -> (u32, u32) {
+    let rates = FeatureFlags::from_env().rates;
+    let level = character.gameplay_data.unit_level().unwrap_or(1) as u8;
+    let experience = match level < MAX_LEVEL {
+        true => character_experience::scale_quest_experience(quest.reward_xp, level, quest.quest_level, rates.experience),
+        false => 0,
+    };
+    let money = (quest.reward_money as f32 * rates.money) as u32;
+    (experience, money)
+}
+
+async fn send_offer_reward(
+    world: &World,
+    character: &Character,
+    quest_giver: Guid,
+    quest: &DBQuestTemplate,
+    reward_items: &[DBQuestRewardItem],
+) -> Result<()> {
+    let game_db = world.get_game_database();
+    let mut choice_item_rewards = vec![];
+    let mut item_rewards = vec![];
+    for reward_item in reward_items {
+        let reward = QuestGiverReward {
+            item: reward_item.item,
+            item_count: reward_item.count as u32,
+            display_id: game_db.get_item_template(reward_item.item).await?.displayid,
+        };
+        match reward_item.is_choice {
+            true => choice_item_rewards.push(reward),
+            false => item_rewards.push(reward),
+        }
+    }
+    let (experience, money) = get_quest_payout(character, quest);
+
+    ServerEvent::QuestGiverOfferReward(SMSG_QUESTGIVER_OFFER_REWARD {
+        npc: quest_giver,
+        quest_id: quest.id,
+        title: quest.title.clone(),
+        offer_reward_text: quest.offer_reward_text.clone().unwrap_or_default(),
+        auto_finish: false,
+        flags: quest.flags,
+        suggested_players: quest.suggested_players as u32,
+        emotes: vec![],
+        choice_item_rewards,
+        item_rewards,
+        money_reward: Gold::new(money),
+        experience_reward: experience,
+        honor_reward: 0,
+        honor_reward_multiplier: 0.0,
+        unknown1: 0,
+        reward_spell: 0,
+        casted_spell: 0,
+        title_reward: 0,
+        reward_talents: 0,
+        reward_arena_points: 0,
+        reward_reputation_mask: 0,
+        reward_factions: [0; 5],
+        reward_factions_values: [0; 5],
+        reward_factions_override: [0; 5],
+    })
+    .send_to_character(character)
+    .await
+}
+
+//The inventory position of the item and the quest it starts
+async fn find_quest_started_by_item(world: &World, character: &Character, item_guid: Guid) -> Result<Option<((u8, u8), DBQuestTemplate)>> {
+    let Some(item_position) = character.find_inventory_item(item_guid) else {
+        return Ok(None);
+    };
+    let item_id = character
+        .get_inventory_item(item_position)
+        .and_then(|item| item.update_state.object_entry())
+        .unwrap_or(0) as u32;
+
+    let game_db = world.get_game_database();
+    let Some(quest_id) = game_db.get_item_template(item_id).await?.start_quest_id else {
+        return Ok(None);
+    };
+    let Some(quest) = game_db.get_quest_template(quest_id).await? else {
+        warn!("Item {} starts quest {}, which doesn't exist", item_id, quest_id);
+        return Ok(None);
+    };
+    Ok(Some((item_position, quest)))
+}
+
+async fn send_quest_details(world: &World, character: &Character, quest_giver: Guid, quest: &DBQuestTemplate) -> Result<()> {
+    //Quest texts are looked up when offered, like the quest itself, and fall back to English
+    let locale = character.get_client_locale();
+    let translation = match locale {
+        ClientLocale::EnUs => None,
+        locale => world.get_game_database().get_quest_template_locale(quest.id, locale.code()).await?,
+    };
+    let (title, details, objectives) = match translation {
+        Some(text) => (text.title, text.details, text.objectives),
+        None => (quest.title.clone(), quest.details.clone(), quest.objectives.clone()),
+    };
+
+    ServerEvent::QuestGiverQuestDetails(SMSG_QUESTGIVER_QUEST_DETAILS {
+        guid: quest_giver,
+        guid2: character.get_guid(),
+        quest_id: quest.id,
+        title,
+        details,
+        objectives,
+        auto_finish: false,
+        flags: quest.flags,
+        suggested_players: quest.suggested_players as u32,
+        is_finished: false,
+        choice_item_rewards: vec![],
+        item_rewards: vec![],
+        money_reward: Gold::new(0),
+        experience_reward: 0,
+        honor_reward: 0,
+        honor_reward_multiplier: 0.0,
+        reward_spell: 0,
+        casted_spell: 0,
+        title_reward: 0,
+        reward_talents: 0,
+        reward_arena_points: 0,
+        unknown1: 0,
+        reward_factions: [0; 5],
+        reward_factions_values: [0; 5],
+        reward_factions_override: [0; 5],
+        emotes: vec![],
+    })
+    .send_to_character(character)
+    .await
+}
+
+async fn send_quest_offer_error(character: &Character, error: QuestOfferError) -> Result<()> {
+    let reason = match error {
+        QuestOfferError::AlreadyOnQuest => QuestFailedReason::QuestAlreadyOn,
+        QuestOfferError::AlreadyDone => QuestFailedReason::QuestAlreadyDone,
+        QuestOfferError::LevelTooLow => QuestFailedReason::QuestFailedLowLevel,
+        QuestOfferError::QuestLogFull => {
+            return ServerEvent::QuestLogFull(SMSG_QUESTLOG_FULL {}).send_to_character(character).await;
+        }
+    };
+    ServerEvent::QuestGiverQuestInvalid(SMSG_QUESTGIVER_QUEST_INVALID { msg: reason })
+        .send_to_character(character)
+        .await
+}
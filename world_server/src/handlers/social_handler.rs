@@ -1,16 +1,28 @@
 use std::net::SocketAddr;
 
+use crate::character::character_currency::{ARENA_POINTS, HONOR_POINTS};
+use crate::character::character_deserter::QueuePunishment;
 use crate::character::character_manager::CharacterManager;
+use crate::character::character_social::{SocialError, RELATION_FRIEND, RELATION_IGNORED};
+use crate::chat::hyperlinks;
+use crate::chat::language;
+use crate::chat::logging::{ChatLogEntry, ChatLogType};
+use crate::chat::moderation::ChatVerdict;
 use crate::connection::events::ServerEvent;
+use crate::data::GameDataProvider;
+use crate::handlers::AnnouncementScope;
+use crate::localization::ServerString;
 use crate::prelude::*;
 use crate::world::prelude::GameObject;
 use crate::world::World;
 use crate::{character::*, client_manager::ClientManager};
 
-use wow_world_base::wrath::PlayerChatTag;
+use wow_world_base::wrath::{Language, PlayerChatTag};
 use wow_world_messages::wrath::{
-    CMSG_MESSAGECHAT_ChatType, RelationType, SMSG_MESSAGECHAT_ChatType, CMSG_CONTACT_LIST, CMSG_JOIN_CHANNEL, CMSG_MESSAGECHAT, CMSG_SET_SELECTION,
-    SMSG_CALENDAR_SEND_NUM_PENDING, SMSG_CONTACT_LIST, SMSG_MESSAGECHAT,
+    Area, CMSG_MESSAGECHAT_ChatType, Class, FriendStatus, Level, Race, Relation, RelationType, Relation_FriendStatus, Relation_RelationType,
+    Relation_RelationType_Friend, SMSG_FRIEND_STATUS_FriendResult, SMSG_MESSAGECHAT_ChatType, Vector3d, CMSG_ADD_FRIEND, CMSG_ADD_IGNORE,
+    CMSG_CONTACT_LIST, CMSG_DEL_FRIEND, CMSG_DEL_IGNORE, CMSG_MESSAGECHAT, CMSG_SET_SELECTION, SMSG_CONTACT_LIST, SMSG_FRIEND_STATUS,
+    SMSG_MESSAGECHAT,
 };
 
 pub async fn handle_cmsg_contact_list(
@@ -20,49 +32,221 @@ pub async fn handle_cmsg_contact_list(
     packet: &CMSG_CONTACT_LIST,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
+    let guid = client.get_active_character()?;
     let character = character_manager.get_character(guid)?;
 
     let requested_social_mask = RelationType::new(packet.flags);
-    send_contact_list(character, requested_social_mask).await
+    send_contact_list(character, character_manager, requested_social_mask).await
 }
 
-pub async fn handle_cmsg_calendar_get_num_pending(client_manager: &ClientManager, client_id: SocketAddr) -> Result<()> {
-    let client = client_manager.get_authenticated_client(client_id)?;
-    let msg = SMSG_CALENDAR_SEND_NUM_PENDING { pending_events: 0 };
-    let event = ServerEvent::CalendarSendNumPending(msg);
-    client.connection_sender.send_async(event).await?;
-    Ok(())
+//Area, level and class of a friend that is online, what the friends list shows next to the name
+fn online_friend_status(guid: Guid, character_manager: &CharacterManager) -> Option<(Area, u8, Class)> {
+    character_manager.find_character(guid).map(friend_status)
+}
+
+fn friend_status(friend: &Character) -> (Area, u8, Class) {
+    let level = friend.gameplay_data.unit_level().unwrap_or(1) as u8;
+    (friend.area, level, friend.get_class())
 }
 
-pub async fn send_contact_list(character: &Character, relation_mask: RelationType) -> Result<()> {
+pub async fn send_contact_list(character: &Character, character_manager: &CharacterManager, relation_mask: RelationType) -> Result<()> {
+    let relations = character
+        .get_social_relations()
+        .filter(|(_, relation)| relation.flags as u32 & relation_mask.as_int() != 0)
+        .map(|(guid, relation)| {
+            let mut mask = Relation_RelationType::empty();
+            if relation.flags & RELATION_FRIEND != 0 {
+                let status = match online_friend_status(guid, character_manager) {
+                    Some((area, level, class)) => Relation_FriendStatus::Online {
+                        area,
+                        level: Level::new(level),
+                        class,
+                    },
+                    None => Relation_FriendStatus::Offline,
+                };
+                mask = mask.set_friend(Relation_RelationType_Friend { status });
+            }
+            if relation.flags & RELATION_IGNORED != 0 {
+                mask = mask.set_ignored();
+            }
+            Relation {
+                guid,
+                relation_mask: mask,
+                note: relation.note.clone(),
+            }
+        })
+        .collect();
+
     let msg = SMSG_CONTACT_LIST {
         list_mask: relation_mask,
-        relations: vec![],
+        relations,
     };
     ServerEvent::ContactList(msg).send_to_character(character).await
 }
 
-pub async fn handle_cmsg_set_selection(
+async fn send_friend_status(character: &Character, guid: Guid, result: SMSG_FRIEND_STATUS_FriendResult) -> Result<()> {
+    ServerEvent::FriendStatus(SMSG_FRIEND_STATUS { result, guid })
+        .send_to_character(character)
+        .await
+}
+
+//The other character by name, along with whether it would be added to its own list or is on the other faction
+fn find_social_target(character: &Character, world: &World, name: &str) -> Option<(Guid, bool, bool)> {
+    let info = world.get_character_info_cache().find_by_name(name)?;
+    let is_self = info.guid == character.get_guid();
+    let is_enemy = is_alliance(info.race) != is_alliance(character.get_race());
+    Some((info.guid, is_self, is_enemy))
+}
+
+pub(super) fn is_alliance(race: Race) -> bool {
+    matches!(race, Race::Human | Race::Dwarf | Race::NightElf | Race::Gnome | Race::Draenei)
+}
+
+pub async fn handle_cmsg_add_friend(
     client_manager: &ClientManager,
     character_manager: &mut CharacterManager,
+    world: &World,
     client_id: SocketAddr,
-    packet: &CMSG_SET_SELECTION,
+    packet: &CMSG_ADD_FRIEND,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character(guid)?;
+
+    let Some((friend_guid, is_self, is_enemy)) = find_social_target(character, world, &packet.name) else {
+        return send_friend_status(character, Guid::zero(), SMSG_FRIEND_STATUS_FriendResult::NotFound).await;
+    };
+    if is_self {
+        return send_friend_status(character, friend_guid, SMSG_FRIEND_STATUS_FriendResult::Self_).await;
+    }
+    if is_enemy && !client.data.gm_level.is_staff() {
+        return send_friend_status(character, friend_guid, SMSG_FRIEND_STATUS_FriendResult::Enemy).await;
+    }
+
+    let online_status = online_friend_status(friend_guid, character_manager);
     let character = character_manager.get_character_mut(guid)?;
+    let result = match character
+        .add_social_relation(&world.get_realm_database(), friend_guid, RELATION_FRIEND, &packet.note)
+        .await?
+    {
+        Ok(()) => match online_status {
+            Some((area, level, class)) => SMSG_FRIEND_STATUS_FriendResult::AddedOnline {
+                note: packet.note.clone(),
+                status: FriendStatus::Online,
+                area,
+                level: Level::new(level),
+                class,
+            },
+            None => SMSG_FRIEND_STATUS_FriendResult::AddedOffline,
+        },
+        Err(SocialError::ListFull) => SMSG_FRIEND_STATUS_FriendResult::ListFull,
+        Err(_) => SMSG_FRIEND_STATUS_FriendResult::Already,
+    };
+    send_friend_status(character, friend_guid, result).await
+}
 
-    let selection = if packet.target.is_zero() { None } else { Some(packet.target) };
-    character.set_selection(selection);
+pub async fn handle_cmsg_del_friend(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_DEL_FRIEND,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    let result = match character
+        .remove_social_relation(&world.get_realm_database(), packet.guid, RELATION_FRIEND)
+        .await?
+    {
+        Ok(()) => SMSG_FRIEND_STATUS_FriendResult::Removed,
+        Err(_) => SMSG_FRIEND_STATUS_FriendResult::NotFound,
+    };
+    send_friend_status(character, packet.guid, result).await
+}
+
+pub async fn handle_cmsg_add_ignore(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_ADD_IGNORE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    //Players of the other faction can be ignored too, they can still whisper through GMs or emote
+    let Some((ignored_guid, is_self, _)) = find_social_target(character, world, &packet.name) else {
+        return send_friend_status(character, Guid::zero(), SMSG_FRIEND_STATUS_FriendResult::IgnoreNotFound).await;
+    };
+    if is_self {
+        return send_friend_status(character, ignored_guid, SMSG_FRIEND_STATUS_FriendResult::IgnoreSelf).await;
+    }
+
+    let result = match character
+        .add_social_relation(&world.get_realm_database(), ignored_guid, RELATION_IGNORED, "")
+        .await?
+    {
+        Ok(()) => SMSG_FRIEND_STATUS_FriendResult::IgnoreAdded,
+        Err(SocialError::ListFull) => SMSG_FRIEND_STATUS_FriendResult::IgnoreFull,
+        Err(_) => SMSG_FRIEND_STATUS_FriendResult::IgnoreAlready,
+    };
+    send_friend_status(character, ignored_guid, result).await
+}
+
+pub async fn handle_cmsg_del_ignore(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_DEL_IGNORE,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    let result = match character
+        .remove_social_relation(&world.get_realm_database(), packet.guid, RELATION_IGNORED)
+        .await?
+    {
+        Ok(()) => SMSG_FRIEND_STATUS_FriendResult::IgnoreRemoved,
+        Err(_) => SMSG_FRIEND_STATUS_FriendResult::IgnoreNotFound,
+    };
+    send_friend_status(character, packet.guid, result).await
+}
+
+//Tells every online character that has this one as a friend that it came online or went offline
+pub async fn notify_friends_of_status(character: &Character, character_manager: &CharacterManager, online: bool) -> Result<()> {
+    let guid = character.get_guid();
+    let result = if online {
+        let (area, level, class) = friend_status(character);
+        SMSG_FRIEND_STATUS_FriendResult::Online {
+            status: FriendStatus::Online,
+            area,
+            level: Level::new(level),
+            class,
+        }
+    } else {
+        SMSG_FRIEND_STATUS_FriendResult::Offline
+    };
+    let event = ServerEvent::FriendStatus(SMSG_FRIEND_STATUS { result, guid });
+    for other in character_manager.get_all_characters().filter(|other| other.is_friend(guid)) {
+        event.send_to_character(other).await?;
+    }
     Ok(())
 }
 
-pub async fn handle_cmsg_join_channel(client_manager: &ClientManager, client_id: SocketAddr, _packet: &CMSG_JOIN_CHANNEL) -> Result<()> {
+pub async fn handle_cmsg_set_selection(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    packet: &CMSG_SET_SELECTION,
+) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let _character = client.get_active_character();
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
 
-    //There are no chat systems yet. This packet is "handled" to silence the warning spam
+    let selection = if packet.target.is_zero() { None } else { Some(packet.target) };
+    character.set_selection(selection);
     Ok(())
 }
 
@@ -74,20 +258,80 @@ pub async fn handle_cmsg_messagechat(
     packet: &CMSG_MESSAGECHAT,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
-    let character = character_manager.get_character(guid)?;
+    let guid = client.get_active_character()?;
 
     // Check for GM commands
     if packet.message.starts_with('.') {
         return handle_gm_command(client_manager, character_manager, world, client_id, &packet.message).await;
     }
 
+    //Moderation runs before anything is broadcast, muted and spamming characters only hear back from the server
+    let character = character_manager.get_character_mut(guid)?;
+    let message = match character.moderate_chat_message(world.get_chat_moderation(), &packet.message) {
+        ChatVerdict::Allow(message) => message,
+        ChatVerdict::Muted { remaining } => {
+            let reply = client_manager
+                .data_storage
+                .localize(client.data.locale, ServerString::ChatMuted, &[&remaining.as_secs().max(1)]);
+            return handlers::send_system_message_to_character(character, &reply).await;
+        }
+        ChatVerdict::RateLimited { mute_duration } => {
+            let reply = client_manager
+                .data_storage
+                .localize(client.data.locale, ServerString::ChatSpamMuted, &[&mute_duration.as_secs()]);
+            return handlers::send_system_message_to_character(character, &reply).await;
+        }
+        ChatVerdict::Duplicate => return Ok(()),
+    };
+    let character = character_manager.get_character(guid)?;
+    if !language::knows_language(character, packet.language) {
+        let reply = client_manager
+            .data_storage
+            .localize(client.data.locale, ServerString::LanguageNotKnown, &[]);
+        return handlers::send_system_message_to_character(character, &reply).await;
+    }
+    //Addon messages aren't shown to anyone, only what ends up in a chat frame can crash a client
+    if packet.language != Language::Addon {
+        if let Err(e) = hyperlinks::validate_hyperlinks(&message, &client_manager.data_storage, &world.get_game_database()).await {
+            warn!("Dropped chat message from {} with a bad hyperlink ({:?}): {}", character.name, e, message);
+            let reply = client_manager
+                .data_storage
+                .localize(client.data.locale, ServerString::InvalidChatLink, &[]);
+            return handlers::send_system_message_to_character(character, &reply).await;
+        }
+    }
+
+    if let Some((chat_type, receiver)) = match &packet.chat_type {
+        CMSG_MESSAGECHAT_ChatType::Say => Some((ChatLogType::Say, None)),
+        CMSG_MESSAGECHAT_ChatType::Yell => Some((ChatLogType::Yell, None)),
+        CMSG_MESSAGECHAT_ChatType::Emote => Some((ChatLogType::Emote, None)),
+        CMSG_MESSAGECHAT_ChatType::Whisper { target_player } => Some((ChatLogType::Whisper, Some(target_player.clone()))),
+        CMSG_MESSAGECHAT_ChatType::Guild | CMSG_MESSAGECHAT_ChatType::Officer => Some((ChatLogType::Guild, None)),
+        CMSG_MESSAGECHAT_ChatType::Party => Some((ChatLogType::Party, None)),
+        CMSG_MESSAGECHAT_ChatType::Channel { channel } => Some((ChatLogType::Channel, Some(channel.clone()))),
+        _ => None,
+    } {
+        world.get_chat_logger().log(ChatLogEntry {
+            chat_type,
+            sender_id: guid.guid() as u32,
+            sender_name: character.name.clone(),
+            receiver,
+            message: message.clone(),
+        });
+    }
+
     match &packet.chat_type {
         CMSG_MESSAGECHAT_ChatType::Say | CMSG_MESSAGECHAT_ChatType::Yell | CMSG_MESSAGECHAT_ChatType::Emote => {
-            handle_world_proximity_message(character, character_manager, world, packet).await?
+            handle_world_proximity_message(character, character_manager, world, packet, &message).await?
         }
         CMSG_MESSAGECHAT_ChatType::Whisper { target_player } => {
-            handle_whisper(character, target_player, client_manager, character_manager, packet).await?
+            handle_whisper(character, target_player, client_manager, character_manager, world, packet, &message).await?
+        }
+        CMSG_MESSAGECHAT_ChatType::Guild => handlers::send_guild_chat(character, character_manager, world, false, packet.language, &message).await?,
+        CMSG_MESSAGECHAT_ChatType::Officer => handlers::send_guild_chat(character, character_manager, world, true, packet.language, &message).await?,
+        CMSG_MESSAGECHAT_ChatType::Party => handlers::send_party_chat(character, character_manager, world, packet.language, &message).await?,
+        CMSG_MESSAGECHAT_ChatType::Channel { channel } => {
+            handlers::send_channel_message(character, character_manager, world, channel, packet.language, &message).await?
         }
         _ => {
             warn!("Unhandled chat type: {:?}", packet.chat_type);
@@ -103,6 +347,7 @@ async fn handle_world_proximity_message(
     character_manager: &CharacterManager,
     world: &World,
     packet: &CMSG_MESSAGECHAT,
+    message: &str,
 ) -> Result<()> {
     let chat_type = match packet.chat_type {
         CMSG_MESSAGECHAT_ChatType::Say => SMSG_MESSAGECHAT_ChatType::Say { target6: sender.get_guid() },
@@ -111,18 +356,38 @@ async fn handle_world_proximity_message(
         _ => bail!("This is not a world chat message type"),
     };
 
-    let tag = PlayerChatTag::None;
+    let tag = sender.get_chat_tag();
+    let message_event = |message: String| {
+        ServerEvent::MessageChat(SMSG_MESSAGECHAT {
+            chat_type: chat_type.clone(),
+            language: packet.language,
+            sender: sender.get_guid(),
+            flags: 0,
+            message,
+            tag,
+        })
+    };
+    let understood = message_event(message.to_string());
+    //Emotes are actions rather than speech, everyone can read them
+    let is_emote = matches!(packet.chat_type, CMSG_MESSAGECHAT_ChatType::Emote);
+    if is_emote || world.get_instance_manager().try_get_map_for_character(sender).is_none() {
+        return understood.send_to_all_in_range(sender, character_manager, true, world).await;
+    }
 
-    ServerEvent::MessageChat(SMSG_MESSAGECHAT {
-        chat_type,
-        language: packet.language,
-        sender: sender.get_guid(),
-        flags: 0,
-        message: packet.message.clone(),
-        tag,
-    })
-    .send_to_all_in_range(sender, character_manager, true, world)
-    .await
+    let scrambled = message_event(language::scramble(message, packet.language));
+    understood.send_to_character(sender).await?;
+    for &guid in sender.get_in_range_characters() {
+        let listener = character_manager.get_character(guid)?;
+        if !listener.is_in_range(sender.get_guid()) {
+            continue;
+        }
+        if language::knows_language(listener, packet.language) {
+            understood.send_to_character(listener).await?;
+        } else {
+            scrambled.send_to_character(listener).await?;
+        }
+    }
+    Ok(())
 }
 
 async fn handle_whisper(
@@ -130,20 +395,38 @@ async fn handle_whisper(
     receiver_name: &str,
     client_manager: &ClientManager,
     character_manager: &CharacterManager,
+    world: &World,
     packet: &CMSG_MESSAGECHAT,
+    message: &str,
 ) -> Result<()> {
     assert!(std::matches!(packet.chat_type, CMSG_MESSAGECHAT_ChatType::Whisper { .. }));
 
-    if let Ok(receiving_client) = client_manager.find_client_from_active_character_name(receiver_name, character_manager) {
+    let receiver = world.get_character_info_cache().find_by_name(receiver_name);
+    let receiving_client = receiver
+        .filter(|info| info.online)
+        .and_then(|info| client_manager.find_client_from_active_character_guid(info.guid).ok());
+    if let Some(receiving_client) = receiving_client {
         let chat_type = SMSG_MESSAGECHAT_ChatType::Whisper { target6: sender.get_guid() };
-        let tag = PlayerChatTag::None;
+        let tag = sender.get_chat_tag();
+        let receiver = receiving_client
+            .data
+            .active_character
+            .and_then(|guid| character_manager.find_character(guid));
+        //Whispers to someone that ignores the sender are dropped without telling the sender
+        if receiver.is_some_and(|receiver| receiver.is_ignoring(sender.get_guid())) {
+            return Ok(());
+        }
+        let message = match receiver {
+            Some(receiver) => language::message_for_listener(receiver, packet.language, message),
+            None => message.to_string(),
+        };
 
         let msg = SMSG_MESSAGECHAT {
             chat_type,
             language: packet.language,
             sender: sender.get_guid(),
             flags: 0,
-            message: packet.message.clone(),
+            message,
             tag,
         };
         let event = ServerEvent::MessageChat(msg);
@@ -154,7 +437,10 @@ async fn handle_whisper(
             language: wow_world_base::wrath::Language::Universal,
             sender: sender.get_guid(),
             flags: 0,
-            message: "No player by that name".to_string(),
+            message: match receiver {
+                Some(info) => format!("{} is not online", info.name),
+                None => "No player by that name".to_string(),
+            },
             tag: PlayerChatTag::None,
         };
         let event = ServerEvent::MessageChat(msg);
@@ -175,25 +461,173 @@ async fn handle_gm_command(
     if parts.is_empty() {
         return Ok(());
     }
+    //Everything after the command name, for commands that take free text
+    let text_argument = message[1..].split_once(char::is_whitespace).map_or("", |(_, rest)| rest.trim());
 
-    match parts[0].to_lowercase().as_str() {
+    let command = parts[0].to_lowercase();
+    let gm_level = client_manager.get_authenticated_client(client_id)?.data.gm_level;
+    match crate::handlers::required_gm_level(&command, text_argument) {
+        Some(required) if gm_level >= required => {}
+        //Commands the account isn't allowed to use look the same as commands that don't exist
+        _ => {
+            trace!("Client {} with level {:?} tried to use .{}", client_id, gm_level, command);
+            return Ok(());
+        }
+    }
+
+    let (data_storage, game_db) = (client_manager.data_storage.clone(), world.get_game_database());
+    let data_provider = GameDataProvider::new(&data_storage, &game_db);
+
+    match command.as_str() {
         "speed" => {
             let speed = parts.get(1).and_then(|s| s.parse::<f32>().ok()).unwrap_or(7.0);
             crate::handlers::handle_speed_command(client_manager, character_manager, client_id, speed).await?;
         }
-        "additem" => {
-            if let Some(item_id) = parts.get(1).and_then(|s| s.parse::<u32>().ok()) {
-                crate::handlers::handle_additem_command(
+        "gm" => {
+            crate::handlers::handle_gm_mode_command(client_manager, character_manager, client_id, &parts[1..]).await?;
+        }
+        "god" => {
+            crate::handlers::handle_god_command(client_manager, character_manager, client_id, parts.get(1).copied()).await?;
+        }
+        "fly" => {
+            crate::handlers::handle_fly_command(client_manager, character_manager, client_id, parts.get(1).copied()).await?;
+        }
+        "announce" | "notify" if !text_argument.is_empty() => {
+            //.announce map <text> and .announce zone <text> stay on the GM's map or zone
+            let gm = character_manager.get_character(client_manager.get_authenticated_client(client_id)?.get_active_character()?)?;
+            let (scope, message) = match text_argument.split_once(char::is_whitespace) {
+                Some((scope, rest)) if scope.eq_ignore_ascii_case("map") => (AnnouncementScope::Map(gm.map), rest.trim()),
+                Some((scope, rest)) if scope.eq_ignore_ascii_case("zone") => (AnnouncementScope::Zone(gm.area), rest.trim()),
+                _ => (AnnouncementScope::Server, text_argument),
+            };
+            if command == "announce" {
+                crate::handlers::send_server_announcement(character_manager, world, scope, message).await?;
+            } else {
+                crate::handlers::send_server_notification(character_manager, world, scope, message).await?;
+            }
+        }
+        "motd" => {
+            crate::handlers::handle_motd_command(client_manager, character_manager, world.get_realm_database(), client_id, text_argument).await?;
+        }
+        "lookup" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("player")) => {
+            if let Some(character_name) = parts.get(2) {
+                crate::handlers::handle_lookup_player_command(
                     client_manager,
                     character_manager,
-                    world.get_game_database(),
                     world.get_realm_database(),
                     client_id,
-                    item_id,
+                    character_name,
                 )
                 .await?;
             }
         }
+        "mute" => {
+            let seconds = parts.get(2).and_then(|s| s.parse::<u64>().ok());
+            if let (Some(&name), Some(seconds)) = (parts.get(1), seconds) {
+                crate::handlers::handle_mute_command(client_manager, character_manager, client_id, name, Some(seconds)).await?;
+            }
+        }
+        "unmute" => {
+            if let Some(&name) = parts.get(1) {
+                crate::handlers::handle_mute_command(client_manager, character_manager, client_id, name, None).await?;
+            }
+        }
+        "ban" => {
+            //A duration of 0 seconds bans for good, the rest of the line is the reason
+            let seconds = parts.get(2).and_then(|s| s.parse::<u64>().ok());
+            if let (Some(&name), Some(seconds)) = (parts.get(1), seconds) {
+                let duration = (seconds > 0).then(|| std::time::Duration::from_secs(seconds));
+                let reason = parts[3..].join(" ");
+                crate::handlers::handle_ban_command(
+                    client_manager,
+                    character_manager,
+                    world.get_realm_database(),
+                    client_id,
+                    name,
+                    duration,
+                    &reason,
+                )
+                .await?;
+            }
+        }
+        "groupsummon" => {
+            if let Some(&name) = parts.get(1) {
+                crate::handlers::handle_groupsummon_command(client_manager, character_manager, world, client_id, name).await?;
+            }
+        }
+        "deserter" => {
+            let add = match parts.get(1).map(|p| p.to_lowercase()).as_deref() {
+                Some("add") => Some(true),
+                Some("remove") => Some(false),
+                _ => None,
+            };
+            let punishment = parts.get(2).and_then(|name| QueuePunishment::from_name(name));
+            if let (Some(add), Some(punishment), Some(&name)) = (add, punishment, parts.get(3)) {
+                crate::handlers::handle_deserter_command(client_manager, character_manager, world, client_id, add, punishment, name).await?;
+            }
+        }
+        "unban" => {
+            if let Some(&name) = parts.get(1) {
+                crate::handlers::handle_unban_command(client_manager, character_manager, world.get_realm_database(), client_id, name).await?;
+            }
+        }
+        "modify" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("phase")) => {
+            if let Some(phase_mask) = parts.get(2).and_then(|s| s.parse::<u32>().ok()) {
+                crate::handlers::handle_modify_phase_command(client_manager, character_manager, client_id, phase_mask).await?;
+            }
+        }
+        "modify" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("currency")) => {
+            let currency = match parts.get(2).map(|p| p.to_lowercase()).as_deref() {
+                Some("honor") => Some(HONOR_POINTS),
+                Some("arena") => Some(ARENA_POINTS),
+                Some(item) => item.parse::<u32>().ok(),
+                None => None,
+            };
+            if let (Some(currency), Some(amount)) = (currency, parts.get(3).and_then(|s| s.parse::<i64>().ok())) {
+                crate::handlers::handle_modify_currency_command(client_manager, character_manager, world, client_id, currency, amount).await?;
+            }
+        }
+        "taxi" if parts.get(1).is_some_and(|p| p.eq_ignore_ascii_case("all")) => {
+            crate::handlers::handle_taxi_all_command(client_manager, character_manager, world, client_id).await?;
+        }
+        "recall" => {
+            crate::handlers::handle_recall_command(client_manager, character_manager, world, client_id).await?;
+        }
+        "server" => {
+            crate::handlers::handle_server_info_command(client_manager, character_manager, world, client_id).await?;
+        }
+        "guildnotify" => {
+            crate::handlers::handle_guildnotify_command(client_manager, character_manager, world, client_id, &parts[1..]).await?;
+        }
+        "start" => {
+            crate::handlers::handle_start_command(&data_provider, client_manager, character_manager, world, client_id).await?;
+        }
+        "gmisland" => {
+            crate::handlers::handle_gmisland_command(client_manager, character_manager, world, client_id).await?;
+        }
+        "tele" => match parts.get(1).map(|p| p.to_lowercase()).as_deref() {
+            Some("map") => {
+                let map_id = parts.get(2).and_then(|s| s.parse::<u32>().ok());
+                let coordinates: Option<Vec<f32>> = parts.get(3..).unwrap_or_default().iter().map(|s| s.parse().ok()).collect();
+                if let (Some(map_id), Some([x, y, z])) = (map_id, coordinates.as_deref()) {
+                    let position = Vector3d { x: *x, y: *y, z: *z };
+                    crate::handlers::handle_tele_map_command(client_manager, character_manager, world, client_id, map_id, position).await?;
+                }
+            }
+            Some("random") => {
+                crate::handlers::handle_tele_random_command(client_manager, character_manager, world, client_id).await?;
+            }
+            Some("tour") => {
+                let start = !parts.get(2).is_some_and(|p| p.eq_ignore_ascii_case("stop"));
+                crate::handlers::handle_tele_tour_command(client_manager, character_manager, world, client_id, start).await?;
+            }
+            _ => {}
+        },
+        "additem" => {
+            if let Some(item_id) = parts.get(1).and_then(|s| s.parse::<u32>().ok()) {
+                crate::handlers::handle_additem_command(&data_provider, client_manager, character_manager, world, client_id, item_id).await?;
+            }
+        }
         _ => {
             // Unknown command - silently ignore for now
         }
@@ -201,3 +635,135 @@ async fn handle_gm_command(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestHarness;
+    use wow_world_messages::wrath::opcodes::ClientOpcodeMessage;
+    use wow_world_messages::wrath::Language;
+    use wrath_common::GmLevel;
+
+    fn say(message: &str) -> ClientOpcodeMessage {
+        ClientOpcodeMessage::CMSG_MESSAGECHAT(CMSG_MESSAGECHAT {
+            chat_type: CMSG_MESSAGECHAT_ChatType::Say,
+            language: Language::Common,
+            message: message.to_string(),
+        })
+    }
+
+    #[test]
+    fn say_reaches_nearby_characters() {
+        smol::block_on(async {
+            let mut harness = TestHarness::new();
+            let position = Vector3d {
+                x: -8949.0,
+                y: -132.0,
+                z: 83.5,
+            };
+            let speaker = harness.add_character("Speaker", position).await;
+            let listener = harness.add_character("Listener", position).await;
+            harness.tick().await;
+            listener.take_events();
+
+            harness.handle_packet(&speaker, say("Hello there")).await.unwrap();
+
+            let heard: Vec<SMSG_MESSAGECHAT> = listener
+                .take_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    ServerEvent::MessageChat(msg) => Some(msg),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(heard.len(), 1);
+            assert_eq!(heard[0].message, "Hello there");
+            assert!(matches!(heard[0].chat_type, SMSG_MESSAGECHAT_ChatType::Say { target6 } if target6 == speaker.guid));
+        });
+    }
+
+    fn heard_messages(client: &crate::test_utils::TestClient) -> Vec<String> {
+        client
+            .take_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                ServerEvent::MessageChat(msg) => Some(msg.message),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn foreign_languages_are_refused_or_scrambled() {
+        smol::block_on(async {
+            let mut harness = TestHarness::new();
+            let position = Vector3d {
+                x: -8949.0,
+                y: -132.0,
+                z: 83.5,
+            };
+            let speaker = harness.add_character("Speaker", position).await;
+            let listener = harness.add_character("Listener", position).await;
+            harness.tick().await;
+            speaker.take_events();
+            listener.take_events();
+
+            let orcish = |message: &str| {
+                ClientOpcodeMessage::CMSG_MESSAGECHAT(CMSG_MESSAGECHAT {
+                    chat_type: CMSG_MESSAGECHAT_ChatType::Say,
+                    language: Language::Orcish,
+                    message: message.to_string(),
+                })
+            };
+            //Humans don't speak Orcish
+            harness.handle_packet(&speaker, orcish("Lok'tar")).await.unwrap();
+            assert!(heard_messages(&listener).is_empty());
+            assert_eq!(heard_messages(&speaker), [ServerString::LanguageNotKnown.default_text()]);
+
+            //GMs speak everything, but the human listener still doesn't understand it
+            harness.character_manager.get_character_mut(speaker.guid).unwrap().set_gm_mode(true);
+            harness.handle_packet(&speaker, orcish("Lok'tar ogar")).await.unwrap();
+            assert_eq!(heard_messages(&speaker), ["Lok'tar ogar"]);
+            assert_eq!(heard_messages(&listener), [language::scramble("Lok'tar ogar", Language::Orcish)]);
+        });
+    }
+
+    #[test]
+    fn speed_command_forces_run_speed() {
+        smol::block_on(async {
+            let mut harness = TestHarness::new();
+            let gm = harness.add_character("Gamemaster", Vector3d { x: 0.0, y: 0.0, z: 0.0 }).await;
+            harness.set_gm_level(&gm, GmLevel::GameMaster).await;
+            harness.tick().await;
+            gm.take_events();
+
+            harness.handle_packet(&gm, say(".speed 14")).await.unwrap();
+
+            let events = gm.take_events();
+            assert!(events
+                .iter()
+                .any(|event| matches!(event, ServerEvent::ForceRunSpeedChange(msg) if msg.speed == 14.0)));
+            //Nothing of the command leaks into chat for anyone else
+            assert!(!events
+                .iter()
+                .any(|event| matches!(event, ServerEvent::MessageChat(msg) if msg.message == ".speed 14")));
+        });
+    }
+
+    #[test]
+    fn players_cannot_use_gm_commands() {
+        smol::block_on(async {
+            let mut harness = TestHarness::new();
+            let player = harness.add_character("Player", Vector3d { x: 0.0, y: 0.0, z: 0.0 }).await;
+            harness.tick().await;
+            player.take_events();
+
+            harness.handle_packet(&player, say(".speed 14")).await.unwrap();
+
+            assert!(!player
+                .take_events()
+                .iter()
+                .any(|event| matches!(event, ServerEvent::ForceRunSpeedChange(_))));
+        });
+    }
+}
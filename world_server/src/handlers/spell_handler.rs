@@ -0,0 +1,154 @@
+use crate::character::character_casting::CastPushback;
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::client_manager::ClientManager;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::spell::cast_validation::CastFailure;
+use crate::spell::{auras, spell_cast};
+use crate::world::World;
+use std::net::SocketAddr;
+use wow_world_messages::wrath::{
+    SMSG_CAST_FAILED_SpellCastResult, CMSG_CANCEL_AURA, CMSG_CAST_SPELL, MSG_CHANNEL_UPDATE, SMSG_CAST_FAILED, SMSG_SPELL_DELAYED, SMSG_SPELL_FAILURE,
+};
+
+pub async fn handle_cmsg_cast_spell(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    packet: &CMSG_CAST_SPELL,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    spell_cast::cast_spell(
+        guid,
+        packet.spell,
+        packet.cast_count,
+        packet.targets.clone(),
+        &client_manager.data_storage,
+        character_manager,
+        world,
+    )
+    .await
+}
+
+//Right click on a buff, debuffs can't be cancelled
+pub async fn handle_cmsg_cancel_aura(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_CANCEL_AURA,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character(guid)?;
+    let cancellable = character.get_auras().iter().any(|aura| aura.spell_id == packet.id && !aura.negative);
+    if !cancellable {
+        return Ok(());
+    }
+    auras::remove_auras_from_spell(guid, packet.id, character_manager, world).await
+}
+
+//Right click on the cast bar or escape, no lockout
+pub async fn handle_cmsg_cancel_cast(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    interrupt_character_cast(character_manager, world, guid, None).await
+}
+
+//Tells the caster why the cast was refused, the client shows it as the red error text
+pub async fn send_cast_failed(character: &Character, cast_count: u8, spell_id: u32, failure: CastFailure) -> Result<()> {
+    let result = match failure {
+        CastFailure::RequiresSpellFocus(spell_focus) => SMSG_CAST_FAILED_SpellCastResult::RequiresSpellFocus { spell_focus },
+        CastFailure::CasterDead => SMSG_CAST_FAILED_SpellCastResult::CasterDead,
+        CastFailure::NotReady => SMSG_CAST_FAILED_SpellCastResult::NotReady,
+        CastFailure::ItemNotReady => SMSG_CAST_FAILED_SpellCastResult::ItemNotReady,
+        CastFailure::Moving => SMSG_CAST_FAILED_SpellCastResult::Moving,
+        CastFailure::WrongForm => SMSG_CAST_FAILED_SpellCastResult::OnlyShapeshift,
+        CastFailure::BadTargets => SMSG_CAST_FAILED_SpellCastResult::BadTargets,
+        CastFailure::TargetsDead => SMSG_CAST_FAILED_SpellCastResult::TargetsDead,
+        CastFailure::OutOfRange => SMSG_CAST_FAILED_SpellCastResult::OutOfRange,
+        CastFailure::TooClose => SMSG_CAST_FAILED_SpellCastResult::TooClose,
+        CastFailure::LineOfSight => SMSG_CAST_FAILED_SpellCastResult::LineOfSight,
+        CastFailure::NoPower => SMSG_CAST_FAILED_SpellCastResult::NoPower,
+        CastFailure::NoComboPoints => SMSG_CAST_FAILED_SpellCastResult::NoComboPoints,
+        CastFailure::Interrupted => SMSG_CAST_FAILED_SpellCastResult::Interrupted,
+        CastFailure::NotKnown => SMSG_CAST_FAILED_SpellCastResult::NotKnown,
+        CastFailure::SpellInProgress => SMSG_CAST_FAILED_SpellCastResult::SpellInProgress,
+    };
+
+    let msg = SMSG_CAST_FAILED {
+        cast_count,
+        id: spell_id,
+        result,
+        multiple_casts: false,
+    };
+    ServerEvent::CastFailed(msg).send_to_character(character).await
+}
+
+//A cast that already started and then failed, everyone around sees the cast bar get interrupted
+pub async fn send_spell_failure(
+    character: &Character,
+    character_manager: &CharacterManager,
+    world: &World,
+    cast_count: u8,
+    spell_id: u32,
+    failure: CastFailure,
+) -> Result<()> {
+    let msg = SMSG_SPELL_FAILURE {
+        guid: character.get_guid(),
+        extra_casts: cast_count,
+        spell: spell_id,
+        result: failure.as_spell_cast_result(),
+    };
+    ServerEvent::SpellFailure(msg)
+        .send_to_all_in_range(character, character_manager, true, world)
+        .await
+}
+
+//Called by the combat code for every hit the character takes, a cast in progress gets pushed back
+pub async fn handle_caster_damaged(character_manager: &mut CharacterManager, world: &World, guid: Guid) -> Result<()> {
+    let Some(pushback) = character_manager.get_character_mut(guid)?.apply_cast_pushback() else {
+        return Ok(());
+    };
+
+    let character = character_manager.get_character(guid)?;
+    let event = match pushback {
+        CastPushback::Delayed { delay_ms } => ServerEvent::SpellDelayed(SMSG_SPELL_DELAYED { guid, delay_time: delay_ms }),
+        CastPushback::ChannelShortened { remaining_ms } => ServerEvent::ChannelUpdate(MSG_CHANNEL_UPDATE {
+            caster: guid,
+            time: remaining_ms,
+        }),
+    };
+    event.send_to_all_in_range(character, character_manager, true, world).await
+}
+
+//Stops the cast right away and tells everyone around, lockout_seconds comes from interrupt effects like Kick
+pub async fn interrupt_character_cast(
+    character_manager: &mut CharacterManager,
+    world: &World,
+    guid: Guid,
+    lockout_seconds: Option<f32>,
+) -> Result<()> {
+    let Some(interrupted) = character_manager.get_character_mut(guid)?.interrupt_cast(lockout_seconds) else {
+        return Ok(());
+    };
+
+    let character = character_manager.get_character(guid)?;
+    send_spell_failure(
+        character,
+        character_manager,
+        world,
+        interrupted.cast_count,
+        interrupted.spell_id,
+        CastFailure::Interrupted,
+    )
+    .await
+}
@@ -0,0 +1,84 @@
+use std::net::SocketAddr;
+
+use wow_world_messages::wrath::{
+    CMSG_TAXINODE_STATUS_QUERY, CMSG_TAXIQUERYAVAILABLENODES, SMSG_NEW_TAXI_PATH, SMSG_SHOWTAXINODES, SMSG_TAXINODE_STATUS,
+};
+
+use crate::character::character_manager::CharacterManager;
+use crate::client_manager::ClientManager;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::world::World;
+
+//Talking to a flight master means standing next to it, and it stands on its node
+const FLIGHT_MASTER_NODE_RANGE: f32 = 30.0;
+
+//There are no creatures spawned yet to check that the guid really is a flight master, so the node
+//next to the character is used. Returns the node and whether it was discovered just now.
+async fn discover_nearby_taxi_node(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+) -> Result<Option<(u32, bool)>> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+
+    let Some(node_id) =
+        client_manager
+            .data_storage
+            .get_nearest_taxi_node(character.map.as_int(), character.movement_info.position, FLIGHT_MASTER_NODE_RANGE)
+    else {
+        return Ok(None);
+    };
+
+    let newly_discovered = character.learn_taxi_nodes(&world.get_realm_database(), &[node_id]).await? > 0;
+    if newly_discovered {
+        //Shows the "New flight path discovered!" message
+        ServerEvent::NewTaxiPath(SMSG_NEW_TAXI_PATH {}).send_to_character(character).await?;
+    }
+    Ok(Some((node_id, newly_discovered)))
+}
+
+pub async fn handle_cmsg_taxinode_status_query(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_TAXINODE_STATUS_QUERY,
+) -> Result<()> {
+    let node = discover_nearby_taxi_node(client_manager, character_manager, world, client_id).await?;
+
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    ServerEvent::TaxiNodeStatus(SMSG_TAXINODE_STATUS {
+        guid: packet.guid,
+        taxi_mask_node_known: node.is_some_and(|(node_id, _)| character.knows_taxi_node(node_id)),
+    })
+    .send_to_character(character)
+    .await
+}
+
+pub async fn handle_cmsg_taxiqueryavailablenodes(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    packet: &CMSG_TAXIQUERYAVAILABLENODES,
+) -> Result<()> {
+    let Some((nearest_node, _)) = discover_nearby_taxi_node(client_manager, character_manager, world, client_id).await? else {
+        warn!("Taxi nodes requested from {} but there is no flight path node nearby", packet.guid);
+        return Ok(());
+    };
+
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    ServerEvent::ShowTaxiNodes(SMSG_SHOWTAXINODES {
+        unknown1: 1,
+        guid: packet.guid,
+        nearest_node,
+        nodes: character.get_known_taxi_nodes_mask().to_vec(),
+    })
+    .send_to_character(character)
+    .await
+}
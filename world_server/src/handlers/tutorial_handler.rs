@@ -25,7 +25,7 @@ pub async fn handle_cmsg_tutorial_flag(
     packet: &CMSG_TUTORIAL_FLAG,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character_guid = client.get_active_character();
+    let character_guid = client.get_active_character()?;
     let character = character_manager.get_character_mut(character_guid)?;
 
     let tut_flag_index = packet.tutorial_flag as usize;
@@ -50,7 +50,7 @@ pub async fn handle_cmsg_tutorial_reset(
     client_id: SocketAddr,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let character_guid = client.get_active_character();
+    let character_guid = client.get_active_character()?;
     let character = character_manager.get_character_mut(character_guid)?;
 
     character.tutorial_flags.reset();
@@ -0,0 +1,264 @@
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use wow_world_messages::wrath::{
+    BuyResult, Gold, ListInventoryItem, SellItemResult, CMSG_BUYBACK_ITEM, CMSG_BUY_ITEM, CMSG_LIST_INVENTORY, CMSG_SELL_ITEM, SMSG_BUY_FAILED,
+    SMSG_BUY_ITEM, SMSG_LIST_INVENTORY, SMSG_SELL_ITEM,
+};
+
+use crate::audit::{log_audit_event, AuditEvent, AuditSource};
+use crate::character::character_manager::CharacterManager;
+use crate::character::character_vendor::BuybackError;
+use crate::character::Character;
+use crate::client_manager::ClientManager;
+use crate::combat::damage;
+use crate::connection::events::ServerEvent;
+use crate::handlers;
+use crate::prelude::*;
+use crate::world::creature_manager::SharedCreature;
+use crate::world::prelude::unit_flags::UnitNpcFlags;
+use crate::world::vendors::VendorError;
+use crate::world::World;
+
+//A little more than the client's interaction range, positions lag behind a bit
+const VENDOR_DISTANCE: f32 = 10.0;
+
+//The client shows no count for items that never run out
+const UNLIMITED_STOCK: u32 = u32::MAX;
+
+//Living vendor creatures close enough to trade with
+async fn find_vendor(character: &Character, vendor: Guid, world: &World) -> Option<SharedCreature> {
+    let map = world.get_instance_manager().try_get_map_for_character(character)?;
+    let creature = map.get_creature(vendor)?.clone();
+    {
+        let vendor = creature.read().await;
+        let npc_flags = vendor.gameplay_data.unit_npc_flags().unwrap_or(0);
+        if !vendor.is_alive() || npc_flags & UnitNpcFlags::Vendor as i32 == 0 {
+            return None;
+        }
+        if damage::distance(character.movement_info.position, vendor.movement_info.position) > VENDOR_DISTANCE {
+            return None;
+        }
+    }
+    Some(creature)
+}
+
+fn unix_now() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+pub async fn handle_cmsg_list_inventory(
+    client_manager: &ClientManager,
+    character_manager: &CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_LIST_INVENTORY,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character(client.get_active_character()?)?;
+    let Some(vendor) = find_vendor(character, data.guid, world).await else {
+        return Ok(());
+    };
+    let (spawn_guid, entry) = {
+        let vendor = vendor.read().await;
+        (vendor.spawn_guid, vendor.entry)
+    };
+
+    let now = unix_now()?;
+    let game_db = world.get_game_database();
+    let mut items = vec![];
+    for (index, vendor_item) in world.get_vendors().get_items(entry).iter().enumerate() {
+        let template = game_db.get_item_template(vendor_item.item).await?;
+        let max_items = world
+            .get_vendors()
+            .get_available(spawn_guid, vendor_item, now)
+            .map_or(UNLIMITED_STOCK, u32::from);
+        items.push(ListInventoryItem {
+            item_index: index as u32 + 1,
+            item: vendor_item.item,
+            item_display_id: template.displayid,
+            max_items,
+            price: Gold::new(template.buy_price),
+            max_durability: template.max_durability as u32,
+            buy_count: template.buy_count.max(1) as u32,
+            extended_cost: 0,
+        });
+    }
+
+    ServerEvent::ListInventory(SMSG_LIST_INVENTORY { vendor: data.guid, items })
+        .send_to_character(character)
+        .await
+}
+
+//amount is how many times the item's buy count the character buys, the price is for one buy count
+pub async fn handle_cmsg_buy_item(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+    client_id: SocketAddr,
+    data: &CMSG_BUY_ITEM,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let guid = client.get_active_character()?;
+    let character = character_manager.get_character_mut(guid)?;
+    let Some(vendor) = find_vendor(character, data.vendor, world).await else {
+        return send_buy_failure(character, data, BuyResult::CantFindItem).await;
+    };
+    let (spawn_guid, entry) = {
+        let vendor = vendor.read().await;
+        (vendor.spawn_guid, vendor.entry)
+    };
+
+    let template = world.get_game_database().get_item_template(data.item).await?;
+    let amount = data.amount.max(1);
+    let price = template.buy_price.saturating_mul(amount as u32);
+    if character.get_money() < price {
+        return send_buy_failure(character, data, BuyResult::NotEnoughtMoney).await;
+    }
+    //Items don't stack in the backpack yet, every one takes its own slot
+    let item_count = amount as usize * template.buy_count.max(1) as usize;
+    if character.get_free_backpack_slots().len() < item_count {
+        return send_buy_failure(character, data, BuyResult::CantCarryMore).await;
+    }
+
+    let stock = match world.get_vendors_mut().take(spawn_guid, entry, data.item, amount, unix_now()?) {
+        Ok(stock) => stock,
+        Err(VendorError::NotSold) => return send_buy_failure(character, data, BuyResult::CantFindItem).await,
+        Err(VendorError::SoldOut) => return send_buy_failure(character, data, BuyResult::ItemAlreadySold).await,
+    };
+    let amount_for_sale = match &stock {
+        Some(stock) => {
+            world.get_realm_database().set_vendor_stock(stock).await?;
+            stock.count as u32
+        }
+        None => UNLIMITED_STOCK,
+    };
+
+    character.set_money(character.get_money() - price);
+    for _ in 0..item_count {
+        character
+            .try_add_item_to_backpack(
+                data.item,
+                guid.guid() as u32,
+                &client.connection_sender,
+                Some(world.get_persistence_queue()),
+            )
+            .await;
+    }
+    ServerEvent::BuyItem(SMSG_BUY_ITEM {
+        guid: data.vendor,
+        vendor_slot: data.vendor_slot,
+        amount_for_sale,
+        amount_bought: amount as u32,
+    })
+    .send_to_character(character)
+    .await?;
+
+    let realm_db = world.get_realm_database();
+    let source = AuditSource::Vendor { creature_entry: entry };
+    log_audit_event(&realm_db, client.data.account_id, guid, AuditEvent::MoneySpent(price), source).await?;
+    let created = AuditEvent::ItemCreated {
+        item_id: data.item,
+        count: item_count as u32,
+    };
+    log_audit_event(&realm_db, client.data.account_id, guid, created, source).await
+}
+
+async fn send_buy_failure(character: &Character, data: &CMSG_BUY_ITEM, result: BuyResult) -> Result<()> {
+    ServerEvent::BuyFailed(SMSG_BUY_FAILED {
+        guid: data.vendor,
+        item: data.item,
+        result,
+    })
+    .send_to_character(character)
+    .await
+}
+
+//The whole stack is sold, the client only asks for less when it splits a stack on the vendor window.
+pub async fn handle_cmsg_sell_item(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_SELL_ITEM,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    if find_vendor(character, data.vendor, world).await.is_none() {
+        return send_sell_failure(character, data, SellItemResult::CantFindVendor).await;
+    }
+
+    let Some(item_position) = character.find_inventory_item(data.item) else {
+        return send_sell_failure(character, data, SellItemResult::CantFindItem).await;
+    };
+    let item = character.get_inventory_item(item_position).unwrap();
+    let item_id = item.update_state.object_entry().unwrap_or(0) as u32;
+    let count = item.update_state.item_stack_count().unwrap_or(1) as u32;
+
+    let template = world.get_game_database().get_item_template(item_id).await?;
+    if template.sell_price == 0 {
+        return send_sell_failure(character, data, SellItemResult::CantSellItem).await;
+    }
+    let price = template.sell_price.saturating_mul(count);
+
+    let Some(item) = character
+        .set_item(None, item_position, Some(world.get_persistence_queue()), Some(&client.connection_sender))
+        .await?
+    else {
+        return Ok(());
+    };
+    handlers::send_destroy_object(character, data.item, false).await?;
+    character.set_money(character.get_money().saturating_add(price));
+
+    let sold_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as u32;
+    if let Some(expired_guid) = character.add_to_buyback(item, price, sold_at).await {
+        handlers::send_destroy_object(character, expired_guid, false).await?;
+    }
+    Ok(())
+}
+
+async fn send_sell_failure(character: &Character, data: &CMSG_SELL_ITEM, result: SellItemResult) -> Result<()> {
+    ServerEvent::SellItem(SMSG_SELL_ITEM {
+        guid: data.vendor,
+        item: data.item,
+        result,
+    })
+    .send_to_character(character)
+    .await
+}
+
+pub async fn handle_cmsg_buyback_item(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &World,
+    client_id: SocketAddr,
+    data: &CMSG_BUYBACK_ITEM,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    if find_vendor(character, data.guid, world).await.is_none() {
+        return send_buyback_failure(character, data, BuyResult::CantFindItem).await;
+    }
+
+    match character.buy_back(data.slot.as_int() as u8, Some(world.get_persistence_queue())).await? {
+        Ok(buyback_guid) => handlers::send_destroy_object(character, buyback_guid, false).await,
+        Err(error) => {
+            let result = match error {
+                BuybackError::SlotIsEmpty => BuyResult::CantFindItem,
+                BuybackError::NotEnoughMoney => BuyResult::NotEnoughtMoney,
+                BuybackError::BackpackFull => BuyResult::CantCarryMore,
+            };
+            send_buyback_failure(character, data, result).await
+        }
+    }
+}
+
+async fn send_buyback_failure(character: &Character, data: &CMSG_BUYBACK_ITEM, result: BuyResult) -> Result<()> {
+    ServerEvent::BuyFailed(SMSG_BUY_FAILED {
+        guid: data.guid,
+        item: 0,
+        result,
+    })
+    .send_to_character(character)
+    .await
+}
@@ -5,9 +5,12 @@ use crate::character::*;
 use crate::client_manager::ClientManager;
 use crate::connection::events::ServerEvent;
 use crate::prelude::*;
+use crate::world::World;
 use wow_world_messages::wrath::Area;
+use wow_world_messages::wrath::FarSightOperation;
 use wow_world_messages::wrath::Object;
 use wow_world_messages::wrath::WorldState;
+use wow_world_messages::wrath::CMSG_FAR_SIGHT;
 use wow_world_messages::wrath::CMSG_TIME_SYNC_RESP;
 use wow_world_messages::wrath::CMSG_ZONEUPDATE;
 use wow_world_messages::wrath::SMSG_DESTROY_OBJECT;
@@ -19,13 +22,33 @@ use wow_world_messages::wrath::SMSG_UPDATE_WORLD_STATE;
 pub async fn handle_cmsg_zoneupdate(
     client_manager: &ClientManager,
     character_manager: &mut CharacterManager,
+    world: &mut World,
     client_id: SocketAddr,
     packet: &CMSG_ZONEUPDATE,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
+    let guid = client.get_active_character()?;
     let character = character_manager.get_character_mut(guid)?;
     character.zone_update(packet.area).await?;
+    if let Some(map) = world.get_instance_manager_mut().try_get_map_for_character_mut(character) {
+        map.update_character_zone(guid, packet.area);
+    }
+    Ok(())
+}
+
+//The client asks to look through its own eyes again when the player cancels the sight. Starting it is up to
+//the spell or aura that grants it, so there's nothing to do for the other operation.
+pub async fn handle_cmsg_far_sight(
+    client_manager: &ClientManager,
+    character_manager: &mut CharacterManager,
+    client_id: SocketAddr,
+    packet: &CMSG_FAR_SIGHT,
+) -> Result<()> {
+    let client = client_manager.get_authenticated_client(client_id)?;
+    let character = character_manager.get_character_mut(client.get_active_character()?)?;
+    if packet.operation == FarSightOperation::Remove {
+        character.end_far_sight();
+    }
     Ok(())
 }
 
@@ -88,7 +111,7 @@ pub async fn handle_cmsg_time_sync_resp(
     packet: &CMSG_TIME_SYNC_RESP,
 ) -> Result<()> {
     let client = client_manager.get_authenticated_client(client_id)?;
-    let guid = client.get_active_character();
+    let guid = client.get_active_character()?;
     let character = character_manager.get_character(guid)?;
 
     if packet.time_sync != character.time_sync_counter {
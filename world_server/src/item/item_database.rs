@@ -18,6 +18,7 @@ impl From<&DBItemInstance> for Item {
                 .set_item_durability(100)
                 .set_item_maxdurability(100)
                 .finalize(),
+            permanent_enchant: value.enchant.unwrap_or(0),
         }
     }
 }
@@ -1,6 +1,7 @@
 pub mod item_container;
 mod item_database;
 
+use wow_world_base::wrath::ItemClassAndSubClass;
 use wow_world_messages::wrath::UpdateItem;
 
 use crate::character::character_inventory::InventoryStorable;
@@ -8,9 +9,21 @@ use crate::character::character_inventory::InventoryStorable;
 #[derive(Default)]
 pub struct Item {
     pub update_state: UpdateItem,
+    //Shown on the character model (weapon glows), so it's needed for the visible item fields as well
+    pub permanent_enchant: u32,
 }
 
-impl Item {}
+impl Item {
+    pub fn is_key(&self) -> bool {
+        self.update_state.object_entry().is_some_and(|entry| is_key_item(entry as u32))
+    }
+}
+
+//Keys and lockpicks, the only items the keyring takes
+pub fn is_key_item(item_id: u32) -> bool {
+    wow_items::wrath::lookup_item(item_id)
+        .is_some_and(|item| matches!(item.class_and_sub_class(), ItemClassAndSubClass::Key | ItemClassAndSubClass::Lockpick))
+}
 impl std::fmt::Display for Item {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Item")
@@ -0,0 +1,145 @@
+//! Crash recovery for item moves that take several database writes. Before an item leaves its owner an
+//! intent record goes into the `item_journal` table, taking the item and delivering it each happen in
+//! one transaction together with the journal update. Whatever is left in the journal at startup was cut
+//! short, and is rolled back so the item ends up with its owner again instead of nowhere.
+//!
+//! Journaled moves write to the database directly instead of through the persistence queue, the journal
+//! has to reflect what is actually stored.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::prelude::*;
+use crate::world::prelude::inventory::BagSlot;
+use wrath_realm_db::item_journal::{ITEM_JOURNAL_STAGE_INTENT, ITEM_JOURNAL_STAGE_TAKEN};
+use wrath_realm_db::mail::DBMailCreateParameters;
+use wrath_realm_db::RealmDatabase;
+
+//Mail sends its attachments this way, trading and the auction house will once they exist
+#[derive(Clone, Copy, Debug)]
+pub enum JournaledOperation {
+    Trade,
+    Mail,
+    AuctionSale,
+}
+
+//An item on its way from one owner to another
+#[must_use]
+pub struct ItemMove {
+    journal_id: u64,
+    character_id: u32,
+    slot_id: u8,
+    item_id: u32,
+    enchant: Option<u32>,
+}
+
+impl ItemMove {
+    pub async fn begin(
+        realm_db: &RealmDatabase,
+        operation: JournaledOperation,
+        character_id: u32,
+        slot_id: u8,
+        item_id: u32,
+        enchant: Option<u32>,
+    ) -> Result<Self> {
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let journal_id = realm_db
+            .insert_item_journal_entry(operation as u8, character_id, slot_id, item_id, enchant, created_at)
+            .await?;
+        Ok(Self {
+            journal_id,
+            character_id,
+            slot_id,
+            item_id,
+            enchant,
+        })
+    }
+
+    //Takes the item away from its owner, from here on the item only exists in the journal
+    pub async fn take(&self, realm_db: &RealmDatabase) -> Result<()> {
+        realm_db.take_journaled_item(self.journal_id, self.character_id, self.slot_id).await
+    }
+
+    //Gives the taken item to another character and finishes the move
+    pub async fn deliver_to_character(self, realm_db: &RealmDatabase, character_id: u32, slot_id: u8) -> Result<()> {
+        realm_db
+            .place_journaled_item(self.journal_id, character_id, slot_id, self.item_id, self.enchant)
+            .await
+    }
+
+    //Puts the taken items into a new mail and finishes their moves in the same transaction
+    pub async fn deliver_by_mail(moves: &[ItemMove], realm_db: &RealmDatabase, params: &DBMailCreateParameters) -> Result<u32> {
+        let items: Vec<(u32, Option<u32>)> = moves.iter().map(|item_move| (item_move.item_id, item_move.enchant)).collect();
+        let journal_ids: Vec<u64> = moves.iter().map(|item_move| item_move.journal_id).collect();
+        realm_db.create_mail_from_journal(params, &items, &journal_ids).await
+    }
+
+    //Puts a taken item back into the slot it came from, for moves that failed halfway
+    pub async fn return_to_owner(self, realm_db: &RealmDatabase) -> Result<()> {
+        realm_db
+            .place_journaled_item(self.journal_id, self.character_id, self.slot_id, self.item_id, self.enchant)
+            .await
+    }
+
+    //Backs out of a move whose item was never taken, e.g. a trade that got cancelled
+    pub async fn cancel(self, realm_db: &RealmDatabase) -> Result<()> {
+        realm_db.delete_item_journal_entry(self.journal_id).await
+    }
+}
+
+//Must run before anyone logs in, the characters of interrupted moves may be changed here
+pub async fn reconcile_item_journal(realm_db: &RealmDatabase) -> Result<()> {
+    for entry in realm_db.get_item_journal_entries().await? {
+        match entry.stage {
+            ITEM_JOURNAL_STAGE_INTENT => {
+                //The item never left its owner
+                realm_db.delete_item_journal_entry(entry.id).await?;
+            }
+            ITEM_JOURNAL_STAGE_TAKEN => {
+                let occupied: Vec<u8> = realm_db
+                    .get_all_character_equipment(entry.character_id)
+                    .await?
+                    .iter()
+                    .filter(|item| item.item.is_some())
+                    .map(|item| item.slot_id)
+                    .collect();
+                let Some(slot_id) = restore_slot(entry.slot_id, &occupied) else {
+                    error!(
+                        "Item {} of character {} was lost by interrupted operation {} and there is no room to give it back, leaving it in the journal",
+                        entry.item_id, entry.character_id, entry.operation
+                    );
+                    continue;
+                };
+                realm_db
+                    .place_journaled_item(entry.id, entry.character_id, slot_id, entry.item_id, entry.enchant)
+                    .await?;
+                info!(
+                    "Gave item {} back to character {} after interrupted operation {}",
+                    entry.item_id, entry.character_id, entry.operation
+                );
+            }
+            stage => warn!("Item journal entry {} has unknown stage {}", entry.id, stage),
+        }
+    }
+    Ok(())
+}
+
+//The slot the item came from, or the first free backpack slot if that has been filled since
+fn restore_slot(original_slot: u8, occupied: &[u8]) -> Option<u8> {
+    std::iter::once(original_slot)
+        .chain((BagSlot::Item1 as u8)..=(BagSlot::Item16 as u8))
+        .find(|slot| !occupied.contains(slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interrupted_item_goes_back_to_a_free_slot() {
+        let item1 = BagSlot::Item1 as u8;
+        assert_eq!(restore_slot(item1 + 3, &[item1]), Some(item1 + 3));
+        assert_eq!(restore_slot(item1 + 3, &[item1, item1 + 3]), Some(item1 + 1));
+
+        let full_backpack: Vec<u8> = (item1..=BagSlot::Item16 as u8).collect();
+        assert_eq!(restore_slot(item1, &full_backpack), None);
+    }
+}
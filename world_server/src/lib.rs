@@ -0,0 +1,41 @@
+//! The world server as a library, so benchmarks and tools can drive the game code without the main loop.
+
+pub mod addons;
+pub mod armory;
+pub mod audit;
+pub mod auth;
+pub mod autobroadcast;
+pub mod bot_gateway;
+pub mod character;
+pub mod chat;
+pub mod client;
+pub mod client_manager;
+pub mod combat;
+pub mod connection;
+pub mod connections;
+pub mod console_input;
+pub mod constants;
+pub mod data;
+pub mod error;
+pub mod handlers;
+pub mod item;
+pub mod item_journal;
+pub mod localization;
+pub mod notifications;
+pub mod packet;
+pub mod packet_handler;
+pub mod random;
+pub mod rate_limiter;
+pub mod replay;
+pub mod signals;
+pub mod spell;
+#[cfg(test)]
+mod test_utils;
+pub mod world;
+
+pub mod prelude {
+    pub use super::handlers;
+    pub use anyhow::{anyhow, bail, Result};
+    pub use tracing::{error, info, trace, warn};
+    pub use wow_world_messages::Guid;
+}
@@ -0,0 +1,167 @@
+use std::fmt::Display;
+
+//Locales as the client reports them, see the accounts.locale column in the auth database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ClientLocale {
+    #[default]
+    EnUs,
+    EnGb,
+    KoKr,
+    FrFr,
+    DeDe,
+    ZhCn,
+    ZhTw,
+    EsEs,
+    EsMx,
+    RuRu,
+}
+
+impl ClientLocale {
+    //Unknown locales fall back to English, which is what every text exists in
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "enGB" => Self::EnGb,
+            "koKR" => Self::KoKr,
+            "frFR" => Self::FrFr,
+            "deDE" => Self::DeDe,
+            "zhCN" => Self::ZhCn,
+            "zhTW" => Self::ZhTw,
+            "esES" => Self::EsEs,
+            "esMX" => Self::EsMx,
+            "ruRU" => Self::RuRu,
+            _ => Self::EnUs,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::EnUs => "enUS",
+            Self::EnGb => "enGB",
+            Self::KoKr => "koKR",
+            Self::FrFr => "frFR",
+            Self::DeDe => "deDE",
+            Self::ZhCn => "zhCN",
+            Self::ZhTw => "zhTW",
+            Self::EsEs => "esES",
+            Self::EsMx => "esMX",
+            Self::RuRu => "ruRU",
+        }
+    }
+}
+
+//Server generated texts, the discriminant is the entry in the server_string table.
+//The English text is built in so a missing row never leaves the player without a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServerString {
+    SpeedSet = 1,
+    ItemAdded = 2,
+    GmModeState = 3,
+    GmVisibilityState = 4,
+    GmStatus = 5,
+    GodModeState = 6,
+    CurrentMotd = 7,
+    MotdUpdated = 8,
+    On = 9,
+    Off = 10,
+    ChatMuted = 11,
+    ChatSpamMuted = 12,
+    PlayerMuted = 13,
+    PlayerUnmuted = 14,
+    PlayerNotFound = 15,
+    PhaseSet = 16,
+    TaxiNodesUnlocked = 17,
+    NoRecallLocation = 18,
+    FlyState = 19,
+    AccountBanned = 20,
+    AccountSuspended = 21,
+    AccountUnbanned = 22,
+    AccountNotBanned = 23,
+    LanguageNotKnown = 24,
+    UnknownMap = 25,
+    MapTourStarted = 26,
+    ServerInfo = 27,
+    InvalidChatLink = 28,
+    NotInGroup = 29,
+    GroupSummoned = 30,
+    GuildMemberLevelUp = 31,
+    GuildMemberAchievement = 32,
+    GuildNotificationSet = 33,
+    GuildNotifyUsage = 34,
+    NotInGuild = 35,
+    QueuePunishmentAdded = 36,
+    QueuePunishmentRemoved = 37,
+    CurrencySet = 38,
+    NotACurrency = 39,
+    GatheringSkillTooLow = 40,
+}
+
+impl ServerString {
+    pub fn default_text(&self) -> &'static str {
+        match self {
+            Self::SpeedSet => "Speed set to {}",
+            Self::ItemAdded => "Added item {}",
+            Self::GmModeState => "GM mode is {}",
+            Self::GmVisibilityState => "GM visibility is {}",
+            Self::GmStatus => "GM mode is {}, GM visibility is {}",
+            Self::GodModeState => "God mode is {}",
+            Self::CurrentMotd => "Current message of the day: {}",
+            Self::MotdUpdated => "Message of the day updated",
+            Self::On => "on",
+            Self::Off => "off",
+            Self::ChatMuted => "You are muted for {} more seconds",
+            Self::ChatSpamMuted => "You are sending messages too fast and have been muted for {} seconds",
+            Self::PlayerMuted => "{} is muted for {} seconds",
+            Self::PlayerUnmuted => "{} is no longer muted",
+            Self::PlayerNotFound => "No player named {} is online",
+            Self::PhaseSet => "Phase mask set to {}",
+            Self::TaxiNodesUnlocked => "Unlocked {} flight paths",
+            Self::NoRecallLocation => "There is no location to return to",
+            Self::FlyState => "Fly mode is {}",
+            Self::AccountBanned => "The account of {} is banned",
+            Self::AccountSuspended => "The account of {} is suspended for {} seconds",
+            Self::AccountUnbanned => "The account of {} is no longer banned",
+            Self::AccountNotBanned => "The account of {} is not banned",
+            Self::LanguageNotKnown => "You don't know that language",
+            Self::UnknownMap => "Map {} is not in Map.dbc",
+            Self::MapTourStarted => "Touring {} maps",
+            Self::ServerInfo => "{}, up for {}. Players online: {}, at most {} since the start and {} ever",
+            Self::InvalidChatLink => "Your message contains a broken link and was not sent",
+            Self::NotInGroup => "{} is not in a group",
+            Self::GroupSummoned => "Summoned {} members of {}'s group",
+            Self::GuildMemberLevelUp => "Guild member {} has reached level {}",
+            Self::GuildMemberAchievement => "Guild member {} has earned the achievement {}",
+            Self::GuildNotificationSet => "Guild notifications for {} are {}",
+            Self::GuildNotifyUsage => "Usage: .guildnotify <logins|levels|achievements> <on|off>",
+            Self::NotInGuild => "You are not in a guild",
+            Self::QueuePunishmentAdded => "{} now has debuff {}",
+            Self::QueuePunishmentRemoved => "Removed debuff {} from {}",
+            Self::CurrencySet => "Currency {} set to {}",
+            Self::NotACurrency => "Item {} is not a currency",
+            Self::GatheringSkillTooLow => "Requires skill {} to gather this",
+        }
+    }
+
+    pub fn on_off(enabled: bool) -> Self {
+        if enabled {
+            Self::On
+        } else {
+            Self::Off
+        }
+    }
+}
+
+//Fills the {} placeholders of a server string in order
+pub fn format_server_string(template: &str, args: &[&dyn Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}").peekable();
+    while let Some(part) = parts.next() {
+        result.push_str(part);
+        if parts.peek().is_some() {
+            if let Some(arg) = args.next() {
+                result.push_str(&arg.to_string());
+            }
+        }
+    }
+    result
+}
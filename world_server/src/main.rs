@@ -3,8 +3,6 @@ use std::{
     time::Duration,
 };
 
-use async_ctrlc::CtrlC;
-use client_manager::ClientManager;
 use futures::future::{select, Either};
 use futures::pin_mut;
 use futures_timer::Delay;
@@ -13,33 +11,18 @@ use smol_macros::main;
 use time::macros::format_description;
 use tracing_subscriber::{fmt::time::UtcTime, EnvFilter};
 use wrath_auth_db::AuthDatabase;
+use wrath_common::{config, health, Health};
 use wrath_game_db::GameDatabase;
 use wrath_realm_db::RealmDatabase;
 
-mod auth;
-mod character;
-mod client;
-mod client_manager;
-mod connection;
-mod connections;
-mod console_input;
-mod constants;
-mod data;
-pub mod handlers;
-mod item;
-mod packet;
-mod packet_handler;
-mod world;
-
-pub mod prelude {
-    pub use super::handlers;
-    pub use anyhow::{anyhow, bail, Result};
-    pub use tracing::{error, info, trace, warn};
-    pub use wow_world_messages::Guid;
-}
-use prelude::*;
+use wrath_worldserver::character::character_manager::CharacterManager;
+use wrath_worldserver::client_manager::ClientManager;
+use wrath_worldserver::prelude::*;
+use wrath_worldserver::{
+    armory, auth, autobroadcast, bot_gateway, connections, console_input, data, item_journal, notifications, random, replay, signals, world,
+};
 
-use crate::character::character_manager::CharacterManager;
+const DEFAULT_NETWORK_TICK_RATE: f32 = 50.0;
 
 #[apply(main!)]
 async fn main() -> Result<()> {
@@ -54,46 +37,131 @@ async fn main() -> Result<()> {
 
     info!("Starting World Server");
     let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
-    let ctrlc = CtrlC::new().expect("Failed to register ctrl+c abort handler");
-    smol::spawn(async move {
-        ctrlc.await;
-        info!("Detected Ctrl+C, starting graceful shutdown");
-        r.store(false, std::sync::atomic::Ordering::Relaxed);
-    })
-    .detach();
 
-    let db_connect_timeout = Duration::from_secs(std::env::var("DB_CONNECT_TIMEOUT_SECONDS")?.parse()?);
-    let auth_database = AuthDatabase::new(&std::env::var("AUTH_DATABASE_URL")?, db_connect_timeout).await?;
+    let health = Health::from_env(&["databases", "data", "listener"]);
+    if let Some(bind) = health::bind_address_from_env() {
+        let health = health.clone();
+        smol::spawn(async move {
+            if let Err(e) = health::serve_probes(bind, health).await {
+                error!("Health probe endpoint stopped: {}", e);
+            }
+        })
+        .detach();
+    }
+
+    let db_connect_timeout = Duration::from_secs(config::required("DB_CONNECT_TIMEOUT_SECONDS")?);
+    let auth_database = AuthDatabase::new(&config::required::<String>("AUTH_DATABASE_URL")?, db_connect_timeout).await?;
     let auth_database_ref = std::sync::Arc::new(auth_database);
 
-    let game_database = GameDatabase::new(&std::env::var("GAME_DATABASE_URL")?, db_connect_timeout).await?;
+    let game_database = GameDatabase::new(&config::required::<String>("GAME_DATABASE_URL")?, db_connect_timeout).await?;
     let game_database_ref = std::sync::Arc::new(game_database);
 
-    let realm_database = RealmDatabase::new(&std::env::var("REALM_DATABASE_URL")?, db_connect_timeout).await?;
+    let realm_database = RealmDatabase::new(&config::required::<String>("REALM_DATABASE_URL")?, db_connect_timeout).await?;
+    item_journal::reconcile_item_journal(&realm_database).await?;
     let realm_database_ref = std::sync::Arc::new(realm_database);
+    health.pass_check("databases");
+
+    if let Some(bind) = armory::bind_address_from_env() {
+        let realm_database = realm_database_ref.clone();
+        smol::spawn(async move {
+            if let Err(e) = armory::serve_armory(bind, realm_database).await {
+                error!("Armory endpoint stopped: {}", e);
+            }
+        })
+        .detach();
+    }
 
-    let mut data_storage = data::DataStorage::default();
-    data_storage.load(game_database_ref.clone()).await?;
-    let data_storage = std::sync::Arc::new(data_storage);
+    let data_storage = std::sync::Arc::new(data::DataStorage::load_validated(game_database_ref.clone()).await?);
 
     smol::spawn(auth::auth_server_heartbeats()).detach();
 
     let mut world = world::World::new(game_database_ref, realm_database_ref);
+    world.load().await?;
+    world.get_instance_manager_mut().set_instance_maps(data_storage.get_instance_map_ids());
+    health.pass_check("data");
+    world.get_notifier().notify(notifications::Notification::ServerStarted);
     let mut character_manager = CharacterManager::new();
 
     let mut client_manager = ClientManager::new(auth_database_ref.clone(), data_storage);
+    if let Some(journal_path) = replay_path_from_args() {
+        return replay::replay_journal(&journal_path, &mut client_manager, &mut character_manager, &mut world).await;
+    }
+    if let Some(journal) = replay::EventJournal::from_env()? {
+        client_manager.set_journal(journal);
+    }
     let client_manager_sender = client_manager.get_sender();
 
-    smol::spawn(connections::accept_realm_connections(auth_database_ref.clone(), client_manager_sender)).detach();
+    smol::spawn(connections::accept_realm_connections(
+        auth_database_ref.clone(),
+        client_manager_sender.clone(),
+        health.clone(),
+    ))
+    .detach();
+    if let Some(bot_gateway_config) = bot_gateway::BotGatewayConfig::from_env() {
+        smol::spawn(bot_gateway::accept_bot_connections(
+            bot_gateway_config,
+            auth_database_ref.clone(),
+            client_manager_sender,
+        ))
+        .detach();
+    }
+
+    let mut auto_broadcaster = autobroadcast::AutoBroadcaster::from_env();
+    let (data_storage_sender, data_storage_receiver) = flume::unbounded();
+    let (config_reload_sender, config_reload_receiver) = flume::unbounded();
 
-    smol::spawn(console_input::process_console_commands(running.clone())).detach();
+    smol::spawn(signals::handle_signals(
+        running.clone(),
+        world.get_game_database(),
+        data_storage_sender.clone(),
+        config_reload_sender,
+    ))
+    .detach();
+
+    smol::spawn(console_input::process_console_commands(
+        running.clone(),
+        world.get_realm_database(),
+        world.get_game_database(),
+        auto_broadcaster.get_enabled_flag(),
+        data_storage_sender.clone(),
+    ))
+    .detach();
+    if let Some(hot_reload_config) = data::HotReloadConfig::from_env() {
+        smol::spawn(data::watch_game_database(
+            hot_reload_config,
+            world.get_game_database(),
+            data_storage_sender,
+        ))
+        .detach();
+    }
 
-    let desired_timestep_sec: f32 = 1.0 / 10.0;
+    //The main loop handles packets at this rate, maps simulate at their own, slower rate (see SimulationRates)
+    let desired_timestep_sec: f32 = 1.0 / config::or_default("NETWORK_TICK_RATE", DEFAULT_NETWORK_TICK_RATE).max(1.0);
     let mut previous_loop_total: f32 = desired_timestep_sec;
 
     while running.load(std::sync::atomic::Ordering::Relaxed) {
         let before = std::time::Instant::now();
+        health.heartbeat();
+        if let Ok(reload) = data_storage_receiver.try_recv() {
+            client_manager.data_storage = Arc::new(match reload {
+                data::DataStorageReload::Full(data_storage) => data_storage,
+                data::DataStorageReload::Store(store) => {
+                    let mut data_storage = data::DataStorage::clone(&client_manager.data_storage);
+                    data_storage.replace_store(store);
+                    data_storage
+                }
+            });
+            info!("Data storage reloaded");
+        }
+        if config_reload_receiver.try_recv().is_ok() {
+            world.reload_config();
+            info!("Configuration reloaded");
+        }
+        let seed = rand::random();
+        random::reseed(seed);
+        client_manager.record_tick(previous_loop_total, seed).unwrap_or_else(|e| {
+            error!("Failed to record tick: {}", e);
+        });
         client_manager
             .tick(previous_loop_total, &mut character_manager, &mut world)
             .await
@@ -119,8 +187,14 @@ async fn main() -> Result<()> {
         }
         #[cfg(not(debug_assertions))]
         {
-            world.tick(previous_loop_total).await?;
+            world.tick(&mut character_manager, previous_loop_total).await?;
         }
+        auto_broadcaster
+            .tick(previous_loop_total, &character_manager, &world.get_realm_database())
+            .await
+            .unwrap_or_else(|e| {
+                error!("Error while sending autobroadcast: {}", e);
+            });
         let after = std::time::Instant::now();
         let update_duration = after.duration_since(before);
         if update_duration.as_secs_f32() < desired_timestep_sec {
@@ -131,6 +205,20 @@ async fn main() -> Result<()> {
         previous_loop_total = std::time::Instant::now().duration_since(before).as_secs_f32();
     }
 
+    world
+        .get_persistence_queue()
+        .shutdown()
+        .await
+        .unwrap_or_else(|e| error!("Failed to write pending database changes: {}", e));
+
+    world.get_notifier().notify_and_wait(notifications::Notification::ServerStopped).await;
     info!("World server shut down");
     Ok(())
 }
+
+//`--replay <file>` runs a recorded event journal instead of accepting connections
+fn replay_path_from_args() -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--replay");
+    args.next()?;
+    args.next()
+}
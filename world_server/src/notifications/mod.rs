@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::prelude::*;
+
+pub mod webhook;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotificationKind {
+    Server,
+    GmTicket,
+    WorldBoss,
+    PlayerReport,
+}
+
+impl NotificationKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "server" => Some(Self::Server),
+            "ticket" => Some(Self::GmTicket),
+            "world_boss" => Some(Self::WorldBoss),
+            "report" => Some(Self::PlayerReport),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Notification {
+    ServerStarted,
+    ServerStopped,
+    GmTicketCreated {
+        character_name: String,
+        message: String,
+    },
+    WorldBossKilled {
+        creature_entry: u32,
+        map: u32,
+    },
+    PlayerReported {
+        reporter_name: String,
+        offender_name: String,
+        reason: String,
+    },
+}
+
+impl Notification {
+    pub fn kind(&self) -> NotificationKind {
+        match self {
+            Self::ServerStarted | Self::ServerStopped => NotificationKind::Server,
+            Self::GmTicketCreated { .. } => NotificationKind::GmTicket,
+            Self::WorldBossKilled { .. } => NotificationKind::WorldBoss,
+            Self::PlayerReported { .. } => NotificationKind::PlayerReport,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Self::ServerStarted => "World server started".to_string(),
+            Self::ServerStopped => "World server stopped".to_string(),
+            Self::GmTicketCreated { character_name, .. } => format!("New GM ticket from {}", character_name),
+            Self::WorldBossKilled { .. } => "World boss killed".to_string(),
+            Self::PlayerReported { offender_name, .. } => format!("{} was reported", offender_name),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Self::ServerStarted | Self::ServerStopped => String::new(),
+            Self::GmTicketCreated { message, .. } => message.clone(),
+            Self::WorldBossKilled { creature_entry, map } => format!("Creature {} was killed on map {}", creature_entry, map),
+            Self::PlayerReported { reporter_name, reason, .. } => format!("Reported by {}: {}", reporter_name, reason),
+        }
+    }
+}
+
+//Somewhere notifications can be delivered to. Webhooks are the only backend for now, others
+//(email, IRC, a GM web panel) implement this and get added in Notifier::from_env.
+#[async_trait]
+pub trait NotificationSink: Send + Sync {
+    fn accepts(&self, kind: NotificationKind) -> bool;
+    async fn send(&self, notification: &Notification) -> Result<()>;
+}
+
+//Posts server events to the configured sinks on a background task, so the game loop never waits on the network
+pub struct Notifier {
+    sinks: Arc<Vec<Box<dyn NotificationSink>>>,
+    sender: Option<flume::Sender<Notification>>,
+}
+
+impl Notifier {
+    pub fn from_env() -> Self {
+        let kinds: HashSet<NotificationKind> = std::env::var("NOTIFICATION_EVENTS")
+            .unwrap_or_else(|_| "server,ticket,world_boss,report".to_string())
+            .split(',')
+            .filter_map(NotificationKind::from_name)
+            .collect();
+
+        let sinks: Vec<Box<dyn NotificationSink>> = std::env::var("NOTIFICATION_WEBHOOK_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| Box::new(webhook::WebhookSink::new(url.to_string(), kinds.clone())) as Box<dyn NotificationSink>)
+            .collect();
+        Self::with_sinks(sinks)
+    }
+
+    pub fn with_sinks(sinks: Vec<Box<dyn NotificationSink>>) -> Self {
+        let sinks = Arc::new(sinks);
+        let sender = (!sinks.is_empty()).then(|| {
+            let (sender, receiver) = flume::unbounded();
+            smol::spawn(run_notification_sender(receiver, sinks.clone())).detach();
+            sender
+        });
+        Self { sinks, sender }
+    }
+
+    pub fn notify(&self, notification: Notification) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        if sender.send(notification).is_err() {
+            error!("Notification sender is not running, notification was dropped");
+        }
+    }
+
+    //For the shutdown notification, the background task doesn't get to run once the server stops
+    pub async fn notify_and_wait(&self, notification: Notification) {
+        send_to_sinks(&self.sinks, &notification).await;
+    }
+}
+
+async fn run_notification_sender(receiver: flume::Receiver<Notification>, sinks: Arc<Vec<Box<dyn NotificationSink>>>) {
+    while let Ok(notification) = receiver.recv_async().await {
+        send_to_sinks(&sinks, &notification).await;
+    }
+}
+
+async fn send_to_sinks(sinks: &[Box<dyn NotificationSink>], notification: &Notification) {
+    for sink in sinks.iter().filter(|sink| sink.accepts(notification.kind())) {
+        if let Err(e) = sink.send(notification).await {
+            warn!("Failed to send {:?} notification: {}", notification.kind(), e);
+        }
+    }
+}
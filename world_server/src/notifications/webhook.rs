@@ -0,0 +1,58 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::{Notification, NotificationKind, NotificationSink};
+use crate::prelude::*;
+
+const WEBHOOK_USERNAME: &str = "wrath-rs";
+
+//Discord shows the embed with a colored bar on the left, the rest of the services that accept
+//Discord style webhooks (Slack compatible endpoints, Mattermost, Guilded) ignore it
+fn embed_color(kind: NotificationKind) -> u32 {
+    match kind {
+        NotificationKind::Server => 0x3498db,
+        NotificationKind::GmTicket => 0xf1c40f,
+        NotificationKind::WorldBoss => 0x9b59b6,
+        NotificationKind::PlayerReport => 0xe74c3c,
+    }
+}
+
+pub struct WebhookSink {
+    url: String,
+    kinds: HashSet<NotificationKind>,
+}
+
+impl WebhookSink {
+    pub fn new(url: String, kinds: HashSet<NotificationKind>) -> Self {
+        Self { url, kinds }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for WebhookSink {
+    fn accepts(&self, kind: NotificationKind) -> bool {
+        self.kinds.contains(&kind)
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let body = json!({
+            "username": WEBHOOK_USERNAME,
+            "embeds": [{
+                "title": notification.title(),
+                "description": notification.description(),
+                "color": embed_color(notification.kind()),
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            }],
+        })
+        .to_string();
+
+        //ureq blocks, so the request runs on smol's blocking thread pool
+        let url = self.url.clone();
+        smol::unblock(move || ureq::post(&url).header("Content-Type", "application/json").send(body))
+            .await
+            .map_err(|e| anyhow!("Webhook request failed: {}", e))?;
+        Ok(())
+    }
+}
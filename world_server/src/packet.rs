@@ -37,10 +37,12 @@ impl ServerEvent {
         world: &World,
     ) -> Result<()> {
         if world.get_instance_manager().try_get_map_for_character(character).is_some() {
-            let in_range_guids = character.get_in_range_guids();
-            for guid in in_range_guids {
+            //Creatures are in range as well, but only characters have a connection to send to
+            for &guid in character.get_in_range_characters() {
                 let in_range_character = character_manager.get_character(guid)?;
-                self.send_to_character(in_range_character).await?;
+                if in_range_character.is_in_range(character.get_guid()) {
+                    self.send_to_character(in_range_character).await?;
+                }
             }
             if include_self {
                 self.send_to_character(character).await?;
@@ -51,6 +53,13 @@ impl ServerEvent {
         Ok(())
     }
 
+    pub async fn send_to_all_characters(&self, character_manager: &CharacterManager) -> Result<()> {
+        for character in character_manager.get_all_characters() {
+            self.send_to_character(character).await?;
+        }
+        Ok(())
+    }
+
     pub async fn send_to_character(&self, character: &Character) -> Result<()> {
         character.connection_sender.send_async(self.clone()).await?;
         Ok(())
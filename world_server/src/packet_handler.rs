@@ -1,8 +1,11 @@
 use super::client_manager::ClientManager;
 use crate::character::character_manager::CharacterManager;
 use crate::client::ClientState;
+use crate::data::GameDataProvider;
+use crate::error::{HandlerError, ProtocolError};
 use crate::handlers::*;
 use crate::prelude::*;
+use crate::rate_limiter::{OpcodeClass, RateLimitVerdict};
 use crate::world::World;
 use std::net::SocketAddr;
 use wow_world_messages::wrath::opcodes::ClientOpcodeMessage;
@@ -20,8 +23,8 @@ impl PacketHandler {
         character_manager: &mut CharacterManager,
         world: &mut World,
         packet: PacketToHandle,
-    ) -> Result<()> {
-        if std::env::var("PRINT_INCOMING_PACKETS")?.parse::<usize>()? == 1usize {
+    ) -> std::result::Result<(), HandlerError> {
+        if std::env::var("PRINT_INCOMING_PACKETS").ok().and_then(|v| v.parse::<usize>().ok()) == Some(1) {
             info!("Incoming: {:?}", packet.payload);
         }
         {
@@ -29,11 +32,22 @@ impl PacketHandler {
             //Most likely this won't even be reached since the client manager can't find that
             //client
             if client.data.client_state == ClientState::Disconnected {
-                bail!("PacketHandler received a packet for a client that's already disconnected. Ignoring");
+                return Err(anyhow!("PacketHandler received a packet for a client that's already disconnected. Ignoring").into());
             }
         }
 
-        match &*packet.payload {
+        if let Some(class) = OpcodeClass::of(&packet.payload) {
+            match client_manager.rate_limiter.check(packet.client_id, class, std::time::Instant::now()) {
+                RateLimitVerdict::Allow => {}
+                RateLimitVerdict::Drop => {
+                    trace!("Dropped {} packet from {} over its rate limit", class, packet.client_id);
+                    return Ok(());
+                }
+                RateLimitVerdict::Disconnect => return Err(ProtocolError::Flooding(class).into()),
+            }
+        }
+
+        let result = match &*packet.payload {
             ClientOpcodeMessage::CMSG_PLAYER_LOGOUT => handle_cmsg_player_logout(client_manager, character_manager, packet.client_id).await,
             ClientOpcodeMessage::CMSG_READY_FOR_ACCOUNT_DATA_TIMES => handle_cmsg_ready_for_account_data_times(client_manager, &packet).await,
             ClientOpcodeMessage::CMSG_UPDATE_ACCOUNT_DATA(data) => {
@@ -50,6 +64,17 @@ impl PacketHandler {
             ClientOpcodeMessage::CMSG_PLAYER_LOGIN(data) => {
                 handle_cmsg_player_login(client_manager, character_manager, world, packet.client_id, data).await
             }
+            ClientOpcodeMessage::CMSG_ATTACKSWING(data) => {
+                handle_cmsg_attackswing(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_ATTACKSTOP => handle_cmsg_attackstop(client_manager, character_manager, world, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_CAST_SPELL(data) => {
+                handle_cmsg_cast_spell(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_CANCEL_CAST(_) => handle_cmsg_cancel_cast(client_manager, character_manager, world, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_CANCEL_AURA(data) => {
+                handle_cmsg_cancel_aura(client_manager, character_manager, world, packet.client_id, data).await
+            }
             ClientOpcodeMessage::CMSG_STANDSTATECHANGE(data) => {
                 handle_cmsg_standstate_change(client_manager, character_manager, packet.client_id, data).await
             }
@@ -130,12 +155,42 @@ impl PacketHandler {
             ClientOpcodeMessage::CMSG_TIME_SYNC_RESP(data) => {
                 handle_cmsg_time_sync_resp(client_manager, character_manager, packet.client_id, data).await
             }
-            ClientOpcodeMessage::CMSG_ZONEUPDATE(data) => handle_cmsg_zoneupdate(client_manager, character_manager, packet.client_id, data).await,
-            ClientOpcodeMessage::CMSG_AREATRIGGER(data) => handle_cmsg_areatrigger(client_manager, character_manager, packet.client_id, data).await,
-            ClientOpcodeMessage::CMSG_FORCE_MOVE_ROOT_ACK(_) => Ok(()),
-            ClientOpcodeMessage::CMSG_FORCE_MOVE_UNROOT_ACK(_) => Ok(()),
-            ClientOpcodeMessage::CMSG_FORCE_RUN_SPEED_CHANGE_ACK(_) => Ok(()),
-            ClientOpcodeMessage::CMSG_FORCE_RUN_BACK_SPEED_CHANGE_ACK(_) => Ok(()),
+            ClientOpcodeMessage::CMSG_ZONEUPDATE(data) => {
+                handle_cmsg_zoneupdate(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_FAR_SIGHT(data) => handle_cmsg_far_sight(client_manager, character_manager, packet.client_id, data).await,
+            ClientOpcodeMessage::CMSG_AREATRIGGER(data) => {
+                let (data_storage, game_db) = (client_manager.data_storage.clone(), world.get_game_database());
+                let data_provider = GameDataProvider::new(&data_storage, &game_db);
+                handle_cmsg_areatrigger(&data_provider, client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_MOVE_KNOCK_BACK_ACK(data) => {
+                handle_cmsg_move_knock_back_ack(client_manager, character_manager, packet.client_id, world, data).await
+            }
+            ClientOpcodeMessage::CMSG_MOVE_SET_CAN_FLY_ACK(data) => {
+                handle_movement_change_ack(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_MOVE_WATER_WALK_ACK(data) => {
+                handle_movement_change_ack(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_MOVE_FEATHER_FALL_ACK(data) => {
+                handle_movement_change_ack(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_MOVE_HOVER_ACK(data) => {
+                handle_movement_change_ack(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_FORCE_MOVE_ROOT_ACK(data) => {
+                handle_movement_change_ack(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_FORCE_MOVE_UNROOT_ACK(data) => {
+                handle_movement_change_ack(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_FORCE_RUN_SPEED_CHANGE_ACK(data) => {
+                handle_movement_change_ack(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_FORCE_RUN_BACK_SPEED_CHANGE_ACK(data) => {
+                handle_movement_change_ack(client_manager, character_manager, packet.client_id, data).await
+            }
             ClientOpcodeMessage::CMSG_SET_ACTIVE_MOVER(data) => handle_cmsg_set_active_mover(client_manager, packet.client_id, data).await,
             ClientOpcodeMessage::CMSG_NAME_QUERY(data) => {
                 handle_cmsg_name_query(client_manager, character_manager, packet.client_id, world, data).await
@@ -147,7 +202,21 @@ impl PacketHandler {
             ClientOpcodeMessage::CMSG_SET_SELECTION(data) => {
                 handle_cmsg_set_selection(client_manager, character_manager, packet.client_id, data).await
             }
-            ClientOpcodeMessage::CMSG_JOIN_CHANNEL(data) => handle_cmsg_join_channel(client_manager, packet.client_id, data).await,
+            ClientOpcodeMessage::CMSG_JOIN_CHANNEL(data) => {
+                handle_cmsg_join_channel(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_LEAVE_CHANNEL(data) => {
+                handle_cmsg_leave_channel(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_CHANNEL_PASSWORD(data) => {
+                handle_cmsg_channel_password(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_CHANNEL_MODERATOR(data) => {
+                handle_cmsg_channel_moderator(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_CHANNEL_UNMODERATOR(data) => {
+                handle_cmsg_channel_unmoderator(client_manager, character_manager, world, packet.client_id, data).await
+            }
             ClientOpcodeMessage::CMSG_SET_ACTIVE_VOICE_CHANNEL(_) => {
                 //Voice chat is explicitly not implemented, discard message to silence warning spam
                 Ok(())
@@ -157,23 +226,147 @@ impl PacketHandler {
                 Ok(())
             }
             ClientOpcodeMessage::CMSG_GMTICKET_GETTICKET => handle_cmsg_gmticket_getticket(client_manager, packet.client_id).await,
-            ClientOpcodeMessage::CMSG_GMTICKET_CREATE(data) => handle_cmsg_gmticket_create(client_manager, packet.client_id, data).await,
+            ClientOpcodeMessage::CMSG_GMTICKET_CREATE(data) => {
+                handle_cmsg_gmticket_create(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_COMPLAIN(data) => handle_cmsg_complain(client_manager, character_manager, world, packet.client_id, data).await,
             ClientOpcodeMessage::CMSG_GMTICKET_SYSTEMSTATUS => handle_cmsg_gmticket_system_status(client_manager, packet.client_id).await,
             ClientOpcodeMessage::CMSG_NEXT_CINEMATIC_CAMERA => {
                 handle_cmsg_next_cinematic_camera(client_manager, character_manager, packet.client_id).await
             }
             ClientOpcodeMessage::CMSG_COMPLETE_CINEMATIC => handle_cmsg_complete_cinematic(client_manager, character_manager, packet.client_id).await,
             ClientOpcodeMessage::CMSG_REQUEST_RAID_INFO => handle_cmsg_request_raid_info(client_manager, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_SUMMON_RESPONSE(data) => {
+                handle_cmsg_summon_response(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_LOOT(data) => handle_cmsg_loot(client_manager, character_manager, world, packet.client_id, data).await,
+            ClientOpcodeMessage::CMSG_AUTOSTORE_LOOT_ITEM(data) => {
+                handle_cmsg_autostore_loot_item(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_LOOT_MONEY => handle_cmsg_loot_money(client_manager, character_manager, world, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_LOOT_RELEASE(data) => handle_cmsg_loot_release(client_manager, character_manager, packet.client_id, data).await,
+            ClientOpcodeMessage::CMSG_LOOT_METHOD(data) => {
+                handle_cmsg_loot_method(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_LOOT_ROLL(data) => {
+                handle_cmsg_loot_roll(client_manager, character_manager, world, packet.client_id, data).await
+            }
             ClientOpcodeMessage::CMSG_CONTACT_LIST(data) => handle_cmsg_contact_list(client_manager, character_manager, packet.client_id, data).await,
-            ClientOpcodeMessage::CMSG_CALENDAR_GET_NUM_PENDING => handle_cmsg_calendar_get_num_pending(client_manager, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_ADD_FRIEND(data) => {
+                handle_cmsg_add_friend(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_DEL_FRIEND(data) => {
+                handle_cmsg_del_friend(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_ADD_IGNORE(data) => {
+                handle_cmsg_add_ignore(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_DEL_IGNORE(data) => {
+                handle_cmsg_del_ignore(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_CALENDAR_GET_NUM_PENDING => {
+                handle_cmsg_calendar_get_num_pending(client_manager, character_manager, world, packet.client_id).await
+            }
+            ClientOpcodeMessage::CMSG_CALENDAR_GET_CALENDAR => {
+                handle_cmsg_calendar_get_calendar(client_manager, character_manager, world, packet.client_id).await
+            }
+            ClientOpcodeMessage::CMSG_CALENDAR_GET_EVENT(data) => {
+                handle_cmsg_calendar_get_event(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_CALENDAR_ADD_EVENT(data) => {
+                handle_cmsg_calendar_add_event(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_CALENDAR_EVENT_INVITE(data) => {
+                handle_cmsg_calendar_event_invite(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_CALENDAR_EVENT_RSVP(data) => {
+                handle_cmsg_calendar_event_rsvp(client_manager, character_manager, world, packet.client_id, data).await
+            }
             ClientOpcodeMessage::CMSG_SET_ACTIONBAR_TOGGLES(data) => {
                 handle_cmsg_set_actionbar_toggles(client_manager, character_manager, packet.client_id, data).await
             }
             ClientOpcodeMessage::CMSG_ITEM_QUERY_SINGLE(data) => handle_cmsg_item_query_single(client_manager, packet.client_id, world, data).await,
+            ClientOpcodeMessage::CMSG_CREATURE_QUERY(data) => handle_cmsg_creature_query(client_manager, packet.client_id, data).await,
+            ClientOpcodeMessage::CMSG_GAMEOBJECT_QUERY(data) => handle_cmsg_gameobject_query(client_manager, packet.client_id, data).await,
             ClientOpcodeMessage::CMSG_ITEM_NAME_QUERY(data) => handle_cmsg_item_name_query(client_manager, packet.client_id, world, data).await,
             ClientOpcodeMessage::CMSG_SWAP_INV_ITEM(data) => {
                 handle_cmsg_swap_inv_item(client_manager, character_manager, world, packet.client_id, data).await
             }
+            ClientOpcodeMessage::CMSG_SWAP_ITEM(data) => {
+                handle_cmsg_swap_item(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_GOSSIP_HELLO(data) => {
+                handle_cmsg_gossip_hello(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_GOSSIP_SELECT_OPTION(data) => {
+                handle_cmsg_gossip_select_option(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_LIST_INVENTORY(data) => {
+                handle_cmsg_list_inventory(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_BUY_ITEM(data) => handle_cmsg_buy_item(client_manager, character_manager, world, packet.client_id, data).await,
+            ClientOpcodeMessage::CMSG_SELL_ITEM(data) => {
+                handle_cmsg_sell_item(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_BUYBACK_ITEM(data) => {
+                handle_cmsg_buyback_item(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_GET_MAIL_LIST(data) => {
+                handle_cmsg_get_mail_list(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_SEND_MAIL(data) => {
+                handle_cmsg_send_mail(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_MAIL_TAKE_MONEY(data) => {
+                handle_cmsg_mail_take_money(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_MAIL_TAKE_ITEM(data) => {
+                handle_cmsg_mail_take_item(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_MAIL_MARK_AS_READ(data) => {
+                handle_cmsg_mail_mark_as_read(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_MAIL_DELETE(data) => {
+                handle_cmsg_mail_delete(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_MAIL_RETURN_TO_SENDER(data) => {
+                handle_cmsg_mail_return_to_sender(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_GROUP_INVITE(data) => {
+                handle_cmsg_group_invite(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_GROUP_ACCEPT(_) => handle_cmsg_group_accept(client_manager, character_manager, world, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_GROUP_DECLINE => handle_cmsg_group_decline(client_manager, character_manager, world, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_GROUP_DISBAND => handle_cmsg_group_disband(client_manager, character_manager, world, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_GUILD_CREATE(data) => {
+                handle_cmsg_guild_create(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_GUILD_INVITE(data) => {
+                handle_cmsg_guild_invite(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_GUILD_ACCEPT => handle_cmsg_guild_accept(client_manager, character_manager, world, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_GUILD_DECLINE => handle_cmsg_guild_decline(client_manager, character_manager, world, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_GUILD_LEAVE => handle_cmsg_guild_leave(client_manager, character_manager, world, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_GUILD_ROSTER => handle_cmsg_guild_roster(client_manager, character_manager, world, packet.client_id).await,
+            ClientOpcodeMessage::CMSG_GUILD_MOTD(data) => {
+                handle_cmsg_guild_motd(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_GUILD_QUERY(data) => {
+                handle_cmsg_guild_query(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_USE_ITEM(data) => handle_cmsg_use_item(client_manager, character_manager, world, packet.client_id, data).await,
+            ClientOpcodeMessage::CMSG_QUESTGIVER_ACCEPT_QUEST(data) => {
+                handle_cmsg_questgiver_accept_quest(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_QUESTGIVER_COMPLETE_QUEST(data) => {
+                handle_cmsg_questgiver_complete_quest(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_QUESTGIVER_CHOOSE_REWARD(data) => {
+                handle_cmsg_questgiver_choose_reward(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_DESTROYITEM(data) => {
+                handle_cmsg_destroyitem(client_manager, character_manager, world, packet.client_id, data).await
+            }
             ClientOpcodeMessage::CMSG_AUTOEQUIP_ITEM(data) => {
                 handle_cmsg_autoequip_item(client_manager, character_manager, world, packet.client_id, data).await
             }
@@ -183,7 +376,64 @@ impl PacketHandler {
             ClientOpcodeMessage::CMSG_SET_ACTION_BUTTON(data) => {
                 handle_cmsg_set_action_button(client_manager, character_manager, packet.client_id, data).await
             }
-            _ => bail!("Unhandled packet opcode: {:?}", packet.payload),
-        }
+            ClientOpcodeMessage::CMSG_GAMEOBJ_USE(data) => {
+                handle_cmsg_gameobj_use(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_EQUIPMENT_SET_SAVE(data) => {
+                handle_cmsg_equipment_set_save(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_EQUIPMENT_SET_DELETE(data) => {
+                handle_cmsg_equipment_set_delete(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_EQUIPMENT_SET_USE(data) => {
+                handle_cmsg_equipment_set_use(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_PET_SPELL_AUTOCAST(data) => {
+                handle_cmsg_pet_spell_autocast(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_PET_LEARN_TALENT(data) => {
+                handle_cmsg_pet_learn_talent(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_PET_UNLEARN(data) => handle_cmsg_pet_unlearn(client_manager, character_manager, packet.client_id, data).await,
+            ClientOpcodeMessage::CMSG_PET_ACTION(data) => {
+                handle_cmsg_pet_action(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_TAXINODE_STATUS_QUERY(data) => {
+                handle_cmsg_taxinode_status_query(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_TAXIQUERYAVAILABLENODES(data) => {
+                handle_cmsg_taxiqueryavailablenodes(client_manager, character_manager, world, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::CMSG_QUERY_INSPECT_ACHIEVEMENTS(data) => {
+                handle_cmsg_query_inspect_achievements(client_manager, character_manager, packet.client_id, data).await
+            }
+            ClientOpcodeMessage::MSG_INSPECT_ARENA_TEAMS(data) => {
+                handle_msg_inspect_arena_teams(client_manager, character_manager, packet.client_id, data).await
+            }
+            _ => Err(anyhow!("Unhandled packet opcode: {:?}", packet.payload)),
+        };
+        result.map_err(HandlerError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::TestHarness;
+    use wow_world_messages::wrath::opcodes::ClientOpcodeMessage;
+    use wow_world_messages::wrath::{UnitStandState, Vector3d, CMSG_STANDSTATECHANGE};
+
+    #[test]
+    fn world_packets_without_a_character_disconnect_the_client() {
+        smol::block_on(async {
+            let mut harness = TestHarness::new();
+            let client = harness.add_character("Nobody", Vector3d { x: 0.0, y: 0.0, z: 0.0 }).await;
+            harness.client_manager.get_client_mut(client.addr).await.unwrap().data.active_character = None;
+
+            let packet = ClientOpcodeMessage::CMSG_STANDSTATECHANGE(CMSG_STANDSTATECHANGE {
+                animation_state: UnitStandState::Sit,
+            });
+            let error = harness.handle_packet(&client, packet).await.unwrap_err();
+            assert!(error.disconnects_client(), "{error}");
+        });
     }
 }
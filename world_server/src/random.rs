@@ -0,0 +1,22 @@
+//! The random number generator game code rolls with.
+//!
+//! The main loop reseeds it at the start of every tick and the event journal records that seed, so a replay
+//! of the journal rolls the same numbers in the same ticks. Code that needs randomness should go through
+//! `with_rng` instead of `rand::thread_rng`, which can't be replayed.
+
+use std::sync::Mutex;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+static RNG: Mutex<Option<StdRng>> = Mutex::new(None);
+
+pub fn reseed(seed: u64) {
+    *RNG.lock().unwrap() = Some(StdRng::seed_from_u64(seed));
+}
+
+//Until the first reseed (tools, tests) the generator is seeded from the OS
+pub fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    let mut rng = RNG.lock().unwrap();
+    f(rng.get_or_insert_with(StdRng::from_entropy))
+}
@@ -0,0 +1,228 @@
+//! Token buckets for the opcodes that make the server do real work, like database lookups or walking every
+//! online character. Every client gets a bucket per opcode class, packets over budget are dropped before
+//! they reach a handler. Legitimate clients burst (opening bags queries every item in them) and then go
+//! quiet, so a drop now and then is normal. A client that keeps sending while its bucket is empty is
+//! flooding on purpose and gets disconnected.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use wow_world_messages::wrath::opcodes::ClientOpcodeMessage;
+use wrath_common::config;
+
+const DEFAULT_DISCONNECT_AFTER_DROPS: u32 = 100;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum OpcodeClass {
+    ItemQuery,
+    NameQuery,
+    Who,
+    Chat,
+}
+
+impl OpcodeClass {
+    const ALL: [OpcodeClass; 4] = [OpcodeClass::ItemQuery, OpcodeClass::NameQuery, OpcodeClass::Who, OpcodeClass::Chat];
+
+    pub fn of(packet: &ClientOpcodeMessage) -> Option<Self> {
+        match packet {
+            ClientOpcodeMessage::CMSG_ITEM_QUERY_SINGLE(_) | ClientOpcodeMessage::CMSG_ITEM_NAME_QUERY(_) => Some(OpcodeClass::ItemQuery),
+            ClientOpcodeMessage::CMSG_NAME_QUERY(_) => Some(OpcodeClass::NameQuery),
+            ClientOpcodeMessage::CMSG_WHO(_) => Some(OpcodeClass::Who),
+            ClientOpcodeMessage::CMSG_MESSAGECHAT(_) => Some(OpcodeClass::Chat),
+            _ => None,
+        }
+    }
+
+    fn env_prefix(self) -> &'static str {
+        match self {
+            OpcodeClass::ItemQuery => "RATE_LIMIT_ITEM_QUERY",
+            OpcodeClass::NameQuery => "RATE_LIMIT_NAME_QUERY",
+            OpcodeClass::Who => "RATE_LIMIT_WHO",
+            OpcodeClass::Chat => "RATE_LIMIT_CHAT",
+        }
+    }
+
+    fn default_budget(self) -> Budget {
+        match self {
+            OpcodeClass::ItemQuery => Budget::new(200.0, 50.0),
+            OpcodeClass::NameQuery => Budget::new(100.0, 20.0),
+            OpcodeClass::Who => Budget::new(3.0, 0.5),
+            OpcodeClass::Chat => Budget::new(10.0, 2.0),
+        }
+    }
+}
+
+impl fmt::Display for OpcodeClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Budget {
+    //How many packets can be sent at once
+    burst: f32,
+    per_second: f32,
+}
+
+impl Budget {
+    pub fn new(burst: f32, per_second: f32) -> Self {
+        Self { burst, per_second }
+    }
+}
+
+struct TokenBucket {
+    tokens: f32,
+    last_refill: Instant,
+    //Packets dropped since the bucket last had a token
+    drops: u32,
+}
+
+impl TokenBucket {
+    fn full(budget: Budget, now: Instant) -> Self {
+        Self {
+            tokens: budget.burst,
+            last_refill: now,
+            drops: 0,
+        }
+    }
+
+    fn refill(&mut self, budget: Budget, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f32();
+        self.tokens = (self.tokens + elapsed * budget.per_second).min(budget.burst);
+        self.last_refill = now;
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RateLimitVerdict {
+    Allow,
+    Drop,
+    Disconnect,
+}
+
+pub struct RateLimiter {
+    budgets: HashMap<OpcodeClass, Budget>,
+    disconnect_after_drops: u32,
+    buckets: HashMap<(SocketAddr, OpcodeClass), TokenBucket>,
+}
+
+impl RateLimiter {
+    pub fn new(budgets: HashMap<OpcodeClass, Budget>, disconnect_after_drops: u32) -> Self {
+        Self {
+            budgets,
+            disconnect_after_drops,
+            buckets: HashMap::new(),
+        }
+    }
+
+    //Classes without a positive burst and rate in the environment keep their defaults
+    pub fn from_env() -> Self {
+        let budgets = OpcodeClass::ALL
+            .into_iter()
+            .map(|class| {
+                let prefix = class.env_prefix();
+                let burst = config::optional::<f32>(&format!("{prefix}_BURST")).filter(|&v| v > 0.0);
+                let per_second = config::optional::<f32>(&format!("{prefix}_PER_SECOND")).filter(|&v| v > 0.0);
+                let default = class.default_budget();
+                (
+                    class,
+                    Budget::new(burst.unwrap_or(default.burst), per_second.unwrap_or(default.per_second)),
+                )
+            })
+            .collect();
+        let disconnect_after_drops = config::positive("RATE_LIMIT_DISCONNECT_AFTER_DROPS").map_or(DEFAULT_DISCONNECT_AFTER_DROPS, |v| v as u32);
+        Self::new(budgets, disconnect_after_drops)
+    }
+
+    pub fn check(&mut self, client: SocketAddr, class: OpcodeClass, now: Instant) -> RateLimitVerdict {
+        let budget = self.budgets.get(&class).copied().unwrap_or_else(|| class.default_budget());
+        let bucket = self.buckets.entry((client, class)).or_insert_with(|| TokenBucket::full(budget, now));
+        bucket.refill(budget, now);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            bucket.drops = 0;
+            return RateLimitVerdict::Allow;
+        }
+
+        bucket.drops += 1;
+        if bucket.drops >= self.disconnect_after_drops {
+            RateLimitVerdict::Disconnect
+        } else {
+            RateLimitVerdict::Drop
+        }
+    }
+
+    pub fn forget_client(&mut self, client: SocketAddr) {
+        self.buckets.retain(|(addr, _), _| *addr != client);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::Duration;
+
+    use super::*;
+
+    fn client(port: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port)
+    }
+
+    fn limiter(burst: f32, per_second: f32, disconnect_after_drops: u32) -> RateLimiter {
+        let budgets = OpcodeClass::ALL
+            .into_iter()
+            .map(|class| (class, Budget::new(burst, per_second)))
+            .collect();
+        RateLimiter::new(budgets, disconnect_after_drops)
+    }
+
+    #[test]
+    fn bursts_are_allowed_and_then_refill_over_time() {
+        let mut limiter = limiter(3.0, 2.0, 100);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limiter.check(client(1), OpcodeClass::NameQuery, now), RateLimitVerdict::Allow);
+        }
+        assert_eq!(limiter.check(client(1), OpcodeClass::NameQuery, now), RateLimitVerdict::Drop);
+
+        //Two tokens per second, so one is back after half a second
+        let later = now + Duration::from_millis(500);
+        assert_eq!(limiter.check(client(1), OpcodeClass::NameQuery, later), RateLimitVerdict::Allow);
+        assert_eq!(limiter.check(client(1), OpcodeClass::NameQuery, later), RateLimitVerdict::Drop);
+    }
+
+    #[test]
+    fn buckets_are_per_client_and_class() {
+        let mut limiter = limiter(1.0, 1.0, 100);
+        let now = Instant::now();
+        assert_eq!(limiter.check(client(1), OpcodeClass::Who, now), RateLimitVerdict::Allow);
+        assert_eq!(limiter.check(client(1), OpcodeClass::Who, now), RateLimitVerdict::Drop);
+        assert_eq!(limiter.check(client(1), OpcodeClass::Chat, now), RateLimitVerdict::Allow);
+        assert_eq!(limiter.check(client(2), OpcodeClass::Who, now), RateLimitVerdict::Allow);
+    }
+
+    #[test]
+    fn flooding_disconnects_but_occasional_drops_do_not() {
+        let mut limiter = limiter(1.0, 1.0, 5);
+        let now = Instant::now();
+        assert_eq!(limiter.check(client(1), OpcodeClass::ItemQuery, now), RateLimitVerdict::Allow);
+        for _ in 0..4 {
+            assert_eq!(limiter.check(client(1), OpcodeClass::ItemQuery, now), RateLimitVerdict::Drop);
+        }
+
+        //A token coming back resets the count
+        let later = now + Duration::from_secs(1);
+        assert_eq!(limiter.check(client(1), OpcodeClass::ItemQuery, later), RateLimitVerdict::Allow);
+        for _ in 0..4 {
+            assert_eq!(limiter.check(client(1), OpcodeClass::ItemQuery, later), RateLimitVerdict::Drop);
+        }
+        assert_eq!(limiter.check(client(1), OpcodeClass::ItemQuery, later), RateLimitVerdict::Disconnect);
+
+        limiter.forget_client(client(1));
+        assert_eq!(limiter.check(client(1), OpcodeClass::ItemQuery, later), RateLimitVerdict::Allow);
+    }
+}
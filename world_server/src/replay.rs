@@ -0,0 +1,260 @@
+//! Event journal for reproducing bugs deterministically.
+//!
+//! With `REPLAY_LOG_PATH` set, the world server writes every client event the client manager handles to
+//! that file, one JSON record per line, together with each tick's length and random seed. Starting the
+//! server with `--replay <file>` loads the world as usual, but instead of accepting connections it feeds
+//! the journal back through the client manager and world tick by tick, as fast as it can. Packets sent to
+//! the replayed clients are dropped.
+//!
+//! The journal only covers what comes in over the network. For a faithful replay the databases have to be
+//! in the state they were in when recording started, e.g. restored from a dump taken at the same time.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use wow_world_messages::wrath::opcodes::ClientOpcodeMessage;
+use wrath_common::GmLevel;
+
+use crate::character::character_manager::CharacterManager;
+use crate::client_manager::ClientManager;
+use crate::connection::events::{ClientEvent, ServerEvent};
+use crate::localization::ClientLocale;
+use crate::prelude::*;
+use crate::random;
+use crate::world::World;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JournalRecord {
+    Tick {
+        elapsed_ms: u64,
+        delta_time: f32,
+        seed: u64,
+    },
+    Connected {
+        elapsed_ms: u64,
+        addr: SocketAddr,
+        account_id: u32,
+        client_build: u32,
+        locale: String,
+        gm_level: u8,
+    },
+    Disconnected {
+        elapsed_ms: u64,
+        addr: SocketAddr,
+    },
+    Message {
+        elapsed_ms: u64,
+        addr: SocketAddr,
+        //Unencrypted packet as the client sent it, header included
+        packet: Vec<u8>,
+    },
+}
+
+pub struct EventJournal {
+    writer: BufWriter<File>,
+    started: Instant,
+}
+
+impl EventJournal {
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(path) = std::env::var("REPLAY_LOG_PATH").ok().filter(|path| !path.is_empty()) else {
+            return Ok(None);
+        };
+
+        info!("Recording client events to {}", path);
+        Ok(Some(Self {
+            writer: BufWriter::new(File::create(path)?),
+            started: Instant::now(),
+        }))
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    //Flushes the previous tick's events, so a crash loses at most the tick that crashed
+    pub fn record_tick(&mut self, delta_time: f32, seed: u64) -> Result<()> {
+        self.writer.flush()?;
+        let elapsed_ms = self.elapsed_ms();
+        self.write(&JournalRecord::Tick {
+            elapsed_ms,
+            delta_time,
+            seed,
+        })
+    }
+
+    pub async fn record_event(&mut self, event: &ClientEvent) -> Result<()> {
+        let elapsed_ms = self.elapsed_ms();
+        let record = match event {
+            ClientEvent::Connected {
+                addr,
+                account_id,
+                client_build,
+                locale,
+                gm_level,
+                ..
+            } => JournalRecord::Connected {
+                elapsed_ms,
+                addr: *addr,
+                account_id: *account_id,
+                client_build: *client_build,
+                locale: locale.code().to_string(),
+                gm_level: gm_level.as_db(),
+            },
+            ClientEvent::Disconnected { addr } => JournalRecord::Disconnected { elapsed_ms, addr: *addr },
+            ClientEvent::Message { addr, packet } => {
+                let mut bytes = Vec::new();
+                packet.astd_write_unencrypted_client(&mut bytes).await?;
+                JournalRecord::Message {
+                    elapsed_ms,
+                    addr: *addr,
+                    packet: bytes,
+                }
+            }
+        };
+        self.write(&record)
+    }
+
+    fn write(&mut self, record: &JournalRecord) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+//A tick of the journal: its length, seed and the events the client manager handled during it
+struct ReplayTick {
+    delta_time: f32,
+    seed: u64,
+    events: Vec<JournalRecord>,
+}
+
+fn read_ticks(reader: impl BufRead) -> Result<Vec<ReplayTick>> {
+    let mut ticks: Vec<ReplayTick> = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: JournalRecord = serde_json::from_str(&line).map_err(|e| anyhow!("Journal line {}: {}", line_number + 1, e))?;
+        match record {
+            JournalRecord::Tick { delta_time, seed, .. } => ticks.push(ReplayTick {
+                delta_time,
+                seed,
+                events: Vec::new(),
+            }),
+            event => match ticks.last_mut() {
+                Some(tick) => tick.events.push(event),
+                None => bail!("Journal line {}: event before the first tick", line_number + 1),
+            },
+        }
+    }
+    Ok(ticks)
+}
+
+async fn to_client_event(record: JournalRecord, connection_receivers: &mut Vec<flume::Receiver<ServerEvent>>) -> Result<ClientEvent> {
+    Ok(match record {
+        JournalRecord::Connected {
+            addr,
+            account_id,
+            client_build,
+            locale,
+            gm_level,
+            ..
+        } => {
+            let (connection_sender, connection_receiver) = flume::unbounded();
+            connection_receivers.push(connection_receiver);
+            ClientEvent::Connected {
+                addr,
+                account_id,
+                client_build,
+                locale: ClientLocale::from_code(&locale),
+                gm_level: GmLevel::from_db(gm_level),
+                connection_sender,
+            }
+        }
+        JournalRecord::Disconnected { addr, .. } => ClientEvent::Disconnected { addr },
+        JournalRecord::Message { addr, packet, .. } => ClientEvent::Message {
+            addr,
+            packet: ClientOpcodeMessage::astd_read_unencrypted(&mut packet.as_slice()).await?,
+        },
+        JournalRecord::Tick { .. } => bail!("Ticks are not client events"),
+    })
+}
+
+pub async fn replay_journal(
+    path: &str,
+    client_manager: &mut ClientManager,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+) -> Result<()> {
+    let ticks = read_ticks(BufReader::new(File::open(path)?))?;
+    info!("Replaying {} ticks from {}", ticks.len(), path);
+
+    let client_manager_sender = client_manager.get_sender();
+    //Kept alive so sends to the replayed clients succeed, what was sent is dropped every tick
+    let mut connection_receivers = Vec::new();
+    for (tick_number, tick) in ticks.into_iter().enumerate() {
+        random::reseed(tick.seed);
+        for record in tick.events {
+            let event = to_client_event(record, &mut connection_receivers).await?;
+            client_manager_sender.send(event)?;
+        }
+
+        client_manager
+            .tick(tick.delta_time, character_manager, world)
+            .await
+            .unwrap_or_else(|e| error!("Error while ticking clients in tick {}: {}", tick_number, e));
+        world.tick(character_manager, tick.delta_time).await?;
+        for receiver in &connection_receivers {
+            for _ in receiver.drain() {}
+        }
+    }
+
+    info!("Replay finished");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_grouped_under_their_tick() {
+        let addr: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+        let records = [
+            JournalRecord::Tick {
+                elapsed_ms: 0,
+                delta_time: 0.02,
+                seed: 7,
+            },
+            JournalRecord::Connected {
+                elapsed_ms: 5,
+                addr,
+                account_id: 1,
+                client_build: 12340,
+                locale: "enUS".to_string(),
+                gm_level: 0,
+            },
+            JournalRecord::Tick {
+                elapsed_ms: 20,
+                delta_time: 0.02,
+                seed: 8,
+            },
+            JournalRecord::Disconnected { elapsed_ms: 30, addr },
+        ];
+        let journal: String = records.iter().map(|record| serde_json::to_string(record).unwrap() + "\n").collect();
+
+        let ticks = read_ticks(journal.as_bytes()).unwrap();
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[0].seed, 7);
+        assert_eq!(ticks[0].events, &records[1..2]);
+        assert_eq!(ticks[1].events, &records[3..]);
+
+        assert!(read_ticks(serde_json::to_string(&records[3]).unwrap().as_bytes()).is_err());
+    }
+}
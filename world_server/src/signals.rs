@@ -0,0 +1,65 @@
+//! Process signals. SIGINT and SIGTERM shut the server down cleanly, so a container stop saves everything
+//! like Ctrl+C does. SIGHUP re-reads the .env file and reloads the data caches without a restart.
+use std::sync::{atomic::AtomicBool, Arc};
+
+use wrath_game_db::GameDatabase;
+
+use crate::data::{DataStorage, DataStorageReload};
+use crate::prelude::*;
+
+#[cfg(unix)]
+pub async fn handle_signals(
+    running: Arc<AtomicBool>,
+    game_db: Arc<GameDatabase>,
+    data_storage_sender: flume::Sender<DataStorageReload>,
+    config_reload_sender: flume::Sender<()>,
+) -> Result<()> {
+    use async_signal::{Signal, Signals};
+    use futures::StreamExt;
+
+    let mut signals = Signals::new([Signal::Int, Signal::Term, Signal::Hup])?;
+    while let Some(signal) = signals.next().await {
+        match signal? {
+            Signal::Hup => {
+                info!("Received SIGHUP, reloading configuration and data");
+                if let Err(e) = reload(game_db.clone(), &data_storage_sender, &config_reload_sender).await {
+                    error!("Reload failed, keeping the old configuration and data: {}", e);
+                }
+            }
+            signal => {
+                info!("Received {:?}, starting graceful shutdown", signal);
+                running.store(false, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+    }
+    Ok(())
+}
+
+//There is no SIGTERM or SIGHUP outside of unix, Ctrl+C is all there is
+#[cfg(not(unix))]
+pub async fn handle_signals(
+    running: Arc<AtomicBool>,
+    _game_db: Arc<GameDatabase>,
+    _data_storage_sender: flume::Sender<DataStorageReload>,
+    _config_reload_sender: flume::Sender<()>,
+) -> Result<()> {
+    async_ctrlc::CtrlC::new()?.await;
+    info!("Detected Ctrl+C, starting graceful shutdown");
+    running.store(false, std::sync::atomic::Ordering::Relaxed);
+    Ok(())
+}
+
+//The main loop picks up both between ticks, like a data storage reload from the console
+#[cfg_attr(not(unix), allow(dead_code))]
+async fn reload(
+    game_db: Arc<GameDatabase>,
+    data_storage_sender: &flume::Sender<DataStorageReload>,
+    config_reload_sender: &flume::Sender<()>,
+) -> Result<()> {
+    dotenvy::dotenv_override()?;
+    config_reload_sender.send_async(()).await?;
+
+    let data_storage = DataStorage::load_validated(game_db).await?;
+    data_storage_sender.send_async(DataStorageReload::Full(data_storage)).await?;
+    Ok(())
+}
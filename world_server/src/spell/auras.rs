@@ -0,0 +1,384 @@
+//! Auras on characters: applying them from spell effects, their periodic ticks and the SMSG_AURA_UPDATE
+//! packets. Since 3.0 units have no aura update fields anymore, the client only knows about auras from these
+//! packets, so every change goes to everyone in range and characters coming into view get the full list.
+
+use wow_world_messages::wrath::{
+    AuraUpdate, AuraUpdate_AuraFlag, AuraUpdate_AuraFlag_Duration, AuraUpdate_AuraFlag_NotCaster, Level, SMSG_AURA_UPDATE, SMSG_AURA_UPDATE_ALL,
+};
+
+use super::spell_cast::get_spell_school;
+use super::spell_info::{
+    SpellEffect, SpellEffectKind, SpellInfo, AURA_BIND_SIGHT, AURA_FEATHER_FALL, AURA_FLY, AURA_HOVER, AURA_MOD_INVISIBILITY,
+    AURA_MOD_INVISIBILITY_DETECT, AURA_MOD_ROOT, AURA_MOD_STEALTH, AURA_MOD_STEALTH_DETECT, AURA_PERIODIC_DAMAGE, AURA_PERIODIC_HEAL,
+    AURA_WATER_WALK,
+};
+use crate::character::character_auras::{Aura, AuraApplication, AuraTick, Periodic, PeriodicKind};
+use crate::character::character_deserter::is_queue_punishment_aura;
+use crate::character::character_far_sight::FarSightTarget;
+use crate::character::character_forced_movement::MovementCapability;
+use crate::character::character_manager::CharacterManager;
+use crate::character::character_stealth::{StealthBreakReason, StealthKind};
+use crate::character::Character;
+use crate::combat::combat_log::{self, DamageLogEntry, HealLogEntry};
+use crate::combat::damage::{self, Victim};
+use crate::connection::events::ServerEvent;
+use crate::data::DataStorage;
+use crate::prelude::*;
+use crate::world::World;
+
+fn aura_update(unit: Guid, aura: &Aura) -> AuraUpdate {
+    let mut flags = AuraUpdate_AuraFlag::empty().set_effect_1();
+    flags = match aura.negative {
+        true => flags.set_negative(),
+        false => flags.set_cancellable(),
+    };
+    //The caster is only written out when it's someone else
+    if aura.caster != unit {
+        flags = flags.set_not_caster(AuraUpdate_AuraFlag_NotCaster { caster: aura.caster });
+    }
+    if let (Some(duration), Some(remaining)) = (aura.duration, aura.remaining) {
+        flags = flags.set_duration(AuraUpdate_AuraFlag_Duration {
+            duration: (duration * 1000.0) as u32,
+            time_left: (remaining.max(0.0) * 1000.0) as u32,
+        });
+    }
+
+    AuraUpdate {
+        visual_slot: aura.slot,
+        spell: aura.spell_id,
+        flags,
+        level: Level::new(aura.caster_level),
+        aura_stack_count: 0,
+    }
+}
+
+//An empty slot, the client removes whatever aura it showed there
+fn removed_aura_update(slot: u8) -> AuraUpdate {
+    AuraUpdate {
+        visual_slot: slot,
+        spell: 0,
+        flags: AuraUpdate_AuraFlag::empty(),
+        level: Level::new(0),
+        aura_stack_count: 0,
+    }
+}
+
+async fn send_aura_update(unit: Guid, aura_update: AuraUpdate, character_manager: &CharacterManager, world: &World) -> Result<()> {
+    let character = character_manager.get_character(unit)?;
+    ServerEvent::AuraUpdate(SMSG_AURA_UPDATE { unit, aura_update })
+        .send_to_all_in_range(character, character_manager, true, world)
+        .await
+}
+
+pub async fn apply_aura(
+    caster_guid: Guid,
+    target_guid: Guid,
+    spell: &SpellInfo,
+    effect: &SpellEffect,
+    aura_type: u32,
+    character_manager: &mut CharacterManager,
+    world: &World,
+) -> Result<()> {
+    let periodic = match aura_type {
+        AURA_PERIODIC_DAMAGE => Some(Periodic::new(PeriodicKind::Damage, effect.aura_period)),
+        AURA_PERIODIC_HEAL => Some(Periodic::new(PeriodicKind::Heal, effect.aura_period)),
+        _ => None,
+    };
+    let caster_level = character_manager.get_character(caster_guid)?.gameplay_data.unit_level().unwrap_or(1) as u8;
+    let application = AuraApplication {
+        spell_id: spell.id,
+        caster: caster_guid,
+        caster_level,
+        aura_type,
+        amount: effect.roll_amount() as i32,
+        negative: spell.is_harmful(),
+        periodic,
+        duration: spell.duration,
+    };
+
+    let target = character_manager.get_character_mut(target_guid)?;
+    //A refreshed aura already rooted the target or granted its capability
+    let refreshed = target
+        .get_auras()
+        .iter()
+        .any(|aura| aura.spell_id == spell.id && aura.caster == caster_guid);
+    let Some(aura) = target.apply_aura(application) else {
+        trace!("No free aura slot on {} for spell {}", target_guid, spell.id);
+        return Ok(());
+    };
+    if !refreshed {
+        on_aura_applied(target, &aura).await?;
+    }
+    on_auras_changed(target);
+    //Mind vision sits on the watched unit but moves the caster's camera, a refresh restarts the caster's timer too
+    if aura_type == AURA_BIND_SIGHT {
+        character_manager
+            .get_character_mut(caster_guid)?
+            .start_far_sight(FarSightTarget::Unit(target_guid), spell.duration);
+    }
+    send_aura_update(target_guid, aura_update(target_guid, &aura), character_manager, world).await
+}
+
+pub async fn remove_auras_from_spell(target_guid: Guid, spell_id: u32, character_manager: &mut CharacterManager, world: &World) -> Result<()> {
+    let target = character_manager.get_character_mut(target_guid)?;
+    let removed = target.remove_auras_from_spell(spell_id);
+    for aura in removed.iter() {
+        on_aura_removed(target, aura).await?;
+    }
+    on_auras_changed(target);
+    end_bound_sight(target_guid, &removed, character_manager);
+    for aura in removed {
+        send_aura_update(target_guid, removed_aura_update(aura.slot), character_manager, world).await?;
+    }
+    Ok(())
+}
+
+fn get_movement_capability(aura_type: u32) -> Option<MovementCapability> {
+    match aura_type {
+        AURA_FLY => Some(MovementCapability::Fly),
+        AURA_WATER_WALK => Some(MovementCapability::WaterWalk),
+        AURA_FEATHER_FALL => Some(MovementCapability::FeatherFall),
+        AURA_HOVER => Some(MovementCapability::Hover),
+        _ => None,
+    }
+}
+
+//Roots and movement capabilities are counted on the character, every aura adds its share once and takes it away when it goes
+async fn on_aura_applied(character: &mut Character, aura: &Aura) -> Result<()> {
+    if aura.aura_type == AURA_MOD_ROOT {
+        return character.add_root().await;
+    }
+    match get_movement_capability(aura.aura_type) {
+        Some(capability) => character.grant_movement_capability(capability).await,
+        None => Ok(()),
+    }
+}
+
+async fn on_aura_removed(character: &mut Character, aura: &Aura) -> Result<()> {
+    if aura.aura_type == AURA_MOD_ROOT {
+        return character.remove_root().await;
+    }
+    match get_movement_capability(aura.aura_type) {
+        Some(capability) => character.revoke_movement_capability(capability).await,
+        None => Ok(()),
+    }
+}
+
+//The caster may already be watching something else or have cancelled the sight itself
+fn end_bound_sight(target_guid: Guid, removed: &[Aura], character_manager: &mut CharacterManager) {
+    for aura in removed.iter().filter(|aura| aura.aura_type == AURA_BIND_SIGHT) {
+        if let Some(caster) = character_manager.find_character_mut(aura.caster) {
+            if caster.get_far_sight_target() == Some(FarSightTarget::Unit(target_guid)) {
+                caster.end_far_sight();
+            }
+        }
+    }
+}
+
+//Stealth and detection follow whatever auras the character has left, several can grant them at once
+fn on_auras_changed(character: &mut Character) {
+    let total = |aura_type: u32| -> i32 {
+        character
+            .get_auras()
+            .iter()
+            .filter(|aura| aura.aura_type == aura_type)
+            .map(|aura| aura.amount)
+            .sum()
+    };
+    let (stealth_detection, invisibility_detection) = (total(AURA_MOD_STEALTH_DETECT), total(AURA_MOD_INVISIBILITY_DETECT));
+    character.set_stealth_detection(stealth_detection, invisibility_detection);
+
+    let stealth = character.get_auras().iter().find_map(|aura| match aura.aura_type {
+        AURA_MOD_STEALTH => Some((StealthKind::Stealth, aura.amount)),
+        AURA_MOD_INVISIBILITY => Some((StealthKind::Invisibility, aura.amount)),
+        _ => None,
+    });
+    match stealth {
+        Some((kind, bonus)) => character.apply_stealth(kind, bonus),
+        None => character.remove_stealth(),
+    }
+}
+
+//Attacking, casting and for stealth also taking damage bring the character out of hiding along with its aura
+pub async fn break_stealth(guid: Guid, reason: StealthBreakReason, character_manager: &mut CharacterManager, world: &World) -> Result<()> {
+    let character = character_manager.get_character_mut(guid)?;
+    if !character.is_stealthed() || !character.handle_stealth_break(reason) {
+        return Ok(());
+    }
+    let spell_ids: Vec<u32> = character
+        .get_auras()
+        .iter()
+        .filter(|aura| matches!(aura.aura_type, AURA_MOD_STEALTH | AURA_MOD_INVISIBILITY))
+        .map(|aura| aura.spell_id)
+        .collect();
+    for spell_id in spell_ids {
+        remove_auras_from_spell(guid, spell_id, character_manager, world).await?;
+    }
+    Ok(())
+}
+
+//Enemies lose their buffs and friends their debuffs, as many spells' worth as the effect rolled
+pub async fn dispel_auras(
+    caster_guid: Guid,
+    target_guid: Guid,
+    spell: &SpellInfo,
+    effect: &SpellEffect,
+    data_storage: &DataStorage,
+    character_manager: &mut CharacterManager,
+    world: &World,
+) -> Result<()> {
+    let SpellEffectKind::Dispel { dispel_type } = effect.kind else {
+        return Ok(());
+    };
+    let caster = character_manager.get_character(caster_guid)?;
+    let hostile = damage::can_attack(caster, target_guid, &Victim::Character, character_manager).await?;
+    let count = effect.roll_amount().max(1) as usize;
+
+    let mut dispelled: Vec<u32> = Vec::new();
+    for aura in character_manager.get_character(target_guid)?.get_auras() {
+        if dispelled.len() == count {
+            break;
+        }
+        if aura.negative == hostile || dispelled.contains(&aura.spell_id) {
+            continue;
+        }
+        if SpellInfo::load(data_storage, aura.spell_id)?.is_some_and(|aura_spell| aura_spell.dispel_type == dispel_type) {
+            dispelled.push(aura.spell_id);
+        }
+    }
+    if dispelled.is_empty() {
+        return Ok(());
+    }
+
+    for &spell_id in dispelled.iter() {
+        remove_auras_from_spell(target_guid, spell_id, character_manager, world).await?;
+    }
+    let caster = character_manager.get_character(caster_guid)?;
+    combat_log::log_dispel(caster, character_manager, world, caster_guid, target_guid, spell.id, &dispelled).await
+}
+
+//Dying takes every aura away, except for the deserter debuffs which the character doesn't get out of that easily
+pub async fn remove_all_auras(target_guid: Guid, character_manager: &mut CharacterManager, world: &World) -> Result<()> {
+    let spell_ids: Vec<u32> = character_manager
+        .get_character(target_guid)?
+        .get_auras()
+        .iter()
+        .map(|aura| aura.spell_id)
+        .filter(|&spell_id| !is_queue_punishment_aura(spell_id))
+        .collect();
+    for spell_id in spell_ids {
+        remove_auras_from_spell(target_guid, spell_id, character_manager, world).await?;
+    }
+    Ok(())
+}
+
+//For auras the server puts on the character itself instead of a spell cast
+pub async fn send_applied_aura(target_guid: Guid, aura: &Aura, character_manager: &CharacterManager, world: &World) -> Result<()> {
+    send_aura_update(target_guid, aura_update(target_guid, aura), character_manager, world).await
+}
+
+pub async fn send_removed_aura_slots(target_guid: Guid, slots: Vec<u8>, character_manager: &CharacterManager, world: &World) -> Result<()> {
+    for slot in slots {
+        send_aura_update(target_guid, removed_aura_update(slot), character_manager, world).await?;
+    }
+    Ok(())
+}
+
+//Auras that were loaded with the character, the client only hears about its own auras from us
+pub async fn send_own_auras(character: &Character) -> Result<()> {
+    if character.get_auras().is_empty() {
+        return Ok(());
+    }
+    let unit = character.get_guid();
+    let aura_updates = character.get_auras().iter().map(|aura| aura_update(unit, aura)).collect();
+    ServerEvent::AuraUpdateAll(SMSG_AURA_UPDATE_ALL { unit, aura_updates })
+        .send_to_character(character)
+        .await
+}
+
+//Called by the client tick, runs what the character's auras did since the last tick
+pub async fn process_aura_events(guid: Guid, data_storage: &DataStorage, character_manager: &mut CharacterManager, world: &mut World) -> Result<()> {
+    if !character_manager.get_character(guid)?.has_pending_aura_events() {
+        return Ok(());
+    }
+
+    let character = character_manager.get_character_mut(guid)?;
+    let expired = character.take_expired_auras();
+    for aura in expired.iter() {
+        on_aura_removed(character, aura).await?;
+    }
+    if !expired.is_empty() {
+        on_auras_changed(character);
+    }
+    end_bound_sight(guid, &expired, character_manager);
+    for aura in expired {
+        send_aura_update(guid, removed_aura_update(aura.slot), character_manager, world).await?;
+    }
+
+    for tick in character_manager.get_character_mut(guid)?.take_aura_ticks() {
+        //The target can die to an earlier tick, and the log needs the caster to still be around
+        if !character_manager.get_character(guid)?.is_alive() || character_manager.find_character(tick.caster).is_none() {
+            continue;
+        }
+        apply_aura_tick(guid, &tick, data_storage, character_manager, world).await?;
+    }
+    Ok(())
+}
+
+async fn apply_aura_tick(
+    target_guid: Guid,
+    tick: &AuraTick,
+    data_storage: &DataStorage,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+) -> Result<()> {
+    let amount = tick.amount.max(0) as u32;
+    match tick.kind {
+        PeriodicKind::Damage => {
+            let school_mask = SpellInfo::load(data_storage, tick.spell_id)?.map_or(1, |spell| spell.school_mask);
+            let entry = DamageLogEntry {
+                attacker: tick.caster,
+                target: target_guid,
+                spell_id: Some(tick.spell_id),
+                school: get_spell_school(school_mask),
+                damage: amount,
+                overkill: 0,
+                absorbed: 0,
+                resisted: 0,
+                blocked: 0,
+                critical: false,
+            };
+            damage::deal_damage(entry, &Victim::Character, true, character_manager, world).await?;
+        }
+        PeriodicKind::Heal => {
+            let entry = HealLogEntry {
+                caster: tick.caster,
+                target: target_guid,
+                spell_id: tick.spell_id,
+                amount,
+                overheal: 0,
+                absorbed: 0,
+                critical: false,
+            };
+            damage::heal(entry, &Victim::Character, true, character_manager, world).await?;
+        }
+    }
+    Ok(())
+}
+
+//Called by the map once the create blocks went out, the client ignores auras of units it doesn't know yet
+pub async fn send_aura_snapshots(guid: Guid, character_manager: &mut CharacterManager) -> Result<()> {
+    for unit in character_manager.get_character_mut(guid)?.take_pending_aura_snapshots() {
+        let Some(other) = character_manager.find_character(unit) else {
+            continue;
+        };
+        if other.get_auras().is_empty() {
+            continue;
+        }
+        let aura_updates = other.get_auras().iter().map(|aura| aura_update(unit, aura)).collect();
+        ServerEvent::AuraUpdateAll(SMSG_AURA_UPDATE_ALL { unit, aura_updates })
+            .send_to_character(character_manager.get_character(guid)?)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use wow_world_messages::wrath::SpellCastResult;
+
+use crate::character::character_power::PowerCostError;
+
+//Spells and items share the validation, only the cooldown error differs between them
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum CastSource {
+    Spell,
+    Item { item_id: u32 },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TargetConditions {
+    pub distance: f32,
+    pub is_dead: bool,
+    pub in_line_of_sight: bool,
+}
+
+//Everything validation needs to know about the cast, gathered by the caller from the caster, the target and the spell data
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct CastConditions {
+    pub caster_dead: bool,
+    pub caster_moving: bool,
+    pub has_cast_time: bool,
+    pub on_cooldown: bool,
+    //One of the spell's schools is locked out by an interrupt
+    pub school_locked: bool,
+    //Bit mask of shapeshift forms the spell can be cast in, 0 means any form
+    pub required_forms: u32,
+    pub current_form: u8,
+    //Focus id and whether a matching object is close enough
+    pub required_spell_focus: Option<(u32, bool)>,
+    pub requires_target: bool,
+    pub target: Option<TargetConditions>,
+    pub targets_dead_allowed: bool,
+    pub min_range: f32,
+    pub max_range: f32,
+    pub power: Option<PowerCostError>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CastFailure {
+    CasterDead,
+    NotReady,
+    ItemNotReady,
+    Moving,
+    RequiresSpellFocus(u32),
+    WrongForm,
+    BadTargets,
+    TargetsDead,
+    OutOfRange,
+    TooClose,
+    LineOfSight,
+    NoPower,
+    NoComboPoints,
+    Interrupted,
+    NotKnown,
+    SpellInProgress,
+}
+
+impl CastFailure {
+    pub fn as_spell_cast_result(self) -> SpellCastResult {
+        match self {
+            CastFailure::CasterDead => SpellCastResult::CasterDead,
+            CastFailure::NotReady => SpellCastResult::NotReady,
+            CastFailure::ItemNotReady => SpellCastResult::ItemNotReady,
+            CastFailure::Moving => SpellCastResult::Moving,
+            CastFailure::RequiresSpellFocus(_) => SpellCastResult::RequiresSpellFocus,
+            CastFailure::WrongForm => SpellCastResult::OnlyShapeshift,
+            CastFailure::BadTargets => SpellCastResult::BadTargets,
+            CastFailure::TargetsDead => SpellCastResult::TargetsDead,
+            CastFailure::OutOfRange => SpellCastResult::OutOfRange,
+            CastFailure::TooClose => SpellCastResult::TooClose,
+            CastFailure::LineOfSight => SpellCastResult::LineOfSight,
+            CastFailure::NoPower => SpellCastResult::NoPower,
+            CastFailure::NoComboPoints => SpellCastResult::NoComboPoints,
+            CastFailure::Interrupted => SpellCastResult::Interrupted,
+            CastFailure::NotKnown => SpellCastResult::NotKnown,
+            CastFailure::SpellInProgress => SpellCastResult::SpellInProgress,
+        }
+    }
+}
+
+//Checks run in the same order as the client's own checks, so the first error it would show is the one we send
+pub fn validate_cast(conditions: &CastConditions, source: CastSource) -> Result<(), CastFailure> {
+    if conditions.caster_dead {
+        return Err(CastFailure::CasterDead);
+    }
+
+    if conditions.on_cooldown {
+        return Err(match source {
+            CastSource::Spell => CastFailure::NotReady,
+            CastSource::Item { .. } => CastFailure::ItemNotReady,
+        });
+    }
+
+    //The client shows school lockouts as cooldowns, so it gets the same error
+    if conditions.school_locked {
+        return Err(CastFailure::NotReady);
+    }
+
+    if conditions.caster_moving && conditions.has_cast_time {
+        return Err(CastFailure::Moving);
+    }
+
+    if conditions.required_forms != 0 && conditions.required_forms & 1u32.checked_shl(conditions.current_form as u32).unwrap_or(0) == 0 {
+        return Err(CastFailure::WrongForm);
+    }
+
+    if let Some((focus_id, false)) = conditions.required_spell_focus {
+        return Err(CastFailure::RequiresSpellFocus(focus_id));
+    }
+
+    match conditions.target {
+        None if conditions.requires_target => return Err(CastFailure::BadTargets),
+        None => {}
+        Some(target) => {
+            if target.is_dead && !conditions.targets_dead_allowed {
+                return Err(CastFailure::TargetsDead);
+            }
+            if target.distance > conditions.max_range {
+                return Err(CastFailure::OutOfRange);
+            }
+            if target.distance < conditions.min_range {
+                return Err(CastFailure::TooClose);
+            }
+            if !target.in_line_of_sight {
+                return Err(CastFailure::LineOfSight);
+            }
+        }
+    }
+
+    match conditions.power {
+        Some(PowerCostError::NoComboPoints) => Err(CastFailure::NoComboPoints),
+        Some(PowerCostError::NotEnoughPower(_) | PowerCostError::NotEnoughRunes) => Err(CastFailure::NoPower),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wow_world_messages::wrath::Power;
+
+    fn targeted_cast() -> CastConditions {
+        CastConditions {
+            requires_target: true,
+            target: Some(TargetConditions {
+                distance: 10.0,
+                is_dead: false,
+                in_line_of_sight: true,
+            }),
+            max_range: 30.0,
+            ..Default::default()
+        }
+    }
+
+    fn target_mut(conditions: &mut CastConditions) -> &mut TargetConditions {
+        conditions.target.as_mut().unwrap()
+    }
+
+    #[test]
+    fn valid_cast_passes() {
+        assert_eq!(validate_cast(&targeted_cast(), CastSource::Spell), Ok(()));
+        assert_eq!(validate_cast(&CastConditions::default(), CastSource::Spell), Ok(()));
+    }
+
+    #[test]
+    fn dead_caster_fails() {
+        let conditions = CastConditions {
+            caster_dead: true,
+            ..targeted_cast()
+        };
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::CasterDead));
+    }
+
+    #[test]
+    fn cooldown_error_depends_on_source() {
+        let conditions = CastConditions {
+            on_cooldown: true,
+            ..targeted_cast()
+        };
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::NotReady));
+        assert_eq!(
+            validate_cast(&conditions, CastSource::Item { item_id: 6948 }),
+            Err(CastFailure::ItemNotReady)
+        );
+    }
+
+    #[test]
+    fn locked_school_fails() {
+        let conditions = CastConditions {
+            school_locked: true,
+            ..targeted_cast()
+        };
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::NotReady));
+    }
+
+    #[test]
+    fn moving_only_fails_casts_with_cast_time() {
+        let mut conditions = CastConditions {
+            caster_moving: true,
+            ..targeted_cast()
+        };
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Ok(()));
+        conditions.has_cast_time = true;
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::Moving));
+    }
+
+    #[test]
+    fn wrong_form_fails() {
+        let mut conditions = CastConditions {
+            required_forms: 1 << 17,
+            current_form: 0,
+            ..targeted_cast()
+        };
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::WrongForm));
+        conditions.current_form = 17;
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Ok(()));
+    }
+
+    #[test]
+    fn missing_spell_focus_fails() {
+        let mut conditions = CastConditions {
+            required_spell_focus: Some((3, false)),
+            ..targeted_cast()
+        };
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::RequiresSpellFocus(3)));
+        conditions.required_spell_focus = Some((3, true));
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Ok(()));
+    }
+
+    #[test]
+    fn missing_target_fails() {
+        let conditions = CastConditions {
+            target: None,
+            ..targeted_cast()
+        };
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::BadTargets));
+    }
+
+    #[test]
+    fn dead_target_fails_unless_allowed() {
+        let mut conditions = targeted_cast();
+        target_mut(&mut conditions).is_dead = true;
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::TargetsDead));
+        conditions.targets_dead_allowed = true;
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Ok(()));
+    }
+
+    #[test]
+    fn range_is_checked_both_ways() {
+        let mut conditions = targeted_cast();
+        target_mut(&mut conditions).distance = 31.0;
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::OutOfRange));
+
+        conditions.min_range = 8.0;
+        target_mut(&mut conditions).distance = 5.0;
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::TooClose));
+    }
+
+    #[test]
+    fn line_of_sight_fails() {
+        let mut conditions = targeted_cast();
+        target_mut(&mut conditions).in_line_of_sight = false;
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::LineOfSight));
+    }
+
+    #[test]
+    fn power_errors_are_mapped() {
+        let mut conditions = CastConditions {
+            power: Some(PowerCostError::NotEnoughPower(Power::Rage)),
+            ..targeted_cast()
+        };
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::NoPower));
+        conditions.power = Some(PowerCostError::NotEnoughRunes);
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::NoPower));
+        conditions.power = Some(PowerCostError::NoComboPoints);
+        assert_eq!(validate_cast(&conditions, CastSource::Spell), Err(CastFailure::NoComboPoints));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod auras;
+pub mod cast_validation;
+pub mod spell_cast;
+pub mod spell_info;
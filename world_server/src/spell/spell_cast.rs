@@ -0,0 +1,385 @@
+//! The spell pipeline from CMSG_CAST_SPELL to the effects landing. A cast is validated, announced with
+//! SMSG_SPELL_START and, for spells with a cast time, handed to the character's casting state until the client
+//! tick finds it completed. Finishing a cast pays its cost, sends SMSG_SPELL_GO and runs the effects.
+
+use rand::Rng;
+use wow_world_messages::wrath::{
+    SMSG_SPELL_GO_GameobjectCastFlags, SMSG_SPELL_START_CastFlags, SpellCastTargets, SpellMissInfo, SpellSchool, Vector2d, Vector3d,
+    SMSG_MONSTER_MOVE, SMSG_SPELL_GO, SMSG_SPELL_START,
+};
+
+use super::auras;
+use super::cast_validation::{self, CastConditions, CastFailure, CastSource, TargetConditions};
+use super::spell_info::{SpellEffect, SpellEffectKind, SpellInfo};
+use crate::character::character_casting::CompletedCast;
+use crate::character::character_far_sight::FarSightTarget;
+use crate::character::character_manager::CharacterManager;
+use crate::character::character_stealth::StealthBreakReason;
+use crate::combat::combat_log::{self, DamageLogEntry, HealLogEntry};
+use crate::combat::damage::{self, Victim};
+use crate::connection::events::ServerEvent;
+use crate::data::DataStorage;
+use crate::prelude::*;
+use crate::random;
+use crate::world::prelude::GameObject;
+use crate::world::World;
+
+//The unit the client targeted, area and ground targeted spells aren't supported yet
+fn get_unit_target(targets: &SpellCastTargets) -> Option<Guid> {
+    targets.target_flags.get_unit().map(|unit| unit.unit_target)
+}
+
+//What a single effect of the spell lands on
+struct EffectTarget<'a> {
+    guid: Guid,
+    victim: &'a Victim,
+    //The item in the caster's bags a spell like Feed Pet was cast on
+    item: Option<Guid>,
+    //The spot on the ground the caster picked for spells like Heroic Leap
+    destination: Option<Vector3d>,
+}
+
+fn get_item_target(targets: &SpellCastTargets) -> Option<Guid> {
+    targets.target_flags.get_item().map(|item| item.item_target)
+}
+
+fn get_destination(targets: &SpellCastTargets) -> Option<Vector3d> {
+    targets.target_flags.get_dest_location().map(|location| location.destination)
+}
+
+//Dynamic objects aren't spawned yet, every caster only ever has one far sight point so its own counter keeps the guid unique
+fn get_far_sight_object_guid(caster_guid: Guid) -> Guid {
+    const HIGH_GUID_DYNAMIC_OBJECT: u64 = 0xF100;
+    Guid::new(HIGH_GUID_DYNAMIC_OBJECT << 48 | (caster_guid.guid() & 0xFFFF_FFFF))
+}
+
+pub fn get_spell_school(school_mask: u32) -> SpellSchool {
+    //The lowest school in the mask decides the color of the combat log line
+    SpellSchool::try_from(school_mask.trailing_zeros().min(6) as u8).unwrap_or(SpellSchool::Normal)
+}
+
+fn get_time_ms() -> u32 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u32
+}
+
+async fn check_cast(
+    caster_guid: Guid,
+    spell: &SpellInfo,
+    targets: &SpellCastTargets,
+    character_manager: &CharacterManager,
+    world: &World,
+) -> Result<std::result::Result<(), CastFailure>> {
+    let caster = character_manager.get_character(caster_guid)?;
+    if !caster.knows_spell(spell.id) {
+        return Ok(Err(CastFailure::NotKnown));
+    }
+    if caster.is_casting() {
+        return Ok(Err(CastFailure::SpellInProgress));
+    }
+
+    let target = match get_unit_target(targets).filter(|_| spell.requires_target()) {
+        Some(target_guid) => {
+            let Some(victim) = damage::find_victim(caster, target_guid, character_manager, world) else {
+                return Ok(Err(CastFailure::BadTargets));
+            };
+            if spell.is_harmful() && !damage::can_attack(caster, target_guid, &victim, character_manager).await? {
+                return Ok(Err(CastFailure::BadTargets));
+            }
+            let position = damage::get_victim_position(target_guid, &victim, character_manager).await?;
+            Some(TargetConditions {
+                distance: damage::distance(caster.movement_info.position, position),
+                is_dead: !damage::is_victim_alive(target_guid, &victim, character_manager).await?,
+                //There is no line of sight data yet
+                in_line_of_sight: true,
+            })
+        }
+        None => None,
+    };
+
+    let cost = spell.get_power_cost(caster.gameplay_data.unit_base_mana().unwrap_or(0));
+    let conditions = CastConditions {
+        caster_dead: !caster.is_alive(),
+        caster_moving: caster.is_moving(),
+        has_cast_time: spell.cast_time > 0.0 || spell.channeled,
+        on_cooldown: caster.is_spell_on_cooldown(spell.id),
+        school_locked: caster.is_spell_school_locked(spell.school_mask),
+        required_forms: spell.required_forms,
+        //Characters can't shapeshift yet
+        current_form: 0,
+        required_spell_focus: spell.spell_focus.map(|focus_id| {
+            let in_range = world.get_interactive_objects().is_spell_focus_in_range(
+                focus_id,
+                caster.map.as_int(),
+                caster.movement_info.position,
+                caster.get_character_phase_mask(),
+            );
+            (focus_id, in_range)
+        }),
+        requires_target: spell.requires_target(),
+        target,
+        targets_dead_allowed: false,
+        min_range: spell.min_range,
+        max_range: spell.max_range,
+        power: caster.check_power_cost(&cost).err(),
+    };
+    Ok(cast_validation::validate_cast(&conditions, CastSource::Spell))
+}
+
+pub async fn cast_spell(
+    caster_guid: Guid,
+    spell_id: u32,
+    cast_count: u8,
+    targets: SpellCastTargets,
+    data_storage: &DataStorage,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+) -> Result<()> {
+    let Some(spell) = SpellInfo::load(data_storage, spell_id)? else {
+        let caster = character_manager.get_character(caster_guid)?;
+        return handlers::send_cast_failed(caster, cast_count, spell_id, CastFailure::NotKnown).await;
+    };
+
+    if let Err(failure) = check_cast(caster_guid, &spell, &targets, character_manager, world).await? {
+        let caster = character_manager.get_character(caster_guid)?;
+        return handlers::send_cast_failed(caster, cast_count, spell_id, failure).await;
+    }
+
+    //Casting brings the caster out of hiding, except for the spells that put it there
+    if !spell.grants_stealth() {
+        auras::break_stealth(caster_guid, StealthBreakReason::SpellCast, character_manager, world).await?;
+    }
+
+    let caster = character_manager.get_character(caster_guid)?;
+    ServerEvent::SpellStart(SMSG_SPELL_START {
+        cast_item: Guid::zero(),
+        caster: caster_guid,
+        cast_count,
+        spell: spell_id,
+        flags: SMSG_SPELL_START_CastFlags::empty(),
+        timer: (spell.cast_time * 1000.0) as u32,
+        targets: targets.clone(),
+    })
+    .send_to_all_in_range(caster, character_manager, true, world)
+    .await?;
+
+    if spell.cast_time > 0.0 {
+        let caster = character_manager.get_character_mut(caster_guid)?;
+        caster.start_cast(spell_id, cast_count, targets, spell.school_mask, spell.cast_time, spell.channeled);
+        return Ok(());
+    }
+
+    let cast = CompletedCast {
+        spell_id,
+        cast_count,
+        targets,
+    };
+    finish_cast(caster_guid, cast, data_storage, character_manager, world).await
+}
+
+//Called right away for instant spells and by the client tick once a cast time ran out
+pub async fn finish_cast(
+    caster_guid: Guid,
+    cast: CompletedCast,
+    data_storage: &DataStorage,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+) -> Result<()> {
+    let Some(spell) = SpellInfo::load(data_storage, cast.spell_id)? else {
+        return Ok(());
+    };
+
+    //The target may have died or run off while the cast bar was filling
+    let caster = character_manager.get_character(caster_guid)?;
+    let target_guid = match spell.requires_target() {
+        true => get_unit_target(&cast.targets),
+        false => None,
+    };
+    let target = target_guid.and_then(|guid| damage::find_victim(caster, guid, character_manager, world).map(|victim| (guid, victim)));
+    if spell.requires_target() {
+        let target_alive = match &target {
+            Some((guid, victim)) => damage::is_victim_alive(*guid, victim, character_manager).await?,
+            None => false,
+        };
+        if !target_alive {
+            return handlers::send_spell_failure(caster, character_manager, world, cast.cast_count, cast.spell_id, CastFailure::BadTargets).await;
+        }
+    }
+
+    let cost = spell.get_power_cost(caster.gameplay_data.unit_base_mana().unwrap_or(0));
+    if caster.check_power_cost(&cost).is_err() {
+        return handlers::send_spell_failure(caster, character_manager, world, cast.cast_count, cast.spell_id, CastFailure::NoPower).await;
+    }
+    let caster = character_manager.get_character_mut(caster_guid)?;
+    caster.spend_power_cost(&cost).await?;
+    caster.start_spell_cooldown(spell.id, spell.recovery_time, spell.global_cooldown);
+
+    let caster = character_manager.get_character(caster_guid)?;
+    //Only harmful spells on someone else can miss, the miss shows up in the combat log instead of the hits
+    let missed = match target_guid {
+        Some(guid) if guid != caster_guid && spell.is_harmful() => {
+            random::with_rng(|rng| rng.gen_range(0.0..100.0)) < caster.get_spell_miss_chance(data_storage)
+        }
+        _ => false,
+    };
+    ServerEvent::SpellGo(SMSG_SPELL_GO {
+        cast_item: Guid::zero(),
+        caster: caster_guid,
+        extra_casts: cast.cast_count,
+        spell: cast.spell_id,
+        flags: SMSG_SPELL_GO_GameobjectCastFlags::empty(),
+        timestamp: get_time_ms(),
+        hits: match missed {
+            true => vec![],
+            false => vec![target_guid.unwrap_or(caster_guid)],
+        },
+        misses: vec![],
+        targets: cast.targets.clone(),
+    })
+    .send_to_all_in_range(caster, character_manager, true, world)
+    .await?;
+    if let (true, Some(guid)) = (missed, target_guid) {
+        combat_log::log_spell_miss(caster, character_manager, world, caster_guid, spell.id, &[(guid, SpellMissInfo::Miss)]).await?;
+    }
+
+    for effect in &spell.effects {
+        //Effects on the caster itself still happen when the target was missed
+        if missed && effect.targets_selection {
+            continue;
+        }
+        let (effect_target_guid, effect_target) = match (&target, effect.targets_selection) {
+            (Some((guid, victim)), true) => (*guid, victim),
+            _ => (caster_guid, &Victim::Character),
+        };
+        //The target can die to an earlier effect of the same spell
+        if !damage::is_victim_alive(effect_target_guid, effect_target, character_manager).await? {
+            continue;
+        }
+        let effect_target = EffectTarget {
+            guid: effect_target_guid,
+            victim: effect_target,
+            item: get_item_target(&cast.targets),
+            destination: get_destination(&cast.targets),
+        };
+        apply_effect(caster_guid, &spell, effect, &effect_target, data_storage, character_manager, world).await?;
+    }
+    Ok(())
+}
+
+async fn apply_effect(
+    caster_guid: Guid,
+    spell: &SpellInfo,
+    effect: &SpellEffect,
+    target: &EffectTarget<'_>,
+    data_storage: &DataStorage,
+    character_manager: &mut CharacterManager,
+    world: &mut World,
+) -> Result<()> {
+    match effect.kind {
+        SpellEffectKind::SchoolDamage => {
+            let entry = DamageLogEntry {
+                attacker: caster_guid,
+                target: target.guid,
+                spell_id: Some(spell.id),
+                school: get_spell_school(spell.school_mask),
+                damage: effect.roll_amount(),
+                overkill: 0,
+                absorbed: 0,
+                resisted: 0,
+                blocked: 0,
+                critical: false,
+            };
+            damage::deal_damage(entry, target.victim, false, character_manager, world).await?;
+        }
+        SpellEffectKind::Heal => {
+            let entry = HealLogEntry {
+                caster: caster_guid,
+                target: target.guid,
+                spell_id: spell.id,
+                amount: effect.roll_amount(),
+                overheal: 0,
+                absorbed: 0,
+                critical: false,
+            };
+            damage::heal(entry, target.victim, false, character_manager, world).await?;
+        }
+        SpellEffectKind::ApplyAura { aura } => {
+            //Creatures don't carry auras yet
+            if let Victim::Character = target.victim {
+                auras::apply_aura(caster_guid, target.guid, spell, effect, aura, character_manager, world).await?;
+            }
+        }
+        SpellEffectKind::Dispel { .. } => {
+            //Creatures don't carry auras yet
+            if let Victim::Character = target.victim {
+                auras::dispel_auras(caster_guid, target.guid, spell, effect, data_storage, character_manager, world).await?;
+            }
+        }
+        SpellEffectKind::SummonPet { entry } => handlers::summon_pet(caster_guid, entry, character_manager, world).await?,
+        SpellEffectKind::DismissPet => {
+            let realm_db = world.get_realm_database();
+            character_manager.get_character_mut(caster_guid)?.dismiss_pet(&realm_db).await?;
+        }
+        SpellEffectKind::FeedPet => match target.item {
+            Some(food) => handlers::feed_pet(caster_guid, food, character_manager, world).await?,
+            None => trace!("Spell {} feeds the pet but wasn't cast on an item", spell.id),
+        },
+        SpellEffectKind::AddComboPoints => {
+            let points = effect.roll_amount().min(u8::MAX as u32) as u8;
+            character_manager
+                .get_character_mut(caster_guid)?
+                .add_combo_points(target.guid, points)
+                .await?;
+        }
+        SpellEffectKind::AddFarSight => match target.destination {
+            Some(position) => {
+                let target = FarSightTarget::DynamicObject {
+                    guid: get_far_sight_object_guid(caster_guid),
+                    position,
+                };
+                character_manager.get_character_mut(caster_guid)?.start_far_sight(target, spell.duration);
+            }
+            None => trace!("Spell {} moves the camera but wasn't cast on a destination", spell.id),
+        },
+        SpellEffectKind::LearnPetSpell { spell: pet_spell } => handlers::teach_pet_spell(caster_guid, pet_spell, character_manager)?,
+        SpellEffectKind::Charge => {
+            //Roots keep the caster where it is, the cast still goes off
+            if !character_manager.get_character(caster_guid)?.is_rooted() {
+                let destination = damage::get_victim_position(target.guid, target.victim, character_manager).await?;
+                let msg = character_manager.get_character_mut(caster_guid)?.charge_to(destination);
+                send_forced_move(caster_guid, msg, character_manager, world).await?;
+            }
+        }
+        SpellEffectKind::JumpDest { vertical_speed } => match target.destination {
+            Some(destination) if !character_manager.get_character(caster_guid)?.is_rooted() => {
+                let msg = character_manager
+                    .get_character_mut(caster_guid)?
+                    .jump_to(destination, 0.0, vertical_speed);
+                send_forced_move(caster_guid, msg, character_manager, world).await?;
+            }
+            _ => {}
+        },
+        SpellEffectKind::KnockBack { horizontal_speed } => {
+            //Creatures don't move yet
+            if let Victim::Character = target.victim {
+                let origin = character_manager.get_character(caster_guid)?.movement_info.position;
+                let vertical_speed = effect.roll_amount() as f32 / 10.0;
+                character_manager
+                    .get_character_mut(target.guid)?
+                    .knock_back(Vector2d { x: origin.x, y: origin.y }, horizontal_speed, vertical_speed)
+                    .await?;
+            }
+        }
+        SpellEffectKind::Unsupported(effect) => trace!("Spell {} has effect {} which isn't supported yet", spell.id, effect),
+    }
+    Ok(())
+}
+
+async fn send_forced_move(guid: Guid, msg: SMSG_MONSTER_MOVE, character_manager: &CharacterManager, world: &World) -> Result<()> {
+    let character = character_manager.get_character(guid)?;
+    ServerEvent::MonsterMove(msg)
+        .send_to_all_in_range(character, character_manager, true, world)
+        .await
+}
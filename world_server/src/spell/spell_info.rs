@@ -0,0 +1,282 @@
+//! The parts of a Spell.dbc row the cast pipeline works with, with the cast time, range and duration
+//! indices already looked up in their own tables.
+
+use rand::Rng;
+use wow_dbc::Indexable;
+use wow_world_messages::wrath::Power;
+
+use crate::character::character_power::{PowerCost, RuneCost};
+use crate::data::DataStorage;
+use crate::prelude::*;
+use crate::random;
+
+//Effect ids from Spell.dbc's effect column
+const EFFECT_SCHOOL_DAMAGE: i32 = 2;
+const EFFECT_APPLY_AURA: i32 = 6;
+const EFFECT_HEAL: i32 = 10;
+const EFFECT_DISPEL: i32 = 38;
+const EFFECT_SUMMON_PET: i32 = 56;
+const EFFECT_ADD_COMBO_POINTS: i32 = 80;
+const EFFECT_ADD_FARSIGHT: i32 = 72;
+const EFFECT_CHARGE: i32 = 96;
+const EFFECT_KNOCK_BACK: i32 = 98;
+const EFFECT_JUMP_DEST: i32 = 145;
+const EFFECT_LEARN_PET_SPELL: i32 = 57;
+const EFFECT_FEED_PET: i32 = 101;
+const EFFECT_DISMISS_PET: i32 = 102;
+
+//Aura types from Spell.dbc's effect_aura column
+pub const AURA_BIND_SIGHT: u32 = 2;
+pub const AURA_PERIODIC_DAMAGE: u32 = 3;
+pub const AURA_PERIODIC_HEAL: u32 = 8;
+pub const AURA_MOD_STEALTH: u32 = 16;
+pub const AURA_MOD_STEALTH_DETECT: u32 = 17;
+pub const AURA_MOD_INVISIBILITY: u32 = 18;
+pub const AURA_MOD_INVISIBILITY_DETECT: u32 = 19;
+pub const AURA_MOD_ROOT: u32 = 26;
+pub const AURA_WATER_WALK: u32 = 104;
+pub const AURA_FEATHER_FALL: u32 = 105;
+pub const AURA_HOVER: u32 = 106;
+pub const AURA_FLY: u32 = 201;
+
+//Implicit targets that point at the unit the caster selected, anything else is cast on the caster
+const TARGET_UNIT_TARGET_ENEMY: i32 = 6;
+const TARGET_UNIT_TARGET_ALLY: i32 = 21;
+const TARGET_UNIT_TARGET_ANY: i32 = 25;
+
+//Attribute bits in attributes_ex that mark a spell as channeled
+const ATTRIBUTES_EX_CHANNELED: i32 = 0x4 | 0x40;
+//Finishing moves have either of these set
+const ATTRIBUTES_EX_REQUIRES_COMBO_POINTS: i32 = 0x0010_0000 | 0x0040_0000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SpellEffectKind {
+    SchoolDamage,
+    Heal,
+    ApplyAura { aura: u32 },
+    //Takes auras of the dispel type (magic, curse, disease, poison) off the target
+    Dispel { dispel_type: u32 },
+    //Entry 0 calls the pet the caster already has, warlock demons name the creature to summon
+    SummonPet { entry: u32 },
+    LearnPetSpell { spell: u32 },
+    FeedPet,
+    DismissPet,
+    //Combo moves award points on their target
+    AddComboPoints,
+    //Moves the caster's camera to the spot it picked on the ground
+    AddFarSight,
+    //The caster runs up to the target
+    Charge,
+    //Speeds are in yards per second, the vertical one is rolled from the effect's points
+    KnockBack { horizontal_speed: f32 },
+    //Leaps to the spot the caster picked on the ground
+    JumpDest { vertical_speed: f32 },
+    //Everything the pipeline doesn't handle yet, casting still works but the effect does nothing
+    Unsupported(i32),
+}
+
+impl SpellEffectKind {
+    fn from_dbc(effect: i32, aura: i32, misc_value: i32, trigger_spell: i32) -> Option<Self> {
+        match effect {
+            0 => None,
+            EFFECT_SCHOOL_DAMAGE => Some(SpellEffectKind::SchoolDamage),
+            EFFECT_HEAL => Some(SpellEffectKind::Heal),
+            EFFECT_APPLY_AURA => Some(SpellEffectKind::ApplyAura { aura: aura as u32 }),
+            EFFECT_DISPEL => Some(SpellEffectKind::Dispel {
+                dispel_type: misc_value as u32,
+            }),
+            EFFECT_SUMMON_PET => Some(SpellEffectKind::SummonPet { entry: misc_value as u32 }),
+            EFFECT_LEARN_PET_SPELL => Some(SpellEffectKind::LearnPetSpell { spell: trigger_spell as u32 }),
+            EFFECT_FEED_PET => Some(SpellEffectKind::FeedPet),
+            EFFECT_DISMISS_PET => Some(SpellEffectKind::DismissPet),
+            EFFECT_ADD_COMBO_POINTS => Some(SpellEffectKind::AddComboPoints),
+            EFFECT_ADD_FARSIGHT => Some(SpellEffectKind::AddFarSight),
+            EFFECT_CHARGE => Some(SpellEffectKind::Charge),
+            //Spell.dbc has the speeds in tenths of a yard
+            EFFECT_KNOCK_BACK => Some(SpellEffectKind::KnockBack {
+                horizontal_speed: misc_value as f32 / 10.0,
+            }),
+            EFFECT_JUMP_DEST => Some(SpellEffectKind::JumpDest {
+                vertical_speed: misc_value as f32 / 10.0,
+            }),
+            other => Some(SpellEffectKind::Unsupported(other)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SpellEffect {
+    pub kind: SpellEffectKind,
+    //Spell.dbc stores one less than the smallest value, the die adds 1 to die_sides on top
+    pub base_points: i32,
+    pub die_sides: i32,
+    pub targets_selection: bool,
+    //Seconds between ticks of periodic auras
+    pub aura_period: f32,
+}
+
+impl SpellEffect {
+    pub fn roll_amount(&self) -> u32 {
+        let roll = match self.die_sides {
+            sides if sides > 0 => random::with_rng(|rng| rng.gen_range(1..=sides)),
+            _ => 1,
+        };
+        (self.base_points + roll).max(0) as u32
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SpellInfo {
+    pub id: u32,
+    pub school_mask: u32,
+    //Which dispels can take the spell's auras off, 0 for none
+    pub dispel_type: u32,
+    pub cast_time: f32,
+    pub channeled: bool,
+    //Aura duration in seconds, None for auras that last until cancelled
+    pub duration: Option<f32>,
+    pub min_range: f32,
+    pub max_range: f32,
+    pub power_type: Power,
+    pub power_cost: i32,
+    //Percentage of the caster's base mana, used by most spells from level 60 on
+    pub power_cost_percent: i32,
+    pub rune_cost: RuneCost,
+    pub requires_combo_points: bool,
+    pub recovery_time: f32,
+    pub global_cooldown: f32,
+    pub required_forms: u32,
+    //Spells like smelting need a matching gameobject (a forge) close by
+    pub spell_focus: Option<u32>,
+    pub effects: Vec<SpellEffect>,
+}
+
+impl SpellInfo {
+    pub fn load(data_storage: &DataStorage, spell_id: u32) -> Result<Option<Self>> {
+        let Some(row) = data_storage.get_dbc_spell()?.get(spell_id) else {
+            return Ok(None);
+        };
+
+        let cast_time = data_storage
+            .get_dbc_spell_cast_times()?
+            .get(row.casting_time_index.id)
+            .map_or(0, |cast_time| cast_time.base.max(0));
+        let (min_range, max_range) = data_storage
+            .get_dbc_spell_range()?
+            .get(row.range_index.id)
+            .map_or((0.0, 0.0), |range| (range.range_min[0], range.range_max[0]));
+        let duration = data_storage
+            .get_dbc_spell_duration()?
+            .get(row.duration_index.id)
+            .map(|duration| duration.duration)
+            .filter(|&duration| duration > 0)
+            .map(|duration| duration as f32 / 1000.0);
+        let rune_cost = data_storage
+            .get_dbc_spell_rune_cost()?
+            .get(row.rune_cost_id.id)
+            .map_or(RuneCost::default(), |cost| RuneCost {
+                blood: cost.blood.max(0) as u8,
+                unholy: cost.unholy.max(0) as u8,
+                frost: cost.frost.max(0) as u8,
+            });
+
+        let effects = (0..3)
+            .filter_map(|i| {
+                let kind = SpellEffectKind::from_dbc(row.effect[i], row.effect_aura[i], row.effect_misc_value[i], row.effect_trigger_spell[i])?;
+                let implicit_target = row.implicit_target_a[i];
+                Some(SpellEffect {
+                    kind,
+                    base_points: row.effect_base_points[i],
+                    die_sides: row.effect_die_sides[i],
+                    aura_period: row.effect_aura_period[i].max(0) as f32 / 1000.0,
+                    targets_selection: matches!(
+                        implicit_target,
+                        TARGET_UNIT_TARGET_ENEMY | TARGET_UNIT_TARGET_ALLY | TARGET_UNIT_TARGET_ANY
+                    ),
+                })
+            })
+            .collect();
+
+        Ok(Some(SpellInfo {
+            id: spell_id,
+            school_mask: row.school_mask as u32,
+            dispel_type: row.dispel_type.id as u32,
+            cast_time: cast_time as f32 / 1000.0,
+            channeled: row.attributes_ex & ATTRIBUTES_EX_CHANNELED != 0,
+            duration,
+            min_range,
+            max_range,
+            power_type: Power::try_from(row.power_type as u8).unwrap_or(Power::Mana),
+            power_cost: row.mana_cost,
+            power_cost_percent: row.mana_cost_pct,
+            rune_cost,
+            requires_combo_points: row.attributes_ex & ATTRIBUTES_EX_REQUIRES_COMBO_POINTS != 0,
+            recovery_time: row.recovery_time.max(0) as f32 / 1000.0,
+            global_cooldown: row.start_recovery_time.max(0) as f32 / 1000.0,
+            required_forms: row.shapeshift_mask[0] as u32,
+            spell_focus: Some(row.requires_spell_focus.id as u32).filter(|&focus| focus != 0),
+            effects,
+        }))
+    }
+
+    //Spells that hit someone else need that someone selected, self buffs don't
+    pub fn requires_target(&self) -> bool {
+        self.effects.iter().any(|effect| effect.targets_selection)
+    }
+
+    pub fn is_harmful(&self) -> bool {
+        self.effects.iter().any(|effect| {
+            matches!(
+                effect.kind,
+                SpellEffectKind::SchoolDamage | SpellEffectKind::ApplyAura { aura: AURA_PERIODIC_DAMAGE }
+            )
+        })
+    }
+
+    pub fn grants_stealth(&self) -> bool {
+        self.effects.iter().any(|effect| {
+            matches!(
+                effect.kind,
+                SpellEffectKind::ApplyAura { aura: AURA_MOD_STEALTH } | SpellEffectKind::ApplyAura { aura: AURA_MOD_INVISIBILITY }
+            )
+        })
+    }
+
+    //Only the flat cost and the base mana percentage, there are no cost modifiers yet
+    pub fn get_power_cost(&self, base_mana: i32) -> PowerCost {
+        let percent_cost = match self.power_type {
+            Power::Mana => base_mana * self.power_cost_percent / 100,
+            _ => 0,
+        };
+        PowerCost {
+            power: self.power_type,
+            amount: self.power_cost + percent_cost,
+            runes: self.rune_cost,
+            requires_combo_points: self.requires_combo_points,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effect_amount_stays_within_the_die() {
+        let effect = SpellEffect {
+            kind: SpellEffectKind::from_dbc(EFFECT_SCHOOL_DAMAGE, 0, 0, 0).unwrap(),
+            base_points: 13,
+            die_sides: 7,
+            targets_selection: true,
+            aura_period: 0.0,
+        };
+        assert_eq!(effect.kind, SpellEffectKind::SchoolDamage);
+        for _ in 0..100 {
+            assert!((14..=20).contains(&effect.roll_amount()));
+        }
+
+        let fixed = SpellEffect { die_sides: 0, ..effect };
+        assert_eq!(fixed.roll_amount(), 14);
+        assert_eq!(SpellEffectKind::from_dbc(0, 0, 0, 0), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! Data shared by every character of an account: vanity pets, mounts and account wide achievements.
+//!
+//! An account's collection is loaded when one of its characters enters the world and dropped again when it
+//! leaves, so only accounts that are playing take up memory. Characters see the collection on login, e.g.
+//! pets and mounts show up in their spellbook no matter which character learned them.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use wrath_realm_db::account_collection::DBAccountCollectionEntry;
+use wrath_realm_db::RealmDatabase;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CollectionKind {
+    VanityPet = 0,
+    Mount = 1,
+    Achievement = 2,
+}
+
+impl CollectionKind {
+    fn from_db(kind: u8) -> Option<Self> {
+        match kind {
+            0 => Some(Self::VanityPet),
+            1 => Some(Self::Mount),
+            2 => Some(Self::Achievement),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AccountCollection {
+    entries: HashMap<CollectionKind, BTreeSet<u32>>,
+}
+
+impl AccountCollection {
+    fn from_rows(account_id: u32, rows: Vec<DBAccountCollectionEntry>) -> Self {
+        let mut collection = Self::default();
+        for row in rows {
+            match CollectionKind::from_db(row.kind) {
+                Some(kind) => {
+                    collection.insert(kind, row.entry);
+                }
+                None => warn!("Account {} has collection entry {} of unknown kind {}", account_id, row.entry, row.kind),
+            }
+        }
+        collection
+    }
+
+    fn insert(&mut self, kind: CollectionKind, entry: u32) -> bool {
+        self.entries.entry(kind).or_default().insert(entry)
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, kind: CollectionKind, entry: u32) -> bool {
+        self.entries.get(&kind).is_some_and(|entries| entries.contains(&entry))
+    }
+
+    pub fn entries(&self, kind: CollectionKind) -> impl Iterator<Item = u32> + '_ {
+        self.entries.get(&kind).into_iter().flatten().copied()
+    }
+
+    //Summon spells of the pets and mounts, every character of the account knows them
+    pub fn known_spells(&self) -> impl Iterator<Item = u32> + '_ {
+        self.entries(CollectionKind::VanityPet).chain(self.entries(CollectionKind::Mount))
+    }
+}
+
+pub struct AccountDataService {
+    realm_db: Arc<RealmDatabase>,
+    collections: HashMap<u32, AccountCollection>,
+}
+
+impl AccountDataService {
+    pub fn new(realm_db: Arc<RealmDatabase>) -> Self {
+        Self {
+            realm_db,
+            collections: HashMap::new(),
+        }
+    }
+
+    //Called before a character of the account is loaded, so it can consult the collection
+    pub async fn load_account(&mut self, account_id: u32) -> Result<()> {
+        let rows = self.realm_db.get_account_collection(account_id).await?;
+        self.collections.insert(account_id, AccountCollection::from_rows(account_id, rows));
+        Ok(())
+    }
+
+    pub fn unload_account(&mut self, account_id: u32) {
+        self.collections.remove(&account_id);
+    }
+
+    pub fn get_collection(&self, account_id: u32) -> Option<&AccountCollection> {
+        self.collections.get(&account_id)
+    }
+
+    //Adds to the collection of an online account, entries it already has are left alone
+    #[allow(dead_code)]
+    pub async fn add_to_collection(&mut self, account_id: u32, kind: CollectionKind, entry: u32) -> Result<bool> {
+        let collection = self
+            .collections
+            .get_mut(&account_id)
+            .ok_or_else(|| anyhow!("Collection of account {} is not loaded", account_id))?;
+        if !collection.insert(kind, entry) {
+            return Ok(false);
+        }
+
+        let added_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        self.realm_db
+            .add_account_collection_entry(account_id, kind as u8, entry, added_at)
+            .await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pets_and_mounts_are_known_spells() {
+        let rows = vec![
+            DBAccountCollectionEntry { kind: 0, entry: 10673 },
+            DBAccountCollectionEntry { kind: 1, entry: 458 },
+            DBAccountCollectionEntry { kind: 2, entry: 1017 },
+            DBAccountCollectionEntry { kind: 9, entry: 1 },
+        ];
+        let collection = AccountCollection::from_rows(1, rows);
+
+        assert!(collection.contains(CollectionKind::Achievement, 1017));
+        assert_eq!(collection.known_spells().collect::<Vec<_>>(), [10673, 458]);
+    }
+}
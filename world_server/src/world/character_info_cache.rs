@@ -0,0 +1,218 @@
+//! Name, race, class, level and online state of every character on the realm, whether it's loaded or not.
+//!
+//! Clients ask for the name of every guid they see, in chat, in the guild roster, on mail and so on,
+//! mostly for characters that aren't online, and whispers, mail and invites address characters by name.
+//! The cache is filled from the realm database in one query when the world loads and kept up to date on
+//! character creation, deletion, login and logout, so none of that needs a database round trip or the
+//! character to be in the `CharacterManager`. Guids the database doesn't know are remembered for a while
+//! as well, so a client asking for a bogus guid over and over doesn't reach the database every time.
+//! Anything that changes a name, race or gender (renames, race changes) must call `invalidate`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use wow_world_messages::wrath::{Class, Gender, Race};
+use wrath_realm_db::character::DBCharacterInfo;
+use wrath_realm_db::RealmDatabase;
+
+use crate::character::Character;
+use crate::prelude::*;
+
+const MISSING_GUID_LIFETIME: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CharacterInfo {
+    pub guid: Guid,
+    pub name: String,
+    pub race: Race,
+    pub class: Class,
+    pub gender: Gender,
+    pub level: u8,
+    pub online: bool,
+}
+
+impl CharacterInfo {
+    pub fn of_character(character: &Character) -> Self {
+        Self {
+            guid: character.get_guid(),
+            name: character.name.clone(),
+            race: character.get_race(),
+            class: character.get_class(),
+            gender: character.get_gender(),
+            level: character.gameplay_data.unit_level().unwrap_or(1) as u8,
+            online: true,
+        }
+    }
+
+    //The database doesn't know who is online, characters are marked online when they enter the world
+    fn from_db(db_info: DBCharacterInfo) -> Self {
+        Self {
+            guid: Guid::new(db_info.id as u64),
+            name: db_info.name,
+            race: Race::try_from(db_info.race).unwrap_or(Race::Human),
+            class: Class::try_from(db_info.class).unwrap_or(Class::Warrior),
+            gender: Gender::try_from(db_info.gender).unwrap_or(Gender::Male),
+            level: db_info.level,
+            online: false,
+        }
+    }
+}
+
+enum Entry {
+    Known(CharacterInfo),
+    Missing { since: Instant },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CharacterLookup<'a> {
+    Known(&'a CharacterInfo),
+    //The database was asked recently and doesn't know this guid
+    Missing,
+    NotCached,
+}
+
+#[derive(Default)]
+pub struct CharacterInfoCache {
+    entries: HashMap<Guid, Entry>,
+    //Lowercased names, character names are unique on a realm regardless of case
+    guids_by_name: HashMap<String, Guid>,
+}
+
+impl CharacterInfoCache {
+    pub async fn load(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        let infos = realm_db.get_all_character_infos().await?;
+        info!("Loaded info of {} characters", infos.len());
+        self.entries.clear();
+        self.guids_by_name.clear();
+        for db_info in infos {
+            self.insert(CharacterInfo::from_db(db_info));
+        }
+        Ok(())
+    }
+
+    pub fn lookup(&self, guid: Guid, now: Instant) -> CharacterLookup<'_> {
+        match self.entries.get(&guid) {
+            Some(Entry::Known(info)) => CharacterLookup::Known(info),
+            Some(Entry::Missing { since }) if now.duration_since(*since) < MISSING_GUID_LIFETIME => CharacterLookup::Missing,
+            _ => CharacterLookup::NotCached,
+        }
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&CharacterInfo> {
+        let guid = self.guids_by_name.get(&name.trim().to_lowercase())?;
+        match self.entries.get(guid) {
+            Some(Entry::Known(info)) => Some(info),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&mut self, info: CharacterInfo) {
+        self.invalidate(info.guid);
+        self.guids_by_name.insert(info.name.to_lowercase(), info.guid);
+        self.entries.insert(info.guid, Entry::Known(info));
+    }
+
+    pub fn insert_missing(&mut self, guid: Guid, now: Instant) {
+        self.invalidate(guid);
+        self.entries.insert(guid, Entry::Missing { since: now });
+    }
+
+    pub fn invalidate(&mut self, guid: Guid) {
+        if let Some(Entry::Known(info)) = self.entries.remove(&guid) {
+            self.guids_by_name.remove(&info.name.to_lowercase());
+        }
+    }
+
+    pub fn set_online(&mut self, guid: Guid, online: bool) {
+        if let Some(Entry::Known(info)) = self.entries.get_mut(&guid) {
+            info.online = online;
+        }
+    }
+
+    pub fn set_level(&mut self, guid: Guid, level: u8) {
+        if let Some(Entry::Known(info)) = self.entries.get_mut(&guid) {
+            info.level = level;
+        }
+    }
+
+    //Answers from the cache, going to the database only for guids that aren't cached yet
+    pub async fn resolve(&mut self, guid: Guid, realm_db: &RealmDatabase) -> Result<Option<&CharacterInfo>> {
+        let now = Instant::now();
+        if self.lookup(guid, now) == CharacterLookup::NotCached {
+            match realm_db.get_character_info(guid.guid() as u32).await? {
+                Some(db_info) => self.insert(CharacterInfo::from_db(db_info)),
+                None => self.insert_missing(guid, now),
+            }
+        }
+
+        Ok(match self.lookup(guid, now) {
+            CharacterLookup::Known(info) => Some(info),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn character_info(guid: u64, name: &str) -> CharacterInfo {
+        CharacterInfo {
+            guid: Guid::new(guid),
+            name: name.to_string(),
+            race: Race::Orc,
+            class: Class::Shaman,
+            gender: Gender::Female,
+            level: 1,
+            online: false,
+        }
+    }
+
+    #[test]
+    fn known_characters_stay_until_invalidated() {
+        let mut cache = CharacterInfoCache::default();
+        let guid = Guid::new(7);
+        let now = Instant::now();
+        assert_eq!(cache.lookup(guid, now), CharacterLookup::NotCached);
+
+        cache.insert(character_info(7, "Thrallina"));
+        assert_eq!(
+            cache.lookup(guid, now + Duration::from_secs(3600)),
+            CharacterLookup::Known(&character_info(7, "Thrallina"))
+        );
+
+        //A rename replaces the old name on the next lookup
+        cache.invalidate(guid);
+        assert_eq!(cache.lookup(guid, now), CharacterLookup::NotCached);
+        assert_eq!(cache.find_by_name("Thrallina"), None);
+    }
+
+    #[test]
+    fn missing_guids_are_remembered_for_a_while() {
+        let mut cache = CharacterInfoCache::default();
+        let guid = Guid::new(404);
+        let now = Instant::now();
+        cache.insert_missing(guid, now);
+
+        assert_eq!(cache.lookup(guid, now + Duration::from_secs(1)), CharacterLookup::Missing);
+        assert_eq!(cache.lookup(guid, now + MISSING_GUID_LIFETIME), CharacterLookup::NotCached);
+
+        //A character created with that guid replaces the negative entry
+        cache.insert(character_info(404, "Newbie"));
+        assert_eq!(cache.lookup(guid, now), CharacterLookup::Known(&character_info(404, "Newbie")));
+    }
+
+    #[test]
+    fn offline_characters_are_found_by_name() {
+        let mut cache = CharacterInfoCache::default();
+        cache.insert(character_info(12, "Jaina"));
+        assert_eq!(cache.find_by_name("jAINA ").map(|info| info.online), Some(false));
+
+        cache.set_online(Guid::new(12), true);
+        cache.set_level(Guid::new(12), 80);
+        let info = cache.find_by_name("Jaina").unwrap();
+        assert!(info.online);
+        assert_eq!(info.level, 80);
+        assert_eq!(cache.find_by_name("Arthas"), None);
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::Weak;
+
+use smol::lock::RwLock;
+use wow_world_messages::wrath::{MovementInfo, ObjectType, UpdateMask, UpdateUnit, Vector3d};
+use wrath_game_db::{DBCreatureSpawn, DBCreatureTemplate};
+
+use super::loot::Loot;
+use super::map_manager::MapManager;
+use super::prelude::unit_flags::UnitDynamicFlags;
+use super::prelude::*;
+use crate::character::Character;
+use crate::data::PositionAndOrientation;
+use crate::prelude::*;
+
+//Creatures use the HIGHGUID_UNIT guid layout, with the template entry in the middle and the spawn's guid at the bottom
+const HIGH_GUID_UNIT: u64 = 0xF130;
+
+pub fn creature_guid(entry: u32, spawn_guid: u32) -> Guid {
+    Guid::new((HIGH_GUID_UNIT << 48) | ((entry as u64 & 0xFFFFFF) << 24) | (spawn_guid as u64 & 0xFFFFFF))
+}
+
+pub struct Creature {
+    pub spawn_guid: u32,
+    pub entry: u32,
+    pub gameplay_data: UpdateUnit,
+    pub movement_info: MovementInfo,
+    phase_mask: u32,
+    //Rolled when the creature dies, it goes away with the corpse
+    loot: Option<Loot>,
+}
+
+impl Creature {
+    pub fn spawn(spawn: &DBCreatureSpawn, template: &DBCreatureTemplate) -> Self {
+        let guid = creature_guid(spawn.entry, spawn.guid);
+        let level = template.min_level.max(1);
+        let health = ((template.base_health as f32 * template.health_modifier) as i32).max(1);
+        let display_id = [template.display_id1, template.display_id2, template.display_id3, template.display_id4]
+            .into_iter()
+            .find(|&id| id != 0)
+            .unwrap_or(0) as i32;
+
+        let gameplay_data = UpdateUnit::builder()
+            .set_object_guid(guid)
+            .set_object_entry(spawn.entry as i32)
+            .set_object_scale_x(template.scale)
+            .set_unit_health(health)
+            .set_unit_maxhealth(health)
+            .set_unit_level(level as i32)
+            .set_unit_factiontemplate(template.faction as i32)
+            .set_unit_displayid(display_id)
+            .set_unit_nativedisplayid(display_id)
+            .set_unit_npc_flags(template.npc_flags as i32)
+            .set_unit_flags(template.unit_flags as i32)
+            .finalize();
+
+        let movement_info = MovementInfo {
+            position: Vector3d {
+                x: spawn.position_x,
+                y: spawn.position_y,
+                z: spawn.position_z,
+            },
+            orientation: spawn.orientation,
+            ..Default::default()
+        };
+
+        Self {
+            spawn_guid: spawn.guid,
+            entry: spawn.entry,
+            gameplay_data,
+            movement_info,
+            phase_mask: spawn.phase_mask,
+            loot: None,
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.gameplay_data.unit_health().unwrap_or(0) > 0
+    }
+
+    //The corpse sparkles while there's something left on it
+    pub fn set_loot(&mut self, loot: Option<Loot>) {
+        let flags = self.gameplay_data.unit_dynamic_flags().unwrap_or(0);
+        let flags = match loot {
+            Some(_) => flags | UnitDynamicFlags::Lootable as i32,
+            None => flags & !(UnitDynamicFlags::Lootable as i32),
+        };
+        self.gameplay_data.set_unit_dynamic_flags(flags);
+        self.loot = loot;
+    }
+
+    pub fn get_loot(&self) -> Option<&Loot> {
+        self.loot.as_ref()
+    }
+
+    pub fn get_loot_mut(&mut self) -> Option<&mut Loot> {
+        self.loot.as_mut()
+    }
+
+    //Called once something was taken, an empty corpse stops being lootable
+    pub fn clear_loot_if_empty(&mut self) {
+        if self.loot.as_ref().is_some_and(Loot::is_empty) {
+            self.set_loot(None);
+        }
+    }
+}
+
+//Creatures don't keep track of what's around them, the map decides which characters get to see them
+impl GameObject for Creature {
+    fn get_position(&self) -> Option<PositionAndOrientation> {
+        Some(PositionAndOrientation {
+            position: self.movement_info.position,
+            orientation: self.movement_info.orientation,
+        })
+    }
+
+    fn get_movement_info(&self) -> &MovementInfo {
+        &self.movement_info
+    }
+
+    fn get_update_mask(&self) -> UpdateMask {
+        UpdateMask::Unit(self.gameplay_data.clone())
+    }
+
+    fn clear_update_mask_header(&mut self) {
+        self.gameplay_data.dirty_reset();
+    }
+
+    fn is_in_range(&self, _guid: Guid) -> bool {
+        false
+    }
+
+    fn add_in_range_object(&mut self, _guid: Guid, _object: Weak<RwLock<dyn GameObject>>) -> Result<()> {
+        Ok(())
+    }
+
+    fn add_in_range_character(&mut self, _guid: Guid) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_in_range_guids(&self) -> impl Iterator<Item = Guid> + '_ {
+        std::iter::empty()
+    }
+
+    fn get_in_range_characters(&self) -> &[Guid] {
+        &[]
+    }
+
+    fn remove_in_range_object(&mut self, _guid: Guid) -> Result<()> {
+        Ok(())
+    }
+
+    fn clear_in_range_objects(&mut self) {}
+
+    fn get_recently_removed_range_guids(&self) -> &[Guid] {
+        &[]
+    }
+
+    fn clear_recently_removed_range_guids(&mut self) {}
+
+    fn as_character(&self) -> Option<&Character> {
+        None
+    }
+
+    fn as_update_receiver(&self) -> Option<&dyn ReceiveUpdates> {
+        None
+    }
+
+    fn as_update_receiver_mut(&mut self) -> Option<&mut dyn ReceiveUpdates> {
+        None
+    }
+
+    fn get_guid(&self) -> Guid {
+        self.gameplay_data.object_guid().unwrap()
+    }
+
+    fn get_type(&self) -> ObjectType {
+        ObjectType::Unit
+    }
+
+    fn get_phase_mask(&self) -> u32 {
+        self.phase_mask
+    }
+
+    fn on_pushed_to_map(&mut self, _map_manager: &MapManager) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+//! NPCs placed in the world through the game database's `creature` table.
+//!
+//! `CreatureSpawns` holds every spawn and template and is loaded once at startup. Each map gets its own
+//! `CreatureManager` with the spawns of its map id when it's created, so instances start out with a fresh
+//! set of creatures. Creatures that die leave their corpse around for a while and come back after their spawn's
+//! respawn time.
+//!
+//! Creatures don't fight back yet, a creature is in combat for as long as a character that attacked it is
+//! still alive on the map. Once none are left it evades and heals back up.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use smol::lock::RwLock;
+use wrath_game_db::{DBCreatureSpawn, DBCreatureTemplate, DBRareSpawn, GameDatabase};
+
+use super::creature::{creature_guid, Creature};
+use super::instance_manager::MapID;
+use super::prelude::GameObject;
+use crate::prelude::*;
+
+const CORPSE_DECAY_SECONDS: f32 = 60.0;
+//Rare spawns get spawn guids of their own at the top of the range, so they don't share a guid with a creature table spawn
+const RARE_SPAWN_GUID_BASE: u32 = 0xF00000;
+
+#[derive(Default)]
+pub struct CreatureSpawns {
+    templates: HashMap<u32, DBCreatureTemplate>,
+    spawns: HashMap<u32, DBCreatureSpawn>,
+    spawns_by_map: HashMap<MapID, Vec<u32>>,
+}
+
+impl CreatureSpawns {
+    pub async fn load(game_db: &GameDatabase) -> Result<Self> {
+        let templates: HashMap<u32, DBCreatureTemplate> = game_db
+            .get_all_creature_templates()
+            .await?
+            .into_iter()
+            .map(|template| (template.entry, template))
+            .collect();
+
+        let mut creature_spawns = Self {
+            templates,
+            ..Default::default()
+        };
+        for spawn in game_db.get_all_creature_spawns().await? {
+            if !creature_spawns.templates.contains_key(&spawn.entry) {
+                warn!("Creature spawn {} uses creature entry {} which has no template", spawn.guid, spawn.entry);
+                continue;
+            }
+            creature_spawns.spawns_by_map.entry(spawn.map as MapID).or_default().push(spawn.guid);
+            creature_spawns.spawns.insert(spawn.guid, spawn);
+        }
+
+        info!(
+            "Loaded {} creature spawns on {} maps",
+            creature_spawns.spawns.len(),
+            creature_spawns.spawns_by_map.len()
+        );
+        Ok(creature_spawns)
+    }
+
+    pub fn get_template(&self, entry: u32) -> Option<&DBCreatureTemplate> {
+        self.templates.get(&entry)
+    }
+
+    fn spawn_creature(&self, spawn_guid: u32) -> Option<Creature> {
+        let spawn = self.spawns.get(&spawn_guid)?;
+        let template = self.templates.get(&spawn.entry)?;
+        Some(Creature::spawn(spawn, template))
+    }
+}
+
+//Creatures are shared behind a lock because characters keep a weak reference to what's in their range
+pub type SharedCreature = Arc<RwLock<Creature>>;
+
+//What the map needs to know about a creature without locking it. Creatures don't move yet, so this stays
+//what the spawn says.
+pub struct SpawnedCreature {
+    pub spawn_guid: u32,
+    pub phase_mask: u32,
+    pub x: f32,
+    pub y: f32,
+    pub creature: SharedCreature,
+}
+
+#[derive(Default)]
+pub struct CreatureManager {
+    spawn_data: Arc<CreatureSpawns>,
+    creatures: HashMap<Guid, SpawnedCreature>,
+    //Spawn guids of dead creatures with the seconds until they come back
+    respawns: Vec<(u32, f32)>,
+    //Creatures that died with the seconds until their corpse goes away
+    corpses: Vec<(Guid, f32)>,
+    //The characters that attacked each creature that is in combat
+    attackers: HashMap<Guid, HashSet<Guid>>,
+    //Spawns that stay gone once they die, like bosses that were killed in an instance
+    without_respawn: HashSet<u32>,
+    //Rare spawn ids of the rares that are up, the rare spawn scheduler brings them back instead of the respawn timer
+    rare_spawns: HashMap<Guid, u32>,
+}
+
+impl CreatureManager {
+    pub fn new(map: MapID, spawn_data: Arc<CreatureSpawns>) -> Self {
+        let mut manager = Self {
+            spawn_data,
+            ..Default::default()
+        };
+        let spawn_guids = manager.spawn_data.spawns_by_map.get(&map).cloned().unwrap_or_default();
+        for spawn_guid in spawn_guids {
+            manager.spawn(spawn_guid);
+        }
+        if !manager.creatures.is_empty() {
+            info!("Spawned {} creatures on map {}", manager.creatures.len(), map);
+        }
+        manager
+    }
+
+    fn spawn(&mut self, spawn_guid: u32) -> Option<Guid> {
+        let creature = self.spawn_data.spawn_creature(spawn_guid)?;
+        let guid = creature_guid(creature.entry, spawn_guid);
+        let spawned = SpawnedCreature {
+            spawn_guid,
+            phase_mask: creature.get_phase_mask(),
+            x: creature.movement_info.position.x,
+            y: creature.movement_info.position.y,
+            creature: Arc::new(RwLock::new(creature)),
+        };
+        self.creatures.insert(guid, spawned);
+        Some(guid)
+    }
+
+    pub fn spawn_rare(&mut self, rare_spawn: &DBRareSpawn, template: &DBCreatureTemplate) -> Guid {
+        let spawn = DBCreatureSpawn {
+            guid: RARE_SPAWN_GUID_BASE | rare_spawn.id,
+            entry: rare_spawn.creature_entry,
+            map: rare_spawn.map,
+            position_x: rare_spawn.position_x,
+            position_y: rare_spawn.position_y,
+            position_z: rare_spawn.position_z,
+            orientation: rare_spawn.orientation,
+            phase_mask: 1,
+            spawn_time_seconds: 0,
+        };
+        let creature = Creature::spawn(&spawn, template);
+        let guid = creature_guid(spawn.entry, spawn.guid);
+        let spawned = SpawnedCreature {
+            spawn_guid: spawn.guid,
+            phase_mask: creature.get_phase_mask(),
+            x: spawn.position_x,
+            y: spawn.position_y,
+            creature: Arc::new(RwLock::new(creature)),
+        };
+        self.creatures.insert(guid, spawned);
+        self.without_respawn.insert(spawn.guid);
+        self.rare_spawns.insert(guid, rare_spawn.id);
+        guid
+    }
+
+    pub fn get_rare_spawn_id(&self, guid: Guid) -> Option<u32> {
+        self.rare_spawns.get(&guid).copied()
+    }
+
+    pub fn get(&self, guid: Guid) -> Option<&SpawnedCreature> {
+        self.creatures.get(&guid)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Guid, &SpawnedCreature)> {
+        self.creatures.iter().map(|(&guid, creature)| (guid, creature))
+    }
+
+    pub fn len(&self) -> usize {
+        self.creatures.len()
+    }
+
+    //Counts down the corpse and respawn timers, returns whether any creature came back
+    pub fn tick(&mut self, delta_time: f32) -> bool {
+        for (_, decay_in) in self.corpses.iter_mut() {
+            *decay_in -= delta_time;
+        }
+        if self.respawns.is_empty() {
+            return false;
+        }
+        for (_, respawn_in) in self.respawns.iter_mut() {
+            *respawn_in -= delta_time;
+        }
+
+        let mut any_respawned = false;
+        let mut respawns = std::mem::take(&mut self.respawns);
+        respawns.retain(|&(spawn_guid, respawn_in)| {
+            if respawn_in > 0.0 {
+                return true;
+            }
+            any_respawned |= self.spawn(spawn_guid).is_some();
+            false
+        });
+        self.respawns = respawns;
+        any_respawned
+    }
+
+    //Returns whether the creature just entered combat
+    pub fn add_attacker(&mut self, guid: Guid, attacker: Guid) -> bool {
+        if !self.creatures.contains_key(&guid) {
+            return false;
+        }
+        let attackers = self.attackers.entry(guid).or_default();
+        let entered_combat = attackers.is_empty();
+        attackers.insert(attacker);
+        entered_combat
+    }
+
+    //Forgets attackers that can't fight anymore, returns the creatures that were left with none and evade
+    pub fn take_evading(&mut self, can_fight: impl Fn(Guid) -> bool) -> Vec<Guid> {
+        let mut evading = Vec::new();
+        self.attackers.retain(|&guid, attackers| {
+            attackers.retain(|&attacker| can_fight(attacker));
+            if attackers.is_empty() {
+                evading.push(guid);
+            }
+            !attackers.is_empty()
+        });
+        evading
+    }
+
+    pub fn prevent_respawn(&mut self, guid: Guid) {
+        if let Some(spawned) = self.creatures.get(&guid) {
+            self.without_respawn.insert(spawned.spawn_guid);
+        }
+    }
+
+    pub fn start_corpse_decay(&mut self, guid: Guid) {
+        self.attackers.remove(&guid);
+        if self.creatures.contains_key(&guid) && !self.corpses.iter().any(|&(corpse, _)| corpse == guid) {
+            self.corpses.push((guid, CORPSE_DECAY_SECONDS));
+        }
+    }
+
+    //A corpse that has been lying around long enough to despawn
+    pub fn take_decayed_corpse(&mut self) -> Option<Guid> {
+        let index = self.corpses.iter().position(|&(_, decay_in)| decay_in <= 0.0)?;
+        Some(self.corpses.swap_remove(index).0)
+    }
+
+    //Takes the creature out of the world until its spawn time has passed
+    pub fn despawn(&mut self, guid: Guid) -> Option<SpawnedCreature> {
+        let spawned = self.creatures.remove(&guid)?;
+        self.corpses.retain(|&(corpse, _)| corpse != guid);
+        self.attackers.remove(&guid);
+        self.rare_spawns.remove(&guid);
+        if self.without_respawn.contains(&spawned.spawn_guid) {
+            return Some(spawned);
+        }
+        let respawn_in = self
+            .spawn_data
+            .spawns
+            .get(&spawned.spawn_guid)
+            .map_or(0, |spawn| spawn.spawn_time_seconds);
+        self.respawns.push((spawned.spawn_guid, respawn_in as f32));
+        Some(spawned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_data() -> CreatureSpawns {
+        let template = DBCreatureTemplate {
+            entry: 299,
+            name: "Young Wolf".to_string(),
+            subname: String::new(),
+            icon_name: String::new(),
+            type_flags: 0,
+            creature_type: 1,
+            family: 1,
+            creature_rank: 0,
+            kill_credit1: 0,
+            kill_credit2: 0,
+            display_id1: 903,
+            display_id2: 0,
+            display_id3: 0,
+            display_id4: 0,
+            health_modifier: 1.0,
+            mana_modifier: 1.0,
+            racial_leader: 0,
+            movement_id: 0,
+            min_level: 1,
+            max_level: 2,
+            faction: 32,
+            npc_flags: 0,
+            unit_flags: 0,
+            base_health: 42,
+            scale: 1.0,
+        };
+        let spawn = DBCreatureSpawn {
+            guid: 7,
+            entry: 299,
+            map: 0,
+            position_x: -8900.0,
+            position_y: -120.0,
+            position_z: 82.0,
+            orientation: 0.0,
+            phase_mask: 1,
+            spawn_time_seconds: 30,
+        };
+        CreatureSpawns {
+            templates: HashMap::from([(299, template)]),
+            spawns: HashMap::from([(7, spawn)]),
+            spawns_by_map: HashMap::from([(0, vec![7])]),
+        }
+    }
+
+    #[test]
+    fn creatures_spawn_on_their_map_and_respawn() {
+        let spawn_data = Arc::new(spawn_data());
+        assert_eq!(CreatureManager::new(1, spawn_data.clone()).len(), 0);
+
+        let mut creatures = CreatureManager::new(0, spawn_data);
+        let guid = creature_guid(299, 7);
+        assert!(creatures.get(guid).is_some_and(|spawned| spawned.phase_mask == 1));
+
+        creatures.start_corpse_decay(guid);
+        assert!(!creatures.tick(CORPSE_DECAY_SECONDS));
+        assert_eq!(creatures.take_decayed_corpse(), Some(guid));
+        creatures.despawn(guid).unwrap();
+        assert!(creatures.get(guid).is_none());
+        assert!(!creatures.tick(29.0));
+        assert!(creatures.tick(1.0));
+        assert!(creatures.get(guid).is_some());
+    }
+
+    #[test]
+    fn creatures_evade_once_every_attacker_is_gone() {
+        let mut creatures = CreatureManager::new(0, Arc::new(spawn_data()));
+        let guid = creature_guid(299, 7);
+        let (first, second) = (Guid::new(1), Guid::new(2));
+
+        assert!(creatures.add_attacker(guid, first));
+        assert!(!creatures.add_attacker(guid, second));
+        assert!(creatures.take_evading(|attacker| attacker == second).is_empty());
+        assert_eq!(creatures.take_evading(|_| false), vec![guid]);
+        assert!(creatures.add_attacker(guid, first));
+    }
+
+    #[test]
+    fn rare_spawns_stay_gone_once_killed() {
+        let spawn_data = spawn_data();
+        let rare_spawn = DBRareSpawn {
+            id: 3,
+            creature_entry: 299,
+            map: 0,
+            position_x: -9000.0,
+            position_y: -100.0,
+            position_z: 80.0,
+            orientation: 0.0,
+            respawn_min_seconds: 3600,
+            respawn_max_seconds: 7200,
+            spawn_emote: None,
+        };
+        let mut creatures = CreatureManager::new(1, Arc::new(CreatureSpawns::default()));
+        let guid = creatures.spawn_rare(&rare_spawn, spawn_data.get_template(299).unwrap());
+        assert_eq!(creatures.get_rare_spawn_id(guid), Some(3));
+
+        creatures.despawn(guid).unwrap();
+        assert_eq!(creatures.get_rare_spawn_id(guid), None);
+        assert!(!creatures.tick(7200.0));
+        assert!(creatures.get(guid).is_none());
+    }
+}
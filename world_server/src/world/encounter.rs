@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::character::character_manager::CharacterManager;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use wow_world_messages::wrath::{SMSG_UPDATE_INSTANCE_ENCOUNTER_UNIT_EncounterFrame, SMSG_UPDATE_INSTANCE_ENCOUNTER_UNIT};
+use wrath_game_db::GameDatabase;
+use wrath_realm_db::instance::DBInstance;
+use wrath_realm_db::RealmDatabase;
+
+use super::instance_manager::{InstanceID, MapID};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EncounterState {
+    #[default]
+    NotStarted,
+    InProgress,
+    Done,
+}
+
+//Boss mechanics are implemented by scripts, the encounter only tells them when the fight changes state
+pub trait EncounterScript: Send + Sync {
+    fn on_engage(&self, _encounter: &Encounter) {}
+    fn on_wipe(&self, _encounter: &Encounter) {}
+    fn on_kill(&self, _encounter: &Encounter) {}
+}
+
+//Bosses that only have their game database entry, they fight like any other creature
+struct UnscriptedEncounter;
+
+impl EncounterScript for UnscriptedEncounter {}
+
+pub struct EncounterDefinition {
+    pub id: u32,
+    //The creature entry of the boss, pulling it starts the encounter
+    pub boss_entry: u32,
+    //Bit in the instance's completed encounter mask
+    pub index: u8,
+    pub script: Arc<dyn EncounterScript>,
+}
+
+#[derive(Default)]
+pub struct EncounterScriptRegistry {
+    definitions: HashMap<MapID, Vec<Arc<EncounterDefinition>>>,
+}
+
+impl EncounterScriptRegistry {
+    pub async fn load(&mut self, game_db: &GameDatabase) -> Result<()> {
+        let encounters = game_db.get_all_instance_encounters().await?;
+        info!("Loaded {} instance encounters", encounters.len());
+        for encounter in encounters {
+            self.register(
+                encounter.map,
+                EncounterDefinition {
+                    id: encounter.id,
+                    boss_entry: encounter.boss_entry,
+                    index: encounter.encounter_index,
+                    script: Arc::new(UnscriptedEncounter),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    //A scripted encounter replaces the unscripted one that was loaded for it
+    pub fn register(&mut self, map_id: MapID, definition: EncounterDefinition) {
+        let definitions = self.definitions.entry(map_id).or_default();
+        definitions.retain(|existing| existing.id != definition.id);
+        definitions.push(Arc::new(definition));
+    }
+
+    pub fn create_encounters(&self, instance_id: InstanceID, map_id: MapID, difficulty: u8) -> InstanceEncounters {
+        let encounters = self
+            .definitions
+            .get(&map_id)
+            .into_iter()
+            .flatten()
+            .map(|definition| Encounter {
+                definition: definition.clone(),
+                state: EncounterState::NotStarted,
+                boss_guid: None,
+            })
+            .collect();
+
+        InstanceEncounters {
+            instance_id,
+            map_id,
+            difficulty,
+            encounters,
+        }
+    }
+}
+
+pub struct Encounter {
+    definition: Arc<EncounterDefinition>,
+    state: EncounterState,
+    boss_guid: Option<Guid>,
+}
+
+impl Encounter {
+    pub fn id(&self) -> u32 {
+        self.definition.id
+    }
+
+    pub fn state(&self) -> EncounterState {
+        self.state
+    }
+
+    pub fn boss_guid(&self) -> Option<Guid> {
+        self.boss_guid
+    }
+}
+
+pub struct InstanceEncounters {
+    instance_id: InstanceID,
+    map_id: MapID,
+    difficulty: u8,
+    encounters: Vec<Encounter>,
+}
+
+impl InstanceEncounters {
+    pub fn get_state(&self, encounter_id: u32) -> Option<EncounterState> {
+        self.encounters.iter().find(|e| e.id() == encounter_id).map(|e| e.state)
+    }
+
+    //The encounter the creature is the boss of
+    pub fn find_by_boss_entry(&self, entry: u32) -> Option<&Encounter> {
+        self.encounters.iter().find(|e| e.definition.boss_entry == entry)
+    }
+
+    pub fn completed_encounters_mask(&self) -> u32 {
+        self.encounters
+            .iter()
+            .filter(|e| e.state == EncounterState::Done)
+            .fold(0, |mask, e| mask | (1 << e.definition.index))
+    }
+
+    //Restores kills that were saved before the instance was unloaded
+    pub fn apply_completed_encounters_mask(&mut self, mask: u32) {
+        for encounter in self.encounters.iter_mut() {
+            if mask & (1 << encounter.definition.index) != 0 {
+                encounter.state = EncounterState::Done;
+            }
+        }
+    }
+
+    pub async fn engage(&mut self, encounter_id: u32, boss_guid: Guid, players: &[Guid], character_manager: &CharacterManager) -> Result<()> {
+        let encounter = self.transition(encounter_id, EncounterState::NotStarted, EncounterState::InProgress)?;
+        encounter.boss_guid = Some(boss_guid);
+        encounter.definition.script.on_engage(encounter);
+
+        let frame = SMSG_UPDATE_INSTANCE_ENCOUNTER_UNIT_EncounterFrame::Engage {
+            guid: boss_guid,
+            parameter1: 0,
+        };
+        send_encounter_frame(frame, players, character_manager).await
+    }
+
+    pub async fn wipe(&mut self, encounter_id: u32, players: &[Guid], character_manager: &CharacterManager) -> Result<()> {
+        let encounter = self.transition(encounter_id, EncounterState::InProgress, EncounterState::NotStarted)?;
+        let boss_guid = encounter.boss_guid.take();
+        encounter.definition.script.on_wipe(encounter);
+
+        if let Some(guid) = boss_guid {
+            let frame = SMSG_UPDATE_INSTANCE_ENCOUNTER_UNIT_EncounterFrame::Disengage { guid };
+            send_encounter_frame(frame, players, character_manager).await?;
+        }
+        Ok(())
+    }
+
+    //Kills are what lock players to the instance, everyone present at the time of the kill gets saved
+    pub async fn kill(&mut self, encounter_id: u32, players: &[Guid], character_manager: &CharacterManager, realm_db: &RealmDatabase) -> Result<()> {
+        let encounter = self.transition(encounter_id, EncounterState::InProgress, EncounterState::Done)?;
+        let boss_guid = encounter.boss_guid.take();
+        encounter.definition.script.on_kill(encounter);
+
+        let instance = DBInstance {
+            id: self.instance_id,
+            map_id: self.map_id,
+            difficulty: self.difficulty,
+            completed_encounters: self.completed_encounters_mask(),
+        };
+        let character_ids: Vec<u32> = players.iter().map(|guid| guid.guid() as u32).collect();
+        realm_db.save_instance_encounter_kill(&instance, &character_ids).await?;
+
+        if let Some(guid) = boss_guid {
+            let frame = SMSG_UPDATE_INSTANCE_ENCOUNTER_UNIT_EncounterFrame::Disengage { guid };
+            send_encounter_frame(frame, players, character_manager).await?;
+        }
+        Ok(())
+    }
+
+    fn transition(&mut self, encounter_id: u32, from: EncounterState, to: EncounterState) -> Result<&mut Encounter> {
+        let instance_id = self.instance_id;
+        let encounter = self
+            .encounters
+            .iter_mut()
+            .find(|e| e.id() == encounter_id)
+            .ok_or_else(|| anyhow!("Encounter {} does not exist in instance {}", encounter_id, instance_id))?;
+
+        if encounter.state != from {
+            bail!(
+                "Encounter {} in instance {} can't go from {:?} to {:?}",
+                encounter_id,
+                instance_id,
+                encounter.state,
+                to
+            );
+        }
+        encounter.state = to;
+        Ok(encounter)
+    }
+}
+
+async fn send_encounter_frame(
+    frame: SMSG_UPDATE_INSTANCE_ENCOUNTER_UNIT_EncounterFrame,
+    players: &[Guid],
+    character_manager: &CharacterManager,
+) -> Result<()> {
+    let event = ServerEvent::UpdateInstanceEncounterUnit(SMSG_UPDATE_INSTANCE_ENCOUNTER_UNIT { frame });
+    for &guid in players {
+        if let Some(character) = character_manager.find_character(guid) {
+            event.send_to_character(character).await?;
+        }
+    }
+    Ok(())
+}
@@ -1,15 +1,46 @@
 use std::sync::Weak;
 
 use smol::lock::RwLock;
-use wow_world_messages::wrath::{MovementInfo, ObjectType, UpdateMask};
+use wow_world_messages::wrath::{MovementInfo, ObjectType, UpdateMask, Vector3d};
 use wow_world_messages::Guid;
+use wrath_game_db::GameDatabase;
 
+use super::gathering::GatheringNodes;
+use super::interactive_objects::InteractiveObjects;
 use super::map_manager::MapManager;
 use super::prelude::ReceiveUpdates;
 use crate::character::Character;
 use crate::data::PositionAndOrientation;
 use crate::prelude::*;
 
+//Gameobjects use the HIGHGUID_GAMEOBJECT guid layout, with the template entry in the middle and the spawn's guid at the bottom
+const HIGH_GUID_GAMEOBJECT: u64 = 0xF110;
+
+pub fn gameobject_guid(entry: u32, spawn_guid: u32) -> Guid {
+    Guid::new((HIGH_GUID_GAMEOBJECT << 48) | ((entry as u64 & 0xFFFFFF) << 24) | (spawn_guid as u64 & 0xFFFFFF))
+}
+
+//Gameobject spawns are registered with the systems that track them, their templates have to be loaded first
+pub async fn register_gameobject_spawns(
+    game_db: &GameDatabase,
+    gathering_nodes: &mut GatheringNodes,
+    interactive_objects: &mut InteractiveObjects,
+) -> Result<()> {
+    let spawns = game_db.get_all_gameobject_spawns().await?;
+    for spawn in &spawns {
+        let guid = gameobject_guid(spawn.entry, spawn.guid);
+        let position = Vector3d {
+            x: spawn.position_x,
+            y: spawn.position_y,
+            z: spawn.position_z,
+        };
+        gathering_nodes.register_node(guid, spawn.entry, spawn.map as u32, position);
+        interactive_objects.register_object(guid, spawn.entry, spawn.map as u32, position, spawn.phase_mask);
+    }
+    info!("Registered {} gameobject spawns", spawns.len());
+    Ok(())
+}
+
 pub trait GameObject: Send + Sync {
     //Gets position of object. Some objects may not have position (Item, Container) = None
     fn get_position(&self) -> Option<PositionAndOrientation>;
@@ -19,7 +50,9 @@ pub trait GameObject: Send + Sync {
     fn is_in_range(&self, guid: Guid) -> bool;
     fn add_in_range_object(&mut self, guid: Guid, object: Weak<RwLock<dyn GameObject>>) -> Result<()>;
     fn add_in_range_character(&mut self, guid: Guid) -> Result<()>;
-    fn get_in_range_guids(&self) -> Vec<Guid>;
+    fn get_in_range_guids(&self) -> impl Iterator<Item = Guid> + '_
+    where
+        Self: Sized;
     fn get_in_range_characters(&self) -> &[Guid];
     fn remove_in_range_object(&mut self, guid: Guid) -> Result<()>;
     fn clear_in_range_objects(&mut self);
@@ -32,5 +65,7 @@ pub trait GameObject: Send + Sync {
 
     fn get_guid(&self) -> Guid;
     fn get_type(&self) -> ObjectType;
+    //Objects only see each other when their phase masks have at least one phase in common
+    fn get_phase_mask(&self) -> u32;
     fn on_pushed_to_map(&mut self, map_manager: &MapManager) -> Result<()>;
 }
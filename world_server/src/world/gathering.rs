@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use wow_world_messages::wrath::Vector3d;
+use wrath_game_db::{DBGatheringNodeTemplate, GameDatabase};
+
+use super::instance_manager::MapID;
+use super::interactive_objects::INTERACTION_RANGE;
+use super::loot::{Loot, LootSource};
+use crate::character::Character;
+use crate::combat::damage;
+use crate::prelude::*;
+use crate::random;
+
+//Gathering stops giving skill at this many points above the node's requirement
+const GREY_SKILL_OFFSET: u16 = 100;
+const GREEN_SKILL_OFFSET: u16 = 50;
+const YELLOW_SKILL_OFFSET: u16 = 25;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GatherResult {
+    //The loot waits on the node until the character takes it from the loot window
+    Gathered { new_skill_value: Option<u16>, depleted: bool },
+    SkillTooLow { required: u16 },
+    Depleted,
+    OutOfRange,
+}
+
+struct GatheringNode {
+    entry: u32,
+    map: MapID,
+    position: Vector3d,
+    gathers_left: u8,
+    //Seconds until a depleted node is back
+    respawn_in: f32,
+    loot: Option<Loot>,
+}
+
+//Resource nodes are gameobjects, the gameobject system registers them here when it spawns one
+#[derive(Default)]
+pub struct GatheringNodes {
+    templates: HashMap<u32, DBGatheringNodeTemplate>,
+    nodes: HashMap<Guid, GatheringNode>,
+}
+
+impl GatheringNodes {
+    pub async fn load(&mut self, game_db: &GameDatabase) -> Result<()> {
+        self.templates = game_db
+            .get_all_gathering_node_templates()
+            .await?
+            .into_iter()
+            .map(|template| (template.entry, template))
+            .collect();
+        info!("Loaded {} gathering node templates", self.templates.len());
+        Ok(())
+    }
+
+    //Gameobjects that aren't a gathering node are ignored
+    pub fn register_node(&mut self, guid: Guid, entry: u32, map: MapID, position: Vector3d) {
+        let Some(template) = self.templates.get(&entry) else {
+            return;
+        };
+        let node = GatheringNode {
+            entry,
+            map,
+            position,
+            gathers_left: roll_gathers(template),
+            respawn_in: 0.0,
+            loot: None,
+        };
+        self.nodes.insert(guid, node);
+    }
+
+    pub fn is_node(&self, guid: Guid) -> bool {
+        self.nodes.contains_key(&guid)
+    }
+
+    pub fn get_loot(&self, guid: Guid) -> Option<&Loot> {
+        self.nodes.get(&guid)?.loot.as_ref()
+    }
+
+    pub fn get_loot_mut(&mut self, guid: Guid) -> Option<&mut Loot> {
+        self.nodes.get_mut(&guid)?.loot.as_mut()
+    }
+
+    pub fn clear_loot_if_empty(&mut self, guid: Guid) {
+        if let Some(node) = self.nodes.get_mut(&guid) {
+            if node.loot.as_ref().is_some_and(Loot::is_empty) {
+                node.loot = None;
+            }
+        }
+    }
+
+    pub fn tick(&mut self, delta_time: f32) {
+        for node in self.nodes.values_mut().filter(|node| node.gathers_left == 0) {
+            node.respawn_in -= delta_time;
+            if node.respawn_in <= 0.0 {
+                if let Some(template) = self.templates.get(&node.entry) {
+                    node.gathers_left = roll_gathers(template);
+                }
+            }
+        }
+    }
+
+    //The rolled loot is kept on the node, the character takes it through the loot window
+    pub fn gather(&mut self, guid: Guid, character: &mut Character) -> Result<GatherResult> {
+        let node = self.nodes.get_mut(&guid).ok_or_else(|| anyhow!("No gathering node with guid {}", guid))?;
+        let template = self
+            .templates
+            .get(&node.entry)
+            .ok_or_else(|| anyhow!("Gathering node {} has no template", node.entry))?;
+
+        if node.gathers_left == 0 {
+            return Ok(GatherResult::Depleted);
+        }
+        if node.map != character.map.as_int() || damage::distance(node.position, character.movement_info.position) > INTERACTION_RANGE {
+            return Ok(GatherResult::OutOfRange);
+        }
+
+        let skill_value = character.get_skill_value(template.skill).unwrap_or(0);
+        if skill_value < template.required_skill_value {
+            return Ok(GatherResult::SkillTooLow {
+                required: template.required_skill_value,
+            });
+        }
+
+        let skill_up = random::with_rng(|rng| rng.gen_bool(skill_up_chance(skill_value, template.required_skill_value)));
+        let new_skill_value = if skill_up {
+            Some(character.increase_skill(template.skill, 1)?)
+        } else {
+            None
+        };
+
+        let count = random::with_rng(|rng| rng.gen_range(template.loot_min_count..=template.loot_max_count.max(template.loot_min_count)));
+        node.gathers_left -= 1;
+        let depleted = node.gathers_left == 0;
+        if depleted {
+            let window = template.respawn_min_seconds..=template.respawn_max_seconds.max(template.respawn_min_seconds);
+            node.respawn_in = random::with_rng(|rng| rng.gen_range(window)) as f32;
+        }
+
+        node.loot = Some(Loot::single_item(
+            LootSource::GatheringNode { entry: node.entry },
+            template.loot_item,
+            count,
+            character.get_guid(),
+        ));
+        Ok(GatherResult::Gathered { new_skill_value, depleted })
+    }
+}
+
+fn roll_gathers(template: &DBGatheringNodeTemplate) -> u8 {
+    random::with_rng(|rng| rng.gen_range(template.min_gathers..=template.max_gathers.max(template.min_gathers)))
+}
+
+//Same color bands the client shows for the node: orange always gives a point, grey never does
+fn skill_up_chance(skill_value: u16, required_skill_value: u16) -> f64 {
+    let difference = skill_value.saturating_sub(required_skill_value);
+    if difference < YELLOW_SKILL_OFFSET {
+        1.0
+    } else if difference < GREEN_SKILL_OFFSET {
+        0.5
+    } else if difference < GREY_SKILL_OFFSET {
+        0.25
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,32 @@
+//! The options creatures offer in their gossip window, one menu per creature entry.
+
+use std::collections::HashMap;
+
+use wrath_game_db::{DBNpcGossipOption, GameDatabase};
+
+use crate::prelude::*;
+
+#[derive(Default)]
+pub struct GossipMenus {
+    //By creature entry, in the order the gossip window shows them
+    options: HashMap<u32, Vec<DBNpcGossipOption>>,
+}
+
+impl GossipMenus {
+    pub async fn load(&mut self, game_db: &GameDatabase) -> Result<()> {
+        self.options.clear();
+        for option in game_db.get_all_npc_gossip_options().await? {
+            self.options.entry(option.entry).or_default().push(option);
+        }
+        info!("Loaded {} gossip menus", self.options.len());
+        Ok(())
+    }
+
+    pub fn get_options(&self, entry: u32) -> &[DBNpcGossipOption] {
+        self.options.get(&entry).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn get_option(&self, entry: u32, option_id: u32) -> Option<&DBNpcGossipOption> {
+        self.get_options(entry).iter().find(|option| option.option_id == option_id)
+    }
+}
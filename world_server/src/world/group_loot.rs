@@ -0,0 +1,511 @@
+//! Who may take what from a corpse when a group kills something, and the need/greed rolls for the items
+//! that are rolled on. The group leader picks the loot method and the quality threshold; items below the
+//! threshold go to the round robin looter under every method except free for all.
+//!
+//! When a group kills something `apply_group_loot` asks the group's `LootRules` who may take each item and
+//! starts a `LootRoll` for every item that is rolled on. Rolls end when everyone voted or the timer runs out,
+//! the winner gets the item and it leaves the corpse.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use rand::Rng;
+use wow_world_messages::wrath::{
+    Map, RollFlags, RollVote, SMSG_LOOT_ALL_PASSED, SMSG_LOOT_REMOVED, SMSG_LOOT_ROLL, SMSG_LOOT_ROLL_WON, SMSG_LOOT_START_ROLL,
+};
+use wrath_game_db::DBItemTemplate;
+use wrath_realm_db::RealmDatabase;
+
+use super::instance_manager::InstanceManager;
+use super::loot::{Loot, LootSource};
+use super::persistence_queue::RealmPersistenceQueue;
+use super::World;
+use crate::audit::{log_audit_event, AuditEvent, AuditSource};
+use crate::character::character_manager::CharacterManager;
+use crate::character::Character;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+use crate::random;
+
+const ROLL_DURATION: Duration = Duration::from_secs(60);
+//Uncommon (green) items and better are rolled on by default
+const DEFAULT_LOOT_THRESHOLD: u8 = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LootMethod {
+    FreeForAll,
+    RoundRobin,
+    MasterLoot,
+    GroupLoot,
+    NeedBeforeGreed,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LootRight {
+    Anyone,
+    Looter(Guid),
+    //The master looter hands the item out through the loot window
+    MasterLooter(Guid),
+    Roll,
+}
+
+//Kept by the group, the leader changes the method and threshold
+#[derive(Debug)]
+pub struct LootRules {
+    pub method: LootMethod,
+    pub threshold: u8,
+    pub master_looter: Option<Guid>,
+    //The member that looted last under round robin
+    last_looter: Option<Guid>,
+}
+
+impl Default for LootRules {
+    fn default() -> Self {
+        Self {
+            method: LootMethod::GroupLoot,
+            threshold: DEFAULT_LOOT_THRESHOLD,
+            master_looter: None,
+            last_looter: None,
+        }
+    }
+}
+
+impl LootRules {
+    //The master looter is only kept under master loot
+    pub fn set(&mut self, method: LootMethod, master_looter: Option<Guid>, threshold: u8) {
+        self.method = method;
+        self.master_looter = master_looter.filter(|_| method == LootMethod::MasterLoot);
+        self.threshold = threshold;
+    }
+
+    //Picks the member that gets the kill under round robin, members are the eligible ones in group order
+    pub fn next_looter(&mut self, members: &[Guid]) -> Option<Guid> {
+        let next = match self.last_looter.and_then(|last| members.iter().position(|&member| member == last)) {
+            Some(index) => members[(index + 1) % members.len()],
+            None => *members.first()?,
+        };
+        self.last_looter = Some(next);
+        Some(next)
+    }
+
+    //Who may take an item of this quality from a kill that went to looter
+    pub fn item_right(&self, item_quality: u8, looter: Guid) -> LootRight {
+        let above_threshold = item_quality >= self.threshold;
+        match self.method {
+            LootMethod::FreeForAll => LootRight::Anyone,
+            LootMethod::RoundRobin => LootRight::Looter(looter),
+            LootMethod::MasterLoot if above_threshold => LootRight::MasterLooter(self.master_looter.unwrap_or(looter)),
+            LootMethod::GroupLoot | LootMethod::NeedBeforeGreed if above_threshold => LootRight::Roll,
+            _ => LootRight::Looter(looter),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LootRollError {
+    NoSuchRoll,
+    NotAllowedToRoll,
+    AlreadyVoted,
+    //Need before greed only lets characters that can use the item roll need
+    CantNeed,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RollOutcome {
+    Won { winner: Guid, vote: RollVote, roll: u8 },
+    AllPassed,
+}
+
+struct Voter {
+    guid: Guid,
+    can_need: bool,
+    vote: Option<RollVote>,
+}
+
+pub struct LootRoll {
+    looted_object: Guid,
+    loot_slot: u32,
+    item_id: u32,
+    item_count: u8,
+    creature_entry: u32,
+    voters: Vec<Voter>,
+    remaining: Duration,
+}
+
+impl LootRoll {
+    //Under group loot everybody may need, need before greed passes who can use the item in can_need
+    pub fn new(looted_object: Guid, loot_slot: u32, item_id: u32, item_count: u8, creature_entry: u32, voters: &[(Guid, bool)]) -> Self {
+        Self {
+            looted_object,
+            loot_slot,
+            item_id,
+            item_count,
+            creature_entry,
+            voters: voters.iter().map(|&(guid, can_need)| Voter { guid, can_need, vote: None }).collect(),
+            remaining: ROLL_DURATION,
+        }
+    }
+
+    fn vote(&mut self, guid: Guid, vote: RollVote) -> std::result::Result<(), LootRollError> {
+        let voter = self
+            .voters
+            .iter_mut()
+            .find(|voter| voter.guid == guid)
+            .ok_or(LootRollError::NotAllowedToRoll)?;
+        if voter.vote.is_some() {
+            return Err(LootRollError::AlreadyVoted);
+        }
+        if vote == RollVote::Need && !voter.can_need {
+            return Err(LootRollError::CantNeed);
+        }
+        voter.vote = Some(vote);
+        Ok(())
+    }
+
+    fn everyone_voted(&self) -> bool {
+        self.voters.iter().all(|voter| voter.vote.is_some())
+    }
+
+    //Need beats greed, within the winning vote the highest 1-100 roll wins. Returns the roll of every
+    //voter in the winning vote as well, since everyone gets to see them.
+    fn resolve(&self, rng: &mut impl Rng) -> (RollOutcome, Vec<(Guid, u8)>) {
+        let winning_vote = [RollVote::Need, RollVote::Greed]
+            .into_iter()
+            .find(|&vote| self.voters.iter().any(|voter| voter.vote == Some(vote)));
+        let Some(winning_vote) = winning_vote else {
+            return (RollOutcome::AllPassed, vec![]);
+        };
+
+        let rolls: Vec<(Guid, u8)> = self
+            .voters
+            .iter()
+            .filter(|voter| voter.vote == Some(winning_vote))
+            .map(|voter| (voter.guid, rng.gen_range(1..=100)))
+            .collect();
+        //Ties go to whoever voted first
+        let &(winner, roll) = rolls.iter().rev().max_by_key(|(_, roll)| *roll).unwrap();
+        (
+            RollOutcome::Won {
+                winner,
+                vote: winning_vote,
+                roll,
+            },
+            rolls,
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct LootRolls {
+    //By looted object and loot slot
+    rolls: HashMap<(Guid, u32), LootRoll>,
+}
+
+impl LootRolls {
+    pub async fn start_roll(&mut self, roll: LootRoll, map: Map, character_manager: &CharacterManager) -> Result<()> {
+        let message = SMSG_LOOT_START_ROLL {
+            creature: roll.looted_object,
+            map,
+            loot_slot: roll.loot_slot,
+            item: roll.item_id,
+            item_random_suffix: 0,
+            item_random_property_id: 0,
+            item_count: roll.item_count as u32,
+            countdown_time: ROLL_DURATION,
+            roll_flags: RollFlags::empty().set_pass().set_need().set_greed(),
+        };
+        for voter in &roll.voters {
+            if let Ok(character) = character_manager.get_character(voter.guid) {
+                ServerEvent::LootStartRoll(message.clone()).send_to_character(character).await?;
+            }
+        }
+        self.rolls.insert((roll.looted_object, roll.loot_slot), roll);
+        Ok(())
+    }
+
+    pub async fn vote(
+        &mut self,
+        looted_object: Guid,
+        loot_slot: u32,
+        guid: Guid,
+        vote: RollVote,
+        character_manager: &CharacterManager,
+    ) -> std::result::Result<(), LootRollError> {
+        let roll = self.rolls.get_mut(&(looted_object, loot_slot)).ok_or(LootRollError::NoSuchRoll)?;
+        roll.vote(guid, vote)?;
+
+        //The roll numbers are only known once the roll ends, votes are shown right away
+        send_loot_roll(roll, guid, 0, vote, character_manager).await;
+        Ok(())
+    }
+
+    pub async fn tick(
+        &mut self,
+        delta_time: f32,
+        character_manager: &mut CharacterManager,
+        realm_db: &RealmDatabase,
+        persistence_queue: &RealmPersistenceQueue,
+        instance_manager: &InstanceManager,
+    ) -> Result<()> {
+        let elapsed = Duration::from_secs_f32(delta_time);
+        let mut finished = Vec::new();
+        for (&key, roll) in self.rolls.iter_mut() {
+            roll.remaining = roll.remaining.saturating_sub(elapsed);
+            if roll.remaining.is_zero() || roll.everyone_voted() {
+                finished.push(key);
+            }
+        }
+
+        for key in finished {
+            let roll = self.rolls.remove(&key).unwrap();
+            let (outcome, rolls) = random::with_rng(|rng| roll.resolve(rng));
+            let winner = finish_roll(&roll, outcome, &rolls, character_manager, realm_db, persistence_queue).await?;
+            end_roll_on_corpse(&roll, winner, character_manager, instance_manager).await?;
+        }
+        Ok(())
+    }
+}
+
+//Need before greed only lets characters roll need on items their class can use
+fn can_need(template: &DBItemTemplate, character: &Character) -> bool {
+    template
+        .allowed_classes_mask
+        .map_or(true, |mask| mask & (1 << (character.get_class() as u32 - 1)) != 0)
+}
+
+//Called with the loot of a creature the killer's group killed, recipients are the members that were around.
+//Items below the threshold go to the next looter, the rest is rolled on or handed out by the method.
+pub async fn apply_group_loot(
+    loot: &mut Loot,
+    corpse: Guid,
+    killer: &Character,
+    recipients: &[Guid],
+    character_manager: &CharacterManager,
+    world: &mut World,
+) -> Result<()> {
+    let Some(group_id) = world.get_groups().get_group_id(killer.get_guid()) else {
+        return Ok(());
+    };
+    let LootSource::Creature { entry: creature_entry } = loot.source else {
+        return Ok(());
+    };
+    let game_db = world.get_game_database();
+    let mut templates = Vec::new();
+    for (_, item) in loot.get_items() {
+        templates.push(game_db.get_item_template(item.item_id).await?);
+    }
+    let qualities: Vec<u8> = templates.iter().map(|template| template.quality).collect();
+
+    let Some(group) = world.get_groups_mut().get_group_mut(group_id) else {
+        return Ok(());
+    };
+    let members: Vec<Guid> = group.get_members().into_iter().filter(|member| recipients.contains(member)).collect();
+    let rules = &mut group.loot_rules;
+    let looter = match rules.method {
+        LootMethod::FreeForAll => killer.get_guid(),
+        _ => rules.next_looter(&members).unwrap_or(killer.get_guid()),
+    };
+    loot.apply_rules(rules, looter, &qualities);
+    let need_before_greed = rules.method == LootMethod::NeedBeforeGreed;
+
+    let rolled: Vec<(u8, _)> = loot
+        .get_items()
+        .filter(|&(slot, _)| loot.get_item_right(slot) == LootRight::Roll)
+        .collect();
+    for (slot, item) in rolled {
+        let template = &templates[slot as usize];
+        let voters: Vec<(Guid, bool)> = members
+            .iter()
+            .filter_map(|&member| character_manager.find_character(member))
+            .map(|voter| (voter.get_guid(), !need_before_greed || can_need(template, voter)))
+            .collect();
+        let roll = LootRoll::new(corpse, slot as u32, item.item_id, item.count, creature_entry, &voters);
+        world.get_loot_rolls_mut().start_roll(roll, killer.map, character_manager).await?;
+    }
+    Ok(())
+}
+
+//The item leaves the corpse when someone won it, everybody passing leaves it for anyone to take
+async fn end_roll_on_corpse(
+    roll: &LootRoll,
+    winner: Option<Guid>,
+    character_manager: &CharacterManager,
+    instance_manager: &InstanceManager,
+) -> Result<()> {
+    //The corpse may have decayed while the roll ran
+    let Some(corpse) = instance_manager.get_maps().find_map(|map| map.get_creature(roll.looted_object).cloned()) else {
+        return Ok(());
+    };
+    let mut corpse = corpse.write().await;
+    let Some(loot) = corpse.get_loot_mut() else {
+        return Ok(());
+    };
+    loot.end_roll(roll.loot_slot as u8, winner);
+    corpse.clear_loot_if_empty();
+    drop(corpse);
+
+    if winner.is_none() {
+        return Ok(());
+    }
+    for character in character_manager
+        .get_all_characters()
+        .filter(|character| character.get_loot_target() == Some(roll.looted_object))
+    {
+        ServerEvent::LootRemoved(SMSG_LOOT_REMOVED { slot: roll.loot_slot as u8 })
+            .send_to_character(character)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn send_loot_roll(roll: &LootRoll, player: Guid, roll_number: u8, vote: RollVote, character_manager: &CharacterManager) {
+    let message = SMSG_LOOT_ROLL {
+        creature: roll.looted_object,
+        loot_slot: roll.loot_slot,
+        player,
+        item: roll.item_id,
+        item_random_suffix: 0,
+        item_random_property_id: 0,
+        roll_number,
+        vote,
+        auto_pass: false,
+    };
+    for voter in &roll.voters {
+        if let Ok(character) = character_manager.get_character(voter.guid) {
+            ServerEvent::LootRoll(message.clone()).send_to_character(character).await.ok();
+        }
+    }
+}
+
+async fn finish_roll(
+    roll: &LootRoll,
+    outcome: RollOutcome,
+    rolls: &[(Guid, u8)],
+    character_manager: &mut CharacterManager,
+    realm_db: &RealmDatabase,
+    persistence_queue: &RealmPersistenceQueue,
+) -> Result<Option<Guid>> {
+    let RollOutcome::Won {
+        winner,
+        vote,
+        roll: winning_roll,
+    } = outcome
+    else {
+        for voter in &roll.voters {
+            if let Ok(character) = character_manager.get_character(voter.guid) {
+                ServerEvent::LootAllPassed(SMSG_LOOT_ALL_PASSED {
+                    looted_target: roll.looted_object,
+                    loot_slot: roll.loot_slot,
+                    item: roll.item_id,
+                    item_random_property_id: 0,
+                    item_random_suffix_id: 0,
+                })
+                .send_to_character(character)
+                .await?;
+            }
+        }
+        return Ok(None);
+    };
+
+    for &(player, roll_number) in rolls {
+        send_loot_roll(roll, player, roll_number, vote, character_manager).await;
+    }
+    let won = SMSG_LOOT_ROLL_WON {
+        looted_target: roll.looted_object,
+        loot_slot: roll.loot_slot,
+        item: roll.item_id,
+        item_random_suffix: 0,
+        item_random_property_id: 0,
+        winning_player: winner,
+        winning_roll,
+        vote,
+    };
+    for voter in &roll.voters {
+        if let Ok(character) = character_manager.get_character(voter.guid) {
+            ServerEvent::LootRollWon(won.clone()).send_to_character(character).await?;
+        }
+    }
+
+    //The winner may have logged out while the roll ran or have no room, the item stays for anyone to take then
+    let Ok(character) = character_manager.get_character_mut(winner) else {
+        return Ok(None);
+    };
+    let connection_sender = character.connection_sender.clone();
+    let character_id = winner.guid() as u32;
+    let mut stored = 0;
+    for _ in 0..roll.item_count {
+        let added = character
+            .try_add_item_to_backpack(roll.item_id, character_id, &connection_sender, Some(persistence_queue))
+            .await;
+        if added.is_none() {
+            break;
+        }
+        stored += 1;
+    }
+    if stored == 0 {
+        warn!("{} won item {} but has no room for it", character.name, roll.item_id);
+        return Ok(None);
+    }
+    let account_id = realm_db.get_account_id_for_character_name(&character.name).await?.unwrap_or(0);
+    log_audit_event(
+        realm_db,
+        account_id,
+        winner,
+        AuditEvent::ItemCreated {
+            item_id: roll.item_id,
+            count: stored,
+        },
+        AuditSource::Loot {
+            creature_entry: roll.creature_entry,
+        },
+    )
+    .await?;
+    Ok(Some(winner))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn round_robin_rotates_through_the_group() {
+        let members = [Guid::new(1), Guid::new(2), Guid::new(3)];
+        let mut rules = LootRules {
+            method: LootMethod::RoundRobin,
+            ..Default::default()
+        };
+        let looters: Vec<Guid> = (0..4).filter_map(|_| rules.next_looter(&members)).collect();
+        assert_eq!(looters, [members[0], members[1], members[2], members[0]]);
+
+        //Under group loot only items at the threshold are rolled on
+        rules.method = LootMethod::GroupLoot;
+        assert_eq!(rules.item_right(DEFAULT_LOOT_THRESHOLD, members[0]), LootRight::Roll);
+        assert_eq!(rules.item_right(DEFAULT_LOOT_THRESHOLD - 1, members[0]), LootRight::Looter(members[0]));
+    }
+
+    #[test]
+    fn need_beats_greed() {
+        let (needer, greeder, passer) = (Guid::new(1), Guid::new(2), Guid::new(3));
+        let mut roll = LootRoll::new(Guid::new(100), 0, 25, 1, 0, &[(needer, true), (greeder, true), (passer, false)]);
+        assert_eq!(roll.vote(passer, RollVote::Need), Err(LootRollError::CantNeed));
+        roll.vote(greeder, RollVote::Greed).unwrap();
+        roll.vote(needer, RollVote::Need).unwrap();
+        assert_eq!(roll.vote(needer, RollVote::Greed), Err(LootRollError::AlreadyVoted));
+        assert!(!roll.everyone_voted());
+        roll.vote(passer, RollVote::Pass).unwrap();
+        assert!(roll.everyone_voted());
+
+        let (outcome, rolls) = roll.resolve(&mut StdRng::seed_from_u64(7));
+        assert!(matches!(outcome, RollOutcome::Won { winner, vote: RollVote::Need, .. } if winner == needer));
+        assert_eq!(rolls.len(), 1);
+    }
+
+    #[test]
+    fn everyone_passing_leaves_the_item() {
+        let mut roll = LootRoll::new(Guid::new(100), 0, 25, 1, 0, &[(Guid::new(1), true)]);
+        roll.vote(Guid::new(1), RollVote::Pass).unwrap();
+        assert_eq!(roll.resolve(&mut StdRng::seed_from_u64(7)).0, RollOutcome::AllPassed);
+    }
+}
@@ -0,0 +1,203 @@
+//! Parties: who is in which group and who leads it. Groups only exist while the server runs, a restart
+//! breaks them all up.
+
+use std::collections::HashMap;
+
+use super::group_loot::LootRules;
+use crate::prelude::*;
+
+//Raids aren't supported, groups stay parties
+pub const MAX_PARTY_SIZE: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupError {
+    AlreadyInGroup,
+    AlreadyInvited,
+    GroupFull,
+    NotLeader,
+    NotInvited,
+    InviteSelf,
+}
+
+pub struct Group {
+    pub id: u32,
+    pub leader: Guid,
+    //In the order they joined, the leader included
+    members: Vec<Guid>,
+    pub loot_rules: LootRules,
+    //Sent with every group list, the client ignores lists older than the last one it got
+    update_counter: u32,
+}
+
+impl Group {
+    //Leader first
+    pub fn get_members(&self) -> Vec<Guid> {
+        std::iter::once(self.leader)
+            .chain(self.members.iter().copied().filter(|&member| member != self.leader))
+            .collect()
+    }
+
+    pub fn next_update_counter(&mut self) -> u32 {
+        self.update_counter += 1;
+        self.update_counter
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GroupLeft {
+    //The group goes on without the character, with a new leader if it was the leader
+    Left { group_id: u32, new_leader: Option<Guid> },
+    //Nobody would have been left to group with, these are the members it had
+    Disbanded { members: Vec<Guid> },
+}
+
+#[derive(Default)]
+pub struct GroupManager {
+    groups: HashMap<u32, Group>,
+    group_by_member: HashMap<Guid, u32>,
+    //Inviter by invited character
+    invites: HashMap<Guid, Guid>,
+    next_group_id: u32,
+}
+
+impl GroupManager {
+    pub fn get_group(&self, group_id: u32) -> Option<&Group> {
+        self.groups.get(&group_id)
+    }
+
+    pub fn get_group_mut(&mut self, group_id: u32) -> Option<&mut Group> {
+        self.groups.get_mut(&group_id)
+    }
+
+    pub fn get_group_id(&self, guid: Guid) -> Option<u32> {
+        self.group_by_member.get(&guid).copied()
+    }
+
+    pub fn get_group_of(&self, guid: Guid) -> Option<&Group> {
+        self.get_group_id(guid).and_then(|group_id| self.groups.get(&group_id))
+    }
+
+    //Anyone can start a group by inviting someone, inside a group only the leader invites
+    pub fn invite(&mut self, inviter: Guid, invitee: Guid) -> std::result::Result<(), GroupError> {
+        if inviter == invitee {
+            return Err(GroupError::InviteSelf);
+        }
+        if let Some(group) = self.get_group_of(inviter) {
+            if group.leader != inviter {
+                return Err(GroupError::NotLeader);
+            }
+            if group.members.len() >= MAX_PARTY_SIZE {
+                return Err(GroupError::GroupFull);
+            }
+        }
+        if self.group_by_member.contains_key(&invitee) {
+            return Err(GroupError::AlreadyInGroup);
+        }
+        if self.invites.contains_key(&invitee) {
+            return Err(GroupError::AlreadyInvited);
+        }
+        self.invites.insert(invitee, inviter);
+        Ok(())
+    }
+
+    //Returns who sent the invite
+    pub fn decline_invite(&mut self, invitee: Guid) -> Option<Guid> {
+        self.invites.remove(&invitee)
+    }
+
+    //The inviter's group gets a new member, or a new group is formed around the inviter. Returns the group.
+    pub fn accept_invite(&mut self, invitee: Guid) -> std::result::Result<u32, GroupError> {
+        let inviter = self.invites.remove(&invitee).ok_or(GroupError::NotInvited)?;
+        if self.group_by_member.contains_key(&invitee) {
+            return Err(GroupError::AlreadyInGroup);
+        }
+
+        let group_id = match self.get_group_of(inviter) {
+            //The inviter may have joined someone else's group since sending the invite
+            Some(group) if group.leader != inviter => return Err(GroupError::NotLeader),
+            Some(group) if group.members.len() >= MAX_PARTY_SIZE => return Err(GroupError::GroupFull),
+            Some(group) => group.id,
+            None => {
+                self.next_group_id += 1;
+                let group_id = self.next_group_id;
+                self.groups.insert(
+                    group_id,
+                    Group {
+                        id: group_id,
+                        leader: inviter,
+                        members: vec![inviter],
+                        loot_rules: LootRules::default(),
+                        update_counter: 0,
+                    },
+                );
+                self.group_by_member.insert(inviter, group_id);
+                group_id
+            }
+        };
+        if let Some(group) = self.groups.get_mut(&group_id) {
+            group.members.push(invitee);
+        }
+        self.group_by_member.insert(invitee, group_id);
+        Ok(group_id)
+    }
+
+    //Leading passes on to whoever joined first after the leader
+    pub fn leave(&mut self, guid: Guid) -> Option<GroupLeft> {
+        let group_id = self.group_by_member.remove(&guid)?;
+        let group = self.groups.get_mut(&group_id)?;
+        group.members.retain(|&member| member != guid);
+
+        if group.members.len() < 2 {
+            let mut members = self.groups.remove(&group_id)?.members;
+            for member in &members {
+                self.group_by_member.remove(member);
+            }
+            members.insert(0, guid);
+            return Some(GroupLeft::Disbanded { members });
+        }
+
+        let new_leader = (group.leader == guid).then(|| group.members[0]);
+        if let Some(new_leader) = new_leader {
+            group.leader = new_leader;
+        }
+        Some(GroupLeft::Left { group_id, new_leader })
+    }
+
+    //Invites to or from a character that logged out are dropped, its group membership is kept
+    pub fn on_character_left_world(&mut self, guid: Guid) {
+        self.invites.retain(|&invitee, &mut inviter| invitee != guid && inviter != guid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_form_on_accept_and_disband_below_two_members() {
+        let (leader, second, third) = (Guid::new(1), Guid::new(2), Guid::new(3));
+        let mut groups = GroupManager::default();
+
+        groups.invite(leader, second).unwrap();
+        let group_id = groups.accept_invite(second).unwrap();
+        assert_eq!(groups.invite(second, third), Err(GroupError::NotLeader));
+        groups.invite(leader, third).unwrap();
+        assert_eq!(groups.accept_invite(third), Ok(group_id));
+        assert_eq!(groups.get_group(group_id).unwrap().get_members(), vec![leader, second, third]);
+
+        assert_eq!(
+            groups.leave(leader),
+            Some(GroupLeft::Left {
+                group_id,
+                new_leader: Some(second)
+            })
+        );
+        assert_eq!(
+            groups.leave(third),
+            Some(GroupLeft::Disbanded {
+                members: vec![third, second]
+            })
+        );
+        assert_eq!(groups.get_group_id(second), None);
+    }
+}
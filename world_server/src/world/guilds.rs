@@ -0,0 +1,308 @@
+//! Guilds, their ranks and who is in them. Every guild is kept in memory, offline members included, so the
+//! roster and rank checks don't have to go to the realm database. Online members are tracked separately in
+//! the world's guild membership index.
+
+use std::collections::HashMap;
+
+use wrath_realm_db::RealmDatabase;
+
+use crate::prelude::*;
+
+//Bits of guild_rank.rights, the same bits the client uses to decide which guild controls to show
+pub const GUILD_RIGHT_CHAT_LISTEN: u32 = 0x1;
+pub const GUILD_RIGHT_CHAT_SPEAK: u32 = 0x2;
+pub const GUILD_RIGHT_OFFICER_CHAT_LISTEN: u32 = 0x4;
+pub const GUILD_RIGHT_OFFICER_CHAT_SPEAK: u32 = 0x8;
+pub const GUILD_RIGHT_INVITE: u32 = 0x10;
+pub const GUILD_RIGHT_REMOVE: u32 = 0x20;
+pub const GUILD_RIGHT_SET_MOTD: u32 = 0x1000;
+//Everything the client knows about, what the guild master's rank always has
+const GUILD_RIGHTS_ALL: u32 = 0x00DD_FFBF;
+
+const GUILD_RIGHTS_MEMBER: u32 = GUILD_RIGHT_CHAT_LISTEN | GUILD_RIGHT_CHAT_SPEAK;
+const GUILD_RIGHTS_OFFICER: u32 = GUILD_RIGHTS_MEMBER
+    | GUILD_RIGHT_OFFICER_CHAT_LISTEN
+    | GUILD_RIGHT_OFFICER_CHAT_SPEAK
+    | GUILD_RIGHT_INVITE
+    | GUILD_RIGHT_REMOVE
+    | GUILD_RIGHT_SET_MOTD;
+
+//The ranks a new guild starts with, from the guild master down. New members join at the lowest one.
+const DEFAULT_RANKS: [(&str, u32); 5] = [
+    ("Guild Master", GUILD_RIGHTS_ALL),
+    ("Officer", GUILD_RIGHTS_OFFICER),
+    ("Veteran", GUILD_RIGHTS_MEMBER),
+    ("Member", GUILD_RIGHTS_MEMBER),
+    ("Initiate", GUILD_RIGHTS_MEMBER),
+];
+
+pub const GUILD_MASTER_RANK: u8 = 0;
+const MAX_GUILD_NAME_LENGTH: usize = 24;
+const MAX_MOTD_LENGTH: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuildError {
+    NameInvalid,
+    NameTaken,
+    AlreadyInGuild,
+    TargetAlreadyInGuild,
+    TargetAlreadyInvited,
+    NotInGuild,
+    NotInvited,
+    NoPermission,
+    //The guild master has to be the last one out, leaving disbands the guild
+    LeaderCannotLeave,
+}
+
+pub struct GuildRank {
+    pub name: String,
+    pub rights: u32,
+}
+
+pub struct Guild {
+    pub id: u32,
+    pub name: String,
+    pub leader: Guid,
+    pub motd: String,
+    //By rank id
+    pub ranks: Vec<GuildRank>,
+    //Rank id by member
+    members: HashMap<Guid, u8>,
+}
+
+impl Guild {
+    pub fn get_members(&self) -> impl Iterator<Item = (Guid, u8)> + '_ {
+        self.members.iter().map(|(&guid, &rank)| (guid, rank))
+    }
+
+    pub fn get_rights(&self, rank: u8) -> u32 {
+        self.ranks.get(rank as usize).map_or(0, |rank| rank.rights)
+    }
+
+    fn lowest_rank(&self) -> u8 {
+        self.ranks.len().saturating_sub(1) as u8
+    }
+}
+
+#[derive(Default)]
+pub struct GuildManager {
+    guilds: HashMap<u32, Guild>,
+    guild_by_member: HashMap<Guid, u32>,
+    //Guild and inviter by invited character, invites only last until the server restarts
+    invites: HashMap<Guid, (u32, Guid)>,
+}
+
+fn validate_guild_name(name: &str) -> std::result::Result<(), GuildError> {
+    let valid_length = (2..=MAX_GUILD_NAME_LENGTH).contains(&name.chars().count());
+    let valid_characters = name.chars().all(|c| c.is_alphabetic() || c == ' ');
+    if valid_length && valid_characters && !name.starts_with(' ') && !name.ends_with(' ') && !name.contains("  ") {
+        Ok(())
+    } else {
+        Err(GuildError::NameInvalid)
+    }
+}
+
+impl GuildManager {
+    pub async fn load(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        self.guilds.clear();
+        self.guild_by_member.clear();
+        for guild in realm_db.get_all_guilds().await? {
+            let guild = Guild {
+                id: guild.id,
+                name: guild.name,
+                leader: Guid::new(guild.leader_id as u64),
+                motd: guild.motd,
+                ranks: Vec::new(),
+                members: HashMap::new(),
+            };
+            self.guilds.insert(guild.id, guild);
+        }
+        for rank in realm_db.get_all_guild_ranks().await? {
+            if let Some(guild) = self.guilds.get_mut(&rank.guild_id) {
+                guild.ranks.push(GuildRank {
+                    name: rank.name,
+                    rights: rank.rights,
+                });
+            }
+        }
+        for member in realm_db.get_all_guild_members().await? {
+            let guid = Guid::new(member.character_id as u64);
+            if let Some(guild) = self.guilds.get_mut(&member.guild_id) {
+                guild.members.insert(guid, member.rank_id);
+                self.guild_by_member.insert(guid, member.guild_id);
+            }
+        }
+        info!("Loaded {} guilds with {} members", self.guilds.len(), self.guild_by_member.len());
+        Ok(())
+    }
+
+    pub fn get_guild(&self, guild_id: u32) -> Option<&Guild> {
+        self.guilds.get(&guild_id)
+    }
+
+    pub fn get_guild_of(&self, guid: Guid) -> Option<&Guild> {
+        self.guild_by_member.get(&guid).and_then(|guild_id| self.guilds.get(guild_id))
+    }
+
+    //Guild id and rank id of the character, None if it isn't in a guild
+    pub fn get_membership(&self, guid: Guid) -> Option<(u32, u8)> {
+        let guild = self.get_guild_of(guid)?;
+        Some((guild.id, *guild.members.get(&guid)?))
+    }
+
+    pub fn has_right(&self, guid: Guid, right: u32) -> bool {
+        let Some(guild) = self.get_guild_of(guid) else {
+            return false;
+        };
+        guild.members.get(&guid).is_some_and(|&rank| guild.get_rights(rank) & right != 0)
+    }
+
+    pub async fn create_guild(&mut self, realm_db: &RealmDatabase, name: &str, leader: Guid) -> Result<std::result::Result<u32, GuildError>> {
+        if let Err(e) = validate_guild_name(name) {
+            return Ok(Err(e));
+        }
+        if self.guild_by_member.contains_key(&leader) {
+            return Ok(Err(GuildError::AlreadyInGuild));
+        }
+        if self.guilds.values().any(|guild| guild.name.eq_ignore_ascii_case(name)) {
+            return Ok(Err(GuildError::NameTaken));
+        }
+
+        let created_time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+        let guild_id = realm_db.create_guild(name, leader.guid() as u32, &DEFAULT_RANKS, created_time).await?;
+        let guild = Guild {
+            id: guild_id,
+            name: name.to_string(),
+            leader,
+            motd: String::new(),
+            ranks: DEFAULT_RANKS
+                .iter()
+                .map(|&(name, rights)| GuildRank {
+                    name: name.to_string(),
+                    rights,
+                })
+                .collect(),
+            members: HashMap::from([(leader, GUILD_MASTER_RANK)]),
+        };
+        self.guilds.insert(guild_id, guild);
+        self.guild_by_member.insert(leader, guild_id);
+        self.invites.remove(&leader);
+        Ok(Ok(guild_id))
+    }
+
+    pub fn invite(&mut self, inviter: Guid, invitee: Guid) -> std::result::Result<u32, GuildError> {
+        let guild_id = *self.guild_by_member.get(&inviter).ok_or(GuildError::NotInGuild)?;
+        if !self.has_right(inviter, GUILD_RIGHT_INVITE) {
+            return Err(GuildError::NoPermission);
+        }
+        if self.guild_by_member.contains_key(&invitee) {
+            return Err(GuildError::TargetAlreadyInGuild);
+        }
+        if self.invites.contains_key(&invitee) {
+            return Err(GuildError::TargetAlreadyInvited);
+        }
+        self.invites.insert(invitee, (guild_id, inviter));
+        Ok(guild_id)
+    }
+
+    //Returns who sent the invite
+    pub fn decline_invite(&mut self, invitee: Guid) -> Option<Guid> {
+        self.invites.remove(&invitee).map(|(_, inviter)| inviter)
+    }
+
+    //Returns the guild and rank the character joined at
+    pub async fn accept_invite(&mut self, realm_db: &RealmDatabase, invitee: Guid) -> Result<std::result::Result<(u32, u8), GuildError>> {
+        let Some((guild_id, _)) = self.invites.remove(&invitee) else {
+            return Ok(Err(GuildError::NotInvited));
+        };
+        if self.guild_by_member.contains_key(&invitee) {
+            return Ok(Err(GuildError::AlreadyInGuild));
+        }
+        //The guild may have been disbanded since the invite went out
+        let Some(guild) = self.guilds.get_mut(&guild_id) else {
+            return Ok(Err(GuildError::NotInvited));
+        };
+        let rank = guild.lowest_rank();
+        realm_db.add_guild_member(guild_id, invitee.guid() as u32, rank).await?;
+        guild.members.insert(invitee, rank);
+        self.guild_by_member.insert(invitee, guild_id);
+        Ok(Ok((guild_id, rank)))
+    }
+
+    //Returns the guild that was left
+    pub async fn leave(&mut self, realm_db: &RealmDatabase, guid: Guid) -> Result<std::result::Result<u32, GuildError>> {
+        let Some(guild) = self.get_guild_of(guid) else {
+            return Ok(Err(GuildError::NotInGuild));
+        };
+        if guild.leader == guid && guild.members.len() > 1 {
+            return Ok(Err(GuildError::LeaderCannotLeave));
+        }
+        let guild_id = guild.id;
+        if guild.leader == guid {
+            self.disband(realm_db, guild_id).await?;
+            return Ok(Ok(guild_id));
+        }
+
+        realm_db.remove_guild_member(guid.guid() as u32).await?;
+        self.guild_by_member.remove(&guid);
+        if let Some(guild) = self.guilds.get_mut(&guild_id) {
+            guild.members.remove(&guid);
+        }
+        Ok(Ok(guild_id))
+    }
+
+    //Returns the members the guild had
+    pub async fn disband(&mut self, realm_db: &RealmDatabase, guild_id: u32) -> Result<Vec<Guid>> {
+        realm_db.delete_guild(guild_id).await?;
+        let Some(guild) = self.guilds.remove(&guild_id) else {
+            return Ok(vec![]);
+        };
+        self.invites.retain(|_, (invited_to, _)| *invited_to != guild_id);
+        let members: Vec<Guid> = guild.members.into_keys().collect();
+        for member in &members {
+            self.guild_by_member.remove(member);
+        }
+        Ok(members)
+    }
+
+    pub async fn set_motd(&mut self, realm_db: &RealmDatabase, guid: Guid, motd: &str) -> Result<std::result::Result<u32, GuildError>> {
+        let Some(guild_id) = self.guild_by_member.get(&guid).copied() else {
+            return Ok(Err(GuildError::NotInGuild));
+        };
+        if !self.has_right(guid, GUILD_RIGHT_SET_MOTD) {
+            return Ok(Err(GuildError::NoPermission));
+        }
+        let motd: String = motd.chars().take(MAX_MOTD_LENGTH).collect();
+        realm_db.set_guild_motd(guild_id, &motd).await?;
+        if let Some(guild) = self.guilds.get_mut(&guild_id) {
+            guild.motd = motd;
+        }
+        Ok(Ok(guild_id))
+    }
+
+    //A deleted character just disappears from its guild, deleting the guild master is refused before it gets here
+    pub fn on_character_deleted(&mut self, guid: Guid) {
+        self.invites.remove(&guid);
+        if let Some(guild_id) = self.guild_by_member.remove(&guid) {
+            if let Some(guild) = self.guilds.get_mut(&guild_id) {
+                guild.members.remove(&guid);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guild_names_are_letters_and_single_spaces() {
+        assert_eq!(validate_guild_name("Knights of Stormwind"), Ok(()));
+        assert_eq!(validate_guild_name("X"), Err(GuildError::NameInvalid));
+        assert_eq!(validate_guild_name("Double  Space"), Err(GuildError::NameInvalid));
+        assert_eq!(validate_guild_name(" Leading"), Err(GuildError::NameInvalid));
+        assert_eq!(validate_guild_name("L33t"), Err(GuildError::NameInvalid));
+        assert_eq!(validate_guild_name(&"a".repeat(MAX_GUILD_NAME_LENGTH + 1)), Err(GuildError::NameInvalid));
+        assert!(GUILD_RIGHTS_ALL & GUILD_RIGHTS_OFFICER == GUILD_RIGHTS_OFFICER);
+    }
+}
@@ -1,22 +1,136 @@
 use crate::character::character_manager::CharacterManager;
 use crate::character::Character;
 use crate::client::Client;
+use crate::error::GameLogicError;
 use crate::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use wow_world_messages::wrath::Map;
+use wrath_common::config;
+use wrath_game_db::{DBCreatureTemplate, DBRareSpawn};
+use wrath_realm_db::RealmDatabase;
 
-use super::map_manager::MapManager;
+use super::creature_manager::{CreatureManager, CreatureSpawns};
+use super::encounter::EncounterScriptRegistry;
+use super::map_manager::{MapManager, DEFAULT_VISIBILITY_RANGE};
 use super::prelude::GameObject;
 
 pub type InstanceID = u32;
 pub type MapID = u32;
 
+const DEFAULT_SIMULATION_TICK_RATE: f32 = 10.0;
+//Characters can't pick a dungeon difficulty yet, new instances are all normal
+pub const DEFAULT_INSTANCE_DIFFICULTY: u8 = 0;
+
+fn to_interval(rate: f32) -> f32 {
+    if rate > 0.0 {
+        1.0 / rate
+    } else {
+        0.0
+    }
+}
+
+//Per map overrides are comma separated "map:value" pairs, pairs that don't parse are skipped
+fn parse_map_overrides(overrides: &str) -> HashMap<MapID, f32> {
+    overrides
+        .split(',')
+        .filter_map(|pair| {
+            let (map, value) = pair.trim().split_once(':')?;
+            Some((map.trim().parse().ok()?, value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+fn map_overrides_from_env(key: &str) -> HashMap<MapID, f32> {
+    parse_map_overrides(&config::optional::<String>(key).unwrap_or_default())
+}
+
+//How often maps simulate (object updates, visibility), in seconds between simulation ticks. Packets are
+//handled on every tick of the main loop, which runs faster, so chat and movement don't wait for a busy map.
+//The default of zero simulates on every main loop tick.
+#[derive(Default)]
+pub struct SimulationRates {
+    default_interval: f32,
+    map_intervals: HashMap<MapID, f32>,
+}
+
+impl SimulationRates {
+    //Rates are in ticks per second, MAP_SIMULATION_TICK_RATES overrides them per map as "map:rate" pairs
+    pub fn from_env() -> Self {
+        let map_intervals = map_overrides_from_env("MAP_SIMULATION_TICK_RATES")
+            .into_iter()
+            .map(|(map, rate)| (map, to_interval(rate)))
+            .collect();
+        Self {
+            default_interval: to_interval(config::or_default("SIMULATION_TICK_RATE", DEFAULT_SIMULATION_TICK_RATE)),
+            map_intervals,
+        }
+    }
+
+    fn interval_for(&self, map: MapID) -> f32 {
+        self.map_intervals.get(&map).copied().unwrap_or(self.default_interval)
+    }
+}
+
+//How far characters see and how often their movement heartbeats are passed on to the characters around them.
+//Battlegrounds and arenas want a short range and every heartbeat, the open world can afford to be looser.
+pub struct VisibilitySettings {
+    default_range: f32,
+    map_ranges: HashMap<MapID, f32>,
+    default_relay_interval: f32,
+    map_relay_intervals: HashMap<MapID, f32>,
+}
+
+impl Default for VisibilitySettings {
+    fn default() -> Self {
+        Self {
+            default_range: DEFAULT_VISIBILITY_RANGE,
+            map_ranges: HashMap::default(),
+            default_relay_interval: 0.0,
+            map_relay_intervals: HashMap::default(),
+        }
+    }
+}
+
+impl VisibilitySettings {
+    //Ranges are in yards and relay rates in heartbeats per second, both can be overridden per map as "map:value" pairs
+    pub fn from_env() -> Self {
+        let map_relay_intervals = map_overrides_from_env("MAP_MOVEMENT_RELAY_RATES")
+            .into_iter()
+            .map(|(map, rate)| (map, to_interval(rate)))
+            .collect();
+        Self {
+            default_range: config::or_default("VISIBILITY_RANGE", DEFAULT_VISIBILITY_RANGE),
+            map_ranges: map_overrides_from_env("MAP_VISIBILITY_RANGES"),
+            default_relay_interval: to_interval(config::or_default("MOVEMENT_RELAY_RATE", 0.0)),
+            map_relay_intervals,
+        }
+    }
+
+    fn range_for(&self, map: MapID) -> f32 {
+        self.map_ranges.get(&map).copied().unwrap_or(self.default_range)
+    }
+
+    fn relay_interval_for(&self, map: MapID) -> f32 {
+        self.map_relay_intervals.get(&map).copied().unwrap_or(self.default_relay_interval)
+    }
+}
+
 #[derive(Default)]
 pub struct InstanceManager {
     //Multiple instances are things like raids and dungeons which can spawn many times for
     //different groups
     multiple_instances: HashMap<InstanceID, MapManager>,
     world_maps: HashMap<MapID, MapManager>,
+    encounter_scripts: EncounterScriptRegistry,
+    //Dungeons, raids, battlegrounds and arenas, the maps that get a copy per group
+    instance_maps: HashSet<MapID>,
+    last_instance_id: InstanceID,
+    creature_spawns: Arc<CreatureSpawns>,
+    simulation_rates: SimulationRates,
+    visibility_settings: VisibilitySettings,
+    //Characters per map that refresh their in-range set each simulation tick, zero for all of them
+    visibility_budget: usize,
 }
 
 impl InstanceManager {
@@ -24,9 +138,45 @@ impl InstanceManager {
         Self {
             multiple_instances: HashMap::default(),
             world_maps: HashMap::default(),
+            encounter_scripts: EncounterScriptRegistry::default(),
+            instance_maps: HashSet::default(),
+            last_instance_id: 0,
+            creature_spawns: Arc::default(),
+            simulation_rates: SimulationRates::from_env(),
+            visibility_settings: VisibilitySettings::from_env(),
+            visibility_budget: config::or_default("VISIBILITY_UPDATES_PER_TICK", 0),
         }
     }
 
+    //Maps that are created from now on are populated with these spawns
+    pub fn set_creature_spawns(&mut self, creature_spawns: CreatureSpawns) {
+        self.creature_spawns = Arc::new(creature_spawns);
+    }
+
+    pub fn get_creature_template(&self, entry: u32) -> Option<&DBCreatureTemplate> {
+        self.creature_spawns.get_template(entry)
+    }
+
+    pub fn set_instance_maps(&mut self, instance_maps: HashSet<MapID>) {
+        self.instance_maps = instance_maps;
+    }
+
+    //Instances that were saved in an earlier run keep their ids, new ones are numbered after them
+    pub async fn load_instance_ids(&mut self, realm_db: &RealmDatabase) -> Result<()> {
+        self.last_instance_id = realm_db.get_max_instance_id().await?;
+        Ok(())
+    }
+
+    pub fn create_instance_id(&mut self) -> InstanceID {
+        self.last_instance_id += 1;
+        self.last_instance_id
+    }
+
+    #[cfg(test)]
+    pub fn set_simulation_rates(&mut self, simulation_rates: SimulationRates) {
+        self.simulation_rates = simulation_rates;
+    }
+
     pub async fn tick(&mut self, character_manager: &mut CharacterManager, delta_time: f32) -> Result<()> {
         Self::tick_maps::<MapID>(&mut self.world_maps, character_manager, delta_time).await?;
         Self::tick_maps::<InstanceID>(&mut self.multiple_instances, character_manager, delta_time).await?;
@@ -42,7 +192,9 @@ impl InstanceManager {
         delta_time: f32,
     ) -> Result<()> {
         for map in maps.values_mut() {
-            map.tick(delta_time, character_manager).await?;
+            if let Some(simulation_delta) = map.advance_simulation_clock(delta_time) {
+                map.tick(simulation_delta, character_manager).await?;
+            }
         }
         Ok(())
     }
@@ -64,18 +216,30 @@ impl InstanceManager {
         Ok(())
     }
 
-    fn is_instance(&self, _map_id: Map) -> bool {
-        //TODO: implement based on DBC storage
-        false
+    pub fn is_instance(&self, map: Map) -> bool {
+        self.instance_maps.contains(&map.as_int())
     }
 
     pub async fn get_or_create_map(&mut self, object: &impl GameObject, map: Map) -> Result<&mut MapManager> {
+        let simulation_interval = self.simulation_rates.interval_for(map.as_int());
+        let visibility_budget = self.visibility_budget;
+        let visibility_range = self.visibility_settings.range_for(map.as_int());
+        let movement_relay_interval = self.visibility_settings.relay_interval_for(map.as_int());
+        let creature_spawns = &self.creature_spawns;
         let map = if !self.is_instance(map) {
-            Ok(self.world_maps.entry(map.as_int()).or_insert_with(|| MapManager::new(map.as_int())))
+            Ok(self.world_maps.entry(map.as_int()).or_insert_with(|| {
+                MapManager::new(map.as_int())
+                    .with_simulation_interval(simulation_interval)
+                    .with_visibility_budget(visibility_budget)
+                    .with_visibility(visibility_range, movement_relay_interval)
+                    .with_creatures(CreatureManager::new(map.as_int(), creature_spawns.clone()))
+            }))
         } else if let Some(character) = object.as_character() {
-            Ok(self.get_or_create_map_for_instance(map, character.instance_id).await)
+            Ok(self
+                .get_or_create_map_for_instance(map, character.instance_id, DEFAULT_INSTANCE_DIFFICULTY)
+                .await)
         } else {
-            Err(anyhow!("Not a valid map"))
+            Err(GameLogicError::InvalidMap(map.as_int()).into())
         };
 
         map
@@ -101,19 +265,87 @@ impl InstanceManager {
         }
     }
 
-    async fn get_or_create_map_for_instance(&mut self, map: Map, instance_id: InstanceID) -> &mut MapManager {
-        self.multiple_instances.entry(instance_id).or_insert(MapManager::new(map.as_int()))
+    //Starts the instance if it isn't running. An instance that was saved before is loaded with the difficulty and
+    //killed bosses it was saved with, a new one gets the given difficulty.
+    pub async fn get_or_load_instance(&mut self, map: Map, instance_id: InstanceID, difficulty: u8, realm_db: &RealmDatabase) -> Result<()> {
+        if self.multiple_instances.contains_key(&instance_id) {
+            return Ok(());
+        }
+        let saved = realm_db.get_instance(instance_id).await?;
+        let difficulty = saved.as_ref().map_or(difficulty, |instance| instance.difficulty);
+        let instance = self.get_or_create_map_for_instance(map, instance_id, difficulty).await;
+        if let Some(saved) = saved {
+            instance.restore_completed_encounters(saved.completed_encounters).await;
+        }
+        Ok(())
     }
 
-    pub async fn handle_client_disconnected(&mut self, client: &Client, character_manager: &CharacterManager) -> Result<()> {
-        if let Some(guid) = client.data.active_character {
-            let character = character_manager.get_character(guid)?;
-            let map = self.try_get_map_for_character_mut(character);
-            if let Some(map) = map {
-                map.remove_object_by_guid(guid);
-            }
+    async fn get_or_create_map_for_instance(&mut self, map: Map, instance_id: InstanceID, difficulty: u8) -> &mut MapManager {
+        let encounter_scripts = &self.encounter_scripts;
+        let simulation_interval = self.simulation_rates.interval_for(map.as_int());
+        let visibility_budget = self.visibility_budget;
+        let visibility_range = self.visibility_settings.range_for(map.as_int());
+        let movement_relay_interval = self.visibility_settings.relay_interval_for(map.as_int());
+        let creature_spawns = &self.creature_spawns;
+        self.multiple_instances.entry(instance_id).or_insert_with(|| {
+            let encounters = encounter_scripts.create_encounters(instance_id, map.as_int(), difficulty);
+            MapManager::new_instance(map.as_int(), encounters)
+                .with_simulation_interval(simulation_interval)
+                .with_visibility_budget(visibility_budget)
+                .with_visibility(visibility_range, movement_relay_interval)
+                .with_creatures(CreatureManager::new(map.as_int(), creature_spawns.clone()))
+        })
+    }
+
+    pub fn is_world_map_running(&self, map: MapID) -> bool {
+        self.world_maps.contains_key(&map)
+    }
+
+    //Rare spawns are only placed on maps that don't come in copies
+    pub fn spawn_rare_creature(&mut self, rare_spawn: &DBRareSpawn, character_manager: &mut CharacterManager) -> Result<()> {
+        let Some(template) = self.creature_spawns.get_template(rare_spawn.creature_entry) else {
+            warn!(
+                "Rare spawn {} uses creature entry {} which has no template",
+                rare_spawn.id, rare_spawn.creature_entry
+            );
+            return Ok(());
+        };
+        let Some(map) = self.world_maps.get_mut(&(rare_spawn.map as MapID)) else {
+            return Ok(());
+        };
+        map.spawn_rare_creature(rare_spawn, template, character_manager)
+    }
+
+    //Every map and instance that is currently running
+    pub fn get_maps(&self) -> impl Iterator<Item = &MapManager> {
+        self.world_maps.values().chain(self.multiple_instances.values())
+    }
+
+    pub fn get_encounter_scripts_mut(&mut self) -> &mut EncounterScriptRegistry {
+        &mut self.encounter_scripts
+    }
+
+    //A character that is mid teleport is no longer on its old map, queuing its removal again is harmless
+    pub fn handle_client_disconnected(&mut self, client: &Client, character_manager: &CharacterManager) {
+        let Some(character) = client.data.active_character.and_then(|guid| character_manager.find_character(guid)) else {
+            return;
+        };
+        if let Some(map) = self.try_get_map_for_character_mut(character) {
+            map.remove_object_by_guid(character.get_guid());
         }
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_overrides_skip_malformed_pairs() {
+        let overrides = parse_map_overrides(" 489:40, 559:35.5,571,abc:3,0:");
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides[&489], 40.0);
+        assert_eq!(overrides[&559], 35.5);
+        assert!(parse_map_overrides("").is_empty());
     }
 }
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use wow_world_messages::wrath::Vector3d;
+use wrath_game_db::{DBGameObjectTemplate, GameDatabase};
+
+use crate::character::Character;
+use crate::prelude::*;
+
+use super::instance_manager::MapID;
+
+const GAMEOBJECT_TYPE_SPELL_FOCUS: u8 = 8;
+const GAMEOBJECT_TYPE_GOOBER: u8 = 10;
+
+//Same range the client uses before it lets the player click an object
+pub const INTERACTION_RANGE: f32 = 5.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum InteractionKind {
+    SpellFocus { focus_id: u32, radius: f32 },
+    Goober { required_quest: Option<u32> },
+}
+
+struct InteractiveObject {
+    entry: u32,
+    map: MapID,
+    position: Vector3d,
+    phase_mask: u32,
+    kind: InteractionKind,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ObjectUseResult {
+    //The quest system gives credit for the objective that matches the gameobject entry
+    QuestCredit { gameobject_entry: u32, quest_id: Option<u32> },
+    NotUsable,
+    OutOfRange,
+}
+
+//Gameobjects that the server itself has to reason about: spell foci (forges, moonwells) and objects
+//that count towards quests. The gameobject system registers them here when it spawns one.
+#[derive(Default)]
+pub struct InteractiveObjects {
+    kinds: HashMap<u32, InteractionKind>,
+    objects: HashMap<Guid, InteractiveObject>,
+}
+
+impl InteractiveObjects {
+    pub async fn load(&mut self, game_db: &GameDatabase) -> Result<()> {
+        self.kinds = game_db
+            .get_all_gameobject_templates()
+            .await?
+            .iter()
+            .filter_map(|template| Some((template.entry, interaction_kind(template)?)))
+            .collect();
+        info!("Loaded {} interactive gameobject templates", self.kinds.len());
+        Ok(())
+    }
+
+    //Objects that aren't a spell focus or goober are ignored, there is nothing to track for them
+    pub fn register_object(&mut self, guid: Guid, entry: u32, map: MapID, position: Vector3d, phase_mask: u32) {
+        if let Some(&kind) = self.kinds.get(&entry) {
+            self.objects.insert(
+                guid,
+                InteractiveObject {
+                    entry,
+                    map,
+                    position,
+                    phase_mask,
+                    kind,
+                },
+            );
+        }
+    }
+
+    //Spell cast validation: spells with a required focus can only be cast near a matching object
+    pub fn is_spell_focus_in_range(&self, focus_id: u32, map: MapID, position: Vector3d, phase_mask: u32) -> bool {
+        self.objects.values().any(|object| match object.kind {
+            InteractionKind::SpellFocus { focus_id: id, radius } => {
+                id == focus_id && object.map == map && object.phase_mask & phase_mask != 0 && distance(object.position, position) <= radius
+            }
+            InteractionKind::Goober { .. } => false,
+        })
+    }
+
+    pub fn use_object(&self, guid: Guid, character: &Character) -> ObjectUseResult {
+        //Objects in another phase don't exist as far as the character is concerned
+        let Some(object) = self
+            .objects
+            .get(&guid)
+            .filter(|object| object.phase_mask & character.get_character_phase_mask() != 0)
+        else {
+            return ObjectUseResult::NotUsable;
+        };
+        if object.map != character.map.as_int() || distance(object.position, character.movement_info.position) > INTERACTION_RANGE {
+            return ObjectUseResult::OutOfRange;
+        }
+
+        match object.kind {
+            InteractionKind::Goober { required_quest } => ObjectUseResult::QuestCredit {
+                gameobject_entry: object.entry,
+                quest_id: required_quest,
+            },
+            InteractionKind::SpellFocus { .. } => ObjectUseResult::NotUsable,
+        }
+    }
+}
+
+fn interaction_kind(template: &DBGameObjectTemplate) -> Option<InteractionKind> {
+    match template.gameobject_type {
+        GAMEOBJECT_TYPE_SPELL_FOCUS => Some(InteractionKind::SpellFocus {
+            focus_id: template.data0,
+            radius: template.data1 as f32,
+        }),
+        GAMEOBJECT_TYPE_GOOBER => Some(InteractionKind::Goober {
+            required_quest: Some(template.data1).filter(|&quest| quest != 0),
+        }),
+        _ => None,
+    }
+}
+
+fn distance(a: Vector3d, b: Vector3d) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
@@ -0,0 +1,248 @@
+//! What creatures drop when they die. The loot is rolled from the game database's loot tables once, when the
+//! creature dies, and stays on the corpse until it's taken or the corpse decays. Everyone that had a part in
+//! the kill may loot it, the money is split between them. In a group the loot method decides who may take
+//! which item, see `group_loot`.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+use wrath_game_db::{DBCreatureLootItem, DBCreatureLootMoney, GameDatabase};
+
+use super::group_loot::{LootRight, LootRules};
+use crate::audit::AuditSource;
+use crate::prelude::*;
+use crate::random;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LootError {
+    NotAllowed,
+    SlotIsEmpty,
+}
+
+//Where the loot came from, audited with every item taken from it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LootSource {
+    Creature { entry: u32 },
+    GatheringNode { entry: u32 },
+}
+
+impl LootSource {
+    pub fn audit_source(self) -> AuditSource {
+        match self {
+            LootSource::Creature { entry } => AuditSource::Loot { creature_entry: entry },
+            LootSource::GatheringNode { entry } => AuditSource::Gathering { gameobject_entry: entry },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LootItem {
+    pub item_id: u32,
+    pub count: u8,
+}
+
+#[derive(Debug)]
+pub struct Loot {
+    pub source: LootSource,
+    money: u32,
+    //Taken items leave their slot empty, the client addresses items by slot
+    items: Vec<Option<LootItem>>,
+    //Who may take each item, by slot. Everyone that may loot unless a group loot method says otherwise.
+    rights: Vec<LootRight>,
+    //Who may loot, the killer and the group members that were around
+    recipients: Vec<Guid>,
+}
+
+impl Loot {
+    //What a gathering node gives, only the gatherer may take it
+    pub fn single_item(source: LootSource, item_id: u32, count: u8, recipient: Guid) -> Self {
+        Self {
+            source,
+            money: 0,
+            items: vec![Some(LootItem { item_id, count })],
+            rights: vec![],
+            recipients: vec![recipient],
+        }
+    }
+
+    pub fn may_loot(&self, guid: Guid) -> bool {
+        self.recipients.contains(&guid)
+    }
+
+    pub fn get_recipients(&self) -> &[Guid] {
+        &self.recipients
+    }
+
+    //Items that are being rolled on can't be taken until the roll ends
+    pub fn may_take_item(&self, slot: u8, guid: Guid) -> bool {
+        match self.get_item_right(slot) {
+            LootRight::Anyone => self.may_loot(guid),
+            LootRight::Looter(looter) | LootRight::MasterLooter(looter) => looter == guid,
+            LootRight::Roll => false,
+        }
+    }
+
+    pub fn get_item_right(&self, slot: u8) -> LootRight {
+        self.rights.get(slot as usize).copied().unwrap_or(LootRight::Anyone)
+    }
+
+    //Gives every item its right under the group's loot method, qualities are by slot. looter gets the items
+    //nobody rolls on.
+    pub fn apply_rules(&mut self, rules: &LootRules, looter: Guid, qualities: &[u8]) {
+        self.rights = qualities.iter().map(|&quality| rules.item_right(quality, looter)).collect();
+    }
+
+    //The roll on the slot ended. The winner got the item, when everybody passed anyone may take it.
+    pub fn end_roll(&mut self, slot: u8, winner: Option<Guid>) {
+        match winner {
+            Some(_) => {
+                if let Some(item) = self.items.get_mut(slot as usize) {
+                    *item = None;
+                }
+            }
+            None => {
+                if let Some(right) = self.rights.get_mut(slot as usize) {
+                    *right = LootRight::Anyone;
+                }
+            }
+        }
+    }
+
+    pub fn get_money(&self) -> u32 {
+        self.money
+    }
+
+    //The items that are still there, with their slot
+    pub fn get_items(&self) -> impl Iterator<Item = (u8, LootItem)> + '_ {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, item)| item.map(|item| (slot as u8, item)))
+    }
+
+    pub fn get_item(&self, slot: u8) -> std::result::Result<LootItem, LootError> {
+        self.items.get(slot as usize).copied().flatten().ok_or(LootError::SlotIsEmpty)
+    }
+
+    //Takes count of the item in the slot, whatever is left stays for the next try
+    pub fn take_item(&mut self, slot: u8, count: u8) -> std::result::Result<(), LootError> {
+        let item = self.items.get_mut(slot as usize).and_then(Option::as_mut).ok_or(LootError::SlotIsEmpty)?;
+        item.count = item.count.saturating_sub(count);
+        if item.count == 0 {
+            self.items[slot as usize] = None;
+        }
+        Ok(())
+    }
+
+    pub fn take_money(&mut self) -> u32 {
+        std::mem::take(&mut self.money)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.money == 0 && self.items.iter().all(Option::is_none)
+    }
+}
+
+//Everyone gets the same share, what can't be split evenly goes to the character that looted the money
+pub fn split_money(money: u32, recipients: usize) -> (u32, u32) {
+    let recipients = recipients.max(1) as u32;
+    (money / recipients, money % recipients)
+}
+
+#[derive(Default)]
+pub struct LootTemplates {
+    items: HashMap<u32, Vec<DBCreatureLootItem>>,
+    money: HashMap<u32, DBCreatureLootMoney>,
+}
+
+impl LootTemplates {
+    pub async fn load(&mut self, game_db: &GameDatabase) -> Result<()> {
+        self.items.clear();
+        for item in game_db.get_all_creature_loot_items().await? {
+            self.items.entry(item.entry).or_default().push(item);
+        }
+        self.money = game_db
+            .get_all_creature_loot_money()
+            .await?
+            .into_iter()
+            .map(|money| (money.entry, money))
+            .collect();
+        info!("Loaded loot for {} creatures", self.items.len().max(self.money.len()));
+        Ok(())
+    }
+
+    //None if the creature dropped nothing, its corpse isn't lootable then
+    pub fn generate(&self, creature_entry: u32, recipients: Vec<Guid>) -> Option<Loot> {
+        let items = self
+            .items
+            .get(&creature_entry)
+            .into_iter()
+            .flatten()
+            .filter(|item| random::with_rng(|rng| rng.gen_range(0.0..100.0)) < item.chance)
+            .map(|item| {
+                let count = random::with_rng(|rng| rng.gen_range(item.min_count..=item.max_count.max(item.min_count)));
+                Some(LootItem {
+                    item_id: item.item,
+                    count: count.max(1),
+                })
+            })
+            .collect();
+        let money = self.money.get(&creature_entry).map_or(0, |money| {
+            random::with_rng(|rng| rng.gen_range(money.min_money..=money.max_money.max(money.min_money)))
+        });
+
+        let loot = Loot {
+            source: LootSource::Creature { entry: creature_entry },
+            money,
+            items,
+            rights: vec![],
+            recipients,
+        };
+        (!loot.is_empty()).then_some(loot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::group_loot::LootMethod;
+
+    #[test]
+    fn loot_is_taken_slot_by_slot_and_money_is_split() {
+        let templates = LootTemplates {
+            items: HashMap::from([(
+                299,
+                vec![DBCreatureLootItem {
+                    entry: 299,
+                    item: 750,
+                    chance: 100.0,
+                    min_count: 3,
+                    max_count: 3,
+                }],
+            )]),
+            money: HashMap::new(),
+        };
+        let killer = Guid::new(1);
+        assert!(templates.generate(300, vec![killer]).is_none());
+
+        let mut loot = templates.generate(299, vec![killer]).unwrap();
+        assert!(loot.may_loot(killer) && !loot.may_loot(Guid::new(2)));
+        assert_eq!(loot.get_item(0), Ok(LootItem { item_id: 750, count: 3 }));
+        loot.take_item(0, 2).unwrap();
+        assert_eq!(loot.get_item(0).map(|item| item.count), Ok(1));
+        loot.take_item(0, 1).unwrap();
+        assert_eq!(loot.take_item(0, 1), Err(LootError::SlotIsEmpty));
+        assert!(loot.is_empty());
+
+        //Under round robin only the looter takes the items below the threshold
+        let mut loot = templates.generate(299, vec![killer, Guid::new(2)]).unwrap();
+        assert!(loot.may_take_item(0, Guid::new(2)));
+        let mut rules = LootRules::default();
+        rules.set(LootMethod::RoundRobin, None, 2);
+        loot.apply_rules(&rules, killer, &[1]);
+        assert!(loot.may_take_item(0, killer) && !loot.may_take_item(0, Guid::new(2)));
+
+        assert_eq!(split_money(100, 3), (33, 1));
+        assert_eq!(split_money(5, 0), (5, 0));
+    }
+}
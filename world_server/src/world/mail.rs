@@ -0,0 +1,121 @@
+//! Mail between characters. Mailboxes live in the realm database, the world only runs the timer that
+//! sends expired mail back to its sender or deletes it.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use wrath_realm_db::mail::{DBMail, MAIL_FLAG_RETURNED};
+use wrath_realm_db::RealmDatabase;
+
+use crate::character::character_manager::CharacterManager;
+use crate::prelude::*;
+
+pub const MAX_ATTACHMENTS: usize = 12;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const MAIL_LIFETIME_DAYS: u64 = 30;
+//Cash on delivery mail goes back quickly, the sender is waiting for the money
+const CASH_ON_DELIVERY_LIFETIME_DAYS: u64 = 3;
+
+//Copper for every mail, each attached item costs as much again
+const POSTAGE: u32 = 30;
+
+//Expiry is counted in days, there is no point in checking it every tick
+const CHECK_INTERVAL_SECONDS: f32 = 60.0;
+
+pub fn postage(item_count: usize) -> u32 {
+    POSTAGE * item_count.max(1) as u32
+}
+
+//Seconds until a new mail expires
+pub fn mail_lifetime(cash_on_delivery: u32) -> u64 {
+    match cash_on_delivery {
+        0 => MAIL_LIFETIME_DAYS * SECONDS_PER_DAY,
+        _ => CASH_ON_DELIVERY_LIFETIME_DAYS * SECONDS_PER_DAY,
+    }
+}
+
+pub fn current_unix_time() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+#[derive(Debug, PartialEq)]
+enum ExpiredMail {
+    ReturnToSender,
+    Delete,
+}
+
+//Mail that was already returned once, has nothing on it worth returning or whose sender was deleted is gone for good
+fn expired_mail_action(mail: &DBMail, has_items: bool, sender_exists: bool) -> ExpiredMail {
+    let returned = mail.flags & MAIL_FLAG_RETURNED != 0;
+    if returned || (mail.money == 0 && !has_items) || !sender_exists {
+        ExpiredMail::Delete
+    } else {
+        ExpiredMail::ReturnToSender
+    }
+}
+
+pub struct MailExpiry {
+    cooldown: f32,
+}
+
+impl Default for MailExpiry {
+    fn default() -> Self {
+        Self {
+            cooldown: CHECK_INTERVAL_SECONDS,
+        }
+    }
+}
+
+impl MailExpiry {
+    pub async fn tick(&mut self, delta_time: f32, character_manager: &CharacterManager, realm_db: &RealmDatabase) -> Result<()> {
+        self.cooldown -= delta_time;
+        if self.cooldown > 0.0 {
+            return Ok(());
+        }
+        self.cooldown = CHECK_INTERVAL_SECONDS;
+
+        let now = current_unix_time()?;
+        for mail in realm_db.get_expired_mails(now).await? {
+            let has_items = !realm_db.get_mail_items(mail.id).await?.is_empty();
+            let sender_exists = realm_db.get_character_info(mail.sender_id).await?.is_some();
+            match expired_mail_action(&mail, has_items, sender_exists) {
+                ExpiredMail::ReturnToSender => {
+                    realm_db.return_mail(&mail, now + mail_lifetime(0)).await?;
+                    handlers::notify_mail_received(Guid::new(mail.sender_id as u64), character_manager).await?;
+                }
+                ExpiredMail::Delete => realm_db.delete_mail(mail.id).await?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mail(money: u32, flags: u8) -> DBMail {
+        DBMail {
+            id: 1,
+            sender_id: 1,
+            receiver_id: 2,
+            subject: String::new(),
+            body: String::new(),
+            money,
+            cash_on_delivery: 0,
+            expire_time: 0,
+            flags,
+        }
+    }
+
+    #[test]
+    fn expired_mail_goes_back_once_if_anything_is_attached() {
+        assert_eq!(expired_mail_action(&mail(0, 0), true, true), ExpiredMail::ReturnToSender);
+        assert_eq!(expired_mail_action(&mail(10, 0), false, true), ExpiredMail::ReturnToSender);
+        assert_eq!(expired_mail_action(&mail(0, 0), false, true), ExpiredMail::Delete);
+        assert_eq!(expired_mail_action(&mail(10, MAIL_FLAG_RETURNED), true, true), ExpiredMail::Delete);
+        assert_eq!(expired_mail_action(&mail(10, 0), true, false), ExpiredMail::Delete);
+        assert_eq!(postage(0), 30);
+        assert_eq!(postage(3), 90);
+    }
+}
@@ -1,19 +1,28 @@
 use super::{
+    creature_manager::{CreatureManager, SharedCreature},
+    encounter::{EncounterState, InstanceEncounters},
     instance_manager::MapID,
     prelude::{build_create_update_block_for_player, build_out_of_range_update_block_for_player, build_values_update_block},
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 
 use super::prelude::GameObject;
 use crate::world::update_builder::ReceiveUpdates;
 use crate::{
-    character::{character_manager::CharacterManager, Character},
+    character::{character_far_sight::FarSightTarget, character_manager::CharacterManager, Character},
+    connection::events::ServerEvent,
     prelude::*,
+    spell,
 };
 use rstar::{PointDistance, RTree, RTreeObject, AABB};
-use wow_world_messages::wrath::UpdateMask;
+use smol::lock::RwLock;
+use wow_world_messages::wrath::{Area, Vector3d};
+use wrath_game_db::{DBCreatureTemplate, DBRareSpawn};
+use wrath_realm_db::RealmDatabase;
 
-const VISIBILITY_RANGE: f32 = 5000.0f32;
+//In yards, VISIBILITY_RANGE and MAP_VISIBILITY_RANGES override it
+pub const DEFAULT_VISIBILITY_RANGE: f32 = 70.71;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 struct RStarTreeItem {
@@ -39,9 +48,39 @@ pub struct MapManager {
     id: MapID,
 
     characters_on_map: HashSet<Guid>,
+    //Who is in which zone, for zone-wide broadcasts, and the other way around to keep it up to date
+    characters_by_zone: HashMap<Area, HashSet<Guid>>,
+    character_zones: HashMap<Guid, Area>,
     characters_query_tree: RTree<RStarTreeItem>,
+    creatures: CreatureManager,
+    //Creatures stand still, so their tree is only rebuilt when one spawns or despawns
+    creatures_query_tree: RTree<RStarTreeItem>,
     add_queue: Vec<Guid>,
     remove_queue: Vec<Guid>,
+
+    //The items the query tree was last built from, and buffers that are reused every tick so a map where
+    //nothing changes doesn't allocate
+    query_items: Vec<RStarTreeItem>,
+    query_items_scratch: Vec<RStarTreeItem>,
+    tick_guids: Vec<Guid>,
+    within_range_scratch: Vec<Guid>,
+    guid_scratch: Vec<Guid>,
+
+    //Only instanced maps (dungeons, raids) have boss encounters
+    encounters: Option<InstanceEncounters>,
+
+    //Seconds between simulation ticks, and the time that passed since the last one
+    simulation_interval: f32,
+    time_since_simulation: f32,
+
+    //Characters take turns refreshing their in-range set, at most visibility_budget of them per tick
+    //(zero for all), so the tick stays bounded on crowded maps
+    visibility_rotation: VecDeque<Guid>,
+    visibility_budget: usize,
+    //Squared, that's how the query tree takes it
+    visibility_range_squared: f32,
+    //Seconds between movement heartbeats that are passed on to others, zero passes on all of them
+    movement_relay_interval: f32,
 }
 
 impl MapManager {
@@ -50,10 +89,72 @@ impl MapManager {
         Self {
             id,
             characters_on_map: HashSet::new(),
+            characters_by_zone: HashMap::new(),
+            character_zones: HashMap::new(),
             characters_query_tree: RTree::new(),
+            creatures: CreatureManager::default(),
+            creatures_query_tree: RTree::new(),
             add_queue: Vec::new(),
             remove_queue: Vec::new(),
+            query_items: Vec::new(),
+            query_items_scratch: Vec::new(),
+            tick_guids: Vec::new(),
+            within_range_scratch: Vec::new(),
+            guid_scratch: Vec::new(),
+            encounters: None,
+            simulation_interval: 0.0,
+            time_since_simulation: 0.0,
+            visibility_rotation: VecDeque::new(),
+            visibility_budget: 0,
+            visibility_range_squared: DEFAULT_VISIBILITY_RANGE * DEFAULT_VISIBILITY_RANGE,
+            movement_relay_interval: 0.0,
+        }
+    }
+
+    pub fn with_visibility_budget(self, visibility_budget: usize) -> Self {
+        Self { visibility_budget, ..self }
+    }
+
+    pub fn with_simulation_interval(self, simulation_interval: f32) -> Self {
+        Self { simulation_interval, ..self }
+    }
+
+    pub fn with_visibility(self, visibility_range: f32, movement_relay_interval: f32) -> Self {
+        Self {
+            visibility_range_squared: visibility_range * visibility_range,
+            movement_relay_interval,
+            ..self
+        }
+    }
+
+    pub fn get_movement_relay_interval(&self) -> f32 {
+        self.movement_relay_interval
+    }
+
+    pub fn with_creatures(self, creatures: CreatureManager) -> Self {
+        let mut map = Self { creatures, ..self };
+        map.rebuild_creature_query_tree();
+        map
+    }
+
+    //Returns the time to simulate when the map is due for a simulation tick
+    pub fn advance_simulation_clock(&mut self, delta_time: f32) -> Option<f32> {
+        self.time_since_simulation += delta_time;
+        if self.time_since_simulation < self.simulation_interval {
+            return None;
         }
+        Some(std::mem::take(&mut self.time_since_simulation))
+    }
+
+    pub fn new_instance(id: MapID, encounters: InstanceEncounters) -> Self {
+        Self {
+            encounters: Some(encounters),
+            ..Self::new(id)
+        }
+    }
+
+    pub fn get_id(&self) -> MapID {
+        self.id
     }
 
     pub async fn shutdown(&self) -> Result<()> {
@@ -65,45 +166,57 @@ impl MapManager {
         self.characters_on_map.is_empty()
     }
 
-    pub async fn tick(&mut self, _delta_time: f32, character_manager: &mut CharacterManager) -> Result<()> {
+    pub async fn tick(&mut self, delta_time: f32, character_manager: &mut CharacterManager) -> Result<()> {
+        self.tick_creatures(delta_time, character_manager).await?;
         self.rebuild_object_querying_tree(character_manager)?;
         let any_removed = self.process_remove_queue(character_manager).await?;
         let any_added = self.process_add_queue(character_manager)?;
 
-        for guid in self.characters_on_map.iter().copied() {
-            self.update_in_range_set(guid, character_manager).await?;
+        self.schedule_visibility_updates(character_manager)?;
 
-            let has_any_update_bit = if let UpdateMask::Player(update_mask) = character_manager.get_character(guid)?.get_update_mask() {
-                update_mask.has_any_dirty_fields()
-            } else {
-                bail!("any other type not supported");
-            };
+        let mut tick_guids = std::mem::take(&mut self.tick_guids);
+        tick_guids.clear();
+        tick_guids.extend(self.characters_on_map.iter().copied());
+        for &guid in &tick_guids {
+            if character_manager.get_character_mut(guid)?.take_visibility_update_request() {
+                self.update_in_range_set(guid, character_manager).await?;
+            }
+
+            let has_any_update_bit = character_manager.get_character(guid)?.gameplay_data.has_any_dirty_fields();
 
-            let has_something_recently_removed = character_manager.get_character(guid)?.get_recently_removed_range_guids().is_empty();
+            let has_something_recently_removed = !character_manager.get_character(guid)?.get_recently_removed_range_guids().is_empty();
 
             if has_any_update_bit || any_removed || any_added || has_something_recently_removed {
                 {
                     let character = character_manager.get_character_mut(guid)?;
                     if let Some(out_of_range_update) = build_out_of_range_update_block_for_player(character) {
                         character.clear_recently_removed_range_guids();
-                        character.push_object_update(out_of_range_update);
+                        character.push_object_update(Arc::new(out_of_range_update));
                     }
                 }
 
                 if has_any_update_bit {
                     let values_update = {
                         let character = character_manager.get_character_mut(guid)?;
-                        let values_update = build_values_update_block(character)?;
+                        let values_update = Arc::new(build_values_update_block(character)?);
                         character.push_object_update(values_update.clone());
                         values_update
                     };
 
-                    for in_range_guid in character_manager.get_character_mut(guid)?.get_in_range_characters().to_vec() {
+                    let mut update_receivers = std::mem::take(&mut self.guid_scratch);
+                    update_receivers.clear();
+                    update_receivers.extend_from_slice(character_manager.get_character(guid)?.get_in_range_characters());
+                    for &in_range_guid in &update_receivers {
                         let in_range_character = character_manager.get_character_mut(in_range_guid)?;
+                        //Visibility isn't always mutual (GM invisibility), only send updates to those that know about us
+                        if !in_range_character.is_in_range(guid) {
+                            continue;
+                        }
                         if let Some(update_receiver) = in_range_character.as_update_receiver_mut() {
                             update_receiver.push_object_update(values_update.clone());
                         }
                     }
+                    self.guid_scratch = update_receivers;
 
                     let character = character_manager.get_character_mut(guid)?;
                     character.clear_update_mask_header();
@@ -111,10 +224,202 @@ impl MapManager {
             }
             let character = character_manager.get_character_mut(guid)?;
             character.process_pending_updates().await?;
+            spell::auras::send_aura_snapshots(guid, character_manager).await?;
+        }
+        self.tick_guids = tick_guids;
+        Ok(())
+    }
+
+    async fn tick_creatures(&mut self, delta_time: f32, character_manager: &mut CharacterManager) -> Result<()> {
+        //Everyone looks around again so the characters near a respawned creature see it come back
+        if self.creatures.tick(delta_time) {
+            self.rebuild_creature_query_tree();
+            for &guid in &self.characters_on_map {
+                character_manager.get_character_mut(guid)?.request_visibility_update();
+            }
+        }
+        while let Some(guid) = self.creatures.take_decayed_corpse() {
+            self.despawn_creature(guid, character_manager).await?;
+        }
+        self.process_evading_creatures(character_manager).await?;
+
+        //Changes to creatures (health, flags) go to every character that has them in range
+        for (creature_guid, spawned) in self.creatures.iter() {
+            let mut creature = spawned.creature.write().await;
+            if !creature.gameplay_data.has_any_dirty_fields() {
+                continue;
+            }
+            let values_update = Arc::new(build_values_update_block(&*creature)?);
+            creature.clear_update_mask_header();
+            for &guid in &self.characters_on_map {
+                let character = character_manager.get_character_mut(guid)?;
+                if character.is_in_range(creature_guid) {
+                    character.push_object_update(values_update.clone());
+                }
+            }
         }
         Ok(())
     }
 
+    fn rebuild_creature_query_tree(&mut self) {
+        let query_items = self
+            .creatures
+            .iter()
+            .map(|(guid, spawned)| RStarTreeItem {
+                x: spawned.x,
+                y: spawned.y,
+                guid,
+            })
+            .collect();
+        self.creatures_query_tree = RTree::bulk_load(query_items);
+    }
+
+    pub fn get_creature(&self, guid: Guid) -> Option<&SharedCreature> {
+        self.creatures.get(guid).map(|spawned| &spawned.creature)
+    }
+
+    //Everyone looks around again so the characters near the rare see it appear
+    pub fn spawn_rare_creature(
+        &mut self,
+        rare_spawn: &DBRareSpawn,
+        template: &DBCreatureTemplate,
+        character_manager: &mut CharacterManager,
+    ) -> Result<()> {
+        self.creatures.spawn_rare(rare_spawn, template);
+        self.rebuild_creature_query_tree();
+        for &guid in &self.characters_on_map {
+            character_manager.get_character_mut(guid)?.request_visibility_update();
+        }
+        Ok(())
+    }
+
+    pub fn get_rare_spawn_id(&self, guid: Guid) -> Option<u32> {
+        self.creatures.get_rare_spawn_id(guid)
+    }
+
+    //The corpse stays where it fell until it decays, then the creature despawns until its respawn time. Killed
+    //bosses save everyone on the map to the instance and don't come back.
+    pub async fn on_creature_died(&mut self, guid: Guid, character_manager: &CharacterManager, realm_db: &RealmDatabase) -> Result<()> {
+        if let Some((encounter_id, EncounterState::InProgress)) = self.find_boss_encounter(guid).await {
+            self.creatures.prevent_respawn(guid);
+            self.kill_encounter(encounter_id, character_manager, realm_db).await?;
+        }
+        self.creatures.start_corpse_decay(guid);
+        Ok(())
+    }
+
+    //A character hit the creature, the first hit pulls it and starts its boss encounter
+    pub async fn on_creature_attacked(&mut self, guid: Guid, attacker: Guid, character_manager: &CharacterManager) -> Result<()> {
+        if !self.creatures.add_attacker(guid, attacker) {
+            return Ok(());
+        }
+        if let Some((encounter_id, EncounterState::NotStarted)) = self.find_boss_encounter(guid).await {
+            self.engage_encounter(encounter_id, guid, character_manager).await?;
+        }
+        Ok(())
+    }
+
+    //The encounter the creature is the boss of and the state it's in
+    async fn find_boss_encounter(&self, guid: Guid) -> Option<(u32, EncounterState)> {
+        let encounters = self.encounters.as_ref()?;
+        let entry = self.get_creature(guid)?.read().await.entry;
+        let encounter = encounters.find_by_boss_entry(entry)?;
+        Some((encounter.id(), encounter.state()))
+    }
+
+    //Creatures whose attackers all died or left heal back up, a boss that does so means the group wiped
+    async fn process_evading_creatures(&mut self, character_manager: &CharacterManager) -> Result<()> {
+        let characters_on_map = &self.characters_on_map;
+        let evading = self.creatures.take_evading(|attacker| {
+            characters_on_map.contains(&attacker) && character_manager.find_character(attacker).is_some_and(|character| character.is_alive())
+        });
+        for guid in evading {
+            if let Some(creature) = self.get_creature(guid) {
+                let mut creature = creature.write().await;
+                if creature.is_alive() {
+                    let max_health = creature.gameplay_data.unit_maxhealth().unwrap_or(1);
+                    creature.gameplay_data.set_unit_health(max_health);
+                }
+            }
+            if let Some((encounter_id, EncounterState::InProgress)) = self.find_boss_encounter(guid).await {
+                self.wipe_encounter(encounter_id, character_manager).await?;
+            }
+        }
+        Ok(())
+    }
+
+    //Takes a dead creature out of the world until it respawns
+    pub async fn despawn_creature(&mut self, guid: Guid, character_manager: &mut CharacterManager) -> Result<()> {
+        if self.creatures.despawn(guid).is_none() {
+            return Ok(());
+        }
+        self.rebuild_creature_query_tree();
+        for &character_guid in &self.characters_on_map {
+            let character = character_manager.get_character_mut(character_guid)?;
+            if character.is_in_range(guid) {
+                handlers::send_destroy_object(character, guid, false).await?;
+                character.remove_in_range_object(guid)?;
+            }
+        }
+        Ok(())
+    }
+
+    //Requests an update for the next characters in the rotation, on top of the ones that asked for one
+    fn schedule_visibility_updates(&mut self, character_manager: &mut CharacterManager) -> Result<()> {
+        let count = match self.visibility_budget {
+            0 => self.visibility_rotation.len(),
+            budget => budget.min(self.visibility_rotation.len()),
+        };
+        for _ in 0..count {
+            let guid = self.visibility_rotation[0];
+            self.visibility_rotation.rotate_left(1);
+            character_manager.get_character_mut(guid)?.request_visibility_update();
+        }
+        Ok(())
+    }
+
+    //Bosses that were killed before the instance was unloaded stay dead when it's loaded again
+    pub async fn restore_completed_encounters(&mut self, mask: u32) {
+        let Some(encounters) = self.encounters.as_mut() else {
+            return;
+        };
+        encounters.apply_completed_encounters_mask(mask);
+
+        let mut killed_bosses = Vec::new();
+        for (guid, _) in self.creatures.iter() {
+            if let Some((_, EncounterState::Done)) = self.find_boss_encounter(guid).await {
+                killed_bosses.push(guid);
+            }
+        }
+        for guid in killed_bosses {
+            self.creatures.prevent_respawn(guid);
+            self.creatures.despawn(guid);
+        }
+        self.rebuild_creature_query_tree();
+    }
+
+    async fn engage_encounter(&mut self, encounter_id: u32, boss_guid: Guid, character_manager: &CharacterManager) -> Result<()> {
+        let players: Vec<Guid> = self.characters_on_map.iter().copied().collect();
+        self.get_encounters_mut()?
+            .engage(encounter_id, boss_guid, &players, character_manager)
+            .await
+    }
+
+    async fn wipe_encounter(&mut self, encounter_id: u32, character_manager: &CharacterManager) -> Result<()> {
+        let players: Vec<Guid> = self.characters_on_map.iter().copied().collect();
+        self.get_encounters_mut()?.wipe(encounter_id, &players, character_manager).await
+    }
+
+    async fn kill_encounter(&mut self, encounter_id: u32, character_manager: &CharacterManager, realm_db: &RealmDatabase) -> Result<()> {
+        let players: Vec<Guid> = self.characters_on_map.iter().copied().collect();
+        self.get_encounters_mut()?.kill(encounter_id, &players, character_manager, realm_db).await
+    }
+
+    fn get_encounters_mut(&mut self) -> Result<&mut InstanceEncounters> {
+        let id = self.id;
+        self.encounters.as_mut().ok_or_else(|| anyhow!("Map {} has no encounters", id))
+    }
+
     pub fn push_character(&mut self, character: &Character) {
         self.add_queue.push(character.get_guid());
     }
@@ -123,13 +428,49 @@ impl MapManager {
         self.characters_on_map.contains(&guid)
     }
 
+    pub fn update_character_zone(&mut self, guid: Guid, zone: Area) {
+        if !self.characters_on_map.contains(&guid) {
+            return;
+        }
+        if let Some(old_zone) = self.character_zones.insert(guid, zone) {
+            self.remove_from_zone_index(guid, old_zone);
+        }
+        self.characters_by_zone.entry(zone).or_default().insert(guid);
+    }
+
+    fn remove_from_zone_index(&mut self, guid: Guid, zone: Area) {
+        if let Some(members) = self.characters_by_zone.get_mut(&zone) {
+            members.remove(&guid);
+            if members.is_empty() {
+                self.characters_by_zone.remove(&zone);
+            }
+        }
+    }
+
+    //Sends the event to everyone on the map, regardless of what they can see
+    pub async fn broadcast(&self, event: &ServerEvent, character_manager: &CharacterManager) -> Result<()> {
+        for &guid in &self.characters_on_map {
+            event.send_to_character(character_manager.get_character(guid)?).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn broadcast_to_zone(&self, zone: Area, event: &ServerEvent, character_manager: &CharacterManager) -> Result<()> {
+        for &guid in self.characters_by_zone.get(&zone).into_iter().flatten() {
+            event.send_to_character(character_manager.get_character(guid)?).await?;
+        }
+        Ok(())
+    }
+
     fn process_add_queue(&mut self, character_manager: &mut CharacterManager) -> Result<bool> {
         let has_any_added = !self.add_queue.is_empty();
 
-        for to_add in self.add_queue.clone() {
+        //Taken out so the characters can be pushed while going through it, and put back to keep its capacity
+        let mut add_queue = std::mem::take(&mut self.add_queue);
+        for to_add in add_queue.drain(..) {
             self.push_character_internal(to_add, character_manager)?;
         }
-        self.add_queue.clear();
+        self.add_queue = add_queue;
 
         Ok(has_any_added)
     }
@@ -138,6 +479,8 @@ impl MapManager {
         let character = character_manager.get_character(guid)?;
         let position = character.get_position().unwrap();
         self.characters_on_map.insert(guid);
+        self.visibility_rotation.push_back(guid);
+        let zone = character.area;
         let query_item = RStarTreeItem {
             x: position.position.x,
             y: position.position.y,
@@ -145,48 +488,80 @@ impl MapManager {
         };
         self.characters_query_tree.insert(query_item);
 
+        self.update_character_zone(guid, zone);
         character_manager.get_character_mut(guid)?.on_pushed_to_map(self)?;
         Ok(())
     }
 
-    async fn update_in_range_set(&self, guid: Guid, character_manager: &mut CharacterManager) -> Result<()> {
+    pub async fn update_in_range_set(&mut self, guid: Guid, character_manager: &mut CharacterManager) -> Result<()> {
         //Check if this object even has positional data
-        if character_manager.get_character(guid)?.get_position().is_none() {
+        let Some(position) = character_manager.get_character(guid)?.get_position() else {
             return Ok(());
-        }
-        let tree = &self.characters_query_tree;
-        let within_range: Vec<Guid> = {
-            //Safe to unwrap because we checked it before
-            let character = character_manager.get_character(guid)?;
-            let position = character.get_position().unwrap().position;
-            tree.locate_within_distance([position.x, position.y], VISIBILITY_RANGE)
-                .map(|a| a.guid)
-                .collect()
         };
+        let far_sight_anchor = self.get_far_sight_anchor(guid, character_manager)?;
+        let visibility_range_squared = self.visibility_range_squared;
+        let mut within_range = std::mem::take(&mut self.within_range_scratch);
+        within_range.clear();
+        {
+            let character = character_manager.get_character(guid)?;
+            let can_see = |other_guid: Guid| {
+                other_guid == guid
+                    || character_manager
+                        .get_character(other_guid)
+                        .is_ok_and(|other_character| share_phase(character, other_character) && character.can_see_character(other_character))
+            };
+            let position = position.position;
+            within_range.extend(
+                self.characters_query_tree
+                    .locate_within_distance([position.x, position.y], visibility_range_squared)
+                    .map(|a| a.guid)
+                    .filter(|&other_guid| can_see(other_guid)),
+            );
+            let phase_mask = character.get_phase_mask();
+            within_range.extend(
+                self.creatures_query_tree
+                    .locate_within_distance([position.x, position.y], visibility_range_squared)
+                    .map(|a| a.guid)
+                    .filter(|&creature_guid| self.creatures.get(creature_guid).is_some_and(|c| c.phase_mask & phase_mask != 0)),
+            );
+
+            //The character keeps seeing what's around its body while it looks somewhere else
+            if let Some(anchor) = far_sight_anchor {
+                let around_anchor: Vec<Guid> = self
+                    .characters_query_tree
+                    .locate_within_distance([anchor.x, anchor.y], visibility_range_squared)
+                    .map(|a| a.guid)
+                    .filter(|other_guid| !within_range.contains(other_guid) && can_see(*other_guid))
+                    .collect();
+                within_range.extend(around_anchor);
+            }
+        }
 
         //Remove objects that we have in our in-range-list but that are no longer in range
         //according to the data tree
         {
-            let mut destroyed_guids = vec![];
-            {
-                let character = character_manager.get_character_mut(guid)?;
-                let in_range_list: Vec<Guid> = character.get_in_range_guids();
-                for guid in in_range_list {
-                    if !within_range.contains(&guid) {
-                        character.remove_in_range_object(guid)?;
+            let mut destroyed_guids = std::mem::take(&mut self.guid_scratch);
+            destroyed_guids.clear();
+            destroyed_guids.extend(
+                character_manager
+                    .get_character(guid)?
+                    .get_in_range_guids()
+                    .filter(|in_range_guid| !within_range.contains(in_range_guid)),
+            );
 
-                        destroyed_guids.push(guid);
-                    }
-                }
+            let character = character_manager.get_character_mut(guid)?;
+            for &destroyed_guid in &destroyed_guids {
+                character.remove_in_range_object(destroyed_guid)?;
             }
 
-            for guid in destroyed_guids {
-                let character = character_manager.get_character(guid)?;
-                handlers::send_destroy_object(character, guid, false).await?;
+            let character = character_manager.get_character(guid)?;
+            for &destroyed_guid in &destroyed_guids {
+                handlers::send_destroy_object(character, destroyed_guid, false).await?;
             }
+            self.guid_scratch = destroyed_guids;
         }
 
-        for in_range_guid in within_range {
+        for &in_range_guid in &within_range {
             {
                 if in_range_guid == guid {
                     //skip ourselves
@@ -202,16 +577,38 @@ impl MapManager {
 
             trace!("New object in range! Guid: {}", in_range_guid);
 
-            {
-                let other_character = character_manager.get_character_mut(in_range_guid)?;
-                other_character.add_in_range_character(guid)?;
+            //Creatures don't look around, only the character learns about them
+            if let Some(spawned) = self.creatures.get(in_range_guid) {
+                let creature = spawned.creature.read().await;
+                let character = character_manager.get_character_mut(guid)?;
+                let create_block = Arc::new(build_create_update_block_for_player(character, &*creature)?);
+                let shared: Arc<RwLock<dyn GameObject>> = spawned.creature.clone();
+                character.add_in_range_object(in_range_guid, Arc::downgrade(&shared))?;
+                character.push_object_update(create_block);
+                continue;
             }
-            {
+
+            let other_can_see_us = {
                 let other_character = character_manager.get_character(in_range_guid)?;
                 let character = character_manager.get_character(guid)?;
-                let create_block = build_create_update_block_for_player(other_character, character)?;
-                let other_character = character_manager.get_character_mut(in_range_guid)?;
-                other_character.push_object_update(create_block);
+                //Far sight makes visibility one-sided, the other side only learns about us when we're actually close
+                !other_character.is_in_range(guid)
+                    && within_visibility_range(other_character, character, self.visibility_range_squared)
+                    && share_phase(other_character, character)
+                    && other_character.can_see_character(character)
+            };
+            if other_can_see_us {
+                {
+                    let other_character = character_manager.get_character_mut(in_range_guid)?;
+                    other_character.add_in_range_character(guid)?;
+                }
+                {
+                    let other_character = character_manager.get_character(in_range_guid)?;
+                    let character = character_manager.get_character(guid)?;
+                    let create_block = Arc::new(build_create_update_block_for_player(other_character, character)?);
+                    let other_character = character_manager.get_character_mut(in_range_guid)?;
+                    other_character.push_object_update(create_block);
+                }
             }
             {
                 let character = character_manager.get_character_mut(guid)?;
@@ -220,21 +617,42 @@ impl MapManager {
             {
                 let other_character = character_manager.get_character(in_range_guid)?;
                 let character = character_manager.get_character(guid)?;
-                let create_block = build_create_update_block_for_player(character, other_character)?;
+                let create_block = Arc::new(build_create_update_block_for_player(character, other_character)?);
                 let character = character_manager.get_character_mut(guid)?;
                 character.push_object_update(create_block);
             }
         }
 
+        self.within_range_scratch = within_range;
         Ok(())
     }
 
+    //The point the far sight of the character looks at, if it has any. Sight bound to a unit that left the map ends.
+    fn get_far_sight_anchor(&self, guid: Guid, character_manager: &mut CharacterManager) -> Result<Option<Vector3d>> {
+        let anchor = match character_manager.get_character(guid)?.get_far_sight_target() {
+            None => return Ok(None),
+            Some(FarSightTarget::DynamicObject { position, .. }) => Some(position),
+            Some(FarSightTarget::Unit(unit_guid)) => self
+                .characters_on_map
+                .contains(&unit_guid)
+                .then(|| character_manager.get_character(unit_guid).ok()?.get_position())
+                .flatten()
+                .map(|position| position.position),
+        };
+
+        if anchor.is_none() {
+            character_manager.get_character_mut(guid)?.end_far_sight();
+        }
+        Ok(anchor)
+    }
+
     fn rebuild_object_querying_tree(&mut self, character_manager: &CharacterManager) -> Result<()> {
-        let mut obj_list = vec![];
+        let mut query_items = std::mem::take(&mut self.query_items_scratch);
+        query_items.clear();
         for guid in self.characters_on_map.iter().copied() {
             let character = character_manager.get_character(guid)?;
             if let Some(position) = character.get_position() {
-                obj_list.push(RStarTreeItem {
+                query_items.push(RStarTreeItem {
                     guid,
                     x: position.position.x,
                     y: position.position.y,
@@ -242,7 +660,12 @@ impl MapManager {
             }
         }
 
-        self.characters_query_tree = RTree::bulk_load(obj_list);
+        //Nobody moved, arrived or left since the last rebuild, so the tree is still correct
+        if query_items != self.query_items {
+            self.characters_query_tree = RTree::bulk_load(query_items.clone());
+            std::mem::swap(&mut self.query_items, &mut query_items);
+        }
+        self.query_items_scratch = query_items;
         Ok(())
     }
 
@@ -252,22 +675,31 @@ impl MapManager {
 
     async fn process_remove_queue(&mut self, character_manager: &mut CharacterManager) -> Result<bool> {
         let any_to_remove = !self.remove_queue.is_empty();
-        for to_remove in self.remove_queue.clone() {
+        let mut remove_queue = std::mem::take(&mut self.remove_queue);
+        for to_remove in remove_queue.drain(..) {
             self.remove_object_by_guid_internal(to_remove, character_manager).await?;
         }
-        self.remove_queue.clear();
+        self.remove_queue = remove_queue;
         Ok(any_to_remove)
     }
 
     async fn remove_object_by_guid_internal(&mut self, guid: Guid, character_manager: &mut CharacterManager) -> Result<()> {
         if self.characters_on_map.remove(&guid) {
+            self.visibility_rotation.retain(|&g| g != guid);
+            if let Some(zone) = self.character_zones.remove(&guid) {
+                self.remove_from_zone_index(guid, zone);
+            }
             if character_manager.find_character(guid).is_some() {
-                let in_range_guids = {
-                    let removed_character = character_manager.get_character(guid)?;
-                    removed_character.get_in_range_guids().to_vec()
-                };
-
-                for &in_range_guid in in_range_guids.iter().filter_map(|g| self.characters_on_map.get(g)) {
+                //Visibility isn't always mutual, so look for everyone that knows about the removed character
+                //instead of going through its own in-range list
+                let in_range_guids: Vec<Guid> = self
+                    .characters_on_map
+                    .iter()
+                    .copied()
+                    .filter(|&g| character_manager.get_character(g).is_ok_and(|c| c.is_in_range(guid)))
+                    .collect();
+
+                for in_range_guid in in_range_guids {
                     let in_range_character = character_manager.get_character_mut(in_range_guid)?;
                     handlers::send_destroy_object(in_range_character, guid, false).await?;
                     trace!("removed {} from range of {}", guid, in_range_guid);
@@ -277,12 +709,13 @@ impl MapManager {
                 let removed_character = character_manager.get_character_mut(guid)?;
                 removed_character.clear_in_range_objects();
             } else {
-                //Failed to find character. This means the object is really gone, and we
-                //can't access its in-range-list anymore. Bruteforce the removal from everything on
-                //this map.
+                //Failed to find character. This means the object is really gone, e.g. evicted after a
+                //disconnect, and we can't access its in-range-list anymore. Bruteforce the removal from
+                //everything on this map.
                 for &character_guid in self.characters_on_map.iter() {
                     let character = character_manager.get_character_mut(character_guid)?;
                     if character.is_in_range(guid) {
+                        handlers::send_destroy_object(character, guid, false).await?;
                         character.remove_in_range_object(guid)?;
                     }
                 }
@@ -293,3 +726,162 @@ impl MapManager {
         Ok(())
     }
 }
+
+fn within_visibility_range(object: &dyn GameObject, other: &dyn GameObject, range_squared: f32) -> bool {
+    match (object.get_position(), other.get_position()) {
+        (Some(a), Some(b)) => {
+            let (dx, dy) = (a.position.x - b.position.x, a.position.y - b.position.y);
+            //Compared like the query tree does, which takes the range as a squared distance
+            dx * dx + dy * dy <= range_squared
+        }
+        _ => false,
+    }
+}
+
+fn share_phase(object: &dyn GameObject, other: &dyn GameObject) -> bool {
+    object.get_phase_mask() & other.get_phase_mask() != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::count_allocations;
+    use wow_world_messages::wrath::Vector3d;
+
+    fn populated_map(positions: &[(f32, f32)]) -> (MapManager, CharacterManager, flume::Receiver<crate::connection::events::ServerEvent>) {
+        let (connection_sender, connection_receiver) = flume::unbounded();
+        let mut character_manager = CharacterManager::new();
+        let mut map = MapManager::new(0);
+        for (i, &(x, y)) in positions.iter().enumerate() {
+            let mut character = Character::new(connection_sender.clone(), Guid::new(i as u64 + 1));
+            character.movement_info.position = Vector3d { x, y, z: 0.0 };
+            map.push_character(&character);
+            character_manager.add_character(character);
+        }
+        (map, character_manager, connection_receiver)
+    }
+
+    #[test]
+    fn steady_state_tick_does_not_allocate() {
+        let (mut map, mut character_manager, connection_receiver) = populated_map(&[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0), (9000.0, 0.0)]);
+
+        //The first ticks put everyone on the map, build the query tree and size the reusable buffers
+        for _ in 0..3 {
+            count_allocations(map.tick(0.1, &mut character_manager)).0.unwrap();
+        }
+        connection_receiver.drain().for_each(drop);
+
+        let (result, allocations) = count_allocations(map.tick(0.1, &mut character_manager));
+        result.unwrap();
+        assert_eq!(allocations, 0);
+        assert!(connection_receiver.is_empty());
+    }
+
+    #[test]
+    fn queues_are_processed_and_keep_working() {
+        let (mut map, mut character_manager, _connection_receiver) = populated_map(&[(0.0, 0.0), (10.0, 0.0), (9000.0, 0.0)]);
+        let (first, second, far_away) = (Guid::new(1), Guid::new(2), Guid::new(3));
+
+        count_allocations(map.tick(0.1, &mut character_manager)).0.unwrap();
+        assert!(map.find_character(first) && map.find_character(second) && map.find_character(far_away));
+        assert!(character_manager.get_character(first).unwrap().is_in_range(second));
+        assert!(!character_manager.get_character(first).unwrap().is_in_range(far_away));
+
+        map.remove_object_by_guid(second);
+        count_allocations(map.tick(0.1, &mut character_manager)).0.unwrap();
+        assert!(!map.find_character(second));
+        assert!(!character_manager.get_character(first).unwrap().is_in_range(second));
+
+        //Moving into range is picked up even though the tree is only rebuilt when something changed
+        character_manager.get_character_mut(far_away).unwrap().movement_info.position.x = 20.0;
+        count_allocations(map.tick(0.1, &mut character_manager)).0.unwrap();
+        assert!(character_manager.get_character(first).unwrap().is_in_range(far_away));
+    }
+
+    #[test]
+    fn visibility_updates_take_turns() {
+        let (mut map, mut character_manager, _connection_receiver) = populated_map(&[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)]);
+        smol::block_on(map.tick(0.0, &mut character_manager)).unwrap();
+        map = map.with_visibility_budget(1);
+
+        //With a budget of one every character gets its turn once every three ticks
+        let mut turns = Vec::new();
+        for _ in 0..3 {
+            map.schedule_visibility_updates(&mut character_manager).unwrap();
+            let due: Vec<u64> = (1..=3)
+                .filter(|&guid| {
+                    character_manager
+                        .get_character_mut(Guid::new(guid))
+                        .unwrap()
+                        .take_visibility_update_request()
+                })
+                .collect();
+            assert_eq!(due.len(), 1);
+            turns.extend(due);
+        }
+        turns.sort();
+        assert_eq!(turns, [1, 2, 3]);
+
+        //A teleport is picked up on the next tick, even when it's someone else's turn
+        let teleported = character_manager.get_character_mut(Guid::new(1)).unwrap();
+        teleported.set_position(&crate::data::PositionAndOrientation {
+            position: Vector3d { x: 20000.0, y: 0.0, z: 0.0 },
+            orientation: 0.0,
+        });
+        smol::block_on(map.tick(0.0, &mut character_manager)).unwrap();
+        assert!(!character_manager.get_character(Guid::new(1)).unwrap().is_in_range(Guid::new(2)));
+    }
+
+    #[test]
+    fn zone_broadcast_reaches_only_the_zone() {
+        let (mut map, mut character_manager, connection_receiver) = populated_map(&[(0.0, 0.0), (10.0, 0.0), (9000.0, 0.0)]);
+        smol::block_on(map.tick(0.0, &mut character_manager)).unwrap();
+        map.update_character_zone(Guid::new(3), Area::NorthshireValley);
+        connection_receiver.drain();
+
+        let event = ServerEvent::RaidInstanceInfo(wow_world_messages::wrath::SMSG_RAID_INSTANCE_INFO { raid_infos: vec![] });
+        smol::block_on(map.broadcast_to_zone(Area::NorthshireAbbey, &event, &character_manager)).unwrap();
+        assert_eq!(connection_receiver.drain().count(), 2);
+        smol::block_on(map.broadcast(&event, &character_manager)).unwrap();
+        assert_eq!(connection_receiver.drain().count(), 3);
+
+        //Leaving the map takes the character out of its zone as well
+        map.remove_object_by_guid(Guid::new(3));
+        smol::block_on(map.tick(0.0, &mut character_manager)).unwrap();
+        connection_receiver.drain();
+        smol::block_on(map.broadcast_to_zone(Area::NorthshireValley, &event, &character_manager)).unwrap();
+        assert_eq!(connection_receiver.drain().count(), 0);
+    }
+
+    #[test]
+    fn simulation_waits_for_its_interval() {
+        let mut map = MapManager::new(0).with_simulation_interval(0.1);
+        assert_eq!(map.advance_simulation_clock(0.02), None);
+        assert_eq!(map.advance_simulation_clock(0.05), None);
+        let simulated = map.advance_simulation_clock(0.04).unwrap();
+        assert!((simulated - 0.11).abs() < 1e-6);
+        assert_eq!(map.advance_simulation_clock(0.02), None);
+    }
+
+    #[test]
+    fn far_sight_streams_visibility_around_the_target() {
+        let (mut map, mut character_manager, _connection_receiver) = populated_map(&[(0.0, 0.0), (9000.0, 0.0), (20000.0, 0.0)]);
+        let (seer, watched, far_away) = (Guid::new(1), Guid::new(2), Guid::new(3));
+        count_allocations(map.tick(0.1, &mut character_manager)).0.unwrap();
+        assert!(!character_manager.get_character(seer).unwrap().is_in_range(watched));
+
+        character_manager
+            .get_character_mut(seer)
+            .unwrap()
+            .start_far_sight(FarSightTarget::Unit(watched), None);
+        count_allocations(map.tick(0.1, &mut character_manager)).0.unwrap();
+        let seer_character = character_manager.get_character(seer).unwrap();
+        assert!(seer_character.is_in_range(watched));
+        assert!(!seer_character.is_in_range(far_away));
+
+        //Once the watched character is gone the sight ends and the camera is back home
+        map.remove_object_by_guid(watched);
+        count_allocations(map.tick(0.1, &mut character_manager)).0.unwrap();
+        assert_eq!(character_manager.get_character(seer).unwrap().get_far_sight_target(), None);
+    }
+}
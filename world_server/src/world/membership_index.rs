@@ -0,0 +1,101 @@
+//! Reverse indexes from guilds and chat channels to the characters in them that are online, so sending
+//! something to a guild or channel only touches its members instead of every client.
+//!
+//! Members join when they enter the world (their guild) or join a channel, and leave everything at once
+//! when they log out.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::character::character_manager::CharacterManager;
+use crate::connection::events::ServerEvent;
+use crate::prelude::*;
+
+pub struct MembershipIndex<K> {
+    members: HashMap<K, HashSet<Guid>>,
+    memberships: HashMap<Guid, Vec<K>>,
+}
+
+impl<K> Default for MembershipIndex<K> {
+    fn default() -> Self {
+        Self {
+            members: HashMap::new(),
+            memberships: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> MembershipIndex<K> {
+    pub fn join(&mut self, key: K, guid: Guid) {
+        if self.members.entry(key.clone()).or_default().insert(guid) {
+            self.memberships.entry(guid).or_default().push(key);
+        }
+    }
+
+    pub fn leave(&mut self, key: &K, guid: Guid) {
+        self.remove_member(key, guid);
+        if let Some(keys) = self.memberships.get_mut(&guid) {
+            keys.retain(|k| k != key);
+            if keys.is_empty() {
+                self.memberships.remove(&guid);
+            }
+        }
+    }
+
+    //On logout, the character is no longer an online member of anything
+    pub fn leave_all(&mut self, guid: Guid) {
+        for key in self.memberships.remove(&guid).unwrap_or_default() {
+            self.remove_member(&key, guid);
+        }
+    }
+
+    fn remove_member(&mut self, key: &K, guid: Guid) {
+        if let Some(members) = self.members.get_mut(key) {
+            members.remove(&guid);
+            if members.is_empty() {
+                self.members.remove(key);
+            }
+        }
+    }
+
+    pub fn members(&self, key: &K) -> impl Iterator<Item = Guid> + '_ {
+        self.members.get(key).into_iter().flatten().copied()
+    }
+
+    pub fn memberships(&self, guid: Guid) -> &[K] {
+        self.memberships.get(&guid).map_or(&[], Vec::as_slice)
+    }
+
+    #[allow(dead_code)]
+    pub async fn send_to_members(&self, key: &K, event: &ServerEvent, character_manager: &CharacterManager) -> Result<()> {
+        for guid in self.members(key) {
+            event.send_to_character(character_manager.get_character(guid)?).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logout_leaves_every_membership() {
+        let mut channels = MembershipIndex::<String>::default();
+        let (first, second) = (Guid::new(1), Guid::new(2));
+        channels.join("trade".into(), first);
+        channels.join("trade".into(), first);
+        channels.join("lookingforgroup".into(), first);
+        channels.join("trade".into(), second);
+        assert_eq!(channels.members(&"trade".into()).count(), 2);
+        assert_eq!(channels.memberships(first).len(), 2);
+
+        channels.leave(&"trade".into(), second);
+        assert_eq!(channels.members(&"trade".into()).collect::<Vec<_>>(), [first]);
+
+        channels.leave_all(first);
+        assert_eq!(channels.members(&"trade".into()).count(), 0);
+        assert_eq!(channels.members(&"lookingforgroup".into()).count(), 0);
+        assert!(channels.memberships(first).is_empty());
+    }
+}
@@ -1,17 +1,61 @@
-use crate::{character::character_manager::CharacterManager, prelude::*};
-use instance_manager::InstanceManager;
+use crate::{
+    character::character_manager::CharacterManager,
+    chat::{channels::ChannelManager, logging::ChatLogger, moderation::ChatModeration},
+    connection::events::ServerEvent,
+    notifications::{Notification, Notifier},
+    prelude::*,
+};
+use account_data::AccountDataService;
+use character_info_cache::CharacterInfoCache;
+use creature_manager::CreatureSpawns;
+use gathering::GatheringNodes;
+use gossip_menus::GossipMenus;
+use group_loot::LootRolls;
+use groups::{Group, GroupManager};
+use guilds::GuildManager;
+use instance_manager::{InstanceManager, DEFAULT_INSTANCE_DIFFICULTY};
+use interactive_objects::InteractiveObjects;
+use loot::LootTemplates;
+use mail::MailExpiry;
+use membership_index::MembershipIndex;
+use persistence_queue::RealmPersistenceQueue;
+use points_of_interest::PointsOfInterest;
+use rare_spawns::RareSpawnScheduler;
 use std::sync::Arc;
+use uptime::UptimeTracker;
+use vendors::Vendors;
+use wow_world_messages::wrath::{Area, Map};
 use wrath_game_db::GameDatabase;
 use wrath_realm_db::RealmDatabase;
 
+pub mod account_data;
+pub mod character_info_cache;
+pub mod creature;
+pub mod creature_manager;
+pub mod encounter;
 pub mod game_object;
+pub mod gathering;
+pub mod gossip_menus;
+pub mod group_loot;
+pub mod groups;
+pub mod guilds;
 mod instance_manager;
+pub mod interactive_objects;
+pub mod loot;
+pub mod mail;
 mod map_manager;
+pub mod membership_index;
+pub mod persistence_queue;
+pub mod points_of_interest;
+mod rare_spawns;
 mod update_builder;
+pub mod uptime;
+pub mod vendors;
 
 pub mod prelude {
     pub use super::super::constants::*;
     pub use super::game_object::*;
+    pub use super::instance_manager::SimulationRates;
     pub use super::map_manager::*;
     pub use super::update_builder::*;
     pub use super::World;
@@ -21,6 +65,27 @@ pub struct World {
     instance_manager: InstanceManager,
     game_db: Arc<GameDatabase>,
     realm_db: Arc<RealmDatabase>,
+    persistence_queue: RealmPersistenceQueue,
+    chat_moderation: ChatModeration,
+    chat_logger: ChatLogger,
+    rare_spawns: RareSpawnScheduler,
+    gathering_nodes: GatheringNodes,
+    loot_rolls: LootRolls,
+    loot_templates: LootTemplates,
+    vendors: Vendors,
+    mail_expiry: MailExpiry,
+    interactive_objects: InteractiveObjects,
+    points_of_interest: PointsOfInterest,
+    gossip_menus: GossipMenus,
+    character_info_cache: CharacterInfoCache,
+    guilds: GuildManager,
+    //Online members by guild id
+    guild_members: MembershipIndex<u32>,
+    groups: GroupManager,
+    channels: ChannelManager,
+    account_data: AccountDataService,
+    notifier: Notifier,
+    uptime: UptimeTracker,
 }
 
 impl World {
@@ -28,6 +93,26 @@ impl World {
         Self {
             instance_manager: InstanceManager::new(),
             game_db,
+            persistence_queue: RealmPersistenceQueue::new(realm_db.clone()),
+            chat_moderation: ChatModeration::from_env(),
+            chat_logger: ChatLogger::from_env(realm_db.clone()),
+            rare_spawns: RareSpawnScheduler::new(),
+            gathering_nodes: GatheringNodes::default(),
+            loot_rolls: LootRolls::default(),
+            loot_templates: LootTemplates::default(),
+            vendors: Vendors::default(),
+            mail_expiry: MailExpiry::default(),
+            interactive_objects: InteractiveObjects::default(),
+            points_of_interest: PointsOfInterest::default(),
+            gossip_menus: GossipMenus::default(),
+            character_info_cache: CharacterInfoCache::default(),
+            guilds: GuildManager::default(),
+            guild_members: MembershipIndex::default(),
+            groups: GroupManager::default(),
+            channels: ChannelManager::default(),
+            account_data: AccountDataService::new(realm_db.clone()),
+            notifier: Notifier::from_env(),
+            uptime: UptimeTracker::new(),
             realm_db,
         }
     }
@@ -48,8 +133,241 @@ impl World {
         self.realm_db.clone()
     }
 
+    pub fn get_persistence_queue(&self) -> &RealmPersistenceQueue {
+        &self.persistence_queue
+    }
+
+    pub fn get_chat_moderation(&self) -> &ChatModeration {
+        &self.chat_moderation
+    }
+
+    //Settings that can change at runtime are read from the environment again, e.g. after a SIGHUP
+    pub fn reload_config(&mut self) {
+        self.chat_moderation = ChatModeration::from_env();
+    }
+
+    pub fn get_chat_logger(&self) -> &ChatLogger {
+        &self.chat_logger
+    }
+
+    pub fn get_character_info_cache(&self) -> &CharacterInfoCache {
+        &self.character_info_cache
+    }
+
+    pub fn get_character_info_cache_mut(&mut self) -> &mut CharacterInfoCache {
+        &mut self.character_info_cache
+    }
+
+    pub fn get_uptime(&self) -> &UptimeTracker {
+        &self.uptime
+    }
+
+    pub fn get_account_data(&self) -> &AccountDataService {
+        &self.account_data
+    }
+
+    pub fn get_account_data_mut(&mut self) -> &mut AccountDataService {
+        &mut self.account_data
+    }
+
+    pub fn get_guilds(&self) -> &GuildManager {
+        &self.guilds
+    }
+
+    pub fn get_guilds_mut(&mut self) -> &mut GuildManager {
+        &mut self.guilds
+    }
+
+    pub fn get_guild_members(&self) -> &MembershipIndex<u32> {
+        &self.guild_members
+    }
+
+    pub fn get_guild_members_mut(&mut self) -> &mut MembershipIndex<u32> {
+        &mut self.guild_members
+    }
+
+    pub fn get_groups(&self) -> &GroupManager {
+        &self.groups
+    }
+
+    pub fn get_groups_mut(&mut self) -> &mut GroupManager {
+        &mut self.groups
+    }
+
+    pub fn get_channels(&self) -> &ChannelManager {
+        &self.channels
+    }
+
+    pub fn get_channels_mut(&mut self) -> &mut ChannelManager {
+        &mut self.channels
+    }
+
+    //The members of the character's group with the leader first, None if it isn't in one.
+    pub fn get_group_members(&self, guid: Guid) -> Option<Vec<Guid>> {
+        self.groups.get_group_of(guid).map(Group::get_members)
+    }
+
+    //Called when a character enters the world, it shows up online in its guild
+    pub fn on_character_entered_world(&mut self, guid: Guid, guild_id: Option<u32>) {
+        if let Some(guild_id) = guild_id {
+            self.guild_members.join(guild_id, guid);
+        }
+    }
+
+    //Called on logout and disconnect, the character leaves every online group
+    pub fn on_character_left_world(&mut self, guid: Guid) {
+        self.guild_members.leave_all(guid);
+        self.channels.leave_all(guid);
+        self.groups.on_character_left_world(guid);
+    }
+
+    pub async fn load(&mut self) -> Result<()> {
+        self.uptime.record_start(&self.realm_db).await?;
+        self.character_info_cache.load(&self.realm_db).await?;
+        self.guilds.load(&self.realm_db).await?;
+        let creature_spawns = CreatureSpawns::load(&self.game_db).await?;
+        self.instance_manager.set_creature_spawns(creature_spawns);
+        self.instance_manager.get_encounter_scripts_mut().load(&self.game_db).await?;
+        self.instance_manager.load_instance_ids(&self.realm_db).await?;
+        self.loot_templates.load(&self.game_db).await?;
+        self.vendors.load(&self.game_db, &self.realm_db).await?;
+        self.rare_spawns.load(&self.game_db, &self.realm_db).await?;
+        self.gathering_nodes.load(&self.game_db).await?;
+        self.interactive_objects.load(&self.game_db).await?;
+        game_object::register_gameobject_spawns(&self.game_db, &mut self.gathering_nodes, &mut self.interactive_objects).await?;
+        self.points_of_interest.load(&self.game_db).await?;
+        self.gossip_menus.load(&self.game_db).await
+    }
+
+    //Instanced maps come in copies, this picks the character's copy before it's added to the map: the one it's
+    //saved to, otherwise the one its group is in, otherwise a new one
+    pub async fn assign_instance(&mut self, guid: Guid, map: Map, character_manager: &mut CharacterManager) -> Result<()> {
+        if !self.instance_manager.is_instance(map) {
+            return Ok(());
+        }
+        let difficulty = DEFAULT_INSTANCE_DIFFICULTY;
+        let binds = self.realm_db.get_character_instance_binds(guid.guid() as u32).await?;
+        let bound_instance = binds
+            .iter()
+            .find(|bind| bind.map_id == map.as_int() && bind.difficulty == difficulty)
+            .map(|bind| bind.instance_id);
+        let group_instance = || {
+            self.get_group_members(guid)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&member| member != guid)
+                .filter_map(|member| character_manager.find_character(member))
+                .find(|member| member.map == map && member.instance_id != 0)
+                .map(|member| member.instance_id)
+        };
+        let instance_id = match bound_instance.or_else(group_instance) {
+            Some(instance_id) => instance_id,
+            None => self.instance_manager.create_instance_id(),
+        };
+
+        self.instance_manager
+            .get_or_load_instance(map, instance_id, difficulty, &self.realm_db)
+            .await?;
+        character_manager.get_character_mut(guid)?.instance_id = instance_id;
+        Ok(())
+    }
+
+    //Called by the creature system when a rare spawn dies, so its respawn timer starts and the staff hears about it
+    pub async fn on_rare_spawn_killed(&mut self, spawn_id: u32) -> Result<()> {
+        let spawn = self.rare_spawns.on_rare_spawn_killed(spawn_id, &self.realm_db).await?;
+        self.notifier.notify(Notification::WorldBossKilled {
+            creature_entry: spawn.creature_entry,
+            map: spawn.map as u32,
+        });
+        Ok(())
+    }
+
+    pub fn get_gathering_nodes(&self) -> &GatheringNodes {
+        &self.gathering_nodes
+    }
+
+    pub fn get_gathering_nodes_mut(&mut self) -> &mut GatheringNodes {
+        &mut self.gathering_nodes
+    }
+
+    pub fn get_loot_rolls_mut(&mut self) -> &mut LootRolls {
+        &mut self.loot_rolls
+    }
+
+    pub fn get_loot_templates(&self) -> &LootTemplates {
+        &self.loot_templates
+    }
+
+    pub fn get_vendors(&self) -> &Vendors {
+        &self.vendors
+    }
+
+    pub fn get_vendors_mut(&mut self) -> &mut Vendors {
+        &mut self.vendors
+    }
+
+    pub fn get_interactive_objects(&self) -> &InteractiveObjects {
+        &self.interactive_objects
+    }
+
+    pub fn get_points_of_interest(&self) -> &PointsOfInterest {
+        &self.points_of_interest
+    }
+
+    pub fn get_gossip_menus(&self) -> &GossipMenus {
+        &self.gossip_menus
+    }
+
+    pub fn get_notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+
+    //For world events, weather and announcements that concern a whole map, every instance of it included
+    pub async fn broadcast_to_map(&self, map: Map, event: &ServerEvent, character_manager: &CharacterManager) -> Result<()> {
+        for map_manager in self
+            .instance_manager
+            .get_maps()
+            .filter(|map_manager| map_manager.get_id() == map.as_int())
+        {
+            map_manager.broadcast(event, character_manager).await?;
+        }
+        Ok(())
+    }
+
+    //For zone defense messages and zone weather, zones are unique across maps
+    pub async fn broadcast_to_zone(&self, zone: Area, event: &ServerEvent, character_manager: &CharacterManager) -> Result<()> {
+        for map_manager in self.instance_manager.get_maps() {
+            map_manager.broadcast_to_zone(zone, event, character_manager).await?;
+        }
+        Ok(())
+    }
+
     pub async fn tick(&mut self, character_manager: &mut CharacterManager, delta_time: f32) -> Result<()> {
         self.instance_manager.tick(character_manager, delta_time).await?;
+        self.rare_spawns.tick(delta_time, character_manager).await?;
+        let instance_manager = &mut self.instance_manager;
+        for rare_spawn in self.rare_spawns.take_pending_spawns(|map| instance_manager.is_world_map_running(map)) {
+            instance_manager.spawn_rare_creature(rare_spawn, character_manager)?;
+        }
+        self.gathering_nodes.tick(delta_time);
+        self.loot_rolls
+            .tick(
+                delta_time,
+                character_manager,
+                &self.realm_db,
+                &self.persistence_queue,
+                &self.instance_manager,
+            )
+            .await?;
+        self.mail_expiry
+            .tick(delta_time, character_manager, &self.realm_db)
+            .await
+            .unwrap_or_else(|e| warn!("Failed to expire mail: {}", e));
+        self.uptime
+            .tick(delta_time, character_manager.get_all_characters().count(), &self.realm_db)
+            .await
+            .unwrap_or_else(|e| warn!("Failed to update the uptime table: {}", e));
+        self.persistence_queue.end_tick();
         Ok(())
     }
 }
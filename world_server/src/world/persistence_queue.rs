@@ -0,0 +1,141 @@
+//! Write-behind queue for realm database mutations coming from the game loop.
+//!
+//! Inventory shuffling used to await a DELETE and an INSERT for every slot that changed, which
+//! stalls the tick on MySQL. Mutations are now handed to a background worker instead. The worker
+//! coalesces everything it receives during a tick (only the last state of every slot matters),
+//! writes it in one batch once the tick ends and retries failed batches before giving up.
+//! Characters leaving the world save their position and playtime the same way.
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::prelude::*;
+use wrath_realm_db::{character::DBCharacterUpdate, item_instance::DBCharacterItemChange, RealmDatabase};
+
+const MAX_FLUSH_ATTEMPTS: u32 = 5;
+const RETRY_BACKOFF_MILLIS: u64 = 200;
+
+enum PersistenceMessage {
+    CharacterItem(DBCharacterItemChange),
+    CharacterPosition(DBCharacterUpdate),
+    EndOfTick,
+    Shutdown(flume::Sender<()>),
+}
+
+pub struct RealmPersistenceQueue {
+    sender: flume::Sender<PersistenceMessage>,
+}
+
+impl RealmPersistenceQueue {
+    pub fn new(realm_db: Arc<RealmDatabase>) -> Self {
+        let (sender, receiver) = flume::unbounded();
+        smol::spawn(run_persistence_worker(receiver, realm_db)).detach();
+        Self { sender }
+    }
+
+    pub fn set_character_item(&self, character_id: u32, slot_id: u8, item: Option<u32>, enchant: Option<u32>) {
+        self.send(PersistenceMessage::CharacterItem(DBCharacterItemChange {
+            character_id,
+            slot_id,
+            item,
+            enchant,
+        }));
+    }
+
+    pub fn set_character_position(&self, update: DBCharacterUpdate) {
+        self.send(PersistenceMessage::CharacterPosition(update));
+    }
+
+    //Everything queued before this call is written as one batch
+    pub fn end_tick(&self) {
+        self.send(PersistenceMessage::EndOfTick);
+    }
+
+    //Writes out whatever is still pending and waits for the worker to finish
+    pub async fn shutdown(&self) -> Result<()> {
+        let (done_sender, done_receiver) = flume::bounded(1);
+        self.sender.send_async(PersistenceMessage::Shutdown(done_sender)).await?;
+        done_receiver.recv_async().await?;
+        Ok(())
+    }
+
+    fn send(&self, message: PersistenceMessage) {
+        if self.sender.send(message).is_err() {
+            error!("Realm persistence worker is not running, database write was dropped");
+        }
+    }
+}
+
+async fn run_persistence_worker(receiver: flume::Receiver<PersistenceMessage>, realm_db: Arc<RealmDatabase>) {
+    let mut pending_items: HashMap<(u32, u8), (Option<u32>, Option<u32>)> = HashMap::new();
+    let mut pending_positions: HashMap<u32, DBCharacterUpdate> = HashMap::new();
+
+    while let Ok(message) = receiver.recv_async().await {
+        match message {
+            PersistenceMessage::CharacterItem(change) => {
+                pending_items.insert((change.character_id, change.slot_id), (change.item, change.enchant));
+            }
+            PersistenceMessage::CharacterPosition(update) => {
+                pending_positions.insert(update.id, update);
+            }
+            PersistenceMessage::EndOfTick => {
+                flush_character_items(&realm_db, &mut pending_items).await;
+                flush_character_positions(&realm_db, &mut pending_positions).await;
+            }
+            PersistenceMessage::Shutdown(done_sender) => {
+                flush_character_items(&realm_db, &mut pending_items).await;
+                flush_character_positions(&realm_db, &mut pending_positions).await;
+                let _ = done_sender.send(());
+                break;
+            }
+        }
+    }
+}
+
+async fn flush_character_items(realm_db: &RealmDatabase, pending_items: &mut HashMap<(u32, u8), (Option<u32>, Option<u32>)>) {
+    if pending_items.is_empty() {
+        return;
+    }
+
+    let changes: Vec<DBCharacterItemChange> = pending_items
+        .drain()
+        .map(|((character_id, slot_id), (item, enchant))| DBCharacterItemChange {
+            character_id,
+            slot_id,
+            item,
+            enchant,
+        })
+        .collect();
+
+    let description = format!("{} character item changes", changes.len());
+    if !write_with_retries(&description, || realm_db.apply_character_item_changes(&changes)).await {
+        error!("Giving up on writing character item changes: {:?}", changes);
+    }
+}
+
+async fn flush_character_positions(realm_db: &RealmDatabase, pending_positions: &mut HashMap<u32, DBCharacterUpdate>) {
+    for (character_id, update) in pending_positions.drain() {
+        let description = format!("position of character {}", character_id);
+        if !write_with_retries(&description, || realm_db.update_character_position(&update)).await {
+            error!("Giving up on writing the position of character {}", character_id);
+        }
+    }
+}
+
+async fn write_with_retries<F, Fut>(description: &str, mut write: F) -> bool
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    for attempt in 1..=MAX_FLUSH_ATTEMPTS {
+        match write().await {
+            Ok(()) => return true,
+            Err(e) => {
+                warn!("Failed to write {} (attempt {}/{}): {}", description, attempt, MAX_FLUSH_ATTEMPTS, e);
+                async_io::Timer::after(Duration::from_millis(RETRY_BACKOFF_MILLIS * attempt as u64)).await;
+            }
+        }
+    }
+    false
+}
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use wow_world_messages::wrath::{Icon, Vector2d};
+use wrath_game_db::{DBPointOfInterest, GameDatabase};
+
+use crate::prelude::*;
+
+#[derive(Clone, Debug)]
+pub struct PointOfInterest {
+    pub position: Vector2d,
+    pub icon: Icon,
+    pub flags: u32,
+    pub importance: u32,
+    pub name: String,
+}
+
+impl TryFrom<&DBPointOfInterest> for PointOfInterest {
+    type Error = anyhow::Error;
+
+    fn try_from(row: &DBPointOfInterest) -> Result<Self> {
+        Ok(Self {
+            position: Vector2d {
+                x: row.position_x,
+                y: row.position_y,
+            },
+            icon: Icon::try_from(row.icon)?,
+            flags: row.flags,
+            importance: row.importance,
+            name: row.name.clone(),
+        })
+    }
+}
+
+//Map markers from the points_of_interest table. Scripts can also build a PointOfInterest on the fly
+//and send it with handlers::send_gossip_poi when the marker doesn't need to be in the database.
+#[derive(Default)]
+pub struct PointsOfInterest {
+    points: HashMap<u32, PointOfInterest>,
+}
+
+impl PointsOfInterest {
+    pub async fn load(&mut self, game_db: &GameDatabase) -> Result<()> {
+        self.points.clear();
+        for row in game_db.get_all_points_of_interest().await? {
+            match PointOfInterest::try_from(&row) {
+                Ok(point) => {
+                    self.points.insert(row.id, point);
+                }
+                Err(e) => warn!("Skipping point of interest {}: {}", row.id, e),
+            }
+        }
+        info!("Loaded {} points of interest", self.points.len());
+        Ok(())
+    }
+
+    pub fn get(&self, id: u32) -> Option<&PointOfInterest> {
+        self.points.get(&id)
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::Rng;
+use wrath_game_db::{DBRareSpawn, GameDatabase};
+use wrath_realm_db::RealmDatabase;
+
+use super::instance_manager::MapID;
+use crate::character::character_manager::CharacterManager;
+use crate::prelude::*;
+use crate::random;
+
+//Respawn windows are hours or days long, there is no point in checking them every tick
+const CHECK_INTERVAL_SECONDS: f32 = 5.0;
+
+struct RareSpawnState {
+    spawn: DBRareSpawn,
+    respawn_time: u64,
+    alive: bool,
+}
+
+//Keeps track of world bosses and rares whose respawn timers have to survive server restarts.
+//Due spawns are queued up for the creature manager, which reports back when they get killed.
+pub struct RareSpawnScheduler {
+    spawns: HashMap<u32, RareSpawnState>,
+    announce: bool,
+    pending_spawns: Vec<u32>,
+    cooldown: f32,
+}
+
+impl RareSpawnScheduler {
+    pub fn new() -> Self {
+        let announce = std::env::var("RARE_SPAWN_ANNOUNCE")
+            .ok()
+            .and_then(|v| v.parse::<u8>().ok())
+            .is_some_and(|v| v == 1);
+
+        Self {
+            spawns: HashMap::new(),
+            announce,
+            pending_spawns: Vec::new(),
+            cooldown: 0.0,
+        }
+    }
+
+    pub async fn load(&mut self, game_db: &GameDatabase, realm_db: &RealmDatabase) -> Result<()> {
+        let respawn_times: HashMap<u32, u64> = realm_db
+            .get_rare_spawn_respawn_times()
+            .await?
+            .into_iter()
+            .map(|row| (row.spawn_id, row.respawn_time))
+            .collect();
+
+        //Spawns that were never killed have no stored time and are due right away
+        self.spawns = game_db
+            .get_all_rare_spawns()
+            .await?
+            .into_iter()
+            .map(|spawn| {
+                let respawn_time = respawn_times.get(&spawn.id).copied().unwrap_or(0);
+                let state = RareSpawnState {
+                    spawn,
+                    respawn_time,
+                    alive: false,
+                };
+                (state.spawn.id, state)
+            })
+            .collect();
+
+        info!("Loaded {} rare spawns", self.spawns.len());
+        Ok(())
+    }
+
+    pub async fn tick(&mut self, delta_time: f32, character_manager: &CharacterManager) -> Result<()> {
+        self.cooldown -= delta_time;
+        if self.cooldown > 0.0 {
+            return Ok(());
+        }
+        self.cooldown = CHECK_INTERVAL_SECONDS;
+
+        let now = current_unix_time()?;
+        for state in self.spawns.values_mut().filter(|state| !state.alive && state.respawn_time <= now) {
+            state.alive = true;
+            self.pending_spawns.push(state.spawn.id);
+            info!(
+                "Rare spawn {} (creature {}) is up on map {}",
+                state.spawn.id, state.spawn.creature_entry, state.spawn.map
+            );
+
+            if let Some(emote) = state.spawn.spawn_emote.as_deref().filter(|_| self.announce) {
+                handlers::send_system_message_to_all(character_manager, emote).await?;
+            }
+        }
+        Ok(())
+    }
+
+    //Spawns that are up and whose map is running, for the creature manager to place in the world. Spawns on a map
+    //that isn't running wait until a character brings it back, which also covers maps that stopped with the
+    //creature still alive.
+    pub fn take_pending_spawns(&mut self, is_map_running: impl Fn(MapID) -> bool) -> Vec<&DBRareSpawn> {
+        for state in self
+            .spawns
+            .values()
+            .filter(|state| state.alive && !is_map_running(state.spawn.map as MapID))
+        {
+            if !self.pending_spawns.contains(&state.spawn.id) {
+                self.pending_spawns.push(state.spawn.id);
+            }
+        }
+
+        let (ready, waiting): (Vec<u32>, Vec<u32>) = std::mem::take(&mut self.pending_spawns)
+            .into_iter()
+            .partition(|id| self.spawns.get(id).is_some_and(|state| is_map_running(state.spawn.map as MapID)));
+        self.pending_spawns = waiting;
+        ready.iter().filter_map(|id| self.spawns.get(id)).map(|state| &state.spawn).collect()
+    }
+
+    //Picks the next respawn time within the spawn's window and stores it, so a restart doesn't bring the creature back early
+    pub async fn on_rare_spawn_killed(&mut self, spawn_id: u32, realm_db: &RealmDatabase) -> Result<&DBRareSpawn> {
+        let state = self
+            .spawns
+            .get_mut(&spawn_id)
+            .ok_or_else(|| anyhow!("Rare spawn {} does not exist", spawn_id))?;
+
+        let window = state.spawn.respawn_min_seconds..=state.spawn.respawn_max_seconds.max(state.spawn.respawn_min_seconds);
+        let delay = random::with_rng(|rng| rng.gen_range(window));
+        state.respawn_time = current_unix_time()? + delay as u64;
+        state.alive = false;
+
+        realm_db.set_rare_spawn_respawn_time(spawn_id, state.respawn_time).await?;
+        Ok(&state.spawn)
+    }
+}
+
+fn current_unix_time() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
@@ -1,18 +1,43 @@
+use std::sync::Arc;
+
+use wow_world_messages::wrath::{Object, Object_UpdateType};
+
 use super::prelude::*;
 use crate::prelude::*;
 
+//Update blocks are built once and shared by every observer that receives them
+pub type SharedObjectUpdate = Arc<Object>;
+
 #[async_trait::async_trait]
 pub trait ReceiveUpdates {
-    fn push_object_update(&mut self, object_update: wow_world_messages::wrath::Object);
-    fn get_object_updates(&self) -> &Vec<wow_world_messages::wrath::Object>;
+    fn push_object_update(&mut self, object_update: SharedObjectUpdate);
+    fn get_object_updates(&self) -> &[SharedObjectUpdate];
     fn clear_object_updates(&mut self);
     async fn process_pending_updates(&mut self) -> Result<()>;
 }
 
-pub fn build_create_update_block_for_player(player: &dyn GameObject, object: &dyn GameObject) -> Result<wow_world_messages::wrath::Object> {
-    use wow_world_messages::wrath::{MovementBlock, MovementBlock_UpdateFlag, Object, Object_UpdateType};
+//Adds a block to the updates an observer gets at the end of its tick, dropping what became redundant:
+//the same shared block twice, values blocks for an object whose full create block follows, and values
+//blocks for objects that just went out of range
+pub fn coalesce_object_update(pending: &mut Vec<SharedObjectUpdate>, object_update: SharedObjectUpdate) {
+    if pending.iter().any(|p| Arc::ptr_eq(p, &object_update)) {
+        return;
+    }
 
-    assert!(object.as_character().is_some(), "Only characters currently supported");
+    match &object_update.update_type {
+        Object_UpdateType::CreateObject { guid3, .. } | Object_UpdateType::CreateObject2 { guid3, .. } => {
+            pending.retain(|p| !matches!(&p.update_type, Object_UpdateType::Values { guid1, .. } if guid1 == guid3));
+        }
+        Object_UpdateType::OutOfRangeObjects { guids } => {
+            pending.retain(|p| !matches!(&p.update_type, Object_UpdateType::Values { guid1, .. } if guids.contains(guid1)));
+        }
+        _ => {}
+    }
+    pending.push(object_update);
+}
+
+pub fn build_create_update_block_for_player(player: &dyn GameObject, object: &dyn GameObject) -> Result<Object> {
+    use wow_world_messages::wrath::{MovementBlock, MovementBlock_UpdateFlag};
 
     let object_guid = object.get_guid();
     let player_guid = player.get_guid();
@@ -50,6 +75,7 @@ pub fn build_create_update_block_for_player(player: &dyn GameObject, object: &dy
     let mut all_dirty_update_mask = object.get_update_mask();
     match all_dirty_update_mask {
         wow_world_messages::wrath::UpdateMask::Player(ref mut inner) => inner.mark_fully_dirty(),
+        wow_world_messages::wrath::UpdateMask::Unit(ref mut inner) => inner.mark_fully_dirty(),
         _ => unimplemented!(),
     }
 
@@ -72,9 +98,7 @@ pub fn build_create_update_block_for_player(player: &dyn GameObject, object: &dy
     Ok(Object { update_type })
 }
 
-pub fn build_out_of_range_update_block_for_player(player: &dyn GameObject) -> Option<wow_world_messages::wrath::Object> {
-    use wow_world_messages::wrath::{Object, Object_UpdateType};
-
+pub fn build_out_of_range_update_block_for_player(player: &dyn GameObject) -> Option<Object> {
     let out_of_range_guids = player.get_recently_removed_range_guids();
     if out_of_range_guids.is_empty() {
         None
@@ -87,9 +111,7 @@ pub fn build_out_of_range_update_block_for_player(player: &dyn GameObject) -> Op
     }
 }
 
-pub fn build_values_update_block(object: &dyn GameObject) -> Result<wow_world_messages::wrath::Object> {
-    use wow_world_messages::wrath::{Object, Object_UpdateType};
-
+pub fn build_values_update_block(object: &dyn GameObject) -> Result<Object> {
     Ok(Object {
         update_type: Object_UpdateType::Values {
             guid1: object.get_guid(),
@@ -97,3 +119,45 @@ pub fn build_values_update_block(object: &dyn GameObject) -> Result<wow_world_me
         },
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wow_world_messages::wrath::{UpdateMask, UpdatePlayer};
+
+    fn values_block(guid: u64) -> SharedObjectUpdate {
+        let guid = Guid::new(guid);
+        Arc::new(Object {
+            update_type: Object_UpdateType::Values {
+                guid1: guid,
+                mask1: UpdateMask::Player(UpdatePlayer::builder().set_object_guid(guid).finalize()),
+            },
+        })
+    }
+
+    #[test]
+    fn shared_block_is_only_queued_once() {
+        let mut pending = vec![];
+        let block = values_block(1);
+        coalesce_object_update(&mut pending, block.clone());
+        coalesce_object_update(&mut pending, block.clone());
+        coalesce_object_update(&mut pending, values_block(2));
+        assert_eq!(pending.len(), 2);
+        assert!(Arc::ptr_eq(&pending[0], &block));
+    }
+
+    #[test]
+    fn out_of_range_drops_pending_values_blocks() {
+        let mut pending = vec![];
+        coalesce_object_update(&mut pending, values_block(1));
+        coalesce_object_update(&mut pending, values_block(2));
+        let out_of_range = Arc::new(Object {
+            update_type: Object_UpdateType::OutOfRangeObjects { guids: vec![Guid::new(1)] },
+        });
+        coalesce_object_update(&mut pending, out_of_range.clone());
+
+        assert_eq!(pending.len(), 2);
+        assert!(matches!(&pending[0].update_type, Object_UpdateType::Values { guid1, .. } if *guid1 == Guid::new(2)));
+        assert!(Arc::ptr_eq(&pending[1], &out_of_range));
+    }
+}
@@ -0,0 +1,87 @@
+//! Keeps the realm database's `uptime` table up to date: a row for every run of the server with how long it
+//! has been up and the most players that were online at once.
+
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use wrath_common::config;
+use wrath_realm_db::RealmDatabase;
+
+use crate::prelude::*;
+
+const DEFAULT_UPDATE_INTERVAL_SECONDS: f32 = 300.0;
+
+//Set WRATH_REVISION when building to tell builds of the same version apart, e.g. to the git commit
+pub fn revision() -> String {
+    format!(
+        "wrath-worldserver {} ({})",
+        env!("CARGO_PKG_VERSION"),
+        option_env!("WRATH_REVISION").unwrap_or("unknown revision")
+    )
+}
+
+pub struct UptimeTracker {
+    //Unix time of the start, the key of this run's row
+    start_time: u64,
+    started: Instant,
+    max_players: u32,
+    update_interval: f32,
+    since_update: f32,
+}
+
+impl UptimeTracker {
+    pub fn new() -> Self {
+        Self {
+            start_time: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs()),
+            started: Instant::now(),
+            max_players: 0,
+            update_interval: config::or_default("UPTIME_UPDATE_INTERVAL_SECONDS", DEFAULT_UPDATE_INTERVAL_SECONDS),
+            since_update: 0.0,
+        }
+    }
+
+    pub async fn record_start(&self, realm_db: &RealmDatabase) -> Result<()> {
+        realm_db.start_uptime(self.start_time, &revision()).await
+    }
+
+    pub async fn tick(&mut self, delta_time: f32, online_players: usize, realm_db: &RealmDatabase) -> Result<()> {
+        self.max_players = self.max_players.max(online_players as u32);
+        self.since_update += delta_time;
+        if self.since_update < self.update_interval {
+            return Ok(());
+        }
+        self.since_update = 0.0;
+        realm_db.update_uptime(self.start_time, self.uptime_seconds(), self.max_players).await
+    }
+
+    pub fn uptime_seconds(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+
+    pub fn max_players(&self) -> u32 {
+        self.max_players
+    }
+}
+
+//"2d 3h 4m 5s", leaving out the units in front that are still zero
+pub fn format_uptime(seconds: u64) -> String {
+    let (days, hours, minutes, seconds) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60, seconds % 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{seconds}s"),
+        (0, 0, _) => format!("{minutes}m {seconds}s"),
+        (0, _, _) => format!("{hours}h {minutes}m {seconds}s"),
+        _ => format!("{days}d {hours}h {minutes}m {seconds}s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uptime_formatting_skips_leading_zero_units() {
+        assert_eq!(format_uptime(42), "42s");
+        assert_eq!(format_uptime(3 * 60 + 1), "3m 1s");
+        assert_eq!(format_uptime(2 * 3600 + 5), "2h 0m 5s");
+        assert_eq!(format_uptime(86400 + 3600 + 60 + 1), "1d 1h 1m 1s");
+    }
+}
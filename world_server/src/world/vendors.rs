@@ -0,0 +1,149 @@
+//! What vendor creatures sell and how much of their limited items is left. Every vendor spawn has a stock of
+//! its own, kept in the realm database so that a restart doesn't refill rare recipes early.
+
+use std::collections::HashMap;
+
+use wrath_game_db::{DBNpcVendorItem, GameDatabase};
+use wrath_realm_db::vendor_stock::DBVendorStock;
+use wrath_realm_db::RealmDatabase;
+
+use crate::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VendorError {
+    NotSold,
+    SoldOut,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Stock {
+    count: u8,
+    restock_time: u64,
+}
+
+#[derive(Default)]
+pub struct Vendors {
+    //By creature entry, in the order the vendor window shows them
+    items: HashMap<u32, Vec<DBNpcVendorItem>>,
+    //By vendor spawn and item, only limited items that were bought from since they last restocked
+    stock: HashMap<(u32, u32), Stock>,
+}
+
+impl Vendors {
+    pub async fn load(&mut self, game_db: &GameDatabase, realm_db: &RealmDatabase) -> Result<()> {
+        self.items.clear();
+        for item in game_db.get_all_npc_vendor_items().await? {
+            self.items.entry(item.entry).or_default().push(item);
+        }
+
+        //Stock that refilled while the server was down is full again
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+        self.stock.clear();
+        for row in realm_db.get_all_vendor_stock().await? {
+            if row.restock_time <= now {
+                realm_db.delete_vendor_stock(row.spawn_guid, row.item).await?;
+                continue;
+            }
+            let stock = Stock {
+                count: row.count,
+                restock_time: row.restock_time,
+            };
+            self.stock.insert((row.spawn_guid, row.item), stock);
+        }
+        info!("Loaded {} vendors, {} limited items are restocking", self.items.len(), self.stock.len());
+        Ok(())
+    }
+
+    pub fn get_items(&self, entry: u32) -> &[DBNpcVendorItem] {
+        self.items.get(&entry).map_or(&[], Vec::as_slice)
+    }
+
+    //How many the vendor spawn has left, None for items that never run out
+    pub fn get_available(&self, spawn_guid: u32, item: &DBNpcVendorItem, now: u64) -> Option<u8> {
+        if item.max_count == 0 {
+            return None;
+        }
+        match self.stock.get(&(spawn_guid, item.item)) {
+            Some(stock) if now < stock.restock_time => Some(stock.count),
+            _ => Some(item.max_count),
+        }
+    }
+
+    //Takes count of the item from the vendor spawn's stock. Returns the stock to write to the realm database
+    //for limited items, None for items that never run out.
+    pub fn take(
+        &mut self,
+        spawn_guid: u32,
+        entry: u32,
+        item_id: u32,
+        count: u8,
+        now: u64,
+    ) -> std::result::Result<Option<DBVendorStock>, VendorError> {
+        let item = self
+            .get_items(entry)
+            .iter()
+            .find(|item| item.item == item_id)
+            .ok_or(VendorError::NotSold)?
+            .clone();
+        let Some(available) = self.get_available(spawn_guid, &item, now) else {
+            return Ok(None);
+        };
+        if available < count {
+            return Err(VendorError::SoldOut);
+        }
+
+        //The restock timer starts with the first item bought from a full stock
+        let restock_time = match self.stock.get(&(spawn_guid, item_id)) {
+            Some(stock) if now < stock.restock_time => stock.restock_time,
+            _ => now + item.restock_seconds as u64,
+        };
+        let stock = Stock {
+            count: available - count,
+            restock_time,
+        };
+        self.stock.insert((spawn_guid, item_id), stock);
+        Ok(Some(DBVendorStock {
+            spawn_guid,
+            item: item_id,
+            count: stock.count,
+            restock_time,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limited_items_run_out_per_spawn_and_restock() {
+        let mut vendors = Vendors::default();
+        let recipe = DBNpcVendorItem {
+            entry: 1,
+            slot: 0,
+            item: 2000,
+            max_count: 2,
+            restock_seconds: 100,
+        };
+        let water = DBNpcVendorItem {
+            entry: 1,
+            slot: 1,
+            item: 159,
+            max_count: 0,
+            restock_seconds: 0,
+        };
+        vendors.items.insert(1, vec![recipe.clone(), water]);
+
+        assert_eq!(vendors.take(10, 1, 159, 5, 0), Ok(None));
+        assert_eq!(vendors.take(10, 1, 3000, 1, 0).unwrap_err(), VendorError::NotSold);
+
+        assert_eq!(vendors.take(10, 1, 2000, 1, 0).unwrap().unwrap().restock_time, 100);
+        assert_eq!(vendors.take(10, 1, 2000, 1, 50).unwrap().unwrap().restock_time, 100);
+        assert_eq!(vendors.take(10, 1, 2000, 1, 60).unwrap_err(), VendorError::SoldOut);
+        //Another spawn of the same vendor has its own stock
+        assert_eq!(vendors.get_available(11, &recipe, 60), Some(2));
+
+        assert_eq!(vendors.get_available(10, &recipe, 100), Some(2));
+        assert_eq!(vendors.take(10, 1, 2000, 2, 100).unwrap().unwrap().restock_time, 200);
+    }
+}